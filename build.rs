@@ -0,0 +1,20 @@
+//! Build script: compiles the protobuf definitions when the `grpc` feature
+//! is enabled, using a vendored `protoc` so no system install is needed.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto/cursor_chat.proto");
+
+    let protoc = protoc_bin_vendored::protoc_bin_path()
+        .unwrap_or_else(|e| panic!("vendored protoc unavailable: {e}"));
+    std::env::set_var("PROTOC", protoc);
+
+    tonic_prost_build::configure()
+        .compile_protos(&["proto/cursor_chat.proto"], &["proto"])
+        .unwrap_or_else(|e| panic!("failed to compile proto/cursor_chat.proto: {e}"));
+}
@@ -0,0 +1,49 @@
+/*
+ * C interface of cursor-chat-handler.
+ *
+ * Build with `cargo build --release --features capi` and link against
+ * target/release/libcursor_chat_handler.{so,dylib} (cursor_chat_handler.dll
+ * on Windows).
+ *
+ * Functions returning `char *` return a NUL-terminated UTF-8 JSON string
+ * owned by the caller, to be released with cch_free_string(). On failure
+ * they return NULL and cch_last_error() describes why. A panic inside the
+ * library is reported the same way instead of unwinding into the caller.
+ */
+#ifndef CURSOR_CHAT_H
+#define CURSOR_CHAT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Use `dir` as Cursor's configuration directory instead of the detected one.
+ * Only the first call takes effect: later ones fail. Returns 0 on success,
+ * -1 on error (see cch_last_error).
+ */
+int cch_set_cursor_dir(const char *dir);
+
+/*
+ * JSON array of every conversation, newest first:
+ * [{"composer_id", "title", "created_at", "messages", "model"}, ...]
+ */
+char *cch_list_conversations(void);
+
+/* JSON of one conversation with all its messages, by full or partial ID. */
+char *cch_get_conversation(const char *id);
+
+/* Release a string returned by this library. NULL is ignored. */
+void cch_free_string(char *s);
+
+/*
+ * Message of the last error on the calling thread, or NULL. Valid until the
+ * next call into the library on the same thread; do not free it.
+ */
+const char *cch_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CURSOR_CHAT_H */
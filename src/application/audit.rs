@@ -0,0 +1,51 @@
+//! Audit log of operations that touch Cursor or local storage.
+//!
+//! Recording is best effort: a failure to write the log is logged and never
+//! fails the operation itself.
+
+use chrono::Utc;
+
+use crate::domain::{AppConfig, Operation, OperationKind, Result};
+use crate::infrastructure::LocalStorage;
+
+/// Run `run` and record its outcome as a `kind` operation.
+///
+/// `describe` turns the result into the affected item count and a summary.
+/// Returns `None` for outcomes not worth logging (e.g. nothing to prune).
+///
+/// # Errors
+/// Returns the error of `run`, unchanged.
+pub fn recorded<T>(
+    config: &AppConfig,
+    kind: OperationKind,
+    run: impl FnOnce() -> Result<T>,
+    describe: impl FnOnce(&T) -> Option<(usize, String)>,
+) -> Result<T> {
+    let started_at = Utc::now();
+    let result = run();
+
+    let operation = match &result {
+        Ok(value) => describe(value)
+            .map(|(items, details)| Operation::succeeded(kind, started_at, items, details)),
+        Err(e) => Some(Operation::failed(kind, started_at, e)),
+    };
+    if let Some(operation) = operation {
+        record(config, &operation);
+    }
+
+    result
+}
+
+/// Append `operation` to the audit log, if local storage exists.
+pub fn record(config: &AppConfig, operation: &Operation) {
+    let storage_path = config.storage_db_path();
+    if !storage_path.exists() {
+        return;
+    }
+
+    if let Err(e) =
+        LocalStorage::open(&storage_path).and_then(|storage| storage.record_operation(operation))
+    {
+        tracing::warn!(kind = operation.kind.as_str(), error = %e, "Failed to record operation");
+    }
+}
@@ -0,0 +1,209 @@
+//! Readable mirror of synced conversations (`[export] auto`).
+//!
+//! After each daemon sync, the conversations in the change feed since the
+//! previous run are exported to the export directory, one file per
+//! conversation. A changed conversation replaces its earlier file, and the
+//! export manifest is refreshed.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::LocalStorage;
+
+use super::export_manifest::ExportManifest;
+use super::extractor::load_short_ids;
+use super::formatter::{write_conversation_markdown, write_conversations, OutputFormat};
+use super::redact::PathMap;
+
+/// Upper bound on changes read per run.
+const MAX_CHANGES: usize = 100_000;
+
+/// Exports conversations changed since the previous run.
+pub struct AutoExporter {
+    dir: PathBuf,
+    format: OutputFormat,
+    path_map: PathMap,
+    storage_path: PathBuf,
+    /// Changes synced after this are not exported yet; `None` exports the
+    /// whole change feed.
+    since: Option<DateTime<Utc>>,
+}
+
+impl AutoExporter {
+    /// Exporter for the `[export]` settings, or `None` if `auto` is off.
+    ///
+    /// Picks up after the last export recorded in the directory's manifest.
+    ///
+    /// # Errors
+    /// Returns error if the format is unknown, or the path map or manifest
+    /// cannot be read.
+    pub fn from_config(config: &AppConfig) -> Result<Option<Self>> {
+        if !config.export.auto {
+            return Ok(None);
+        }
+
+        let format = config
+            .export
+            .format
+            .parse()
+            .map_err(|message| AppError::Config { message })?;
+        let path_map = config
+            .export
+            .path_map
+            .as_deref()
+            .map_or_else(|| Ok(PathMap::default()), PathMap::load)?;
+        let dir = config.auto_export_dir();
+        let since = ExportManifest::load(&dir)?.generated_at;
+
+        Ok(Some(Self {
+            dir,
+            format,
+            path_map,
+            storage_path: config.storage_db_path(),
+            since,
+        }))
+    }
+
+    /// Directory the files are written to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Export the conversations changed since the previous run.
+    ///
+    /// Returns the number of files written. On error, the same changes are
+    /// tried again next run.
+    ///
+    /// # Errors
+    /// Returns error if storage cannot be read or a file cannot be written.
+    pub fn export_changed(&mut self) -> Result<usize> {
+        let started_at = Utc::now();
+        let storage = LocalStorage::open_read_only(&self.storage_path)?;
+
+        let mut ids: Vec<String> = storage
+            .get_changes(self.since, MAX_CHANGES)?
+            .into_iter()
+            .map(|change| change.composer_id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.is_empty() {
+            self.since = Some(started_at);
+            return Ok(0);
+        }
+
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            AppError::io(
+                format!("Failed to create directory {}", self.dir.display()),
+                e,
+            )
+        })?;
+        let short_ids = load_short_ids(&self.storage_path);
+        let mut manifest = ExportManifest::load(&self.dir)?;
+
+        let mut written = 0;
+        for id in &ids {
+            let Some(mut conv) = storage.get_conversation(id)? else {
+                continue;
+            };
+            self.path_map.apply_to_conversation(&mut conv);
+
+            let path = self
+                .dir
+                .join(format!("{}.{}", conv.filename(), self.format.extension()));
+            let file = File::create(&path)
+                .map_err(|e| AppError::io(format!("Failed to create {}", path.display()), e))?;
+            let mut writer = BufWriter::new(file);
+            match self.format {
+                OutputFormat::Markdown => {
+                    write_conversation_markdown(&mut writer, &conv, &short_ids)
+                }
+                OutputFormat::Json | OutputFormat::Table => write_conversations(
+                    &mut writer,
+                    std::slice::from_ref(&conv),
+                    self.format,
+                    &short_ids,
+                ),
+            }
+            .and_then(|()| writer.flush())
+            .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))?;
+
+            // A renamed conversation gets a new file name; drop the old file
+            if let Some(previous) = manifest.conversations.get(id) {
+                let previous = self.dir.join(&previous.file);
+                if previous != path {
+                    let _ = fs::remove_file(previous);
+                }
+            }
+            manifest.record(id, &conv.title, &path)?;
+            written += 1;
+        }
+        manifest.save()?;
+
+        self.since = Some(started_at);
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Bubble, BubbleType, ChangeType, Conversation, ModelConfig};
+
+    #[test]
+    fn test_export_changed_writes_each_change_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().join("data"));
+        config.export.auto = true;
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+
+        let storage = LocalStorage::open(&config.storage_db_path()).unwrap();
+        let conv = Conversation {
+            composer_id: "c1234567-aaaa".into(),
+            title: "Parser".into(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![Bubble {
+                bubble_id: "b1".into(),
+                bubble_type: BubbleType::User,
+                text: "Fix the parser".into(),
+                ..Default::default()
+            }],
+            git: None,
+            workspace: None,
+        };
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+        storage.upsert_bubble(&conv.bubbles[0], conv_id).unwrap();
+        storage
+            .record_change(&conv.composer_id, ChangeType::Created, Utc::now())
+            .unwrap();
+
+        let mut exporter = AutoExporter::from_config(&config).unwrap().unwrap();
+        assert_eq!(exporter.export_changed().unwrap(), 1);
+        let file = config.exports_dir().join("c1234567_fix_parser.md");
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("Fix the parser"));
+
+        // Nothing new since the last run
+        assert_eq!(exporter.export_changed().unwrap(), 0);
+
+        storage
+            .record_change(&conv.composer_id, ChangeType::Updated, Utc::now())
+            .unwrap();
+        assert_eq!(exporter.export_changed().unwrap(), 1);
+
+        let manifest = ExportManifest::load(&config.exports_dir()).unwrap();
+        assert_eq!(
+            manifest.conversations["c1234567-aaaa"].file,
+            "c1234567_fix_parser.md"
+        );
+    }
+}
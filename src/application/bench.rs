@@ -0,0 +1,301 @@
+//! Benchmark of the extraction pipeline.
+//!
+//! Times each phase (raw read, JSON parse, full extraction, storage write,
+//! export) against a Cursor database, either the user's real one or a
+//! generated synthetic dataset, without touching local storage.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::domain::{AppError, Result, ShortIds};
+use crate::infrastructure::{LocalStorage, StateDbReader};
+
+use super::extractor::{extract_from_path, ExtractOptions};
+use super::formatter::{write_conversations, OutputFormat};
+use super::parser::{parse_bubble, parse_composer};
+
+/// Measured phases, in pipeline order.
+pub const PHASES: [&str; 6] = [
+    "read",
+    "parse",
+    "extract",
+    "sync",
+    "export markdown",
+    "export json",
+];
+
+/// Timing of one phase.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    /// Phase name (one of [`PHASES`]).
+    pub phase: &'static str,
+    /// Median elapsed time over all iterations.
+    #[serde(rename = "elapsed_ms", serialize_with = "as_millis")]
+    pub elapsed: Duration,
+    /// Items handled by the phase (rows, conversations, messages or bytes).
+    pub items: usize,
+    /// What `items` counts.
+    pub unit: &'static str,
+}
+
+/// Timings of every phase for one dataset.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Dataset label (e.g. `real`, `synthetic`).
+    pub dataset: String,
+    /// Database that was measured.
+    pub database: PathBuf,
+    /// Per-phase timings, in pipeline order.
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl BenchReport {
+    /// Timing of `phase`, if it was measured.
+    #[must_use]
+    pub fn phase(&self, phase: &str) -> Option<&PhaseTiming> {
+        self.phases.iter().find(|p| p.phase == phase)
+    }
+}
+
+fn as_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// Time every phase against `db_path`, keeping the median of `iterations` runs.
+///
+/// Storage writes go to a scratch database under `scratch_dir`, which is
+/// removed afterwards.
+///
+/// # Errors
+/// Returns error if the database cannot be read or the scratch storage written.
+pub fn run_benchmark(
+    dataset: &str,
+    db_path: &Path,
+    scratch_dir: &Path,
+    iterations: usize,
+) -> Result<BenchReport> {
+    let iterations = iterations.max(1);
+    let mut samples: Vec<Vec<Duration>> = vec![Vec::with_capacity(iterations); PHASES.len()];
+    let mut items = [0; PHASES.len()];
+
+    for _ in 0..iterations {
+        let timings = run_once(db_path, scratch_dir)?;
+        for (i, (elapsed, count)) in timings.into_iter().enumerate() {
+            samples[i].push(elapsed);
+            items[i] = count;
+        }
+    }
+
+    let units = [
+        "rows",
+        "rows",
+        "conversations",
+        "messages",
+        "bytes",
+        "bytes",
+    ];
+    let phases = PHASES
+        .iter()
+        .zip(samples)
+        .zip(items.iter().zip(units))
+        .map(|((phase, times), (items, unit))| PhaseTiming {
+            phase,
+            elapsed: median(times),
+            items: *items,
+            unit,
+        })
+        .collect();
+
+    Ok(BenchReport {
+        dataset: dataset.to_string(),
+        database: db_path.to_path_buf(),
+        phases,
+    })
+}
+
+/// One pass over every phase: elapsed time and item count, in [`PHASES`] order.
+fn run_once(db_path: &Path, scratch_dir: &Path) -> Result<Vec<(Duration, usize)>> {
+    let mut timings = Vec::with_capacity(PHASES.len());
+
+    let start = Instant::now();
+    let reader = StateDbReader::open(db_path)?;
+    let composers = reader.fetch_composers()?;
+    let bubbles = reader.fetch_bubbles()?;
+    drop(reader);
+    timings.push((start.elapsed(), composers.len() + bubbles.len()));
+
+    let start = Instant::now();
+    let parsed = composers
+        .iter()
+        .filter(|e| parse_composer(&e.value).is_ok())
+        .count()
+        + bubbles
+            .iter()
+            .filter(|e| parse_bubble(&e.value).is_ok())
+            .count();
+    timings.push((start.elapsed(), parsed));
+    drop((composers, bubbles));
+
+    let start = Instant::now();
+    let (conversations, stats) = extract_from_path(db_path, &ExtractOptions::default())?;
+    timings.push((start.elapsed(), stats.conversation_count));
+
+    timings.push(time_storage_write(&conversations, scratch_dir)?);
+
+    let ids = ShortIds::default();
+    for format in [OutputFormat::Markdown, OutputFormat::Json] {
+        let start = Instant::now();
+        let mut sink = CountingSink::default();
+        write_conversations(&mut sink, &conversations, format, &ids)
+            .map_err(|e| AppError::io("Failed to format conversations", e))?;
+        timings.push((start.elapsed(), sink.bytes));
+    }
+
+    Ok(timings)
+}
+
+/// Write `conversations` into a fresh scratch storage, as a full sync would.
+fn time_storage_write(
+    conversations: &[crate::domain::Conversation],
+    scratch_dir: &Path,
+) -> Result<(Duration, usize)> {
+    std::fs::create_dir_all(scratch_dir)
+        .map_err(|e| AppError::io("Failed to create benchmark directory", e))?;
+    let path = scratch_dir.join("bench-storage.db");
+    let _ = std::fs::remove_file(&path);
+
+    let start = Instant::now();
+    let storage = LocalStorage::open(&path)?;
+    let mut messages = 0;
+    for conv in conversations {
+        let conv_id = storage.upsert_conversation(conv, None, Some(&conv.content_hash()))?;
+        for bubble in &conv.bubbles {
+            storage.upsert_bubble(bubble, conv_id)?;
+        }
+        messages += conv.bubbles.len();
+    }
+    let elapsed = start.elapsed();
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+
+    Ok((elapsed, messages))
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort_unstable();
+    times.get(times.len() / 2).copied().unwrap_or_default()
+}
+
+/// Writer that only counts bytes, so export timings exclude disk I/O.
+#[derive(Default)]
+struct CountingSink {
+    bytes: usize,
+}
+
+impl io::Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Create a Cursor-style database with `conversations` conversations of
+/// `messages` alternating user/assistant messages each.
+///
+/// # Errors
+/// Returns error if the database cannot be written.
+pub fn generate_synthetic_db(path: &Path, conversations: usize, messages: usize) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .map_err(|e| AppError::io("Failed to replace synthetic database", e))?;
+    }
+
+    let mut conn = rusqlite::Connection::open(path).map_err(AppError::database)?;
+    conn.execute_batch(
+        "CREATE TABLE cursorDiskKV (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+    )
+    .map_err(AppError::database)?;
+
+    let tx = conn.transaction().map_err(AppError::database)?;
+    {
+        let mut insert = tx
+            .prepare("INSERT INTO cursorDiskKV (key, value) VALUES (?1, ?2)")
+            .map_err(AppError::database)?;
+        let base_ms: u64 = 1_700_000_000_000;
+
+        for c in 0..conversations {
+            let id = format!("{c:08x}-0000-4000-8000-bench0000000");
+            let created_ms = base_ms + c as u64 * 3_600_000;
+            let composer = serde_json::json!({
+                "_v": 3,
+                "createdAt": created_ms,
+                "modelConfig": { "modelName": "synthetic-model", "maxMode": false },
+                "unifiedMode": "agent",
+            });
+            insert
+                .execute((format!("composerData:{id}"), composer.to_string()))
+                .map_err(AppError::database)?;
+
+            for m in 0..messages {
+                let bubble = serde_json::json!({
+                    "_v": 2,
+                    "type": if m % 2 == 0 { 1 } else { 2 },
+                    "bubbleId": format!("{m:08}"),
+                    "text": synthetic_text(c, m),
+                    "createdAt": i64::try_from(created_ms + m as u64 * 30_000)
+                        .ok()
+                        .and_then(chrono::DateTime::from_timestamp_millis)
+                        .map(|t| t.to_rfc3339()),
+                    "tokenCount": { "inputTokens": 120, "outputTokens": 480 },
+                });
+                insert
+                    .execute((format!("bubbleId:{id}:{m:08}"), bubble.to_string()))
+                    .map_err(AppError::database)?;
+            }
+        }
+    }
+    tx.commit().map_err(AppError::database)
+}
+
+/// Message text of a few hundred bytes, with some code like real chats.
+fn synthetic_text(conversation: usize, message: usize) -> String {
+    format!(
+        "Message {message} of conversation {conversation}. Lorem ipsum dolor sit amet, \
+         consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore \
+         magna aliqua.\n\n```rust\nfn step_{message}() -> usize {{\n    {conversation} + \
+         {message}\n}}\n```\n\nUt enim ad minim veniam, quis nostrud exercitation ullamco."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_synthetic_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("synthetic.vscdb");
+        generate_synthetic_db(&db, 3, 4).unwrap();
+
+        let report = run_benchmark("synthetic", &db, dir.path(), 1).unwrap();
+
+        let names: Vec<&str> = report.phases.iter().map(|p| p.phase).collect();
+        assert_eq!(names, PHASES);
+        assert_eq!(report.phase("read").unwrap().items, 15);
+        assert_eq!(report.phase("extract").unwrap().items, 3);
+        assert_eq!(report.phase("sync").unwrap().items, 12);
+        assert!(report.phase("export json").unwrap().items > 0);
+        assert!(!dir.path().join("bench-storage.db").exists());
+    }
+}
@@ -0,0 +1,208 @@
+//! Conversation messages interleaved with the commits made meanwhile.
+//!
+//! Connects what was discussed in a chat with what got committed in its
+//! workspace, for the `correlate` command.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::domain::{BubbleType, Conversation, GitCommit};
+
+/// Characters of message text kept in each entry.
+const PREVIEW_CHARS: usize = 120;
+
+/// One entry of the correlated timeline.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CorrelatedEvent {
+    /// A message of the conversation.
+    Message {
+        /// When it was sent.
+        at: DateTime<Utc>,
+        /// `user` or `assistant`.
+        role: &'static str,
+        /// Start of the first line of text.
+        preview: String,
+    },
+    /// A commit in the workspace's repository.
+    Commit(GitCommit),
+}
+
+impl CorrelatedEvent {
+    /// When the event happened.
+    #[must_use]
+    pub const fn at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Message { at, .. } => *at,
+            Self::Commit(commit) => commit.committed_at,
+        }
+    }
+}
+
+/// A conversation's messages and commits, in time order.
+#[derive(Debug, Clone, Serialize)]
+pub struct Correlation {
+    /// Composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Repository the commits come from.
+    pub repo_root: String,
+    /// First message, minus the margin.
+    pub start: DateTime<Utc>,
+    /// Last message, plus the margin.
+    pub end: DateTime<Utc>,
+    /// Messages and commits, oldest first.
+    pub events: Vec<CorrelatedEvent>,
+}
+
+impl Correlation {
+    /// Activity window of `conv`: its first to last timestamped message (or
+    /// its creation time), widened by `margin` on both sides.
+    ///
+    /// Returns `None` if the conversation has no timestamp at all.
+    #[must_use]
+    pub fn window(conv: &Conversation, margin: Duration) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let times = conv.bubbles.iter().filter_map(|b| b.created_at);
+        let start = times.clone().min().or(conv.created_at)?;
+        let end = times.max().unwrap_or(start).max(start);
+        Some((start - margin, end + margin))
+    }
+
+    /// Interleave the timestamped messages of `conv` with `commits`.
+    ///
+    /// Commits outside `window` are dropped; at equal times a message comes
+    /// before a commit.
+    #[must_use]
+    pub fn build(
+        conv: &Conversation,
+        repo_root: &str,
+        window: (DateTime<Utc>, DateTime<Utc>),
+        commits: Vec<GitCommit>,
+    ) -> Self {
+        let (start, end) = window;
+        let mut events: Vec<CorrelatedEvent> = conv
+            .bubbles
+            .iter()
+            .filter_map(|bubble| {
+                Some(CorrelatedEvent::Message {
+                    at: bubble.created_at?,
+                    role: match bubble.bubble_type {
+                        BubbleType::User => "user",
+                        BubbleType::Assistant => "assistant",
+                        BubbleType::Unknown => "unknown",
+                    },
+                    preview: preview(&bubble.text),
+                })
+            })
+            .chain(
+                commits
+                    .into_iter()
+                    .filter(|c| (start..=end).contains(&c.committed_at))
+                    .map(CorrelatedEvent::Commit),
+            )
+            .collect();
+
+        // Stable sort keeps messages (chained first) ahead of commits at the same time
+        events.sort_by_key(CorrelatedEvent::at);
+
+        Self {
+            composer_id: conv.composer_id.clone(),
+            title: conv.title.clone(),
+            repo_root: repo_root.to_string(),
+            start,
+            end,
+            events,
+        }
+    }
+
+    /// Number of commits in the window.
+    #[must_use]
+    pub fn commit_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| matches!(e, CorrelatedEvent::Commit(_)))
+            .count()
+    }
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let mut preview: String = line.trim().chars().take(PREVIEW_CHARS).collect();
+    if line.trim().chars().count() > PREVIEW_CHARS {
+        preview.push('…');
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Bubble, ModelConfig};
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::minutes(minute)
+    }
+
+    fn commit(minute: i64) -> GitCommit {
+        GitCommit {
+            hash: format!("c{minute}"),
+            committed_at: at(minute),
+            author: "Ana".to_string(),
+            subject: format!("commit at {minute}"),
+        }
+    }
+
+    #[test]
+    fn test_build_interleaves_commits_in_window() {
+        let bubble = |minute: i64, bubble_type| Bubble {
+            bubble_id: format!("b{minute}"),
+            bubble_type,
+            text: "\n  Fix the parser  \nmore".to_string(),
+            created_at: Some(at(minute)),
+            ..Default::default()
+        };
+        let conv = Conversation {
+            composer_id: "abc".to_string(),
+            title: "parser".to_string(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![
+                bubble(0, BubbleType::User),
+                bubble(20, BubbleType::Assistant),
+            ],
+            git: None,
+            workspace: None,
+        };
+
+        let window = Correlation::window(&conv, Duration::minutes(10)).unwrap();
+        assert_eq!(window, (at(-10), at(30)));
+
+        let commits = vec![commit(-30), commit(5), commit(20), commit(30), commit(31)];
+        let correlation = Correlation::build(&conv, "/repo", window, commits);
+
+        let order: Vec<String> = correlation
+            .events
+            .iter()
+            .map(|e| match e {
+                CorrelatedEvent::Message { role, preview, .. } => format!("{role}: {preview}"),
+                CorrelatedEvent::Commit(c) => c.hash.clone(),
+            })
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "user: Fix the parser",
+                "c5",
+                "assistant: Fix the parser",
+                "c20",
+                "c30"
+            ]
+        );
+        assert_eq!(correlation.commit_count(), 3);
+    }
+}
@@ -0,0 +1,337 @@
+//! End-to-end environment diagnostics.
+//!
+//! Runs independent checks over the Cursor installation, local storage,
+//! daemon and configuration, each with a remediation hint on failure.
+
+use std::fs;
+use std::path::Path;
+
+use crate::domain::AppConfig;
+use crate::infrastructure::{
+    available_space, cursor_channel, detect_cursor_version, detected_channels,
+    find_cursor_config_dir, global_state_db_path, is_newer_than_known, is_read_only, load_config,
+    service_manager, validate_config, StateDbReader, NEWEST_KNOWN_CURSOR_VERSION,
+};
+
+use super::storage_manager::{format_bytes, StorageManager};
+
+/// Free space below which the disk check warns.
+const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Free space below which the disk check fails.
+const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Everything is fine.
+    Pass,
+    /// Works, but something should be looked at.
+    Warn,
+    /// Broken; the tool will not work correctly.
+    Fail,
+}
+
+/// Result of a single diagnostic check.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// Short name of what was checked.
+    pub name: &'static str,
+    /// Outcome.
+    pub status: CheckStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix it, when not passing.
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every diagnostic check in order.
+#[must_use]
+pub fn run_diagnostics(config: &AppConfig) -> Vec<CheckResult> {
+    vec![
+        check_config(),
+        check_cursor_install(),
+        check_cursor_version(),
+        check_cursor_db(),
+        check_local_storage(config),
+        check_daemon(config),
+        check_disk_space(config),
+        check_permissions(config),
+    ]
+}
+
+fn check_config() -> CheckResult {
+    const NAME: &str = "Config";
+
+    match load_config().and_then(|c| validate_config(&c).map(|()| c)) {
+        Ok(config) => {
+            let mode = if config.safety.read_only || is_read_only() {
+                " (read-only mode)"
+            } else {
+                ""
+            };
+            CheckResult::pass(
+                NAME,
+                format!("{}{mode}", config.config_file_path().display()),
+            )
+        }
+        Err(e) => CheckResult::fail(
+            NAME,
+            e.to_string(),
+            "Fix it with 'cursor-chat config edit' or delete config.toml to use defaults",
+        ),
+    }
+}
+
+fn check_cursor_install() -> CheckResult {
+    const NAME: &str = "Cursor install";
+
+    // Point out other channels, whose chats are not read with this one
+    let current = cursor_channel();
+    let others: Vec<String> = detected_channels()
+        .into_iter()
+        .filter(|c| *c != current)
+        .map(|c| format!("{} (--channel {})", c.app_name(), c.as_str()))
+        .collect();
+
+    match find_cursor_config_dir() {
+        Ok(dir) if others.is_empty() => CheckResult::pass(NAME, dir.display().to_string()),
+        Ok(dir) => CheckResult::pass(
+            NAME,
+            format!("{}; also found {}", dir.display(), others.join(", ")),
+        ),
+        Err(_) if !others.is_empty() => CheckResult::fail(
+            NAME,
+            format!("{} not found", current.app_name()),
+            format!("Found {}", others.join(", ")),
+        ),
+        Err(e) => CheckResult::fail(
+            NAME,
+            e.to_string(),
+            "Install and open Cursor once, or set paths.cursor_dir in config.toml",
+        ),
+    }
+}
+
+fn check_cursor_version() -> CheckResult {
+    const NAME: &str = "Cursor version";
+
+    match detect_cursor_version() {
+        Some(version) if is_newer_than_known(&version) => CheckResult::warn(
+            NAME,
+            format!("{version} is newer than the last tested ({NEWEST_KNOWN_CURSOR_VERSION})"),
+            "Conversations may be missing or incomplete; please report any parsing problems",
+        ),
+        Some(version) => CheckResult::pass(NAME, format!("{version} (compatible)")),
+        None => CheckResult::warn(
+            NAME,
+            "not detected (product.json not found)",
+            "Compatibility cannot be checked; syncing still works if the database check passes",
+        ),
+    }
+}
+
+fn check_cursor_db() -> CheckResult {
+    const NAME: &str = "Cursor database";
+
+    let db_path = match global_state_db_path() {
+        Ok(path) if path.exists() => path,
+        Ok(path) => {
+            return CheckResult::fail(
+                NAME,
+                format!("{} does not exist", path.display()),
+                "Open Cursor once so it creates its database",
+            );
+        }
+        Err(e) => return CheckResult::fail(NAME, e.to_string(), "Check that $HOME is set"),
+    };
+
+    let reader = match StateDbReader::open(&db_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                e.to_string(),
+                "Check file permissions, or close Cursor if the database is locked",
+            );
+        }
+    };
+
+    match reader.has_kv_table() {
+        Ok(true) => match reader.count_composers() {
+            Ok(count) if reader.chat_table() == "cursorDiskKV" => {
+                CheckResult::pass(NAME, format!("readable, {count} conversations"))
+            }
+            Ok(count) => CheckResult::pass(
+                NAME,
+                format!(
+                    "readable, {count} conversations (in {})",
+                    reader.chat_table()
+                ),
+            ),
+            Err(e) => CheckResult::fail(NAME, e.to_string(), "The database may be corrupt"),
+        },
+        Ok(false) => CheckResult::fail(
+            NAME,
+            "no cursorDiskKV or ItemTable table found (unrecognized schema)",
+            "This Cursor version may not be supported yet; please report it",
+        ),
+        Err(e) => CheckResult::fail(NAME, e.to_string(), "The database may be corrupt"),
+    }
+}
+
+fn check_local_storage(config: &AppConfig) -> CheckResult {
+    const NAME: &str = "Local storage";
+
+    if !config.storage_db_path().exists() {
+        return CheckResult::warn(
+            NAME,
+            "no storage.db yet",
+            "Run 'cursor-chat sync now' to create it",
+        );
+    }
+
+    match StorageManager::new(config.clone()).check_health() {
+        Ok(report) if report.is_healthy() => CheckResult::pass(NAME, "healthy"),
+        Ok(report) if report.is_corrupt() => CheckResult::fail(
+            NAME,
+            format!(
+                "integrity check failed ({} errors)",
+                report.integrity_errors.len()
+            ),
+            "Run 'cursor-chat storage doctor --fix'",
+        ),
+        Ok(_) => CheckResult::warn(
+            NAME,
+            "inconsistent rows found",
+            "Run 'cursor-chat storage doctor --fix'",
+        ),
+        Err(e) => CheckResult::fail(NAME, e.to_string(), "Run 'cursor-chat storage doctor'"),
+    }
+}
+
+fn check_daemon(config: &AppConfig) -> CheckResult {
+    const NAME: &str = "Sync daemon";
+
+    match service_manager(config.clone()).get_status() {
+        Ok(status) if status.is_running => CheckResult::pass(NAME, status.short_status()),
+        Ok(status) => CheckResult::warn(
+            NAME,
+            status.short_status(),
+            "Run 'cursor-chat sync start' for automatic backups",
+        ),
+        Err(e) => CheckResult::warn(
+            NAME,
+            e.to_string(),
+            "systemd user services, cron or launchd (macOS) are required for the daemon",
+        ),
+    }
+}
+
+fn check_disk_space(config: &AppConfig) -> CheckResult {
+    const NAME: &str = "Disk space";
+
+    // The data directory may not exist yet; check the closest existing parent
+    let data_dir = config.data_dir();
+    let existing = data_dir
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("/"));
+
+    match available_space(existing) {
+        Ok(bytes) if bytes < CRITICAL_DISK_BYTES => CheckResult::fail(
+            NAME,
+            format!("only {} free", format_bytes(bytes)),
+            "Free disk space or move paths.data_dir to another disk",
+        ),
+        Ok(bytes) if bytes < LOW_DISK_BYTES => CheckResult::warn(
+            NAME,
+            format!("{} free", format_bytes(bytes)),
+            "Run 'cursor-chat storage cleanup' or free disk space",
+        ),
+        Ok(bytes) => CheckResult::pass(NAME, format!("{} free", format_bytes(bytes))),
+        Err(e) => CheckResult::warn(
+            NAME,
+            e.to_string(),
+            "Check free space manually with 'df -h'",
+        ),
+    }
+}
+
+fn check_permissions(config: &AppConfig) -> CheckResult {
+    const NAME: &str = "Permissions";
+
+    let data_dir = config.data_dir();
+    let probe = data_dir.join(".doctor-write-test");
+    let writable = fs::create_dir_all(&data_dir)
+        .and_then(|()| fs::write(&probe, b"ok"))
+        .and_then(|()| fs::remove_file(&probe));
+
+    if let Err(e) = writable {
+        return CheckResult::fail(
+            NAME,
+            format!("{} is not writable: {e}", data_dir.display()),
+            format!(
+                "Fix ownership, e.g. 'chown -R $USER {}'",
+                data_dir.display()
+            ),
+        );
+    }
+
+    if let Ok(db_path) = global_state_db_path() {
+        if db_path.exists() && fs::File::open(&db_path).is_err() {
+            return CheckResult::fail(
+                NAME,
+                format!("{} is not readable", db_path.display()),
+                "Fix ownership of Cursor's config directory",
+            );
+        }
+    }
+
+    CheckResult::pass(NAME, "data directory writable, Cursor database readable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permissions_check_on_writable_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().join("data"));
+
+        let result = check_permissions(&config);
+        assert_ne!(result.status, CheckStatus::Fail);
+        assert!(!dir.path().join("data/.doctor-write-test").exists());
+    }
+}
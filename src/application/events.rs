@@ -0,0 +1,192 @@
+//! Live events for `daemon --listen`.
+//!
+//! After each sync the daemon turns the new entries of the change feed into
+//! `conversation` and `message` events and pushes them to the connected
+//! [`EventStream`] clients.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{BubbleType, ChangeType, Result};
+use crate::infrastructure::{EventStream, LocalStorage};
+
+/// Upper bound on changes streamed per sync cycle.
+const MAX_CHANGES: usize = 100_000;
+
+/// A conversation that was created or updated by a sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationEvent {
+    /// Whether the conversation is new or changed.
+    pub kind: ChangeType,
+    /// Composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Total number of messages after the sync.
+    pub messages: usize,
+    /// When the sync ran.
+    pub synced_at: DateTime<Utc>,
+}
+
+/// A message that appeared in a synced conversation.
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageEvent {
+    /// Composer ID of the conversation.
+    pub composer_id: String,
+    /// Bubble ID.
+    pub bubble_id: String,
+    /// `user`, `assistant` or `unknown`.
+    pub role: &'static str,
+    /// When the message was written.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Message text.
+    pub text: String,
+}
+
+/// Event pushed to stream clients.
+#[derive(Debug, Clone)]
+pub enum SyncEvent {
+    /// A conversation was created or updated.
+    Conversation(ConversationEvent),
+    /// A message was added.
+    Message(MessageEvent),
+}
+
+impl SyncEvent {
+    /// SSE event name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Conversation(_) => "conversation",
+            Self::Message(_) => "message",
+        }
+    }
+}
+
+/// Events for every change synced after `since`, oldest first.
+///
+/// New conversations emit all their messages; updated ones only the
+/// messages created after `since`.
+///
+/// # Errors
+/// Returns error if local storage cannot be read.
+pub fn collect_events(storage: &LocalStorage, since: DateTime<Utc>) -> Result<Vec<SyncEvent>> {
+    let mut changes = storage.get_changes(Some(since), MAX_CHANGES)?;
+    changes.reverse();
+
+    let mut events = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for change in changes {
+        if !seen.insert(change.composer_id.clone()) {
+            continue;
+        }
+        let Some(conv) = storage.get_conversation(&change.composer_id)? else {
+            continue;
+        };
+
+        events.push(SyncEvent::Conversation(ConversationEvent {
+            kind: change.kind,
+            composer_id: change.composer_id.clone(),
+            title: change.title,
+            messages: conv.bubbles.len(),
+            synced_at: change.synced_at,
+        }));
+
+        let is_new = change.kind == ChangeType::Created;
+        events.extend(
+            conv.bubbles
+                .into_iter()
+                .filter(|b| is_new || b.created_at.is_some_and(|t| t > since))
+                .map(|b| {
+                    SyncEvent::Message(MessageEvent {
+                        composer_id: change.composer_id.clone(),
+                        bubble_id: b.bubble_id,
+                        role: match b.bubble_type {
+                            BubbleType::User => "user",
+                            BubbleType::Assistant => "assistant",
+                            BubbleType::Unknown => "unknown",
+                        },
+                        created_at: b.created_at,
+                        text: b.text,
+                    })
+                }),
+        );
+    }
+
+    Ok(events)
+}
+
+/// Push the events for changes synced after `since` to `stream`.
+///
+/// Returns the number of events sent.
+///
+/// # Errors
+/// Returns error if local storage cannot be read.
+pub fn publish_changes(
+    storage: &LocalStorage,
+    stream: &EventStream,
+    since: DateTime<Utc>,
+) -> Result<usize> {
+    let events = collect_events(storage, since)?;
+    for event in &events {
+        match event {
+            SyncEvent::Conversation(data) => stream.broadcast(event.name(), data)?,
+            SyncEvent::Message(data) => stream.broadcast(event.name(), data)?,
+        }
+    }
+    Ok(events.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Bubble, Conversation, ModelConfig};
+    use chrono::Duration;
+
+    fn bubble(id: &str, created_at: DateTime<Utc>) -> Bubble {
+        Bubble {
+            bubble_id: id.into(),
+            bubble_type: BubbleType::User,
+            text: format!("text {id}"),
+            created_at: Some(created_at),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect_events_only_new_messages_of_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        let since = Utc::now() - Duration::minutes(5);
+
+        let mut conv = Conversation {
+            composer_id: "c1".into(),
+            title: "Chat".into(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![bubble("old", since - Duration::hours(1))],
+            git: None,
+            workspace: None,
+        };
+        conv.bubbles.push(bubble("new", Utc::now()));
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+        for b in &conv.bubbles {
+            storage.upsert_bubble(b, conv_id).unwrap();
+        }
+        storage
+            .record_change("c1", ChangeType::Created, since - Duration::hours(1))
+            .unwrap();
+        storage
+            .record_change("c1", ChangeType::Updated, Utc::now())
+            .unwrap();
+
+        let events = collect_events(&storage, since).unwrap();
+        let names: Vec<&str> = events.iter().map(SyncEvent::name).collect();
+        assert_eq!(names, ["conversation", "message"]);
+        let SyncEvent::Message(message) = &events[1] else {
+            panic!("expected a message event");
+        };
+        assert_eq!((message.bubble_id.as_str(), message.role), ("new", "user"));
+    }
+}
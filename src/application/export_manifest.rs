@@ -0,0 +1,278 @@
+//! Manifest and `latest/` links for an exports directory.
+//!
+//! After an export run, `manifest.json` maps each conversation to its newest
+//! export file (with content hash and modification time), and `latest/`
+//! holds one link per conversation named after its composer ID, so tooling
+//! can find the newest export of a chat without guessing file names.
+//!
+//! The directory is the user's: only the links a previous manifest lists
+//! are ever replaced, and anything else in `latest/` stops the export.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{AppError, Result};
+
+/// Manifest file name inside the exports directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory of per-conversation links inside the exports directory.
+pub const LATEST_DIR: &str = "latest";
+
+/// Newest export of one conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name relative to the exports directory.
+    pub file: String,
+    /// Conversation title at export time.
+    pub title: String,
+    /// blake3 hash of the file contents.
+    pub hash: String,
+    /// File modification time.
+    pub mtime: DateTime<Utc>,
+}
+
+/// Index of the exports directory, keyed by composer ID.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// When the manifest was last written.
+    pub generated_at: Option<DateTime<Utc>>,
+    /// Newest export per conversation.
+    pub conversations: BTreeMap<String, ManifestEntry>,
+    #[serde(skip)]
+    dir: PathBuf,
+    /// `latest/` entries written for the manifest as loaded.
+    #[serde(skip)]
+    linked: BTreeSet<String>,
+}
+
+impl ExportManifest {
+    /// Load the manifest of `dir`, or start an empty one.
+    ///
+    /// # Errors
+    /// Returns error if an existing manifest cannot be read or parsed, or
+    /// `latest/` holds entries it does not list.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let mut manifest: Self = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+            serde_json::from_str(&content).map_err(AppError::json_parse)?
+        } else {
+            Self::default()
+        };
+        manifest.dir = dir.to_path_buf();
+        manifest.linked = manifest
+            .conversations
+            .iter()
+            .map(|(composer_id, entry)| link_name(composer_id, entry))
+            .collect();
+        manifest.check_latest()?;
+
+        Ok(manifest)
+    }
+
+    /// Record `file` (inside the exports directory) as the newest export
+    /// of a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read.
+    pub fn record(&mut self, composer_id: &str, title: &str, file: &Path) -> Result<()> {
+        let content = fs::read(file)
+            .map_err(|e| AppError::io(format!("Failed to read {}", file.display()), e))?;
+        let mtime = fs::metadata(file)
+            .and_then(|m| m.modified())
+            .map_or_else(|_| Utc::now(), DateTime::<Utc>::from);
+        let name = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        self.conversations.insert(
+            composer_id.to_string(),
+            ManifestEntry {
+                file: name,
+                title: title.to_string(),
+                hash: blake3::hash(&content).to_hex().to_string(),
+                mtime,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Write `manifest.json` and refresh the `latest/` links.
+    ///
+    /// Entries whose file no longer exists (e.g. removed by storage cleanup)
+    /// are dropped.
+    ///
+    /// # Errors
+    /// Returns error if `latest/` holds entries the loaded manifest does not
+    /// list, or the manifest or links cannot be written.
+    pub fn save(&mut self) -> Result<()> {
+        self.check_latest()?;
+        let dir = self.dir.clone();
+        self.conversations
+            .retain(|_, entry| dir.join(&entry.file).is_file());
+        self.generated_at = Some(Utc::now());
+
+        let content = serde_json::to_string_pretty(self).map_err(AppError::json_parse)?;
+        let path = dir.join(MANIFEST_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| AppError::io("Failed to write export manifest", e))?;
+        fs::rename(&tmp, &path).map_err(|e| AppError::io("Failed to save export manifest", e))?;
+
+        self.refresh_latest()
+    }
+
+    /// Make sure `latest/` only holds the links this manifest wrote.
+    fn check_latest(&self) -> Result<()> {
+        let latest = self.dir.join(LATEST_DIR);
+        if !latest.exists() {
+            return Ok(());
+        }
+
+        let entries = fs::read_dir(&latest)
+            .map_err(|e| AppError::io(format!("Failed to read {}", latest.display()), e))?;
+        for entry in entries {
+            let entry = entry
+                .map_err(|e| AppError::io(format!("Failed to read {}", latest.display()), e))?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !self.linked.contains(&name) {
+                return Err(AppError::Config {
+                    message: format!(
+                        "{} is not listed in {MANIFEST_FILE}; move it out of {} or export \
+                         to another directory",
+                        entry.path().display(),
+                        latest.display()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the links of the loaded manifest with one
+    /// `<composer_id>.<ext>` link per entry.
+    fn refresh_latest(&mut self) -> Result<()> {
+        let latest = self.dir.join(LATEST_DIR);
+        for name in &self.linked {
+            let link = latest.join(name);
+            if link.symlink_metadata().is_ok() {
+                fs::remove_file(&link)
+                    .map_err(|e| AppError::io(format!("Failed to remove {}", link.display()), e))?;
+            }
+        }
+        fs::create_dir_all(&latest)
+            .map_err(|e| AppError::io("Failed to create latest exports directory", e))?;
+
+        self.linked.clear();
+        for (composer_id, entry) in &self.conversations {
+            let name = link_name(composer_id, entry);
+            let link = latest.join(&name);
+            link_latest(
+                &Path::new("..").join(&entry.file),
+                &self.dir.join(&entry.file),
+                &link,
+            )
+            .map_err(|e| AppError::io(format!("Failed to link {}", link.display()), e))?;
+            self.linked.insert(name);
+        }
+
+        Ok(())
+    }
+}
+
+/// Name of a conversation's entry in `latest/`: `<composer_id>.<ext>`.
+fn link_name(composer_id: &str, entry: &ManifestEntry) -> String {
+    let extension = Path::new(&entry.file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map_or_else(String::new, |ext| format!(".{ext}"));
+    format!("{composer_id}{extension}")
+}
+
+/// Point `link` at an export: a relative symlink where supported, a copy
+/// elsewhere.
+#[cfg(unix)]
+fn link_latest(relative: &Path, _target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(relative, link)
+}
+
+#[cfg(not(unix))]
+fn link_latest(_relative: &Path, target: &Path, link: &Path) -> std::io::Result<()> {
+    fs::copy(target, link).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_manifest_tracks_newest_export() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("c1_old.md"), "old").unwrap();
+        fs::write(dir.path().join("c1_new.md"), "new").unwrap();
+        fs::write(dir.path().join("c2_gone.md"), "gone").unwrap();
+
+        let mut manifest = ExportManifest::load(dir.path()).unwrap();
+        manifest
+            .record("c1", "Old", &dir.path().join("c1_old.md"))
+            .unwrap();
+        manifest
+            .record("c2", "Gone", &dir.path().join("c2_gone.md"))
+            .unwrap();
+        manifest.save().unwrap();
+
+        fs::remove_file(dir.path().join("c2_gone.md")).unwrap();
+        let mut manifest = ExportManifest::load(dir.path()).unwrap();
+        manifest
+            .record("c1", "New", &dir.path().join("c1_new.md"))
+            .unwrap();
+        manifest.save().unwrap();
+
+        let manifest = ExportManifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.conversations.len(), 1);
+        assert_eq!(manifest.conversations["c1"].file, "c1_new.md");
+        assert_eq!(
+            manifest.conversations["c1"].hash,
+            blake3::hash(b"new").to_hex().to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("latest/c1.md")).unwrap(),
+            "new"
+        );
+        assert!(!dir.path().join("latest/c2.md").exists());
+    }
+
+    #[test]
+    fn test_unlisted_latest_entries_are_kept() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("c1_chat.md"), "chat").unwrap();
+        fs::create_dir(dir.path().join(LATEST_DIR)).unwrap();
+        fs::write(dir.path().join("latest/notes.txt"), "mine").unwrap();
+
+        assert!(ExportManifest::load(dir.path()).is_err());
+
+        // Also when the file shows up after loading
+        fs::remove_file(dir.path().join("latest/notes.txt")).unwrap();
+        let mut manifest = ExportManifest::load(dir.path()).unwrap();
+        manifest
+            .record("c1", "Chat", &dir.path().join("c1_chat.md"))
+            .unwrap();
+        fs::write(dir.path().join("latest/notes.txt"), "mine").unwrap();
+        assert!(manifest.save().is_err());
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("latest/notes.txt")).unwrap(),
+            "mine"
+        );
+        assert!(!dir.path().join(MANIFEST_FILE).exists());
+    }
+}
@@ -0,0 +1,160 @@
+//! Splitting oversized conversation exports into parts.
+//!
+//! A conversation is cut between messages, never inside one, so every part
+//! renders as a valid export of its own. A single message larger than the
+//! limit becomes a part by itself.
+
+use crate::domain::Conversation;
+
+use super::summarizer::CHARS_PER_TOKEN;
+
+/// Upper bound for one part of a split export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLimit {
+    /// Estimated tokens of the rendered part.
+    Tokens(usize),
+    /// Bytes of the rendered part.
+    Bytes(usize),
+}
+
+impl SplitLimit {
+    /// Size of `text` in the unit of this limit.
+    fn measure(self, text: &str) -> usize {
+        match self {
+            Self::Tokens(_) => text.chars().count().div_ceil(CHARS_PER_TOKEN),
+            Self::Bytes(_) => text.len(),
+        }
+    }
+
+    const fn max(self) -> usize {
+        match self {
+            Self::Tokens(max) | Self::Bytes(max) => max,
+        }
+    }
+}
+
+/// Split a conversation into parts whose rendering fits within `limit`.
+///
+/// `render` is the export formatter. Each message is measured once, as the
+/// growth of the rendering when it is added, so long sessions stay linear.
+/// Part titles are suffixed with `(part i/n)` when there is more than one.
+pub fn split_conversation(
+    conv: &Conversation,
+    limit: SplitLimit,
+    render: impl Fn(&Conversation) -> String,
+) -> Vec<Conversation> {
+    let empty = Conversation {
+        bubbles: Vec::new(),
+        ..conv.clone()
+    };
+    // Measure with room for the part suffix added to the title afterwards
+    let measured = Conversation {
+        title: format!("{} (part 999/999)", conv.title),
+        ..empty.clone()
+    };
+    let header = limit.measure(&render(&measured));
+
+    let mut groups: Vec<Vec<_>> = Vec::new();
+    let mut current = Vec::new();
+    let mut size = header;
+
+    for bubble in &conv.bubbles {
+        let single = Conversation {
+            bubbles: vec![bubble.clone()],
+            ..measured.clone()
+        };
+        let cost = limit.measure(&render(&single)).saturating_sub(header);
+
+        if !current.is_empty() && size + cost > limit.max() {
+            groups.push(std::mem::take(&mut current));
+            size = header;
+        }
+        current.push(bubble.clone());
+        size += cost;
+    }
+    if !current.is_empty() || groups.is_empty() {
+        groups.push(current);
+    }
+
+    let total = groups.len();
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, bubbles)| Conversation {
+            title: if total > 1 {
+                format!("{} (part {}/{total})", conv.title, i + 1)
+            } else {
+                conv.title.clone()
+            },
+            bubbles,
+            ..empty.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Bubble, BubbleType, ModelConfig};
+
+    fn conversation(texts: &[&str]) -> Conversation {
+        Conversation {
+            composer_id: "c1".to_string(),
+            title: "Long session".to_string(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| Bubble {
+                    bubble_id: format!("b{i}"),
+                    bubble_type: BubbleType::User,
+                    text: (*text).to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            git: None,
+            workspace: None,
+        }
+    }
+
+    fn render(conv: &Conversation) -> String {
+        let mut out = format!("# {}\n", conv.title);
+        for bubble in &conv.bubbles {
+            out.push_str(&bubble.text);
+        }
+        out
+    }
+
+    #[test]
+    fn test_split_by_bytes() {
+        let conv = conversation(&["aaaa", "bbbb", "cccc", "dddd"]);
+        let parts = split_conversation(&conv, SplitLimit::Bytes(40), render);
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].bubbles.len(), 2);
+        assert_eq!(parts[1].bubbles[0].bubble_id, "b2");
+        assert_eq!(parts[1].title, "Long session (part 2/2)");
+        assert!(parts.iter().all(|p| render(p).len() <= 40));
+    }
+
+    #[test]
+    fn test_split_keeps_oversized_message_whole() {
+        let long = "x".repeat(400);
+        let conv = conversation(&["hi", &long, "bye"]);
+        let parts = split_conversation(&conv, SplitLimit::Tokens(20), render);
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[1].bubbles[0].text.len(), 400);
+    }
+
+    #[test]
+    fn test_small_conversation_is_one_part() {
+        let conv = conversation(&["hi"]);
+        let parts = split_conversation(&conv, SplitLimit::Bytes(1_000), render);
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].title, "Long session");
+    }
+}
@@ -2,15 +2,26 @@
 //!
 //! Orchestrates reading from databases and building conversation structures.
 
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 
-use crate::domain::{AppError, Conversation, ExtractionStats, ModelConfig, Result};
-use crate::infrastructure::{find_state_databases, StateDbReader};
+use crate::domain::{
+    AppError, Bubble, BubbleType, Conversation, ExtractionStats, ModelConfig, Result, ShortIds,
+    WorkspaceInfo,
+};
+use crate::infrastructure::{
+    find_state_databases, global_state_db_path, state_db_fingerprint, workspace_of_database,
+    CachedConversation, ConversationCache, LocalStorage, RawKvEntry, StateDbReader, TIMINGS_TARGET,
+};
 
-use super::parser::{extract_composer_id, extract_conversation_id, parse_bubble, parse_composer};
+use super::legacy_chat::read_legacy_chats;
+use super::parser::{
+    extract_composer_id, extract_conversation_id, parse_bubble, parse_bubble_workspace,
+    parse_composer, RawComposer,
+};
+use super::workspace_chats::read_workspace_composers;
 
 /// Options for chat extraction.
 #[derive(Debug, Clone, Default)]
@@ -23,6 +34,46 @@ pub struct ExtractOptions {
     pub min_messages: usize,
     /// Include conversations with empty text.
     pub include_empty: bool,
+    /// Reuse parsed conversations from this cache file, and refresh it.
+    pub cache_path: Option<PathBuf>,
+    /// Only conversations with messages at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only conversations with messages before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Also drop the messages outside `since`..`until`.
+    pub trim_bubbles: bool,
+}
+
+impl ExtractOptions {
+    /// Whether `since` or `until` is set.
+    #[must_use]
+    pub const fn has_date_range(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Whether `at` falls within `since`..`until`.
+    fn in_date_range(&self, at: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| at >= since) && self.until.is_none_or(|until| at < until)
+    }
+
+    /// Apply the date range to `conv`: whether it has a message in the range
+    /// (its creation time if no message is timed), dropping the messages
+    /// outside the range with `trim_bubbles`. Untimed messages are kept.
+    fn keep_in_date_range(&self, conv: &mut Conversation) -> bool {
+        if !self.has_date_range() {
+            return true;
+        }
+        if self.trim_bubbles {
+            conv.bubbles
+                .retain(|b| b.created_at.is_none_or(|at| self.in_date_range(at)));
+        }
+
+        let mut times = conv.bubbles.iter().filter_map(|b| b.created_at).peekable();
+        if times.peek().is_none() {
+            return conv.created_at.is_some_and(|at| self.in_date_range(at));
+        }
+        times.any(|at| self.in_date_range(at))
+    }
 }
 
 /// Extracts all conversations from Cursor databases.
@@ -43,7 +94,27 @@ pub fn extract_all_conversations(
             message: "Global storage database not found".into(),
         })?;
 
-    let conversations = extract_from_database(global_db, options, &mut stats)?;
+    let mut conversations = match &options.cache_path {
+        Some(cache_path) => {
+            let entries = parse_with_cache(global_db, cache_path)?;
+            let selected = select_cached(entries, options, &mut stats);
+            finish_all(selected, options, &mut stats)
+        }
+        None => extract_from_database(global_db, options, &mut stats)?,
+    };
+    if !options.global_only {
+        append_database_chats(
+            &databases,
+            global_db,
+            options,
+            &mut conversations,
+            &mut stats,
+        );
+    }
+    // Messages were counted before the range dropped some
+    if options.has_date_range() {
+        stats = stats.of_subset(&conversations);
+    }
 
     stats.databases_scanned = if options.global_only {
         1
@@ -54,6 +125,117 @@ pub fn extract_all_conversations(
     Ok((conversations, stats))
 }
 
+/// Add the chats only found outside the global composer store, skipping
+/// IDs already extracted: see [`database_chats`]. Chats from a workspace
+/// database are attributed to its workspace.
+fn append_database_chats(
+    databases: &[PathBuf],
+    global_db: &Path,
+    options: &ExtractOptions,
+    conversations: &mut Vec<Conversation>,
+    stats: &mut ExtractionStats,
+) {
+    let mut seen: HashSet<String> = conversations
+        .iter()
+        .map(|c| c.composer_id.clone())
+        .collect();
+    let before = conversations.len();
+
+    for (mut conv, workspace) in database_chats(databases, global_db) {
+        if let Some(ref filter) = options.conversation_ids {
+            if !matches_any_filter(&conv.composer_id, filter) {
+                continue;
+            }
+        }
+        if !options.include_empty {
+            conv.bubbles.retain(|b| !b.is_empty());
+        }
+        if !options.keep_in_date_range(&mut conv) {
+            continue;
+        }
+        if conv.bubbles.len() < options.min_messages || !seen.insert(conv.composer_id.clone()) {
+            continue;
+        }
+
+        for bubble in &conv.bubbles {
+            count_bubble(stats, bubble);
+        }
+        conv.workspace = workspace.map(|info| info.name);
+        conversations.push(finish_conversation(conv));
+    }
+
+    if conversations.len() > before {
+        tracing::info!(
+            count = conversations.len() - before,
+            "Extracted chats from legacy tabs and workspace databases"
+        );
+        conversations.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+        stats.conversation_count = conversations.len();
+    }
+}
+
+/// Chats kept outside the global composer store: pre-composer chat tabs in
+/// any of `databases`, and composers stored inline in workspace databases,
+/// with the workspace of the database they came from. Each ID is listed
+/// once, from the first database holding it; unreadable databases are
+/// skipped.
+pub(crate) fn database_chats(
+    databases: &[PathBuf],
+    global_db: &Path,
+) -> Vec<(Conversation, Option<WorkspaceInfo>)> {
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+
+    for path in databases {
+        let mut chats = match read_legacy_chats(path) {
+            Ok(tabs) => tabs,
+            Err(e) => {
+                tracing::debug!(path = %path.display(), error = %e, "Skipping legacy chats");
+                Vec::new()
+            }
+        };
+        if path != global_db {
+            match read_workspace_composers(path) {
+                Ok(composers) => chats.extend(composers),
+                Err(e) => {
+                    tracing::debug!(path = %path.display(), error = %e, "Skipping workspace composers");
+                }
+            }
+        }
+        if chats.is_empty() {
+            continue;
+        }
+        let workspace = (path != global_db)
+            .then(|| workspace_of_database(path))
+            .flatten();
+
+        for conv in chats {
+            if seen.insert(conv.composer_id.clone()) {
+                result.push((conv, workspace.clone()));
+            }
+        }
+    }
+
+    result
+}
+
+/// Extracts conversations from a specific Cursor database file.
+///
+/// # Errors
+/// Returns error if the database cannot be read.
+pub fn extract_from_path(
+    path: &Path,
+    options: &ExtractOptions,
+) -> Result<(Vec<Conversation>, ExtractionStats)> {
+    let mut stats = ExtractionStats {
+        databases_scanned: 1,
+        ..Default::default()
+    };
+    let conversations = extract_from_database(path, options, &mut stats)?;
+
+    Ok((conversations, stats))
+}
+
 /// Extracts conversations from a single database file.
 fn extract_from_database(
     path: &Path,
@@ -67,6 +249,9 @@ fn extract_from_database(
     // Load composers (conversation metadata)
     let composer_entries = reader.fetch_composers()?;
     let mut composer_map: HashMap<String, Conversation> = HashMap::new();
+    let parsing =
+        tracing::info_span!(target: TIMINGS_TARGET, "json parse", rows = composer_entries.len())
+            .entered();
 
     for entry in composer_entries {
         if let Some(id) = extract_composer_id(&entry.key) {
@@ -78,28 +263,7 @@ fn extract_from_database(
 
             match parse_composer(&entry.value) {
                 Ok(raw) => {
-                    let created_at = raw
-                        .created_at
-                        .and_then(|ms| DateTime::from_timestamp_millis(ms as i64));
-
-                    let model_config =
-                        raw.model_config
-                            .map_or_else(ModelConfig::default, |m| ModelConfig {
-                                model_name: m.model_name,
-                                max_mode: m.max_mode,
-                            });
-
-                    composer_map.insert(
-                        id.to_string(),
-                        Conversation {
-                            composer_id: id.to_string(),
-                            title: String::new(), // Will be filled after bubbles load
-                            created_at,
-                            model_config,
-                            unified_mode: raw.unified_mode.unwrap_or_default(),
-                            bubbles: Vec::new(),
-                        },
-                    );
+                    composer_map.insert(id.to_string(), conversation_from_composer(id, raw));
                 }
                 Err(e) => {
                     tracing::debug!("Failed to parse composer {}: {}", id, e);
@@ -108,8 +272,13 @@ fn extract_from_database(
         }
     }
 
+    drop(parsing);
+
     // Load bubbles and attach to conversations
     let bubble_entries = reader.fetch_bubbles()?;
+    let parsing =
+        tracing::info_span!(target: TIMINGS_TARGET, "json parse", rows = bubble_entries.len())
+            .entered();
 
     for entry in bubble_entries {
         if let Some(conv_id) = extract_conversation_id(&entry.key) {
@@ -123,16 +292,10 @@ fn extract_from_database(
             match parse_bubble(&entry.value) {
                 Ok(bubble) => {
                     // Skip empty messages unless requested
-                    if !options.include_empty && bubble.text.trim().is_empty() {
+                    if !options.include_empty && bubble.is_empty() {
                         continue;
                     }
-
-                    stats.total_bubbles += 1;
-                    match bubble.bubble_type {
-                        crate::domain::BubbleType::User => stats.user_messages += 1,
-                        crate::domain::BubbleType::Assistant => stats.assistant_messages += 1,
-                        crate::domain::BubbleType::Unknown => {}
-                    }
+                    count_bubble(stats, &bubble);
 
                     // Create conversation if it doesn't exist (orphan bubble)
                     let conversation =
@@ -145,6 +308,8 @@ fn extract_from_database(
                                 model_config: ModelConfig::default(),
                                 unified_mode: String::new(),
                                 bubbles: Vec::new(),
+                                git: None,
+                                workspace: None,
                             });
 
                     conversation.bubbles.push(bubble);
@@ -156,19 +321,34 @@ fn extract_from_database(
         }
     }
 
-    // Sort bubbles by creation time, generate titles, and filter conversations
-    let mut conversations: Vec<Conversation> = composer_map
-        .into_values()
+    drop(parsing);
+
+    Ok(finish_all(
+        composer_map.into_values().collect(),
+        options,
+        stats,
+    ))
+}
+
+/// Drop conversations outside the date range or below `min_messages`, then
+/// sort bubbles, generate titles and order conversations newest first.
+fn finish_all(
+    conversations: Vec<Conversation>,
+    options: &ExtractOptions,
+    stats: &mut ExtractionStats,
+) -> Vec<Conversation> {
+    let mut conversations: Vec<Conversation> = conversations
+        .into_iter()
+        .filter_map(|mut c| options.keep_in_date_range(&mut c).then_some(c))
         .filter(|c| c.bubbles.len() >= options.min_messages)
-        .map(|mut c| {
-            c.bubbles.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-            c.title = c.generate_title();
-            c
-        })
+        .map(finish_conversation)
         .collect();
 
     // Sort conversations by creation time (newest first)
-    conversations.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    let sorting =
+        tracing::info_span!(target: TIMINGS_TARGET, "sort", rows = conversations.len()).entered();
+    conversations.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+    drop(sorting);
 
     stats.conversation_count = conversations.len();
 
@@ -178,10 +358,636 @@ fn extract_from_database(
         stats.total_bubbles
     );
 
-    Ok(conversations)
+    conversations
+}
+
+/// Parsed conversations of `path`, from the cache when the database is
+/// unchanged.
+///
+/// Otherwise the rows are read again, but conversations whose rows hash the
+/// same as when they were cached are not parsed again. The refreshed cache
+/// is saved on a best-effort basis.
+fn parse_with_cache(path: &Path, cache_path: &Path) -> Result<Vec<CachedConversation>> {
+    tracing::info!("Extracting from: {}", path.display());
+
+    // Taken before reading, so writes made meanwhile invalidate the cache
+    let fingerprint = state_db_fingerprint(path)?;
+    let cache = ConversationCache::load(cache_path);
+    if cache.is_current(path, &fingerprint) {
+        tracing::debug!("Using cached conversations from {}", cache_path.display());
+        return Ok(cache.entries);
+    }
+
+    let mut previous: HashMap<String, CachedConversation> = cache
+        .entries
+        .into_iter()
+        .map(|entry| (entry.conversation.composer_id.clone(), entry))
+        .collect();
+
+    let reader = StateDbReader::open(path)?;
+    let composers = reader.fetch_composers()?;
+    let bubbles = reader.fetch_bubbles()?;
+    drop(reader);
+
+    // Group rows by conversation, keeping database order
+    let mut order: Vec<&str> = Vec::new();
+    let mut groups: HashMap<&str, (Option<&RawKvEntry>, Vec<&RawKvEntry>)> = HashMap::new();
+    for entry in &composers {
+        if let Some(id) = extract_composer_id(&entry.key) {
+            groups
+                .entry(id)
+                .or_insert_with(|| {
+                    order.push(id);
+                    (None, Vec::new())
+                })
+                .0 = Some(entry);
+        }
+    }
+    for entry in &bubbles {
+        if let Some(id) = extract_conversation_id(&entry.key) {
+            groups
+                .entry(id)
+                .or_insert_with(|| {
+                    order.push(id);
+                    (None, Vec::new())
+                })
+                .1
+                .push(entry);
+        }
+    }
+
+    let parsing = tracing::info_span!(
+        target: TIMINGS_TARGET,
+        "json parse",
+        rows = tracing::field::Empty
+    )
+    .entered();
+    let mut parsed_rows = 0;
+    let entries: Vec<CachedConversation> = order
+        .into_iter()
+        .filter_map(|id| {
+            let (composer, bubble_rows) = groups.remove(id)?;
+            let raw_hash = hash_rows(composer, &bubble_rows);
+            if let Some(entry) = previous.remove(id).filter(|e| e.raw_hash == raw_hash) {
+                return Some(entry);
+            }
+            parsed_rows += bubble_rows.len() + usize::from(composer.is_some());
+            parse_rows(id, composer, &bubble_rows).map(|(conversation, orphan)| {
+                CachedConversation {
+                    raw_hash,
+                    orphan,
+                    conversation,
+                }
+            })
+        })
+        .collect();
+    parsing.record("rows", parsed_rows);
+    drop(parsing);
+
+    let cache = ConversationCache::new(path, fingerprint, entries);
+    if let Err(e) = cache.save(cache_path) {
+        tracing::warn!(error = %e, "Failed to save conversation cache");
+    }
+
+    Ok(cache.entries)
+}
+
+/// Hash of a conversation's raw rows, to detect changes.
+fn hash_rows(composer: Option<&RawKvEntry>, bubbles: &[&RawKvEntry]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for entry in composer.into_iter().chain(bubbles.iter().copied()) {
+        hasher.update(&(entry.key.len() as u64).to_le_bytes());
+        hasher.update(entry.key.as_bytes());
+        hasher.update(&(entry.value.len() as u64).to_le_bytes());
+        hasher.update(&entry.value);
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Parse one conversation with all its bubbles, empty ones included.
+///
+/// Also returns whether it is an orphan (bubbles without a usable composer).
+fn parse_rows(
+    id: &str,
+    composer: Option<&RawKvEntry>,
+    bubbles: &[&RawKvEntry],
+) -> Option<(Conversation, bool)> {
+    let parsed = composer.and_then(|entry| {
+        parse_composer(&entry.value)
+            .map_err(|e| tracing::debug!("Failed to parse composer {}: {}", id, e))
+            .ok()
+    });
+    let orphan = parsed.is_none();
+    if orphan && bubbles.is_empty() {
+        return None;
+    }
+
+    let mut conversation = parsed.map_or_else(
+        || Conversation {
+            composer_id: id.to_string(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        },
+        |raw| conversation_from_composer(id, raw),
+    );
+    conversation.bubbles = bubbles
+        .iter()
+        .filter_map(|entry| {
+            parse_bubble(&entry.value)
+                .map_err(|e| tracing::debug!("Failed to parse bubble: {}", e))
+                .ok()
+        })
+        .collect();
+
+    Some((conversation, orphan))
+}
+
+/// Apply `options` to cached conversations, as [`extract_from_database`]
+/// does while parsing.
+fn select_cached(
+    entries: Vec<CachedConversation>,
+    options: &ExtractOptions,
+    stats: &mut ExtractionStats,
+) -> Vec<Conversation> {
+    entries
+        .into_iter()
+        .filter(|entry| {
+            options
+                .conversation_ids
+                .as_ref()
+                .is_none_or(|filter| matches_any_filter(&entry.conversation.composer_id, filter))
+        })
+        .filter_map(|entry| {
+            let mut conversation = entry.conversation;
+            if !options.include_empty {
+                conversation.bubbles.retain(|b| !b.is_empty());
+            }
+            // Orphans exist only through their bubbles and start with the first one
+            if entry.orphan {
+                conversation.created_at = conversation.bubbles.first()?.created_at;
+            }
+            for bubble in &conversation.bubbles {
+                count_bubble(stats, bubble);
+            }
+            Some(conversation)
+        })
+        .collect()
+}
+
+/// Conversations handed over by [`extract_in_batches`].
+#[derive(Debug, Default)]
+pub struct ConversationBatch {
+    /// Conversations, newest first within the batch.
+    pub conversations: Vec<Conversation>,
+    /// Workspace of each conversation, when its bubbles record one.
+    pub workspaces: HashMap<String, WorkspaceInfo>,
+}
+
+/// Extracts conversations from a Cursor database one at a time, handing
+/// them to `on_batch` whenever their estimated size reaches `budget_bytes`.
+///
+/// Keeps memory near the budget on huge histories, at the cost of one query
+/// per conversation. Conversations come newest first, then those whose
+/// composer entry is missing. Warns once if the history exceeds the budget.
+///
+/// # Errors
+/// Returns error if the database cannot be read or `on_batch` fails.
+pub fn extract_in_batches(
+    db_path: &Path,
+    options: &ExtractOptions,
+    budget_bytes: usize,
+    mut on_batch: impl FnMut(ConversationBatch) -> Result<()>,
+) -> Result<ExtractionStats> {
+    let reader = StateDbReader::open(db_path)?;
+    let mut stats = ExtractionStats {
+        databases_scanned: 1,
+        ..Default::default()
+    };
+
+    let composer_entries = reader.fetch_composers()?;
+    let parsing =
+        tracing::info_span!(target: TIMINGS_TARGET, "json parse", rows = composer_entries.len())
+            .entered();
+    let mut composers: Vec<Conversation> = composer_entries
+        .iter()
+        .filter_map(|entry| {
+            let id = extract_composer_id(&entry.key)?;
+            let raw = parse_composer(&entry.value)
+                .map_err(|e| tracing::debug!("Failed to parse composer {}: {}", id, e))
+                .ok()?;
+            Some(conversation_from_composer(id, raw))
+        })
+        .collect();
+    drop(parsing);
+    composers.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+
+    let known: std::collections::HashSet<String> =
+        composers.iter().map(|c| c.composer_id.clone()).collect();
+    let orphans = reader
+        .bubble_conversation_ids()?
+        .into_iter()
+        .filter(|id| !known.contains(id))
+        .map(|id| Conversation {
+            composer_id: id,
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        });
+
+    let mut batch = ConversationBatch::default();
+    let mut batch_bytes = 0;
+    let mut warned = false;
+
+    for mut conv in composers.into_iter().chain(orphans) {
+        if let Some(ref filter) = options.conversation_ids {
+            if !matches_any_filter(&conv.composer_id, filter) {
+                continue;
+            }
+        }
+
+        let entries = reader.fetch_bubbles_for(&conv.composer_id)?;
+        let parsing =
+            tracing::info_span!(target: TIMINGS_TARGET, "json parse", rows = entries.len())
+                .entered();
+        for entry in entries {
+            let bubble = match parse_bubble(&entry.value) {
+                Ok(bubble) => bubble,
+                Err(e) => {
+                    tracing::debug!("Failed to parse bubble: {}", e);
+                    continue;
+                }
+            };
+            if !options.include_empty && bubble.is_empty() {
+                continue;
+            }
+            if let Some(ws_info) = parse_bubble_workspace(&entry.value) {
+                batch.workspaces.insert(conv.composer_id.clone(), ws_info);
+            }
+            if conv.created_at.is_none() {
+                conv.created_at = bubble.created_at;
+            }
+            conv.bubbles.push(bubble);
+        }
+        drop(parsing);
+
+        if conv.bubbles.is_empty()
+            || !options.keep_in_date_range(&mut conv)
+            || conv.bubbles.len() < options.min_messages
+        {
+            batch.workspaces.remove(&conv.composer_id);
+            continue;
+        }
+        for bubble in &conv.bubbles {
+            count_bubble(&mut stats, bubble);
+        }
+        stats.conversation_count += 1;
+
+        batch_bytes += estimated_bytes(&conv);
+        batch.conversations.push(finish_conversation(conv));
+
+        if batch_bytes >= budget_bytes {
+            if !warned {
+                tracing::warn!(
+                    budget_mb = budget_bytes / (1024 * 1024),
+                    "Conversations exceed the memory budget, processing in batches"
+                );
+                warned = true;
+            }
+            on_batch(std::mem::take(&mut batch))?;
+            batch_bytes = 0;
+        }
+    }
+
+    if !batch.conversations.is_empty() {
+        on_batch(batch)?;
+    }
+
+    Ok(stats)
+}
+
+/// Conversation metadata from a composer entry, without messages.
+fn conversation_from_composer(id: &str, raw: RawComposer) -> Conversation {
+    let created_at = raw
+        .created_at
+        .and_then(|ms| DateTime::from_timestamp_millis(ms.cast_signed()));
+
+    let model_config = raw
+        .model_config
+        .map_or_else(ModelConfig::default, |m| ModelConfig {
+            model_name: m.model_name,
+            max_mode: m.max_mode,
+        });
+
+    Conversation {
+        composer_id: id.to_string(),
+        title: String::new(), // Will be filled after bubbles load
+        created_at,
+        model_config,
+        unified_mode: raw.unified_mode.unwrap_or_default(),
+        bubbles: Vec::new(),
+        git: None,
+        workspace: None,
+    }
+}
+
+/// Sort messages by creation time and generate the title.
+fn finish_conversation(mut conv: Conversation) -> Conversation {
+    let _sorting =
+        tracing::info_span!(target: TIMINGS_TARGET, "sort", rows = conv.bubbles.len()).entered();
+    conv.bubbles.sort_by_key(|b| b.created_at);
+    conv.title = conv.generate_title();
+    conv
+}
+
+const fn count_bubble(stats: &mut ExtractionStats, bubble: &Bubble) {
+    stats.total_bubbles += 1;
+    match bubble.bubble_type {
+        BubbleType::User => stats.user_messages += 1,
+        BubbleType::Assistant => stats.assistant_messages += 1,
+        BubbleType::Unknown => {}
+    }
+}
+
+/// Rough in-memory size of a conversation: its text plus per-message overhead.
+fn estimated_bytes(conv: &Conversation) -> usize {
+    const BUBBLE_OVERHEAD: usize = 256;
+
+    conv.bubbles
+        .iter()
+        .map(|b| {
+            b.text.len()
+                + b.bubble_id.len()
+                + b.thinking.as_ref().map_or(0, |t| t.text.len())
+                + BUBBLE_OVERHEAD
+        })
+        .sum()
+}
+
+/// Short IDs unique across every conversation in Cursor and in local storage.
+///
+/// Display helper: unreadable sources are left out rather than failing.
+#[must_use]
+pub fn load_short_ids(storage_path: &Path) -> ShortIds {
+    let mut ids = global_state_db_path()
+        .and_then(|path| StateDbReader::open(&path)?.composer_ids())
+        .unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "Could not list Cursor conversation IDs");
+            Vec::new()
+        });
+
+    if storage_path.exists() {
+        // An empty fragment matches every stored ID
+        match LocalStorage::open_read_only(storage_path).and_then(|s| s.find_composer_ids("")) {
+            Ok(stored) => ids.extend(stored),
+            Err(e) => tracing::debug!(error = %e, "Could not list stored conversation IDs"),
+        }
+    }
+
+    ShortIds::new(ids.iter().map(String::as_str))
 }
 
 /// Checks if an ID matches any of the filter patterns (partial match).
 fn matches_any_filter(id: &str, filters: &[String]) -> bool {
     filters.iter().any(|f| id.starts_with(f) || id.contains(f))
 }
+
+/// Find the conversation an ID refers to: the exact ID, else the only one
+/// starting with it, else the only one containing it.
+///
+/// # Errors
+/// Returns error if nothing matches, or if several conversations match at
+/// the same level; the message lists them so a longer ID can be picked.
+pub fn find_conversation<'a>(
+    conversations: &'a [Conversation],
+    id: &str,
+) -> Result<&'a Conversation> {
+    if let Some(exact) = conversations.iter().find(|c| c.composer_id == id) {
+        return Ok(exact);
+    }
+
+    let prefixed: Vec<&Conversation> = conversations
+        .iter()
+        .filter(|c| c.composer_id.starts_with(id))
+        .collect();
+    let matches = if prefixed.is_empty() {
+        conversations
+            .iter()
+            .filter(|c| c.composer_id.contains(id))
+            .collect()
+    } else {
+        prefixed
+    };
+
+    match matches.as_slice() {
+        [] => Err(AppError::InvalidData {
+            message: format!("Conversation not found: {id}"),
+        }),
+        [single] => Ok(single),
+        _ => {
+            let listing: Vec<String> = matches
+                .iter()
+                .map(|c| format!("  {}  {}", c.composer_id, c.title))
+                .collect();
+            Err(AppError::InvalidData {
+                message: format!(
+                    "'{id}' matches {} conversations; use a longer ID:\n{}",
+                    matches.len(),
+                    listing.join("\n")
+                ),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+        let bubble = |id: &str, n: u32, text: &str| {
+            format!(
+                "('bubbleId:{id}:{n}', '{{\"bubbleId\":\"{n}\",\"type\":1,\"text\":\"{text}\"}}')"
+            )
+        };
+        let sql = format!(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES
+                 ('composerData:old', '{{\"createdAt\":1000}}'),
+                 ('composerData:new', '{{\"createdAt\":2000}}'),
+                 {}, {}, {}, {};",
+            bubble("old", 1, "first"),
+            bubble("new", 1, "second"),
+            bubble("new", 2, ""),
+            bubble("orphan", 1, "third"),
+        );
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(&sql)
+            .unwrap();
+
+        let mut batches = Vec::new();
+        let stats = extract_in_batches(&path, &ExtractOptions::default(), 1, |batch| {
+            batches.push(batch.conversations);
+            Ok(())
+        })
+        .unwrap();
+
+        let order: Vec<&str> = batches
+            .iter()
+            .flatten()
+            .map(|c| c.composer_id.as_str())
+            .collect();
+        assert_eq!(order, ["new", "old", "orphan"]);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(stats.conversation_count, 3);
+        assert_eq!(stats.total_bubbles, 3);
+    }
+
+    #[test]
+    fn test_cached_extraction_matches_and_refreshes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+        let cache_path = dir.path().join("cache").join("conversations.json.zst");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES
+                 ('composerData:a', '{\"createdAt\":1000}'),
+                 ('bubbleId:a:1', '{\"bubbleId\":\"1\",\"type\":1,\"text\":\"fix parser\"}'),
+                 ('bubbleId:a:2', '{\"bubbleId\":\"2\",\"type\":2,\"text\":\"\"}'),
+                 ('bubbleId:orphan:1', '{\"bubbleId\":\"1\",\"type\":1,\"text\":\"\"}');",
+        )
+        .unwrap();
+
+        let summary = |conversations: &[Conversation]| -> Vec<(String, String, usize)> {
+            conversations
+                .iter()
+                .map(|c| (c.composer_id.clone(), c.title.clone(), c.bubbles.len()))
+                .collect()
+        };
+        let cached = |options: &ExtractOptions| {
+            let mut stats = ExtractionStats::default();
+            let entries = parse_with_cache(&path, &cache_path).unwrap();
+            finish_all(
+                select_cached(entries, options, &mut stats),
+                options,
+                &mut stats,
+            )
+        };
+
+        for options in [
+            ExtractOptions::default(),
+            ExtractOptions {
+                include_empty: true,
+                ..Default::default()
+            },
+        ] {
+            let (direct, _) = extract_from_path(&path, &options).unwrap();
+            assert_eq!(summary(&cached(&options)), summary(&direct));
+        }
+        assert!(cache_path.exists());
+
+        conn.execute(
+            "UPDATE cursorDiskKV SET value = '{\"bubbleId\":\"2\",\"type\":2,\"text\":\"done\"}'
+             WHERE key = 'bubbleId:a:2'",
+            [],
+        )
+        .unwrap();
+        let refreshed = cached(&ExtractOptions::default());
+        assert_eq!(refreshed[0].bubbles.len(), 2);
+        assert_eq!(refreshed[0].bubbles[1].text, "done");
+    }
+
+    #[test]
+    fn test_extract_date_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+                 INSERT INTO cursorDiskKV VALUES
+                     ('bubbleId:a:1', '{\"bubbleId\":\"1\",\"type\":1,\"text\":\"old\",\"createdAt\":\"2024-05-01T10:00:00Z\"}'),
+                     ('bubbleId:a:2', '{\"bubbleId\":\"2\",\"type\":1,\"text\":\"new\",\"createdAt\":\"2024-05-03T10:00:00Z\"}'),
+                     ('bubbleId:b:1', '{\"bubbleId\":\"1\",\"type\":1,\"text\":\"older\",\"createdAt\":\"2024-04-01T10:00:00Z\"}');",
+            )
+            .unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        let mut options = ExtractOptions {
+            since: Some(at("2024-05-02T00:00:00Z")),
+            ..Default::default()
+        };
+        let (conversations, _) = extract_from_path(&path, &options).unwrap();
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].bubbles.len(), 2);
+
+        options.trim_bubbles = true;
+        let (conversations, _) = extract_from_path(&path, &options).unwrap();
+        assert_eq!(conversations[0].bubbles.len(), 1);
+        assert_eq!(conversations[0].bubbles[0].text, "new");
+
+        let options = ExtractOptions {
+            until: Some(at("2024-05-01T00:00:00Z")),
+            ..Default::default()
+        };
+        let (conversations, _) = extract_from_path(&path, &options).unwrap();
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].composer_id, "b");
+    }
+
+    fn conversation(id: &str) -> Conversation {
+        Conversation {
+            composer_id: id.to_string(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_find_conversation() {
+        let conversations = [
+            conversation("abc123"),
+            conversation("abd456"),
+            conversation("ffabc0"),
+        ];
+
+        assert_eq!(
+            find_conversation(&conversations, "abc")
+                .unwrap()
+                .composer_id,
+            "abc123"
+        );
+        assert_eq!(
+            find_conversation(&conversations, "456")
+                .unwrap()
+                .composer_id,
+            "abd456"
+        );
+
+        let err = find_conversation(&conversations, "ab")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("matches 2 conversations"));
+        assert!(err.contains("abd456"));
+
+        assert!(find_conversation(&conversations, "zzz").is_err());
+    }
+}
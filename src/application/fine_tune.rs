@@ -0,0 +1,199 @@
+//! Fine-tuning dataset export (`export --format jsonl`).
+//!
+//! Turns each conversation into one training example in the chat format
+//! fine-tuning APIs accept, `{"messages": [{"role": "user", ...}]}`.
+//! Consecutive messages of the same role are merged, since agent turns span
+//! many assistant bubbles and both formats expect roles to alternate.
+
+use serde::Serialize;
+
+use crate::domain::{Bubble, BubbleType, Conversation};
+
+/// Blocks Cursor injects into prompts as context rather than user text.
+const CONTEXT_TAGS: &[&str] = &[
+    "additional_data",
+    "attached_files",
+    "cursor_commands",
+    "project_layout",
+    "rules",
+    "system_reminder",
+    "user_info",
+];
+
+/// What goes into each example.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrainingOptions {
+    /// Leave out thinking blocks instead of prefixing them to replies.
+    pub drop_thinking: bool,
+    /// Leave out Cursor's context blocks and the tool-use steps of agent
+    /// turns (messages that ran commands or reported linter errors).
+    pub drop_noise: bool,
+}
+
+/// One message of an example.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrainingMessage {
+    /// `user` or `assistant`.
+    pub role: &'static str,
+    /// Message text.
+    pub content: String,
+}
+
+/// One JSONL record: a whole conversation.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainingExample {
+    /// Alternating messages, starting with the user.
+    pub messages: Vec<TrainingMessage>,
+}
+
+/// Build the example for a conversation.
+///
+/// Returns `None` when nothing is left to learn from: no user prompt
+/// followed by an assistant reply.
+#[must_use]
+pub fn training_example(conv: &Conversation, options: TrainingOptions) -> Option<TrainingExample> {
+    let mut messages: Vec<TrainingMessage> = Vec::new();
+    for bubble in &conv.bubbles {
+        let Some((role, content)) = message_content(bubble, options) else {
+            continue;
+        };
+        match messages.last_mut() {
+            Some(last) if last.role == role => {
+                last.content.push_str("\n\n");
+                last.content.push_str(&content);
+            }
+            // Replies before the first prompt have nothing to answer
+            None if role == "assistant" => {}
+            _ => messages.push(TrainingMessage { role, content }),
+        }
+    }
+
+    // An example must end on a reply for the model to learn from
+    if messages.last().is_some_and(|m| m.role == "user") {
+        messages.pop();
+    }
+    (!messages.is_empty()).then_some(TrainingExample { messages })
+}
+
+fn message_content(bubble: &Bubble, options: TrainingOptions) -> Option<(&'static str, String)> {
+    let role = match bubble.bubble_type {
+        BubbleType::User => "user",
+        BubbleType::Assistant => "assistant",
+        BubbleType::Unknown => return None,
+    };
+    let is_tool_step = bubble.is_agentic
+        && (!bubble.commands.is_empty()
+            || !bubble.tool_calls.is_empty()
+            || !bubble.diagnostics.is_empty());
+    if options.drop_noise && role == "assistant" && is_tool_step {
+        return None;
+    }
+
+    let text = if options.drop_noise {
+        strip_context_blocks(&bubble.text)
+    } else {
+        bubble.text.trim().to_string()
+    };
+    let thinking = bubble
+        .thinking
+        .as_ref()
+        .map(|t| t.text.trim())
+        .filter(|t| !options.drop_thinking && !t.is_empty());
+
+    let content = match thinking {
+        Some(thinking) if text.is_empty() => format!("<thinking>\n{thinking}\n</thinking>"),
+        Some(thinking) => format!("<thinking>\n{thinking}\n</thinking>\n\n{text}"),
+        None => text,
+    };
+    (!content.is_empty()).then_some((role, content))
+}
+
+/// Remove `<tag>...</tag>` blocks of [`CONTEXT_TAGS`] from a message.
+fn strip_context_blocks(text: &str) -> String {
+    let mut text = text.to_string();
+    for tag in CONTEXT_TAGS {
+        let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+        while let Some(start) = text.find(&open) {
+            let Some(end) = text[start..].find(&close) else {
+                break;
+            };
+            text.replace_range(start..start + end + close.len(), "");
+        }
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ModelConfig, TerminalCommand, ThinkingBlock};
+
+    fn bubble(bubble_type: BubbleType, text: &str) -> Bubble {
+        Bubble {
+            bubble_id: text.into(),
+            bubble_type,
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_training_example() {
+        let mut thought = bubble(BubbleType::Assistant, "Looking.");
+        thought.thinking = Some(ThinkingBlock {
+            text: "check the lock".into(),
+            signature: None,
+        });
+        let mut tool = bubble(BubbleType::Assistant, "Running tests");
+        tool.is_agentic = true;
+        tool.commands.push(TerminalCommand {
+            command: "cargo test".into(),
+            cwd: None,
+            output: None,
+            exit_code: Some(0),
+        });
+        let conv = Conversation {
+            composer_id: "c1".into(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![
+                bubble(BubbleType::Assistant, "Welcome"),
+                bubble(
+                    BubbleType::User,
+                    "<user_info>\nOS: linux\n</user_info>\nWhy does sync hang?",
+                ),
+                thought,
+                tool,
+                bubble(BubbleType::Assistant, "The lock was held."),
+                bubble(BubbleType::User, "Thanks"),
+            ],
+            git: None,
+            workspace: None,
+        };
+
+        let full = training_example(&conv, TrainingOptions::default()).unwrap();
+        assert_eq!(full.messages.len(), 2);
+        assert_eq!(full.messages[0].role, "user");
+        assert!(full.messages[0].content.starts_with("<user_info>"));
+        assert_eq!(
+            full.messages[1].content,
+            "<thinking>\ncheck the lock\n</thinking>\n\nLooking.\n\nRunning tests\n\nThe lock was held."
+        );
+
+        let clean = TrainingOptions {
+            drop_thinking: true,
+            drop_noise: true,
+        };
+        let messages = training_example(&conv, clean).unwrap().messages;
+        assert_eq!(messages[0].content, "Why does sync hang?");
+        assert_eq!(messages[1].content, "Looking.\n\nThe lock was held.");
+
+        let unanswered = Conversation {
+            bubbles: vec![bubble(BubbleType::User, "Hello?")],
+            ..conv
+        };
+        assert!(training_example(&unanswered, clean).is_none());
+    }
+}
@@ -2,10 +2,28 @@
 //!
 //! Supports multiple output formats: Markdown, JSON, and table view.
 
+use std::io::{self, Write};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use colored::Colorize;
-use comfy_table::{presets::UTF8_FULL, Table};
+use comfy_table::{presets::UTF8_FULL, Cell, Color, Table};
+use serde::Serialize;
+
+use crate::domain::{
+    truncate_chars, BubbleType, Conversation, Diagnostic, ExtractionStats, GitInfo, ShortIds,
+    TerminalCommand, TokenCount, ToolCall,
+};
+use crate::infrastructure::{MessageHit, QueryResult, StageTiming, TIMINGS_TARGET};
 
-use crate::domain::{BubbleType, Conversation, ExtractionStats};
+use super::bench::{BenchReport, PHASES};
+use super::correlate::{CorrelatedEvent, Correlation};
+use super::leaderboard::Leaderboard;
+use super::replay::Exchange;
+use super::timeline::Timeline;
+use super::todos::ActionItem;
+use super::usage::{ModeUsage, UsageRow};
+use super::work_sessions::SessionReport;
 
 /// Output format options.
 #[derive(Debug, Clone, Copy, Default)]
@@ -19,6 +37,18 @@ pub enum OutputFormat {
     Table,
 }
 
+impl OutputFormat {
+    /// File extension for a conversation exported in this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Table => "txt",
+        }
+    }
+}
+
 impl std::str::FromStr for OutputFormat {
     type Err = String;
 
@@ -32,41 +62,84 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+/// How `list` sections its table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListGrouping {
+    /// One section per local calendar day.
+    Day,
+}
+
+impl std::str::FromStr for ListGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            _ => Err(format!("Unknown grouping: {s}. Use: day")),
+        }
+    }
+}
+
 /// Formats a single conversation as Markdown.
-pub fn format_conversation_markdown(conv: &Conversation) -> String {
-    let mut out = String::new();
+#[must_use]
+pub fn format_conversation_markdown(conv: &Conversation, ids: &ShortIds) -> String {
+    let mut out = Vec::new();
+    // Writing into a Vec cannot fail
+    let _ = write_conversation_markdown(&mut out, conv, ids);
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Writes a single conversation as Markdown, one message at a time.
+///
+/// # Errors
+/// Returns error if writing fails.
+pub fn write_conversation_markdown(
+    w: &mut impl Write,
+    conv: &Conversation,
+    ids: &ShortIds,
+) -> io::Result<()> {
+    let _formatting = tracing::info_span!(target: TIMINGS_TARGET, "format", rows = 1).entered();
 
     // Header with title
     let title = if conv.title.is_empty() {
         conv.composer_id.clone()
     } else {
-        format!("{} ({})", conv.title, &conv.composer_id[..8])
+        format!("{} ({})", conv.title, ids.get(&conv.composer_id))
     };
-    out.push_str(&format!("# {title}\n\n"));
+    write!(w, "# {title}\n\n")?;
 
     if let Some(dt) = conv.created_at {
-        out.push_str(&format!(
-            "**Created:** {}\n",
-            dt.format("%Y-%m-%d %H:%M:%S UTC")
-        ));
+        writeln!(w, "**Created:** {}", dt.format("%Y-%m-%d %H:%M:%S UTC"))?;
     }
 
     if !conv.model_config.model_name.is_empty() {
-        out.push_str(&format!("**Model:** {}\n", conv.model_config.model_name));
+        writeln!(w, "**Model:** {}", conv.model_config.model_name)?;
     }
 
     if !conv.unified_mode.is_empty() {
-        out.push_str(&format!("**Mode:** {}\n", conv.unified_mode));
+        writeln!(w, "**Mode:** {}", conv.unified_mode)?;
     }
 
-    out.push_str(&format!(
+    if let Some(git) = &conv.git {
+        writeln!(
+            w,
+            "**Repository:** {}",
+            git.origin_url.as_deref().unwrap_or(&git.repo_root)
+        )?;
+        if let Some(branch) = git_branch(git) {
+            writeln!(w, "**Branch:** {branch}")?;
+        }
+    }
+
+    write!(
+        w,
         "**Messages:** {} ({} user, {} assistant)\n\n",
         conv.message_count(),
         conv.user_message_count(),
         conv.assistant_message_count()
-    ));
+    )?;
 
-    out.push_str("---\n\n");
+    w.write_all(b"---\n\n")?;
 
     // Messages
     for bubble in &conv.bubbles {
@@ -76,37 +149,244 @@ pub fn format_conversation_markdown(conv: &Conversation) -> String {
             BubbleType::Unknown => "❓ **Unknown**",
         };
 
-        out.push_str(&format!("### {role}\n\n"));
+        write!(w, "### {role}\n\n")?;
 
         if let Some(dt) = bubble.created_at {
-            out.push_str(&format!("*{}*\n\n", dt.format("%H:%M:%S")));
+            write!(w, "*{}*\n\n", dt.format("%H:%M:%S"))?;
         }
 
         // Thinking block (if present)
         if let Some(ref thinking) = bubble.thinking {
             if !thinking.text.is_empty() {
-                out.push_str("<details>\n<summary>💭 Thinking</summary>\n\n");
-                out.push_str(&thinking.text);
-                out.push_str("\n\n</details>\n\n");
+                w.write_all("<details>\n<summary>💭 Thinking</summary>\n\n".as_bytes())?;
+                w.write_all(thinking.text.as_bytes())?;
+                w.write_all(b"\n\n</details>\n\n")?;
             }
         }
 
+        if !bubble.context.is_empty() {
+            write!(w, "📎 *Context: {}*\n\n", bubble.context)?;
+        }
+
         // Message content
-        out.push_str(&bubble.text);
-        out.push_str("\n\n");
+        w.write_all(bubble.text.as_bytes())?;
+        w.write_all(b"\n\n")?;
+
+        for command in &bubble.commands {
+            write_terminal_command(w, command)?;
+        }
+
+        for call in &bubble.tool_calls {
+            write_tool_call(w, call)?;
+        }
+
+        if !bubble.diagnostics.is_empty() {
+            write_diagnostics(w, &bubble.diagnostics)?;
+        }
 
         // Token info
-        if bubble.token_count.input_tokens > 0 || bubble.token_count.output_tokens > 0 {
-            out.push_str(&format!(
-                "*Tokens: {} in / {} out*\n\n",
-                bubble.token_count.input_tokens, bubble.token_count.output_tokens
-            ));
+        let tokens = &bubble.token_count;
+        if !tokens.is_empty() {
+            write!(
+                w,
+                "*Tokens: {} in / {} out",
+                tokens.input_tokens, tokens.output_tokens
+            )?;
+            if tokens.reasoning_tokens > 0 {
+                write!(w, " / {} reasoning", tokens.reasoning_tokens)?;
+            }
+            w.write_all(b"*\n\n")?;
         }
 
-        out.push_str("---\n\n");
+        w.write_all(b"---\n\n")?;
+    }
+
+    Ok(())
+}
+
+/// Writes a terminal command and its output as a fenced shell block.
+fn write_terminal_command(w: &mut impl Write, command: &TerminalCommand) -> io::Result<()> {
+    w.write_all(b"```console\n$ ")?;
+    w.write_all(command.command.as_bytes())?;
+    w.write_all(b"\n")?;
+    if let Some(output) = command.output.as_deref().filter(|o| !o.trim().is_empty()) {
+        w.write_all(output.trim_end().as_bytes())?;
+        w.write_all(b"\n")?;
+    }
+    w.write_all(b"```\n")?;
+
+    let mut notes = Vec::new();
+    if let Some(cwd) = &command.cwd {
+        notes.push(format!("in `{cwd}`"));
+    }
+    if let Some(code) = command.exit_code {
+        notes.push(format!("exit code {code}"));
+    }
+    if !notes.is_empty() {
+        writeln!(w, "*{}*", notes.join(", "))?;
+    }
+    w.write_all(b"\n")
+}
+
+/// Writes an agent tool call: name, target and, for edits, the new content
+/// collapsed.
+fn write_tool_call(w: &mut impl Write, call: &ToolCall) -> io::Result<()> {
+    write!(w, "🔧 *{}*", call.name)?;
+    if let Some(file) = &call.file {
+        write!(w, " `{file}`")?;
+    }
+    if let Some(query) = &call.query {
+        write!(w, " “{}”", query.replace('\n', " "))?;
+    }
+    if let Some(description) = &call.description {
+        write!(w, " — {}", description.replace('\n', " "))?;
+    }
+    if let Some(status) = call.status.as_deref().filter(|s| *s != "completed") {
+        write!(w, " *({status})*")?;
+    }
+    w.write_all(b"\n\n")?;
+
+    if let Some(edit) = call.edit.as_deref().filter(|e| !e.trim().is_empty()) {
+        w.write_all("<details>\n<summary>✏️ Edit</summary>\n\n```\n".as_bytes())?;
+        w.write_all(edit.trim_end().as_bytes())?;
+        w.write_all(b"\n```\n\n</details>\n\n")?;
+    }
+    Ok(())
+}
+
+/// Writes diagnostics as a collapsed list.
+fn write_diagnostics(w: &mut impl Write, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    write!(
+        w,
+        "<details>\n<summary>⚠️ Diagnostics ({})</summary>\n\n",
+        diagnostics.len()
+    )?;
+    for diagnostic in diagnostics {
+        w.write_all(b"- ")?;
+        if let Some(severity) = &diagnostic.severity {
+            write!(w, "**{severity}** ")?;
+        }
+        match (&diagnostic.file, diagnostic.line) {
+            (Some(file), Some(line)) => write!(w, "`{file}:{line}`: ")?,
+            (Some(file), None) => write!(w, "`{file}`: ")?,
+            (None, _) => {}
+        }
+        writeln!(w, "{}", diagnostic.message.replace('\n', " "))?;
+    }
+    w.write_all(b"\n</details>\n\n")
+}
+
+/// Formats the terminal commands of a conversation as Markdown (`commands`).
+#[must_use]
+pub fn format_terminal_commands_markdown(conv: &Conversation) -> String {
+    let title = if conv.title.is_empty() {
+        truncate(conv.preview(), 60)
+    } else {
+        conv.title.clone()
+    };
+    let mut out = format!("# Commands: {title}\n\n*{}*\n\n", conv.composer_id).into_bytes();
+    for (i, command) in conv.terminal_commands().enumerate() {
+        // Writing to a Vec cannot fail
+        let _ = write!(out, "## {}\n\n", i + 1);
+        let _ = write_terminal_command(&mut out, command);
+    }
+    String::from_utf8_lossy(&out).trim_end().to_string()
+}
+
+/// Formats the terminal commands of a conversation as a shell script
+/// (`export-script`), each preceded by a comment with its directory and
+/// exit code.
+#[must_use]
+pub fn format_shell_script(conv: &Conversation) -> String {
+    let title = if conv.title.is_empty() {
+        conv.preview()
+    } else {
+        &conv.title
+    };
+    let mut lines = vec![
+        "#!/bin/sh".to_string(),
+        format!(
+            "# Terminal commands run by the agent in: {}",
+            title.lines().next().unwrap_or_default()
+        ),
+        format!("# Conversation: {}", conv.composer_id),
+    ];
+    if let Some(dt) = conv.created_at {
+        lines.push(format!("# Created: {}", dt.format("%Y-%m-%d %H:%M:%S UTC")));
+    }
+    lines.push("#".to_string());
+    lines.push("# Review before running: commands are replayed as they were run,".to_string());
+    lines.push("# from the directory this script is started in.".to_string());
+
+    for (i, command) in conv.terminal_commands().enumerate() {
+        let mut notes = Vec::new();
+        if let Some(cwd) = &command.cwd {
+            notes.push(format!("in {cwd}"));
+        }
+        match command.exit_code {
+            Some(0) | None => {}
+            Some(code) => notes.push(format!("failed with exit code {code}")),
+        }
+        lines.push(String::new());
+        if notes.is_empty() {
+            lines.push(format!("# {}", i + 1));
+        } else {
+            lines.push(format!("# {}. {}", i + 1, notes.join(", ")));
+        }
+        lines.push(command.command.trim().to_string());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Formats one exchange of a replay for the terminal.
+#[must_use]
+pub fn format_exchange(exchange: &Exchange<'_>, number: usize, total: usize) -> String {
+    let mut lines = Vec::new();
+    let time = exchange
+        .started_at()
+        .map(|at| {
+            at.with_timezone(&chrono::Local)
+                .format(" %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    lines.push(format!("── {number}/{total}{time} ──").dimmed().to_string());
+
+    for bubble in exchange.prompt.iter().chain(&exchange.replies) {
+        let role = match bubble.bubble_type {
+            BubbleType::User => "👤 User".green().bold(),
+            BubbleType::Assistant => "🤖 Assistant".cyan().bold(),
+            BubbleType::Unknown => "❓ Unknown".normal(),
+        };
+        lines.push(role.to_string());
+        if let Some(ms) = bubble.thinking_duration_ms {
+            lines.push(
+                format!(
+                    "💭 thought for {:.1}s",
+                    Duration::from_millis(ms).as_secs_f64()
+                )
+                .dimmed()
+                .to_string(),
+            );
+        }
+        if !bubble.context.is_empty() {
+            lines.push(format!("📎 {}", bubble.context).dimmed().to_string());
+        }
+        if !bubble.text.trim().is_empty() {
+            lines.push(bubble.text.trim_end().to_string());
+        }
+        for command in &bubble.commands {
+            lines.push(format!("$ {}", command.command).yellow().to_string());
+        }
+        for call in &bubble.tool_calls {
+            let target = call.file.as_deref().or(call.query.as_deref()).unwrap_or("");
+            lines.push(format!("🔧 {} {target}", call.name).dimmed().to_string());
+        }
+        lines.push(String::new());
     }
 
-    out
+    lines.join("\n")
 }
 
 /// Formats multiple conversations as JSON.
@@ -119,11 +399,221 @@ pub fn format_conversations_json(
     serde_json::to_string_pretty(conversations)
 }
 
+/// Formats full-text search hits: conversation, time and title, then the
+/// snippet with matched terms highlighted.
+#[must_use]
+pub fn format_message_hits(hits: &[MessageHit], ids: &ShortIds) -> String {
+    let mut lines = Vec::with_capacity(hits.len() * 3);
+    for hit in hits {
+        let when = hit
+            .created_at
+            .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        lines.push(format!(
+            "  {}  {}  {}  {}",
+            ids.get(&hit.composer_id).cyan(),
+            when.dimmed(),
+            hit.bubble_type.to_string().dimmed(),
+            hit.title.bold()
+        ));
+        lines.push(format!("    {}", highlight_matches(&hit.snippet)));
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+/// Snippet on one line, with the `**`-wrapped matches highlighted.
+fn highlight_matches(snippet: &str) -> String {
+    snippet
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split("**")
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                part.yellow().bold().to_string()
+            } else {
+                part.to_string()
+            }
+        })
+        .collect()
+}
+
+/// One conversation of `quick --machine`.
+#[derive(Serialize)]
+struct QuickEntry<'a> {
+    index: usize,
+    id: &'a str,
+    title: &'a str,
+    workspace: Option<&'a str>,
+    last_activity: Option<DateTime<Utc>>,
+    message_count: usize,
+}
+
+/// Formats the quick menu as a single-line JSON array for scripts and
+/// agents; `index` is the number `open` accepts.
+///
+/// # Errors
+/// Returns error if serialization fails.
+pub fn format_quick_machine(conversations: &[Conversation]) -> Result<String, serde_json::Error> {
+    let entries: Vec<QuickEntry<'_>> = conversations
+        .iter()
+        .enumerate()
+        .map(|(i, conv)| QuickEntry {
+            index: i + 1,
+            id: &conv.composer_id,
+            title: &conv.title,
+            workspace: conv.workspace.as_deref(),
+            last_activity: conv.last_activity(),
+            message_count: conv.message_count(),
+        })
+        .collect();
+    serde_json::to_string(&entries)
+}
+
+/// Writes conversations in `format`, streaming Markdown and JSON instead of
+/// building the whole export in memory.
+///
+/// Markdown conversations are separated by a blank line.
+///
+/// # Errors
+/// Returns error if writing or serialization fails.
+pub fn write_conversations(
+    w: &mut impl Write,
+    conversations: &[Conversation],
+    format: OutputFormat,
+    ids: &ShortIds,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Markdown => {
+            for conv in conversations {
+                write_conversation_markdown(w, conv, ids)?;
+                w.write_all(b"\n\n")?;
+            }
+        }
+        OutputFormat::Json => {
+            let mut array = JsonArrayWriter::new(&mut *w)?;
+            for conv in conversations {
+                array.push(conv)?;
+            }
+            array.finish()?;
+        }
+        OutputFormat::Table => {
+            w.write_all(format_conversations_table(conversations, ids).as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Streams a JSON array one element at a time.
+///
+/// The output is identical to `serde_json::to_string_pretty` of the whole
+/// array, but only one element is serialized at a time, so memory stays flat
+/// however many elements are written.
+pub struct JsonArrayWriter<W: Write> {
+    writer: W,
+    count: usize,
+}
+
+impl<W: Write> JsonArrayWriter<W> {
+    /// Open the array.
+    ///
+    /// # Errors
+    /// Returns error if writing fails.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(b"[")?;
+        Ok(Self { writer, count: 0 })
+    }
+
+    /// Append one element.
+    ///
+    /// # Errors
+    /// Returns error if writing or serialization fails.
+    pub fn push<T: Serialize>(&mut self, item: &T) -> io::Result<()> {
+        let _formatting = tracing::info_span!(target: TIMINGS_TARGET, "format", rows = 1).entered();
+        let separator: &[u8] = if self.count == 0 { b"\n  " } else { b",\n  " };
+        self.writer.write_all(separator)?;
+        serde_json::to_writer_pretty(Indented(&mut self.writer), item)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Close the array and return the number of elements written.
+    ///
+    /// # Errors
+    /// Returns error if writing fails.
+    pub fn finish(mut self) -> io::Result<usize> {
+        let end: &[u8] = if self.count == 0 { b"]" } else { b"\n]" };
+        self.writer.write_all(end)?;
+        Ok(self.count)
+    }
+}
+
+/// Indents every line after the first by one level (two spaces).
+///
+/// Pretty JSON never has raw newlines inside strings, so every newline
+/// starts a new line of structure.
+struct Indented<W: Write>(W);
+
+impl<W: Write> Write for Indented<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut lines = buf.split(|&b| b == b'\n');
+        if let Some(first) = lines.next() {
+            self.0.write_all(first)?;
+        }
+        for line in lines {
+            self.0.write_all(b"\n  ")?;
+            self.0.write_all(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Writes one JSON object per line.
+///
+/// # Errors
+/// Returns error if writing or serialization fails.
+pub fn write_jsonl<T: Serialize>(
+    w: &mut impl Write,
+    items: impl IntoIterator<Item = T>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    for item in items {
+        serde_json::to_writer(&mut *w, &item)?;
+        w.write_all(b"\n")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
 /// Formats a table listing of conversations.
-pub fn format_conversations_table(conversations: &[Conversation]) -> String {
+pub fn format_conversations_table(conversations: &[Conversation], ids: &ShortIds) -> String {
+    let _formatting =
+        tracing::info_span!(target: TIMINGS_TARGET, "format", rows = conversations.len()).entered();
+
+    // The workspace and branch columns only appear once sync has recorded them
+    let with_workspace = conversations.iter().any(|c| c.workspace.is_some());
+    let with_git = conversations.iter().any(|c| c.git.is_some());
+
     let mut table = Table::new();
     table.load_preset(UTF8_FULL);
-    table.set_header(vec!["ID", "Created", "Model", "Msgs", "Title"]);
+    let mut header = vec!["ID", "Created"];
+    if with_workspace {
+        header.push("Workspace");
+    }
+    header.extend(["Model", "Msgs"]);
+    if with_git {
+        header.push("Branch");
+    }
+    header.push("Title");
+    table.set_header(header);
 
     for conv in conversations {
         let created = conv.created_at.map_or_else(
@@ -132,9 +622,10 @@ pub fn format_conversations_table(conversations: &[Conversation]) -> String {
         );
 
         let model = if conv.model_config.model_name.is_empty() {
-            "-".to_string()
+            Cell::new("-")
         } else {
-            truncate(&conv.model_config.model_name, 18)
+            Cell::new(truncate(&conv.model_config.model_name, 18))
+                .fg(model_color(&conv.model_config.model_name))
         };
 
         let title = if conv.title.is_empty() {
@@ -143,44 +634,680 @@ pub fn format_conversations_table(conversations: &[Conversation]) -> String {
             truncate(&conv.title, 35)
         };
 
-        table.add_row(vec![
-            &conv.composer_id[..8],
-            &created,
-            &model,
-            &conv.message_count().to_string(),
-            &title,
-        ]);
+        let mut row = vec![Cell::new(ids.get(&conv.composer_id)), Cell::new(created)];
+        if with_workspace {
+            row.push(Cell::new(
+                conv.workspace
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), |w| truncate(w, 20)),
+            ));
+        }
+        row.extend([model, Cell::new(conv.message_count())]);
+        if with_git {
+            let branch = conv.git.as_ref().and_then(|git| {
+                git.branch
+                    .as_deref()
+                    .or_else(|| git.head_commit.as_deref().map(|c| truncate_chars(c, 7)))
+            });
+            row.push(Cell::new(
+                branch.map_or_else(|| "-".to_string(), |b| truncate(b, 20)),
+            ));
+        }
+        row.push(Cell::new(title));
+        table.add_row(row);
     }
 
     table.to_string()
 }
 
+/// Tables of conversations under a header per day, like Cursor's sidebar.
+///
+/// Conversations are expected newest first, as extracted.
+#[must_use]
+pub fn format_conversations_by_day(conversations: &[Conversation], ids: &ShortIds) -> String {
+    let today = chrono::Local::now().date_naive();
+    conversations
+        .chunk_by(|a, b| day_label(a.created_at, today) == day_label(b.created_at, today))
+        .map(|group| {
+            format!(
+                "{}\n{}",
+                day_label(group[0].created_at, today).bold(),
+                format_conversations_table(group, ids)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// "Today", "Yesterday" or the local date of `at`; "Undated" without one.
+fn day_label(at: Option<DateTime<Utc>>, today: NaiveDate) -> String {
+    let Some(day) = at.map(|at| at.with_timezone(&chrono::Local).date_naive()) else {
+        return "Undated".to_string();
+    };
+    if day == today {
+        "Today".to_string()
+    } else if today.pred_opt() == Some(day) {
+        "Yesterday".to_string()
+    } else {
+        day.to_string()
+    }
+}
+
+/// Color of a model in tables, the same for a model on every run.
+fn model_color(model: &str) -> Color {
+    const PALETTE: [Color; 6] = [
+        Color::Cyan,
+        Color::Green,
+        Color::Yellow,
+        Color::Magenta,
+        Color::Blue,
+        Color::Red,
+    ];
+    // FNV-1a: unlike the std hasher, stable across processes
+    let hash = model.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    PALETTE[usize::try_from(hash % PALETTE.len() as u64).unwrap_or_default()]
+}
+
+/// Branch name with the short commit, or just the commit on a detached HEAD.
+fn git_branch(git: &GitInfo) -> Option<String> {
+    let commit = git.head_commit.as_deref().map(|c| truncate_chars(c, 7));
+    match (&git.branch, commit) {
+        (Some(branch), Some(commit)) => Some(format!("{branch} @ {commit}")),
+        (Some(branch), None) => Some(branch.clone()),
+        (None, commit) => commit.map(String::from),
+    }
+}
+
 /// Formats extraction statistics for display.
-pub fn format_stats(stats: &ExtractionStats) -> String {
+#[must_use]
+pub fn format_stats(stats: &ExtractionStats, tokens: &TokenCount) -> String {
     format!(
-        "{}\n  Conversations: {}\n  Total messages: {}\n  User messages: {}\n  Assistant messages: {}\n  Databases scanned: {}",
+        "{}\n  Conversations: {}\n  Total messages: {}\n  User messages: {}\n  Assistant messages: {}\n  Databases scanned: {}\n  Tokens: {} in / {} out / {} reasoning",
         "📊 Statistics".bold(),
         stats.conversation_count.to_string().cyan(),
         stats.total_bubbles.to_string().cyan(),
         stats.user_messages.to_string().green(),
         stats.assistant_messages.to_string().blue(),
-        stats.databases_scanned.to_string().yellow()
+        stats.databases_scanned.to_string().yellow(),
+        tokens.input_tokens.to_string().cyan(),
+        tokens.output_tokens.to_string().cyan(),
+        tokens.reasoning_tokens.to_string().magenta()
+    )
+}
+
+/// Formats the `stats top` rankings as a compact ranked view.
+#[must_use]
+pub fn format_leaderboard(board: &Leaderboard, ids: &ShortIds) -> String {
+    let mut lines = vec!["🏆 Leaderboard".bold().to_string()];
+
+    lines.push(format!("\n{}", "Longest conversations".cyan()));
+    for (i, conv) in board.longest.iter().enumerate() {
+        lines.push(format!(
+            "  {}. {:>5} msgs  {}  {}",
+            i + 1,
+            conv.value,
+            ids.get(&conv.composer_id),
+            truncate(&conv.title, 45)
+        ));
+    }
+
+    lines.push(format!("\n{}", "Top token consumers".cyan()));
+    if board.token_consumers.is_empty() {
+        lines.push("  (no token counts recorded)".to_string());
+    }
+    for (i, conv) in board.token_consumers.iter().enumerate() {
+        lines.push(format!(
+            "  {}. {:>9} tok  {}  {}",
+            i + 1,
+            conv.value,
+            ids.get(&conv.composer_id),
+            truncate(&conv.title, 45)
+        ));
+    }
+
+    lines.push(format!("\n{}", "Most active days".cyan()));
+    for (i, day) in board.busiest_days.iter().enumerate() {
+        lines.push(format!(
+            "  {}. {}  {:>5} msgs in {} chats",
+            i + 1,
+            day.date,
+            day.messages,
+            day.conversations
+        ));
+    }
+
+    lines.push(format!("\n{}", "Most used models".cyan()));
+    for (i, model) in board.top_models.iter().enumerate() {
+        lines.push(format!(
+            "  {}. {:<24} {:>4} chats  {:>6} msgs",
+            i + 1,
+            truncate(&model.model, 24),
+            model.conversations,
+            model.messages
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Formats benchmark reports side by side, one column per dataset.
+#[must_use]
+pub fn format_bench(reports: &[BenchReport]) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    let mut header = vec!["Phase".to_string()];
+    header.extend(reports.iter().map(|r| r.dataset.clone()));
+    table.set_header(header);
+
+    for phase in PHASES {
+        let mut row = vec![phase.to_string()];
+        row.extend(reports.iter().map(|report| {
+            report.phase(phase).map_or_else(
+                || "-".to_string(),
+                |t| {
+                    format!(
+                        "{:>9.1} ms  {} {}",
+                        t.elapsed.as_secs_f64() * 1000.0,
+                        t.items,
+                        t.unit
+                    )
+                },
+            )
+        }));
+        table.add_row(row);
+    }
+
+    let sources: Vec<String> = reports
+        .iter()
+        .map(|r| format!("  {}: {}", r.dataset, r.database.display()))
+        .collect();
+    format!("{table}\n{}", sources.join("\n").dimmed())
+}
+
+/// Formats `--timings` output: one row per stage, with a total.
+#[must_use]
+pub fn format_timings(stages: &[StageTiming]) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Stage", "Calls", "Time", "Rows"]);
+
+    for stage in stages {
+        let unit = match stage.stage {
+            "write" => "bytes",
+            "format" => "conversations",
+            "sort" => "items",
+            _ => "rows",
+        };
+        table.add_row(vec![
+            stage.stage.to_string(),
+            stage.calls.to_string(),
+            format!("{:.1} ms", stage.elapsed.as_secs_f64() * 1000.0),
+            format!("{} {unit}", stage.rows),
+        ]);
+    }
+
+    let total: f64 = stages.iter().map(|s| s.elapsed.as_secs_f64()).sum();
+    format!(
+        "{}\n{table}\n{}",
+        "⏱  Timings".bold(),
+        format!("Total in timed stages: {:.1} ms", total * 1000.0).dimmed()
     )
 }
 
+/// Formats `query` results as a table; long values are cut to one line.
+#[must_use]
+pub fn format_query_table(result: &QueryResult) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(result.columns.clone());
+    for row in &result.rows {
+        table.add_row(row.iter().map(|v| truncate(&query_cell(v), 60)));
+    }
+
+    format!(
+        "{table}\n{}",
+        format!("{} row(s)", result.rows.len()).dimmed()
+    )
+}
+
+/// Writes `query` results as RFC 4180 CSV with a header row.
+///
+/// # Errors
+/// Returns error if writing fails.
+pub fn write_query_csv(writer: &mut impl Write, result: &QueryResult) -> io::Result<()> {
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .map(|cell| csv_field(cell))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    writeln!(writer, "{}", line(result.columns.clone()))?;
+    for row in &result.rows {
+        writeln!(writer, "{}", line(row.iter().map(query_cell).collect()))?;
+    }
+    Ok(())
+}
+
+/// Writes usage rows as CSV: `group,key,conversations,messages` and the
+/// token counts, one row per day, model and workspace.
+///
+/// # Errors
+/// Returns error if writing fails.
+pub fn write_usage_csv(writer: &mut impl Write, rows: &[UsageRow]) -> io::Result<()> {
+    writeln!(
+        writer,
+        "group,key,conversations,messages,input_tokens,output_tokens,reasoning_tokens"
+    )?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{}",
+            row.group.as_str(),
+            csv_field(&row.key),
+            row.conversations,
+            row.messages,
+            row.tokens.input_tokens,
+            row.tokens.output_tokens,
+            row.tokens.reasoning_tokens
+        )?;
+    }
+    Ok(())
+}
+
+fn query_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Formats action items as a Markdown task list grouped by conversation.
+#[must_use]
+pub fn format_action_items_markdown(
+    conversations: &[Conversation],
+    items: &[ActionItem],
+) -> String {
+    let mut lines = vec!["# Action Items".to_string()];
+
+    for conv in conversations {
+        let conv_items: Vec<_> = items
+            .iter()
+            .filter(|i| i.composer_id == conv.composer_id)
+            .collect();
+        if conv_items.is_empty() {
+            continue;
+        }
+
+        let title = if conv.title.is_empty() {
+            truncate(conv.preview(), 60)
+        } else {
+            conv.title.clone()
+        };
+        lines.push(format!("\n## {title}\n\n*{}*\n", conv.composer_id));
+
+        for item in conv_items {
+            lines.push(format!(
+                "- [ ] {} _({}, message #{}, bubble {})_",
+                item.text,
+                item.kind.label(),
+                item.message_index,
+                &item.bubble_id[..8.min(item.bubble_id.len())]
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
 /// Truncates a string to max length with ellipsis.
+/// Format a timeline grouped by local day.
+#[must_use]
+pub fn format_timeline(timeline: &Timeline, ids: &ShortIds) -> String {
+    let mut lines = vec!["🕒 Timeline".bold().to_string()];
+    let mut current_day = None;
+
+    for session in &timeline.sessions {
+        let start = session.start.with_timezone(&chrono::Local);
+        let end = session.end.with_timezone(&chrono::Local);
+
+        if current_day != Some(start.date_naive()) {
+            current_day = Some(start.date_naive());
+            lines.push(format!(
+                "\n{}",
+                start.format("%a %Y-%m-%d").to_string().cyan()
+            ));
+        }
+
+        let minutes = (session.end - session.start).num_minutes();
+        let span = if end.date_naive() == start.date_naive() {
+            format!("{}–{}", start.format("%H:%M"), end.format("%H:%M"))
+        } else {
+            format!("{}–{}", start.format("%H:%M"), end.format("%m-%d %H:%M"))
+        };
+        let workspace = session
+            .workspace
+            .as_deref()
+            .map(|w| format!("[{w}] ").blue().to_string())
+            .unwrap_or_default();
+
+        lines.push(format!(
+            "  {span:<17} {:>6}  {}  {workspace}{} ({} msgs)",
+            format!("{}h{:02}", minutes / 60, minutes % 60),
+            ids.get(&session.composer_id),
+            truncate(&session.title, 45),
+            session.messages
+        ));
+
+        if !session.overlaps.is_empty() {
+            let ids: Vec<&str> = session.overlaps.iter().map(|id| ids.get(id)).collect();
+            lines.push(
+                format!("  {:<25} ⇄ overlaps {}", "", ids.join(", "))
+                    .dimmed()
+                    .to_string(),
+            );
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Formats work time per week and workspace (`stats sessions`).
+#[must_use]
+pub fn format_session_report(report: &SessionReport) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Week", "Workspace", "Sessions", "Hours"]);
+
+    for week in &report.weeks {
+        table.add_row(vec![
+            week.week_start.format("%G-W%V").to_string(),
+            truncate(&week.workspace, 30),
+            week.sessions.to_string(),
+            format_minutes(week.minutes),
+        ]);
+    }
+
+    let total: u32 = report.weeks.iter().map(|w| w.minutes).sum();
+    format!(
+        "{table}\n{} sessions, {} of AI-assisted work (a pause over {} min ends a session)",
+        report.sessions.len(),
+        format_minutes(total),
+        report.gap_minutes
+    )
+}
+
+/// Formats usage per conversation mode (`stats modes`).
+#[must_use]
+pub fn format_mode_usage(modes: &[ModeUsage]) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec![
+        "Mode",
+        "Chats",
+        "Messages",
+        "Agentic msgs",
+        "Share",
+        "Tokens",
+    ]);
+
+    for mode in modes {
+        table.add_row(vec![
+            mode.mode.clone(),
+            mode.conversations.to_string(),
+            mode.messages.to_string(),
+            mode.agentic_messages.to_string(),
+            format!("{:.1}%", mode.message_share),
+            mode.tokens.total().to_string(),
+        ]);
+    }
+
+    let messages: usize = modes.iter().map(|m| m.messages).sum();
+    let agentic: usize = modes.iter().map(|m| m.agentic_messages).sum();
+    format!(
+        "{table}
+{agentic} of {messages} messages were agentic"
+    )
+}
+
+fn format_minutes(minutes: u32) -> String {
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+/// Formats a conversation interleaved with its commits (`correlate`).
+#[must_use]
+pub fn format_correlation(correlation: &Correlation, ids: &ShortIds) -> String {
+    let mut lines = vec![format!(
+        "{} {} ({})\n{}",
+        "🔗".bold(),
+        truncate(&correlation.title, 60).bold(),
+        ids.get(&correlation.composer_id),
+        format!("   {}", correlation.repo_root).dimmed()
+    )];
+    let mut current_day = None;
+
+    for event in &correlation.events {
+        let at = event.at().with_timezone(&chrono::Local);
+        if current_day != Some(at.date_naive()) {
+            current_day = Some(at.date_naive());
+            lines.push(format!("\n{}", at.format("%a %Y-%m-%d").to_string().cyan()));
+        }
+
+        let time = at.format("%H:%M");
+        lines.push(match event {
+            CorrelatedEvent::Message { role, preview, .. } => {
+                let icon = if *role == "user" { "👤" } else { "🤖" };
+                format!("  {time}  {icon} {}", truncate(preview, 70))
+            }
+            CorrelatedEvent::Commit(commit) => format!(
+                "  {time}  {} {} {} {}",
+                "●".green(),
+                truncate_chars(&commit.hash, 7).yellow(),
+                truncate(&commit.subject, 55),
+                format!("({})", commit.author).dimmed()
+            ),
+        });
+    }
+
+    lines.push(format!(
+        "\n{} commit(s) between {} and {}",
+        correlation.commit_count(),
+        correlation
+            .start
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M"),
+        correlation
+            .end
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+    ));
+
+    lines.join("\n")
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     let s = s.lines().next().unwrap_or(s);
-    if s.len() <= max_len {
+    if s.chars().count() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len - 3])
+        format!("{}...", truncate_chars(s, max_len.saturating_sub(3)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_query_csv_quotes_fields() {
+        let result = QueryResult {
+            columns: vec!["id".into(), "text".into()],
+            rows: vec![vec![serde_json::json!(1), serde_json::json!("a, \"b\"")]],
+        };
+        let mut out = Vec::new();
+        write_query_csv(&mut out, &result).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,text\n1,\"a, \"\"b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_json_array_writer_matches_pretty() {
+        let items = [
+            serde_json::json!({"id": "a", "bubbles": [{"text": "line\nbreak"}], "empty": []}),
+            serde_json::json!({"id": "b", "nested": {"n": 1}}),
+        ];
+
+        for slice in [&items[..], &items[..1], &[]] {
+            let mut out = Vec::new();
+            let mut array = JsonArrayWriter::new(&mut out).unwrap();
+            for item in slice {
+                array.push(item).unwrap();
+            }
+            assert_eq!(array.finish().unwrap(), slice.len());
+
+            let expected = serde_json::to_string_pretty(slice).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_conversations_table_workspace_column() {
+        let conversation = |id: &str, workspace: Option<&str>| Conversation {
+            composer_id: id.to_string(),
+            title: format!("Chat {id}"),
+            created_at: None,
+            model_config: crate::domain::ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: workspace.map(String::from),
+        };
+
+        let without = format_conversations_table(&[conversation("a", None)], &ShortIds::default());
+        assert!(!without.contains("Workspace"));
+
+        let table = format_conversations_table(
+            &[conversation("a", Some("api")), conversation("b", None)],
+            &ShortIds::default(),
+        );
+        assert!(table.contains("Workspace"));
+        assert!(table.contains("api"));
+
+        assert_eq!(model_color("gpt-4o"), model_color("gpt-4o"));
+    }
+
+    #[test]
+    fn test_format_shell_script() {
+        let command = |command: &str, exit_code| TerminalCommand {
+            command: command.into(),
+            cwd: None,
+            output: None,
+            exit_code,
+        };
+        let conv = Conversation {
+            composer_id: "c1".into(),
+            title: "Setup".into(),
+            created_at: None,
+            model_config: crate::domain::ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![crate::domain::Bubble {
+                bubble_id: "b1".into(),
+                bubble_type: BubbleType::Assistant,
+                is_agentic: true,
+                commands: vec![
+                    command("npm install", Some(0)),
+                    command("npm test", Some(1)),
+                ],
+                ..Default::default()
+            }],
+            git: None,
+            workspace: None,
+        };
+
+        let script = format_shell_script(&conv);
+        assert!(script.starts_with("#!/bin/sh\n# Terminal commands run by the agent in: Setup\n"));
+        assert!(script.ends_with("\n# 1\nnpm install\n\n# 2. failed with exit code 1\nnpm test\n"));
+    }
+
+    #[test]
+    fn test_write_tool_call() {
+        let call = ToolCall {
+            name: "edit_file".into(),
+            kind: crate::domain::ToolKind::Edit,
+            file: Some("src/lib.rs".into()),
+            query: None,
+            description: Some("Add the module".into()),
+            edit: Some("pub mod cli;\n".into()),
+            status: Some("completed".into()),
+        };
+        let mut out = Vec::new();
+        write_tool_call(&mut out, &call).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("🔧 *edit_file* `src/lib.rs` — Add the module\n\n"));
+        assert!(out.contains("```\npub mod cli;\n```"));
+
+        let failed = ToolCall {
+            edit: None,
+            status: Some("error".into()),
+            ..call
+        };
+        let mut out = Vec::new();
+        write_tool_call(&mut out, &failed).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("*(error)*"));
+    }
+
+    #[test]
+    fn test_format_quick_machine() {
+        let conv = Conversation {
+            composer_id: "c1".into(),
+            title: "Setup".into(),
+            created_at: Some(Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()),
+            model_config: crate::domain::ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: Some("api".into()),
+        };
+
+        assert_eq!(
+            format_quick_machine(&[conv]).unwrap(),
+            r#"[{"index":1,"id":"c1","title":"Setup","workspace":"api","last_activity":"2025-01-01T12:00:00Z","message_count":0}]"#
+        );
+    }
+
+    #[test]
+    fn test_day_label() {
+        let today = chrono::Local::now();
+        let label = |days: i64| {
+            day_label(
+                Some((today - chrono::Duration::days(days)).into()),
+                today.date_naive(),
+            )
+        };
+
+        assert_eq!(label(0), "Today");
+        assert_eq!(label(1), "Yesterday");
+        assert_eq!(
+            label(3),
+            (today - chrono::Duration::days(3)).date_naive().to_string()
+        );
+        assert_eq!(day_label(None, today.date_naive()), "Undated");
+    }
 
     #[test]
     fn test_truncate() {
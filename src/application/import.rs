@@ -0,0 +1,561 @@
+//! Import of exported conversations back into local storage.
+//!
+//! JSON exports hold whole conversations and import without loss, merged
+//! into stored ones by composer and bubble ID. Markdown exports keep only the
+//! title, model, mode and each message's role, time, thinking and text
+//! (commands, tool calls and diagnostics stay inside the text), and name a
+//! conversation by a short ID. A Markdown conversation whose short ID and
+//! title match a stored one is skipped; any other gets an ID derived from its
+//! content, so importing the same file again is a no-op.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{
+    AppConfig, AppError, Bubble, BubbleType, Conversation, ModelConfig, OperationKind, Result,
+    ThinkingBlock, TokenCount,
+};
+use crate::infrastructure::LocalStorage;
+
+use super::audit;
+use super::export_manifest::MANIFEST_FILE;
+use super::sync_service::SyncService;
+
+/// An export that could not be read or parsed.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFailure {
+    /// The export file.
+    pub path: PathBuf,
+    /// Why it was skipped.
+    pub error: String,
+}
+
+/// Result of an import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportResult {
+    /// Export files read.
+    pub files: usize,
+    /// Conversations new to local storage.
+    pub created: usize,
+    /// Stored conversations that gained messages or metadata.
+    pub updated: usize,
+    /// Conversations already stored with everything the export had.
+    pub unchanged: usize,
+    /// Messages added.
+    pub messages: usize,
+    /// Files skipped.
+    pub failed: Vec<ImportFailure>,
+}
+
+/// A conversation read from an export.
+struct Exported {
+    conversation: Conversation,
+    /// Short (or, if untitled, full) ID a Markdown export named the
+    /// conversation by.
+    short_id: Option<String>,
+}
+
+/// Import the JSON and Markdown exports at `path` (a file, or a directory
+/// searched recursively) into local storage.
+///
+/// Files that cannot be parsed are reported in the result and skipped.
+///
+/// # Errors
+/// Returns error if `path` holds no exports or local storage cannot be
+/// written.
+pub fn import_exports(config: &AppConfig, path: &Path) -> Result<ImportResult> {
+    audit::recorded(
+        config,
+        OperationKind::Import,
+        || run_import(config, path),
+        |result| {
+            Some((
+                result.created + result.updated,
+                format!(
+                    "{} new, {} updated, {} messages from {} files",
+                    result.created, result.updated, result.messages, result.files
+                ),
+            ))
+        },
+    )
+}
+
+fn run_import(config: &AppConfig, path: &Path) -> Result<ImportResult> {
+    let files = export_files(path)?;
+    if files.is_empty() {
+        return Err(AppError::InvalidData {
+            message: format!("No .json or .md exports in {}", path.display()),
+        });
+    }
+
+    let mut exported = Vec::new();
+    let mut failed = Vec::new();
+    for file in &files {
+        match read_export(file) {
+            Ok(parsed) => exported.extend(parsed),
+            Err(e) => failed.push(ImportFailure {
+                path: file.clone(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    let storage_path = config.storage_db_path();
+    let storage = if storage_path.exists() {
+        Some(LocalStorage::open_read_only(&storage_path)?)
+    } else {
+        None
+    };
+    let mut conversations = Vec::new();
+    let mut already_stored = 0;
+    for Exported {
+        conversation,
+        short_id,
+    } in exported
+    {
+        if let (Some(storage), Some(short_id)) = (&storage, short_id) {
+            if is_stored(storage, &short_id, &conversation.title)? {
+                already_stored += 1;
+                continue;
+            }
+        }
+        conversations.push(conversation);
+    }
+    drop(storage);
+
+    let mut result = SyncService::new(config.clone())?.import(&conversations)?;
+    result.files = files.len() - failed.len();
+    result.unchanged += already_stored;
+    result.failed = failed;
+    Ok(result)
+}
+
+/// Whether local storage holds a conversation named `title` whose ID starts
+/// with `short_id`.
+fn is_stored(storage: &LocalStorage, short_id: &str, title: &str) -> Result<bool> {
+    for id in storage.find_composer_ids(short_id)? {
+        if id.starts_with(short_id)
+            && storage
+                .get_conversation(&id)?
+                .is_some_and(|stored| stored.title == title)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The export files at `path`, sorted; manifests and links are skipped.
+fn export_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+    if !metadata.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| AppError::io(format!("Failed to read {}", dir.display()), e))?;
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let entry_path = entry.path();
+            if file_type.is_dir() {
+                dirs.push(entry_path);
+            } else if file_type.is_file()
+                && entry.file_name() != MANIFEST_FILE
+                && export_kind(&entry_path).is_some()
+            {
+                files.push(entry_path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `path` is a JSON (`true`) or Markdown (`false`) export.
+fn export_kind(path: &Path) -> Option<bool> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "json" => Some(true),
+        "md" | "markdown" => Some(false),
+        _ => None,
+    }
+}
+
+fn read_export(path: &Path) -> Result<Vec<Exported>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+    if export_kind(path) == Some(false) {
+        let exported = parse_markdown(&content);
+        if exported.is_empty() {
+            return Err(AppError::InvalidData {
+                message: "no exported conversation found".to_string(),
+            });
+        }
+        Ok(exported)
+    } else {
+        let conversations = parse_json_export(&content)?;
+        Ok(conversations
+            .into_iter()
+            .map(|conversation| Exported {
+                conversation,
+                short_id: None,
+            })
+            .collect())
+    }
+}
+
+/// Parse a JSON export: an array of conversations, or a single one.
+///
+/// # Errors
+/// Returns error if `content` is not an exported conversation.
+pub fn parse_json_export(content: &str) -> Result<Vec<Conversation>> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(AppError::json_parse)?;
+    let conversations: Vec<Conversation> = if value.is_array() {
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value(value).map(|conv| vec![conv])
+    }
+    .map_err(AppError::json_parse)?;
+
+    if conversations.iter().any(|c| c.composer_id.is_empty()) {
+        return Err(AppError::InvalidData {
+            message: "conversation without a composer ID".to_string(),
+        });
+    }
+    Ok(conversations)
+}
+
+/// Parse a Markdown export holding one or more conversations.
+#[must_use]
+pub fn parse_markdown_export(content: &str) -> Vec<Conversation> {
+    parse_markdown(content)
+        .into_iter()
+        .map(|exported| exported.conversation)
+        .collect()
+}
+
+fn parse_markdown(content: &str) -> Vec<Exported> {
+    let lines: Vec<&str> = content.lines().collect();
+    let starts: Vec<usize> = (0..lines.len())
+        .filter(|&i| is_conversation_start(&lines, i))
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let end = starts.get(n + 1).copied().unwrap_or(lines.len());
+            parse_markdown_conversation(&lines[start..end])
+        })
+        .collect()
+}
+
+/// A `# title` line followed by the `**Key:** value` header ending in
+/// `**Messages:**`.
+fn is_conversation_start(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("# ")
+        && lines.get(i + 1).is_some_and(|l| l.is_empty())
+        && lines[i + 2..]
+            .iter()
+            .take_while(|l| l.starts_with("**"))
+            .any(|l| l.starts_with("**Messages:**"))
+}
+
+fn parse_markdown_conversation(lines: &[&str]) -> Exported {
+    let heading = lines[0].trim_start_matches("# ").trim();
+    let (title, composer_id, short_id) =
+        match heading.strip_suffix(')').and_then(|h| h.rsplit_once(" (")) {
+            Some((title, short_id)) => (
+                title.to_string(),
+                derived_id(&lines.join("\n")),
+                Some(short_id.to_string()),
+            ),
+            // Untitled conversations are headed by their full ID
+            None => (
+                String::new(),
+                heading.to_string(),
+                Some(heading.to_string()),
+            ),
+        };
+
+    let mut conv = Conversation {
+        composer_id,
+        title,
+        created_at: None,
+        model_config: ModelConfig::default(),
+        unified_mode: String::new(),
+        bubbles: Vec::new(),
+        git: None,
+        workspace: None,
+    };
+
+    let header = lines[2..].iter().take_while(|l| l.starts_with("**"));
+    for line in header {
+        let Some((key, value)) = line.strip_prefix("**").and_then(|l| l.split_once(":** ")) else {
+            continue;
+        };
+        match key {
+            "Created" => {
+                conv.created_at = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S UTC")
+                    .ok()
+                    .map(|dt| dt.and_utc());
+            }
+            "Model" => conv.model_config.model_name = value.to_string(),
+            "Mode" => conv.unified_mode = value.to_string(),
+            _ => {}
+        }
+    }
+
+    let mut day = conv.created_at.map(|dt| dt.date_naive());
+    let mut last_time: Option<NaiveTime> = None;
+    let roles: Vec<(usize, BubbleType)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| role_of(line).map(|role| (i, role)))
+        .collect();
+    for (n, &(start, bubble_type)) in roles.iter().enumerate() {
+        let end = roles.get(n + 1).map_or(lines.len(), |&(i, _)| i);
+        let mut bubble = parse_markdown_message(&lines[start + 1..end], bubble_type);
+        bubble.bubble_id = derived_id(&format!("{}:{n}", conv.composer_id));
+
+        if let (Some(time), Some(date)) = (message_time(&lines[start + 1..end]), day.as_mut()) {
+            // Times are shown without a date; one going backwards is the next day
+            if last_time.is_some_and(|last| time < last) {
+                *date += Duration::days(1);
+            }
+            last_time = Some(time);
+            bubble.created_at = Some(DateTime::from_naive_utc_and_offset(
+                NaiveDateTime::new(*date, time),
+                Utc,
+            ));
+        }
+        conv.bubbles.push(bubble);
+    }
+
+    Exported {
+        conversation: conv,
+        short_id,
+    }
+}
+
+fn role_of(line: &str) -> Option<BubbleType> {
+    match line {
+        "### 👤 **User**" => Some(BubbleType::User),
+        "### 🤖 **Assistant**" => Some(BubbleType::Assistant),
+        "### ❓ **Unknown**" => Some(BubbleType::Unknown),
+        _ => None,
+    }
+}
+
+/// The `*HH:MM:SS*` line opening a message, if any.
+fn message_time(lines: &[&str]) -> Option<NaiveTime> {
+    let line = lines.iter().find(|l| !l.is_empty())?;
+    let time = line.strip_prefix('*')?.strip_suffix('*')?;
+    NaiveTime::parse_from_str(time, "%H:%M:%S").ok()
+}
+
+fn parse_markdown_message(lines: &[&str], bubble_type: BubbleType) -> Bubble {
+    let mut body = lines;
+    let trim_blank = |body: &mut &[&str]| {
+        while let Some((first, rest)) = body.split_first() {
+            if !first.is_empty() {
+                break;
+            }
+            *body = rest;
+        }
+        while let Some((last, rest)) = body.split_last() {
+            if !last.is_empty() {
+                break;
+            }
+            *body = rest;
+        }
+    };
+
+    trim_blank(&mut body);
+    if let Some((last, rest)) = body.split_last() {
+        if *last == "---" {
+            body = rest;
+            trim_blank(&mut body);
+        }
+    }
+    if message_time(body).is_some() {
+        body = &body[1..];
+        trim_blank(&mut body);
+    }
+
+    let mut thinking = None;
+    if body.starts_with(&["<details>", "<summary>💭 Thinking</summary>"]) {
+        if let Some(close) = body.iter().position(|l| *l == "</details>") {
+            let text = body[2..close].join("\n").trim().to_string();
+            thinking = Some(ThinkingBlock {
+                text,
+                signature: None,
+            });
+            body = &body[close + 1..];
+            trim_blank(&mut body);
+        }
+    }
+
+    if body.first().is_some_and(|l| l.starts_with("📎 *Context: ")) {
+        body = &body[1..];
+        trim_blank(&mut body);
+    }
+
+    let mut token_count = TokenCount::default();
+    if let Some(tokens) = body.last().and_then(|l| parse_tokens(l)) {
+        token_count = tokens;
+        body = &body[..body.len() - 1];
+        trim_blank(&mut body);
+    }
+
+    Bubble {
+        bubble_type,
+        text: body.join("\n"),
+        thinking,
+        token_count,
+        ..Default::default()
+    }
+}
+
+/// Parse `*Tokens: 10 in / 20 out[ / 5 reasoning]*`.
+fn parse_tokens(line: &str) -> Option<TokenCount> {
+    let counts = line.strip_prefix("*Tokens: ")?.strip_suffix('*')?;
+    let mut tokens = TokenCount::default();
+    for part in counts.split(" / ") {
+        let (count, kind) = part.split_once(' ')?;
+        let count = count.parse().ok()?;
+        match kind {
+            "in" => tokens.input_tokens = count,
+            "out" => tokens.output_tokens = count,
+            "reasoning" => tokens.reasoning_tokens = count,
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// A UUID-shaped ID derived from `seed`.
+pub(crate) fn derived_id(seed: &str) -> String {
+    let hex = blake3::hash(seed.as_bytes()).to_hex();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::formatter::format_conversation_markdown;
+    use crate::domain::ShortIds;
+
+    fn bubble(id: &str, bubble_type: BubbleType, text: &str, secs: i64) -> Bubble {
+        Bubble {
+            bubble_id: id.to_string(),
+            bubble_type,
+            text: text.to_string(),
+            created_at: DateTime::from_timestamp(1_700_000_000 + secs, 0),
+            ..Default::default()
+        }
+    }
+
+    fn conversation(id: &str, title: &str) -> Conversation {
+        let mut answer = bubble(
+            "b2",
+            BubbleType::Assistant,
+            "Done.\n\n---\n\nReally.",
+            10_800,
+        );
+        answer.thinking = Some(ThinkingBlock {
+            text: "Let me check".to_string(),
+            signature: None,
+        });
+        answer.token_count = TokenCount {
+            input_tokens: 10,
+            output_tokens: 20,
+            reasoning_tokens: 5,
+        };
+        Conversation {
+            composer_id: id.to_string(),
+            title: title.to_string(),
+            created_at: DateTime::from_timestamp(1_700_000_000, 0),
+            model_config: ModelConfig {
+                model_name: "gpt-4".to_string(),
+                max_mode: false,
+            },
+            unified_mode: "agent".to_string(),
+            bubbles: vec![bubble("b1", BubbleType::User, "Fix it", 0), answer],
+            git: None,
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_json_export() {
+        let conv = conversation("abc-123", "Fix");
+        let array = serde_json::to_string(&[&conv]).unwrap();
+        let single = serde_json::to_string(&conv).unwrap();
+
+        for content in [array, single] {
+            let parsed = parse_json_export(&content).unwrap();
+            assert_eq!(parsed.len(), 1);
+            assert_eq!(parsed[0].composer_id, "abc-123");
+            assert_eq!(parsed[0].bubbles.len(), 2);
+        }
+        assert!(parse_json_export("{\"files\": {}}").is_err());
+    }
+
+    #[test]
+    fn test_parse_markdown_export() {
+        let ids = ShortIds::default();
+        let titled = conversation("abc-123", "Fix the build");
+        let untitled = conversation("def-456", "");
+        let content = format!(
+            "{}\n\n{}",
+            format_conversation_markdown(&titled, &ids),
+            format_conversation_markdown(&untitled, &ids)
+        );
+
+        let parsed = parse_markdown_export(&content);
+        assert_eq!(parsed.len(), 2);
+
+        let conv = &parsed[0];
+        assert_eq!(conv.title, "Fix the build");
+        assert_ne!(conv.composer_id, "abc-123");
+        assert_eq!(conv.created_at, titled.created_at);
+        assert_eq!(conv.model_config.model_name, "gpt-4");
+        assert_eq!(conv.unified_mode, "agent");
+        assert_eq!(conv.bubbles.len(), 2);
+
+        let answer = &conv.bubbles[1];
+        assert_eq!(answer.bubble_type, BubbleType::Assistant);
+        assert_eq!(answer.text, "Done.\n\n---\n\nReally.");
+        assert_eq!(answer.thinking.as_ref().unwrap().text, "Let me check");
+        assert_eq!(answer.token_count.reasoning_tokens, 5);
+        // The next day, though only the time is exported
+        assert_eq!(answer.created_at, titled.bubbles[1].created_at);
+
+        assert_eq!(parsed[1].composer_id, "def-456");
+        assert_eq!(parsed[1].bubbles[0].text, "Fix it");
+
+        // The derived IDs are stable
+        assert_eq!(
+            parse_markdown_export(&content)[0].composer_id,
+            conv.composer_id
+        );
+    }
+}
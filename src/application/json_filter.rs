@@ -0,0 +1,637 @@
+//! `--filter` expressions: a small subset of jq for JSON output.
+//!
+//! Supported: `.`, `.field`, `."field"`, `.[n]`, `.[]`, pipes (`|`),
+//! multiple outputs (`,`), `[...]` and `{a, b: expr}` construction,
+//! literals, comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`), `and`, `or`,
+//! and the functions `select`, `map`, `length`, `keys` and `not`.
+//!
+//! Like jq, an expression produces any number of outputs for one input,
+//! e.g. `.[] | select(.bubbles | length > 10) | {composer_id, title}`.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+
+use crate::domain::{AppError, Result};
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonFilter(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Identity,
+    Literal(Value),
+    Field(Box<Self>, String),
+    Index(Box<Self>, i64),
+    Iterate(Box<Self>),
+    Pipe(Box<Self>, Box<Self>),
+    Comma(Box<Self>, Box<Self>),
+    Binary(Box<Self>, BinOp, Box<Self>),
+    Array(Option<Box<Self>>),
+    Object(Vec<(String, Self)>),
+    Call(Builtin, Option<Box<Self>>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Builtin {
+    Select,
+    Map,
+    Length,
+    Keys,
+    Not,
+}
+
+impl JsonFilter {
+    /// Apply the filter to `input`, returning every output in order.
+    ///
+    /// # Errors
+    /// Returns error if the expression does not fit the data, e.g. `.title`
+    /// on an array.
+    pub fn apply(&self, input: &Value) -> Result<Vec<Value>> {
+        eval(&self.0, input).map_err(|message| AppError::InvalidData {
+            message: format!("Filter failed: {message}"),
+        })
+    }
+}
+
+impl FromStr for JsonFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let expr = parser.pipe()?;
+        parser.peek().map_or(Ok(Self(expr)), |token| {
+            Err(format!("unexpected '{token}' in filter"))
+        })
+    }
+}
+
+fn eval(expr: &Expr, input: &Value) -> std::result::Result<Vec<Value>, String> {
+    Ok(match expr {
+        Expr::Identity => vec![input.clone()],
+        Expr::Literal(value) => vec![value.clone()],
+        Expr::Field(target, name) => each(target, input, |value| match value {
+            Value::Object(map) => Ok(vec![map.get(name).cloned().unwrap_or(Value::Null)]),
+            Value::Null => Ok(vec![Value::Null]),
+            other => Err(format!("cannot get .{name} of {}", type_name(&other))),
+        })?,
+        Expr::Index(target, index) => each(target, input, |value| match value {
+            Value::Array(items) => {
+                let len = i64::try_from(items.len()).unwrap_or(i64::MAX);
+                let at = if *index < 0 { len + index } else { *index };
+                Ok(vec![usize::try_from(at)
+                    .ok()
+                    .and_then(|at| items.get(at).cloned())
+                    .unwrap_or(Value::Null)])
+            }
+            Value::Null => Ok(vec![Value::Null]),
+            other => Err(format!("cannot index {} with a number", type_name(&other))),
+        })?,
+        Expr::Iterate(target) => each(target, input, |value| match value {
+            Value::Array(items) => Ok(items),
+            Value::Object(map) => Ok(map.into_iter().map(|(_, v)| v).collect()),
+            other => Err(format!("cannot iterate over {}", type_name(&other))),
+        })?,
+        Expr::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for value in eval(left, input)? {
+                out.extend(eval(right, &value)?);
+            }
+            out
+        }
+        Expr::Comma(left, right) => {
+            let mut out = eval(left, input)?;
+            out.extend(eval(right, input)?);
+            out
+        }
+        Expr::Binary(left, op, right) => {
+            let mut out = Vec::new();
+            for r in eval(right, input)? {
+                for l in eval(left, input)? {
+                    out.push(Value::Bool(compare(&l, *op, &r)));
+                }
+            }
+            out
+        }
+        Expr::Array(None) => vec![Value::Array(Vec::new())],
+        Expr::Array(Some(inner)) => vec![Value::Array(eval(inner, input)?)],
+        Expr::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key, value_expr) in entries {
+                let values = eval(value_expr, input)?;
+                objects = objects
+                    .into_iter()
+                    .flat_map(|object| {
+                        values.iter().map(move |value| {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            object
+                        })
+                    })
+                    .collect();
+            }
+            objects.into_iter().map(Value::Object).collect()
+        }
+        Expr::Call(builtin, arg) => call(*builtin, arg.as_deref(), input)?,
+    })
+}
+
+/// Evaluate `target`, then `f` on each of its outputs.
+fn each(
+    target: &Expr,
+    input: &Value,
+    f: impl Fn(Value) -> std::result::Result<Vec<Value>, String>,
+) -> std::result::Result<Vec<Value>, String> {
+    let mut out = Vec::new();
+    for value in eval(target, input)? {
+        out.extend(f(value)?);
+    }
+    Ok(out)
+}
+
+fn call(
+    builtin: Builtin,
+    arg: Option<&Expr>,
+    input: &Value,
+) -> std::result::Result<Vec<Value>, String> {
+    Ok(match (builtin, arg) {
+        (Builtin::Select, Some(cond)) => {
+            if eval(cond, input)?.iter().any(truthy) {
+                vec![input.clone()]
+            } else {
+                Vec::new()
+            }
+        }
+        (Builtin::Map, Some(f)) => {
+            let Value::Array(items) = input else {
+                return Err(format!("cannot map over {}", type_name(input)));
+            };
+            let mut out = Vec::new();
+            for item in items {
+                out.extend(eval(f, item)?);
+            }
+            vec![Value::Array(out)]
+        }
+        (Builtin::Length, None) => vec![match input {
+            Value::Null => 0.into(),
+            Value::String(s) => s.chars().count().into(),
+            Value::Array(items) => items.len().into(),
+            Value::Object(map) => map.len().into(),
+            Value::Number(n) => n.as_f64().map_or(Value::Null, |n| n.abs().into()),
+            Value::Bool(_) => return Err("boolean has no length".into()),
+        }],
+        (Builtin::Keys, None) => match input {
+            Value::Object(map) => vec![map.keys().cloned().map(Value::String).collect()],
+            Value::Array(items) => vec![(0..items.len()).map(Value::from).collect()],
+            other => return Err(format!("{} has no keys", type_name(other))),
+        },
+        (Builtin::Not, None) => vec![Value::Bool(!truthy(input))],
+        _ => unreachable!("arity is checked by the parser"),
+    })
+}
+
+fn compare(left: &Value, op: BinOp, right: &Value) -> bool {
+    match op {
+        BinOp::And => truthy(left) && truthy(right),
+        BinOp::Or => truthy(left) || truthy(right),
+        BinOp::Eq => order(left, right) == Ordering::Equal,
+        BinOp::Ne => order(left, right) != Ordering::Equal,
+        BinOp::Lt => order(left, right) == Ordering::Less,
+        BinOp::Le => order(left, right) != Ordering::Greater,
+        BinOp::Gt => order(left, right) == Ordering::Greater,
+        BinOp::Ge => order(left, right) != Ordering::Less,
+    }
+}
+
+/// jq's total order: null < false < true < numbers < strings < arrays < objects.
+fn order(left: &Value, right: &Value) -> Ordering {
+    const fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| order(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(_), Value::Object(_)) => {
+            if left == right {
+                Ordering::Equal
+            } else {
+                left.to_string().cmp(&right.to_string())
+            }
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+const fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+const fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dot => f.write_str("."),
+            Self::Ident(s) => f.write_str(s),
+            Self::Str(s) => write!(f, "\"{s}\""),
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Punct(p) => f.write_str(p),
+        }
+    }
+}
+
+const PUNCTUATION: [&str; 15] = [
+    "==", "!=", "<=", ">=", "<", ">", "|", ",", "(", ")", "[", "]", "{", "}", ":",
+];
+
+fn tokenize(s: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '.' {
+            tokens.push(Token::Dot);
+            rest = &rest[1..];
+        } else if c == '"' {
+            let (text, len) = string_literal(rest)?;
+            tokens.push(Token::Str(text));
+            rest = &rest[len..];
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|d: char| d.is_ascii_digit()))
+        {
+            let len = rest[1..]
+                .find(|d: char| !d.is_ascii_digit() && d != '.')
+                .map_or(rest.len(), |i| i + 1);
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", &rest[..len]))?;
+            tokens.push(Token::Num(number));
+            rest = &rest[len..];
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|d: char| !d.is_alphanumeric() && d != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            rest = &rest[len..];
+        } else if let Some(p) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+            tokens.push(Token::Punct(p));
+            rest = &rest[p.len()..];
+        } else {
+            return Err(format!("unexpected '{c}' in filter"));
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// A double-quoted string at the start of `s` and its length in bytes.
+fn string_literal(s: &str) -> std::result::Result<(String, usize), String> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' => escaped = !escaped,
+            '"' if !escaped => {
+                let text = serde_json::from_str(&s[..=i])
+                    .map_err(|_| format!("invalid string {}", &s[..=i]))?;
+                return Ok((text, i + 1));
+            }
+            _ => escaped = false,
+        }
+    }
+    Err("unterminated string in filter".into())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> std::result::Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.peek().map_or_else(
+                || format!("expected '{punct}' at end of filter"),
+                |token| format!("expected '{punct}', found '{token}'"),
+            ))
+        }
+    }
+
+    fn pipe(&mut self) -> std::result::Result<Expr, String> {
+        let mut expr = self.comma()?;
+        while self.eat("|") {
+            expr = Expr::Pipe(Box::new(expr), Box::new(self.comma()?));
+        }
+        Ok(expr)
+    }
+
+    fn comma(&mut self) -> std::result::Result<Expr, String> {
+        let mut expr = self.or()?;
+        while self.eat(",") {
+            expr = Expr::Comma(Box::new(expr), Box::new(self.or()?));
+        }
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> std::result::Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat_word("or") {
+            expr = Expr::Binary(Box::new(expr), BinOp::Or, Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> std::result::Result<Expr, String> {
+        let mut expr = self.comparison()?;
+        while self.eat_word("and") {
+            expr = Expr::Binary(Box::new(expr), BinOp::And, Box::new(self.comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> std::result::Result<Expr, String> {
+        let left = self.postfix()?;
+        let op = match self.peek() {
+            Some(Token::Punct("==")) => BinOp::Eq,
+            Some(Token::Punct("!=")) => BinOp::Ne,
+            Some(Token::Punct("<")) => BinOp::Lt,
+            Some(Token::Punct("<=")) => BinOp::Le,
+            Some(Token::Punct(">")) => BinOp::Gt,
+            Some(Token::Punct(">=")) => BinOp::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Binary(Box::new(left), op, Box::new(self.postfix()?)))
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn postfix(&mut self) -> std::result::Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.peek() == Some(&Token::Dot) {
+                self.pos += 1;
+                expr = self.suffix_after_dot(expr)?;
+            } else if self.eat("[") {
+                expr = self.bracket(expr)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// `.name`, `."name"` or `.[...]` applied to `target`.
+    fn suffix_after_dot(&mut self, target: Expr) -> std::result::Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Ident(name) | Token::Str(name)) => {
+                self.pos += 1;
+                Ok(Expr::Field(Box::new(target), name))
+            }
+            Some(Token::Punct("[")) => {
+                self.pos += 1;
+                self.bracket(target)
+            }
+            _ => Ok(target),
+        }
+    }
+
+    /// `[]`, `[n]` or `["name"]` applied to `target` (after the `[`).
+    fn bracket(&mut self, target: Expr) -> std::result::Result<Expr, String> {
+        if self.eat("]") {
+            return Ok(Expr::Iterate(Box::new(target)));
+        }
+        let expr = match self.next() {
+            #[allow(clippy::cast_possible_truncation)]
+            Some(Token::Num(n)) if n.fract() == 0.0 => Expr::Index(Box::new(target), n as i64),
+            Some(Token::Str(name)) => Expr::Field(Box::new(target), name),
+            Some(token) => return Err(format!("unsupported index '{token}'")),
+            None => return Err("unterminated '['".into()),
+        };
+        self.expect("]")?;
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> std::result::Result<Expr, String> {
+        match self.next() {
+            Some(Token::Dot) => self.suffix_after_dot(Expr::Identity),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Num(n)) => Ok(Expr::Literal(number(n))),
+            Some(Token::Punct("(")) => {
+                let expr = self.pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Punct("[")) => {
+                if self.eat("]") {
+                    return Ok(Expr::Array(None));
+                }
+                let expr = self.pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Array(Some(Box::new(expr))))
+            }
+            Some(Token::Punct("{")) => self.object(),
+            Some(Token::Ident(word)) => self.word(&word),
+            Some(token) => Err(format!("unexpected '{token}' in filter")),
+            None => Err("filter ends unexpectedly".into()),
+        }
+    }
+
+    fn word(&mut self, word: &str) -> std::result::Result<Expr, String> {
+        let (builtin, takes_arg) = match word {
+            "true" => return Ok(Expr::Literal(Value::Bool(true))),
+            "false" => return Ok(Expr::Literal(Value::Bool(false))),
+            "null" => return Ok(Expr::Literal(Value::Null)),
+            "select" => (Builtin::Select, true),
+            "map" => (Builtin::Map, true),
+            "length" => (Builtin::Length, false),
+            "keys" => (Builtin::Keys, false),
+            "not" => (Builtin::Not, false),
+            _ => return Err(format!("unknown function '{word}'")),
+        };
+
+        if !takes_arg {
+            return Ok(Expr::Call(builtin, None));
+        }
+        self.expect("(")?;
+        let arg = self.pipe()?;
+        self.expect(")")?;
+        Ok(Expr::Call(builtin, Some(Box::new(arg))))
+    }
+
+    /// `{a, "b", c: expr}` (after the `{`).
+    fn object(&mut self) -> std::result::Result<Expr, String> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Expr::Object(entries));
+        }
+
+        loop {
+            let key = match self.next() {
+                Some(Token::Ident(key) | Token::Str(key)) => key,
+                Some(token) => return Err(format!("unexpected '{token}' as object key")),
+                None => return Err("unterminated '{'".into()),
+            };
+            let value = if self.eat(":") {
+                self.or()?
+            } else {
+                Expr::Field(Box::new(Expr::Identity), key.clone())
+            };
+            entries.push((key, value));
+
+            if self.eat("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9e15 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(filter: &str, input: &Value) -> Vec<Value> {
+        filter.parse::<JsonFilter>().unwrap().apply(input).unwrap()
+    }
+
+    #[test]
+    fn test_filter_paths_and_construction() {
+        let input = json!([
+            {"composer_id": "a", "title": "First", "bubbles": [{"text": "hi"}, {"text": "yo"}]},
+            {"composer_id": "b", "title": "Second", "bubbles": []}
+        ]);
+
+        assert_eq!(run(".", &input)[0], input);
+        assert_eq!(run(".[0].title", &input), [json!("First")]);
+        assert_eq!(run(".[-1].composer_id", &input), [json!("b")]);
+        assert_eq!(run(".[].title", &input), [json!("First"), json!("Second")]);
+        assert_eq!(
+            run(".[] | {id: .composer_id, title}", &input),
+            [
+                json!({"id": "a", "title": "First"}),
+                json!({"id": "b", "title": "Second"})
+            ]
+        );
+        assert_eq!(
+            run(
+                "[.[] | select(.bubbles | length > 0) | .bubbles[].text]",
+                &input
+            ),
+            [json!(["hi", "yo"])]
+        );
+        assert_eq!(run("map(.title) | length", &input), [json!(2)]);
+        assert_eq!(
+            run(".[0] | keys", &input),
+            [json!(["bubbles", "composer_id", "title"])]
+        );
+        assert_eq!(
+            run(
+                ".[] | select(.title == \"Second\" or .composer_id == \"a\") | .composer_id",
+                &input
+            ),
+            [json!("a"), json!("b")]
+        );
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        assert!("select(".parse::<JsonFilter>().is_err());
+        assert!(".foo bar".parse::<JsonFilter>().is_err());
+        assert!("nope".parse::<JsonFilter>().is_err());
+
+        let filter: JsonFilter = ".title".parse().unwrap();
+        let err = filter.apply(&json!([1])).unwrap_err();
+        assert!(err.to_string().contains("cannot get .title of an array"));
+    }
+}
@@ -0,0 +1,213 @@
+//! Ranked statistics over conversations.
+//!
+//! Builds the `stats top` view: longest chats, biggest token consumers,
+//! most active days and most used models.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::domain::Conversation;
+
+/// A conversation ranked by some count.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedConversation {
+    /// Composer ID.
+    pub composer_id: String,
+    /// Title (or first message when untitled).
+    pub title: String,
+    /// Value the conversation is ranked by.
+    pub value: u64,
+}
+
+/// A day ranked by activity.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveDay {
+    /// Calendar day (UTC).
+    pub date: NaiveDate,
+    /// Messages sent that day.
+    pub messages: usize,
+    /// Conversations with at least one message that day.
+    pub conversations: usize,
+}
+
+/// A model ranked by usage.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUsage {
+    /// Model name (`unknown` when Cursor did not record one).
+    pub model: String,
+    /// Conversations using this model.
+    pub conversations: usize,
+    /// Messages in those conversations.
+    pub messages: usize,
+}
+
+/// Top-N rankings across all conversations.
+#[derive(Debug, Clone, Serialize)]
+pub struct Leaderboard {
+    /// Conversations with the most messages.
+    pub longest: Vec<RankedConversation>,
+    /// Conversations with the most input + output + reasoning tokens.
+    pub token_consumers: Vec<RankedConversation>,
+    /// Days with the most messages.
+    pub busiest_days: Vec<ActiveDay>,
+    /// Models used in the most conversations.
+    pub top_models: Vec<ModelUsage>,
+}
+
+impl Leaderboard {
+    /// Rank `conversations`, keeping the top `limit` entries per category.
+    #[must_use]
+    pub fn from_conversations(conversations: &[Conversation], limit: usize) -> Self {
+        let longest = top_conversations(conversations, limit, |c| c.message_count() as u64);
+
+        let token_consumers = top_conversations(conversations, limit, |c| c.token_count().total());
+
+        Self {
+            longest,
+            token_consumers,
+            busiest_days: busiest_days(conversations, limit),
+            top_models: top_models(conversations, limit),
+        }
+    }
+
+    /// Whether there is nothing to show.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.longest.is_empty() && self.busiest_days.is_empty()
+    }
+}
+
+fn top_conversations(
+    conversations: &[Conversation],
+    limit: usize,
+    value: impl Fn(&Conversation) -> u64,
+) -> Vec<RankedConversation> {
+    let mut ranked: Vec<_> = conversations
+        .iter()
+        .map(|c| (c, value(c)))
+        .filter(|(_, v)| *v > 0)
+        .collect();
+    ranked.sort_by_key(|(_, v)| Reverse(*v));
+
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(c, value)| RankedConversation {
+            composer_id: c.composer_id.clone(),
+            title: if c.title.is_empty() {
+                c.preview().to_string()
+            } else {
+                c.title.clone()
+            },
+            value,
+        })
+        .collect()
+}
+
+fn busiest_days(conversations: &[Conversation], limit: usize) -> Vec<ActiveDay> {
+    let mut days: HashMap<NaiveDate, (usize, usize)> = HashMap::new();
+
+    for conv in conversations {
+        let mut seen = Vec::new();
+        for bubble in &conv.bubbles {
+            // Older bubbles have no timestamp; fall back to the conversation's
+            let Some(at) = bubble.created_at.or(conv.created_at) else {
+                continue;
+            };
+            let date = at.date_naive();
+            let entry = days.entry(date).or_default();
+            entry.0 += 1;
+            if !seen.contains(&date) {
+                seen.push(date);
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<_> = days
+        .into_iter()
+        .map(|(date, (messages, conversations))| ActiveDay {
+            date,
+            messages,
+            conversations,
+        })
+        .collect();
+    ranked.sort_by_key(|d| (Reverse(d.messages), Reverse(d.date)));
+    ranked.truncate(limit);
+    ranked
+}
+
+fn top_models(conversations: &[Conversation], limit: usize) -> Vec<ModelUsage> {
+    let mut models: HashMap<&str, (usize, usize)> = HashMap::new();
+
+    for conv in conversations {
+        let name = conv.model_config.model_name.as_str();
+        let name = if name.is_empty() { "unknown" } else { name };
+        let entry = models.entry(name).or_default();
+        entry.0 += 1;
+        entry.1 += conv.message_count();
+    }
+
+    let mut ranked: Vec<_> = models
+        .into_iter()
+        .map(|(model, (conversations, messages))| ModelUsage {
+            model: model.to_string(),
+            conversations,
+            messages,
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.conversations
+            .cmp(&a.conversations)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::test_support::{conversation, january};
+    use crate::domain::TokenCount;
+
+    #[test]
+    fn test_leaderboard_rankings() {
+        let input = |input_tokens| TokenCount {
+            input_tokens,
+            ..TokenCount::default()
+        };
+        let conversations = vec![
+            conversation("a", "gpt-4", &january(&[1, 1, 2]), &input(10)),
+            conversation("b", "claude", &january(&[2, 2, 2, 2]), &input(1)),
+            conversation("c", "", &january(&[3]), &input(500)),
+            conversation("d", "claude", &[], &input(0)),
+        ];
+
+        let board = Leaderboard::from_conversations(&conversations, 2);
+
+        let longest: Vec<_> = board
+            .longest
+            .iter()
+            .map(|r| r.composer_id.as_str())
+            .collect();
+        assert_eq!(longest, ["b", "a"]);
+
+        assert_eq!(board.token_consumers[0].composer_id, "c");
+        assert_eq!(board.token_consumers[0].value, 500);
+
+        assert_eq!(
+            board.busiest_days[0].date,
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()
+        );
+        assert_eq!(board.busiest_days[0].messages, 5);
+        assert_eq!(board.busiest_days[0].conversations, 2);
+
+        assert_eq!(board.top_models[0].model, "claude");
+        assert_eq!(board.top_models[0].conversations, 2);
+        assert_eq!(board.top_models[1].model, "gpt-4");
+    }
+}
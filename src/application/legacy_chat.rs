@@ -0,0 +1,146 @@
+//! Pre-composer chat tabs (the old `aichat` panel).
+//!
+//! Older Cursor versions stored each chat tab, with its messages inline,
+//! in one `ItemTable` entry per database. Those tabs are converted to
+//! conversations so their history can still be listed and exported.
+
+use std::path::Path;
+
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::domain::{AppError, Bubble, BubbleType, Conversation, ModelConfig, Result};
+use crate::infrastructure::StateDbReader;
+
+/// `ItemTable` key holding the chat tabs.
+pub const LEGACY_CHAT_KEY: &str = "workbench.panel.aichat.view.aichat.chatdata";
+
+#[derive(Debug, Deserialize)]
+struct RawChatData {
+    #[serde(default)]
+    tabs: Vec<RawTab>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTab {
+    tab_id: String,
+    #[serde(default)]
+    last_send_time: Option<i64>,
+    #[serde(default)]
+    bubbles: Vec<RawTabBubble>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTabBubble {
+    #[serde(rename = "type", default)]
+    bubble_type: String,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    raw_text: Option<String>,
+    #[serde(default)]
+    model_type: Option<String>,
+}
+
+/// Converts the chat tabs of a `chatdata` entry into conversations.
+///
+/// Tabs without messages are skipped. Messages carry no timestamps, so
+/// each conversation is dated by the tab's last send time.
+///
+/// # Errors
+/// Returns error if JSON parsing fails.
+pub fn parse_legacy_chats(data: &[u8]) -> Result<Vec<Conversation>> {
+    let raw: RawChatData = serde_json::from_slice(data).map_err(AppError::json_parse)?;
+
+    Ok(raw
+        .tabs
+        .into_iter()
+        .filter(|tab| !tab.bubbles.is_empty())
+        .map(conversation_from_tab)
+        .collect())
+}
+
+/// Chat tabs stored in the database at `path`, if any.
+///
+/// # Errors
+/// Returns error if the database cannot be read or the entry is malformed.
+pub fn read_legacy_chats(path: &Path) -> Result<Vec<Conversation>> {
+    StateDbReader::open(path)?
+        .item_value(LEGACY_CHAT_KEY)?
+        .map_or_else(|| Ok(Vec::new()), |data| parse_legacy_chats(&data))
+}
+
+fn conversation_from_tab(tab: RawTab) -> Conversation {
+    let model_name = tab
+        .bubbles
+        .iter()
+        .find_map(|b| b.model_type.clone())
+        .unwrap_or_default();
+
+    let bubbles = tab
+        .bubbles
+        .into_iter()
+        .enumerate()
+        .map(|(i, b)| Bubble {
+            bubble_id: b.id.unwrap_or_else(|| format!("{}-{i}", tab.tab_id)),
+            bubble_type: match b.bubble_type.as_str() {
+                "user" => BubbleType::User,
+                "ai" => BubbleType::Assistant,
+                _ => BubbleType::Unknown,
+            },
+            text: b
+                .text
+                .filter(|t| !t.trim().is_empty())
+                .or(b.raw_text)
+                .unwrap_or_default(),
+            ..Default::default()
+        })
+        .collect();
+
+    Conversation {
+        composer_id: tab.tab_id,
+        title: String::new(),
+        created_at: tab.last_send_time.and_then(DateTime::from_timestamp_millis),
+        model_config: ModelConfig {
+            model_name,
+            max_mode: false,
+        },
+        unified_mode: "chat".into(),
+        bubbles,
+        git: None,
+        workspace: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_chats() {
+        let data = br#"{"tabs": [
+            {"tabId": "tab-1", "chatTitle": "Parser", "lastSendTime": 1700000000000,
+             "bubbles": [
+                {"type": "user", "id": "u1", "text": "Why does the parser fail?"},
+                {"type": "ai", "rawText": "Missing semicolon.", "text": "", "modelType": "gpt-4"}
+             ]},
+            {"tabId": "tab-2", "bubbles": []}
+        ]}"#;
+
+        let conversations = parse_legacy_chats(data).unwrap();
+        assert_eq!(conversations.len(), 1);
+
+        let conv = &conversations[0];
+        assert_eq!(conv.composer_id, "tab-1");
+        assert_eq!(conv.model_config.model_name, "gpt-4");
+        assert_eq!(conv.created_at.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(conv.bubbles[0].bubble_type, BubbleType::User);
+        assert_eq!(conv.bubbles[1].bubble_type, BubbleType::Assistant);
+        assert_eq!(conv.bubbles[1].bubble_id, "tab-1-1");
+        assert_eq!(conv.bubbles[1].text, "Missing semicolon.");
+    }
+}
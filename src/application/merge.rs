@@ -0,0 +1,220 @@
+//! Merge of conversations Cursor split across several composers.
+//!
+//! The messages of every conversation are interleaved by time, identical
+//! messages are kept once, and the result is written to local storage,
+//! either into one of the merged conversations or as a new one. The source
+//! conversations are left as they are.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{AppConfig, AppError, Bubble, Conversation, OperationKind, Result};
+use crate::infrastructure::LocalStorage;
+
+use super::audit;
+use super::import::derived_id;
+use super::summarizer::resolve_conversation;
+use super::sync_service::SyncService;
+
+/// `--into` value that merges into a new conversation.
+pub const NEW_CONVERSATION: &str = "new";
+
+/// Result of a merge.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+    /// Conversation the messages were merged into.
+    pub composer_id: String,
+    /// Its title.
+    pub title: String,
+    /// Whether it was created by the merge.
+    pub created: bool,
+    /// Composer IDs of the merged conversations, in the order given.
+    pub sources: Vec<String>,
+    /// Messages of the merged conversation.
+    pub messages: usize,
+    /// Identical messages kept once.
+    pub duplicates: usize,
+}
+
+/// Merge the stored conversations matching `ids` (full or partial) into
+/// `into`: one of them, or [`NEW_CONVERSATION`]. Without `into`, the first
+/// one is kept.
+///
+/// Cursor is synced first, when it can be, so the latest messages are
+/// merged.
+///
+/// # Errors
+/// Returns error if fewer than two distinct conversations match, an ID is
+/// ambiguous, `into` is not one of them, or storage cannot be written.
+pub fn merge(config: &AppConfig, ids: &[String], into: Option<&str>) -> Result<MergeResult> {
+    audit::recorded(
+        config,
+        OperationKind::Merge,
+        || run_merge(config, ids, into),
+        |result| {
+            Some((
+                result.sources.len(),
+                format!("{} into {}", result.sources.join(", "), result.composer_id),
+            ))
+        },
+    )
+}
+
+fn run_merge(config: &AppConfig, ids: &[String], into: Option<&str>) -> Result<MergeResult> {
+    let service = SyncService::new(config.clone())?;
+    if let Err(e) = service.sync() {
+        tracing::warn!(error = %e, "Sync before merge failed; merging stored conversations");
+    }
+
+    let storage = LocalStorage::open_read_only(&config.storage_db_path())?;
+    let mut sources: Vec<Conversation> = Vec::new();
+    for id in ids {
+        let conv = resolve_conversation(&storage, id)?;
+        if !sources.iter().any(|c| c.composer_id == conv.composer_id) {
+            sources.push(conv);
+        }
+    }
+    if sources.len() < 2 {
+        return Err(AppError::InvalidData {
+            message: "Give at least two different conversations to merge".into(),
+        });
+    }
+
+    let target = match into {
+        None => Some(0),
+        Some(NEW_CONVERSATION) => None,
+        Some(into) => {
+            let target = resolve_conversation(&storage, into)?;
+            let index = sources
+                .iter()
+                .position(|c| c.composer_id == target.composer_id)
+                .ok_or_else(|| AppError::InvalidData {
+                    message: format!(
+                        "--into must be one of the merged conversations or '{NEW_CONVERSATION}'"
+                    ),
+                })?;
+            Some(index)
+        }
+    };
+    drop(storage);
+
+    let (merged, duplicates) = merge_conversations(&sources, target);
+    let imported = service.import(std::slice::from_ref(&merged))?;
+
+    tracing::info!(
+        composer_id = %merged.composer_id,
+        sources = sources.len(),
+        "Merged conversations"
+    );
+
+    Ok(MergeResult {
+        created: imported.created > 0,
+        messages: merged.bubbles.len(),
+        duplicates,
+        sources: sources.into_iter().map(|c| c.composer_id).collect(),
+        composer_id: merged.composer_id,
+        title: merged.title,
+    })
+}
+
+/// Interleave the messages of `sources` by time into `sources[target]`, or
+/// into a new conversation when `target` is `None`.
+///
+/// Messages copied from another conversation get IDs derived from the
+/// target and their original ID, since a message ID belongs to one
+/// conversation. Returns the merged conversation and the number of
+/// identical messages dropped.
+#[must_use]
+pub fn merge_conversations(
+    sources: &[Conversation],
+    target: Option<usize>,
+) -> (Conversation, usize) {
+    let mut merged = target.map_or_else(
+        || {
+            let ids: Vec<&str> = sources.iter().map(|c| c.composer_id.as_str()).collect();
+            let mut conv = sources[0].clone();
+            conv.composer_id = derived_id(&format!("merge:{}", ids.join(",")));
+            conv
+        },
+        |index| sources[index].clone(),
+    );
+    merged.created_at = sources.iter().filter_map(|c| c.created_at).min();
+
+    // Untimed messages sort right after the message before them, and the
+    // target's own come first among simultaneous ones so they are kept
+    let mut timed: Vec<(Option<DateTime<Utc>>, bool, Bubble)> = Vec::new();
+    for (index, conv) in sources.iter().enumerate() {
+        let mut last = conv.created_at;
+        for bubble in &conv.bubbles {
+            last = bubble.created_at.or(last);
+            let copied = target != Some(index);
+            let mut bubble = bubble.clone();
+            if copied {
+                bubble.bubble_id =
+                    derived_id(&format!("{}:{}", merged.composer_id, bubble.bubble_id));
+            }
+            timed.push((last, copied, bubble));
+        }
+    }
+    timed.sort_by_key(|(at, copied, _)| (*at, *copied));
+
+    let mut seen: HashSet<(u8, String, Option<DateTime<Utc>>)> = HashSet::new();
+    let total = timed.len();
+    merged.bubbles = timed
+        .into_iter()
+        .map(|(_, _, bubble)| bubble)
+        .filter(|b| seen.insert((b.bubble_type as u8, b.text.clone(), b.created_at)))
+        .collect();
+
+    let duplicates = total - merged.bubbles.len();
+    (merged, duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(id: &str, bubbles: &[(&str, &str, i64)]) -> Conversation {
+        let bubbles: Vec<_> = bubbles
+            .iter()
+            .map(|(bubble_id, text, secs)| {
+                serde_json::json!({
+                    "bubble_id": bubble_id, "bubble_type": 1, "text": text,
+                    "created_at": DateTime::from_timestamp(1_700_000_000 + secs, 0),
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "composer_id": id, "title": id, "created_at": null, "bubbles": bubbles,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_conversations() {
+        let first = conversation("c1", &[("a", "one", 0), ("b", "three", 20)]);
+        let second = conversation("c2", &[("y", "three", 20), ("x", "two", 10)]);
+        let sources = [first, second];
+
+        let (merged, duplicates) = merge_conversations(&sources, Some(0));
+        assert_eq!(merged.composer_id, "c1");
+        assert_eq!(duplicates, 1);
+        let texts: Vec<&str> = merged.bubbles.iter().map(|b| b.text.as_str()).collect();
+        assert_eq!(texts, ["one", "two", "three"]);
+        // Kept IDs of the target, new ones for copied messages
+        assert_eq!(merged.bubbles[0].bubble_id, "a");
+        assert_ne!(merged.bubbles[1].bubble_id, "x");
+        assert_eq!(merged.bubbles[2].bubble_id, "b");
+
+        let (new, _) = merge_conversations(&sources, None);
+        assert_ne!(new.composer_id, "c1");
+        assert_eq!(new.title, "c1");
+        assert!(new.bubbles.iter().all(|b| b.bubble_id.len() == 36));
+        assert_eq!(
+            merge_conversations(&sources, None).0.composer_id,
+            new.composer_id
+        );
+    }
+}
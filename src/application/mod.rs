@@ -3,18 +3,76 @@
 //! This layer contains the main business logic for extracting
 //! and formatting chat data.
 
+pub mod audit;
+pub mod auto_export;
+pub mod bench;
+pub mod correlate;
+pub mod diagnostics;
+pub mod events;
+pub mod export_manifest;
+pub mod export_split;
 pub mod extractor;
+pub mod fine_tune;
 pub mod formatter;
+pub mod import;
+pub mod json_filter;
+pub mod leaderboard;
+pub mod legacy_chat;
+pub mod merge;
 pub mod parser;
+pub mod rag;
+pub mod redact;
+pub mod replay;
 pub mod restore_service;
+pub mod semantic_index;
 pub mod storage_manager;
+pub mod summarizer;
 pub mod sync_service;
+#[cfg(test)]
+mod test_support;
+pub mod timeline;
+pub mod todos;
+pub mod usage;
+pub mod work_sessions;
+pub mod workspace_chats;
 
-pub use extractor::{extract_all_conversations, ExtractOptions};
+pub use auto_export::AutoExporter;
+pub use bench::{generate_synthetic_db, run_benchmark, BenchReport};
+pub use correlate::Correlation;
+pub use diagnostics::{run_diagnostics, CheckStatus};
+pub use events::publish_changes;
+pub use export_manifest::ExportManifest;
+pub use export_split::{split_conversation, SplitLimit};
+pub use extractor::{
+    extract_all_conversations, extract_in_batches, find_conversation, load_short_ids,
+    ExtractOptions,
+};
+pub use fine_tune::{training_example, TrainingOptions};
 pub use formatter::{
-    format_conversation_markdown, format_conversations_json, format_conversations_table,
-    format_stats, OutputFormat,
+    format_action_items_markdown, format_bench, format_conversation_markdown,
+    format_conversations_by_day, format_conversations_json, format_conversations_table,
+    format_correlation, format_exchange, format_leaderboard, format_message_hits,
+    format_mode_usage, format_query_table, format_quick_machine, format_session_report,
+    format_shell_script, format_stats, format_terminal_commands_markdown, format_timeline,
+    format_timings, write_conversation_markdown, write_conversations, write_jsonl, write_query_csv,
+    write_usage_csv, JsonArrayWriter, ListGrouping, OutputFormat,
+};
+pub use import::{import_exports, ImportResult};
+pub use json_filter::JsonFilter;
+pub use leaderboard::Leaderboard;
+pub use merge::{merge, MergeResult};
+pub use rag::{chunk_conversation, ChunkOptions};
+pub use redact::PathMap;
+pub use replay::{exchanges, pause_between};
+pub use restore_service::{PruneFilter, PruneResult, RestoreResult, RestoreService};
+pub use semantic_index::{SemanticIndex, SimilarityMethod};
+pub use storage_manager::{
+    format_bytes, is_data_dir, profile_data_dirs, purge_data_dir, CleanupResult, MaintenanceResult,
+    RepairResult, StorageManager, StorageSummary, VacuumResult,
 };
-pub use restore_service::{RestoreResult, RestoreService};
-pub use storage_manager::{CleanupResult, StorageManager, StorageSummary};
+pub use summarizer::{resolve_conversation, Summarizer};
 pub use sync_service::{StorageInfo, SyncService};
+pub use timeline::Timeline;
+pub use todos::extract_action_items;
+pub use usage::{mode_breakdown, usage_breakdown};
+pub use work_sessions::SessionReport;
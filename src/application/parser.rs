@@ -5,7 +5,14 @@
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
-use crate::domain::{AppError, Bubble, BubbleType, Result, ThinkingBlock, TokenCount};
+use crate::domain::{
+    AppError, Bubble, BubbleContext, BubbleType, ContextFile, Diagnostic, Result, TerminalCommand,
+    ThinkingBlock, TokenCount, ToolCall, ToolKind, WorkspaceInfo,
+};
+
+/// Prefix of the tool names Cursor uses for terminal commands
+/// (`run_terminal_cmd`, `run_terminal_command_v2`).
+const TERMINAL_TOOL_PREFIX: &str = "run_terminal_";
 
 /// Raw bubble data as stored in the database (JSON format).
 #[derive(Debug, Deserialize)]
@@ -29,6 +36,44 @@ struct RawBubble {
     token_count: Option<RawTokenCount>,
     #[serde(default)]
     is_agentic: bool,
+    #[serde(default)]
+    tool_former_data: Option<RawToolCall>,
+    // Lint arrays are kept untyped: their shape varies between versions and
+    // a mismatch must not lose the whole bubble
+    #[serde(default)]
+    lints: serde_json::Value,
+    #[serde(default)]
+    approximate_lint_errors: serde_json::Value,
+    #[serde(default)]
+    multi_file_linter_errors: serde_json::Value,
+    // Attachments, untyped for the same reason. Newer versions nest them
+    // under `context`, older ones keep the arrays on the bubble
+    #[serde(default)]
+    context: serde_json::Value,
+    #[serde(default)]
+    file_selections: serde_json::Value,
+    #[serde(default)]
+    folder_selections: serde_json::Value,
+    #[serde(default)]
+    selections: serde_json::Value,
+    #[serde(default)]
+    attached_code_chunks: serde_json::Value,
+}
+
+/// Tool call made by the agent, with JSON-encoded arguments and result.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RawToolCall {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    raw_args: Option<String>,
+    #[serde(default)]
+    params: Option<String>,
+    #[serde(default)]
+    result: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -46,6 +91,18 @@ struct RawTokenCount {
     input_tokens: u64,
     #[serde(default)]
     output_tokens: u64,
+    #[serde(default, alias = "thinkingTokens", alias = "reasoningOutputTokens")]
+    reasoning_tokens: u64,
+    #[serde(default)]
+    output_tokens_details: Option<RawOutputTokensDetails>,
+}
+
+/// `OpenAI`-style breakdown of output tokens.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RawOutputTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u64,
 }
 
 /// Raw composer data as stored in the database.
@@ -53,7 +110,7 @@ struct RawTokenCount {
 #[serde(rename_all = "camelCase")]
 pub struct RawComposer {
     #[serde(rename = "_v")]
-    pub _version: Option<u8>,
+    pub version: Option<u8>,
     #[serde(default)]
     pub created_at: Option<u64>,
     #[serde(default)]
@@ -78,8 +135,17 @@ pub struct RawModelConfig {
 pub fn parse_bubble(data: &[u8]) -> Result<Bubble> {
     let raw: RawBubble = serde_json::from_slice(data).map_err(AppError::json_parse)?;
 
-    let created_at = parse_datetime(&raw.created_at);
+    let created_at = parse_datetime(raw.created_at.as_ref());
+    let mut diagnostics = Vec::new();
+    for lints in [
+        &raw.lints,
+        &raw.approximate_lint_errors,
+        &raw.multi_file_linter_errors,
+    ] {
+        collect_diagnostics(lints, None, &mut diagnostics);
+    }
     let bubble_type = BubbleType::try_from(raw.bubble_type).unwrap_or(BubbleType::Unknown);
+    let context = parse_context(&raw);
 
     let thinking = raw.thinking.map(|t| ThinkingBlock {
         text: t.text,
@@ -91,6 +157,10 @@ pub fn parse_bubble(data: &[u8]) -> Result<Bubble> {
         .map_or_else(TokenCount::default, |t| TokenCount {
             input_tokens: t.input_tokens,
             output_tokens: t.output_tokens,
+            reasoning_tokens: t.reasoning_tokens.max(
+                t.output_tokens_details
+                    .map_or(0, |details| details.reasoning_tokens),
+            ),
         });
 
     Ok(Bubble {
@@ -102,9 +172,289 @@ pub fn parse_bubble(data: &[u8]) -> Result<Bubble> {
         thinking_duration_ms: raw.thinking_duration_ms,
         token_count,
         is_agentic: raw.is_agentic,
+        tool_calls: raw
+            .tool_former_data
+            .as_ref()
+            .and_then(parse_tool_call)
+            .into_iter()
+            .collect(),
+        context,
+        commands: raw
+            .tool_former_data
+            .and_then(parse_terminal_command)
+            .into_iter()
+            .collect(),
+        diagnostics,
     })
 }
 
+/// Collects the diagnostics in one of Cursor's lint arrays.
+///
+/// Entries may group their diagnostics per file (`errors`, `lints`) or wrap
+/// each one in a `lint` object; nested diagnostics inherit the parent's
+/// file. Duplicates across the arrays are dropped.
+fn collect_diagnostics(value: &serde_json::Value, file: Option<&str>, out: &mut Vec<Diagnostic>) {
+    let str_field = |obj: &serde_json::Map<String, serde_json::Value>, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| obj.get(*key).and_then(|v| v.as_str()))
+            .map(String::from)
+    };
+
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_diagnostics(item, file, out);
+            }
+        }
+        serde_json::Value::Object(obj) => {
+            let file = str_field(obj, &["relativeWorkspacePath", "file", "path", "uri"])
+                .or_else(|| file.map(String::from));
+            if let Some(nested) = ["errors", "lints", "lint"]
+                .iter()
+                .find_map(|key| obj.get(*key))
+            {
+                collect_diagnostics(nested, file.as_deref(), out);
+                return;
+            }
+            let Some(message) = str_field(obj, &["message"]) else {
+                return;
+            };
+            let range = obj.get("range");
+            let line = [
+                obj.get("startLineNumber"),
+                obj.get("line"),
+                range.and_then(|r| r.get("startLineNumber")),
+                range.and_then(|r| r.pointer("/startPosition/line")),
+            ]
+            .into_iter()
+            .flatten()
+            .find_map(serde_json::Value::as_u64)
+            .and_then(|line| u32::try_from(line).ok());
+
+            let diagnostic = Diagnostic {
+                message,
+                file,
+                line,
+                severity: str_field(obj, &["severity"]).map(|s| s.to_lowercase()),
+            };
+            if !out.contains(&diagnostic) {
+                out.push(diagnostic);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Files, selections and folders attached to a bubble.
+///
+/// Both the nested `context` arrays and the older top-level ones are read;
+/// an attachment listed in several of them is kept once.
+fn parse_context(raw: &RawBubble) -> BubbleContext {
+    let nested = |key: &str| raw.context.get(key).unwrap_or(&serde_json::Value::Null);
+    let entries = |arrays: &[&serde_json::Value]| {
+        arrays
+            .iter()
+            .filter_map(|array| array.as_array())
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let mut context = BubbleContext::default();
+    for entry in entries(&[
+        nested("fileSelections"),
+        &raw.file_selections,
+        nested("selections"),
+        &raw.selections,
+        &raw.attached_code_chunks,
+    ]) {
+        if let Some(file) = context_file(&entry) {
+            if !context.files.contains(&file) {
+                context.files.push(file);
+            }
+        }
+    }
+    for entry in entries(&[nested("folderSelections"), &raw.folder_selections]) {
+        if let Some(folder) = context_path(&entry) {
+            if !context.folders.contains(&folder) {
+                context.folders.push(folder);
+            }
+        }
+    }
+    context
+}
+
+/// Path of an attachment: workspace-relative when recorded, else the path
+/// of its URI.
+fn context_path(entry: &serde_json::Value) -> Option<String> {
+    [
+        "/relativeWorkspacePath",
+        "/relativePath",
+        "/uri/fsPath",
+        "/uri/path",
+        "/uri",
+        "/path",
+    ]
+    .iter()
+    .find_map(|pointer| entry.pointer(pointer).and_then(|v| v.as_str()))
+    .filter(|path| !path.is_empty())
+    .map(String::from)
+}
+
+/// An attached file, with the selected lines when it is a selection or a
+/// code chunk.
+fn context_file(entry: &serde_json::Value) -> Option<ContextFile> {
+    let path = context_path(entry)?;
+    let line = |pointers: &[&str]| {
+        pointers
+            .iter()
+            .find_map(|pointer| entry.pointer(pointer).and_then(serde_json::Value::as_u64))
+            .and_then(|line| u32::try_from(line).ok())
+    };
+
+    let start = line(&[
+        "/range/selectionStartLineNumber",
+        "/range/startLineNumber",
+        "/startLineNumber",
+    ]);
+    let end = line(&[
+        "/range/positionLineNumber",
+        "/range/endLineNumber",
+        "/endLineNumber",
+    ])
+    .or_else(|| {
+        // Code chunks give their lines instead of an end
+        let count = entry.get("lines")?.as_array()?.len();
+        Some(start? + u32::try_from(count).ok()?.saturating_sub(1))
+    });
+    // Selections made bottom-up start after they end
+    let (start_line, end_line) = match (start, end) {
+        (Some(start), Some(end)) if end < start => (Some(end), Some(start)),
+        range => range,
+    };
+
+    Some(ContextFile {
+        path,
+        start_line,
+        end_line,
+    })
+}
+
+/// The terminal command of a tool call, if it ran one.
+///
+/// The command comes from the call's arguments; output and exit code from
+/// its result, which is kept as plain output when it is not JSON.
+fn parse_terminal_command(tool: RawToolCall) -> Option<TerminalCommand> {
+    if !tool.name.starts_with(TERMINAL_TOOL_PREFIX) {
+        return None;
+    }
+
+    let args: serde_json::Value = [&tool.raw_args, &tool.params]
+        .into_iter()
+        .flatten()
+        .find_map(|a| serde_json::from_str(a).ok())
+        .unwrap_or_default();
+    let command = args.get("command").and_then(|v| v.as_str())?.to_string();
+    let cwd = args.get("cwd").and_then(|v| v.as_str()).map(String::from);
+
+    let (output, exit_code) = tool.result.map_or((None, None), parse_terminal_result);
+
+    Some(TerminalCommand {
+        command,
+        cwd,
+        output,
+        exit_code,
+    })
+}
+
+/// A tool call other than a terminal command, if the bubble made one.
+///
+/// Argument names differ between tools and Cursor versions, so the
+/// target file, query and description are taken from whichever of the
+/// known names is present.
+fn parse_tool_call(tool: &RawToolCall) -> Option<ToolCall> {
+    if tool.name.is_empty() || tool.name.starts_with(TERMINAL_TOOL_PREFIX) {
+        return None;
+    }
+
+    let args: serde_json::Value = [&tool.raw_args, &tool.params]
+        .into_iter()
+        .flatten()
+        .find_map(|a| serde_json::from_str(a).ok())
+        .unwrap_or_default();
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+            .filter(|v| !v.trim().is_empty())
+            .map(String::from)
+    };
+
+    Some(ToolCall {
+        kind: tool_kind(&tool.name),
+        file: field(&[
+            "target_file",
+            "targetFile",
+            "file_path",
+            "relative_workspace_path",
+            "relativeWorkspacePath",
+            "target_directory",
+            "path",
+        ]),
+        query: field(&["query", "search_term", "pattern", "glob_pattern"]),
+        description: field(&["instructions", "explanation"]),
+        edit: field(&["code_edit", "new_string", "contents", "codeEdit"]),
+        status: tool.status.clone(),
+        name: tool.name.clone(),
+    })
+}
+
+/// What a tool does, from its name.
+fn tool_kind(name: &str) -> ToolKind {
+    match name {
+        "edit_file" | "search_replace" | "multi_edit" | "write" | "create_file" | "delete_file"
+        | "reapply" | "apply_patch" | "edit_notebook" => ToolKind::Edit,
+        "read_file" | "list_dir" | "list_directory" => ToolKind::Read,
+        "codebase_search" | "grep_search" | "grep" | "file_search" | "glob_file_search"
+        | "web_search" => ToolKind::Search,
+        _ => ToolKind::Other,
+    }
+}
+
+/// Output and exit code of a terminal tool call's result.
+fn parse_terminal_result(result: String) -> (Option<String>, Option<i32>) {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&result) else {
+        return (Some(result), None);
+    };
+    let output = json
+        .get("output")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let exit_code = ["exitCode", "exitCodeV2"]
+        .iter()
+        .find_map(|key| json.get(key).and_then(serde_json::Value::as_i64))
+        .and_then(|code| i32::try_from(code).ok());
+    (output, exit_code)
+}
+
+/// Parses the workspace a bubble was written in, from its `workspaceUris`.
+#[must_use]
+pub fn parse_bubble_workspace(data: &[u8]) -> Option<WorkspaceInfo> {
+    let json: serde_json::Value = serde_json::from_slice(data).ok()?;
+    let workspace_uri = json
+        .get("workspaceUris")
+        .and_then(|v| v.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|v| v.as_str())?;
+
+    let mut ws_info = WorkspaceInfo::from_uri(workspace_uri);
+    ws_info.cursor_path = json
+        .get("workspaceProjectDir")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    Some(ws_info)
+}
+
 /// Parses a composer from raw JSON bytes.
 ///
 /// # Errors
@@ -114,8 +464,8 @@ pub fn parse_composer(data: &[u8]) -> Result<RawComposer> {
 }
 
 /// Parses datetime from various formats used by Cursor.
-fn parse_datetime(value: &Option<String>) -> Option<DateTime<Utc>> {
-    let s = value.as_ref()?;
+fn parse_datetime(value: Option<&String>) -> Option<DateTime<Utc>> {
+    let s = value?;
 
     // Try ISO 8601 format first
     if let Ok(dt) = s.parse::<DateTime<Utc>>() {
@@ -133,6 +483,7 @@ fn parse_datetime(value: &Option<String>) -> Option<DateTime<Utc>> {
 /// Extracts conversation ID from a bubble key.
 ///
 /// Key format: `bubbleId:{composer_id}:{bubble_id}`
+#[must_use]
 pub fn extract_conversation_id(key: &str) -> Option<&str> {
     let stripped = key.strip_prefix("bubbleId:")?;
     stripped.split(':').next()
@@ -141,6 +492,7 @@ pub fn extract_conversation_id(key: &str) -> Option<&str> {
 /// Extracts composer ID from a composer key.
 ///
 /// Key format: `composerData:{composer_id}`
+#[must_use]
 pub fn extract_composer_id(key: &str) -> Option<&str> {
     key.strip_prefix("composerData:")
 }
@@ -163,13 +515,157 @@ mod tests {
 
     #[test]
     fn test_parse_datetime_iso() {
-        let dt = parse_datetime(&Some("2025-12-01T16:25:48.612Z".to_string()));
+        let dt = parse_datetime(Some(&"2025-12-01T16:25:48.612Z".to_string()));
         assert!(dt.is_some());
     }
 
     #[test]
     fn test_parse_datetime_millis() {
-        let dt = parse_datetime(&Some("1764561943374".to_string()));
+        let dt = parse_datetime(Some(&"1764561943374".to_string()));
         assert!(dt.is_some());
     }
+
+    #[test]
+    fn test_parse_bubble_reasoning_tokens() {
+        let tokens = |json: &str| {
+            let data = format!(r#"{{"type": 2, "bubbleId": "b1", "tokenCount": {json}}}"#);
+            parse_bubble(data.as_bytes()).unwrap().token_count
+        };
+
+        let plain = tokens(r#"{"inputTokens": 10, "outputTokens": 5}"#);
+        assert_eq!(plain.total(), 15);
+        assert_eq!(plain.reasoning_tokens, 0);
+
+        let named = tokens(r#"{"outputTokens": 5, "thinkingTokens": 7}"#);
+        assert_eq!(named.reasoning_tokens, 7);
+
+        let details = tokens(r#"{"outputTokensDetails": {"reasoningTokens": 3}}"#);
+        assert_eq!(details.reasoning_tokens, 3);
+    }
+
+    #[test]
+    fn test_parse_bubble_terminal_command() {
+        let data = br#"{"type": 2, "bubbleId": "b1", "toolFormerData": {
+            "name": "run_terminal_cmd",
+            "rawArgs": "{\"command\": \"cargo test\", \"is_background\": false}",
+            "result": "{\"output\": \"ok\", \"exitCodeV2\": 101}"
+        }}"#;
+        let bubble = parse_bubble(data).unwrap();
+        assert_eq!(
+            bubble.commands,
+            [TerminalCommand {
+                command: "cargo test".into(),
+                cwd: None,
+                output: Some("ok".into()),
+                exit_code: Some(101),
+            }]
+        );
+
+        let other = br#"{"type": 2, "bubbleId": "b2", "toolFormerData": {
+            "name": "read_file", "rawArgs": "{\"command\": \"x\"}"
+        }}"#;
+        assert!(parse_bubble(other).unwrap().commands.is_empty());
+        assert!(bubble.tool_calls.is_empty());
+    }
+
+    #[test]
+    fn test_parse_bubble_tool_call() {
+        let data = br#"{"type": 2, "bubbleId": "b1", "text": "", "toolFormerData": {
+            "name": "edit_file", "status": "completed",
+            "rawArgs": "{\"target_file\": \"src/lib.rs\", \"instructions\": \"Add the error type\", \"code_edit\": \"pub enum Error {}\"}"
+        }}"#;
+        let bubble = parse_bubble(data).unwrap();
+        assert!(!bubble.is_empty());
+        assert_eq!(
+            bubble.tool_calls,
+            [ToolCall {
+                name: "edit_file".into(),
+                kind: ToolKind::Edit,
+                file: Some("src/lib.rs".into()),
+                query: None,
+                description: Some("Add the error type".into()),
+                edit: Some("pub enum Error {}".into()),
+                status: Some("completed".into()),
+            }]
+        );
+
+        let search = br#"{"type": 2, "bubbleId": "b2", "toolFormerData": {
+            "name": "codebase_search", "params": "{\"query\": \"where is the lock taken\"}"
+        }}"#;
+        let call = &parse_bubble(search).unwrap().tool_calls[0];
+        assert_eq!(call.kind, ToolKind::Search);
+        assert_eq!(call.query.as_deref(), Some("where is the lock taken"));
+    }
+
+    #[test]
+    fn test_parse_bubble_context() {
+        let data = br#"{"type": 1, "bubbleId": "b1", "text": "Why does this panic?",
+            "context": {
+                "fileSelections": [{"uri": {"fsPath": "/home/me/app/Cargo.toml"}}],
+                "selections": [{"uri": {"fsPath": "/home/me/app/src/foo.rs"}, "text": "...",
+                    "range": {"selectionStartLineNumber": 40, "positionLineNumber": 10}}],
+                "folderSelections": [{"relativePath": "docs"}]
+            },
+            "attachedCodeChunks": [
+                {"relativeWorkspacePath": "src/bar.rs", "startLineNumber": 5, "lines": ["a", "b", "c"]}
+            ],
+            "fileSelections": [{"uri": {"fsPath": "/home/me/app/Cargo.toml"}}]
+        }"#;
+        let context = parse_bubble(data).unwrap().context;
+
+        assert_eq!(context.files.len(), 3);
+        assert_eq!(
+            context.files[1],
+            ContextFile {
+                path: "/home/me/app/src/foo.rs".into(),
+                start_line: Some(10),
+                end_line: Some(40),
+            }
+        );
+        assert_eq!(
+            context.to_string(),
+            "/home/me/app/Cargo.toml, /home/me/app/src/foo.rs lines 10-40, src/bar.rs lines 5-7, docs/"
+        );
+
+        let plain = parse_bubble(br#"{"type": 1, "bubbleId": "b2", "text": "hi"}"#).unwrap();
+        assert!(plain.context.is_empty());
+    }
+
+    #[test]
+    fn test_parse_bubble_diagnostics() {
+        let data = br#"{"type": 2, "bubbleId": "b1",
+            "lints": null,
+            "approximateLintErrors": [
+                {"message": "unused variable `x`", "severity": "Warning",
+                 "relativeWorkspacePath": "src/main.rs", "startLineNumber": 3}
+            ],
+            "multiFileLinterErrors": [
+                {"relativeWorkspacePath": "src/lib.rs", "errors": [
+                    {"message": "expected `;`", "severity": "error",
+                     "range": {"startPosition": {"line": 10, "column": 4}}}
+                ]},
+                {"relativeWorkspacePath": "src/main.rs", "errors": [
+                    {"message": "unused variable `x`", "severity": "warning", "startLineNumber": 3}
+                ]}
+            ]}"#;
+
+        let diagnostics = parse_bubble(data).unwrap().diagnostics;
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    message: "unused variable `x`".into(),
+                    file: Some("src/main.rs".into()),
+                    line: Some(3),
+                    severity: Some("warning".into()),
+                },
+                Diagnostic {
+                    message: "expected `;`".into(),
+                    file: Some("src/lib.rs".into()),
+                    line: Some(10),
+                    severity: Some("error".into()),
+                },
+            ]
+        );
+    }
 }
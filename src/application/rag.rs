@@ -0,0 +1,197 @@
+//! Chunked export for retrieval pipelines.
+//!
+//! Splits every message into overlapping, token-bounded chunks carrying
+//! the metadata a vector store needs to filter and cite them. Chunks never
+//! span messages, so each one has a single role and timestamp.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{AppError, Conversation, Result};
+
+use super::summarizer::CHARS_PER_TOKEN;
+
+/// Chunk size settings, in estimated tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkOptions {
+    /// Upper bound for a chunk.
+    pub chunk_tokens: usize,
+    /// Tokens repeated from the end of the previous chunk of the same message.
+    pub overlap: usize,
+}
+
+impl ChunkOptions {
+    /// Check that chunks can make progress.
+    ///
+    /// # Errors
+    /// Returns error if `chunk_tokens` is zero or `overlap` is not smaller.
+    pub fn validate(self) -> Result<Self> {
+        if self.chunk_tokens == 0 || self.overlap >= self.chunk_tokens {
+            return Err(AppError::Config {
+                message: format!(
+                    "--overlap ({}) must be smaller than --chunk-tokens ({})",
+                    self.overlap, self.chunk_tokens
+                ),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// One JSONL record of the RAG export.
+#[derive(Debug, Clone, Serialize)]
+pub struct RagChunk {
+    /// Stable ID: `<conversation>:<bubble>:<chunk>`.
+    pub id: String,
+    /// Cursor composer ID.
+    pub conversation_id: String,
+    /// Conversation title.
+    pub conversation_title: String,
+    /// Project the conversation belongs to, if known.
+    pub workspace: Option<String>,
+    /// Model used in the conversation.
+    pub model: String,
+    /// `user`, `assistant` or `unknown`.
+    pub role: String,
+    /// Source bubble.
+    pub bubble_id: String,
+    /// 1-based position of the message in the conversation.
+    pub message_index: usize,
+    /// 0-based position of the chunk in the message.
+    pub chunk_index: usize,
+    /// When the message was sent.
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the conversation started.
+    pub conversation_created_at: Option<DateTime<Utc>>,
+    /// Estimated token count of `text`.
+    pub tokens: usize,
+    /// Chunk text.
+    pub text: String,
+}
+
+/// Split the messages of a conversation into chunks.
+#[must_use]
+pub fn chunk_conversation(
+    conv: &Conversation,
+    workspace: Option<&str>,
+    options: ChunkOptions,
+) -> Vec<RagChunk> {
+    let mut chunks = Vec::new();
+
+    for (index, bubble) in conv.bubbles.iter().enumerate() {
+        let text = bubble.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        for (chunk_index, piece) in split_text(text, options).into_iter().enumerate() {
+            chunks.push(RagChunk {
+                id: format!("{}:{}:{chunk_index}", conv.composer_id, bubble.bubble_id),
+                conversation_id: conv.composer_id.clone(),
+                conversation_title: conv.title.clone(),
+                workspace: workspace.map(str::to_string),
+                model: conv.model_config.model_name.clone(),
+                role: bubble.bubble_type.to_string().to_lowercase(),
+                bubble_id: bubble.bubble_id.clone(),
+                message_index: index + 1,
+                chunk_index,
+                created_at: bubble.created_at,
+                conversation_created_at: conv.created_at,
+                tokens: estimate_tokens(&piece),
+                text: piece,
+            });
+        }
+    }
+
+    chunks
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Split `text` at word boundaries into windows of at most `chunk_tokens`,
+/// each starting `overlap` tokens before the end of the previous one.
+///
+/// A single word longer than the window becomes its own chunk.
+fn split_text(text: &str, options: ChunkOptions) -> Vec<String> {
+    let max_chars = options.chunk_tokens * CHARS_PER_TOKEN;
+    let overlap_chars = options.overlap * CHARS_PER_TOKEN;
+
+    // Word spans as (start, end) byte offsets; whitespace between them is kept
+    let mut words = Vec::new();
+    let mut start = None;
+    for (pos, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(pos),
+            (true, Some(s)) => {
+                words.push((s, pos));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, text.len()));
+    }
+
+    let chars = |from: usize, to: usize| text[words[from].0..words[to].1].chars().count();
+
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < words.len() {
+        let mut last = first;
+        while last + 1 < words.len() && chars(first, last + 1) <= max_chars {
+            last += 1;
+        }
+        chunks.push(text[words[first].0..words[last].1].to_string());
+
+        if last + 1 == words.len() {
+            break;
+        }
+
+        // Step back from the end for the overlap, always moving forward
+        let mut next = last + 1;
+        while next - 1 > first && chars(next - 1, last) <= overlap_chars {
+            next -= 1;
+        }
+        first = next;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_text_windows_and_overlap() {
+        let options = ChunkOptions {
+            chunk_tokens: 5,
+            overlap: 3,
+        };
+        // 20 chars per window, up to 12 chars of overlap
+        let text = "aaaa bbbb cccc dddd eeee ffff gggg";
+        let chunks = split_text(text, options);
+
+        assert_eq!(
+            chunks,
+            [
+                "aaaa bbbb cccc dddd",
+                "cccc dddd eeee ffff",
+                "eeee ffff gggg"
+            ]
+        );
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 5));
+
+        assert_eq!(split_text("short", options), ["short"]);
+        assert_eq!(split_text(&"x".repeat(50), options).len(), 1);
+        assert!(ChunkOptions {
+            chunk_tokens: 4,
+            overlap: 4
+        }
+        .validate()
+        .is_err());
+    }
+}
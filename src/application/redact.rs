@@ -0,0 +1,261 @@
+//! Masking of sensitive values in chat text.
+//!
+//! Applied before conversation text leaves the machine (e.g. LLM
+//! summaries). Replaces e-mail addresses, well-known API key formats,
+//! `password=...` style assignments and long random-looking tokens.
+//!
+//! Exports can also apply a user-provided [`PathMap`] so internal paths and
+//! project names are replaced the same way across all conversations.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::domain::{AppError, Conversation, Result};
+
+/// Prefixes of well-known API keys and access tokens.
+const SECRET_PREFIXES: &[&str] = &[
+    "sk-",
+    "sk_live_",
+    "pk_live_",
+    "ghp_",
+    "gho_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "AIza",
+];
+
+/// Key names whose assigned value is masked (`api_key=...`, `token: ...`).
+const SECRET_KEYS: &[&str] = &["password", "passwd", "secret", "token", "api_key", "apikey"];
+
+/// Random-looking tokens at least this long are masked.
+const MIN_OPAQUE_TOKEN_LEN: usize = 32;
+
+/// Replace sensitive values in `text`, keeping whitespace and punctuation intact.
+#[must_use]
+pub fn redact_secrets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end_matches(char::is_whitespace);
+        out.push_str(&redact_word(word));
+        out.push_str(&piece[word.len()..]);
+    }
+
+    out
+}
+
+fn redact_word(word: &str) -> String {
+    let core = word.trim_matches(|c: char| "\"'`()[]{}<>,;.".contains(c));
+    if core.is_empty() {
+        return word.to_string();
+    }
+
+    let replacement = if is_email(core) {
+        "[EMAIL]".to_string()
+    } else if let Some(key) = secret_assignment(core) {
+        format!("{key}[SECRET]")
+    } else if is_secret_token(core) {
+        "[SECRET]".to_string()
+    } else {
+        return word.to_string();
+    };
+
+    word.replacen(core, &replacement, 1)
+}
+
+fn is_email(word: &str) -> bool {
+    word.split_once('@').is_some_and(|(user, domain)| {
+        !user.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+    })
+}
+
+/// For `key=value` / `key:value` with a secret-looking key, the `key=` part.
+fn secret_assignment(word: &str) -> Option<&str> {
+    let pos = word.find(['=', ':'])?;
+    let (key, value) = (&word[..pos], &word[pos + 1..]);
+    let key_lower = key.to_lowercase();
+
+    (value.trim_matches(['"', '\'']).len() >= 4
+        && SECRET_KEYS.iter().any(|k| key_lower.ends_with(k)))
+    .then(|| &word[..=pos])
+}
+
+fn is_secret_token(word: &str) -> bool {
+    if SECRET_PREFIXES
+        .iter()
+        .any(|p| word.starts_with(p) && word.len() >= p.len() + 12)
+    {
+        return true;
+    }
+
+    // Long opaque strings mixing letters and digits (hashes, tokens)
+    word.len() >= MIN_OPAQUE_TOKEN_LEN
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Literal replacements read from a mapping file, one per line:
+///
+/// ```text
+/// # Comments and blank lines are ignored
+/// /home/alice/work → $PROJECT
+/// acme-internal => client
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathMap {
+    /// `(from, to)` pairs, longest `from` first so nested paths win.
+    rules: Vec<(String, String)>,
+}
+
+impl PathMap {
+    /// Read a mapping file.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read or a line has no `→`/`=>`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+        content.parse().map_err(|message: String| AppError::Config {
+            message: format!("{}: {message}", path.display()),
+        })
+    }
+
+    /// Whether the map has no rules.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Apply every replacement to `text`.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+
+        // One left-to-right pass, so a replacement is never replaced again
+        'scan: while let Some(c) = rest.chars().next() {
+            for (from, to) in &self.rules {
+                if let Some(after) = rest.strip_prefix(from.as_str()) {
+                    out.push_str(to);
+                    rest = after;
+                    continue 'scan;
+                }
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        out
+    }
+
+    /// Apply the map to a conversation's title, messages, thinking, terminal
+    /// commands, tool calls, attachments, diagnostics and repository root.
+    pub fn apply_to_conversation(&self, conv: &mut Conversation) {
+        if self.is_empty() {
+            return;
+        }
+
+        conv.title = self.apply(&conv.title);
+        for bubble in &mut conv.bubbles {
+            bubble.text = self.apply(&bubble.text);
+            if let Some(thinking) = &mut bubble.thinking {
+                thinking.text = self.apply(&thinking.text);
+            }
+            for command in &mut bubble.commands {
+                command.command = self.apply(&command.command);
+                command.cwd = command.cwd.as_deref().map(|cwd| self.apply(cwd));
+                command.output = command.output.as_deref().map(|out| self.apply(out));
+            }
+            for call in &mut bubble.tool_calls {
+                call.file = call.file.as_deref().map(|file| self.apply(file));
+                call.description = call.description.as_deref().map(|d| self.apply(d));
+                call.edit = call.edit.as_deref().map(|edit| self.apply(edit));
+            }
+            for file in &mut bubble.context.files {
+                file.path = self.apply(&file.path);
+            }
+            for folder in &mut bubble.context.folders {
+                *folder = self.apply(folder);
+            }
+            for diagnostic in &mut bubble.diagnostics {
+                diagnostic.file = diagnostic.file.as_deref().map(|file| self.apply(file));
+            }
+        }
+        if let Some(git) = &mut conv.git {
+            git.repo_root = self.apply(&git.repo_root);
+        }
+    }
+}
+
+impl FromStr for PathMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut rules = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (from, to) = line
+                .split_once('→')
+                .or_else(|| line.split_once("=>"))
+                .ok_or_else(|| format!("line {}: expected 'from → to'", number + 1))?;
+            let from = from.trim();
+            if from.is_empty() {
+                return Err(format!("line {}: nothing to replace", number + 1));
+            }
+            rules.push((from.to_string(), to.trim().to_string()));
+        }
+
+        rules.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Ok(Self { rules })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        let text = "Mail me at dev@example.com, key sk-abcdefghijklmnopqrstuv\n\
+                    export API_KEY=hunter2hunter2 and PASSWORD: x\n\
+                    see src/main.rs for the path handling.";
+
+        assert_eq!(
+            redact_secrets(text),
+            "Mail me at [EMAIL], key [SECRET]\n\
+             export API_KEY=[SECRET] and PASSWORD: x\n\
+             see src/main.rs for the path handling."
+        );
+    }
+
+    #[test]
+    fn test_path_map_replaces_longest_first() {
+        let map: PathMap = "# shared transcripts\n\
+                            /home/alice/work → $PROJECT\n\
+                            /home/alice/work/acme-api => $PROJECT/api\n\
+                            AcmeCorp → Client\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            map.apply("cd /home/alice/work/acme-api && ls /home/alice/work; AcmeCorp"),
+            "cd $PROJECT/api && ls $PROJECT; Client"
+        );
+        assert!("no separator".parse::<PathMap>().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Step-through replay of a conversation (`replay`).
+//!
+//! Splits a conversation into exchanges, a user prompt with the replies
+//! that follow it, and works out how long to pause between them when the
+//! original timing is honored.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{Bubble, BubbleType, Conversation};
+
+/// Longest pause between exchanges, however long the original gap was.
+pub const MAX_PAUSE: Duration = Duration::from_secs(10);
+
+/// A user prompt and the replies to it.
+#[derive(Debug, Clone, Default)]
+pub struct Exchange<'a> {
+    /// The prompt; `None` for replies before the first user message.
+    pub prompt: Option<&'a Bubble>,
+    /// Assistant (and unknown) messages up to the next prompt.
+    pub replies: Vec<&'a Bubble>,
+}
+
+impl Exchange<'_> {
+    /// When the exchange started: its first timestamped message.
+    #[must_use]
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.prompt
+            .into_iter()
+            .chain(self.replies.iter().copied())
+            .find_map(|b| b.created_at)
+    }
+}
+
+/// Split a conversation into exchanges, in message order.
+#[must_use]
+pub fn exchanges(conv: &Conversation) -> Vec<Exchange<'_>> {
+    let mut exchanges: Vec<Exchange<'_>> = Vec::new();
+    for bubble in &conv.bubbles {
+        match (bubble.bubble_type, exchanges.last_mut()) {
+            (BubbleType::User, _) | (_, None) => {
+                let prompt = (bubble.bubble_type == BubbleType::User).then_some(bubble);
+                let replies = if prompt.is_some() {
+                    Vec::new()
+                } else {
+                    vec![bubble]
+                };
+                exchanges.push(Exchange { prompt, replies });
+            }
+            (_, Some(current)) => current.replies.push(bubble),
+        }
+    }
+    exchanges
+}
+
+/// Pause before `next`: the original gap divided by `speed`, capped at
+/// [`MAX_PAUSE`]. Zero when either exchange has no timestamp.
+#[must_use]
+pub fn pause_between(previous: &Exchange<'_>, next: &Exchange<'_>, speed: f64) -> Duration {
+    let (Some(start), Some(end)) = (previous.started_at(), next.started_at()) else {
+        return Duration::ZERO;
+    };
+    let gap = (end - start).to_std().unwrap_or_default();
+    Duration::try_from_secs_f64(gap.as_secs_f64() / speed)
+        .unwrap_or(MAX_PAUSE)
+        .min(MAX_PAUSE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ModelConfig;
+    use chrono::TimeZone;
+
+    fn bubble(id: &str, bubble_type: BubbleType, second: u32) -> Bubble {
+        Bubble {
+            bubble_id: id.into(),
+            bubble_type,
+            text: id.into(),
+            created_at: Some(Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, second).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exchanges_and_pauses() {
+        let conv = Conversation {
+            composer_id: "c1".into(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![
+                bubble("intro", BubbleType::Assistant, 0),
+                bubble("q1", BubbleType::User, 10),
+                bubble("a1", BubbleType::Assistant, 12),
+                bubble("a2", BubbleType::Assistant, 14),
+                bubble("q2", BubbleType::User, 50),
+            ],
+            git: None,
+            workspace: None,
+        };
+
+        let exchanges = exchanges(&conv);
+        let shape: Vec<_> = exchanges
+            .iter()
+            .map(|e| (e.prompt.map(|b| b.text.as_str()), e.replies.len()))
+            .collect();
+        assert_eq!(shape, [(None, 1), (Some("q1"), 2), (Some("q2"), 0)]);
+
+        assert_eq!(
+            pause_between(&exchanges[0], &exchanges[1], 2.0),
+            Duration::from_secs(5)
+        );
+        assert_eq!(pause_between(&exchanges[1], &exchanges[2], 1.0), MAX_PAUSE);
+    }
+}
@@ -1,23 +1,93 @@
 //! Restore service for recovering chat history.
 //!
 //! Detects when Cursor's database was cleared and restores
-//! chat history from local backup storage.
+//! chat history from local backup storage. Also archives conversations
+//! and erases them from Cursor once they are kept locally, one at a time
+//! or pruned in bulk.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crate::domain::{AppConfig, AppError, Result};
-use crate::infrastructure::{find_cursor_config_dir, CursorWriter, LocalStorage};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{AppConfig, AppError, Conversation, OperationKind, ResetEvent, Result};
+use crate::infrastructure::{
+    find_cursor_config_dir, fire_hook, global_state_db_path, is_cursor_running, CursorReset,
+    CursorWriter, LocalStorage, StateDbReader,
+};
+
+use super::audit;
+use super::summarizer::resolve_conversation;
+use super::sync_service::SyncService;
+
+/// How long to wait for Cursor to exit after asking it to close.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Called before each conversation is restored with the number done so
+/// far, the total and the conversation's title, and once more at the end.
+type ProgressFn = Box<dyn Fn(usize, usize, &str) + Send + Sync>;
 
 /// Service for restoring chat history to Cursor.
 pub struct RestoreService {
     config: AppConfig,
+    close_cursor: bool,
+    progress: Option<ProgressFn>,
+    /// Process check, replaced in tests.
+    cursor_running: fn() -> bool,
 }
 
 impl RestoreService {
     /// Create a new restore service.
     #[must_use]
     pub const fn new(config: AppConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            close_cursor: false,
+            progress: None,
+            cursor_running: is_cursor_running,
+        }
+    }
+
+    /// Close a running Cursor before writing instead of refusing to restore.
+    #[must_use]
+    pub const fn with_close_cursor(mut self, enabled: bool) -> Self {
+        self.close_cursor = enabled;
+        self
+    }
+
+    /// Report progress through `progress` while restoring.
+    #[must_use]
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(usize, usize, &str) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Make sure Cursor is not running before its database is written.
+    ///
+    /// A running Cursor may overwrite restored rows or corrupt the database.
+    ///
+    /// `action` names the write in messages (`restoring`, `deleting`...).
+    fn ensure_cursor_closed(&self, action: &str) -> Result<()> {
+        if !(self.cursor_running)() {
+            return Ok(());
+        }
+
+        if !self.close_cursor {
+            return Err(AppError::Config {
+                message: format!(
+                    "Cursor is running; {action} now could be overwritten or corrupt its \
+                     database. Close Cursor or use --close-cursor."
+                ),
+            });
+        }
+
+        tracing::info!("Closing Cursor before {action}");
+        CursorReset::new(false).kill_cursor()?;
+        Self::wait_for_cursor_exit(CLOSE_TIMEOUT, Duration::from_millis(500))
     }
 
     /// Get the path to Cursor's global state database.
@@ -26,57 +96,165 @@ impl RestoreService {
         Ok(config_dir.join("User/globalStorage/state.vscdb"))
     }
 
+    /// Wait until Cursor recreates its global database after a reset.
+    ///
+    /// Polls every `poll` until the database exists.
+    ///
+    /// # Errors
+    /// Returns error if the database does not appear within `timeout`.
+    pub fn wait_for_cursor_db(timeout: Duration, poll: Duration) -> Result<PathBuf> {
+        let db_path = global_state_db_path()?;
+        let start = Instant::now();
+
+        while !db_path.exists() {
+            if start.elapsed() >= timeout {
+                return Err(AppError::Config {
+                    message: format!(
+                        "Cursor database was not created within {}s: {}",
+                        timeout.as_secs(),
+                        db_path.display()
+                    ),
+                });
+            }
+            std::thread::sleep(poll);
+        }
+
+        tracing::info!(path = %db_path.display(), "Cursor database detected");
+        Ok(db_path)
+    }
+
+    /// Wait until Cursor is no longer running.
+    ///
+    /// # Errors
+    /// Returns error if Cursor is still running after `timeout`.
+    pub fn wait_for_cursor_exit(timeout: Duration, poll: Duration) -> Result<()> {
+        let start = Instant::now();
+
+        while is_cursor_running() {
+            if start.elapsed() >= timeout {
+                return Err(AppError::Config {
+                    message: format!("Cursor is still running after {}s", timeout.as_secs()),
+                });
+            }
+            std::thread::sleep(poll);
+        }
+
+        Ok(())
+    }
+
     /// Check if Cursor's database appears to have been reset.
     ///
     /// Returns true if local storage has more conversations than Cursor.
+    ///
+    /// # Errors
+    /// Returns error if Cursor's database or local storage cannot be accessed.
     pub fn needs_restore(&self) -> Result<bool> {
+        Ok(self.detect_reset()?.is_some())
+    }
+
+    /// Detect a Cursor reset by comparing conversation counts.
+    ///
+    /// Returns the reset event if Cursor has lost most of the locally stored conversations.
+    ///
+    /// # Errors
+    /// Returns error if Cursor's database or local storage cannot be accessed.
+    pub fn detect_reset(&self) -> Result<Option<ResetEvent>> {
         let storage_path = self.config.storage_db_path();
         if !storage_path.exists() {
-            return Ok(false);
+            return Ok(None);
         }
 
+        let local_storage = LocalStorage::open_read_only(&storage_path)?;
+        // Conversations archived out of Cursor are expected to be missing
+        let removed = local_storage
+            .archived()?
+            .iter()
+            .filter(|a| a.removed_from_cursor)
+            .count();
+        let local_count = local_storage
+            .get_conversation_count()?
+            .saturating_sub(removed);
+
         let cursor_db = Self::cursor_db_path()?;
         if !cursor_db.exists() {
-            return Ok(true); // Cursor DB doesn't exist, needs restore
+            // Cursor DB doesn't exist, needs restore
+            return Ok(Some(ResetEvent::new(local_count, 0)));
         }
 
-        // Compare conversation counts
-        let local_storage = LocalStorage::open(&storage_path)?;
-        let local_count = local_storage.get_conversation_count()?;
-
-        let cursor_writer = CursorWriter::open(&cursor_db)?;
-        let cursor_count = cursor_writer.conversation_count()?;
+        let cursor_count = cursor_conversation_count(&cursor_db)?;
 
         // If local has significantly more, Cursor was probably reset
-        let needs = local_count > 0 && cursor_count < local_count / 2;
-
-        if needs {
+        if local_count > 0 && cursor_count < local_count / 2 {
             tracing::info!(
                 local = local_count,
                 cursor = cursor_count,
                 "Cursor appears to have been reset"
             );
+            return Ok(Some(ResetEvent::new(local_count, cursor_count)));
         }
 
-        Ok(needs)
+        Ok(None)
     }
 
     /// Check if Cursor's database is completely empty.
+    ///
+    /// # Errors
+    /// Returns error if Cursor's database or local storage cannot be accessed.
     pub fn cursor_is_empty(&self) -> Result<bool> {
         let cursor_db = Self::cursor_db_path()?;
         if !cursor_db.exists() {
             return Ok(true);
         }
 
-        let cursor_writer = CursorWriter::open(&cursor_db)?;
-        cursor_writer.is_empty()
+        Ok(cursor_conversation_count(&cursor_db)? == 0)
     }
 
-    /// Restore all conversations from local storage to Cursor.
+    /// Restore all conversations from local storage to Cursor, except those
+    /// archived out of it.
     ///
     /// # Errors
     /// Returns error if restore fails.
     pub fn restore_all(&self) -> Result<RestoreResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Restore,
+            || self.run_restore(&[]),
+            |result| Some(result.summary("all")),
+        )
+        .inspect(|result| self.run_restore_hook(result, &[]))
+    }
+
+    /// Restore specific conversations by ID.
+    ///
+    /// # Errors
+    /// Returns error if Cursor's database or local storage cannot be accessed.
+    pub fn restore_by_ids(&self, ids: &[String]) -> Result<RestoreResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Restore,
+            || self.run_restore(ids),
+            |result| Some(result.summary(&ids.join(", "))),
+        )
+        .inspect(|result| self.run_restore_hook(result, ids))
+    }
+
+    /// Run the `on_restore` hook; `ids` is empty for a full restore.
+    fn run_restore_hook(&self, result: &RestoreResult, ids: &[String]) {
+        let payload = serde_json::json!({
+            "ids": ids,
+            "restored_conversations": result.restored_conversations,
+            "restored_messages": result.restored_messages,
+            "cursor_db_path": result.cursor_db_path,
+        });
+        fire_hook(self.config.hooks.on_restore.as_deref(), "restore", &payload);
+    }
+
+    /// Restore the conversations matching `ids`, or all when empty, and
+    /// write a report of the outcome for each.
+    ///
+    /// A full restore skips conversations archived out of Cursor; restoring
+    /// one by ID brings it back and clears its archived mark.
+    fn run_restore(&self, ids: &[String]) -> Result<RestoreResult> {
         let storage_path = self.config.storage_db_path();
         if !storage_path.exists() {
             return Err(AppError::Config {
@@ -84,6 +262,7 @@ impl RestoreService {
             });
         }
 
+        self.ensure_cursor_closed("restoring")?;
         let cursor_db = Self::cursor_db_path()?;
 
         tracing::info!(
@@ -91,111 +270,430 @@ impl RestoreService {
             "Starting restore to Cursor"
         );
 
-        let local_storage = LocalStorage::open(&storage_path)?;
+        let local_storage = LocalStorage::open_read_only(&storage_path)?;
         let cursor_writer = CursorWriter::open(&cursor_db)?;
 
-        // Get all conversations from local storage
-        let conversations = local_storage.get_conversations(None)?;
-
-        let mut restored_count = 0;
-        let mut message_count = 0;
+        // Partial IDs match anywhere in the composer ID
+        let conversations: Vec<Conversation> = local_storage
+            .get_conversations(None)?
+            .into_iter()
+            .filter(|conv| ids.is_empty() || ids.iter().any(|id| conv.composer_id.contains(id)))
+            .collect();
+
+        let archived: Vec<String> = local_storage
+            .archived()?
+            .into_iter()
+            .filter(|a| a.removed_from_cursor)
+            .map(|a| a.composer_id)
+            .collect();
+
+        let mut report = RestoreReport::new(ids, &cursor_db);
+        for id in ids {
+            if !conversations
+                .iter()
+                .any(|conv| conv.composer_id.contains(id))
+            {
+                report.push(RestoreEntry {
+                    composer_id: id.clone(),
+                    title: String::new(),
+                    messages: 0,
+                    status: RestoreStatus::Skipped,
+                    reason: Some("no conversation in local storage matches this ID".into()),
+                });
+            }
+        }
 
-        for conv in &conversations {
-            match cursor_writer.restore_conversation(conv) {
-                Ok(()) => {
-                    restored_count += 1;
-                    message_count += conv.bubbles.len();
-                }
+        for (done, conv) in conversations.iter().enumerate() {
+            if let Some(progress) = &self.progress {
+                progress(done, conversations.len(), &conv.title);
+            }
+            if ids.is_empty() && archived.contains(&conv.composer_id) {
+                report.push(RestoreEntry {
+                    composer_id: conv.composer_id.clone(),
+                    title: conv.title.clone(),
+                    messages: conv.bubbles.len(),
+                    status: RestoreStatus::Skipped,
+                    reason: Some("archived; restore it by ID to bring it back".into()),
+                });
+                continue;
+            }
+            let (status, reason) = match cursor_writer.restore_conversation(conv) {
+                Ok(()) => (RestoreStatus::Restored, None),
                 Err(e) => {
                     tracing::warn!(
-                        composer_id = &conv.composer_id[..8],
+                        composer_id = conv.short_id(),
                         error = %e,
                         "Failed to restore conversation"
                     );
+                    (RestoreStatus::Failed, Some(e.to_string()))
                 }
-            }
+            };
+            report.push(RestoreEntry {
+                composer_id: conv.composer_id.clone(),
+                title: conv.title.clone(),
+                messages: conv.bubbles.len(),
+                status,
+                reason,
+            });
         }
+        if let Some(progress) = &self.progress {
+            progress(conversations.len(), conversations.len(), "");
+        }
+
+        report.finished_at = Utc::now();
+        let report_path = self.write_report(&report);
+        self.unarchive_restored(&report, &archived);
 
         tracing::info!(
-            restored = restored_count,
-            messages = message_count,
+            restored = report.restored,
+            failed = report.failed,
+            skipped = report.skipped,
             "Restore completed"
         );
 
         Ok(RestoreResult {
-            restored_conversations: restored_count,
-            restored_messages: message_count,
+            restored_conversations: report.restored,
+            restored_messages: report.restored_messages(),
+            failed_conversations: report.failed,
+            skipped: report.skipped,
             cursor_db_path: cursor_db,
+            report_path,
         })
     }
 
-    /// Restore specific conversations by ID.
-    pub fn restore_by_ids(&self, ids: &[String]) -> Result<RestoreResult> {
-        let storage_path = self.config.storage_db_path();
-        if !storage_path.exists() {
-            return Err(AppError::Config {
-                message: "No local storage found.".into(),
-            });
+    /// Clear the archived mark of the conversations archived out of Cursor
+    /// that `report` restored. A mark that cannot be cleared does not fail
+    /// the restore.
+    fn unarchive_restored(&self, report: &RestoreReport, archived: &[String]) {
+        let restored: Vec<&str> = report
+            .conversations
+            .iter()
+            .filter(|e| e.status == RestoreStatus::Restored && archived.contains(&e.composer_id))
+            .map(|e| e.composer_id.as_str())
+            .collect();
+        if restored.is_empty() {
+            return;
         }
 
-        let cursor_db = Self::cursor_db_path()?;
-        let local_storage = LocalStorage::open(&storage_path)?;
-        let cursor_writer = CursorWriter::open(&cursor_db)?;
+        let cleared = LocalStorage::open(&self.config.storage_db_path()).and_then(|storage| {
+            restored
+                .iter()
+                .try_for_each(|id| storage.unarchive(id).map(|_| ()))
+        });
+        if let Err(e) = cleared {
+            tracing::warn!(error = %e, "Failed to clear archived marks");
+        }
+    }
 
-        let conversations = local_storage.get_conversations(None)?;
+    /// Write `report` to `restore-report-<timestamp>.json` in the data
+    /// directory. A report that cannot be written does not fail the restore.
+    fn write_report(&self, report: &RestoreReport) -> Option<PathBuf> {
+        let path = self.config.data_dir().join(format!(
+            "restore-report-{}.json",
+            report.started_at.format("%Y%m%d-%H%M%S")
+        ));
+        let written = serde_json::to_vec_pretty(report)
+            .map_err(AppError::json_parse)
+            .and_then(|json| {
+                std::fs::write(&path, json)
+                    .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))
+            });
+        match written {
+            Ok(()) => Some(path),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to write restore report");
+                None
+            }
+        }
+    }
 
-        let mut restored_count = 0;
-        let mut message_count = 0;
+    /// Erase a conversation from Cursor's database, keeping the local copy.
+    ///
+    /// Syncs first and refuses to delete anything that did not make it into
+    /// local storage. `id` may be a partial composer ID but must be unambiguous.
+    ///
+    /// # Errors
+    /// Returns error if the ID matches zero or several conversations, Cursor
+    /// is running, or the conversation could not be archived.
+    pub fn delete_from_cursor(&self, id: &str) -> Result<DeleteResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Delete,
+            || self.run_delete_from_cursor(id),
+            |result| {
+                Some((
+                    1,
+                    format!(
+                        "{} ({} keys removed)",
+                        result.composer_id, result.removed_keys
+                    ),
+                ))
+            },
+        )
+    }
 
-        for conv in &conversations {
-            // Check if this conversation matches any of the requested IDs
-            let matches = ids.iter().any(|id| {
-                conv.composer_id.starts_with(id) || conv.composer_id.contains(id)
-            });
+    fn run_delete_from_cursor(&self, id: &str) -> Result<DeleteResult> {
+        let cursor_db = Self::cursor_db_path()?;
+        if !cursor_db.exists() {
+            return Err(AppError::DatabaseNotFound { path: cursor_db });
+        }
 
-            if matches {
-                match cursor_writer.restore_conversation(conv) {
-                    Ok(()) => {
-                        restored_count += 1;
-                        message_count += conv.bubbles.len();
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            composer_id = &conv.composer_id[..8],
-                            error = %e,
-                            "Failed to restore conversation"
-                        );
-                    }
+        // Looked up read-only: Cursor may still be running
+        let composer_id = {
+            let matches = StateDbReader::open(&cursor_db)?.find_composer_ids(id)?;
+            match matches.as_slice() {
+                [] => {
+                    return Err(AppError::InvalidData {
+                        message: format!("Conversation not found in Cursor: {id}"),
+                    });
+                }
+                [single] => single.clone(),
+                _ if matches.iter().any(|m| m == id) => id.to_string(),
+                _ => {
+                    return Err(AppError::InvalidData {
+                        message: format!(
+                            "'{id}' matches {} conversations; use a longer ID",
+                            matches.len()
+                        ),
+                    });
                 }
             }
+        };
+
+        // Close Cursor before syncing so the archive has its final state
+        self.ensure_cursor_closed("deleting")?;
+        SyncService::new(self.config.clone())?.sync()?;
+
+        let local_storage = LocalStorage::open(&self.config.storage_db_path())?;
+        if local_storage.get_conversation_hash(&composer_id)?.is_none() {
+            return Err(AppError::Sync {
+                message: format!(
+                    "Conversation {composer_id} is not in local storage; refusing to delete it"
+                ),
+            });
         }
+        let archived_messages = local_storage.get_bubbles(&composer_id)?.len();
 
-        Ok(RestoreResult {
-            restored_conversations: restored_count,
-            restored_messages: message_count,
-            cursor_db_path: cursor_db,
+        let removed_keys = CursorWriter::open(&cursor_db)?.delete_conversation(&composer_id)?;
+
+        tracing::info!(
+            composer_id = %composer_id,
+            removed_keys,
+            archived_messages,
+            "Deleted conversation from Cursor"
+        );
+
+        Ok(DeleteResult {
+            composer_id,
+            removed_keys,
+            archived_messages,
         })
     }
 
-    /// Auto-restore if needed (called by daemon).
+    /// Archive a conversation: sync it, check it is in local storage and
+    /// mark it archived. With `delete`, also erase it from Cursor's
+    /// database (see [`Self::delete_from_cursor`]); `restore` brings it back.
     ///
-    /// Returns true if restore was performed.
-    pub fn auto_restore_if_needed(&self) -> Result<bool> {
-        if !self.needs_restore()? {
-            return Ok(false);
+    /// # Errors
+    /// Returns error if the ID matches zero or several conversations, the
+    /// sync fails, or (with `delete`) Cursor is running.
+    pub fn archive(&self, id: &str, delete: bool) -> Result<ArchiveResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Archive,
+            || self.run_archive(id, delete),
+            |result| {
+                let detail = if result.removed_keys.is_some() {
+                    format!("{} (removed from Cursor)", result.composer_id)
+                } else {
+                    result.composer_id.clone()
+                };
+                Some((1, detail))
+            },
+        )
+    }
+
+    fn run_archive(&self, id: &str, delete: bool) -> Result<ArchiveResult> {
+        if delete {
+            let deleted = self.run_delete_from_cursor(id)?;
+            let storage = LocalStorage::open(&self.config.storage_db_path())?;
+            storage.set_archived(&deleted.composer_id, true)?;
+            let title = storage
+                .get_conversation(&deleted.composer_id)?
+                .map(|conv| conv.title)
+                .unwrap_or_default();
+            return Ok(ArchiveResult {
+                composer_id: deleted.composer_id,
+                title,
+                archived_messages: deleted.archived_messages,
+                removed_keys: Some(deleted.removed_keys),
+            });
         }
 
-        tracing::info!("Auto-restore triggered - Cursor database appears reset");
+        SyncService::new(self.config.clone())?.sync()?;
+        let storage = LocalStorage::open(&self.config.storage_db_path())?;
+        let conv = resolve_conversation(&storage, id)?;
+        storage.set_archived(&conv.composer_id, false)?;
+
+        tracing::info!(composer_id = %conv.composer_id, "Archived conversation");
+
+        Ok(ArchiveResult {
+            archived_messages: conv.bubbles.len(),
+            composer_id: conv.composer_id,
+            title: conv.title,
+            removed_keys: None,
+        })
+    }
+
+    /// Erase the conversations matching `filter` from Cursor's database and
+    /// reclaim the space, keeping them in local storage as archived.
+    ///
+    /// Cursor is synced first; conversations missing from local storage
+    /// are never removed. With `dry_run`, nothing is changed or logged.
+    ///
+    /// # Errors
+    /// Returns error if an ID matches zero or several conversations, the
+    /// sync fails, or Cursor is running.
+    pub fn prune(&self, filter: &PruneFilter, dry_run: bool) -> Result<PruneResult> {
+        if dry_run {
+            return self.run_prune(filter, true);
+        }
+        audit::recorded(
+            &self.config,
+            OperationKind::Prune,
+            || self.run_prune(filter, false),
+            |result| {
+                Some((
+                    result.conversations.len(),
+                    format!(
+                        "Cursor: {} conversations, {} keys",
+                        result.conversations.len(),
+                        result.removed_keys
+                    ),
+                ))
+            },
+        )
+    }
+
+    fn run_prune(&self, filter: &PruneFilter, dry_run: bool) -> Result<PruneResult> {
+        let cursor_db = Self::cursor_db_path()?;
+        if !cursor_db.exists() {
+            return Err(AppError::DatabaseNotFound { path: cursor_db });
+        }
+
+        if !dry_run {
+            self.ensure_cursor_closed("pruning")?;
+        }
+        SyncService::new(self.config.clone())?.sync()?;
+
+        let footprints = StateDbReader::open(&cursor_db)?.conversation_footprints()?;
+        let activity = LocalStorage::open_read_only(&self.config.storage_db_path())?
+            .conversation_activity()?;
+
+        let mut selected: Vec<&String> = if filter.ids.is_empty() {
+            let cutoff = filter.older_than.map(|age| Utc::now() - age);
+            footprints
+                .keys()
+                .filter(|id| {
+                    let last = activity.get(*id).and_then(|a| a.last_activity);
+                    matches!((cutoff, last), (Some(cutoff), Some(last)) if last < cutoff)
+                })
+                .collect()
+        } else {
+            filter
+                .ids
+                .iter()
+                .map(|id| match_composer_id(footprints.keys(), id))
+                .collect::<Result<_>>()?
+        };
+        selected.sort();
+        selected.dedup();
+
+        let mut result = PruneResult {
+            dry_run,
+            ..PruneResult::default()
+        };
+        for id in selected {
+            let Some(stored) = activity.get(id) else {
+                result.not_synced.push(id.clone());
+                continue;
+            };
+            let footprint = footprints.get(id).copied().unwrap_or_default();
+            result.conversations.push(PruneEntry {
+                composer_id: id.clone(),
+                title: stored.title.clone(),
+                last_activity: stored.last_activity,
+                keys: footprint.keys,
+                bytes: footprint.bytes,
+            });
+        }
+        result.conversations.sort_by_key(|e| e.last_activity);
 
-        let result = self.restore_all()?;
+        result.size_before = db_file_size(&cursor_db);
+        if dry_run || result.conversations.is_empty() {
+            result.size_after = result.size_before;
+            return Ok(result);
+        }
+
+        let ids: Vec<String> = result
+            .conversations
+            .iter()
+            .map(|e| e.composer_id.clone())
+            .collect();
+        let writer = CursorWriter::open(&cursor_db)?;
+        result.removed_keys = writer.delete_conversations(&ids)?;
+
+        let storage = LocalStorage::open(&self.config.storage_db_path())?;
+        for id in &ids {
+            storage.set_archived(id, true)?;
+        }
+
+        if let Err(e) = writer.vacuum() {
+            tracing::warn!(error = %e, "Failed to vacuum Cursor's database");
+        }
+        result.size_after = db_file_size(&cursor_db);
 
         tracing::info!(
-            conversations = result.restored_conversations,
-            messages = result.restored_messages,
-            "Auto-restore completed"
+            conversations = ids.len(),
+            removed_keys = result.removed_keys,
+            size_before = result.size_before,
+            size_after = result.size_after,
+            "Pruned Cursor's database"
         );
 
-        Ok(true)
+        Ok(result)
+    }
+
+    /// Auto-restore if needed (called by daemon).
+    ///
+    /// Returns the detected reset event; `restored` tells whether the
+    /// automatic restore succeeded.
+    ///
+    /// # Errors
+    /// Returns error if Cursor's database or local storage cannot be accessed.
+    pub fn auto_restore_if_needed(&self) -> Result<Option<ResetEvent>> {
+        let Some(event) = self.detect_reset()? else {
+            return Ok(None);
+        };
+
+        tracing::info!("Auto-restore triggered - Cursor database appears reset");
+
+        match self.restore_all() {
+            Ok(result) => {
+                tracing::info!(
+                    conversations = result.restored_conversations,
+                    messages = result.restored_messages,
+                    failed = result.failed_conversations,
+                    report = ?result.report_path,
+                    "Auto-restore completed"
+                );
+                Ok(Some(event.restored()))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Auto-restore failed");
+                Ok(Some(event.restore_failed(&e)))
+            }
+        }
     }
 }
 
@@ -206,18 +704,312 @@ pub struct RestoreResult {
     pub restored_conversations: usize,
     /// Number of messages restored.
     pub restored_messages: usize,
+    /// Conversations that could not be written to Cursor.
+    pub failed_conversations: usize,
+    /// Requested IDs that matched nothing in local storage, and archived
+    /// conversations left out of a full restore.
+    pub skipped: usize,
     /// Path to Cursor's database.
     pub cursor_db_path: PathBuf,
+    /// Report of the restore, unless it could not be written.
+    pub report_path: Option<PathBuf>,
+}
+
+impl RestoreResult {
+    /// Audit log entry for restoring `scope`.
+    fn summary(&self, scope: &str) -> (usize, String) {
+        (
+            self.restored_conversations,
+            format!(
+                "{scope}: {} conversations, {} messages",
+                self.restored_conversations, self.restored_messages
+            ),
+        )
+    }
+}
+
+/// What happened to one conversation in a restore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreStatus {
+    /// Written to Cursor's database.
+    Restored,
+    /// Not attempted.
+    Skipped,
+    /// Writing it failed.
+    Failed,
+}
+
+/// One conversation in a [`RestoreReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreEntry {
+    /// Composer ID, or the requested ID for a skipped one.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Messages in the conversation.
+    pub messages: usize,
+    /// Outcome.
+    pub status: RestoreStatus,
+    /// Why it was skipped or failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Outcome of a restore, written as `restore-report-<timestamp>.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    /// When the restore started.
+    pub started_at: DateTime<Utc>,
+    /// When it finished.
+    pub finished_at: DateTime<Utc>,
+    /// Database the conversations were written to.
+    pub cursor_db_path: PathBuf,
+    /// IDs asked for; empty for a full restore.
+    pub requested_ids: Vec<String>,
+    /// Conversations restored.
+    pub restored: usize,
+    /// Conversations or IDs skipped.
+    pub skipped: usize,
+    /// Conversations that failed.
+    pub failed: usize,
+    /// Every conversation, in restore order.
+    pub conversations: Vec<RestoreEntry>,
+}
+
+impl RestoreReport {
+    fn new(ids: &[String], cursor_db_path: &Path) -> Self {
+        let now = Utc::now();
+        Self {
+            started_at: now,
+            finished_at: now,
+            cursor_db_path: cursor_db_path.to_path_buf(),
+            requested_ids: ids.to_vec(),
+            restored: 0,
+            skipped: 0,
+            failed: 0,
+            conversations: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, entry: RestoreEntry) {
+        match entry.status {
+            RestoreStatus::Restored => self.restored += 1,
+            RestoreStatus::Skipped => self.skipped += 1,
+            RestoreStatus::Failed => self.failed += 1,
+        }
+        self.conversations.push(entry);
+    }
+
+    /// Messages of the restored conversations.
+    #[must_use]
+    pub fn restored_messages(&self) -> usize {
+        self.conversations
+            .iter()
+            .filter(|e| e.status == RestoreStatus::Restored)
+            .map(|e| e.messages)
+            .sum()
+    }
+}
+
+/// Result of deleting a conversation from Cursor.
+#[derive(Debug)]
+pub struct DeleteResult {
+    /// Full composer ID of the deleted conversation.
+    pub composer_id: String,
+    /// Number of keys removed from Cursor's database.
+    pub removed_keys: usize,
+    /// Number of messages kept in local storage.
+    pub archived_messages: usize,
+}
+
+/// The one ID in `ids` matching `id`: itself, or the only one containing it.
+fn match_composer_id<'a>(ids: impl Iterator<Item = &'a String>, id: &str) -> Result<&'a String> {
+    let matches: Vec<&String> = ids.filter(|candidate| candidate.contains(id)).collect();
+    match matches.as_slice() {
+        [] => Err(AppError::InvalidData {
+            message: format!("Conversation not found in Cursor: {id}"),
+        }),
+        [single] => Ok(single),
+        _ => matches
+            .iter()
+            .find(|candidate| candidate.as_str() == id)
+            .copied()
+            .ok_or_else(|| AppError::InvalidData {
+                message: format!(
+                    "'{id}' matches {} conversations; use a longer ID",
+                    matches.len()
+                ),
+            }),
+    }
+}
+
+/// Conversations in Cursor's database, read without opening it for
+/// writing, so checks work in read-only mode and never create the file.
+fn cursor_conversation_count(cursor_db: &Path) -> Result<usize> {
+    let reader = StateDbReader::open(cursor_db)?;
+    if !reader.has_kv_table()? {
+        return Ok(0);
+    }
+    reader.count_composers()
+}
+
+/// Size of a database file and its WAL.
+fn db_file_size(path: &Path) -> u64 {
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    [path.as_os_str(), wal.as_os_str()]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Which conversations [`RestoreService::prune`] removes.
+#[derive(Debug, Clone, Default)]
+pub struct PruneFilter {
+    /// Those whose last message is older than this.
+    pub older_than: Option<chrono::Duration>,
+    /// These conversations (full or unambiguous partial IDs); takes
+    /// precedence over `older_than`.
+    pub ids: Vec<String>,
+}
+
+/// A conversation selected by a prune.
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneEntry {
+    /// Full composer ID.
+    pub composer_id: String,
+    /// Its title.
+    pub title: String,
+    /// When its last message was sent.
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Keys it has in Cursor's database.
+    pub keys: usize,
+    /// Size of their values.
+    pub bytes: u64,
+}
+
+/// Result of pruning Cursor's database.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneResult {
+    /// Whether nothing was changed.
+    pub dry_run: bool,
+    /// Conversations removed (or to remove, in a dry run), oldest first.
+    pub conversations: Vec<PruneEntry>,
+    /// Requested conversations left in Cursor because they are not in
+    /// local storage.
+    pub not_synced: Vec<String>,
+    /// Keys removed from Cursor's database.
+    pub removed_keys: usize,
+    /// Size of Cursor's database (with WAL) before the prune.
+    pub size_before: u64,
+    /// Its size after the prune and vacuum.
+    pub size_after: u64,
+}
+
+/// Result of archiving a conversation.
+#[derive(Debug, Clone)]
+pub struct ArchiveResult {
+    /// Full composer ID of the archived conversation.
+    pub composer_id: String,
+    /// Its title.
+    pub title: String,
+    /// Number of messages kept in local storage.
+    pub archived_messages: usize,
+    /// Keys removed from Cursor's database, if it was erased there.
+    pub removed_keys: Option<usize>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A service on `data_dir` that sees Cursor as running or not.
+    fn service(data_dir: &Path, cursor_running: fn() -> bool) -> RestoreService {
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(data_dir.to_path_buf());
+        RestoreService {
+            cursor_running,
+            ..RestoreService::new(config)
+        }
+    }
+
+    #[test]
+    fn test_refuses_to_write_while_cursor_runs() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = service(dir.path(), || true)
+            .ensure_cursor_closed("pruning")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("pruning now"), "{err}");
+        assert!(err.contains("--close-cursor"), "{err}");
+        assert!(service(dir.path(), || false)
+            .ensure_cursor_closed("pruning")
+            .is_ok());
+
+        // Refused before Cursor's database is looked up
+        std::fs::write(dir.path().join("storage.db"), b"").unwrap();
+        let err = service(dir.path(), || true)
+            .restore_by_ids(&["abc".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("restoring now"), "{err}");
+    }
+
+    #[test]
+    fn test_restore_report_counts() {
+        let mut report = RestoreReport::new(&["abc".to_string()], Path::new("state.vscdb"));
+        for (messages, status) in [
+            (3, RestoreStatus::Restored),
+            (5, RestoreStatus::Failed),
+            (2, RestoreStatus::Restored),
+            (0, RestoreStatus::Skipped),
+        ] {
+            report.push(RestoreEntry {
+                composer_id: "c".into(),
+                title: String::new(),
+                messages,
+                status,
+                reason: None,
+            });
+        }
+
+        assert_eq!((report.restored, report.failed, report.skipped), (2, 1, 1));
+        assert_eq!(report.restored_messages(), 5);
+    }
+
+    #[test]
+    fn test_match_composer_id() {
+        let ids = ["abc-1".to_string(), "abc-12".to_string(), "def".to_string()];
+        assert_eq!(match_composer_id(ids.iter(), "abc-1").unwrap(), "abc-1");
+        assert_eq!(match_composer_id(ids.iter(), "-12").unwrap(), "abc-12");
+        assert!(match_composer_id(ids.iter(), "abc").is_err());
+        assert!(match_composer_id(ids.iter(), "xyz").is_err());
+    }
+
+    #[test]
+    fn test_cursor_conversation_count_in_read_only_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("state.vscdb");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ItemTable (key TEXT UNIQUE, value BLOB);
+             CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES ('composerData:a', '{}'), ('composerData:b', '{}');",
+        )
+        .unwrap();
+
+        crate::infrastructure::enable_read_only();
+        assert_eq!(cursor_conversation_count(&db_path).unwrap(), 2);
+        assert!(cursor_conversation_count(&dir.path().join("missing.vscdb")).is_err());
+        assert!(!dir.path().join("missing.vscdb").exists());
+    }
+
     #[test]
     fn test_cursor_db_path() {
         // Just verify it doesn't panic
         let _ = RestoreService::cursor_db_path();
     }
 }
-
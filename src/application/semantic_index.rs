@@ -0,0 +1,396 @@
+//! Semantic search over stored conversations.
+//!
+//! Each conversation is embedded once (title plus user messages) and the
+//! vector is kept in local storage until the conversation changes. Queries
+//! are embedded with the same model and ranked by cosine similarity.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::domain::{AppConfig, AppError, BubbleType, Conversation, EmbeddingBackend, Result};
+use crate::infrastructure::{create_embedder, Embedder, LocalStorage, StoredEmbedding};
+
+use super::redact::redact_secrets;
+use super::summarizer::resolve_conversation;
+
+/// Characters of a conversation used for its embedding.
+const MAX_EMBED_CHARS: usize = 2_000;
+
+/// Conversations embedded per backend call.
+const BATCH_SIZE: usize = 16;
+
+/// Outcome of an index build.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexBuild {
+    /// Embedding model used.
+    pub model: String,
+    /// Conversations embedded in this run.
+    pub embedded: usize,
+    /// Conversations whose embedding was already current.
+    pub unchanged: usize,
+}
+
+/// A conversation matching a semantic query.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// Full composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Cosine similarity to the query (higher is closer).
+    pub score: f32,
+}
+
+/// How related conversations were ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityMethod {
+    /// Stored embeddings of the configured model.
+    Embeddings,
+    /// TF-IDF over the stored text, used when the conversation is not indexed.
+    Tfidf,
+}
+
+/// Conversations related to a given one.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarConversations {
+    /// Full composer ID of the conversation compared against.
+    pub composer_id: String,
+    /// Its title.
+    pub title: String,
+    /// How the ranking was computed.
+    pub method: SimilarityMethod,
+    /// Most related conversations, best first.
+    pub hits: Vec<SearchHit>,
+}
+
+/// Builds and queries the embedding index.
+pub struct SemanticIndex {
+    config: AppConfig,
+}
+
+impl SemanticIndex {
+    /// Create an index using the `[embeddings]` configuration.
+    #[must_use]
+    pub const fn new(config: AppConfig) -> Self {
+        Self { config }
+    }
+
+    /// Embed stored conversations that are new or changed since the last build.
+    ///
+    /// With `force`, every conversation is embedded again.
+    ///
+    /// # Errors
+    /// Returns error if storage cannot be read or the embedding backend fails.
+    pub fn build(&self, force: bool) -> Result<IndexBuild> {
+        let storage = self.open_storage()?;
+        let embedder = create_embedder(&self.config)?;
+        let model = embedder.model_id().to_string();
+
+        let mut pending = Vec::new();
+        let mut unchanged = 0;
+        for conv in storage.get_conversations(None)? {
+            if conv.bubbles.is_empty() {
+                continue;
+            }
+            let hash = storage.get_conversation_hash(&conv.composer_id)?;
+            let current =
+                hash.is_some() && storage.embedding_hash(&conv.composer_id, &model)? == hash;
+            if current && !force {
+                unchanged += 1;
+            } else {
+                pending.push((conv.composer_id.clone(), hash, self.embedding_text(&conv)));
+            }
+        }
+
+        tracing::info!(model = %model, pending = pending.len(), "Building semantic index");
+
+        for batch in pending.chunks(BATCH_SIZE) {
+            let texts: Vec<String> = batch.iter().map(|(_, _, text)| text.clone()).collect();
+            let vectors = embedder.embed(&texts)?;
+            for ((composer_id, hash, _), vector) in batch.iter().zip(vectors) {
+                storage.upsert_embedding(composer_id, &model, &vector, hash.as_deref())?;
+            }
+        }
+
+        Ok(IndexBuild {
+            model,
+            embedded: pending.len(),
+            unchanged,
+        })
+    }
+
+    /// Find the conversations closest in meaning to `query`, optionally only
+    /// among those of one workspace or with one tag.
+    ///
+    /// # Errors
+    /// Returns error if the index is empty for the configured model or the
+    /// embedding backend fails.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        workspace: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<Vec<SearchHit>> {
+        let storage = self.open_storage()?;
+        let embedder = create_embedder(&self.config)?;
+        let index = load_index(&storage, embedder.as_ref(), workspace, tag)?;
+
+        let query_vector = embedder
+            .embed(&[self.prepare(query)])?
+            .pop()
+            .unwrap_or_default();
+
+        let mut hits: Vec<SearchHit> = index
+            .into_iter()
+            .map(|entry| SearchHit {
+                score: cosine(&query_vector, &entry.vector),
+                composer_id: entry.composer_id,
+                title: entry.title,
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Find the stored conversations most related to `id` (full or partial).
+    ///
+    /// Uses the embedding index when the conversation is in it, and TF-IDF
+    /// over the stored text otherwise, so it works before `index build`.
+    ///
+    /// # Errors
+    /// Returns error if the conversation is not stored or the ID is ambiguous.
+    pub fn similar(&self, id: &str, limit: usize) -> Result<SimilarConversations> {
+        let storage = self.open_storage()?;
+        let target = resolve_conversation(&storage, id)?;
+
+        let indexed = match create_embedder(&self.config) {
+            Ok(embedder) => storage.get_embeddings(embedder.model_id(), None, None)?,
+            Err(e) => {
+                tracing::warn!(error = %e, "Embedding backend unavailable, using TF-IDF");
+                Vec::new()
+            }
+        };
+
+        let (method, mut hits) =
+            if let Some(entry) = indexed.iter().find(|e| e.composer_id == target.composer_id) {
+                let hits = indexed
+                    .iter()
+                    .filter(|other| other.composer_id != target.composer_id)
+                    .map(|other| SearchHit {
+                        composer_id: other.composer_id.clone(),
+                        title: other.title.clone(),
+                        score: cosine(&entry.vector, &other.vector),
+                    })
+                    .collect();
+                (SimilarityMethod::Embeddings, hits)
+            } else {
+                (
+                    SimilarityMethod::Tfidf,
+                    self.tfidf_neighbours(&storage, &target)?,
+                )
+            };
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.retain(|hit| hit.score > 0.0);
+        hits.truncate(limit);
+
+        Ok(SimilarConversations {
+            composer_id: target.composer_id,
+            title: target.title,
+            method,
+            hits,
+        })
+    }
+
+    fn tfidf_neighbours(
+        &self,
+        storage: &LocalStorage,
+        target: &Conversation,
+    ) -> Result<Vec<SearchHit>> {
+        let others: Vec<Conversation> = storage
+            .get_conversations(None)?
+            .into_iter()
+            .filter(|c| c.composer_id != target.composer_id && !c.bubbles.is_empty())
+            .collect();
+
+        let mut documents = vec![self.embedding_text(target)];
+        documents.extend(others.iter().map(|c| self.embedding_text(c)));
+        let vectors = tfidf_vectors(&documents);
+
+        Ok(others
+            .into_iter()
+            .zip(&vectors[1..])
+            .map(|(conv, vector)| SearchHit {
+                score: sparse_cosine(&vectors[0], vector),
+                composer_id: conv.composer_id,
+                title: conv.title,
+            })
+            .collect())
+    }
+
+    /// Title and user messages, which carry most of what a chat is about.
+    fn embedding_text(&self, conv: &Conversation) -> String {
+        let mut text = conv.title.replace('_', " ");
+        for bubble in &conv.bubbles {
+            if text.len() >= MAX_EMBED_CHARS {
+                break;
+            }
+            if bubble.bubble_type == BubbleType::User && !bubble.text.trim().is_empty() {
+                text.push('\n');
+                text.push_str(bubble.text.trim());
+            }
+        }
+        self.prepare(&text.chars().take(MAX_EMBED_CHARS).collect::<String>())
+    }
+
+    /// Redact text bound for a remote embedding API, if configured.
+    fn prepare(&self, text: &str) -> String {
+        if self.config.embeddings.backend == EmbeddingBackend::Api && self.config.llm.redact {
+            redact_secrets(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn open_storage(&self) -> Result<LocalStorage> {
+        let path = self.config.storage_db_path();
+        if !path.exists() {
+            return Err(AppError::Config {
+                message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+            });
+        }
+        LocalStorage::open(&path)
+    }
+}
+
+/// Stored vectors for the embedder's model; errors if there are none.
+fn load_index(
+    storage: &LocalStorage,
+    embedder: &dyn Embedder,
+    workspace: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Vec<StoredEmbedding>> {
+    let index = storage.get_embeddings(embedder.model_id(), workspace, tag)?;
+    // A workspace or tag without indexed conversations is not a missing index
+    let missing = index.is_empty()
+        && ((workspace.is_none() && tag.is_none())
+            || storage
+                .get_embeddings(embedder.model_id(), None, None)?
+                .is_empty());
+    if missing {
+        return Err(AppError::Config {
+            message: format!(
+                "No semantic index for {}. Run 'cursor-chat index build' first.",
+                embedder.model_id()
+            ),
+        });
+    }
+    Ok(index)
+}
+
+/// Cosine similarity of two vectors (0 when either is empty or zero).
+#[must_use]
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// TF-IDF weights of each document's terms (sublinear tf, smoothed idf).
+fn tfidf_vectors(documents: &[String]) -> Vec<HashMap<String, f32>> {
+    let term_counts: Vec<HashMap<String, f32>> = documents
+        .iter()
+        .map(|doc| {
+            let mut counts = HashMap::new();
+            for term in doc
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|t| t.chars().count() > 2)
+            {
+                *counts.entry(term.to_string()).or_insert(0.0) += 1.0;
+            }
+            counts
+        })
+        .collect();
+
+    let mut document_frequency: HashMap<&str, f32> = HashMap::new();
+    for counts in &term_counts {
+        let terms: HashSet<&str> = counts.keys().map(String::as_str).collect();
+        for term in terms {
+            *document_frequency.entry(term).or_insert(0.0) += 1.0;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let total = documents.len() as f32;
+    term_counts
+        .iter()
+        .map(|counts| {
+            counts
+                .iter()
+                .map(|(term, count)| {
+                    let idf =
+                        ((1.0 + total) / (1.0 + document_frequency[term.as_str()])).ln() + 1.0;
+                    (term.clone(), (1.0 + count.ln()) * idf)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Cosine similarity of two sparse vectors.
+fn sparse_cosine(a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
+    let dot: f32 = a
+        .iter()
+        .filter_map(|(term, x)| b.get(term).map(|y| x * y))
+        .sum();
+    let norm_a = a.values().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.values().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert!((cosine(&[1.0, 1.0], &[-1.0, -1.0]) + 1.0).abs() < 1e-6);
+        assert!(cosine(&[0.0, 0.0], &[1.0, 0.0]).abs() < 1e-6);
+        assert!(cosine(&[1.0], &[1.0, 0.0]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tfidf_prefers_shared_rare_terms() {
+        let documents = [
+            "sqlite database locked during sync".to_string(),
+            "database locked again in the sqlite sync daemon".to_string(),
+            "the button is not centered in the navbar".to_string(),
+        ];
+        let vectors = tfidf_vectors(&documents);
+
+        let related = sparse_cosine(&vectors[0], &vectors[1]);
+        let unrelated = sparse_cosine(&vectors[0], &vectors[2]);
+        assert!(related > 0.5);
+        assert!(unrelated.abs() < 1e-6);
+    }
+}
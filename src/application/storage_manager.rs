@@ -7,7 +7,39 @@ use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 
-use crate::domain::{AppConfig, AppError, BackupMetadata, Result};
+use crate::domain::{AppConfig, AppError, BackupMetadata, CursorChannel, OperationKind, Result};
+use crate::infrastructure::{
+    ConversationSize, HealthReport, LocalStorage, StandaloneExport, SyncLock, TableStats,
+};
+
+use super::audit;
+
+/// Number of conversations listed in the storage breakdown.
+const LARGEST_CONVERSATIONS: usize = 5;
+
+/// Appended to a backup's file name for its metadata sidecar.
+const BACKUP_SIDECAR_SUFFIX: &str = ".meta.json";
+
+/// File marking a data directory created by this tool.
+const DATA_DIR_MARKER: &str = ".cursor-chat-handler";
+
+/// Files this tool writes in a data directory, removed by a purge.
+const OWNED_FILES: &[&str] = &[
+    DATA_DIR_MARKER,
+    "storage.db",
+    "storage.db-wal",
+    "storage.db-shm",
+    "sync.lock",
+    "sync.pid",
+    "sync.log",
+    "quick_menu.json",
+];
+
+/// Subdirectories this tool creates in a data directory, removed by a purge.
+const OWNED_DIRS: &[&str] = &["backups", "exports", "cache"];
+
+/// Prefix of the restore reports written to the data directory.
+const RESTORE_REPORT_PREFIX: &str = "restore-report-";
 
 /// Service for managing storage limits and backups.
 pub struct StorageManager {
@@ -22,11 +54,20 @@ impl StorageManager {
     }
 
     /// Ensure data directory exists.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn ensure_directories(&self) -> Result<()> {
         let data_dir = self.config.data_dir();
 
-        fs::create_dir_all(&data_dir)
-            .map_err(|e| AppError::io("Failed to create data directory", e))?;
+        // Only a directory created here is marked: an existing one (`~`,
+        // `~/Documents`) must never look like ours to a purge
+        if !data_dir.exists() {
+            fs::create_dir_all(&data_dir)
+                .map_err(|e| AppError::io("Failed to create data directory", e))?;
+            fs::write(data_dir.join(DATA_DIR_MARKER), "")
+                .map_err(|e| AppError::io("Failed to mark data directory", e))?;
+        }
 
         fs::create_dir_all(self.config.exports_dir())
             .map_err(|e| AppError::io("Failed to create exports directory", e))?;
@@ -38,6 +79,9 @@ impl StorageManager {
     }
 
     /// Get total storage usage in bytes.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn get_total_size(&self) -> Result<u64> {
         let data_dir = self.config.data_dir();
         if !data_dir.exists() {
@@ -48,6 +92,9 @@ impl StorageManager {
     }
 
     /// Check if storage is within configured limits.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn is_within_limits(&self) -> Result<bool> {
         let current = self.get_total_size()?;
         let max = self.config.max_storage_bytes();
@@ -55,19 +102,30 @@ impl StorageManager {
     }
 
     /// Get storage usage as percentage.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn get_usage_percent(&self) -> Result<f64> {
         let current = self.get_total_size()?;
         let max = self.config.max_storage_bytes();
 
-        if max == 0 {
-            return Ok(0.0);
-        }
-
-        Ok((current as f64 / max as f64) * 100.0)
+        Ok(percent(current, max))
     }
 
     /// Clean up old backups based on retention policy.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn cleanup_old_backups(&self) -> Result<CleanupResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Prune,
+            || self.remove_old_backups(),
+            |result| result.summary("old backups"),
+        )
+    }
+
+    fn remove_old_backups(&self) -> Result<CleanupResult> {
         let backups_dir = self.config.backups_dir();
         if !backups_dir.exists() {
             return Ok(CleanupResult::default());
@@ -82,7 +140,7 @@ impl StorageManager {
 
         for entry in entries.filter_map(std::result::Result::ok) {
             let path = entry.path();
-            if !path.is_file() {
+            if !path.is_file() || is_sidecar(&path) {
                 continue;
             }
 
@@ -100,6 +158,12 @@ impl StorageManager {
                         if fs::remove_file(&path).is_ok() {
                             deleted_count += 1;
                             freed_bytes += size;
+                            let sidecar = sidecar_path(&path);
+                            if let Ok(meta) = fs::metadata(&sidecar) {
+                                if fs::remove_file(&sidecar).is_ok() {
+                                    freed_bytes += meta.len();
+                                }
+                            }
                             tracing::info!(
                                 path = %path.display(),
                                 age_days = age_days,
@@ -118,11 +182,23 @@ impl StorageManager {
     }
 
     /// Run cleanup to bring storage under limit.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn enforce_storage_limit(&self) -> Result<CleanupResult> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Prune,
+            || self.free_storage(),
+            |result| result.summary("storage limit"),
+        )
+    }
+
+    fn free_storage(&self) -> Result<CleanupResult> {
         let mut total_result = CleanupResult::default();
 
         // First, clean up old backups
-        let backup_result = self.cleanup_old_backups()?;
+        let backup_result = self.remove_old_backups()?;
         total_result.deleted_count += backup_result.deleted_count;
         total_result.freed_bytes += backup_result.freed_bytes;
 
@@ -150,7 +226,7 @@ impl StorageManager {
         collect_files_recursively(&exports_dir, &mut files)?;
 
         // Sort by modification time (oldest first)
-        files.sort_by(|a, b| a.1.cmp(&b.1));
+        files.sort_by_key(|a| a.1);
 
         let mut deleted_count = 0;
         let mut freed_bytes = 0u64;
@@ -181,6 +257,9 @@ impl StorageManager {
     }
 
     /// List all backups with metadata.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn list_backups(&self) -> Result<Vec<BackupMetadata>> {
         let backups_dir = self.config.backups_dir();
         if !backups_dir.exists() {
@@ -194,32 +273,269 @@ impl StorageManager {
 
         for entry in entries.filter_map(std::result::Result::ok) {
             let path = entry.path();
-            if !path.is_file() {
+            if !path.is_file() || is_sidecar(&path) {
                 continue;
             }
 
-            if let Some(metadata) = self.read_backup_metadata(&path)? {
+            if let Some(metadata) = Self::read_backup_metadata(&path)? {
                 backups.push(metadata);
             }
         }
 
         // Sort by creation time (newest first)
-        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         Ok(backups)
     }
 
-    /// Read backup metadata from a file.
-    fn read_backup_metadata(&self, path: &Path) -> Result<Option<BackupMetadata>> {
-        let metadata = fs::metadata(path)
-            .map_err(|e| AppError::io("Failed to read backup metadata", e))?;
+    /// Snapshot the local storage database into the backups directory.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing or the copy fails.
+    pub fn create_backup(&self) -> Result<BackupMetadata> {
+        audit::recorded(
+            &self.config,
+            OperationKind::Backup,
+            || self.write_backup(),
+            |backup| {
+                Some((
+                    backup.conversation_count,
+                    format!(
+                        "{} ({})",
+                        backup.file_path.display(),
+                        format_bytes(backup.size_bytes)
+                    ),
+                ))
+            },
+        )
+    }
+
+    fn write_backup(&self) -> Result<BackupMetadata> {
+        let storage_path = self.config.storage_db_path();
+        if !storage_path.exists() {
+            return Err(AppError::Config {
+                message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+            });
+        }
+
+        self.ensure_directories()?;
+
+        let id = format!("storage-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+        let backup_path = self.config.backups_dir().join(format!("{id}.db"));
+
+        LocalStorage::open(&storage_path)?.backup_to(&backup_path)?;
+
+        let size_bytes = fs::metadata(&backup_path)
+            .map_err(|e| AppError::io("Failed to read backup metadata", e))?
+            .len();
+
+        // Counted from the copy itself, so they match what a restore would get
+        let backup = LocalStorage::open_read_only(&backup_path)?;
+        let metadata = BackupMetadata {
+            message_count: backup.get_message_count()?,
+            source_machine: machine_name(),
+            app_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..BackupMetadata::new(
+                id,
+                size_bytes,
+                backup.get_conversation_count()?,
+                hash_file(&backup_path)?,
+                backup_path,
+            )
+        };
+        drop(backup);
+        write_sidecar(&metadata)?;
+
+        tracing::info!(
+            path = %metadata.file_path.display(),
+            size = size_bytes,
+            conversations = metadata.conversation_count,
+            "Backup created"
+        );
+
+        Ok(metadata)
+    }
+
+    /// Whether the periodic maintenance interval has elapsed.
+    ///
+    /// Always false when maintenance is disabled or storage does not exist yet.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be read.
+    pub fn maintenance_due(&self) -> Result<bool> {
+        let interval_hours = self.config.storage.maintenance_interval_hours;
+        if interval_hours == 0 || !self.config.storage_db_path().exists() {
+            return Ok(false);
+        }
+
+        let last = self.open_existing_storage()?.last_maintenance()?;
+        let interval = chrono::Duration::hours(i64::try_from(interval_hours).unwrap_or(i64::MAX));
+
+        Ok(last.is_none_or(|at| Utc::now() - at >= interval))
+    }
+
+    /// Run periodic maintenance: checkpoint, optimize, orphan purge and backup rotation.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing or any step fails.
+    pub fn run_maintenance(&self) -> Result<MaintenanceResult> {
+        let _lock = SyncLock::acquire(&self.config.lock_file_path())?;
+        let storage = self.open_existing_storage()?;
+        let purged_rows = storage.maintain()?;
+
+        let backup = self.create_backup()?;
+        let cleanup = self.cleanup_old_backups()?;
+
+        storage.record_maintenance(Utc::now(), purged_rows)?;
+
+        Ok(MaintenanceResult {
+            purged_rows,
+            backup,
+            cleanup,
+        })
+    }
+
+    /// Compact the local storage database, reporting reclaimed space.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing or vacuuming fails.
+    pub fn vacuum(&self) -> Result<VacuumResult> {
+        let storage_path = self.config.storage_db_path();
+        if !storage_path.exists() {
+            return Err(AppError::Config {
+                message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+            });
+        }
+
+        let _lock = SyncLock::acquire(&self.config.lock_file_path())?;
+        let before_bytes = database_files_size(&storage_path);
+
+        let storage = LocalStorage::open(&storage_path)?;
+        storage.vacuum()?;
+        drop(storage);
+
+        let after_bytes = database_files_size(&storage_path);
+
+        tracing::info!(
+            before = before_bytes,
+            after = after_bytes,
+            "Storage vacuum completed"
+        );
+
+        Ok(VacuumResult {
+            before_bytes,
+            after_bytes,
+        })
+    }
+
+    /// Check local storage for corruption and inconsistent rows.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing or cannot be checked.
+    pub fn check_health(&self) -> Result<HealthReport> {
+        self.open_existing_storage()?.check_health()
+    }
+
+    /// Repair local storage, salvaging into a fresh database if it is corrupt.
+    ///
+    /// The damaged file is kept next to the new one as `storage.db.corrupt-<timestamp>`.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing or the repair fails.
+    pub fn repair(&self) -> Result<RepairResult> {
+        let _lock = SyncLock::acquire(&self.config.lock_file_path())?;
+        let storage_path = self.config.storage_db_path();
+        let storage = self.open_existing_storage()?;
+        let mut result = RepairResult::default();
+
+        if storage.check_health()?.is_corrupt() {
+            let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+            let salvage_path = storage_path.with_extension(format!("db.salvage-{stamp}"));
+            let corrupt_path = storage_path.with_extension(format!("db.corrupt-{stamp}"));
+
+            result.failed_tables = storage.salvage_into(&salvage_path)?;
+            drop(storage);
+
+            fs::rename(&storage_path, &corrupt_path)
+                .map_err(|e| AppError::io("Failed to move corrupt storage aside", e))?;
+            fs::rename(&salvage_path, &storage_path)
+                .map_err(|e| AppError::io("Failed to install salvaged storage", e))?;
+
+            tracing::warn!(
+                corrupt = %corrupt_path.display(),
+                failed_tables = ?result.failed_tables,
+                "Corrupt storage replaced with salvaged copy"
+            );
+            result.corrupt_backup = Some(corrupt_path);
+
+            result.rows_fixed = LocalStorage::open(&storage_path)?.repair()?;
+        } else {
+            result.rows_fixed = storage.repair()?;
+        }
+
+        Ok(result)
+    }
+
+    /// Export all chats to a standalone, queryable `SQLite` file.
+    ///
+    /// # Errors
+    /// Returns error if local storage is missing, the destination exists
+    /// (unless `overwrite`), or the export fails.
+    pub fn export_db(&self, dest: &Path, overwrite: bool) -> Result<StandaloneExport> {
+        let storage = self.open_existing_storage()?;
+
+        if dest.exists() {
+            if !overwrite {
+                return Err(AppError::Config {
+                    message: format!("{} already exists (use --force)", dest.display()),
+                });
+            }
+            fs::remove_file(dest)
+                .map_err(|e| AppError::io("Failed to replace existing export", e))?;
+        }
+
+        let result = storage.export_standalone(dest)?;
+
+        tracing::info!(
+            path = %dest.display(),
+            conversations = result.conversations,
+            messages = result.messages,
+            "Standalone database exported"
+        );
+
+        Ok(result)
+    }
+
+    /// Open local storage, failing if it has not been created yet.
+    fn open_existing_storage(&self) -> Result<LocalStorage> {
+        let storage_path = self.config.storage_db_path();
+        if !storage_path.exists() {
+            return Err(AppError::Config {
+                message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+            });
+        }
+
+        LocalStorage::open(&storage_path)
+    }
+
+    /// Read backup metadata from its sidecar, or guess it from the file
+    /// for backups written before sidecars existed.
+    fn read_backup_metadata(path: &Path) -> Result<Option<BackupMetadata>> {
+        let metadata =
+            fs::metadata(path).map_err(|e| AppError::io("Failed to read backup metadata", e))?;
+
+        if let Some(mut backup) = read_sidecar(path) {
+            backup.file_path = path.to_path_buf();
+            backup.size_bytes = metadata.len();
+            return Ok(Some(backup));
+        }
 
         let created_at = metadata
             .modified()
             .ok()
             .and_then(|t| {
                 let duration = t.duration_since(std::time::UNIX_EPOCH).ok()?;
-                DateTime::from_timestamp(duration.as_secs() as i64, 0)
+                DateTime::from_timestamp(i64::try_from(duration.as_secs()).ok()?, 0)
             })
             .unwrap_or_else(Utc::now);
 
@@ -231,44 +547,58 @@ impl StorageManager {
 
         let is_compressed = path
             .extension()
-            .map_or(false, |ext| ext == "gz" || ext == "zst");
+            .is_some_and(|ext| ext == "gz" || ext == "zst");
 
         Ok(Some(BackupMetadata {
             id,
             created_at,
             size_bytes: metadata.len(),
-            conversation_count: 0, // Would need to parse file to get this
+            conversation_count: 0,
+            message_count: 0,
             content_hash: String::new(),
+            source_machine: None,
+            app_version: None,
             is_compressed,
             file_path: path.to_path_buf(),
         }))
     }
 
     /// Get storage summary.
+    ///
+    /// # Errors
+    /// Returns error if the data directory or storage cannot be read.
     pub fn get_summary(&self) -> Result<StorageSummary> {
         let total_bytes = self.get_total_size()?;
         let max_bytes = self.config.max_storage_bytes();
 
         // Count files in each directory
-        let db_size = fs::metadata(self.config.storage_db_path())
-            .map(|m| m.len())
-            .unwrap_or(0);
+        let db_size = fs::metadata(self.config.storage_db_path()).map_or(0, |m| m.len());
 
         let exports_size = calculate_dir_size(&self.config.exports_dir()).unwrap_or(0);
         let backups_size = calculate_dir_size(&self.config.backups_dir()).unwrap_or(0);
+        let backups = self.list_backups()?;
+
+        let (tables, largest_conversations) = if self.config.storage_db_path().exists() {
+            let storage = LocalStorage::open_read_only(&self.config.storage_db_path())?;
+            (
+                storage.table_stats()?,
+                storage.largest_conversations(LARGEST_CONVERSATIONS)?,
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
 
         Ok(StorageSummary {
             total_bytes,
             max_bytes,
-            usage_percent: if max_bytes > 0 {
-                (total_bytes as f64 / max_bytes as f64) * 100.0
-            } else {
-                0.0
-            },
+            usage_percent: percent(total_bytes, max_bytes),
             db_size,
             exports_size,
             backups_size,
-            backup_count: self.list_backups()?.len(),
+            backup_count: backups.len(),
+            latest_backup: backups.into_iter().next(),
+            tables,
+            largest_conversations,
         })
     }
 }
@@ -288,6 +618,73 @@ impl CleanupResult {
     pub fn freed_human(&self) -> String {
         format_bytes(self.freed_bytes)
     }
+
+    /// Audit log entry, only when something was removed.
+    fn summary(&self, reason: &str) -> Option<(usize, String)> {
+        (self.deleted_count > 0).then(|| {
+            (
+                self.deleted_count,
+                format!("{reason}: {} freed", self.freed_human()),
+            )
+        })
+    }
+}
+
+/// Result of a vacuum operation.
+#[derive(Debug, Clone, Default)]
+pub struct VacuumResult {
+    /// Database size (including WAL) before vacuuming.
+    pub before_bytes: u64,
+    /// Database size (including WAL) after vacuuming.
+    pub after_bytes: u64,
+}
+
+impl VacuumResult {
+    /// Bytes released by the vacuum.
+    #[must_use]
+    pub const fn reclaimed_bytes(&self) -> u64 {
+        self.before_bytes.saturating_sub(self.after_bytes)
+    }
+
+    /// Format size before vacuum as human readable.
+    #[must_use]
+    pub fn before_human(&self) -> String {
+        format_bytes(self.before_bytes)
+    }
+
+    /// Format size after vacuum as human readable.
+    #[must_use]
+    pub fn after_human(&self) -> String {
+        format_bytes(self.after_bytes)
+    }
+
+    /// Format reclaimed bytes as human readable.
+    #[must_use]
+    pub fn reclaimed_human(&self) -> String {
+        format_bytes(self.reclaimed_bytes())
+    }
+}
+
+/// Result of a maintenance run.
+#[derive(Debug, Clone)]
+pub struct MaintenanceResult {
+    /// Orphan rows purged from storage.
+    pub purged_rows: usize,
+    /// Backup taken during the run.
+    pub backup: BackupMetadata,
+    /// Old backups removed by rotation.
+    pub cleanup: CleanupResult,
+}
+
+/// Result of a storage repair.
+#[derive(Debug, Clone, Default)]
+pub struct RepairResult {
+    /// Rows deleted or corrected.
+    pub rows_fixed: usize,
+    /// Where the corrupt database was moved, if it had to be salvaged.
+    pub corrupt_backup: Option<PathBuf>,
+    /// Tables that could not be copied while salvaging.
+    pub failed_tables: Vec<String>,
 }
 
 /// Storage summary information.
@@ -307,6 +704,12 @@ pub struct StorageSummary {
     pub backups_size: u64,
     /// Number of backups.
     pub backup_count: usize,
+    /// Most recent backup, if any.
+    pub latest_backup: Option<BackupMetadata>,
+    /// Per-table row counts and sizes, largest first.
+    pub tables: Vec<TableStats>,
+    /// Conversations using the most space.
+    pub largest_conversations: Vec<ConversationSize>,
 }
 
 impl StorageSummary {
@@ -341,6 +744,62 @@ impl StorageSummary {
     }
 }
 
+/// Path of the metadata sidecar written next to `backup`.
+fn sidecar_path(backup: &Path) -> PathBuf {
+    let mut name = backup.as_os_str().to_owned();
+    name.push(BACKUP_SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn is_sidecar(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(BACKUP_SIDECAR_SUFFIX))
+}
+
+fn write_sidecar(backup: &BackupMetadata) -> Result<()> {
+    let path = sidecar_path(&backup.file_path);
+    let json = serde_json::to_string_pretty(backup).map_err(AppError::json_parse)?;
+    fs::write(&path, json)
+        .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))
+}
+
+/// Metadata from the sidecar of `backup`; `None` if missing or unreadable.
+fn read_sidecar(backup: &Path) -> Option<BackupMetadata> {
+    let path = sidecar_path(backup);
+    let json = fs::read(&path).ok()?;
+    serde_json::from_slice(&json)
+        .inspect_err(
+            |e| tracing::warn!(path = %path.display(), error = %e, "Unreadable backup metadata"),
+        )
+        .ok()
+}
+
+/// blake3 hash of a file's contents, hex encoded.
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| AppError::io(format!("Failed to open {}", path.display()), e))?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| AppError::io(format!("Failed to hash {}", path.display()), e))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Host name of this machine, if it can be found.
+fn machine_name() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            let output = std::process::Command::new("hostname").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+}
+
 /// Calculate total size of a directory recursively.
 fn calculate_dir_size(path: &Path) -> Result<u64> {
     if !path.exists() {
@@ -353,6 +812,11 @@ fn calculate_dir_size(path: &Path) -> Result<u64> {
         .map_err(|e| AppError::io(format!("Failed to read directory {}", path.display()), e))?;
 
     for entry in entries.filter_map(std::result::Result::ok) {
+        // Links (e.g. exports/latest/) point at files already counted
+        if entry.file_type().is_ok_and(|t| t.is_symlink()) {
+            continue;
+        }
+
         let path = entry.path();
         let metadata = fs::metadata(&path)
             .map_err(|e| AppError::io(format!("Failed to read metadata {}", path.display()), e))?;
@@ -367,6 +831,18 @@ fn calculate_dir_size(path: &Path) -> Result<u64> {
     Ok(total)
 }
 
+/// Size of a `SQLite` database file plus its WAL and shared-memory files.
+fn database_files_size(db_path: &Path) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut path = db_path.as_os_str().to_owned();
+            path.push(suffix);
+            fs::metadata(PathBuf::from(path)).map_or(0, |m| m.len())
+        })
+        .sum()
+}
+
 /// Collect all files recursively with their metadata.
 fn collect_files_recursively(
     path: &Path,
@@ -380,12 +856,18 @@ fn collect_files_recursively(
         .map_err(|e| AppError::io(format!("Failed to read directory {}", path.display()), e))?;
 
     for entry in entries.filter_map(std::result::Result::ok) {
+        if entry.file_type().is_ok_and(|t| t.is_symlink()) {
+            continue;
+        }
+
         let path = entry.path();
         let metadata = fs::metadata(&path)
             .map_err(|e| AppError::io(format!("Failed to read metadata {}", path.display()), e))?;
 
         if metadata.is_file() {
-            let modified = metadata.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+            let modified = metadata
+                .modified()
+                .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
             files.push((path, modified, metadata.len()));
         } else if metadata.is_dir() {
             collect_files_recursively(&path, files)?;
@@ -395,8 +877,20 @@ fn collect_files_recursively(
     Ok(())
 }
 
+/// `part` as a percentage of `whole`, 0 if `whole` is 0.
+pub(crate) fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    #[allow(clippy::cast_precision_loss)] // Byte counts stay far below 2^52
+    let ratio = part as f64 / whole as f64;
+    ratio * 100.0
+}
+
 /// Format bytes as human readable string.
-fn format_bytes(bytes: u64) -> String {
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // Two decimals shown
+pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -408,14 +902,163 @@ fn format_bytes(bytes: u64) -> String {
     } else if bytes >= KB {
         format!("{:.2} KB", bytes as f64 / KB as f64)
     } else {
-        format!("{} B", bytes)
+        format!("{bytes} B")
+    }
+}
+
+/// Existing data directories of `config` and of each of its profiles,
+/// including the built-in channel ones, without duplicates.
+#[must_use]
+pub fn profile_data_dirs(config: &AppConfig) -> Vec<PathBuf> {
+    let channels = CursorChannel::ALL
+        .iter()
+        .filter(|c| **c != CursorChannel::Stable)
+        .map(|c| c.as_str());
+    let profiles = config.profiles.keys().map(String::as_str).chain(channels);
+
+    let mut dirs = vec![config.data_dir(), AppConfig::default_data_dir()];
+    for name in profiles {
+        if let Ok(profile) = config.clone().with_profile(name) {
+            dirs.push(profile.data_dir());
+        }
+    }
+
+    let mut unique = Vec::new();
+    for dir in dirs {
+        if dir.exists() && !unique.contains(&dir) {
+            unique.push(dir);
+        }
     }
+    unique
+}
+
+/// Whether `dir` is a data directory this tool created: it holds the
+/// storage database or the marker [`StorageManager::ensure_directories`]
+/// writes. Anything else is never purged.
+#[must_use]
+pub fn is_data_dir(dir: &Path) -> bool {
+    dir.join("storage.db").is_file() || dir.join(DATA_DIR_MARKER).is_file()
+}
+
+/// Remove what this tool keeps in `dir`: the storage database, backups,
+/// exports, cache, logs and marker. The directory itself is removed only
+/// once nothing else is left in it; returns whether it was.
+///
+/// # Errors
+/// Returns error if an owned file or directory cannot be removed.
+pub fn purge_data_dir(dir: &Path) -> Result<bool> {
+    let remove_err =
+        |path: &Path, e| AppError::io(format!("Failed to remove {}", path.display()), e);
+
+    for name in OWNED_FILES {
+        let path = dir.join(name);
+        if path.is_file() {
+            fs::remove_file(&path).map_err(|e| remove_err(&path, e))?;
+        }
+    }
+    for name in OWNED_DIRS {
+        let path = dir.join(name);
+        if path.is_dir() {
+            fs::remove_dir_all(&path).map_err(|e| remove_err(&path, e))?;
+        }
+    }
+
+    let entries =
+        fs::read_dir(dir).map_err(|e| AppError::io("Failed to read data directory", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_report = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(RESTORE_REPORT_PREFIX));
+        if is_report && path.is_file() {
+            fs::remove_file(&path).map_err(|e| remove_err(&path, e))?;
+        }
+    }
+
+    // Fails while anything else is left in it, which is then kept
+    Ok(fs::remove_dir(dir).is_ok())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_profile_data_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let (work, missing) = (dir.path().join("work"), dir.path().join("missing"));
+        fs::create_dir_all(&work).unwrap();
+
+        let mut config: AppConfig = toml::from_str(&format!(
+            "[profile.work]\ndata_dir = {work:?}\n[profile.missing]\ndata_dir = {missing:?}\n"
+        ))
+        .unwrap();
+        config.paths.data_dir = Some(work.clone());
+
+        // Only existing ones, each once
+        let dirs = profile_data_dirs(&config);
+        assert_eq!(dirs.iter().filter(|d| **d == work).count(), 1);
+        assert!(!dirs.contains(&missing));
+    }
+
+    #[test]
+    fn test_is_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_data_dir(dir.path()));
+
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().join("data"));
+        StorageManager::new(config.clone())
+            .ensure_directories()
+            .unwrap();
+        assert!(is_data_dir(&config.data_dir()));
+
+        let storage = dir.path().join("storage");
+        fs::create_dir_all(&storage).unwrap();
+        fs::write(storage.join("storage.db"), "").unwrap();
+        assert!(is_data_dir(&storage));
+    }
+
+    #[test]
+    fn test_existing_data_dir_is_not_marked() {
+        let home = tempfile::tempdir().unwrap();
+        fs::write(home.path().join("notes.txt"), "mine").unwrap();
+
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(home.path().to_path_buf());
+        let manager = StorageManager::new(config);
+        manager.ensure_directories().unwrap();
+        assert!(!home.path().join(DATA_DIR_MARKER).exists());
+        assert!(!is_data_dir(home.path()));
+
+        // Once synced it holds storage.db, but a purge only takes that
+        fs::write(home.path().join("storage.db"), "").unwrap();
+        assert!(is_data_dir(home.path()));
+        assert!(!purge_data_dir(home.path()).unwrap());
+        assert!(home.path().join("notes.txt").is_file());
+        assert!(!home.path().join("storage.db").exists());
+        assert!(!home.path().join("backups").exists());
+        assert!(!home.path().join("exports").exists());
+    }
+
+    #[test]
+    fn test_purge_data_dir_removes_created_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().join("data"));
+        StorageManager::new(config.clone())
+            .ensure_directories()
+            .unwrap();
+        let data = config.data_dir();
+        fs::write(data.join("storage.db"), "").unwrap();
+        fs::write(data.join("sync.pid"), "1").unwrap();
+        fs::write(data.join("restore-report-20250101-000000.json"), "{}").unwrap();
+
+        assert!(purge_data_dir(&data).unwrap());
+        assert!(!data.exists());
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(0), "0 B");
@@ -426,6 +1069,57 @@ mod tests {
         assert_eq!(format_bytes(1024 * 1024 * 1024), "1.00 GB");
     }
 
+    #[test]
+    fn test_vacuum_result_reclaimed() {
+        let result = VacuumResult {
+            before_bytes: 4096,
+            after_bytes: 1024,
+        };
+        assert_eq!(result.reclaimed_bytes(), 3072);
+        assert_eq!(result.reclaimed_human(), "3.00 KB");
+    }
+
+    #[test]
+    fn test_database_files_size_includes_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("storage.db");
+        fs::write(&db, [0u8; 100]).unwrap();
+        fs::write(dir.path().join("storage.db-wal"), [0u8; 50]).unwrap();
+
+        assert_eq!(database_files_size(&db), 150);
+    }
+
+    #[test]
+    fn test_backup_sidecar_metadata_is_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().to_path_buf());
+        drop(LocalStorage::open(&config.storage_db_path()).unwrap());
+        let manager = StorageManager::new(config);
+
+        let created = manager.create_backup().unwrap();
+        assert!(sidecar_path(&created.file_path).exists());
+        assert_eq!(created.content_hash, hash_file(&created.file_path).unwrap());
+
+        // A backup from before sidecars existed
+        fs::write(dir.path().join("backups/storage-old.db"), b"old").unwrap();
+
+        let backups = manager.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        let listed = backups.iter().find(|b| b.id == created.id).unwrap();
+        assert!(listed.has_details());
+        assert_eq!(listed.content_hash, created.content_hash);
+        assert_eq!(
+            listed.app_version.as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        assert!(!backups
+            .iter()
+            .find(|b| b.id == "storage-old")
+            .unwrap()
+            .has_details());
+    }
+
     #[test]
     fn test_cleanup_result_default() {
         let result = CleanupResult::default();
@@ -433,4 +1127,3 @@ mod tests {
         assert_eq!(result.freed_bytes, 0);
     }
 }
-
@@ -0,0 +1,392 @@
+//! LLM-powered conversation summaries and titles.
+//!
+//! Sends a (redacted, token-trimmed) transcript to the configured
+//! OpenAI-compatible endpoint. Summaries are cached in local storage
+//! until the conversation changes; titles are stored as overrides.
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::domain::{AppConfig, AppError, BubbleType, Conversation, Result};
+use crate::infrastructure::{LlmClient, LocalStorage, StoredSummary};
+
+use super::redact::redact_secrets;
+
+/// Rough characters-per-token ratio used to stay within the input budget.
+pub const CHARS_PER_TOKEN: usize = 4;
+
+const SUMMARY_PROMPT: &str = "You summarize conversations between a developer and an AI \
+coding assistant. Write a concise summary (at most 8 bullet points) covering the goal, \
+the key decisions and changes made, and anything left unresolved. Reply in the language \
+the developer used.";
+
+const TITLE_PROMPT: &str = "You write titles for conversations between a developer and an AI \
+coding assistant. Reply with only the title: at most 8 words, no quotes, no trailing period, \
+in the language the developer used.";
+
+/// Token budget for title requests; the start of a conversation is enough.
+const TITLE_MAX_INPUT_TOKENS: usize = 2_000;
+
+/// Longest title accepted from the model.
+const MAX_TITLE_CHARS: usize = 80;
+
+/// A conversation summary, fresh or from the cache.
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryResult {
+    /// Full composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Summary text.
+    pub summary: String,
+    /// Model that produced it.
+    pub model: String,
+    /// When it was generated.
+    pub created_at: chrono::DateTime<Utc>,
+    /// Whether it came from the cache.
+    pub cached: bool,
+}
+
+/// Generates and caches conversation summaries.
+pub struct Summarizer {
+    config: AppConfig,
+}
+
+impl Summarizer {
+    /// Create a summarizer using the `[llm]` configuration.
+    #[must_use]
+    pub const fn new(config: AppConfig) -> Self {
+        Self { config }
+    }
+
+    /// Summarize a stored conversation.
+    ///
+    /// Returns the cached summary unless the conversation changed since it
+    /// was generated or `refresh` is set.
+    ///
+    /// # Errors
+    /// Returns error if the conversation is not stored locally, the ID is
+    /// ambiguous, or the LLM request fails.
+    pub fn summarize(&self, id: &str, refresh: bool) -> Result<SummaryResult> {
+        let storage = self.open_storage()?;
+        let conv = resolve_conversation(&storage, id)?;
+        let content_hash = storage.get_conversation_hash(&conv.composer_id)?;
+
+        if !refresh {
+            if let Some(cached) = storage.get_summary(&conv.composer_id)? {
+                if cached.content_hash == content_hash {
+                    return Ok(SummaryResult {
+                        composer_id: conv.composer_id,
+                        title: conv.title,
+                        summary: cached.summary,
+                        model: cached.model,
+                        created_at: cached.created_at,
+                        cached: true,
+                    });
+                }
+            }
+        }
+
+        let client = LlmClient::new(&self.config.llm)?;
+        let transcript = self.transcript(&conv);
+        if transcript.is_empty() {
+            return Err(AppError::InvalidData {
+                message: format!("Conversation {} has no messages", conv.composer_id),
+            });
+        }
+
+        tracing::info!(
+            composer_id = %conv.composer_id,
+            model = client.model(),
+            chars = transcript.len(),
+            "Requesting summary"
+        );
+
+        let stored = StoredSummary {
+            summary: client.complete(SUMMARY_PROMPT, &transcript)?,
+            model: client.model().to_string(),
+            content_hash,
+            created_at: Utc::now(),
+        };
+        storage.save_summary(&conv.composer_id, &stored)?;
+
+        Ok(SummaryResult {
+            composer_id: conv.composer_id,
+            title: conv.title,
+            summary: stored.summary,
+            model: stored.model,
+            created_at: stored.created_at,
+            cached: false,
+        })
+    }
+
+    /// Generate titles for conversations whose heuristic title is poor.
+    ///
+    /// With `missing_only`, conversations that already have a title override
+    /// are skipped. At most `limit` conversations are sent (0 = no limit).
+    /// Failures are reported per conversation and do not stop the run.
+    ///
+    /// # Errors
+    /// Returns error if storage cannot be read or the client cannot be created.
+    pub fn regenerate_titles(&self, missing_only: bool, limit: usize) -> Result<TitleRun> {
+        let storage = self.open_storage()?;
+        let client = LlmClient::new(&self.config.llm)?;
+        let overrides = storage.title_overrides()?;
+
+        // Heuristic titles live in the conversations table; overrides are applied on read
+        let candidates: Vec<Conversation> = storage
+            .get_conversations(None)?
+            .into_iter()
+            .filter(|c| !c.bubbles.is_empty())
+            .filter(|c| {
+                if overrides.contains_key(&c.composer_id) {
+                    !missing_only
+                } else {
+                    is_poor_title(&c.title)
+                }
+            })
+            .collect();
+
+        let take = if limit == 0 { candidates.len() } else { limit };
+        let mut run = TitleRun::default();
+
+        for conv in candidates.into_iter().take(take) {
+            match self.generate_title(&client, &conv) {
+                Ok(title) => {
+                    storage.set_title_override(&conv.composer_id, &title, client.model())?;
+                    run.changed.push(TitleChange {
+                        composer_id: conv.composer_id,
+                        old_title: conv.title,
+                        new_title: title,
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(composer_id = %conv.composer_id, error = %e, "Title generation failed");
+                    run.failed.push((conv.composer_id, e.to_string()));
+                }
+            }
+        }
+
+        Ok(run)
+    }
+
+    fn generate_title(&self, client: &LlmClient, conv: &Conversation) -> Result<String> {
+        let budget = self.config.llm.max_input_tokens.min(TITLE_MAX_INPUT_TOKENS) * CHARS_PER_TOKEN;
+        let mut transcript = build_transcript(conv, budget);
+        if self.config.llm.redact {
+            transcript = redact_secrets(&transcript);
+        }
+
+        let reply = client.complete(TITLE_PROMPT, &transcript)?;
+        let title: String = reply
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .trim_matches(['"', '\'', '*', '#', '-', ' '])
+            .trim_end_matches('.')
+            .chars()
+            .take(MAX_TITLE_CHARS)
+            .collect();
+
+        if title.is_empty() {
+            return Err(AppError::Llm {
+                message: "model returned an empty title".into(),
+            });
+        }
+        Ok(title)
+    }
+
+    fn open_storage(&self) -> Result<LocalStorage> {
+        let path = self.config.storage_db_path();
+        if !path.exists() {
+            return Err(AppError::Config {
+                message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+            });
+        }
+        LocalStorage::open(&path)
+    }
+
+    /// Transcript sent to the model, redacted if configured.
+    fn transcript(&self, conv: &Conversation) -> String {
+        let budget = self.config.llm.max_input_tokens * CHARS_PER_TOKEN;
+        let transcript = build_transcript(conv, budget);
+        if self.config.llm.redact {
+            redact_secrets(&transcript)
+        } else {
+            transcript
+        }
+    }
+}
+
+/// A title replaced by `regenerate_titles`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TitleChange {
+    /// Full composer ID.
+    pub composer_id: String,
+    /// Title before the change.
+    pub old_title: String,
+    /// Generated title.
+    pub new_title: String,
+}
+
+/// Outcome of a `regenerate_titles` run.
+#[derive(Debug, Default, Serialize)]
+pub struct TitleRun {
+    /// Titles that were generated and stored.
+    pub changed: Vec<TitleChange>,
+    /// Conversations that failed, with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Whether a heuristic title is too poor to be useful.
+///
+/// Heuristic titles are the first words of the first user message in
+/// `snake_case`; pasted code, paths, logs or one-word prompts make bad ones.
+#[must_use]
+pub fn is_poor_title(title: &str) -> bool {
+    let words: Vec<&str> = title.split(['_', ' ']).filter(|w| !w.is_empty()).collect();
+
+    if words.len() < 3 || title == "conversa" {
+        return true;
+    }
+
+    let letters = title.chars().filter(|c| c.is_alphabetic()).count();
+    let mostly_symbols = letters * 10 < title.chars().count() * 6;
+    let has_blob = words.iter().any(|w| w.chars().count() > 25);
+
+    mostly_symbols || has_blob
+}
+
+/// Find a stored conversation by full or unambiguous partial ID.
+///
+/// # Errors
+/// Returns error if the ID matches no stored conversation or storage cannot be read.
+pub fn resolve_conversation(storage: &LocalStorage, id: &str) -> Result<Conversation> {
+    let ids = storage.find_composer_ids(id)?;
+    let composer_id = match ids.as_slice() {
+        [] => {
+            return Err(AppError::InvalidData {
+                message: format!(
+                    "Conversation not found in local storage: {id} (run 'cursor-chat sync now')"
+                ),
+            });
+        }
+        [single] => single.as_str(),
+        _ if ids.iter().any(|i| i == id) => id,
+        _ => {
+            return Err(AppError::InvalidData {
+                message: format!(
+                    "'{id}' matches {} conversations; use a longer ID",
+                    ids.len()
+                ),
+            });
+        }
+    };
+
+    storage
+        .get_conversation(composer_id)?
+        .ok_or_else(|| AppError::InvalidData {
+            message: format!("Conversation not found: {composer_id}"),
+        })
+}
+
+/// Render a conversation as plain text within `max_chars`.
+///
+/// Over budget, the first message is kept (it states the goal) and the
+/// most recent messages fill the rest; the middle is left out.
+fn build_transcript(conv: &Conversation, max_chars: usize) -> String {
+    // No single message may take more than a quarter of the budget
+    let per_message = (max_chars / 4).max(200);
+
+    let messages: Vec<String> = conv
+        .bubbles
+        .iter()
+        .filter(|b| !b.text.trim().is_empty())
+        .map(|b| {
+            let role = match b.bubble_type {
+                BubbleType::User => "User",
+                BubbleType::Assistant => "Assistant",
+                BubbleType::Unknown => "Other",
+            };
+            let text: String = b.text.trim().chars().take(per_message).collect();
+            format!("{role}: {text}")
+        })
+        .collect();
+
+    let total: usize = messages.iter().map(|m| m.len() + 2).sum();
+    if total <= max_chars || messages.len() < 2 {
+        return messages.join("\n\n");
+    }
+
+    let mut used = messages[0].len();
+    let mut tail = Vec::new();
+    for message in messages[1..].iter().rev() {
+        if used + message.len() + 2 > max_chars {
+            break;
+        }
+        used += message.len() + 2;
+        tail.push(message.as_str());
+    }
+    tail.reverse();
+
+    let omitted = messages.len() - 1 - tail.len();
+    let mut parts = vec![messages[0].clone()];
+    if omitted > 0 {
+        parts.push(format!("[... {omitted} messages omitted ...]"));
+    }
+    parts.extend(tail.into_iter().map(str::to_string));
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Bubble, ModelConfig};
+
+    fn bubble(i: usize, text: &str) -> Bubble {
+        Bubble {
+            bubble_id: format!("b{i}"),
+            bubble_type: if i.is_multiple_of(2) {
+                BubbleType::User
+            } else {
+                BubbleType::Assistant
+            },
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_poor_title() {
+        assert!(is_poor_title(""));
+        assert!(is_poor_title("conversa"));
+        assert!(is_poor_title("fix"));
+        assert!(is_poor_title("1234_5678_9_00"));
+        assert!(is_poor_title("see_homeuserprojectsappsrcmainrs_line_42"));
+        assert!(!is_poor_title("fix_the_login_redirect_loop"));
+    }
+
+    #[test]
+    fn test_build_transcript_keeps_first_and_latest() {
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: (0..20).map(|i| bubble(i, &"x".repeat(100))).collect(),
+            git: None,
+            workspace: None,
+        };
+
+        let full = build_transcript(&conv, 100_000);
+        assert_eq!(full.matches("User: ").count(), 10);
+
+        let trimmed = build_transcript(&conv, 1_000);
+        assert!(trimmed.len() <= 1_100);
+        assert!(trimmed.starts_with("User: "));
+        assert!(trimmed.contains("messages omitted"));
+        assert!(trimmed.ends_with(&"x".repeat(100)));
+    }
+}
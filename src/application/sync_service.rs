@@ -1,18 +1,85 @@
 //! Synchronization service for chat data.
 //!
 //! Handles incremental sync from Cursor's database to local storage,
-//! with change detection and workspace extraction.
+//! with change detection and workspace extraction. Chats kept outside the
+//! global composer store (composers inline in workspace databases, legacy
+//! chat tabs) are synced along with it.
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
-use crate::domain::{AppConfig, Conversation, Result, SyncState, WorkspaceInfo};
-use crate::infrastructure::{find_state_databases, LocalStorage, StateDbReader};
+use crate::domain::{
+    AppConfig, AppError, Bubble, ChangeType, Conversation, GitInfo, ModelConfig, Operation,
+    OperationKind, ResetEvent, Result, SyncState, WorkspaceInfo,
+};
+use crate::infrastructure::{
+    composer_workspaces, detect_cursor_version, detect_git, find_state_databases, fire_hook,
+    is_newer_than_known, state_db_fingerprint, LocalStorage, RawKvEntry, StateDbReader, SyncLock,
+    NEWEST_KNOWN_CURSOR_VERSION,
+};
 
-use super::parser::{extract_composer_id, extract_conversation_id, parse_bubble, parse_composer};
-use super::restore_service::RestoreService;
+use super::extractor::{database_chats, extract_in_batches, ExtractOptions};
+use super::import::ImportResult;
+use super::parser::{
+    extract_composer_id, extract_conversation_id, parse_bubble, parse_bubble_workspace,
+    parse_composer,
+};
+use super::storage_manager::{format_bytes, percent};
+
+/// Upper bound on `new_conversation` hooks run per sync.
+const MAX_HOOK_CHANGES: usize = 100_000;
+
+/// Conversations queued between the stages of the sync pipeline.
+const PIPELINE_QUEUE: usize = 64;
+
+/// Most threads parsing bubbles during a sync.
+const MAX_PARSE_THREADS: usize = 8;
+
+/// A conversation listed in a [`SyncDiff`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    /// Composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+}
+
+/// Differences between local storage and Cursor's database.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncDiff {
+    /// Stored locally but gone from Cursor (restorable).
+    pub local_only: Vec<DiffEntry>,
+    /// In Cursor but never synced.
+    pub cursor_only: Vec<DiffEntry>,
+    /// In both, but Cursor's copy changed since the last sync.
+    pub changed: Vec<DiffEntry>,
+    /// In both and identical.
+    pub unchanged: usize,
+}
+
+impl SyncDiff {
+    /// Whether both sides hold the same conversations.
+    #[must_use]
+    pub const fn is_in_sync(&self) -> bool {
+        self.local_only.is_empty() && self.cursor_only.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// What a sync cycle did.
+enum SyncOutcome {
+    /// Cursor's database had not changed; nothing was read.
+    Unchanged(SyncState),
+    /// Conversations were extracted and changed ones written.
+    Synced {
+        state: SyncState,
+        conversations: usize,
+        messages: usize,
+    },
+}
 
 /// Service for synchronizing chat data from Cursor to local storage.
 pub struct SyncService {
@@ -27,7 +94,8 @@ impl SyncService {
     /// Returns error if local storage cannot be opened.
     pub fn new(config: AppConfig) -> Result<Self> {
         let storage_path = config.storage_db_path();
-        let storage = LocalStorage::open(&storage_path)?;
+        let storage =
+            LocalStorage::open(&storage_path)?.with_compression(config.storage.compress_text);
 
         Ok(Self { config, storage })
     }
@@ -38,64 +106,146 @@ impl SyncService {
         Self { config, storage }
     }
 
-    /// Perform a full sync from Cursor database to local storage.
+    /// Sync Cursor's database to local storage.
+    ///
+    /// Skipped when Cursor's database is unchanged since the last sync; the
+    /// state is still stamped with the time of the check. Waits for a sync
+    /// running in another process (daemon or CLI) to finish first.
     ///
     /// # Errors
     /// Returns error if sync fails.
     pub fn sync(&self) -> Result<SyncState> {
-        tracing::info!("Starting sync...");
+        let _lock = SyncLock::acquire(&self.config.lock_file_path())?;
+        let started_at = Utc::now();
+        let result = self.run_sync();
 
-        // Mark sync as in progress
-        let mut state = self.storage.get_sync_state()?.syncing();
-        self.storage.update_sync_state(&state)?;
-
-        // Find and read Cursor databases
-        let databases = find_state_databases()?;
-        let global_db = databases
-            .iter()
-            .find(|p| p.to_string_lossy().contains("globalStorage"))
-            .ok_or_else(|| crate::domain::AppError::Config {
-                message: "Global storage database not found".into(),
-            })?;
+        let operation = match &result {
+            Ok(SyncOutcome::Unchanged(_)) => None,
+            Ok(SyncOutcome::Synced {
+                conversations,
+                messages,
+                ..
+            }) => Some(Operation::succeeded(
+                OperationKind::Sync,
+                started_at,
+                *conversations,
+                format!("{conversations} conversations, {messages} messages updated"),
+            )),
+            Err(e) => Some(Operation::failed(OperationKind::Sync, started_at, e)),
+        };
+        if let Some(Err(e)) = operation.map(|op| self.storage.record_operation(&op)) {
+            tracing::warn!(error = %e, "Failed to record sync");
+        }
 
-        // Extract conversations
-        let start = std::time::Instant::now();
-        let (conversations, workspace_map) = self.extract_conversations(global_db)?;
+        if let Ok(SyncOutcome::Synced {
+            state,
+            conversations,
+            messages,
+        }) = &result
+        {
+            self.run_hooks(started_at, state, *conversations, *messages);
+        }
 
-        let mut synced_count = 0;
-        let mut message_count = 0;
+        result.map(|outcome| match outcome {
+            SyncOutcome::Unchanged(state) | SyncOutcome::Synced { state, .. } => state,
+        })
+    }
 
-        for conv in &conversations {
-            // Calculate content hash for change detection
-            let content_hash = self.calculate_hash(conv);
+    /// Run the `[hooks]` scripts for a sync that wrote changes.
+    fn run_hooks(
+        &self,
+        started_at: chrono::DateTime<Utc>,
+        state: &SyncState,
+        conversations: usize,
+        messages: usize,
+    ) {
+        let hooks = &self.config.hooks;
 
-            // Check if conversation changed
-            let existing_hash = self.storage.get_conversation_hash(&conv.composer_id)?;
-            if existing_hash.as_ref() == Some(&content_hash) {
-                tracing::debug!("Skipping unchanged conversation: {}", &conv.composer_id[..8]);
-                continue;
+        if let Some(command) = hooks.on_new_conversation.as_deref() {
+            match self.storage.get_changes(Some(started_at), MAX_HOOK_CHANGES) {
+                Ok(changes) => changes
+                    .iter()
+                    .rev()
+                    .filter(|c| c.kind == ChangeType::Created)
+                    .for_each(|change| fire_hook(Some(command), "new_conversation", change)),
+                Err(e) => tracing::warn!(error = %e, "Failed to read new conversations for hook"),
             }
+        }
 
-            // Get or create workspace
-            let workspace_id = workspace_map
-                .get(&conv.composer_id)
-                .map(|ws| self.storage.get_or_create_workspace(ws))
-                .transpose()?;
+        let payload = serde_json::json!({
+            "conversations": conversations,
+            "messages": messages,
+            "state": state,
+        });
+        fire_hook(hooks.on_sync_complete.as_deref(), "sync_complete", &payload);
+    }
 
-            // Upsert conversation
-            let conv_id = self
-                .storage
-                .upsert_conversation(conv, workspace_id, Some(&content_hash))?;
+    fn run_sync(&self) -> Result<SyncOutcome> {
+        let (global, workspace_dbs) = state_databases()?;
+        self.run_sync_from(&global, &workspace_dbs)
+    }
 
-            // Upsert bubbles
-            for bubble in &conv.bubbles {
-                self.storage.upsert_bubble(bubble, conv_id)?;
-            }
+    /// Sync from the Cursor global database at `db_path` and the workspace
+    /// databases `workspace_dbs`, unless none changed since the last sync.
+    fn run_sync_from(&self, db_path: &Path, workspace_dbs: &[PathBuf]) -> Result<SyncOutcome> {
+        // Taken before extracting, so writes made meanwhile trigger the next sync
+        let fingerprint = databases_fingerprint(db_path, workspace_dbs)?;
+        let mut state = self.storage.get_sync_state()?;
+        if state.last_hash.as_deref() == Some(fingerprint.as_str()) {
+            tracing::debug!("Cursor database unchanged, skipping sync");
+            state.last_sync = Some(Utc::now());
+            self.storage.update_sync_state(&state)?;
+            return Ok(SyncOutcome::Unchanged(state));
+        }
 
-            synced_count += 1;
-            message_count += conv.bubbles.len();
+        tracing::info!("Starting sync...");
+
+        let cursor_version = detect_cursor_version();
+        if let Some(version) = &cursor_version {
+            // Once per Cursor update, not on every sync
+            if state.cursor_version.as_ref() != Some(version) && is_newer_than_known(version) {
+                tracing::warn!(
+                    version,
+                    known = NEWEST_KNOWN_CURSOR_VERSION,
+                    "Cursor is newer than the versions this tool was tested with; \
+                     conversations may be missing or incomplete"
+                );
+            }
         }
 
+        // Mark sync as in progress
+        state = state.syncing();
+        self.storage.update_sync_state(&state)?;
+
+        // Extract conversations
+        let start = std::time::Instant::now();
+        let synced_at = Utc::now();
+        let (synced_count, message_count) = if let Some(budget) = self.config.memory_budget_bytes()
+        {
+            let options = ExtractOptions {
+                min_messages: 1,
+                ..Default::default()
+            };
+            let mut totals = (0, 0);
+            extract_in_batches(db_path, &options, budget, |batch| {
+                let (synced, messages) = self.storage.in_transaction(|| {
+                    self.store_changed(&batch.conversations, &batch.workspaces, synced_at)
+                })?;
+                totals.0 += synced;
+                totals.1 += messages;
+                Ok(())
+            })?;
+            totals
+        } else {
+            self.sync_pipeline(db_path, synced_at)?
+        };
+        let (database_synced, database_messages) =
+            self.sync_database_chats(db_path, workspace_dbs, synced_at)?;
+        let synced_count = synced_count + database_synced;
+        let message_count = message_count + database_messages;
+        self.assign_workspaces()?;
+        self.record_git(synced_at)?;
+
         let elapsed = start.elapsed();
         tracing::info!(
             synced = synced_count,
@@ -107,155 +257,403 @@ impl SyncService {
         // Update sync state
         state = SyncState {
             last_sync: Some(Utc::now()),
-            last_hash: None, // Could calculate global hash here
+            last_hash: Some(fingerprint),
             conversation_count: self.storage.get_conversation_count()?,
             message_count: self.storage.get_message_count()?,
             storage_bytes: self.storage.get_storage_size()?,
             is_syncing: false,
             last_error: None,
+            cursor_version,
         };
 
         self.storage.update_sync_state(&state)?;
 
-        Ok(state)
+        Ok(SyncOutcome::Synced {
+            state,
+            conversations: synced_count,
+            messages: message_count,
+        })
     }
 
-    /// Extract conversations from a Cursor database.
-    fn extract_conversations(
+    /// Write new and changed conversations to storage and the change feed.
+    ///
+    /// Returns the number of conversations and messages written.
+    fn store_changed(
         &self,
-        db_path: &std::path::Path,
-    ) -> Result<(Vec<Conversation>, std::collections::HashMap<String, WorkspaceInfo>)> {
-        use std::collections::HashMap;
+        conversations: &[Conversation],
+        workspace_map: &HashMap<String, WorkspaceInfo>,
+        synced_at: DateTime<Utc>,
+    ) -> Result<(usize, usize)> {
+        let mut synced_count = 0;
+        let mut message_count = 0;
 
-        let reader = StateDbReader::open(db_path)?;
-        let mut conversations: HashMap<String, Conversation> = HashMap::new();
-        let mut workspace_map: HashMap<String, WorkspaceInfo> = HashMap::new();
-
-        // Load composers
-        for entry in reader.fetch_composers()? {
-            if let Some(id) = extract_composer_id(&entry.key) {
-                if let Ok(raw) = parse_composer(&entry.value) {
-                    let created_at = raw.created_at.and_then(|ms| {
-                        chrono::DateTime::from_timestamp_millis(ms as i64)
-                    });
-
-                    let model_config = raw.model_config.map_or_else(
-                        crate::domain::ModelConfig::default,
-                        |m| crate::domain::ModelConfig {
-                            model_name: m.model_name,
-                            max_mode: m.max_mode,
-                        },
-                    );
-
-                    conversations.insert(
-                        id.to_string(),
-                        Conversation {
-                            composer_id: id.to_string(),
-                            title: String::new(),
-                            created_at,
-                            model_config,
-                            unified_mode: raw.unified_mode.unwrap_or_default(),
-                            bubbles: Vec::new(),
-                        },
-                    );
-                }
+        for conv in conversations {
+            if self.store_conversation(conv, workspace_map.get(&conv.composer_id), synced_at)? {
+                synced_count += 1;
+                message_count += conv.bubbles.len();
             }
         }
 
-        // Load bubbles and extract workspace info
-        for entry in reader.fetch_bubbles()? {
-            if let Some(conv_id) = extract_conversation_id(&entry.key) {
-                if let Ok(bubble) = parse_bubble(&entry.value) {
-                    // Skip empty messages
-                    if bubble.text.trim().is_empty() {
-                        continue;
-                    }
+        Ok((synced_count, message_count))
+    }
+
+    /// Write a conversation unless storage already has the same content.
+    ///
+    /// Returns whether it was written.
+    fn store_conversation(
+        &self,
+        conv: &Conversation,
+        workspace: Option<&WorkspaceInfo>,
+        synced_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        // Calculate content hash for change detection
+        let content_hash = conv.content_hash();
+
+        // Check if conversation changed
+        let existing_hash = self.storage.get_conversation_hash(&conv.composer_id)?;
+        let change_type = match existing_hash {
+            Some(hash) if hash == content_hash => {
+                tracing::debug!("Skipping unchanged conversation: {}", conv.short_id());
+                return Ok(false);
+            }
+            Some(_) => ChangeType::Updated,
+            None => ChangeType::Created,
+        };
 
-                    // Try to extract workspace info from bubble JSON
-                    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&entry.value) {
-                        if let Some(workspace_uri) = json
-                            .get("workspaceUris")
-                            .and_then(|v| v.as_array())
-                            .and_then(|arr| arr.first())
-                            .and_then(|v| v.as_str())
-                        {
-                            let mut ws_info = WorkspaceInfo::from_uri(workspace_uri);
-                            ws_info.cursor_path = json
-                                .get("workspaceProjectDir")
-                                .and_then(|v| v.as_str())
-                                .map(String::from);
-
-                            workspace_map.insert(conv_id.to_string(), ws_info);
+        // Get or create workspace
+        let workspace_id = workspace
+            .map(|ws| self.storage.get_or_create_workspace(ws))
+            .transpose()?;
+
+        // Upsert conversation
+        let conv_id = self
+            .storage
+            .upsert_conversation(conv, workspace_id, Some(&content_hash))?;
+
+        // Upsert bubbles
+        for bubble in &conv.bubbles {
+            self.storage.upsert_bubble(bubble, conv_id)?;
+        }
+
+        self.storage
+            .record_change(&conv.composer_id, change_type, synced_at)?;
+
+        Ok(true)
+    }
+
+    /// Merge exported conversations into local storage.
+    ///
+    /// Stored messages are kept and imported ones are added by bubble ID, so
+    /// importing the same export twice changes nothing. Waits for a running
+    /// sync to finish first.
+    ///
+    /// # Errors
+    /// Returns error if a write fails; nothing is imported then.
+    pub fn import(&self, conversations: &[Conversation]) -> Result<ImportResult> {
+        let _lock = SyncLock::acquire(&self.config.lock_file_path())?;
+        self.storage
+            .in_transaction(|| self.store_imported(conversations))
+    }
+
+    fn store_imported(&self, conversations: &[Conversation]) -> Result<ImportResult> {
+        let workspaces: HashMap<String, WorkspaceInfo> = self
+            .storage
+            .get_workspaces()?
+            .into_iter()
+            .filter(|ws| ws.path.is_some())
+            .map(|ws| (ws.name.clone(), ws))
+            .collect();
+        let imported_at = Utc::now();
+        let mut result = ImportResult::default();
+
+        for conv in conversations {
+            let stored = self.storage.get_conversation(&conv.composer_id)?;
+            let is_new = stored.is_none();
+            let merged = stored.map_or_else(
+                || Some((conv.clone(), conv.bubbles.len())),
+                |stored| merge_conversation(stored, conv),
+            );
+            let Some((merged, added)) = merged else {
+                result.unchanged += 1;
+                continue;
+            };
+            if is_new {
+                result.created += 1;
+            } else {
+                result.updated += 1;
+            }
+            result.messages += added;
+
+            let workspace = conv
+                .workspace
+                .as_ref()
+                .and_then(|name| workspaces.get(name));
+            self.store_conversation(&merged, workspace, imported_at)?;
+            if let Some(git) = &conv.git {
+                self.storage.set_conversation_git(&conv.composer_id, git)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Sync the whole database through a pipeline, so parsing and writes
+    /// overlap: one thread reads the bubble rows of each conversation, a
+    /// pool of threads parses them, and this thread writes the changed
+    /// conversations in a single transaction.
+    ///
+    /// Returns the number of conversations and messages written.
+    fn sync_pipeline(&self, db_path: &Path, synced_at: DateTime<Utc>) -> Result<(usize, usize)> {
+        let shells = conversation_shells(&StateDbReader::open(db_path)?)?;
+        let threads = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(MAX_PARSE_THREADS);
+
+        let (raw_tx, raw_rx) = mpsc::sync_channel::<(String, Vec<RawKvEntry>)>(PIPELINE_QUEUE);
+        // Owned by the parsers only, so the reader stops once they all exit
+        let raw_rx = Arc::new(Mutex::new(raw_rx));
+        let (parsed_tx, parsed_rx) = mpsc::sync_channel(PIPELINE_QUEUE);
+
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(move || send_bubble_groups(db_path, &raw_tx));
+
+            for _ in 0..threads {
+                let (raw_rx, parsed_tx) = (Arc::clone(&raw_rx), parsed_tx.clone());
+                let shells = &shells;
+                scope.spawn(move || {
+                    // The lock is only held while waiting for the next group
+                    while let Some((id, entries)) = raw_rx.lock().ok().and_then(|rx| rx.recv().ok())
+                    {
+                        let Some(parsed) = assemble_conversation(&id, &entries, shells.get(&id))
+                        else {
+                            continue;
+                        };
+                        if parsed_tx.send(parsed).is_err() {
+                            break;
                         }
                     }
+                });
+            }
+            drop((raw_rx, parsed_tx));
+
+            // Returning early drops the receiver, which stops the other stages
+            let written = self.storage.in_transaction(move || {
+                let mut totals = (0, 0);
+                for (conv, workspace) in parsed_rx {
+                    if self.store_conversation(&conv, workspace.as_ref(), synced_at)? {
+                        totals.0 += 1;
+                        totals.1 += conv.bubbles.len();
+                    }
+                }
+                Ok(totals)
+            });
+            let read = reader.join().unwrap_or_else(|_| {
+                Err(AppError::Config {
+                    message: "Sync reader thread panicked".into(),
+                })
+            });
+
+            written.and_then(|totals| read.map(|()| totals))
+        })
+    }
 
-                    // Get or create conversation
-                    let conv = conversations
-                        .entry(conv_id.to_string())
-                        .or_insert_with(|| Conversation {
-                            composer_id: conv_id.to_string(),
-                            title: String::new(),
-                            created_at: bubble.created_at,
-                            model_config: crate::domain::ModelConfig::default(),
-                            unified_mode: String::new(),
-                            bubbles: Vec::new(),
-                        });
-
-                    conv.bubbles.push(bubble);
+    /// Write the chats kept outside the global composer store that changed
+    /// (see [`outside_chats`]), attributed to the workspace of the database
+    /// holding them.
+    ///
+    /// Returns the number of conversations and messages written.
+    fn sync_database_chats(
+        &self,
+        db_path: &Path,
+        workspace_dbs: &[PathBuf],
+        synced_at: DateTime<Utc>,
+    ) -> Result<(usize, usize)> {
+        let chats = outside_chats(db_path, workspace_dbs)?;
+        self.storage.in_transaction(|| {
+            let mut totals = (0, 0);
+            for (conv, workspace) in &chats {
+                if self.store_conversation(conv, workspace.as_ref(), synced_at)? {
+                    totals.0 += 1;
+                    totals.1 += conv.bubbles.len();
                 }
             }
+            Ok(totals)
+        })
+    }
+
+    /// Attribute stored conversations to the workspaces recorded in Cursor's
+    /// `workspaceStorage`, which overrides the `workspaceUris` of bubbles.
+    fn assign_workspaces(&self) -> Result<()> {
+        let workspaces = match composer_workspaces() {
+            Ok(workspaces) => workspaces,
+            Err(e) => {
+                tracing::debug!(error = %e, "Workspace storage unavailable");
+                return Ok(());
+            }
+        };
+
+        let mut ids: std::collections::HashMap<Option<String>, i64> =
+            std::collections::HashMap::new();
+        let mut assigned = 0;
+        for (composer_id, info) in &workspaces {
+            let workspace_id = if let Some(&id) = ids.get(&info.cursor_path) {
+                id
+            } else {
+                let id = self.storage.get_or_create_workspace(info)?;
+                ids.insert(info.cursor_path.clone(), id);
+                id
+            };
+            if self
+                .storage
+                .set_conversation_workspace(composer_id, workspace_id)?
+            {
+                assigned += 1;
+            }
         }
 
-        // Sort bubbles and generate titles
-        let mut result: Vec<Conversation> = conversations
-            .into_values()
-            .filter(|c| !c.bubbles.is_empty())
-            .map(|mut c| {
-                c.bubbles.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-                c.title = c.generate_title();
-                c
-            })
-            .collect();
+        if assigned > 0 {
+            tracing::info!(conversations = assigned, "Updated workspace attribution");
+        }
+        Ok(())
+    }
 
-        // Sort by creation time (newest first)
-        result.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    /// Record the git repository, origin and branch of the workspace of each
+    /// conversation changed by this sync or not yet associated with one.
+    fn record_git(&self, synced_at: chrono::DateTime<Utc>) -> Result<()> {
+        let mut detected: std::collections::HashMap<std::path::PathBuf, Option<GitInfo>> =
+            std::collections::HashMap::new();
+        for (composer_id, path) in self.storage.git_candidate_paths(synced_at)? {
+            let git = detected
+                .entry(path)
+                .or_insert_with_key(|path| detect_git(path));
+            if let Some(git) = git {
+                self.storage.set_conversation_git(&composer_id, git)?;
+            }
+        }
+        Ok(())
+    }
 
-        Ok((result, workspace_map))
+    /// Compare local storage with Cursor's live database.
+    ///
+    /// # Errors
+    /// Returns error if either database cannot be read.
+    pub fn diff(&self) -> Result<SyncDiff> {
+        let (global, workspace_dbs) = state_databases()?;
+        self.diff_with(&global, &workspace_dbs)
     }
 
-    /// Calculate a hash for change detection.
-    fn calculate_hash(&self, conv: &Conversation) -> String {
-        let mut hasher = DefaultHasher::new();
+    /// Compare local storage with the Cursor global database at `db_path`
+    /// and the workspace databases `workspace_dbs`.
+    fn diff_with(&self, db_path: &Path, workspace_dbs: &[PathBuf]) -> Result<SyncDiff> {
+        let (mut conversations, _) = Self::extract_conversations(db_path)?;
+        conversations.extend(
+            outside_chats(db_path, workspace_dbs)?
+                .into_iter()
+                .map(|(conv, _)| conv),
+        );
+        let mut stored = self.storage.conversation_hashes()?;
+        let mut diff = SyncDiff::default();
+
+        for conv in &conversations {
+            match stored.remove(&conv.composer_id) {
+                None => diff.cursor_only.push(DiffEntry {
+                    composer_id: conv.composer_id.clone(),
+                    title: conv.title.clone(),
+                }),
+                Some((_, hash)) if hash.as_deref() == Some(conv.content_hash().as_str()) => {
+                    diff.unchanged += 1;
+                }
+                Some((title, _)) => diff.changed.push(DiffEntry {
+                    composer_id: conv.composer_id.clone(),
+                    title,
+                }),
+            }
+        }
+
+        diff.local_only = stored
+            .into_iter()
+            .map(|(composer_id, (title, _))| DiffEntry { composer_id, title })
+            .collect();
+        diff.local_only.sort_by(|a, b| a.title.cmp(&b.title));
 
-        conv.composer_id.hash(&mut hasher);
-        conv.title.hash(&mut hasher);
-        conv.bubbles.len().hash(&mut hasher);
+        Ok(diff)
+    }
 
-        // Hash last few bubbles for efficiency
-        for bubble in conv.bubbles.iter().rev().take(5) {
-            bubble.bubble_id.hash(&mut hasher);
-            bubble.text.hash(&mut hasher);
+    /// Extract conversations from a Cursor database.
+    fn extract_conversations(
+        db_path: &Path,
+    ) -> Result<(Vec<Conversation>, HashMap<String, WorkspaceInfo>)> {
+        let reader = StateDbReader::open(db_path)?;
+        let shells = conversation_shells(&reader)?;
+
+        let mut groups: HashMap<String, Vec<RawKvEntry>> = HashMap::new();
+        for entry in reader.fetch_bubbles()? {
+            if let Some(id) = extract_conversation_id(&entry.key) {
+                groups.entry(id.to_string()).or_default().push(entry);
+            }
+        }
+
+        let mut result = Vec::new();
+        let mut workspace_map = HashMap::new();
+        for (id, entries) in groups {
+            if let Some((conv, workspace)) = assemble_conversation(&id, &entries, shells.get(&id)) {
+                if let Some(workspace) = workspace {
+                    workspace_map.insert(id, workspace);
+                }
+                result.push(conv);
+            }
         }
 
-        format!("{:x}", hasher.finish())
+        // Sort by creation time (newest first)
+        result.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+
+        Ok((result, workspace_map))
     }
 
     /// Get current sync state.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
     pub fn get_state(&self) -> Result<SyncState> {
         self.storage.get_sync_state()
     }
 
     /// Get conversations from local storage.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
     pub fn get_conversations(&self, workspace: Option<&str>) -> Result<Vec<Conversation>> {
         self.storage.get_conversations(workspace)
     }
 
+    /// Record a detected Cursor reset.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
+    pub fn record_reset_event(&self, event: &ResetEvent) -> Result<()> {
+        self.storage.record_reset_event(event)
+    }
+
+    /// Get the most recent reset events (newest first).
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
+    pub fn get_reset_events(&self, limit: usize) -> Result<Vec<ResetEvent>> {
+        self.storage.get_reset_events(limit)
+    }
+
     /// Get all workspaces.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
     pub fn get_workspaces(&self) -> Result<Vec<WorkspaceInfo>> {
         self.storage.get_workspaces()
     }
 
     /// Check if storage is within limits.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
     pub fn check_storage_limits(&self) -> Result<bool> {
         let current_size = self.storage.get_storage_size()?;
         let max_size = self.config.max_storage_bytes();
@@ -263,6 +661,9 @@ impl SyncService {
     }
 
     /// Get storage usage info.
+    ///
+    /// # Errors
+    /// Returns error if local storage cannot be queried.
     pub fn get_storage_info(&self) -> Result<StorageInfo> {
         let current_bytes = self.storage.get_storage_size()?;
         let max_bytes = self.config.max_storage_bytes();
@@ -270,7 +671,7 @@ impl SyncService {
         Ok(StorageInfo {
             current_bytes,
             max_bytes,
-            usage_percent: (current_bytes as f64 / max_bytes as f64) * 100.0,
+            usage_percent: percent(current_bytes, max_bytes),
             conversation_count: self.storage.get_conversation_count()?,
             message_count: self.storage.get_message_count()?,
         })
@@ -306,27 +707,538 @@ impl StorageInfo {
     }
 }
 
-/// Format bytes as human readable string.
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+/// Conversations without bubbles for each composer entry, by composer ID.
+fn conversation_shells(reader: &StateDbReader) -> Result<HashMap<String, Conversation>> {
+    let mut shells = HashMap::new();
+
+    for entry in reader.fetch_composers()? {
+        let Some(id) = extract_composer_id(&entry.key) else {
+            continue;
+        };
+        let Ok(raw) = parse_composer(&entry.value) else {
+            continue;
+        };
+        let created_at = raw
+            .created_at
+            .and_then(|ms| DateTime::from_timestamp_millis(ms.cast_signed()));
+        let model_config = raw
+            .model_config
+            .map_or_else(ModelConfig::default, |m| ModelConfig {
+                model_name: m.model_name,
+                max_mode: m.max_mode,
+            });
+
+        shells.insert(
+            id.to_string(),
+            Conversation {
+                composer_id: id.to_string(),
+                title: String::new(),
+                created_at,
+                model_config,
+                unified_mode: raw.unified_mode.unwrap_or_default(),
+                bubbles: Vec::new(),
+                git: None,
+                workspace: None,
+            },
+        );
+    }
+
+    Ok(shells)
+}
+
+/// Build a conversation from its bubble rows and composer shell, with
+/// the workspace of its last bubble that names one.
+///
+/// `None` if no bubble has content. Without a shell, the conversation is
+/// dated by its first bubble.
+fn assemble_conversation(
+    id: &str,
+    entries: &[RawKvEntry],
+    shell: Option<&Conversation>,
+) -> Option<(Conversation, Option<WorkspaceInfo>)> {
+    let mut workspace = None;
+    let mut bubbles = Vec::new();
+    for entry in entries {
+        let Ok(bubble) = parse_bubble(&entry.value) else {
+            continue;
+        };
+        // Skip empty messages
+        if bubble.is_empty() {
+            continue;
+        }
+        if let Some(ws_info) = parse_bubble_workspace(&entry.value) {
+            workspace = Some(ws_info);
+        }
+        bubbles.push(bubble);
+    }
+    if bubbles.is_empty() {
+        return None;
+    }
+
+    let mut conv = shell.cloned().unwrap_or_else(|| Conversation {
+        composer_id: id.to_string(),
+        title: String::new(),
+        created_at: bubbles[0].created_at,
+        model_config: ModelConfig::default(),
+        unified_mode: String::new(),
+        bubbles: Vec::new(),
+        git: None,
+        workspace: None,
+    });
+    bubbles.sort_by_key(|b| b.created_at);
+    conv.bubbles = bubbles;
+    conv.title = conv.generate_title();
+
+    Some((conv, workspace))
+}
+
+/// Add the messages of `imported` missing from `stored`, and the metadata
+/// `stored` lacks.
+///
+/// Returns the merged conversation and the number of messages added, or
+/// `None` when `imported` adds nothing.
+fn merge_conversation(
+    mut stored: Conversation,
+    imported: &Conversation,
+) -> Option<(Conversation, usize)> {
+    let known: std::collections::HashSet<&str> = stored
+        .bubbles
+        .iter()
+        .map(|b| b.bubble_id.as_str())
+        .collect();
+    let missing: Vec<Bubble> = imported
+        .bubbles
+        .iter()
+        .filter(|b| !known.contains(b.bubble_id.as_str()))
+        .cloned()
+        .collect();
+
+    let fill_title = stored.title.is_empty() && !imported.title.is_empty();
+    let fill_created = stored.created_at.is_none() && imported.created_at.is_some();
+    let fill_model =
+        stored.model_config.model_name.is_empty() && !imported.model_config.model_name.is_empty();
+    let fill_mode = stored.unified_mode.is_empty() && !imported.unified_mode.is_empty();
+    if missing.is_empty() && !(fill_title || fill_created || fill_model || fill_mode) {
+        return None;
+    }
+
+    if fill_title {
+        stored.title.clone_from(&imported.title);
+    }
+    if fill_created {
+        stored.created_at = imported.created_at;
+    }
+    if fill_model {
+        stored.model_config = imported.model_config.clone();
+    }
+    if fill_mode {
+        stored.unified_mode.clone_from(&imported.unified_mode);
+    }
+
+    let added = missing.len();
+    stored.bubbles.extend(missing);
+    stored.bubbles.sort_by_key(|b| b.created_at);
+    Some((stored, added))
+}
+
+/// Send the bubble rows of the database grouped by conversation, until
+/// they run out or nobody is receiving.
+fn send_bubble_groups(
+    db_path: &Path,
+    tx: &mpsc::SyncSender<(String, Vec<RawKvEntry>)>,
+) -> Result<()> {
+    let mut group: Option<(String, Vec<RawKvEntry>)> = None;
+    let mut receiving = true;
+
+    StateDbReader::open(db_path)?.for_each_bubble(|entry| {
+        let Some(id) = extract_conversation_id(&entry.key) else {
+            return true;
+        };
+        match &mut group {
+            Some((current, entries)) if current == id => entries.push(entry),
+            _ => {
+                let next = (id.to_string(), vec![entry]);
+                if let Some(done) = group.replace(next) {
+                    receiving = tx.send(done).is_ok();
+                }
+            }
+        }
+        receiving
+    })?;
+
+    if let (true, Some(last)) = (receiving, group) {
+        // A closed channel means the writer stopped and reports why
+        let _ = tx.send(last);
+    }
+    Ok(())
+}
+
+/// Path of Cursor's global state database, and those of its workspaces.
+fn state_databases() -> Result<(PathBuf, Vec<PathBuf>)> {
+    let (global, workspaces): (Vec<_>, Vec<_>) = find_state_databases()?
+        .into_iter()
+        .partition(|p| p.to_string_lossy().contains("globalStorage"));
+    let global = global.into_iter().next().ok_or_else(|| AppError::Config {
+        message: "Global storage database not found".into(),
+    })?;
+    Ok((global, workspaces))
+}
+
+/// Fingerprint of the global database and every workspace database, so a
+/// change to any of them triggers a sync. Unreadable workspace databases
+/// count as empty.
+fn databases_fingerprint(db_path: &Path, workspace_dbs: &[PathBuf]) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(state_db_fingerprint(db_path)?.as_bytes());
+    for path in workspace_dbs {
+        let fingerprint = state_db_fingerprint(path).unwrap_or_default();
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(fingerprint.as_bytes());
+        hasher.update(b"\0");
     }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// The chats kept outside the global composer store at `db_path` (see
+/// [`database_chats`]): composers inline in `workspace_dbs` and legacy chat
+/// tabs in any database, with their workspace, as a sync stores them: empty
+/// messages dropped, in order and titled. IDs the global store has are
+/// left to it.
+fn outside_chats(
+    db_path: &Path,
+    workspace_dbs: &[PathBuf],
+) -> Result<Vec<(Conversation, Option<WorkspaceInfo>)>> {
+    let global_ids: HashSet<String> = conversation_shells(&StateDbReader::open(db_path)?)?
+        .into_keys()
+        .collect();
+    let databases: Vec<PathBuf> = std::iter::once(db_path.to_path_buf())
+        .chain(workspace_dbs.iter().cloned())
+        .collect();
+
+    Ok(database_chats(&databases, db_path)
+        .into_iter()
+        .filter_map(|(mut conv, workspace)| {
+            conv.bubbles.retain(|b| !b.is_empty());
+            if conv.bubbles.is_empty() || global_ids.contains(&conv.composer_id) {
+                return None;
+            }
+            conv.bubbles.sort_by_key(|b| b.created_at);
+            conv.title = conv.generate_title();
+            Some((conv, workspace))
+        })
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A state database with `conversations` conversations of two messages
+    /// and an empty bubble, plus one without a composer entry.
+    fn state_db(dir: &Path, conversations: usize) -> std::path::PathBuf {
+        let db_path = dir.join("state.vscdb");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch("CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB)")
+            .unwrap();
+        let insert = |key: String, value: serde_json::Value| {
+            conn.execute(
+                "INSERT INTO cursorDiskKV (key, value) VALUES (?1, ?2)",
+                (key, value.to_string()),
+            )
+            .unwrap();
+        };
+        for c in 0..conversations {
+            insert(
+                format!("composerData:c{c:02}"),
+                serde_json::json!({"createdAt": 1_700_000_000_000_u64, "unifiedMode": "agent"}),
+            );
+            for m in 0..3 {
+                let text = if m == 2 { "" } else { "hello" };
+                insert(
+                    format!("bubbleId:c{c:02}:b{c}-{m}"),
+                    serde_json::json!({"type": 1, "bubbleId": format!("b{c}-{m}"), "text": text}),
+                );
+            }
+        }
+        // Bubbles without a composer entry still form a conversation
+        insert(
+            "bubbleId:orphan:o1".into(),
+            serde_json::json!({"type": 1, "bubbleId": "o1", "text": "hi"}),
+        );
+        db_path
+    }
+
+    /// A workspace database of the project `/home/me/legacy` holding
+    /// `composers` inline, in its own `workspaceStorage` folder under `dir`.
+    fn workspace_db(dir: &Path, composers: &serde_json::Value) -> PathBuf {
+        let folder = dir.join("workspaceStorage").join("0123abcd");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("workspace.json"),
+            r#"{"folder": "file:///home/me/legacy"}"#,
+        )
+        .unwrap();
+        let db_path = folder.join("state.vscdb");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch("CREATE TABLE ItemTable (key TEXT UNIQUE, value BLOB)")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO ItemTable (key, value) VALUES ('composer.composerData', ?1)",
+            [serde_json::json!({ "allComposers": composers }).to_string()],
+        )
+        .unwrap();
+        db_path
+    }
+
+    #[test]
+    fn test_sync_stores_workspace_database_composers() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = state_db(dir.path(), 2);
+        let inline = |id: &str, texts: &[&str]| {
+            let bubbles: Vec<_> = texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| serde_json::json!({"type": 1, "bubbleId": format!("{id}-{i}"), "text": text}))
+                .collect();
+            serde_json::json!({"composerId": id, "createdAt": 1_600_000_000_000_u64, "conversation": bubbles})
+        };
+        // c00 is also in the global store, which wins
+        let ws_db = workspace_db(
+            dir.path(),
+            &serde_json::json!([
+                inline("w-old", &["Rename it", "Done"]),
+                inline("c00", &["stale"])
+            ]),
+        );
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+
+        let outcome = service
+            .run_sync_from(&db_path, std::slice::from_ref(&ws_db))
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            SyncOutcome::Synced {
+                conversations: 4,
+                ..
+            }
+        ));
+        let stored = service.storage.get_conversations(Some("legacy")).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].composer_id, "w-old");
+        assert_eq!(stored[0].bubbles.len(), 2);
+        assert_eq!(service.storage.get_bubbles("c00").unwrap().len(), 2);
+        assert!(service
+            .diff_with(&db_path, std::slice::from_ref(&ws_db))
+            .unwrap()
+            .is_in_sync());
+
+        // A change to the workspace database alone triggers a sync
+        assert!(matches!(
+            service
+                .run_sync_from(&db_path, std::slice::from_ref(&ws_db))
+                .unwrap(),
+            SyncOutcome::Unchanged(_)
+        ));
+        rusqlite::Connection::open(&ws_db)
+            .unwrap()
+            .execute(
+                "UPDATE ItemTable SET value = ?1",
+                [serde_json::json!({ "allComposers": [inline("w-old", &["Rename it", "Done", "Thanks"])] })
+                    .to_string()],
+            )
+            .unwrap();
+        let outcome = service.run_sync_from(&db_path, &[ws_db]).unwrap();
+        assert!(matches!(
+            outcome,
+            SyncOutcome::Synced {
+                conversations: 1,
+                messages: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_sync_stores_legacy_chat_tabs() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = state_db(dir.path(), 1);
+        let ws_db = workspace_db(dir.path(), &serde_json::json!([]));
+        let tabs = serde_json::json!({"tabs": [{"tabId": "tab-1", "chatTitle": "Parser", "bubbles": [
+            {"type": "user", "id": "u1", "text": "Why does the parser fail?"},
+            {"type": "ai", "id": "a1", "text": "Missing semicolon."},
+        ]}]});
+        rusqlite::Connection::open(&ws_db)
+            .unwrap()
+            .execute(
+                "INSERT INTO ItemTable (key, value) VALUES (?1, ?2)",
+                (
+                    crate::application::legacy_chat::LEGACY_CHAT_KEY,
+                    tabs.to_string(),
+                ),
+            )
+            .unwrap();
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+
+        service
+            .run_sync_from(&db_path, std::slice::from_ref(&ws_db))
+            .unwrap();
+        let stored = service.storage.get_conversations(Some("legacy")).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].composer_id, "tab-1");
+        assert_eq!(stored[0].bubbles.len(), 2);
+        assert!(service.diff_with(&db_path, &[ws_db]).unwrap().is_in_sync());
+    }
+
+    #[test]
+    fn test_sync_pipeline_writes_changed_conversations() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = state_db(dir.path(), 20);
+
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+
+        let synced_at = Utc::now();
+        assert_eq!(
+            service.sync_pipeline(&db_path, synced_at).unwrap(),
+            (21, 41)
+        );
+        assert_eq!(service.storage.get_conversation_count().unwrap(), 21);
+        assert_eq!(service.storage.get_bubbles("c07").unwrap().len(), 2);
+
+        // Nothing changed since
+        assert_eq!(service.sync_pipeline(&db_path, synced_at).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_sync_pipeline_stops_when_writes_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        // More conversations than the queues hold, so the reader is still
+        // sending when the writer gives up
+        let db_path = state_db(dir.path(), 4 * PIPELINE_QUEUE);
+
+        let storage_path = dir.path().join("storage.db");
+        let storage = LocalStorage::open(&storage_path).unwrap();
+        rusqlite::Connection::open(&storage_path)
+            .unwrap()
+            .execute_batch("DROP TABLE changes")
+            .unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(service.sync_pipeline(&db_path, Utc::now()).is_err());
+        });
+        let failed = rx
+            .recv_timeout(std::time::Duration::from_secs(30))
+            .expect("sync pipeline deadlocked");
+        assert!(failed);
+    }
+
+    #[test]
+    fn test_sync_skips_unchanged_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = state_db(dir.path(), 3);
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+
+        let outcome = service.run_sync_from(&db_path, &[]).unwrap();
+        assert!(matches!(
+            outcome,
+            SyncOutcome::Synced {
+                conversations: 4,
+                ..
+            }
+        ));
+        assert!(matches!(
+            service.run_sync_from(&db_path, &[]).unwrap(),
+            SyncOutcome::Unchanged(_)
+        ));
+
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO cursorDiskKV (key, value) VALUES ('bubbleId:c00:b0-9', ?1)",
+                [serde_json::json!({"type": 1, "bubbleId": "b0-9", "text": "more"}).to_string()],
+            )
+            .unwrap();
+        let outcome = service.run_sync_from(&db_path, &[]).unwrap();
+        assert!(matches!(
+            outcome,
+            SyncOutcome::Synced {
+                conversations: 1,
+                messages: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_diff_reports_created_updated_and_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = state_db(dir.path(), 3);
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+        service.sync_pipeline(&db_path, Utc::now()).unwrap();
+
+        let diff = service.diff_with(&db_path, &[]).unwrap();
+        assert!(diff.is_in_sync());
+        assert_eq!(diff.unchanged, 4);
+
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute(
+            "UPDATE cursorDiskKV SET value = ?1 WHERE key = 'bubbleId:c01:b1-0'",
+            [serde_json::json!({"type": 1, "bubbleId": "b1-0", "text": "edited"}).to_string()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO cursorDiskKV (key, value) VALUES ('bubbleId:new:n1', ?1)",
+            [serde_json::json!({"type": 1, "bubbleId": "n1", "text": "new"}).to_string()],
+        )
+        .unwrap();
+        conn.execute("DELETE FROM cursorDiskKV WHERE key LIKE '%:c02%'", [])
+            .unwrap();
+
+        let diff = service.diff_with(&db_path, &[]).unwrap();
+        let ids = |entries: &[DiffEntry]| -> Vec<String> {
+            entries.iter().map(|e| e.composer_id.clone()).collect()
+        };
+        assert_eq!(ids(&diff.changed), ["c01"]);
+        assert_eq!(ids(&diff.cursor_only), ["new"]);
+        assert_eq!(ids(&diff.local_only), ["c02"]);
+        assert_eq!(diff.unchanged, 2);
+        assert!(!diff.is_in_sync());
+    }
+
+    #[test]
+    fn test_import_merges_by_bubble_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("storage.db")).unwrap();
+        let service = SyncService::with_storage(AppConfig::default(), storage);
+        let export = |bubbles: &[&str]| {
+            let bubbles: Vec<_> = bubbles
+                .iter()
+                .map(|id| serde_json::json!({"bubble_id": id, "bubble_type": 1, "text": id, "created_at": null}))
+                .collect();
+            serde_json::from_value::<Conversation>(serde_json::json!({
+                "composer_id": "c1", "title": "Imported", "created_at": null, "bubbles": bubbles,
+            }))
+            .unwrap()
+        };
+
+        let result = service.store_imported(&[export(&["b1", "b2"])]).unwrap();
+        assert_eq!((result.created, result.messages), (1, 2));
+
+        let result = service.store_imported(&[export(&["b2", "b3"])]).unwrap();
+        assert_eq!((result.updated, result.messages), (1, 1));
+        assert_eq!(service.storage.get_bubbles("c1").unwrap().len(), 3);
+
+        let result = service.store_imported(&[export(&["b1"])]).unwrap();
+        assert_eq!((result.unchanged, result.messages), (1, 0));
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(500), "500 B");
@@ -335,4 +1247,3 @@ mod tests {
         assert_eq!(format_bytes(1024 * 1024 * 1024), "1.00 GB");
     }
 }
-
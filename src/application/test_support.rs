@@ -0,0 +1,57 @@
+//! Conversation fixtures shared by the application tests.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::domain::{Bubble, BubbleType, Conversation, ModelConfig, TokenCount};
+
+/// Conversation `id` on `model` with one message at each of `times`, each
+/// using `tokens`.
+pub fn conversation(
+    id: &str,
+    model: &str,
+    times: &[DateTime<Utc>],
+    tokens: &TokenCount,
+) -> Conversation {
+    Conversation {
+        composer_id: id.to_string(),
+        title: id.to_string(),
+        created_at: None,
+        model_config: ModelConfig {
+            model_name: model.to_string(),
+            max_mode: false,
+        },
+        unified_mode: String::new(),
+        bubbles: times
+            .iter()
+            .enumerate()
+            .map(|(i, time)| Bubble {
+                bubble_id: format!("{id}-{i}"),
+                bubble_type: BubbleType::User,
+                text: "hi".to_string(),
+                created_at: Some(*time),
+                token_count: tokens.clone(),
+                ..Default::default()
+            })
+            .collect(),
+        git: None,
+        workspace: None,
+    }
+}
+
+/// `minutes` after the RFC 3339 timestamp `base`.
+pub fn minutes_after(base: &str, minutes: &[i64]) -> Vec<DateTime<Utc>> {
+    let base = DateTime::parse_from_rfc3339(base)
+        .unwrap()
+        .with_timezone(&Utc);
+    minutes
+        .iter()
+        .map(|m| base + Duration::minutes(*m))
+        .collect()
+}
+
+/// Noon on each of `days` of January 2025.
+pub fn january(days: &[u32]) -> Vec<DateTime<Utc>> {
+    days.iter()
+        .map(|day| Utc.with_ymd_and_hms(2025, 1, *day, 12, 0, 0).unwrap())
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! Chronological activity view across conversations.
+//!
+//! Turns each conversation into a session spanning its first and last
+//! message and flags sessions that ran at the same time, for the
+//! `timeline` command.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::Conversation;
+
+/// One conversation placed on the timeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    /// Composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Project, when the conversation was synced with one.
+    pub workspace: Option<String>,
+    /// First message (or creation time).
+    pub start: DateTime<Utc>,
+    /// Last message.
+    pub end: DateTime<Utc>,
+    /// Number of messages.
+    pub messages: usize,
+    /// Composer IDs of sessions active at the same time.
+    pub overlaps: Vec<String>,
+}
+
+/// Sessions in start order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timeline {
+    /// Sessions, oldest first.
+    pub sessions: Vec<Session>,
+}
+
+impl Timeline {
+    /// Build the timeline of conversations active at or after `since`.
+    ///
+    /// Conversations without any timestamp cannot be placed and are skipped.
+    #[must_use]
+    pub fn build(
+        conversations: &[Conversation],
+        workspaces: &HashMap<String, String>,
+        since: Option<DateTime<Utc>>,
+    ) -> Self {
+        let mut sessions: Vec<Session> = conversations
+            .iter()
+            .filter_map(|conv| {
+                let times = conv.bubbles.iter().filter_map(|b| b.created_at);
+                let start = times.clone().min().or(conv.created_at)?;
+                let end = times.max().unwrap_or(start).max(start);
+
+                Some(Session {
+                    composer_id: conv.composer_id.clone(),
+                    title: conv.title.clone(),
+                    workspace: workspaces.get(&conv.composer_id).cloned(),
+                    start,
+                    end,
+                    messages: conv.bubbles.len(),
+                    overlaps: Vec::new(),
+                })
+            })
+            .filter(|s| since.is_none_or(|since| s.end >= since))
+            .collect();
+
+        sessions.sort_by_key(|s| s.start);
+
+        // Sorted by start, so only later sessions starting before this one ends can overlap
+        for i in 0..sessions.len() {
+            for j in i + 1..sessions.len() {
+                if sessions[j].start > sessions[i].end {
+                    break;
+                }
+                let (a, b) = (
+                    sessions[i].composer_id.clone(),
+                    sessions[j].composer_id.clone(),
+                );
+                sessions[i].overlaps.push(b);
+                sessions[j].overlaps.push(a);
+            }
+        }
+
+        Self { sessions }
+    }
+
+    /// Whether there is nothing to show.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::test_support::{conversation, minutes_after};
+    use crate::domain::TokenCount;
+
+    const BASE: &str = "2024-05-01T10:00:00Z";
+
+    #[test]
+    fn test_build_orders_and_flags_overlaps() {
+        let tokens = TokenCount::default();
+        let conversations = [
+            conversation("late", "", &minutes_after(BASE, &[120, 130]), &tokens),
+            conversation("first", "", &minutes_after(BASE, &[0, 30]), &tokens),
+            conversation("during", "", &minutes_after(BASE, &[10, 20]), &tokens),
+            conversation("untimed", "", &[], &tokens),
+        ];
+        let timeline = Timeline::build(&conversations, &HashMap::new(), None);
+
+        let ids: Vec<_> = timeline
+            .sessions
+            .iter()
+            .map(|s| s.composer_id.as_str())
+            .collect();
+        assert_eq!(ids, ["first", "during", "late"]);
+        assert_eq!(timeline.sessions[0].overlaps, ["during"]);
+        assert_eq!(timeline.sessions[1].overlaps, ["first"]);
+        assert!(timeline.sessions[2].overlaps.is_empty());
+
+        let since = timeline.sessions[2].start;
+        let recent = Timeline::build(&conversations, &HashMap::new(), Some(since));
+        assert_eq!(recent.sessions.len(), 1);
+    }
+}
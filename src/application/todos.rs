@@ -0,0 +1,236 @@
+//! Action-item extraction from assistant messages.
+//!
+//! Scans assistant replies for TODO markers, unchecked checklists,
+//! "you should" style suggestions and numbered follow-ups under a
+//! "next steps" heading, keeping a reference to the source bubble.
+
+use serde::Serialize;
+
+use crate::domain::{BubbleType, Conversation};
+
+/// Phrases that introduce a suggestion addressed to the user.
+const SUGGESTION_PHRASES: &[&str] = &[
+    "you should",
+    "you need to",
+    "you'll need to",
+    "you will need to",
+    "make sure to",
+    "don't forget to",
+];
+
+/// Headings after which numbered or bulleted items are follow-ups.
+const FOLLOW_UP_HEADINGS: &[&str] = &[
+    "next step",
+    "follow-up",
+    "follow up",
+    "to do",
+    "todo",
+    "remaining",
+    "próximos passos",
+];
+
+/// How an action item was recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// `TODO:` / `FIXME:` marker.
+    Todo,
+    /// Unchecked `- [ ]` checklist item.
+    Checklist,
+    /// List item under a "next steps" style heading.
+    NextStep,
+    /// Sentence such as "you should ...".
+    Suggestion,
+}
+
+impl ActionKind {
+    /// Short label for display.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Todo => "todo",
+            Self::Checklist => "checklist",
+            Self::NextStep => "next step",
+            Self::Suggestion => "suggestion",
+        }
+    }
+}
+
+/// A task found in a conversation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionItem {
+    /// Conversation the item came from.
+    pub composer_id: String,
+    /// Bubble the item came from.
+    pub bubble_id: String,
+    /// 1-based position of the bubble in the conversation.
+    pub message_index: usize,
+    /// How the item was recognized.
+    pub kind: ActionKind,
+    /// Task text.
+    pub text: String,
+}
+
+/// Extract action items from the assistant messages of a conversation.
+///
+/// Identical tasks are reported once, at their first occurrence.
+#[must_use]
+pub fn extract_action_items(conv: &Conversation) -> Vec<ActionItem> {
+    let mut items: Vec<ActionItem> = Vec::new();
+
+    for (index, bubble) in conv.bubbles.iter().enumerate() {
+        if bubble.bubble_type != BubbleType::Assistant {
+            continue;
+        }
+
+        for (kind, text) in scan_text(&bubble.text) {
+            let duplicate = items.iter().any(|i| i.text.eq_ignore_ascii_case(&text));
+            if !duplicate {
+                items.push(ActionItem {
+                    composer_id: conv.composer_id.clone(),
+                    bubble_id: bubble.bubble_id.clone(),
+                    message_index: index + 1,
+                    kind,
+                    text,
+                });
+            }
+        }
+    }
+
+    items
+}
+
+/// Find action items in one message, skipping code blocks.
+fn scan_text(text: &str) -> Vec<(ActionKind, String)> {
+    let mut found = Vec::new();
+    let mut in_code = false;
+    let mut in_follow_ups = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(task) = trimmed
+            .strip_prefix("- [ ]")
+            .or_else(|| trimmed.strip_prefix("* [ ]"))
+        {
+            push_task(&mut found, ActionKind::Checklist, task);
+            continue;
+        }
+
+        let item = list_item(trimmed);
+        if is_follow_up_heading(trimmed, item.is_some()) {
+            in_follow_ups = true;
+            continue;
+        }
+
+        if let Some(task) = todo_marker(trimmed) {
+            push_task(&mut found, ActionKind::Todo, task);
+            continue;
+        }
+
+        match item {
+            Some(task) if in_follow_ups => {
+                push_task(&mut found, ActionKind::NextStep, task);
+                continue;
+            }
+            // Any other paragraph or heading ends the follow-up list
+            None => in_follow_ups = false,
+            Some(_) => {}
+        }
+
+        let lower = trimmed.to_lowercase();
+        if SUGGESTION_PHRASES.iter().any(|p| lower.contains(p)) {
+            push_task(&mut found, ActionKind::Suggestion, item.unwrap_or(trimmed));
+        }
+    }
+
+    found
+}
+
+fn push_task(found: &mut Vec<(ActionKind, String)>, kind: ActionKind, task: &str) {
+    let task = task.trim().trim_matches('*').trim();
+    if task.len() > 2 {
+        found.push((kind, task.to_string()));
+    }
+}
+
+/// Text after a `TODO:` or `FIXME:` marker.
+fn todo_marker(line: &str) -> Option<&str> {
+    ["TODO:", "TODO ", "FIXME:"].iter().find_map(|marker| {
+        line.find(marker)
+            .map(|pos| &line[pos + marker.len()..])
+            .filter(|rest| !rest.trim().is_empty())
+    })
+}
+
+/// Text of a `-`, `*` or `1.` / `1)` list item.
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(rest);
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))
+}
+
+fn is_follow_up_heading(line: &str, is_item: bool) -> bool {
+    if is_item || line.len() > 60 {
+        return false;
+    }
+    let lower = line.to_lowercase();
+    let heading_like = line.starts_with('#') || line.ends_with(':') || line.starts_with("**");
+    heading_like && FOLLOW_UP_HEADINGS.iter().any(|h| lower.contains(h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_text_finds_all_kinds() {
+        let text = "Done with the parser.\n\
+                    TODO: add tests for empty input\n\
+                    - [ ] update the README\n\
+                    - [x] already finished\n\
+                    ```rust\n\
+                    // TODO: not a real task\n\
+                    ```\n\
+                    You should run `cargo fmt` before committing.\n\
+                    \n\
+                    ## Next steps\n\
+                    1. Wire the command into main\n\
+                    2) Release a new version\n\
+                    \n\
+                    That's all.\n\
+                    1. unrelated numbered item";
+
+        let found = scan_text(text);
+        let kinds: Vec<_> = found.iter().map(|(k, _)| *k).collect();
+        assert_eq!(
+            kinds,
+            [
+                ActionKind::Todo,
+                ActionKind::Checklist,
+                ActionKind::Suggestion,
+                ActionKind::NextStep,
+                ActionKind::NextStep,
+            ]
+        );
+        assert_eq!(found[0].1, "add tests for empty input");
+        assert_eq!(found[1].1, "update the README");
+        assert_eq!(found[4].1, "Release a new version");
+    }
+}
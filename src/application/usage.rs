@@ -0,0 +1,267 @@
+//! Usage totals per day, model, workspace and mode.
+//!
+//! Builds the tables written by `stats --export`, one row per group key,
+//! for spreadsheets and monthly reports, and the `stats modes` breakdown.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+
+use serde::Serialize;
+
+use crate::domain::{Conversation, TokenCount};
+
+use super::work_sessions::NO_WORKSPACE;
+
+/// Dimension a usage row is grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroup {
+    /// Calendar day (UTC) of each message.
+    Day,
+    /// Model of the conversation.
+    Model,
+    /// Project of the conversation.
+    Workspace,
+}
+
+impl UsageGroup {
+    /// Name used in exports.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Model => "model",
+            Self::Workspace => "workspace",
+        }
+    }
+}
+
+/// Totals for one day, model or workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageRow {
+    /// What the row is grouped by.
+    pub group: UsageGroup,
+    /// The day (`YYYY-MM-DD`), model or workspace name.
+    pub key: String,
+    /// Conversations with at least one message counted in the row.
+    pub conversations: usize,
+    /// Messages counted in the row.
+    pub messages: usize,
+    /// Tokens of those messages.
+    pub tokens: TokenCount,
+}
+
+/// Conversation IDs, messages and tokens per group and key.
+type Totals<'a> = BTreeMap<(UsageGroup, String), (HashSet<&'a str>, usize, TokenCount)>;
+
+fn add<'a>(
+    totals: &mut Totals<'a>,
+    key: (UsageGroup, String),
+    composer_id: &'a str,
+    messages: usize,
+    tokens: &TokenCount,
+) {
+    let entry = totals.entry(key).or_default();
+    entry.0.insert(composer_id);
+    entry.1 += messages;
+    entry.2 += tokens;
+}
+
+/// Usage per day, then per model, then per workspace, each sorted by key.
+///
+/// Days come from message timestamps, falling back to the conversation's;
+/// messages without either are left out of the daily table only.
+#[must_use]
+pub fn usage_breakdown<S: BuildHasher>(
+    conversations: &[Conversation],
+    workspaces: &HashMap<String, String, S>,
+) -> Vec<UsageRow> {
+    let mut totals = Totals::new();
+
+    for conv in conversations {
+        if conv.bubbles.is_empty() {
+            continue;
+        }
+        let id = conv.composer_id.as_str();
+
+        for bubble in &conv.bubbles {
+            if let Some(at) = bubble.created_at.or(conv.created_at) {
+                let day = at.date_naive().to_string();
+                add(
+                    &mut totals,
+                    (UsageGroup::Day, day),
+                    id,
+                    1,
+                    &bubble.token_count,
+                );
+            }
+        }
+
+        let tokens = conv.token_count();
+        let model = if conv.model_config.model_name.is_empty() {
+            "unknown"
+        } else {
+            conv.model_config.model_name.as_str()
+        };
+        let workspace = workspaces
+            .get(&conv.composer_id)
+            .map_or(NO_WORKSPACE, String::as_str);
+        for key in [
+            (UsageGroup::Model, model.to_string()),
+            (UsageGroup::Workspace, workspace.to_string()),
+        ] {
+            add(&mut totals, key, id, conv.message_count(), &tokens);
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|((group, key), (ids, messages, tokens))| UsageRow {
+            group,
+            key,
+            conversations: ids.len(),
+            messages,
+            tokens,
+        })
+        .collect()
+}
+
+/// Usage of one conversation mode (`stats modes`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeUsage {
+    /// `agent`, `edit`, `ask`, another mode Cursor reported, or `unknown`.
+    pub mode: String,
+    /// Conversations in this mode.
+    pub conversations: usize,
+    /// Messages of those conversations.
+    pub messages: usize,
+    /// Messages Cursor marked as agentic (autonomous tool use).
+    pub agentic_messages: usize,
+    /// Share of all messages, in percent rounded to one decimal.
+    pub message_share: f64,
+    /// Tokens of those messages.
+    pub tokens: TokenCount,
+}
+
+/// Name of a conversation's mode; Cursor calls ask mode `chat`.
+fn mode_name(unified_mode: &str) -> String {
+    match unified_mode.trim().to_lowercase().as_str() {
+        "" => "unknown".to_string(),
+        "chat" | "ask" => "ask".to_string(),
+        mode => mode.to_string(),
+    }
+}
+
+/// Conversations, messages and tokens per mode, most messages first.
+#[must_use]
+pub fn mode_breakdown(conversations: &[Conversation]) -> Vec<ModeUsage> {
+    let mut modes: BTreeMap<String, ModeUsage> = BTreeMap::new();
+    for conv in conversations.iter().filter(|c| !c.bubbles.is_empty()) {
+        let mode = mode_name(&conv.unified_mode);
+        let entry = modes.entry(mode.clone()).or_insert_with(|| ModeUsage {
+            mode,
+            conversations: 0,
+            messages: 0,
+            agentic_messages: 0,
+            message_share: 0.0,
+            tokens: TokenCount::default(),
+        });
+        entry.conversations += 1;
+        entry.messages += conv.message_count();
+        entry.agentic_messages += conv.bubbles.iter().filter(|b| b.is_agentic).count();
+        entry.tokens += &conv.token_count();
+    }
+
+    let total: usize = modes.values().map(|m| m.messages).sum();
+    let mut rows: Vec<ModeUsage> = modes.into_values().collect();
+    for row in &mut rows {
+        if let Some(per_mille) = (row.messages * 1000 + total / 2).checked_div(total) {
+            row.message_share = f64::from(u32::try_from(per_mille).unwrap_or(1000)) / 10.0;
+        }
+    }
+    rows.sort_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then_with(|| a.mode.cmp(&b.mode))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::test_support::{conversation, january};
+
+    const TOKENS: TokenCount = TokenCount {
+        input_tokens: 10,
+        output_tokens: 5,
+        reasoning_tokens: 2,
+    };
+
+    #[test]
+    fn test_mode_breakdown() {
+        let mut agent = conversation("a", "gpt", &january(&[1, 2, 3]), &TOKENS);
+        agent.unified_mode = "agent".to_string();
+        agent.bubbles[1].is_agentic = true;
+        let mut ask = conversation("b", "gpt", &january(&[1]), &TOKENS);
+        ask.unified_mode = "chat".to_string();
+
+        let rows: Vec<_> = mode_breakdown(&[agent, ask, conversation("c", "gpt", &[], &TOKENS)])
+            .into_iter()
+            .map(|m| {
+                (
+                    m.mode,
+                    m.conversations,
+                    m.messages,
+                    m.agentic_messages,
+                    m.message_share,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("agent".to_string(), 1, 3, 1, 75.0),
+                ("ask".to_string(), 1, 1, 0, 25.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_usage_breakdown_groups() {
+        let conversations = [
+            conversation("a", "gpt", &january(&[1, 2]), &TOKENS),
+            conversation("b", "", &january(&[2]), &TOKENS),
+            conversation("empty", "gpt", &[], &TOKENS),
+        ];
+        let workspaces = HashMap::from([("a".to_string(), "proj".to_string())]);
+
+        let rows: Vec<_> = usage_breakdown(&conversations, &workspaces)
+            .into_iter()
+            .map(|r| {
+                (
+                    r.group.as_str(),
+                    r.key,
+                    r.conversations,
+                    r.messages,
+                    r.tokens.reasoning_tokens,
+                )
+            })
+            .collect();
+
+        let row = |group, key: &str, conversations, messages, reasoning| {
+            (group, key.to_string(), conversations, messages, reasoning)
+        };
+        assert_eq!(
+            rows,
+            [
+                row("day", "2025-01-01", 1, 1, 2),
+                row("day", "2025-01-02", 2, 2, 4),
+                row("model", "gpt", 1, 2, 4),
+                row("model", "unknown", 1, 1, 2),
+                row("workspace", "(no workspace)", 1, 1, 2),
+                row("workspace", "proj", 1, 2, 4),
+            ]
+        );
+    }
+}
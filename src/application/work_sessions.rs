@@ -0,0 +1,222 @@
+//! AI-assisted work time per workspace.
+//!
+//! Builds the `stats sessions` report: message timestamps of all
+//! conversations in a workspace are merged, and a pause longer than the gap
+//! ends a work session. Session time is then summed per ISO week.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::domain::Conversation;
+
+/// Workspace name used for conversations without one.
+pub const NO_WORKSPACE: &str = "(no workspace)";
+
+/// A stretch of activity in one workspace without a long pause.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkSession {
+    /// Project name.
+    pub workspace: String,
+    /// First message.
+    pub start: DateTime<Utc>,
+    /// Last message.
+    pub end: DateTime<Utc>,
+    /// Messages in the session.
+    pub messages: usize,
+    /// Conversations with a message in the session.
+    pub conversations: usize,
+}
+
+impl WorkSession {
+    /// Length in whole minutes.
+    #[must_use]
+    pub fn minutes(&self) -> u32 {
+        u32::try_from((self.end - self.start).num_minutes()).unwrap_or(0)
+    }
+}
+
+/// Work time of one workspace in one week.
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklyHours {
+    /// Monday the (ISO) week starts on, in UTC.
+    pub week_start: NaiveDate,
+    /// Project name.
+    pub workspace: String,
+    /// Sessions started that week.
+    pub sessions: usize,
+    /// Total session time in minutes.
+    pub minutes: u32,
+    /// Total session time in hours, rounded to two decimals.
+    pub hours: f64,
+}
+
+/// Sessions and weekly totals.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionReport {
+    /// Pause that ends a session, in minutes.
+    pub gap_minutes: u32,
+    /// Sessions, oldest first.
+    pub sessions: Vec<WorkSession>,
+    /// Totals by week (oldest first), then workspace.
+    pub weeks: Vec<WeeklyHours>,
+}
+
+impl SessionReport {
+    /// Split the activity of each workspace into sessions separated by more
+    /// than `gap_minutes`, keeping sessions that end at or after `since`.
+    ///
+    /// Only messages with a timestamp count; `workspaces` maps composer IDs
+    /// to project names.
+    #[must_use]
+    pub fn build(
+        conversations: &[Conversation],
+        workspaces: &HashMap<String, String>,
+        gap_minutes: u32,
+        since: Option<DateTime<Utc>>,
+    ) -> Self {
+        let mut activity: HashMap<&str, Vec<(DateTime<Utc>, &str)>> = HashMap::new();
+        for conv in conversations {
+            let workspace = workspaces
+                .get(&conv.composer_id)
+                .map_or(NO_WORKSPACE, String::as_str);
+            activity.entry(workspace).or_default().extend(
+                conv.bubbles
+                    .iter()
+                    .filter_map(|b| b.created_at)
+                    .map(|at| (at, conv.composer_id.as_str())),
+            );
+        }
+
+        let gap = Duration::minutes(i64::from(gap_minutes));
+        let mut sessions = Vec::new();
+        for (workspace, mut messages) in activity {
+            messages.sort_unstable();
+            let mut current: Vec<(DateTime<Utc>, &str)> = Vec::new();
+            for message in messages {
+                if current
+                    .last()
+                    .is_some_and(|(last, _)| message.0 - *last > gap)
+                {
+                    sessions.push(session(workspace, &current));
+                    current.clear();
+                }
+                current.push(message);
+            }
+            if !current.is_empty() {
+                sessions.push(session(workspace, &current));
+            }
+        }
+
+        sessions.retain(|s| since.is_none_or(|since| s.end >= since));
+        sessions.sort_by(|a, b| (a.start, &a.workspace).cmp(&(b.start, &b.workspace)));
+
+        let mut totals: BTreeMap<(NaiveDate, &str), (usize, u32)> = BTreeMap::new();
+        for s in &sessions {
+            let day = s.start.date_naive();
+            let monday = day - Duration::days(i64::from(day.weekday().num_days_from_monday()));
+            let entry = totals.entry((monday, s.workspace.as_str())).or_default();
+            entry.0 += 1;
+            entry.1 += s.minutes();
+        }
+        let weeks = totals
+            .into_iter()
+            .map(|((week_start, workspace), (count, minutes))| WeeklyHours {
+                week_start,
+                workspace: workspace.to_string(),
+                sessions: count,
+                minutes,
+                hours: (f64::from(minutes) / 60.0 * 100.0).round() / 100.0,
+            })
+            .collect();
+
+        Self {
+            gap_minutes,
+            sessions,
+            weeks,
+        }
+    }
+
+    /// Whether there is nothing to show.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+fn session(workspace: &str, messages: &[(DateTime<Utc>, &str)]) -> WorkSession {
+    let mut conversations: Vec<&str> = messages.iter().map(|(_, id)| *id).collect();
+    conversations.sort_unstable();
+    conversations.dedup();
+
+    WorkSession {
+        workspace: workspace.to_string(),
+        start: messages[0].0,
+        end: messages[messages.len() - 1].0,
+        messages: messages.len(),
+        conversations: conversations.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::test_support::{conversation, minutes_after};
+    use crate::domain::TokenCount;
+
+    /// A Friday: three days later is in the next ISO week.
+    const FRIDAY: &str = "2024-05-03T10:00:00Z";
+
+    #[test]
+    fn test_sessions_split_on_gaps_and_sum_per_week() {
+        let tokens = TokenCount::default();
+        let conversations = [
+            conversation("a", "", &minutes_after(FRIDAY, &[0, 20, 45]), &tokens),
+            // Same workspace, continues the session started by "a"
+            conversation("b", "", &minutes_after(FRIDAY, &[60, 90]), &tokens),
+            // Three days later: Monday of the next week
+            conversation(
+                "c",
+                "",
+                &minutes_after(FRIDAY, &[3 * 24 * 60, 3 * 24 * 60 + 30]),
+                &tokens,
+            ),
+            conversation("other", "", &minutes_after(FRIDAY, &[0, 10]), &tokens),
+        ];
+        let workspaces: HashMap<String, String> = ["a", "b", "c"]
+            .iter()
+            .map(|id| ((*id).to_string(), "proj".to_string()))
+            .collect();
+
+        let report = SessionReport::build(&conversations, &workspaces, 30, None);
+
+        let sessions: Vec<_> = report
+            .sessions
+            .iter()
+            .map(|s| (s.workspace.as_str(), s.minutes(), s.conversations))
+            .collect();
+        assert_eq!(
+            sessions,
+            [(NO_WORKSPACE, 10, 1), ("proj", 90, 2), ("proj", 30, 1)]
+        );
+
+        let weeks: Vec<_> = report
+            .weeks
+            .iter()
+            .map(|w| (w.week_start.to_string(), w.workspace.as_str(), w.hours))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                ("2024-04-29".to_string(), NO_WORKSPACE, 0.17),
+                ("2024-04-29".to_string(), "proj", 1.5),
+                ("2024-05-06".to_string(), "proj", 0.5),
+            ]
+        );
+
+        // A shorter gap splits both "proj" sessions
+        let strict = SessionReport::build(&conversations, &workspaces, 20, None);
+        assert_eq!(strict.sessions.len(), 6);
+    }
+}
@@ -0,0 +1,123 @@
+//! Composers kept in workspace databases.
+//!
+//! Before conversations moved to the global `cursorDiskKV` table, Cursor
+//! stored each workspace's composers, messages inline, in the
+//! `composer.composerData` entry of its `workspaceStorage` database. Newer
+//! versions keep only the composer IDs there.
+
+use std::path::Path;
+
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::domain::{AppError, Conversation, ModelConfig, Result};
+use crate::infrastructure::StateDbReader;
+
+use super::parser::parse_bubble;
+
+/// `ItemTable` key listing a workspace's composers.
+pub const WORKSPACE_COMPOSERS_KEY: &str = "composer.composerData";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawComposerData {
+    #[serde(default)]
+    all_composers: Vec<RawInlineComposer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawInlineComposer {
+    composer_id: String,
+    #[serde(default)]
+    created_at: Option<i64>,
+    #[serde(default)]
+    unified_mode: Option<String>,
+    // Bubbles are parsed one by one so a malformed one is skipped alone
+    #[serde(default)]
+    conversation: Vec<serde_json::Value>,
+}
+
+/// Converts the composers of a `composer.composerData` entry that carry
+/// their messages inline into conversations.
+///
+/// Composers listed by ID only (their messages live in the global
+/// database) are skipped.
+///
+/// # Errors
+/// Returns error if JSON parsing fails.
+pub fn parse_workspace_composers(data: &[u8]) -> Result<Vec<Conversation>> {
+    let raw: RawComposerData = serde_json::from_slice(data).map_err(AppError::json_parse)?;
+
+    Ok(raw
+        .all_composers
+        .into_iter()
+        .filter(|composer| !composer.conversation.is_empty())
+        .map(conversation_from_composer)
+        .collect())
+}
+
+/// Composers with inline messages stored in the database at `path`.
+///
+/// # Errors
+/// Returns error if the database cannot be read or the entry is malformed.
+pub fn read_workspace_composers(path: &Path) -> Result<Vec<Conversation>> {
+    StateDbReader::open(path)?
+        .item_value(WORKSPACE_COMPOSERS_KEY)?
+        .map_or_else(|| Ok(Vec::new()), |data| parse_workspace_composers(&data))
+}
+
+fn conversation_from_composer(composer: RawInlineComposer) -> Conversation {
+    let bubbles = composer
+        .conversation
+        .into_iter()
+        .filter_map(|value| {
+            serde_json::to_vec(&value)
+                .ok()
+                .and_then(|data| parse_bubble(&data).ok())
+        })
+        .collect();
+
+    Conversation {
+        composer_id: composer.composer_id,
+        title: String::new(),
+        created_at: composer
+            .created_at
+            .and_then(DateTime::from_timestamp_millis),
+        model_config: ModelConfig::default(),
+        unified_mode: composer.unified_mode.unwrap_or_default(),
+        bubbles,
+        git: None,
+        workspace: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::BubbleType;
+
+    #[test]
+    fn test_parse_workspace_composers() {
+        let data = br#"{"allComposers": [
+            {"composerId": "c-old", "createdAt": 1700000000000, "unifiedMode": "edit",
+             "conversation": [
+                {"type": 1, "bubbleId": "b1", "text": "Rename the module"},
+                {"type": 2, "bubbleId": "b2", "text": "Done."},
+                {"type": 2}
+             ]},
+            {"composerId": "c-new", "createdAt": 1710000000000}
+        ], "selectedComposerId": "c-old"}"#;
+
+        let conversations = parse_workspace_composers(data).unwrap();
+        assert_eq!(conversations.len(), 1);
+
+        let conv = &conversations[0];
+        assert_eq!(conv.composer_id, "c-old");
+        assert_eq!(conv.unified_mode, "edit");
+        assert_eq!(conv.created_at.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(conv.bubbles.len(), 2);
+        assert_eq!(conv.bubbles[0].bubble_type, BubbleType::User);
+        assert_eq!(conv.bubbles[1].text, "Done.");
+    }
+}
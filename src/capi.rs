@@ -0,0 +1,202 @@
+//! C ABI for embedding the extractor (`capi` feature, header in
+//! `include/cursor_chat.h`).
+//!
+//! Every function returning `char *` hands out a NUL-terminated UTF-8 JSON
+//! string owned by the caller, to be released with `cch_free_string`. On
+//! failure they return `NULL` and `cch_last_error` describes why.
+//!
+//! No panic crosses into C: each entry point catches it and fails with its
+//! usual error value, the panic message becoming the last error.
+
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::application::{extract_all_conversations, find_conversation, ExtractOptions};
+use crate::domain::{AppError, Conversation, Result};
+use crate::infrastructure::set_cursor_config_dir;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Entry of `cch_list_conversations`.
+#[derive(Serialize)]
+struct ConversationSummary<'a> {
+    composer_id: &'a str,
+    title: &'a str,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    messages: usize,
+    model: &'a str,
+}
+
+/// Use `dir` as Cursor's configuration directory instead of the detected
+/// one. Only the first call takes effect: later ones fail. Returns 0 on
+/// success, -1 on error.
+///
+/// # Safety
+/// `dir` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cch_set_cursor_dir(dir: *const c_char) -> i32 {
+    LAST_ERROR.with(|e| e.borrow_mut().take());
+    guard(-1, || {
+        // SAFETY: the caller guarantees `dir` is NULL or a valid C string
+        match unsafe { read_str(dir) }.and_then(|dir| set_cursor_config_dir(PathBuf::from(dir))) {
+            Ok(()) => 0,
+            Err(e) => {
+                set_last_error(&e);
+                -1
+            }
+        }
+    })
+}
+
+/// JSON array of every conversation (ID, title, creation time, message
+/// count, model), newest first.
+#[no_mangle]
+pub extern "C" fn cch_list_conversations() -> *mut c_char {
+    respond(|| {
+        let conversations = load()?;
+        let summaries: Vec<ConversationSummary> = conversations
+            .iter()
+            .map(|c| ConversationSummary {
+                composer_id: &c.composer_id,
+                title: &c.title,
+                created_at: c.created_at,
+                messages: c.bubbles.len(),
+                model: &c.model_config.model_name,
+            })
+            .collect();
+        serde_json::to_string(&summaries).map_err(AppError::json_parse)
+    })
+}
+
+/// JSON of one conversation with all its messages, by full or partial ID.
+///
+/// # Safety
+/// `id` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cch_get_conversation(id: *const c_char) -> *mut c_char {
+    respond(|| {
+        // SAFETY: the caller guarantees `id` is NULL or a valid C string
+        let id = unsafe { read_str(id) }?;
+        let conversations = load()?;
+        let conv = find_conversation(&conversations, &id)?;
+        serde_json::to_string(conv).map_err(AppError::json_parse)
+    })
+}
+
+/// Release a string returned by this library. `NULL` is ignored.
+///
+/// # Safety
+/// `s` must be `NULL` or a pointer returned by this library that was not
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cch_free_string(s: *mut c_char) {
+    guard((), || {
+        if !s.is_null() {
+            // SAFETY: `s` came from `CString::into_raw` in `respond`
+            drop(unsafe { CString::from_raw(s) });
+        }
+    });
+}
+
+/// Message of the last error on this thread, or `NULL`. The pointer stays
+/// valid until the next call into the library on the same thread; do not
+/// free it.
+#[no_mangle]
+pub extern "C" fn cch_last_error() -> *const c_char {
+    guard(ptr::null(), || {
+        LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+    })
+}
+
+fn load() -> Result<Vec<Conversation>> {
+    extract_all_conversations(&ExtractOptions::default()).map(|(conversations, _)| conversations)
+}
+
+/// Run `f`, returning its JSON as an owned C string or `NULL` on error.
+fn respond(f: impl FnOnce() -> Result<String>) -> *mut c_char {
+    LAST_ERROR.with(|e| e.borrow_mut().take());
+    guard(ptr::null_mut(), || {
+        let json = f().and_then(|json| {
+            CString::new(json).map_err(|_| AppError::InvalidData {
+                message: "Output contains a NUL byte".into(),
+            })
+        });
+
+        match json {
+            Ok(json) => json.into_raw(),
+            Err(e) => {
+                set_last_error(&e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Run `f`, returning `failed` if it panics, since unwinding into the C
+/// caller is undefined behavior. The panic message becomes the last error.
+fn guard<T>(failed: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".into());
+        set_last_message(&format!("Internal panic: {message}"));
+        failed
+    })
+}
+
+fn set_last_error(error: &AppError) {
+    set_last_message(&error.to_string());
+}
+
+fn set_last_message(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// # Safety
+/// `s` must be `NULL` or a valid NUL-terminated string.
+unsafe fn read_str(s: *const c_char) -> Result<String> {
+    if s.is_null() {
+        return Err(AppError::InvalidData {
+            message: "Unexpected NULL argument".into(),
+        });
+    }
+
+    // SAFETY: checked for NULL above; validity is the caller's contract
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| AppError::InvalidData {
+            message: "Argument is not valid UTF-8".into(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_api_catches_panics() {
+        assert!(respond(|| panic!("boom")).is_null());
+        let error = unsafe { CStr::from_ptr(cch_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "Internal panic: boom");
+
+        assert_eq!(
+            guard(-1, || -> i32 { panic!("{}", String::from("again")) }),
+            -1
+        );
+        let error = unsafe { CStr::from_ptr(cch_last_error()) };
+        assert!(error.to_str().unwrap().ends_with("again"));
+    }
+}
@@ -2,16 +2,19 @@
 //!
 //! Provides command-line arguments and subcommands for the tool.
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
-use crate::application::OutputFormat;
+use crate::application::{JsonFilter, ListGrouping, OutputFormat};
+use crate::domain::{CursorChannel, OperationKind};
+use crate::infrastructure::{Recipient, ResetSteps, ServiceMode, PROFILE_ENV};
 
-const LONG_ABOUT: &str = r#"
+const LONG_ABOUT: &str = r"
 🤖 GUIA RÁPIDO PARA IA
 ══════════════════════════════════════════════════════════════
 
 📋 RECUPERAR CONTEXTO DE CHAT ANTERIOR:
   cursor-chat quick              # Menu com números
+  cursor-chat quick --machine    # JSON compacto para agentes
   cursor-chat open 1             # Abrir última conversa
   cursor-chat show <ID> --last 10  # Ver últimas 10 msgs
 
@@ -34,7 +37,7 @@ const LONG_ABOUT: &str = r#"
   cursor-chat reset              # Reset trial + restaura chats
 
 ══════════════════════════════════════════════════════════════
-"#;
+";
 
 /// Cursor Chat Handler - Extract and display chat history from Cursor IDE.
 #[derive(Parser, Debug)]
@@ -49,6 +52,40 @@ pub struct Cli {
     #[arg(short, long, default_value = "markdown")]
     pub format: String,
 
+    /// jq-style expression applied to the JSON output of list, show and
+    /// export (implies `-f json`), e.g. `.[] | {composer_id, title}`.
+    #[arg(long, global = true, value_name = "EXPR")]
+    pub filter: Option<JsonFilter>,
+
+    /// Configuration profile to use (see [profile.<name>] in config.toml).
+    #[arg(long, global = true, env = PROFILE_ENV)]
+    pub profile: Option<String>,
+
+    /// Cursor channel to read: stable or nightly. Without --profile,
+    /// nightly uses the built-in "nightly" profile and its own storage.
+    #[arg(long, global = true, value_name = "CHANNEL")]
+    pub channel: Option<CursorChannel>,
+
+    /// Memory budget for extracted conversations in MB; beyond it, sync and
+    /// export work in batches (overrides `extract.max_memory_mb`).
+    #[arg(long, global = true, value_name = "MB")]
+    pub max_memory: Option<u64>,
+
+    /// File of `from → to` replacements applied to exported conversations
+    /// (overrides `export.path_map`).
+    #[arg(long, global = true, value_name = "FILE")]
+    pub path_map: Option<std::path::PathBuf>,
+
+    /// Print elapsed time and row counts per stage (DB read, JSON parse,
+    /// sort, format, write) when the command finishes.
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// Never write to Cursor's database or run system-modifying commands
+    /// (restore, delete, reset, sync start/stop).
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -68,6 +105,17 @@ pub enum Commands {
         /// Filter by workspace/project name.
         #[arg(short, long)]
         workspace: Option<String>,
+
+        /// Only conversations with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Section the table under date headers (Today, Yesterday, ...): day.
+        #[arg(long, value_name = "PERIOD")]
+        group_by: Option<ListGrouping>,
+
+        #[command(flatten)]
+        range: DateRangeArgs,
     },
 
     /// Show a specific conversation in detail.
@@ -97,6 +145,48 @@ pub enum Commands {
         /// Minimum number of messages to include a conversation.
         #[arg(short, long, default_value = "1")]
         min_messages: usize,
+
+        /// Only conversations of this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Only conversations with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+
+        #[command(flatten)]
+        range: DateRangeArgs,
+
+        /// Split into numbered part files of at most this many estimated tokens.
+        #[arg(long, requires = "output", conflicts_with = "split_bytes")]
+        split_tokens: Option<usize>,
+
+        /// Split into numbered part files of at most this size (e.g. 500K, 1M).
+        #[arg(long, requires = "output", value_parser = parse_size)]
+        split_bytes: Option<usize>,
+
+        /// Write a dataset instead of the `-f` output: `jsonl` writes one
+        /// fine-tuning example per conversation in chat format.
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            value_parser = ["jsonl"],
+            conflicts_with_all = ["split_tokens", "split_bytes"]
+        )]
+        dataset: Option<String>,
+
+        /// Leave thinking blocks out of the dataset.
+        #[arg(long, requires = "dataset")]
+        no_thinking: bool,
+
+        /// Leave Cursor's context blocks and agent tool-use steps out of the dataset.
+        #[arg(long, requires = "dataset")]
+        drop_noise: bool,
+
+        /// Encrypt the export to this recipient: an age or SSH public key
+        /// (with `age`), or a GPG key ID or e-mail (with `gpg`).
+        #[arg(long, value_name = "RECIPIENT")]
+        encrypt: Option<Recipient>,
     },
 
     /// Export multiple conversations to separate files with auto-generated names.
@@ -112,25 +202,235 @@ pub enum Commands {
         /// Minimum number of messages to include a conversation.
         #[arg(short, long, default_value = "1")]
         min_messages: usize,
+
+        /// Only conversations of this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Only conversations with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+
+        #[command(flatten)]
+        range: DateRangeArgs,
+
+        /// Encrypt each file to this recipient (see `export --encrypt`).
+        #[arg(long, value_name = "RECIPIENT")]
+        encrypt: Option<Recipient>,
+    },
+
+    /// Export messages as overlapping JSONL chunks for a vector store.
+    ExportRag {
+        /// Output file (stdout if not specified).
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Maximum estimated tokens per chunk.
+        #[arg(long, default_value = "512")]
+        chunk_tokens: usize,
+
+        /// Tokens shared between consecutive chunks of a message.
+        #[arg(long, default_value = "64")]
+        overlap: usize,
+
+        /// Minimum number of messages to include a conversation.
+        #[arg(short, long, default_value = "1")]
+        min_messages: usize,
+    },
+
+    /// Replay a conversation one exchange at a time, advancing on keypress.
+    Replay {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Advance on the original timing instead of on keypress.
+        #[arg(long)]
+        timed: bool,
+
+        /// How many times faster than the original to replay (with --timed).
+        #[arg(long, default_value = "10", requires = "timed")]
+        speed: f64,
+    },
+
+    /// Export the terminal commands an agent ran as a commented shell script.
+    ExportScript {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Output file, made executable (stdout if not specified).
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Collect TODOs, checklists and next steps from assistant replies.
+    Todos {
+        /// Conversation ID (full or partial).
+        #[arg(required_unless_present = "all")]
+        conversation_id: Option<String>,
+
+        /// Scan every conversation.
+        #[arg(long, conflicts_with = "conversation_id")]
+        all: bool,
+    },
+
+    /// List the terminal commands the agent ran in a conversation.
+    #[command(name = "commands")]
+    Terminal {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+    },
+
+    /// Summarize a conversation with the LLM endpoint from config.toml ([llm]).
+    Summarize {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Regenerate even if a cached summary is up to date.
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Show conversations chronologically, with start/end times and overlaps.
+    Timeline {
+        /// Only conversations from this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Only activity within this period (e.g. 30m, 12h, 7d, 2w).
+        #[arg(short, long, value_parser = parse_age)]
+        since: Option<chrono::Duration>,
+    },
+
+    /// Interleave a conversation with the commits made in its workspace meanwhile.
+    Correlate {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Also include commits this long before the first and after the
+        /// last message (e.g. 30m, 2h).
+        #[arg(short, long, value_parser = parse_age, default_value = "30m")]
+        margin: chrono::Duration,
+
+        /// Repository to read commits from, instead of the one recorded by sync.
+        #[arg(long)]
+        repo: Option<std::path::PathBuf>,
+    },
+
+    /// List conversations created or updated by recent syncs.
+    Changes {
+        /// Only changes after this time: a period (30m, 12h, 7d, 2w), a date
+        /// (2024-05-01) or an RFC 3339 timestamp.
+        #[arg(short, long, value_parser = parse_since)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Maximum number of changes to show (newest first).
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Show the log of syncs, restores, deletes, archives, prunes, backups and resets.
+    History {
+        /// Number of entries to show (newest first).
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Only this kind of operation (sync, restore, delete, archive, prune, import, merge, backup, reset).
+        #[arg(short, long)]
+        kind: Option<OperationKind>,
+    },
+
+    /// Run a read-only SQL query against local storage.
+    ///
+    /// Besides the internal tables, queries can use the stable views
+    /// `v_conversations`, `v_messages`, `v_changes` and `v_operations`.
+    /// Prints a table, JSON objects with `-f json`, or CSV with `--csv`.
+    Query {
+        /// A single SQL statement, e.g. `SELECT title FROM v_conversations`.
+        sql: String,
+
+        /// Print the result as CSV.
+        #[arg(long)]
+        csv: bool,
+    },
+
+    /// Manage conversation titles.
+    #[command(subcommand)]
+    Titles(TitlesCommands),
+
+    /// Organize synced conversations with tags.
+    #[command(subcommand)]
+    Tag(TagCommands),
+
+    /// Full-text search of synced messages and thinking, best matches first.
+    Search {
+        /// Words that must all appear; end a word with * to match a prefix.
+        query: String,
+
+        /// Maximum number of results.
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Only conversations of this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Only conversations with this tag.
+        #[arg(long)]
+        tag: Option<String>,
     },
 
+    /// List past conversations most related to a given one.
+    Similar {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Maximum number of results.
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Semantic search index (embeddings configured in [embeddings]).
+    #[command(subcommand)]
+    Index(IndexCommands),
+
     /// Show statistics about stored conversations.
-    Stats,
+    Stats {
+        #[command(subcommand)]
+        command: Option<StatsCommands>,
+
+        /// Write usage per day, model and workspace to this CSV file.
+        #[arg(long, value_name = "FILE")]
+        export: Option<String>,
+    },
 
-    /// Show database paths being used.
-    Paths,
+    /// Show database paths with size, last change, lock state and conversation count.
+    Paths {
+        /// Print machine-readable JSON.
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Quick access menu - list conversations with numbers for fast selection.
     Quick {
         /// Number of recent conversations to show.
         #[arg(short, long, default_value = "5")]
         limit: usize,
+
+        /// Print a compact JSON array (`index`, `id`, `title`, `workspace`,
+        /// `last_activity`, `message_count`) and nothing else, for scripts and
+        /// AI agents recovering their earlier context.
+        #[arg(long)]
+        machine: bool,
     },
 
     /// Open conversation directly (shows last 10 messages by default).
     Open {
         /// Conversation ID or number from quick list.
         id: String,
+
+        /// Bring up Cursor instead of printing the conversation.
+        #[arg(long)]
+        in_cursor: bool,
     },
 
     /// Sync management commands.
@@ -146,6 +446,33 @@ pub enum Commands {
         /// Sync interval in seconds.
         #[arg(short, long, default_value = "120")]
         interval: u64,
+
+        /// Stream synced conversations and messages as server-sent events
+        /// on GET /events at this address (e.g. 127.0.0.1:7878).
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<String>,
+
+        /// Run a single cycle (restore check, sync, limits, maintenance) and exit.
+        #[arg(long)]
+        once: bool,
+
+        /// Sync shortly after Cursor writes its state databases instead of
+        /// every interval; the interval still applies when nothing changes.
+        #[arg(long, conflicts_with = "once")]
+        watch: bool,
+
+        /// Record the daemon's PID in this file while it runs (the cron
+        /// service passes the data directory's sync.pid).
+        #[arg(long, value_name = "PATH", conflicts_with = "once")]
+        pid_file: Option<std::path::PathBuf>,
+    },
+
+    /// Serve list/get/search/export over gRPC (`proto/cursor_chat.proto`).
+    #[cfg(feature = "grpc")]
+    Grpc {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        listen: std::net::SocketAddr,
     },
 
     /// Restore chat history to Cursor after clearing/reset.
@@ -157,25 +484,438 @@ pub enum Commands {
         /// Force restore even if Cursor has chats.
         #[arg(long)]
         force: bool,
+
+        /// Close Cursor first if it is running (otherwise the restore is refused).
+        #[arg(long)]
+        close_cursor: bool,
+    },
+
+    /// Erase a conversation from Cursor's database (it stays in local storage).
+    Delete {
+        /// Conversation ID (full or unambiguous partial).
+        id: String,
+
+        /// Close Cursor first if it is running (otherwise the delete is refused).
+        #[arg(long)]
+        close_cursor: bool,
+    },
+
+    /// Archive a conversation: sync it to local storage and mark it archived,
+    /// optionally erasing it from Cursor (bring it back with restore).
+    Archive {
+        /// Conversation ID (full or partial).
+        #[arg(required_unless_present = "list")]
+        id: Option<String>,
+
+        /// Also erase it from Cursor's database to declutter the IDE.
+        #[arg(long)]
+        delete: bool,
+
+        /// Close Cursor first if it is running (otherwise the delete is refused).
+        #[arg(long, requires = "delete")]
+        close_cursor: bool,
+
+        /// List archived conversations instead.
+        #[arg(long, conflicts_with_all = ["id", "delete"])]
+        list: bool,
+    },
+
+    /// Erase old or chosen conversations from Cursor's database to shrink it
+    /// (they stay in local storage, archived).
+    Prune {
+        /// Conversations whose last message is older than this (e.g. 90d, 12w).
+        #[arg(long, value_parser = parse_age, required_unless_present = "ids", conflicts_with = "ids")]
+        older_than: Option<chrono::Duration>,
+
+        /// Conversation to erase (full or partial ID); repeat for several.
+        #[arg(short, long = "id", value_name = "ID")]
+        ids: Vec<String>,
+
+        /// Show what would be erased without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Close Cursor first if it is running (otherwise the prune is refused).
+        #[arg(long, conflicts_with = "dry_run")]
+        close_cursor: bool,
+    },
+
+    /// Import JSON or Markdown exports (a file or a directory) into local
+    /// storage, merging them with the conversations already kept.
+    Import {
+        /// Export file, or directory searched recursively for .json and .md files.
+        path: std::path::PathBuf,
+    },
+
+    /// Merge conversations Cursor split across several composers into one,
+    /// in local storage (and optionally back in Cursor).
+    Merge {
+        /// Conversations to merge (full or partial IDs).
+        #[arg(required = true, num_args = 2..)]
+        ids: Vec<String>,
+
+        /// Conversation to merge into: one of the IDs, or "new" for a new
+        /// conversation (default: the first ID).
+        #[arg(long, value_name = "ID")]
+        into: Option<String>,
+
+        /// Also write the merged conversation to Cursor.
+        #[arg(long)]
+        restore: bool,
+
+        /// Close Cursor first if it is running (otherwise the restore is refused).
+        #[arg(long, requires = "restore")]
+        close_cursor: bool,
     },
 
     /// Complete Cursor reset (backup chats → reset trial → restore chats).
-    Reset {
-        /// Skip chat restore after reset.
+    Reset(ResetArgs),
+
+    /// Check the whole setup (Cursor, storage, daemon, disk, config) with fix hints.
+    Doctor,
+
+    /// Time read, parse, extract, sync and export against the Cursor database.
+    Bench(BenchArgs),
+
+    /// Read or change settings in config.toml.
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Remove the sync services and config file (and all data with --purge).
+    Uninstall {
+        /// Also delete the data: storage.db, backups and exports. The data
+        /// directory itself goes only if nothing else is left in it.
+        #[arg(long)]
+        purge: bool,
+
+        /// Do not ask for confirmation before purging.
+        #[arg(short, long, requires = "purge")]
+        yes: bool,
+    },
+}
+
+/// Date range of `list`, `export` and `export-all`.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub struct DateRangeArgs {
+    /// Only conversations with messages since this time: a period (7d,
+    /// 12h), today, yesterday, a date (2024-05-01) or an RFC 3339 timestamp.
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only conversations with messages before this time; a date or
+    /// today/yesterday includes that whole day.
+    #[arg(long, value_parser = parse_until)]
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Also leave out the messages outside --since/--until.
+    #[arg(long)]
+    pub trim_messages: bool,
+}
+
+/// Arguments for the bench command.
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Also benchmark a generated dataset with this many conversations.
+    #[arg(long, value_name = "CONVERSATIONS")]
+    pub synthetic: Option<usize>,
+
+    /// Messages per conversation in the generated dataset.
+    #[arg(long, default_value = "40", requires = "synthetic")]
+    pub messages: usize,
+
+    /// Runs per phase; the median is reported.
+    #[arg(short, long, default_value = "3")]
+    pub iterations: usize,
+
+    /// Skip the real Cursor database (only with --synthetic).
+    #[arg(long, requires = "synthetic")]
+    pub synthetic_only: bool,
+}
+
+/// Arguments for the reset command.
+#[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ResetArgs {
+    /// Skip chat restore after reset.
+    #[arg(long)]
+    pub no_restore: bool,
+
+    /// Wait for Cursor to be reopened and closed again, then restore chats.
+    #[arg(long, conflicts_with = "no_restore")]
+    pub restore: bool,
+
+    /// Seconds to wait for each Cursor launch/close when using --restore.
+    #[arg(long, default_value = "600", requires = "restore")]
+    pub wait_timeout: u64,
+
+    /// Skip the sync and storage backup taken before resetting.
+    #[arg(long)]
+    pub no_backup: bool,
+
+    /// Also clean `AppImage` files from Downloads.
+    #[arg(long)]
+    pub clean_appimage: bool,
+
+    /// Only stop running Cursor processes.
+    #[arg(long, conflicts_with_all = ["config", "desktop", "appimage", "machine_id", "skip_machine_id", "clean_appimage"])]
+    pub kill_only: bool,
+
+    /// Only clean configuration directories (combinable with other step flags).
+    #[arg(long)]
+    pub config: bool,
+
+    /// Only clean desktop entries and icons (combinable with other step flags).
+    #[arg(long)]
+    pub desktop: bool,
+
+    /// Only clean downloaded Cursor images (combinable with other step flags).
+    #[arg(long)]
+    pub appimage: bool,
+
+    /// Only reset the machine ID (combinable with other step flags).
+    #[arg(long, conflicts_with = "skip_machine_id")]
+    pub machine_id: bool,
+
+    /// Run the full reset but keep the current machine ID.
+    #[arg(long)]
+    pub skip_machine_id: bool,
+
+    /// Print every file, directory and command that would be affected, then exit.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip operations that need root (machine ID, /usr/share cleanup).
+    #[arg(long)]
+    pub no_sudo: bool,
+}
+
+impl ResetArgs {
+    /// Resolve the step flags into the steps to run.
+    ///
+    /// Without any step flag a full reset is performed.
+    #[must_use]
+    pub const fn steps(&self) -> ResetSteps {
+        let steps = if self.kill_only {
+            ResetSteps::kill_only()
+        } else if self.config || self.desktop || self.appimage || self.machine_id {
+            // Cursor is always stopped before any cleanup step
+            ResetSteps {
+                kill: true,
+                config: self.config,
+                desktop: self.desktop,
+                appimage: self.appimage,
+                machine_id: self.machine_id,
+            }
+        } else {
+            ResetSteps::full(self.clean_appimage)
+        };
+
+        if self.skip_machine_id {
+            steps.without_machine_id()
+        } else {
+            steps
+        }
+    }
+}
+
+/// Parse a period such as `30m`, `12h`, `7d` or `2w`.
+fn parse_age(value: &str) -> Result<chrono::Duration, String> {
+    let split = value.len() - value.chars().last().map_or(0, char::len_utf8);
+    let amount: i64 = value[..split]
+        .parse()
+        .map_err(|_| format!("invalid period '{value}' (expected e.g. 30m, 12h, 7d, 2w)"))?;
+    if amount <= 0 {
+        return Err(format!("invalid period '{value}' (must be positive)"));
+    }
+
+    let age = match &value[split..] {
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => return Err(format!("invalid period '{value}' (use m, h, d or w)")),
+    };
+    age.filter(|age| chrono::Utc::now().checked_sub_signed(*age).is_some())
+        .ok_or_else(|| format!("invalid period '{value}' (too long)"))
+}
+
+/// Parse a size such as `800`, `500K`, `1M` or `2MB` (binary units).
+fn parse_size(value: &str) -> Result<usize, String> {
+    let upper = value.trim().to_uppercase();
+    let digits = upper.strip_suffix('B').unwrap_or(&upper);
+    let (amount, multiplier) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 1024),
+        Some('M') => (&digits[..digits.len() - 1], 1024 * 1024),
+        Some('G') => (&digits[..digits.len() - 1], 1024 * 1024 * 1024),
+        _ => (digits, 1),
+    };
+
+    amount
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("invalid size '{value}' (expected e.g. 500K, 1M)"))
+}
+
+/// Parse a point in time: a period before now (see [`parse_age`]), the
+/// start of a local day (`today`, `yesterday`, a date) or an RFC 3339
+/// timestamp.
+fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    parse_time(value, false)
+}
+
+/// Like [`parse_since`], but a day means its end, so `--until 2024-05-01`
+/// includes that day.
+fn parse_until(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    parse_time(value, true)
+}
+
+fn parse_time(value: &str, end_of_day: bool) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&chrono::Utc));
+    }
+    let today = chrono::Local::now().date_naive();
+    let day = match value {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        _ => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+    };
+    if let Some(day) = day {
+        return if end_of_day {
+            day.succ_opt()
+        } else {
+            Some(day)
+        }
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map(|at| at.with_timezone(&chrono::Utc))
+        .ok_or_else(|| format!("invalid date '{value}'"));
+    }
+
+    parse_age(value)
+        .map(|age| chrono::Utc::now() - age)
+        .map_err(|_| {
+            format!(
+                "invalid time '{value}' (expected e.g. 7d, yesterday, 2024-05-01 or 2024-05-01T10:00:00Z)"
+            )
+        })
+}
+
+/// Titles subcommands.
+#[derive(Subcommand, Debug)]
+pub enum TitlesCommands {
+    /// Generate titles with the LLM for conversations with poor heuristic titles.
+    Regenerate {
+        /// Skip conversations that already have a generated title.
+        #[arg(long)]
+        missing_only: bool,
+
+        /// Maximum number of conversations to process (0 = all).
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+    },
+}
+
+/// Tag subcommands.
+#[derive(Subcommand, Debug)]
+pub enum TagCommands {
+    /// Add tags to a conversation.
+    Add {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Tags to add (case-insensitive, without spaces).
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Remove tags from a conversation.
+    Remove {
+        /// Conversation ID (full or partial).
+        conversation_id: String,
+
+        /// Tags to remove.
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Show a conversation's tags, or every tag with its number of conversations.
+    List {
+        /// Conversation ID (full or partial).
+        conversation_id: Option<String>,
+    },
+}
+
+/// Index subcommands.
+#[derive(Subcommand, Debug)]
+pub enum IndexCommands {
+    /// Embed conversations that are new or changed since the last build.
+    Build {
+        /// Embed every conversation again.
         #[arg(long)]
-        no_restore: bool,
+        force: bool,
+    },
 
-        /// Also clean AppImage files from Downloads.
+    /// Find conversations by meaning rather than exact words.
+    Search {
+        /// What you are looking for, in natural language.
+        query: String,
+
+        /// Maximum number of results.
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+
+        /// Only conversations of this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Only conversations with this tag.
         #[arg(long)]
-        clean_appimage: bool,
+        tag: Option<String>,
+    },
+}
+
+/// Stats subcommands.
+#[derive(Subcommand, Debug)]
+pub enum StatsCommands {
+    /// Ranked view: longest chats, top token consumers, busiest days, top models.
+    Top {
+        /// Entries per ranking.
+        #[arg(short, long, default_value = "5")]
+        limit: usize,
+    },
+
+    /// Hours of AI-assisted work per workspace and week, from message times.
+    Sessions {
+        /// A pause longer than this many minutes ends a work session.
+        #[arg(short, long, default_value = "30")]
+        gap: u32,
+
+        /// Only sessions within this period (e.g. 12h, 7d, 4w).
+        #[arg(short, long, value_parser = parse_age)]
+        since: Option<chrono::Duration>,
+
+        /// Only this workspace (project name).
+        #[arg(short, long)]
+        workspace: Option<String>,
     },
+
+    /// Conversations and messages per mode (agent, edit, ask), with agentic message counts.
+    Modes,
 }
 
 /// Sync subcommands.
 #[derive(Subcommand, Debug)]
 pub enum SyncCommands {
     /// Install and start the sync service.
-    Start,
+    Start {
+        /// `daemon` (long-running service) or `timer` (systemd timer or
+        /// launchd interval running one sync cycle every interval).
+        #[arg(long, default_value = "daemon")]
+        mode: ServiceMode,
+    },
 
     /// Stop and disable the sync service.
     Stop,
@@ -186,6 +926,9 @@ pub enum SyncCommands {
     /// Run a sync immediately.
     Now,
 
+    /// Compare local storage with Cursor's database (missing, unsynced, changed).
+    Diff,
+
     /// Show sync logs.
     Logs {
         /// Number of log lines to show.
@@ -205,7 +948,33 @@ pub enum SyncCommands {
         /// Force restore even if not needed.
         #[arg(short, long)]
         force: bool,
+
+        /// Close Cursor first if it is running (otherwise the restore is refused).
+        #[arg(long)]
+        close_cursor: bool,
+    },
+}
+
+/// Config subcommands.
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Print a setting (e.g. `sync.interval_secs`), or the whole config.
+    Get {
+        /// Dotted key; prints everything if omitted.
+        key: Option<String>,
     },
+
+    /// Change a setting, e.g. `config set sync.interval_secs 60`.
+    Set {
+        /// Dotted key.
+        key: String,
+
+        /// New value.
+        value: String,
+    },
+
+    /// Open config.toml in your editor (VISUAL or EDITOR) and validate it afterwards.
+    Edit,
 }
 
 /// Storage subcommands.
@@ -217,16 +986,59 @@ pub enum StorageCommands {
     /// Clean up old backups and enforce storage limits.
     Cleanup,
 
+    /// List storage backups with their conversation counts and origin.
+    Backups,
+
     /// List all workspaces/projects.
     Workspaces,
 
     /// Show storage configuration.
     Config,
+
+    /// Compact storage.db (WAL checkpoint + VACUUM) and report reclaimed space.
+    Vacuum,
+
+    /// Export all chats to a standalone database file for querying with other tools.
+    ExportDb {
+        /// Output database path.
+        output: String,
+
+        /// Overwrite the output file if it exists.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Check storage.db for corruption and inconsistent rows.
+    Doctor {
+        /// Repair problems found (salvages into a fresh database if corrupt).
+        #[arg(long)]
+        fix: bool,
+    },
 }
 
 impl Cli {
-    /// Parse the output format argument.
+    /// Parse the output format argument (always JSON with `--filter`).
     pub fn output_format(&self) -> Result<OutputFormat, String> {
+        if self.filter.is_some() {
+            return Ok(OutputFormat::Json);
+        }
         self.format.parse()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30m"), Ok(chrono::Duration::minutes(30)));
+        assert_eq!(parse_age("2w"), Ok(chrono::Duration::days(14)));
+        assert!(parse_age("-1d").is_err());
+        assert!(parse_age("0d").is_err());
+        assert!(parse_age("9223372036854775807d").is_err());
+        assert!(parse_age("100000000d").is_err());
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("").is_err());
+    }
+}
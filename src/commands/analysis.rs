@@ -0,0 +1,702 @@
+//! Commands that analyse stored conversations: stats, search, tags,
+//! summaries, timelines and the change feed.
+
+use std::io::Write;
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::application::{
+    extract_action_items, find_conversation, format_action_items_markdown, format_correlation,
+    format_leaderboard, format_message_hits, format_mode_usage, format_query_table,
+    format_session_report, format_stats, format_timeline, mode_breakdown, resolve_conversation,
+    usage_breakdown, write_query_csv, write_usage_csv, Correlation, ExtractOptions, Leaderboard,
+    OutputFormat, SemanticIndex, SessionReport, SimilarityMethod, Summarizer, Timeline,
+};
+use crate::domain::{
+    truncate_chars, AppError, ChangeType, Conversation, OperationKind, Result, TokenCount,
+};
+use crate::infrastructure::{commits_between, load_config, query_storage, LocalStorage};
+
+use super::browse::output_writer;
+use super::selection::{load_conversations, normalize_tag, short_ids, stored_workspaces};
+
+/// Show statistics command, optionally writing the usage tables to `export`.
+///
+/// # Errors
+/// Returns error if Cursor's database cannot be read or the tables cannot be
+/// written.
+pub fn cmd_stats(export: Option<&str>) -> Result<()> {
+    let options = ExtractOptions {
+        include_empty: true,
+        ..Default::default()
+    };
+
+    let (conversations, stats) = load_conversations(&options)?;
+    println!("{}", format_stats(&stats, &total_tokens(&conversations)));
+
+    if let Some(path) = export {
+        let rows = usage_breakdown(&conversations, &stored_workspaces()?);
+        let mut writer = output_writer(Some(path))?;
+        write_usage_csv(&mut writer, &rows)
+            .and_then(|()| writer.flush())
+            .map_err(|e| AppError::io(format!("Failed to write {path}"), e))?;
+        println!(
+            "{} Exported {} usage rows to {}",
+            "✓".green(),
+            rows.len(),
+            path
+        );
+    }
+
+    Ok(())
+}
+
+/// Tokens used across `conversations`.
+pub(super) fn total_tokens(conversations: &[Conversation]) -> TokenCount {
+    let mut total = TokenCount::default();
+    for conv in conversations {
+        total += &conv.token_count();
+    }
+    total
+}
+
+/// Extract action items from one conversation, or all when `id` is `None`.
+///
+/// # Errors
+/// Returns error if the conversation is not found or cannot be read.
+pub fn cmd_todos(id: Option<&str>, format: OutputFormat) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+
+    let selected: Vec<_> = match id {
+        Some(id) => vec![find_conversation(&conversations, id)?.clone()],
+        None => conversations,
+    };
+
+    let items: Vec<_> = selected.iter().flat_map(extract_action_items).collect();
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&items).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if items.is_empty() {
+        println!("{}", "No action items found.".yellow());
+    } else {
+        println!("{}", format_action_items_markdown(&selected, &items));
+    }
+
+    Ok(())
+}
+
+/// Summarize a stored conversation with the configured LLM.
+///
+/// # Errors
+/// Returns error if the conversation is not found or the LLM request fails.
+pub fn cmd_summarize(id: &str, refresh: bool, format: OutputFormat) -> Result<()> {
+    let summarizer = Summarizer::new(load_config()?);
+    let result = summarizer.summarize(id, refresh)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&result).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    let title = if result.title.is_empty() {
+        &result.composer_id
+    } else {
+        &result.title
+    };
+    println!("## {title}");
+    println!();
+    println!("{}", result.summary);
+    println!();
+
+    let source = if result.cached { "cached" } else { "new" };
+    println!(
+        "{}",
+        format!(
+            "{source} · {} · {}",
+            result.model,
+            result.created_at.format("%Y-%m-%d %H:%M")
+        )
+        .dimmed()
+    );
+
+    Ok(())
+}
+
+/// Add `tags` to a conversation, or remove them unless `add` (`tag add`,
+/// `tag remove`).
+///
+/// # Errors
+/// Returns error if a tag is invalid, the conversation is not found or storage
+/// cannot be written.
+pub fn cmd_tag(conversation_id: &str, tags: &[String], add: bool) -> Result<()> {
+    let storage = LocalStorage::open(&load_config()?.storage_db_path())?;
+    let ids = short_ids()?;
+
+    let tags = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<_>>>()?;
+    let conv = resolve_conversation(&storage, conversation_id)?;
+    for tag in &tags {
+        let changed = if add {
+            storage.add_tag(&conv.composer_id, tag)?
+        } else {
+            storage.remove_tag(&conv.composer_id, tag)?
+        };
+        let (mark, note) = match (add, changed) {
+            (true, true) => ("✓".green(), "added"),
+            (true, false) => ("·".dimmed(), "already set"),
+            (false, true) => ("✓".green(), "removed"),
+            (false, false) => ("·".dimmed(), "not set"),
+        };
+        println!(
+            "  {mark} {}  {}  {tag} {}",
+            ids.get(&conv.composer_id),
+            truncate_chars(&conv.title, 40),
+            format!("({note})").dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Print one conversation's tags, or every tag with its count (`tag list`).
+///
+/// # Errors
+/// Returns error if the conversation is not found or storage cannot be read.
+pub fn cmd_tag_list(conversation_id: Option<&str>, format: OutputFormat) -> Result<()> {
+    let storage = LocalStorage::open(&load_config()?.storage_db_path())?;
+    let json = matches!(format, OutputFormat::Json);
+
+    if let Some(id) = conversation_id {
+        let conv = resolve_conversation(&storage, id)?;
+        let tags = storage
+            .conversation_tags()?
+            .remove(&conv.composer_id)
+            .unwrap_or_default();
+        if json {
+            let output = serde_json::to_string_pretty(&tags).map_err(AppError::json_parse)?;
+            println!("{output}");
+        } else if tags.is_empty() {
+            println!("{}", "No tags.".yellow());
+        } else {
+            println!(
+                "{}  {}  {}",
+                short_ids()?.get(&conv.composer_id),
+                truncate_chars(&conv.title, 40),
+                tags.join(", ").cyan()
+            );
+        }
+        return Ok(());
+    }
+
+    let counts = storage.tag_counts()?;
+    if json {
+        let counts: Vec<_> = counts
+            .iter()
+            .map(|(tag, count)| serde_json::json!({ "tag": tag, "conversations": count }))
+            .collect();
+        let output = serde_json::to_string_pretty(&counts).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if counts.is_empty() {
+        println!(
+            "{}",
+            "No tags yet. Add one with: cursor-chat tag add <id> <tag>".yellow()
+        );
+    } else {
+        for (tag, count) in &counts {
+            println!("  {:<24} {count}", tag.cyan());
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate LLM titles for conversations with poor heuristic titles.
+///
+/// # Errors
+/// Returns error if storage cannot be read or written, or the LLM is not
+/// configured.
+pub fn cmd_titles_regenerate(missing_only: bool, limit: usize, format: OutputFormat) -> Result<()> {
+    let summarizer = Summarizer::new(load_config()?);
+
+    if !matches!(format, OutputFormat::Json) {
+        println!("{}", "🏷  Generating titles...".bold());
+    }
+    let run = summarizer.regenerate_titles(missing_only, limit)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&run).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    let ids = short_ids()?;
+    for change in &run.changed {
+        let old = if change.old_title.is_empty() {
+            "(none)"
+        } else {
+            &change.old_title
+        };
+        println!(
+            "  {} {}  {} → {}",
+            "✓".green(),
+            ids.get(&change.composer_id),
+            old.dimmed(),
+            change.new_title
+        );
+    }
+    for (composer_id, error) in &run.failed {
+        println!("  {} {}  {error}", "✗".red(), ids.get(composer_id));
+    }
+
+    println!();
+    if run.changed.is_empty() && run.failed.is_empty() {
+        println!("{}", "No conversations need a new title.".green());
+    } else {
+        println!(
+            "{} titles generated, {} failed",
+            run.changed.len(),
+            run.failed.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Build or update the semantic index (`index build`).
+///
+/// # Errors
+/// Returns error if storage cannot be read or an embedding request fails.
+pub fn cmd_index_build(force: bool, format: OutputFormat) -> Result<()> {
+    let index = SemanticIndex::new(load_config()?);
+
+    if !matches!(format, OutputFormat::Json) {
+        println!("{}", "🧭 Building semantic index...".bold());
+    }
+    let build = index.build(force)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&build).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!(
+        "{} {} embedded, {} unchanged ({})",
+        "✓".green(),
+        build.embedded,
+        build.unchanged,
+        build.model.dimmed()
+    );
+    Ok(())
+}
+
+/// Search conversations by meaning (`index search`).
+///
+/// # Errors
+/// Returns error if the tag is invalid, the index cannot be read or the query
+/// cannot be embedded.
+pub fn cmd_index_search(
+    query: &str,
+    limit: usize,
+    workspace: Option<&str>,
+    tag: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let tag = tag.map(normalize_tag).transpose()?;
+    let hits =
+        SemanticIndex::new(load_config()?).search(query, limit, workspace, tag.as_deref())?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&hits).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        let message = match (workspace, tag) {
+            (None, None) => "No conversations indexed.".to_string(),
+            (Some(name), None) => format!("No conversations indexed in workspace '{name}'."),
+            (_, Some(_)) => "No indexed conversations match the filters.".to_string(),
+        };
+        println!("{}", message.yellow());
+        return Ok(());
+    }
+
+    let ids = short_ids()?;
+    for hit in &hits {
+        println!(
+            "  {:.3}  {}  {}",
+            hit.score,
+            ids.get(&hit.composer_id).cyan(),
+            hit.title
+        );
+    }
+    Ok(())
+}
+
+/// Full-text search of synced messages (`search`).
+///
+/// # Errors
+/// Returns error if the tag is invalid or storage cannot be read.
+pub fn cmd_search(
+    query: &str,
+    limit: usize,
+    workspace: Option<&str>,
+    tag: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let tag = tag.map(normalize_tag).transpose()?;
+    let storage_path = load_config()?.storage_db_path();
+    if !storage_path.exists() {
+        return Err(AppError::Config {
+            message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+        });
+    }
+    let hits = LocalStorage::open_read_only(&storage_path)?.search_messages(
+        query,
+        limit,
+        workspace,
+        tag.as_deref(),
+    )?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&hits).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        println!("{}", format!("No messages match '{query}'.").yellow());
+        return Ok(());
+    }
+
+    println!("{}", format_message_hits(&hits, &short_ids()?));
+    Ok(())
+}
+
+/// List conversations related to one (`similar`).
+///
+/// # Errors
+/// Returns error if the conversation is not found or storage cannot be read.
+pub fn cmd_similar(id: &str, limit: usize, format: OutputFormat) -> Result<()> {
+    let similar = SemanticIndex::new(load_config()?).similar(id, limit)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&similar).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("{} {}", "🔗 Similar to".bold(), similar.title.bold());
+    if similar.method == SimilarityMethod::Tfidf {
+        println!(
+            "{}",
+            "   (TF-IDF; run 'cursor-chat index build' for embedding-based results)".dimmed()
+        );
+    }
+    println!();
+
+    if similar.hits.is_empty() {
+        println!("{}", "No related conversations found.".yellow());
+        return Ok(());
+    }
+
+    let ids = short_ids()?;
+    for hit in &similar.hits {
+        println!(
+            "  {:.3}  {}  {}",
+            hit.score,
+            ids.get(&hit.composer_id).cyan(),
+            hit.title
+        );
+    }
+    Ok(())
+}
+
+/// Show conversations chronologically (`timeline`).
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted.
+pub fn cmd_timeline(
+    workspace: Option<&str>,
+    since: Option<chrono::Duration>,
+    format: OutputFormat,
+) -> Result<()> {
+    let (mut conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let workspaces = stored_workspaces()?;
+
+    if let Some(name) = workspace {
+        conversations.retain(|c| {
+            workspaces
+                .get(&c.composer_id)
+                .is_some_and(|w| w.eq_ignore_ascii_case(name))
+        });
+    }
+
+    let since = since.map(|age| chrono::Utc::now() - age);
+    let timeline = Timeline::build(&conversations, &workspaces, since);
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&timeline).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if timeline.is_empty() {
+        println!("{}", "No conversations in this period.".yellow());
+    } else {
+        println!("{}", format_timeline(&timeline, &short_ids()?));
+    }
+
+    Ok(())
+}
+
+/// Interleave a conversation with its workspace's commits (`correlate`).
+///
+/// # Errors
+/// Returns error if the conversation is not found or the repository cannot be
+/// read.
+pub fn cmd_correlate(
+    id: &str,
+    margin: chrono::Duration,
+    repo: Option<&Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let conv = find_conversation(&conversations, id)?;
+
+    let repo_root = match (repo, &conv.git) {
+        (Some(repo), _) => repo.display().to_string(),
+        (None, Some(git)) => git.repo_root.clone(),
+        (None, None) => {
+            return Err(AppError::Config {
+                message: format!(
+                    "No git repository recorded for conversation {}. Run 'cursor-chat sync now' \
+                     or pass --repo.",
+                    conv.composer_id
+                ),
+            })
+        }
+    };
+    let window = Correlation::window(conv, margin).ok_or_else(|| AppError::InvalidData {
+        message: format!(
+            "Conversation {} has no timestamped messages",
+            conv.composer_id
+        ),
+    })?;
+
+    let commits = commits_between(Path::new(&repo_root), window.0, window.1)?;
+    let correlation = Correlation::build(conv, &repo_root, window, commits);
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&correlation).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else {
+        println!("{}", format_correlation(&correlation, &short_ids()?));
+    }
+
+    Ok(())
+}
+
+/// Show the change feed.
+///
+/// # Errors
+/// Returns error if storage cannot be read.
+pub fn cmd_changes(
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    limit: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let storage_path = load_config()?.storage_db_path();
+    let changes = if storage_path.exists() {
+        LocalStorage::open_read_only(&storage_path)?.get_changes(since, limit)?
+    } else {
+        Vec::new()
+    };
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&changes).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if changes.is_empty() {
+        println!("{}", "No changes in this period.".yellow());
+        return Ok(());
+    }
+
+    let ids = short_ids()?;
+    println!("{}", "🆕 Changes".bold());
+    println!();
+    for change in &changes {
+        let marker = match change.kind {
+            ChangeType::Created => "+ new    ".green(),
+            ChangeType::Updated => "~ updated".cyan(),
+        };
+        println!(
+            "  {} {}  {}  {}",
+            change
+                .synced_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .dimmed(),
+            marker,
+            ids.get(&change.composer_id),
+            change.title
+        );
+    }
+
+    Ok(())
+}
+
+/// Show the operations audit log.
+///
+/// # Errors
+/// Returns error if the audit log cannot be read.
+pub fn cmd_history(limit: usize, kind: Option<OperationKind>, format: OutputFormat) -> Result<()> {
+    let storage_path = load_config()?.storage_db_path();
+    let operations = if storage_path.exists() {
+        LocalStorage::open_read_only(&storage_path)?.get_operations(limit, kind)?
+    } else {
+        Vec::new()
+    };
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&operations).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if operations.is_empty() {
+        println!("{}", "No operations recorded yet.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "📜 Operation history".bold());
+    println!();
+    for op in &operations {
+        let status = if op.success {
+            "✓".green()
+        } else {
+            "✗".red()
+        };
+        println!(
+            "  {} {} {:<8} {:>5} items  {:>7}  {}",
+            op.started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .dimmed(),
+            status,
+            op.kind.as_str(),
+            op.items,
+            format!("{}ms", op.duration_ms),
+            op.details
+        );
+    }
+
+    Ok(())
+}
+
+/// Run a read-only SQL query against local storage (`query`).
+///
+/// # Errors
+/// Returns error if the query is not read-only or fails.
+pub fn cmd_query(sql: &str, csv: bool, format: OutputFormat) -> Result<()> {
+    let result = query_storage(&load_config()?.storage_db_path(), sql)?;
+
+    if csv {
+        let mut stdout = std::io::stdout().lock();
+        return write_query_csv(&mut stdout, &result)
+            .map_err(|e| AppError::io("Failed to write CSV", e));
+    }
+
+    if matches!(format, OutputFormat::Json) {
+        let output =
+            serde_json::to_string_pretty(&result.to_objects()).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("{}", format_query_table(&result));
+    Ok(())
+}
+
+/// Show ranked statistics (`stats top`).
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted.
+pub fn cmd_stats_top(limit: usize, format: OutputFormat) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let board = Leaderboard::from_conversations(&conversations, limit);
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&board).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if board.is_empty() {
+        println!("{}", "No conversations found.".yellow());
+    } else {
+        println!("{}", format_leaderboard(&board, &short_ids()?));
+    }
+
+    Ok(())
+}
+
+/// Usage per conversation mode (`stats modes`).
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted.
+pub fn cmd_stats_modes(format: OutputFormat) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let modes = mode_breakdown(&conversations);
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&modes).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if modes.is_empty() {
+        println!("{}", "No conversations found.".yellow());
+    } else {
+        println!("{}", format_mode_usage(&modes));
+    }
+
+    Ok(())
+}
+
+/// Hours of work per workspace and week (`stats sessions`).
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted.
+pub fn cmd_stats_sessions(
+    gap: u32,
+    since: Option<chrono::Duration>,
+    workspace: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let (mut conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let workspaces = stored_workspaces()?;
+
+    if let Some(name) = workspace {
+        conversations.retain(|c| {
+            workspaces
+                .get(&c.composer_id)
+                .is_some_and(|w| w.eq_ignore_ascii_case(name))
+        });
+    }
+
+    let since = since.map(|age| chrono::Utc::now() - age);
+    let report = SessionReport::build(&conversations, &workspaces, gap, since);
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&report).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if report.is_empty() {
+        println!("{}", "No timestamped messages in this period.".yellow());
+    } else {
+        println!("{}", format_session_report(&report));
+    }
+
+    Ok(())
+}
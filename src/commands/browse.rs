@@ -0,0 +1,630 @@
+//! Listing, showing and opening conversations (`list`, `show`, `quick`,
+//! `open`, `paths`).
+
+use std::collections::HashSet;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+use colored::Colorize;
+use dialoguer::Select;
+
+use crate::application::{
+    find_conversation, format_bytes, format_conversation_markdown, format_conversations_by_day,
+    format_conversations_json, format_conversations_table, format_quick_machine, format_stats,
+    ExtractOptions, JsonFilter, ListGrouping, OutputFormat,
+};
+use crate::domain::{
+    truncate_chars, AppConfig, AppError, BubbleType, Conversation, QuickMenu, Result, ShortIds,
+};
+use crate::infrastructure::{
+    copy_to_clipboard, find_state_databases, focus_cursor, inspect_state_db, is_cursor_running,
+    load_config, load_quick_menu, save_quick_menu, LocalStorage, TimedWriter,
+};
+
+use super::analysis::total_tokens;
+use super::export::write_error;
+use super::selection::{load_conversations, short_ids, Selection};
+
+/// List conversations command.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the filter fails.
+pub fn cmd_list(
+    limit: usize,
+    selection: Selection<'_>,
+    group_by: Option<ListGrouping>,
+    format: OutputFormat,
+    filter: Option<&JsonFilter>,
+) -> Result<()> {
+    let (mut conversations, mut stats) = load_conversations(&selection.extract_options())?;
+    if selection.retain(&mut conversations)? {
+        stats = stats.of_subset(&conversations);
+    }
+    let tokens = total_tokens(&conversations);
+    conversations.truncate(limit);
+
+    if matches!(format, OutputFormat::Json) {
+        return print_json(&conversations, filter);
+    }
+
+    let ids = short_ids()?;
+    let table = match group_by {
+        Some(ListGrouping::Day) => format_conversations_by_day(&conversations, &ids),
+        None => format_conversations_table(&conversations, &ids),
+    };
+    println!("{table}");
+    println!();
+    println!("{}", format_stats(&stats, &tokens));
+
+    Ok(())
+}
+
+/// Show a single conversation.
+///
+/// # Errors
+/// Returns error if the conversation is not found or cannot be read.
+pub fn cmd_show(
+    id: &str,
+    include_empty: bool,
+    last: Option<usize>,
+    format: OutputFormat,
+    filter: Option<&JsonFilter>,
+) -> Result<()> {
+    let conv = conversation_to_show(id, include_empty, last)?;
+
+    let output = match format {
+        OutputFormat::Markdown => format_conversation_markdown(&conv, &short_ids()?),
+        OutputFormat::Json => return print_json(std::slice::from_ref(&conv), filter),
+        OutputFormat::Table => {
+            format_conversations_table(std::slice::from_ref(&conv), &short_ids()?)
+        }
+    };
+
+    println!("{output}");
+    Ok(())
+}
+
+/// The conversation `show` prints, limited to the `last` messages.
+pub(super) fn conversation_to_show(
+    id: &str,
+    include_empty: bool,
+    last: Option<usize>,
+) -> Result<Conversation> {
+    let options = ExtractOptions {
+        include_empty,
+        ..Default::default()
+    };
+
+    let (conversations, _) = load_conversations(&options)?;
+
+    // Find conversation by ID (partial match)
+    let conv = find_conversation(&conversations, id)?;
+
+    // Apply --last filter if specified
+    Ok(last.map_or_else(
+        || conv.clone(),
+        |n| {
+            let mut filtered = conv.clone();
+            let len = filtered.bubbles.len();
+            if n < len {
+                filtered.bubbles = filtered.bubbles.into_iter().skip(len - n).collect();
+            }
+            filtered
+        },
+    ))
+}
+
+/// Print conversations as a JSON array, or the outputs of `filter` on it.
+fn print_json(conversations: &[Conversation], filter: Option<&JsonFilter>) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    if let Some(filter) = filter {
+        return write_filtered(&mut stdout, conversations, filter);
+    }
+
+    let output = format_conversations_json(conversations).map_err(AppError::json_parse)?;
+    writeln!(stdout, "{output}").map_err(write_error)
+}
+
+/// Write each output of `filter` on the conversations as pretty JSON.
+pub(super) fn write_filtered(
+    writer: &mut dyn Write,
+    conversations: &[Conversation],
+    filter: &JsonFilter,
+) -> Result<()> {
+    let input = serde_json::to_value(conversations).map_err(AppError::json_parse)?;
+    for value in filter.apply(&input)? {
+        let output = serde_json::to_string_pretty(&value).map_err(AppError::json_parse)?;
+        writeln!(writer, "{output}").map_err(write_error)?;
+    }
+    Ok(())
+}
+
+/// Buffered writer to `path`, or to stdout if `None`.
+pub(super) fn output_writer(path: Option<&str>) -> Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(std::io::BufWriter::new(TimedWriter(
+            std::fs::File::create(path)
+                .map_err(|e| AppError::io(format!("Failed to create {path}"), e))?,
+        ))),
+        None => Box::new(std::io::BufWriter::new(TimedWriter(
+            std::io::stdout().lock(),
+        ))),
+    })
+}
+
+/// Show database paths command.
+///
+/// # Errors
+/// Returns error if Cursor's databases cannot be located.
+pub fn cmd_paths(json: bool) -> Result<()> {
+    let cursor_running = is_cursor_running();
+    let databases: Vec<_> = find_state_databases()?
+        .iter()
+        .map(|path| inspect_state_db(path, cursor_running))
+        .collect();
+
+    if json {
+        let output = serde_json::to_string_pretty(&databases).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("{}", "📂 Cursor Database Paths".bold());
+    println!();
+
+    for (i, db) in databases.iter().enumerate() {
+        let label = if db.is_global {
+            "global".green()
+        } else {
+            "workspace".blue()
+        };
+
+        println!("  {}. [{}] {}", i + 1, label, db.path.display());
+
+        let modified = db.modified.map_or_else(
+            || "unknown".to_string(),
+            |dt| {
+                dt.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            },
+        );
+        let composers = db
+            .composer_count
+            .map_or_else(|| "-".to_string(), |c| c.to_string());
+        let lock = if db.wal_locked {
+            format!("  {}", "🔒 in use by Cursor".yellow())
+        } else {
+            String::new()
+        };
+        println!(
+            "     {}  modified {}  composers {}{}",
+            format_bytes(db.size_bytes),
+            modified,
+            composers,
+            lock
+        );
+    }
+
+    println!();
+    println!("Total: {} database(s)", databases.len());
+
+    Ok(())
+}
+
+/// Quick access menu command.
+///
+/// Interactive arrow-key menu on a terminal, numbered list otherwise.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the menu fails.
+pub fn cmd_quick(limit: usize, machine: bool) -> Result<()> {
+    let options = ExtractOptions {
+        min_messages: 1,
+        ..Default::default()
+    };
+
+    let (mut conversations, _) = load_conversations(&options)?;
+    conversations.truncate(limit);
+
+    let config = load_config()?;
+    let ids = short_ids()?;
+    if !machine && std::io::stdin().is_terminal() && std::io::stderr().is_terminal() {
+        return run_quick_menu(&config, &conversations, &ids);
+    }
+
+    let menu = QuickMenu::new(
+        conversations
+            .iter()
+            .map(|c| c.composer_id.clone())
+            .collect(),
+    );
+    if let Err(e) = save_quick_menu(&config.quick_menu_path(), &menu) {
+        tracing::warn!(error = %e, "Failed to save quick menu numbering");
+    }
+
+    if machine {
+        println!(
+            "{}",
+            format_quick_machine(&conversations).map_err(AppError::json_parse)?
+        );
+        return Ok(());
+    }
+
+    let pinned = pinned_ids(&config);
+    println!("🚀 Quick Access Menu");
+    println!("==================");
+    println!();
+
+    for (i, conv) in conversations.iter().enumerate() {
+        println!(
+            "  {:2}. {}",
+            i + 1,
+            quick_label(conv, pinned.contains(&conv.composer_id), &ids)
+        );
+    }
+
+    println!();
+    println!("💡 Quick commands:");
+    println!(
+        "   cursor-chat open {}        # Open conversation by number",
+        conversations.first().map_or("N", |_| "1")
+    );
+    println!(
+        "   cursor-chat show {}         # Show full conversation",
+        conversations
+            .first()
+            .map_or("ID", |c| ids.get(&c.composer_id))
+    );
+    println!(
+        "   cursor-chat export -c {}    # Export conversation",
+        conversations
+            .first()
+            .map_or("ID", |c| ids.get(&c.composer_id))
+    );
+
+    Ok(())
+}
+
+/// One line of the quick menu: ID, model, message count and title.
+fn quick_label(conv: &Conversation, pinned: bool, ids: &ShortIds) -> String {
+    let model = if conv.model_config.model_name.is_empty() {
+        "unknown"
+    } else {
+        truncate_chars(&conv.model_config.model_name, 15)
+    };
+
+    let title = if conv.title.is_empty() {
+        "Untitled"
+    } else {
+        truncate_chars(&conv.title, 40)
+    };
+
+    format!(
+        "{}{} | {} | {} msgs | {}",
+        if pinned { "📌 " } else { "" },
+        ids.get(&conv.composer_id),
+        model,
+        conv.message_count(),
+        title
+    )
+}
+
+/// IDs of pinned conversations, empty if storage is unavailable.
+fn pinned_ids(config: &AppConfig) -> HashSet<String> {
+    let storage_path = config.storage_db_path();
+    if !storage_path.exists() {
+        return HashSet::new();
+    }
+
+    LocalStorage::open_read_only(&storage_path)
+        .and_then(|storage| storage.pinned_ids())
+        .unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "Pins unavailable");
+            HashSet::new()
+        })
+}
+
+/// Actions offered for the conversation selected in the quick menu.
+const QUICK_ACTIONS: [&str; 5] = [
+    "Show",
+    "Export to Markdown",
+    "Copy as Markdown",
+    "Pin / unpin",
+    "Back",
+];
+
+/// Arrow-key quick menu: pick a conversation, preview it, then act on it.
+///
+/// Pinned conversations are listed first. Esc or `q` quits.
+fn run_quick_menu(
+    config: &AppConfig,
+    conversations: &[Conversation],
+    ids: &ShortIds,
+) -> Result<()> {
+    if conversations.is_empty() {
+        println!("No conversations found.");
+        return Ok(());
+    }
+
+    let mut pinned = pinned_ids(config);
+    let mut selected = 0;
+
+    loop {
+        let mut ordered: Vec<&Conversation> = conversations.iter().collect();
+        ordered.sort_by_key(|c| !pinned.contains(&c.composer_id));
+
+        let labels: Vec<String> = ordered
+            .iter()
+            .map(|c| quick_label(c, pinned.contains(&c.composer_id), ids))
+            .collect();
+
+        let Some(index) = Select::new()
+            .with_prompt("🚀 Select a conversation (Esc to quit)")
+            .items(&labels)
+            .default(selected)
+            .interact_opt()
+            .map_err(menu_error)?
+        else {
+            return Ok(());
+        };
+        let conv = ordered[index];
+        selected = index;
+
+        print_preview(conv, ids);
+
+        let action = Select::new()
+            .with_prompt("Action")
+            .items(&QUICK_ACTIONS)
+            .default(0)
+            .interact_opt()
+            .map_err(menu_error)?;
+
+        match action {
+            Some(0) => {
+                println!("{}", format_conversation_markdown(conv, ids));
+                return Ok(());
+            }
+            Some(1) => match export_markdown(config, conv, ids) {
+                Ok(path) => println!("{} Exported to {}", "✓".green(), path.display()),
+                Err(e) => eprintln!("{} {}", "Error:".red().bold(), e),
+            },
+            Some(2) => match copy_to_clipboard(&format_conversation_markdown(conv, ids)) {
+                Ok(()) => println!("{} Copied to clipboard", "✓".green()),
+                Err(e) => eprintln!("{} {}", "Error:".red().bold(), e),
+            },
+            Some(3) => {
+                let pin = !pinned.contains(&conv.composer_id);
+                LocalStorage::open(&config.storage_db_path())?
+                    .set_pinned(&conv.composer_id, pin)?;
+                if pin {
+                    pinned.insert(conv.composer_id.clone());
+                    selected = 0;
+                } else {
+                    pinned.remove(&conv.composer_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Print the title and opening lines of a conversation.
+fn print_preview(conv: &Conversation, ids: &ShortIds) {
+    const PREVIEW_MESSAGES: usize = 4;
+    const PREVIEW_CHARS: usize = 100;
+
+    println!();
+    println!(
+        "{} {}",
+        ids.get(&conv.composer_id).dimmed(),
+        conv.title.bold()
+    );
+    for bubble in conv.bubbles.iter().take(PREVIEW_MESSAGES) {
+        let icon = match bubble.bubble_type {
+            BubbleType::User => "👤",
+            BubbleType::Assistant => "🤖",
+            BubbleType::Unknown => "❔",
+        };
+        let line = bubble
+            .text
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("");
+        println!("  {icon} {}", truncate_chars(line.trim(), PREVIEW_CHARS));
+    }
+    if conv.message_count() > PREVIEW_MESSAGES {
+        println!(
+            "  {}",
+            format!(
+                "… {} more messages",
+                conv.message_count() - PREVIEW_MESSAGES
+            )
+            .dimmed()
+        );
+    }
+    println!();
+}
+
+/// Write a conversation as Markdown into the exports directory.
+fn export_markdown(config: &AppConfig, conv: &Conversation, ids: &ShortIds) -> Result<PathBuf> {
+    let dir = config.exports_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| AppError::io("Failed to create exports directory", e))?;
+
+    let path = dir.join(format!("{}.md", conv.filename()));
+    std::fs::write(&path, format_conversation_markdown(conv, ids))
+        .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))?;
+
+    Ok(path)
+}
+
+/// Convert a terminal menu failure.
+fn menu_error(e: dialoguer::Error) -> AppError {
+    let dialoguer::Error::IO(e) = e;
+    AppError::io("Interactive menu failed", e)
+}
+
+/// The numbering of the last `quick`, if it ran and can be read.
+fn last_quick_menu() -> Result<Option<QuickMenu>> {
+    let path = load_config()?.quick_menu_path();
+    Ok(load_quick_menu(&path).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Ignoring unreadable quick menu numbering");
+        None
+    }))
+}
+
+/// The conversation `open` works on: a number (1-based) or a partial ID.
+fn open_target<'a>(
+    conversations: &'a [Conversation],
+    id_or_number: &str,
+    menu: Option<&QuickMenu>,
+) -> Result<&'a Conversation> {
+    id_or_number.parse::<usize>().map_or_else(
+        |_| find_conversation(conversations, id_or_number),
+        |number| conversation_by_number(conversations, number, menu),
+    )
+}
+
+/// Resolve a number as listed by the last `quick` (`menu`).
+///
+/// Falls back to the current recency order if `quick` never ran.
+fn conversation_by_number<'a>(
+    conversations: &'a [Conversation],
+    number: usize,
+    menu: Option<&QuickMenu>,
+) -> Result<&'a Conversation> {
+    let Some(menu) = menu else {
+        return number
+            .checked_sub(1)
+            .and_then(|i| conversations.get(i))
+            .ok_or_else(|| AppError::InvalidData {
+                message: format!(
+                    "Number {} is out of range (1-{})",
+                    number,
+                    conversations.len()
+                ),
+            });
+    };
+
+    let id = menu.get(number).ok_or_else(|| AppError::InvalidData {
+        message: format!(
+            "Number {} is out of range (1-{}) of the last quick menu; run `quick --limit {}` first",
+            number,
+            menu.composer_ids.len(),
+            number
+        ),
+    })?;
+
+    conversations
+        .iter()
+        .find(|c| c.composer_id == id)
+        .ok_or_else(|| AppError::InvalidData {
+            message: format!(
+                "Conversation {} of the quick menu from {} ({}) no longer exists",
+                number,
+                menu.generated_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                id
+            ),
+        })
+}
+
+/// Bring up Cursor and point at the conversation's place in its history.
+///
+/// Cursor has no documented link to a single composer, so the title to look
+/// for is printed instead. Launch failures are reported, not fatal.
+fn open_in_cursor(conv: &Conversation) {
+    match focus_cursor() {
+        Ok(method) => println!("{} Cursor opened via {}", "✓".green(), method.description()),
+        Err(e) => eprintln!("{} {}", "⚠".yellow(), e),
+    }
+    println!();
+    println!("💡 Cursor can't jump to a specific chat from outside yet.");
+    println!("   Open the chat history in the Composer panel and look for:");
+    println!("   {}", conv.title.cyan().bold());
+    println!("   {}", conv.composer_id.dimmed());
+}
+
+/// Open conversation directly command.
+///
+/// # Errors
+/// Returns error if the conversation is not found or cannot be read.
+pub fn cmd_open(id_or_number: &str, in_cursor: bool) -> Result<()> {
+    let options = ExtractOptions {
+        min_messages: 1,
+        ..Default::default()
+    };
+
+    let (conversations, _) = load_conversations(&options)?;
+    let conv = open_target(&conversations, id_or_number, last_quick_menu()?.as_ref())?;
+
+    if in_cursor {
+        open_in_cursor(conv);
+        return Ok(());
+    }
+
+    // Show the conversation with last 10 messages
+    cmd_show(
+        &conv.composer_id,
+        false,
+        Some(10),
+        OutputFormat::Markdown,
+        None,
+    )?;
+
+    let ids = short_ids()?;
+    println!();
+    println!("💡 Pro tips:");
+    println!(
+        "   cursor-chat export -c {} -o current.md    # Save this chat",
+        ids.get(&conv.composer_id)
+    );
+    println!(
+        "   cursor-chat show {} --last 20            # See more messages",
+        ids.get(&conv.composer_id)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ModelConfig;
+
+    fn conversations(ids: &[&str]) -> Vec<Conversation> {
+        ids.iter()
+            .map(|id| Conversation {
+                composer_id: (*id).to_string(),
+                title: String::new(),
+                created_at: None,
+                model_config: ModelConfig::default(),
+                unified_mode: String::new(),
+                bubbles: Vec::new(),
+                git: None,
+                workspace: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_open_target() {
+        let conversations = conversations(&["aaa-111", "bbb-222", "ccc-333"]);
+        let target = |arg: &str, menu: Option<&QuickMenu>| {
+            open_target(&conversations, arg, menu).map(|conv| conv.composer_id.as_str())
+        };
+
+        // Numbers follow recency order until `quick` has run
+        assert_eq!(target("2", None).unwrap(), "bbb-222");
+        assert!(target("4", None).is_err());
+        assert_eq!(target("bbb", None).unwrap(), "bbb-222");
+
+        let menu = QuickMenu::new(vec!["ccc-333".into(), "gone".into()]);
+        assert_eq!(target("1", Some(&menu)).unwrap(), "ccc-333");
+        assert!(target("2", Some(&menu)).is_err());
+        assert!(target("3", Some(&menu)).is_err());
+        assert_eq!(target("aaa", Some(&menu)).unwrap(), "aaa-111");
+    }
+}
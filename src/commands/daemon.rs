@@ -0,0 +1,322 @@
+//! The sync daemon loop (`daemon`) and the gRPC server (`grpc`).
+
+use std::path::Path;
+use std::time::Duration;
+
+use colored::Colorize;
+
+#[cfg(feature = "grpc")]
+use crate::application::ExtractOptions;
+use crate::application::{
+    publish_changes, AutoExporter, RestoreService, StorageManager, SyncService,
+};
+use crate::domain::{AppConfig, ResetEvent, Result};
+#[cfg(feature = "grpc")]
+use crate::grpc;
+use crate::infrastructure::{
+    config_file_path, generate_token, load_base_config, load_config, save_config_to_file,
+    send_notification, EventStream, FailureNotifier, LocalStorage, PidFile, StateDbWatcher,
+};
+
+#[cfg(feature = "grpc")]
+use super::selection::load_conversations;
+
+/// Run as daemon (background sync service), or a single cycle if `once`,
+/// recording the PID in `pid_file` while running.
+///
+/// # Errors
+/// Returns error if the daemon cannot start (config, event stream, watcher
+/// or PID file).
+pub fn cmd_daemon(
+    interval_secs: u64,
+    listen: Option<String>,
+    once: bool,
+    watch: bool,
+    pid_file: Option<&Path>,
+) -> Result<()> {
+    let config = load_config()?;
+    let notify_on_reset = config.sync.notify_on_reset;
+    let mut notifier = FailureNotifier::new(config.sync.notify_on_error);
+    let events = listen
+        .or_else(|| config.sync.listen.clone())
+        .map(|addr| start_event_stream(&config, &addr))
+        .transpose()?;
+
+    let mut auto_export = AutoExporter::from_config(&config)?;
+    let watcher = watch.then(StateDbWatcher::start).transpose()?;
+
+    println!("{}", "🔄 Starting sync daemon...".bold());
+    println!("  Interval: {interval_secs} seconds");
+    if let Some(watcher) = &watcher {
+        for dir in watcher.watched() {
+            println!("  Watching: {}", dir.display());
+        }
+    }
+    println!("  Data dir: {}", config.data_dir().display());
+    println!("  Auto-restore: enabled");
+    if let Some(stream) = &events {
+        println!(
+            "  Events: http://{}/events (Authorization: Bearer <sync.listen_token>)",
+            stream.local_addr()
+        );
+    }
+    if let Some(exporter) = &auto_export {
+        println!("  Auto-export: {}", exporter.dir().display());
+    }
+    println!();
+
+    // Ensure directories exist
+    let storage_mgr = StorageManager::new(config.clone());
+    storage_mgr.ensure_directories()?;
+    let _pid_file = pid_file.map(PidFile::create).transpose()?;
+
+    let storage_path = config.storage_db_path();
+    let sync_service = SyncService::new(config.clone())?;
+    let restore_service = RestoreService::new(config);
+
+    // Whether the last cycle saw Cursor in a reset state
+    let mut reset_active = false;
+    // Changes synced after this have not been streamed yet
+    let mut last_published = chrono::Utc::now();
+
+    loop {
+        tracing::info!("Starting sync cycle...");
+
+        // First, check if restore is needed (Cursor was cleared)
+        reset_active = restore_if_reset(
+            &restore_service,
+            &sync_service,
+            reset_active,
+            notify_on_reset,
+            &mut notifier,
+        );
+
+        // Then sync from Cursor to local storage
+        if sync_cycle(&sync_service, &mut notifier) {
+            if let Some(stream) = &events {
+                last_published = stream_changes(stream, &storage_path, last_published);
+            }
+            if let Some(exporter) = &mut auto_export {
+                match exporter.export_changed() {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!(count, "Exported changed conversations"),
+                    Err(e) => tracing::warn!(error = %e, "Auto-export failed"),
+                }
+            }
+        }
+
+        enforce_storage_limit(&storage_mgr, &mut notifier);
+        run_due_maintenance(&storage_mgr);
+
+        if once {
+            return Ok(());
+        }
+
+        // Sleep until next sync, or until Cursor writes a state database
+        match &watcher {
+            Some(watcher) => {
+                if watcher.wait_for_change(Duration::from_secs(interval_secs)) {
+                    tracing::info!("State database changed");
+                }
+            }
+            None => std::thread::sleep(Duration::from_secs(interval_secs)),
+        }
+    }
+}
+
+/// Restore the conversations if Cursor was reset, reporting the reset the
+/// first time it is seen (`reset_active` is whether the last cycle saw it).
+///
+/// Returns whether Cursor is still in a reset state.
+fn restore_if_reset(
+    restore_service: &RestoreService,
+    sync_service: &SyncService,
+    reset_active: bool,
+    notify_on_reset: bool,
+    notifier: &mut FailureNotifier,
+) -> bool {
+    match restore_service.auto_restore_if_needed() {
+        Ok(Some(event)) => {
+            if !reset_active {
+                handle_reset_event(sync_service, &event, notify_on_reset);
+            }
+            match &event.restore_error {
+                Some(error) => {
+                    let body = format!(
+                        "{} conversations vanished from Cursor and could not be restored: {error}",
+                        event.vanished()
+                    );
+                    notifier.failed("restore", "Restoring conversations failed", &body);
+                }
+                None => notifier.recovered("restore"),
+            }
+            // Stay in reset state until Cursor holds the chats again
+            !event.restored
+        }
+        Ok(None) => {
+            notifier.recovered("restore");
+            false
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Auto-restore check failed");
+            reset_active
+        }
+    }
+}
+
+/// Sync from Cursor to local storage, reporting failures. Returns whether
+/// the sync succeeded.
+fn sync_cycle(sync_service: &SyncService, notifier: &mut FailureNotifier) -> bool {
+    match sync_service.sync() {
+        Ok(state) => {
+            tracing::info!(
+                conversations = state.conversation_count,
+                messages = state.message_count,
+                "Sync completed successfully"
+            );
+            notifier.recovered("sync");
+            true
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Sync failed");
+            notifier.failed("sync", "Sync failed", &e.to_string());
+            false
+        }
+    }
+}
+
+/// Periodic maintenance (checkpoint, optimize, purge, backup rotation),
+/// when it is due.
+fn run_due_maintenance(storage_mgr: &StorageManager) {
+    match storage_mgr.maintenance_due() {
+        Ok(true) => match storage_mgr.run_maintenance() {
+            Ok(result) => {
+                tracing::info!(
+                    purged = result.purged_rows,
+                    backup = %result.backup.file_path.display(),
+                    rotated = result.cleanup.deleted_count,
+                    "Maintenance completed"
+                );
+            }
+            Err(e) => tracing::warn!(error = %e, "Maintenance failed"),
+        },
+        Ok(false) => {}
+        Err(e) => tracing::warn!(error = %e, "Failed to check maintenance schedule"),
+    }
+}
+
+/// Free space if storage is over its limit, and report if it stays over.
+pub(super) fn enforce_storage_limit(storage_mgr: &StorageManager, notifier: &mut FailureNotifier) {
+    if let Err(e) = storage_mgr.enforce_storage_limit() {
+        tracing::warn!(error = %e, "Failed to enforce storage limits");
+    }
+
+    match storage_mgr.get_usage_percent() {
+        Ok(percent) if percent >= 100.0 => {
+            tracing::error!(percent, "Storage over limit after cleanup");
+            let body = format!(
+                "Storage is at {percent:.0}% of its limit after removing old backups and exports; \
+                 raise storage.max_size_gb or free space"
+            );
+            notifier.failed("storage_limit", "Storage over limit", &body);
+        }
+        Ok(_) => notifier.recovered("storage_limit"),
+        Err(e) => tracing::warn!(error = %e, "Failed to check storage usage"),
+    }
+}
+
+/// Serve the gRPC interface until interrupted.
+///
+/// # Errors
+/// Returns error if the server cannot be started.
+#[cfg(feature = "grpc")]
+pub fn cmd_grpc(addr: std::net::SocketAddr) -> Result<()> {
+    println!(
+        "{} gRPC server listening on {}",
+        "🔌".bold(),
+        addr.to_string().cyan()
+    );
+    let service = grpc::ChatService::new(|| {
+        load_conversations(&ExtractOptions::default()).map(|(conversations, _)| conversations)
+    });
+    grpc::serve(addr, service)
+}
+
+/// Listen for event stream clients on `addr`, generating the token and
+/// saving it to the config on first use.
+fn start_event_stream(config: &AppConfig, addr: &str) -> Result<EventStream> {
+    let token = if let Some(token) = &config.sync.listen_token {
+        token.clone()
+    } else {
+        let token = generate_token();
+        let mut base = load_base_config()?;
+        base.sync.listen_token = Some(token.clone());
+        let config_path = config_file_path();
+        save_config_to_file(&base, &config_path)?;
+        println!(
+            "  Event stream token saved to {} (sync.listen_token)",
+            config_path.display()
+        );
+        token
+    };
+    EventStream::listen(addr, &token, config.sync.listen_origin.as_deref())
+}
+
+/// Push changes synced after `since` to the event stream; returns the time
+/// to stream from next cycle.
+fn stream_changes(
+    stream: &EventStream,
+    storage_path: &Path,
+    since: chrono::DateTime<chrono::Utc>,
+) -> chrono::DateTime<chrono::Utc> {
+    let now = chrono::Utc::now();
+    let published =
+        LocalStorage::open_read_only(storage_path).and_then(|s| publish_changes(&s, stream, since));
+    stream.heartbeat();
+
+    match published {
+        Ok(count) => {
+            tracing::debug!(
+                events = count,
+                clients = stream.client_count(),
+                "Streamed changes"
+            );
+            now
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to stream changes");
+            since
+        }
+    }
+}
+
+/// Record a newly detected Cursor reset and optionally notify the user.
+fn handle_reset_event(sync_service: &SyncService, event: &ResetEvent, notify: bool) {
+    tracing::warn!(
+        vanished = event.vanished(),
+        restored = event.restored,
+        "Cursor reset detected"
+    );
+
+    if let Err(e) = sync_service.record_reset_event(event) {
+        tracing::warn!(error = %e, "Failed to record reset event");
+    }
+
+    if notify {
+        let body = if event.restored {
+            format!(
+                "{} conversations vanished and were restored",
+                event.vanished()
+            )
+        } else {
+            format!(
+                "{} conversations vanished; run 'cursor-chat restore'",
+                event.vanished()
+            )
+        };
+
+        if let Err(e) = send_notification("Cursor reset detected", &body) {
+            tracing::warn!(error = %e, "Failed to send notification");
+        }
+    }
+}
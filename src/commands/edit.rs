@@ -0,0 +1,464 @@
+//! Commands that change which conversations Cursor or local storage
+//! hold (`restore`, `delete`, `archive`, `prune`, `import`, `merge`).
+
+use std::io::IsTerminal;
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::application::{
+    format_bytes, import_exports, merge, OutputFormat, PruneFilter, RestoreResult, RestoreService,
+};
+use crate::domain::{truncate_chars, AppError, Result};
+use crate::infrastructure::{ensure_writable, is_cursor_running, load_config, LocalStorage};
+
+use super::selection::short_ids;
+
+/// Restore chat history to Cursor.
+///
+/// # Errors
+/// Returns error if storage is read-only or the restore fails.
+pub fn cmd_restore(ids: &[String], force: bool, close_cursor: bool) -> Result<()> {
+    ensure_writable("restore chats into Cursor")?;
+    let config = load_config()?;
+    let restore_service =
+        with_restore_progress(RestoreService::new(config).with_close_cursor(close_cursor));
+
+    println!("{}", "🔄 Checking restore status...".bold());
+    warn_if_cursor_running(close_cursor, "restoring");
+
+    // Check if restore is needed
+    let cursor_empty = restore_service.cursor_is_empty()?;
+    let needs_restore = restore_service.needs_restore()?;
+
+    if !cursor_empty && !needs_restore && !force {
+        println!();
+        println!("  {} Cursor has chats - restore not needed", "ℹ".blue());
+        println!();
+        println!("  Use --force to restore anyway");
+        return Ok(());
+    }
+
+    if cursor_empty {
+        println!("  {} Cursor database is empty", "!".yellow());
+    } else if needs_restore {
+        println!("  {} Cursor appears to have been reset", "!".yellow());
+    }
+
+    println!();
+    println!("{}", "📥 Restoring chats from backup...".bold());
+
+    let result = if ids.is_empty() {
+        restore_service.restore_all()?
+    } else {
+        restore_service.restore_by_ids(ids)?
+    };
+
+    println!();
+    println!("  {} Restore completed!", "✓".green());
+    println!("  Conversations: {}", result.restored_conversations);
+    println!("  Messages:      {}", result.restored_messages);
+    print_restore_problems(&result);
+    println!();
+    println!("  Cursor DB: {}", result.cursor_db_path.display());
+    println!();
+    println!(
+        "{}",
+        "💡 Reinicie o Cursor para ver os chats restaurados".cyan()
+    );
+
+    Ok(())
+}
+
+/// Draw a progress bar on stderr while restoring, when it is a terminal.
+pub(super) fn with_restore_progress(service: RestoreService) -> RestoreService {
+    const WIDTH: usize = 30;
+
+    if !std::io::stderr().is_terminal() {
+        return service;
+    }
+    service.with_progress(|done, total, title| {
+        let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+        eprint!(
+            "\r  [{}{}] {done}/{total} {}\x1b[K",
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            truncate_chars(title, 40)
+        );
+        if done == total {
+            eprintln!();
+        }
+    })
+}
+
+/// Print failed and skipped conversations and where the report is.
+pub(super) fn print_restore_problems(result: &RestoreResult) {
+    if result.failed_conversations > 0 {
+        println!(
+            "  {} Failed:        {}",
+            "⚠".yellow(),
+            result.failed_conversations
+        );
+    }
+    if result.skipped > 0 {
+        println!(
+            "  Skipped:       {} (archived, or IDs not in local storage)",
+            result.skipped
+        );
+    }
+    if let Some(path) = &result.report_path {
+        println!("  Report:        {}", path.display());
+    }
+}
+
+/// Erase a conversation from Cursor after archiving it locally.
+///
+/// # Errors
+/// Returns error if the conversation is not found or Cursor's database cannot
+/// be written.
+pub fn cmd_delete(id: &str, close_cursor: bool) -> Result<()> {
+    ensure_writable("delete chats from Cursor")?;
+    let config = load_config()?;
+    let restore_service = RestoreService::new(config).with_close_cursor(close_cursor);
+
+    println!("{}", "🗑  Deleting chat from Cursor...".bold());
+    warn_if_cursor_running(close_cursor, "deleting");
+
+    let result = restore_service.delete_from_cursor(id)?;
+
+    println!();
+    println!("{}", "✓ Chat removed from Cursor".green().bold());
+    println!("  ID:            {}", result.composer_id);
+    println!("  Keys removed:  {}", result.removed_keys);
+    println!(
+        "  Archived:      {} messages kept in local storage",
+        result.archived_messages
+    );
+    println!();
+    println!(
+        "{}",
+        "💡 Use 'cursor-chat restore -i <ID>' to put it back".cyan()
+    );
+
+    Ok(())
+}
+
+/// Archive a conversation, erasing it from Cursor with `delete`.
+///
+/// # Errors
+/// Returns error if the conversation is not found or a database cannot be
+/// written.
+pub fn cmd_archive(id: &str, delete: bool, close_cursor: bool) -> Result<()> {
+    if delete {
+        ensure_writable("delete chats from Cursor")?;
+    }
+    let restore_service = RestoreService::new(load_config()?).with_close_cursor(close_cursor);
+
+    println!("{}", "📦 Archiving chat...".bold());
+    if delete {
+        warn_if_cursor_running(close_cursor, "deleting");
+    }
+
+    let result = restore_service.archive(id, delete)?;
+
+    println!();
+    println!("{}", "✓ Chat archived".green().bold());
+    println!("  ID:            {}", result.composer_id);
+    if !result.title.is_empty() {
+        println!("  Title:         {}", result.title);
+    }
+    println!(
+        "  Messages:      {} kept in local storage",
+        result.archived_messages
+    );
+    if let Some(keys) = result.removed_keys {
+        println!("  Keys removed:  {keys} (erased from Cursor)");
+        println!();
+        println!(
+            "{}",
+            "💡 Use 'cursor-chat restore -i <ID>' to put it back".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Erase conversations from Cursor's database in bulk (`prune`).
+///
+/// # Errors
+/// Returns error if storage is read-only or Cursor's database cannot be
+/// written.
+pub fn cmd_prune(
+    filter: &PruneFilter,
+    dry_run: bool,
+    close_cursor: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if !dry_run {
+        ensure_writable("prune chats from Cursor")?;
+    }
+    let json = matches!(format, OutputFormat::Json);
+    let restore_service = RestoreService::new(load_config()?).with_close_cursor(close_cursor);
+
+    if !json {
+        println!("{}", "🧹 Pruning Cursor's database...".bold());
+        if !dry_run {
+            warn_if_cursor_running(close_cursor, "pruning");
+        }
+    }
+
+    let result = restore_service.prune(filter, dry_run)?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&result).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!();
+    let ids = short_ids()?;
+    for entry in &result.conversations {
+        let last = entry.last_activity.map_or_else(
+            || "unknown".to_string(),
+            |dt| dt.format("%Y-%m-%d").to_string(),
+        );
+        println!(
+            "  {}  {}  {:>9}  {}",
+            ids.get(&entry.composer_id),
+            last.dimmed(),
+            format_bytes(entry.bytes),
+            truncate_chars(&entry.title, 50)
+        );
+    }
+    for id in &result.not_synced {
+        println!("  {} {id}  kept: not in local storage", "⚠".yellow());
+    }
+
+    let bytes: u64 = result.conversations.iter().map(|e| e.bytes).sum();
+    let keys: usize = result.conversations.iter().map(|e| e.keys).sum();
+    println!();
+    if result.conversations.is_empty() {
+        println!("{}", "Nothing to prune.".green());
+    } else if dry_run {
+        println!(
+            "Would erase {} conversations ({} keys, {}) from Cursor",
+            result.conversations.len(),
+            keys,
+            format_bytes(bytes)
+        );
+        println!("{}", "💡 Run again without --dry-run to erase them".cyan());
+    } else {
+        println!(
+            "{}",
+            format!(
+                "✓ Erased {} conversations ({} keys) from Cursor",
+                result.conversations.len(),
+                result.removed_keys
+            )
+            .green()
+            .bold()
+        );
+        println!(
+            "  Database:      {} → {}",
+            format_bytes(result.size_before),
+            format_bytes(result.size_after)
+        );
+        println!(
+            "{}",
+            "💡 They stay in local storage; 'cursor-chat restore -i <ID>' puts one back".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Import exported conversations into local storage.
+///
+/// # Errors
+/// Returns error if the files cannot be read or storage cannot be written.
+pub fn cmd_import(path: &Path, format: OutputFormat) -> Result<()> {
+    let json = matches!(format, OutputFormat::Json);
+    if !json {
+        println!("{}", format!("📥 Importing {}...", path.display()).bold());
+    }
+
+    let result = import_exports(&load_config()?, path)?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&result).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    for failure in &result.failed {
+        println!(
+            "  {} {}  skipped: {}",
+            "⚠".yellow(),
+            failure.path.display(),
+            failure.error
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        format!(
+            "✓ Imported {} files: {} new, {} updated, {} unchanged conversations",
+            result.files, result.created, result.updated, result.unchanged
+        )
+        .green()
+        .bold()
+    );
+    println!("  Messages added: {}", result.messages);
+    if result.created + result.updated > 0 {
+        println!(
+            "{}",
+            "💡 'cursor-chat restore -i <ID>' puts an imported conversation into Cursor".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Merge conversations into one, optionally writing it back to Cursor.
+///
+/// # Errors
+/// Returns error if the conversations cannot be merged or restored.
+pub fn cmd_merge(
+    ids: &[String],
+    into: Option<&str>,
+    restore: bool,
+    close_cursor: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if restore {
+        ensure_writable("restore the merged chat to Cursor")?;
+    }
+    let json = matches!(format, OutputFormat::Json);
+    let config = load_config()?;
+
+    if !json {
+        println!("{}", "🔗 Merging conversations...".bold());
+    }
+    let result = merge(&config, ids, into)?;
+
+    let restored = if restore {
+        if !json {
+            warn_if_cursor_running(close_cursor, "restoring");
+        }
+        let restore_result = RestoreService::new(config)
+            .with_close_cursor(close_cursor)
+            .restore_by_ids(std::slice::from_ref(&result.composer_id))?;
+        Some(restore_result.restored_conversations > 0)
+    } else {
+        None
+    };
+
+    if json {
+        let mut output = serde_json::to_value(&result).map_err(AppError::json_parse)?;
+        if let Some(restored) = restored {
+            output["restored"] = serde_json::Value::Bool(restored);
+        }
+        let output = serde_json::to_string_pretty(&output).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    let verb = if result.created {
+        "Created"
+    } else {
+        "Merged into"
+    };
+    println!();
+    println!(
+        "{}",
+        format!("✓ {verb} {} ({})", result.title, result.composer_id)
+            .green()
+            .bold()
+    );
+    println!("  Conversations: {}", result.sources.len());
+    println!("  Messages:      {}", result.messages);
+    println!("  Duplicates:    {}", result.duplicates);
+    match restored {
+        Some(true) => println!(
+            "  {}",
+            "✓ Written to Cursor; restart it to see the merge".green()
+        ),
+        Some(false) => println!(
+            "  {} Not written to Cursor (see the restore report)",
+            "⚠".yellow()
+        ),
+        None => println!(
+            "{}",
+            "💡 --restore writes it to Cursor; 'cursor-chat archive --delete <ID>' removes a source"
+                .cyan()
+        ),
+    }
+
+    Ok(())
+}
+
+/// List archived conversations (`archive --list`).
+///
+/// # Errors
+/// Returns error if storage cannot be read.
+pub fn cmd_archive_list(format: OutputFormat) -> Result<()> {
+    let storage_path = load_config()?.storage_db_path();
+    let archived = if storage_path.exists() {
+        LocalStorage::open_read_only(&storage_path)?.archived()?
+    } else {
+        Vec::new()
+    };
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&archived).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+    if archived.is_empty() {
+        println!("{}", "No archived conversations.".yellow());
+        return Ok(());
+    }
+
+    let ids = short_ids()?;
+    for entry in &archived {
+        let place = if entry.removed_from_cursor {
+            "local only".yellow()
+        } else {
+            "in Cursor".normal()
+        };
+        println!(
+            "  {}  {}  {}  {}",
+            ids.get(&entry.composer_id),
+            entry
+                .archived_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .dimmed(),
+            truncate_chars(&entry.title, 50),
+            place
+        );
+    }
+
+    Ok(())
+}
+
+/// Tell the user up front that a running Cursor blocks (or will be closed
+/// for) `action`, such as `restoring`.
+pub(super) fn warn_if_cursor_running(close_cursor: bool, action: &str) {
+    if !is_cursor_running() {
+        return;
+    }
+
+    if close_cursor {
+        println!(
+            "  {} Cursor is running and will be closed first",
+            "⚠".yellow()
+        );
+    } else {
+        println!(
+            "  {} Cursor is running - close it or pass {} before {action}",
+            "⚠".yellow(),
+            "--close-cursor".cyan()
+        );
+    }
+}
@@ -0,0 +1,540 @@
+//! Writing conversations to files (`export`, `export-all`, `export-rag`),
+//! also through formatter plugins.
+
+use std::io::Write;
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::application::{
+    chunk_conversation, extract_in_batches, format_conversation_markdown,
+    format_conversations_json, format_conversations_table, split_conversation, training_example,
+    write_conversation_markdown, write_conversations, write_jsonl, ChunkOptions, ExportManifest,
+    ExtractOptions, JsonArrayWriter, JsonFilter, OutputFormat, SplitLimit, TrainingOptions,
+};
+use crate::domain::{AppError, Conversation, FormatterPlugin, Result, ShortIds};
+use crate::infrastructure::{
+    global_state_db_path, load_config, run_formatter_plugin, EncryptedWriter, LocalStorage,
+    Recipient,
+};
+
+use super::browse::{conversation_to_show, output_writer, write_filtered};
+use super::selection::{
+    apply_git, apply_titles, conversations_to_export, export_path_map, load_conversations,
+    short_ids, stored_git, stored_titles, Selection,
+};
+
+/// Where an export is written: a file or stdout, as is or encrypted.
+enum ExportWriter {
+    Plain(Box<dyn Write>),
+    Encrypted(std::io::BufWriter<EncryptedWriter>),
+}
+
+impl Write for ExportWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            Self::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            Self::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+impl ExportWriter {
+    /// Flush everything and, when encrypting, wait for the output.
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Plain(mut writer) => writer.flush().map_err(write_error),
+            Self::Encrypted(writer) => writer
+                .into_inner()
+                .map_err(|e| write_error(e.into_error()))?
+                .finish(),
+        }
+    }
+}
+
+/// Writer of an export to `path` (stdout if `None`), encrypted to
+/// `encrypt` if set.
+fn export_writer(path: Option<&str>, encrypt: Option<&Recipient>) -> Result<ExportWriter> {
+    match encrypt {
+        Some(recipient) => Ok(ExportWriter::Encrypted(std::io::BufWriter::new(
+            recipient.encrypt_to(path.map(Path::new))?,
+        ))),
+        None => output_writer(path).map(ExportWriter::Plain),
+    }
+}
+
+/// `path` with the extension of files encrypted to `encrypt`, if set.
+fn encrypted_output(path: &str, encrypt: Option<&Recipient>) -> String {
+    encrypt.map_or_else(|| path.to_string(), |r| r.encrypted_path(path))
+}
+
+/// Export conversations to file or stdout.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the output cannot be
+/// written.
+pub fn cmd_export(
+    output_path: Option<&str>,
+    selection: Selection<'_>,
+    format: OutputFormat,
+    split: Option<SplitLimit>,
+    filter: Option<&JsonFilter>,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    if filter.is_some() && split.is_some() {
+        return Err(AppError::Config {
+            message: "--filter cannot be combined with --split-tokens or --split-bytes".into(),
+        });
+    }
+    // A filter needs the whole document, so it cannot work batch by batch
+    if let (None, None, None, None, None, Some(budget)) = (
+        selection.conversation_id,
+        selection.workspace,
+        selection.tag,
+        split,
+        filter,
+        load_config()?.memory_budget_bytes(),
+    ) {
+        return export_in_batches(output_path, selection, format, budget, encrypt);
+    }
+
+    let (conversations, stats) = conversations_to_export(selection)?;
+
+    let ids = short_ids()?;
+    if let (Some(limit), Some(path)) = (split, output_path) {
+        return export_split(&conversations, &ids, format, limit, path, encrypt);
+    }
+
+    let output_path = output_path.map(|path| encrypted_output(path, encrypt));
+    let mut writer = export_writer(output_path.as_deref(), encrypt)?;
+    if let Some(filter) = filter {
+        write_filtered(&mut writer, &conversations, filter)?;
+    } else {
+        write_conversations(&mut writer, &conversations, format, &ids)
+            .and_then(|()| {
+                if output_path.is_none() {
+                    writeln!(writer)?;
+                }
+                Ok(())
+            })
+            .map_err(|e| AppError::io("Failed to write export", e))?;
+    }
+    writer.finish()?;
+
+    if let Some(path) = output_path {
+        println!(
+            "{} Exported {} conversations to {}",
+            "✓".green().bold(),
+            stats.conversation_count,
+            path
+        );
+    }
+
+    Ok(())
+}
+
+/// Export every conversation batch by batch, within the memory budget.
+fn export_in_batches(
+    output_path: Option<&str>,
+    selection: Selection<'_>,
+    format: OutputFormat,
+    budget: usize,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    let options = selection.extract_options();
+    let db_path = global_state_db_path()?;
+    let titles = stored_titles()?;
+    let git = stored_git()?;
+    let path_map = export_path_map()?;
+    let ids = short_ids()?;
+    let output_path = output_path.map(|path| encrypted_output(path, encrypt));
+    let mut writer = export_writer(output_path.as_deref(), encrypt)?;
+    let prepare = |batch: &mut Vec<Conversation>| {
+        apply_titles(batch, &titles);
+        apply_git(batch, &git);
+        for conv in batch {
+            path_map.apply_to_conversation(conv);
+        }
+    };
+
+    let stats = if matches!(format, OutputFormat::Json) {
+        let mut array = JsonArrayWriter::new(&mut writer).map_err(write_error)?;
+        let stats = extract_in_batches(&db_path, &options, budget, |mut batch| {
+            prepare(&mut batch.conversations);
+            batch
+                .conversations
+                .iter()
+                .try_for_each(|conv| array.push(conv))
+                .map_err(write_error)
+        })?;
+        array.finish().map_err(write_error)?;
+        stats
+    } else {
+        extract_in_batches(&db_path, &options, budget, |mut batch| {
+            prepare(&mut batch.conversations);
+            write_conversations(&mut writer, &batch.conversations, format, &ids)
+                .map_err(write_error)
+        })?
+    };
+
+    if output_path.is_none() {
+        writeln!(writer).map_err(write_error)?;
+    }
+    writer.finish()?;
+
+    if let Some(path) = output_path {
+        println!(
+            "{} Exported {} conversations to {}",
+            "✓".green().bold(),
+            stats.conversation_count,
+            path
+        );
+    }
+
+    Ok(())
+}
+
+pub(super) fn write_error(e: std::io::Error) -> AppError {
+    AppError::io("Failed to write export", e)
+}
+
+/// Write each conversation as sequentially numbered part files.
+///
+/// `chat.md` becomes `chat.part01.md`, `chat.part02.md`, ...
+fn export_split(
+    conversations: &[Conversation],
+    ids: &ShortIds,
+    format: OutputFormat,
+    limit: SplitLimit,
+    output_path: &str,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    let render = |conv: &Conversation| match format {
+        OutputFormat::Markdown => format_conversation_markdown(conv, ids),
+        OutputFormat::Json => {
+            format_conversations_json(std::slice::from_ref(conv)).unwrap_or_default()
+        }
+        OutputFormat::Table => format_conversations_table(std::slice::from_ref(conv), ids),
+    };
+
+    let parts: Vec<Conversation> = conversations
+        .iter()
+        .flat_map(|conv| split_conversation(conv, limit, render))
+        .collect();
+
+    let path = std::path::Path::new(output_path);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("export");
+    let extension = path.extension().and_then(|s| s.to_str());
+    let width = parts.len().to_string().len().max(2);
+
+    for (i, part) in parts.iter().enumerate() {
+        let suffix = extension.map_or_else(String::new, |ext| format!(".{ext}"));
+        let name = format!("{stem}.part{:0width$}{suffix}", i + 1);
+        let part_path = path.with_file_name(name);
+        if let Some(recipient) = encrypt {
+            let part_path = recipient.encrypted_path(&part_path.to_string_lossy());
+            let mut writer = export_writer(Some(&part_path), encrypt)?;
+            writer
+                .write_all(render(part).as_bytes())
+                .map_err(write_error)?;
+            writer.finish()?;
+        } else {
+            std::fs::write(&part_path, render(part))
+                .map_err(|e| AppError::io(format!("Failed to write {}", part_path.display()), e))?;
+        }
+    }
+
+    println!(
+        "{} Exported {} conversations as {} parts next to {}",
+        "✓".green().bold(),
+        conversations.len(),
+        parts.len(),
+        output_path
+    );
+
+    Ok(())
+}
+
+/// Export one fine-tuning example per conversation as JSONL.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the output cannot be
+/// written.
+pub fn cmd_export_training(
+    output: Option<&str>,
+    selection: Selection<'_>,
+    options: TrainingOptions,
+    filter: Option<&JsonFilter>,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    if filter.is_some() {
+        return Err(AppError::Config {
+            message: "--filter cannot be combined with --format jsonl".into(),
+        });
+    }
+    let (conversations, _) = conversations_to_export(selection)?;
+
+    let output = output.map(|path| encrypted_output(path, encrypt));
+    let mut writer = export_writer(output.as_deref(), encrypt)?;
+    let examples = conversations
+        .iter()
+        .filter_map(|conv| training_example(conv, options));
+    let count = write_jsonl(&mut writer, examples)
+        .map_err(|e| AppError::io("Failed to write dataset", e))?;
+    writer.finish()?;
+
+    if let Some(path) = output {
+        eprintln!(
+            "{} Exported {} examples from {} conversations to {}",
+            "✓".green(),
+            count,
+            conversations.len(),
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Export all conversations to separate files with auto-generated names.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or a file cannot be
+/// written.
+pub fn cmd_export_all(
+    dir: &str,
+    limit: usize,
+    selection: Selection<'_>,
+    format: OutputFormat,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    let ids = short_ids()?;
+    let ext = format.extension();
+    export_all_with(dir, limit, selection, ext, encrypt, |mut writer, conv| {
+        match format {
+            OutputFormat::Markdown => write_conversation_markdown(&mut writer, conv, &ids),
+            OutputFormat::Json | OutputFormat::Table => {
+                write_conversations(&mut writer, std::slice::from_ref(conv), format, &ids)
+            }
+        }
+        .map_err(write_error)
+    })
+}
+
+/// Write each conversation to `<dir>/<name>.<ext>` with `render`, keeping
+/// the export manifest up to date.
+fn export_all_with(
+    dir: &str,
+    limit: usize,
+    selection: Selection<'_>,
+    ext: &str,
+    encrypt: Option<&Recipient>,
+    render: impl Fn(&mut dyn Write, &Conversation) -> Result<()>,
+) -> Result<()> {
+    let (mut conversations, _) = load_conversations(&selection.extract_options())?;
+    selection.retain(&mut conversations)?;
+
+    if limit > 0 {
+        conversations.truncate(limit);
+    }
+    let path_map = export_path_map()?;
+    for conv in &mut conversations {
+        path_map.apply_to_conversation(conv);
+    }
+
+    // Create output directory
+    std::fs::create_dir_all(dir)
+        .map_err(|e| AppError::io(format!("Failed to create directory {dir}"), e))?;
+
+    let mut manifest = ExportManifest::load(std::path::Path::new(dir))?;
+    for conv in &conversations {
+        let filename = format!("{}/{}.{}", dir, conv.filename(), ext);
+        let filename = encrypted_output(&filename, encrypt);
+
+        let mut writer = export_writer(Some(&filename), encrypt)?;
+        render(&mut writer, conv)?;
+        writer.finish()?;
+
+        manifest.record(
+            &conv.composer_id,
+            &conv.title,
+            std::path::Path::new(&filename),
+        )?;
+        println!("{} {} → {}", "✓".green(), conv.title.cyan(), filename);
+    }
+    manifest.save()?;
+
+    println!(
+        "\n{} Exported {} conversations to {}/",
+        "📁".bold(),
+        conversations.len(),
+        dir
+    );
+
+    Ok(())
+}
+
+/// The `[formatters.<name>]` plugin; `format_error` explains what else
+/// `--format` accepts when there is none.
+///
+/// # Errors
+/// Returns error if there is no such plugin.
+pub fn formatter_plugin(name: &str, format_error: String) -> Result<FormatterPlugin> {
+    let mut config = load_config()?;
+    config.formatters.remove(name).ok_or_else(|| {
+        let plugins: Vec<&str> = config.formatters.keys().map(String::as_str).collect();
+        let message = if plugins.is_empty() {
+            format_error
+        } else {
+            format!(
+                "{format_error}, or a formatter plugin: {}",
+                plugins.join(", ")
+            )
+        };
+        AppError::Config { message }
+    })
+}
+
+/// Render `conversations` through the formatter plugin `name` into `output`.
+fn render_with_plugin(
+    name: &str,
+    plugin: &FormatterPlugin,
+    output: &mut dyn Write,
+    conversations: &[Conversation],
+) -> Result<()> {
+    let input = format_conversations_json(conversations).map_err(AppError::json_parse)?;
+    run_formatter_plugin(name, plugin, input.into_bytes(), output)
+}
+
+/// `show` through a formatter plugin.
+///
+/// # Errors
+/// Returns error if the conversation is not found or the plugin fails.
+pub fn cmd_show_with_plugin(
+    name: &str,
+    plugin: &FormatterPlugin,
+    conversation_id: &str,
+    include_empty: bool,
+    last: Option<usize>,
+) -> Result<()> {
+    let conv = conversation_to_show(conversation_id, include_empty, last)?;
+    render_with_plugin(
+        name,
+        plugin,
+        &mut std::io::stdout().lock(),
+        std::slice::from_ref(&conv),
+    )
+}
+
+/// `export` through a formatter plugin.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the plugin fails.
+pub fn cmd_export_with_plugin(
+    name: &str,
+    plugin: &FormatterPlugin,
+    output: Option<&str>,
+    selection: Selection<'_>,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    let (conversations, stats) = conversations_to_export(selection)?;
+    let output = output.map(|path| encrypted_output(path, encrypt));
+    let mut writer = export_writer(output.as_deref(), encrypt)?;
+    render_with_plugin(name, plugin, &mut writer, &conversations)?;
+    writer.finish()?;
+    if let Some(path) = output {
+        println!(
+            "{} Exported {} conversations to {}",
+            "✓".green().bold(),
+            stats.conversation_count,
+            path
+        );
+    }
+    Ok(())
+}
+
+/// `export-all` through a formatter plugin.
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the plugin fails.
+pub fn cmd_export_all_with_plugin(
+    name: &str,
+    plugin: &FormatterPlugin,
+    dir: &str,
+    limit: usize,
+    selection: Selection<'_>,
+    encrypt: Option<&Recipient>,
+) -> Result<()> {
+    export_all_with(
+        dir,
+        limit,
+        selection,
+        &plugin.extension,
+        encrypt,
+        |writer, conv| render_with_plugin(name, plugin, writer, std::slice::from_ref(conv)),
+    )
+}
+
+/// Export chunked JSONL for retrieval pipelines (`export-rag`).
+///
+/// # Errors
+/// Returns error if conversations cannot be extracted or the output cannot be
+/// written.
+pub fn cmd_export_rag(
+    output: Option<&str>,
+    options: ChunkOptions,
+    min_messages: usize,
+) -> Result<()> {
+    let options = options.validate()?;
+    let extract = ExtractOptions {
+        min_messages,
+        ..Default::default()
+    };
+    let (mut conversations, _) = load_conversations(&extract)?;
+
+    // Workspaces are only known for conversations synced to local storage
+    let storage_path = load_config()?.storage_db_path();
+    let mut workspaces = if storage_path.exists() {
+        LocalStorage::open_read_only(&storage_path)?.conversation_workspaces()?
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    let path_map = export_path_map()?;
+    for conv in &mut conversations {
+        path_map.apply_to_conversation(conv);
+    }
+    for workspace in workspaces.values_mut() {
+        *workspace = path_map.apply(workspace);
+    }
+
+    let mut writer = output_writer(output)?;
+    let chunks = conversations.iter().flat_map(|conv| {
+        let workspace = workspaces.get(&conv.composer_id).map(String::as_str);
+        chunk_conversation(conv, workspace, options)
+    });
+    let count = write_jsonl(&mut writer, chunks)
+        .and_then(|count| writer.flush().map(|()| count))
+        .map_err(|e| AppError::io("Failed to write chunks", e))?;
+
+    if let Some(path) = output {
+        eprintln!(
+            "{} Exported {} chunks from {} conversations to {}",
+            "✓".green(),
+            count,
+            conversations.len(),
+            path
+        );
+    }
+
+    Ok(())
+}
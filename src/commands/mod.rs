@@ -0,0 +1,49 @@
+//! The `cursor-chat` commands.
+//!
+//! Each `cmd_*` function runs one subcommand with its parsed arguments,
+//! printing to the terminal; `main.rs` only parses the command line and
+//! dispatches here.
+
+pub mod analysis;
+pub mod browse;
+pub mod daemon;
+pub mod edit;
+pub mod export;
+pub mod reset;
+pub mod review;
+pub mod selection;
+pub mod setup;
+pub mod storage;
+pub mod sync;
+
+pub use analysis::{
+    cmd_changes, cmd_correlate, cmd_history, cmd_index_build, cmd_index_search, cmd_query,
+    cmd_search, cmd_similar, cmd_stats, cmd_stats_modes, cmd_stats_sessions, cmd_stats_top,
+    cmd_summarize, cmd_tag, cmd_tag_list, cmd_timeline, cmd_titles_regenerate, cmd_todos,
+};
+pub use browse::{cmd_list, cmd_open, cmd_paths, cmd_quick, cmd_show};
+pub use daemon::cmd_daemon;
+#[cfg(feature = "grpc")]
+pub use daemon::cmd_grpc;
+pub use edit::{
+    cmd_archive, cmd_archive_list, cmd_delete, cmd_import, cmd_merge, cmd_prune, cmd_restore,
+};
+pub use export::{
+    cmd_export, cmd_export_all, cmd_export_all_with_plugin, cmd_export_rag, cmd_export_training,
+    cmd_export_with_plugin, cmd_show_with_plugin, formatter_plugin,
+};
+pub use reset::{cmd_reset, ResetOptions};
+pub use review::{cmd_commands, cmd_export_script, cmd_replay};
+pub use selection::{DateRange, Selection};
+pub use setup::{
+    cmd_bench, cmd_config_edit, cmd_config_get, cmd_config_set, cmd_doctor, cmd_uninstall,
+    BenchOptions,
+};
+pub use storage::{
+    cmd_storage_backups, cmd_storage_cleanup, cmd_storage_config, cmd_storage_doctor,
+    cmd_storage_export_db, cmd_storage_stats, cmd_storage_vacuum, cmd_storage_workspaces,
+};
+pub use sync::{
+    cmd_sync_diff, cmd_sync_logs, cmd_sync_now, cmd_sync_restore, cmd_sync_start, cmd_sync_status,
+    cmd_sync_stop, cmd_sync_uninstall,
+};
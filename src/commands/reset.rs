@@ -0,0 +1,371 @@
+//! Cursor reset (`reset`), with a backup before and a restore after.
+
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::application::{audit, RestoreService, StorageManager, SyncService};
+use crate::domain::{AppConfig, AppError, OperationKind, Result};
+use crate::infrastructure::{
+    ensure_writable, global_state_db_path, load_config, CursorReset, ResetSteps,
+};
+
+use super::edit::{print_restore_problems, with_restore_progress};
+
+/// How `reset` runs.
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ResetOptions {
+    /// Steps to run.
+    pub steps: ResetSteps,
+    /// Skip the sync and storage backup taken first.
+    pub no_backup: bool,
+    /// Skip restoring chats afterwards.
+    pub no_restore: bool,
+    /// Wait for Cursor to be reopened and closed, then restore chats.
+    pub restore: bool,
+    /// How long to wait for each Cursor launch or close with `restore`.
+    pub wait_timeout: Duration,
+    /// Skip the steps that need root.
+    pub no_sudo: bool,
+    /// Only print what would be done.
+    pub dry_run: bool,
+}
+
+/// Complete Cursor reset with backup and restore.
+///
+/// # Errors
+/// Returns error if storage is read-only, or the backup or dry run fails.
+pub fn cmd_reset(args: &ResetOptions) -> Result<()> {
+    let config = load_config()?;
+    let steps = args.steps;
+    let mut reset = CursorReset::new(steps.appimage);
+    if args.no_sudo {
+        reset = reset.without_sudo();
+    }
+
+    if args.dry_run {
+        return print_reset_plan(&reset, steps, args, &config);
+    }
+    ensure_writable("reset Cursor")?;
+
+    audit::recorded(
+        &config.clone(),
+        OperationKind::Reset,
+        || run_reset(config, &reset, steps, args),
+        |()| Some((0, steps.names().join(", "))),
+    )
+}
+
+/// Run the selected reset steps, restoring chats afterwards when asked.
+fn run_reset(
+    config: AppConfig,
+    reset: &CursorReset,
+    steps: ResetSteps,
+    args: &ResetOptions,
+) -> Result<()> {
+    let mut step = 0;
+
+    println!("{}", "🔄 CURSOR RESET".bold());
+    println!("{}", "═══════════════════════════════════════════".cyan());
+    println!();
+
+    // Sync and snapshot storage so no chat history is lost
+    if steps.is_destructive() && !args.no_backup {
+        step += 1;
+        println!("{}", format!("📦 Step {step}: Backing up chats...").bold());
+        reset_backup(&config)?;
+        println!();
+    }
+
+    // Kill Cursor processes
+    if steps.kill {
+        step += 1;
+        println!("{}", format!("🔪 Step {step}: Stopping Cursor...").bold());
+        match reset.kill_cursor() {
+            Ok(_) => println!("  {} Cursor processes terminated", "✓".green()),
+            Err(e) => println!("  {} Could not kill Cursor: {}", "⚠".yellow(), e),
+        }
+        println!();
+    }
+
+    // Clean config directories
+    if steps.config {
+        step += 1;
+        println!(
+            "{}",
+            format!("🗑️  Step {step}: Cleaning configuration...").bold()
+        );
+        match reset.clean_config_dirs() {
+            Ok(stats) => {
+                println!(
+                    "  {} Removed {} directories",
+                    "✓".green(),
+                    stats.dirs_removed
+                );
+                for path in &stats.paths_cleaned {
+                    println!("    {} {}", "→".dimmed(), path);
+                }
+            }
+            Err(e) => println!("  {} Failed: {}", "✗".red(), e),
+        }
+        println!();
+    }
+
+    // Clean desktop entries
+    if steps.desktop {
+        step += 1;
+        println!(
+            "{}",
+            format!("🖥️  Step {step}: Cleaning desktop entries...").bold()
+        );
+        match reset.clean_desktop_entries() {
+            Ok(stats) => {
+                println!("  {} Removed {} files", "✓".green(), stats.files_removed);
+            }
+            Err(e) => println!("  {} Failed: {}", "✗".red(), e),
+        }
+        println!();
+    }
+
+    // Clean AppImages
+    if steps.appimage {
+        step += 1;
+        println!(
+            "{}",
+            format!("📦 Step {step}: Cleaning AppImages...").bold()
+        );
+        match reset.clean_appimages() {
+            Ok(stats) => {
+                if stats.files_removed > 0 {
+                    println!(
+                        "  {} Removed {} AppImage files",
+                        "✓".green(),
+                        stats.files_removed
+                    );
+                } else {
+                    println!("  {} No AppImages found", "ℹ".blue());
+                }
+            }
+            Err(e) => println!("  {} Failed: {}", "✗".red(), e),
+        }
+        println!();
+    }
+
+    // Reset machine ID
+    if steps.machine_id {
+        step += 1;
+        println!(
+            "{}",
+            format!("🔑 Step {step}: Resetting machine ID...").bold()
+        );
+        reset_machine_id_step(reset);
+        println!();
+    }
+
+    // Restore chats (only needed when Cursor's data was wiped)
+    if steps.config && !args.no_restore {
+        reset_restore_steps(config, args, &mut step);
+    }
+
+    // Done
+    println!("{}", "═══════════════════════════════════════════".cyan());
+    println!("{}", "✨ RESET COMPLETE!".green().bold());
+    println!();
+    println!("  {} Reopen Cursor to start fresh", "→".cyan());
+    if steps.is_destructive() {
+        println!("  {} Your chats have been preserved", "→".cyan());
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Print what a reset would do without touching anything.
+fn print_reset_plan(
+    reset: &CursorReset,
+    steps: ResetSteps,
+    args: &ResetOptions,
+    config: &AppConfig,
+) -> Result<()> {
+    let plan = reset.plan(steps)?;
+
+    println!("{}", "🔍 CURSOR RESET (dry run)".bold());
+    println!();
+
+    if steps.is_destructive() && !args.no_backup {
+        println!("{}", "Backup:".bold());
+        println!(
+            "  {} sync Cursor → {}",
+            "→".cyan(),
+            config.storage_db_path().display()
+        );
+        println!(
+            "  {} snapshot → {}",
+            "→".cyan(),
+            config.backups_dir().display()
+        );
+        println!();
+    }
+
+    println!("{}", "Commands:".bold());
+    for command in &plan.commands {
+        println!("  {} {}", "$".dimmed(), command);
+    }
+    println!();
+
+    println!("{}", "Directories to remove:".bold());
+    for dir in &plan.dirs {
+        println!("  {} {}", "✗".red(), dir.display());
+    }
+    println!();
+
+    println!("{}", "Files to remove:".bold());
+    for file in &plan.files {
+        println!("  {} {}", "✗".red(), file.display());
+    }
+    println!();
+
+    if steps.machine_id && !reset.uses_sudo() {
+        println!("  {} Machine ID reset skipped (--no-sudo)", "ℹ".blue());
+    }
+    if steps.config && args.restore {
+        println!(
+            "  {} Would wait for Cursor to recreate {}, then restore chats",
+            "ℹ".blue(),
+            global_state_db_path()?.display()
+        );
+    } else if steps.config && !args.no_restore {
+        println!("  {} Chats would be restored afterwards", "ℹ".blue());
+    }
+    println!("{}", "Nothing was changed.".dimmed());
+
+    Ok(())
+}
+
+/// Reset the machine ID unless sudo is disabled.
+fn reset_machine_id_step(reset: &CursorReset) {
+    if reset.uses_sudo() {
+        println!("  {} This requires sudo password", "ℹ".blue());
+        match reset.reset_machine_id() {
+            Ok(result) => {
+                println!(
+                    "  {} New machine ID: {}",
+                    "✓".green(),
+                    &result.new_id[..8.min(result.new_id.len())]
+                );
+            }
+            Err(e) => {
+                println!("  {} Failed: {}", "✗".red(), e);
+                println!("  {} You may need to run with sudo", "💡".yellow());
+            }
+        }
+    } else {
+        println!("  {} Skipped (--no-sudo)", "ℹ".blue());
+    }
+}
+
+/// Sync chats to local storage and snapshot it before a reset.
+///
+/// Fails if either step fails, so the reset never runs without a backup.
+fn reset_backup(config: &AppConfig) -> Result<()> {
+    let sync_service = SyncService::new(config.clone())?;
+    let state = sync_service.sync().map_err(|e| AppError::Sync {
+        message: format!("Backup sync failed, aborting reset: {e}"),
+    })?;
+    println!(
+        "  {} Sync complete: {} chats, {} messages",
+        "✓".green(),
+        state.conversation_count,
+        state.message_count
+    );
+
+    let backup = StorageManager::new(config.clone()).create_backup()?;
+    println!(
+        "  {} Storage backup: {}",
+        "✓".green(),
+        backup.file_path.display()
+    );
+
+    Ok(())
+}
+
+/// Restore chats after a reset, waiting for Cursor's first launch with --restore.
+fn reset_restore_steps(config: AppConfig, args: &ResetOptions, step: &mut usize) {
+    if args.restore {
+        *step += 1;
+        println!(
+            "{}",
+            format!("⏳ Step {step}: Waiting for Cursor...").bold()
+        );
+        let ready = reset_wait_for_cursor(args.wait_timeout);
+        println!();
+        if !ready {
+            return;
+        }
+    }
+
+    *step += 1;
+    println!("{}", format!("📥 Step {step}: Restoring chats...").bold());
+    reset_restore(config);
+    println!();
+}
+
+/// Guide the user through Cursor's first launch so its database exists before restoring.
+///
+/// Returns false (after printing a hint) if Cursor did not launch or close in time.
+fn reset_wait_for_cursor(timeout: Duration) -> bool {
+    let poll = Duration::from_secs(2);
+
+    println!(
+        "  {} Open Cursor now so it recreates its database",
+        "→".cyan()
+    );
+    match RestoreService::wait_for_cursor_db(timeout, poll) {
+        Ok(path) => println!("  {} Database created: {}", "✓".green(), path.display()),
+        Err(e) => {
+            println!("  {} {}", "⚠".yellow(), e);
+            println!(
+                "  {} Run 'cursor-chat restore' after opening Cursor",
+                "💡".blue()
+            );
+            return false;
+        }
+    }
+
+    println!("  {} Close Cursor to continue with the restore", "→".cyan());
+    match RestoreService::wait_for_cursor_exit(timeout, poll) {
+        Ok(()) => {
+            println!("  {} Cursor closed", "✓".green());
+            true
+        }
+        Err(e) => {
+            println!("  {} {}", "⚠".yellow(), e);
+            println!(
+                "  {} Close Cursor and run 'cursor-chat restore'",
+                "💡".blue()
+            );
+            false
+        }
+    }
+}
+
+/// Restore chats from local storage after a reset.
+fn reset_restore(config: AppConfig) {
+    let restore_service = with_restore_progress(RestoreService::new(config));
+    match restore_service.restore_all() {
+        Ok(result) => {
+            println!(
+                "  {} Restored {} chats, {} messages",
+                "✓".green(),
+                result.restored_conversations,
+                result.restored_messages
+            );
+            print_restore_problems(&result);
+        }
+        Err(e) => {
+            println!("  {} Restore failed: {}", "⚠".yellow(), e);
+            println!("  {} Run 'cursor-chat sync restore' later", "💡".blue());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Reviewing what an agent did in one conversation (`terminal`, `replay`,
+//! `export-script`).
+
+use std::io::Write;
+
+use colored::Colorize;
+
+use crate::application::{
+    exchanges, find_conversation, format_exchange, format_shell_script,
+    format_terminal_commands_markdown, pause_between, ExtractOptions, OutputFormat,
+};
+use crate::domain::{AppError, Result};
+
+use super::browse::output_writer;
+use super::export::write_error;
+use super::selection::{export_path_map, load_conversations};
+
+/// List the terminal commands the agent ran in a conversation.
+///
+/// # Errors
+/// Returns error if the conversation is not found or cannot be read.
+pub fn cmd_commands(id: &str, format: OutputFormat) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let conv = find_conversation(&conversations, id)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let commands: Vec<_> = conv.terminal_commands().collect();
+        let output = serde_json::to_string_pretty(&commands).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else if conv.terminal_commands().next().is_none() {
+        println!("{}", "No terminal commands found.".yellow());
+    } else {
+        println!("{}", format_terminal_commands_markdown(conv));
+    }
+
+    Ok(())
+}
+
+/// Print a conversation one exchange at a time.
+///
+/// Waits for a key between exchanges (q or Esc stops), or with `timed`
+/// for the original gap divided by `speed`. Output that is not a terminal
+/// is printed without waiting.
+///
+/// # Errors
+/// Returns error if the conversation is not found or cannot be read.
+pub fn cmd_replay(id: &str, timed: bool, speed: f64) -> Result<()> {
+    use dialoguer::console::{Key, Term};
+
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(AppError::Config {
+            message: "--speed must be greater than 0".into(),
+        });
+    }
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let conv = find_conversation(&conversations, id)?;
+    let exchanges = exchanges(conv);
+
+    let term = Term::stdout();
+    let interactive = !timed && term.is_term();
+    let key_error = |e| AppError::io("Failed to read key", e);
+    let title = if conv.title.is_empty() {
+        &conv.composer_id
+    } else {
+        &conv.title
+    };
+    println!("{}\n", format!("▶ {title}").bold());
+
+    for (i, exchange) in exchanges.iter().enumerate() {
+        if i > 0 && timed {
+            std::thread::sleep(pause_between(&exchanges[i - 1], exchange, speed));
+        } else if i > 0 && interactive {
+            term.write_str(&"space/enter: next, q: quit".dimmed().to_string())
+                .map_err(key_error)?;
+            let key = term.read_key().map_err(key_error)?;
+            term.clear_line().map_err(key_error)?;
+            if matches!(key, Key::Char('q') | Key::Escape) {
+                break;
+            }
+        }
+        println!("{}", format_exchange(exchange, i + 1, exchanges.len()));
+    }
+
+    Ok(())
+}
+
+/// Write the terminal commands of a conversation as a shell script.
+///
+/// # Errors
+/// Returns error if the conversation is not found or the script cannot be
+/// written.
+pub fn cmd_export_script(id: &str, output: Option<&str>) -> Result<()> {
+    let (conversations, _) = load_conversations(&ExtractOptions::default())?;
+    let mut conv = find_conversation(&conversations, id)?.clone();
+    let count = conv.terminal_commands().count();
+    if count == 0 {
+        println!("{}", "No terminal commands found.".yellow());
+        return Ok(());
+    }
+    export_path_map()?.apply_to_conversation(&mut conv);
+
+    let mut writer = output_writer(output)?;
+    writer
+        .write_all(format_shell_script(&conv).as_bytes())
+        .and_then(|()| writer.flush())
+        .map_err(write_error)?;
+    drop(writer);
+
+    if let Some(path) = output {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| AppError::io(format!("Failed to make {path} executable"), e))?;
+        }
+        println!("{} Exported {count} commands to {path}", "✓".green().bold());
+    }
+
+    Ok(())
+}
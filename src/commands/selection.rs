@@ -0,0 +1,282 @@
+//! Which conversations a command works on, and the stored data
+//! (titles, tags, workspaces, git info) shown with them.
+
+use std::collections::HashMap;
+
+use crate::application::{
+    extract_all_conversations, find_conversation, load_short_ids, ExtractOptions, PathMap,
+};
+use crate::domain::{AppError, Conversation, ExtractionStats, GitInfo, Result, ShortIds};
+use crate::infrastructure::{composer_workspaces, load_config, LocalStorage};
+
+/// Extract conversations from Cursor, showing stored title overrides.
+///
+/// Goes through the parsed-conversation cache unless `extract.cache` is off.
+pub(super) fn load_conversations(
+    options: &ExtractOptions,
+) -> Result<(Vec<Conversation>, ExtractionStats)> {
+    let config = load_config()?;
+    let options = ExtractOptions {
+        cache_path: config
+            .extract
+            .cache
+            .then(|| config.conversation_cache_path()),
+        ..options.clone()
+    };
+    let (mut conversations, stats) = extract_all_conversations(&options)?;
+    apply_titles(&mut conversations, &stored_titles()?);
+    apply_git(&mut conversations, &stored_git()?);
+    apply_workspaces(&mut conversations, &stored_workspaces()?);
+
+    Ok((conversations, stats))
+}
+
+/// Title overrides from local storage, empty if unavailable.
+pub(super) fn stored_titles() -> Result<HashMap<String, String>> {
+    stored_map(LocalStorage::title_overrides, "Title overrides")
+}
+
+/// Git repositories recorded by sync, empty if unavailable.
+pub(super) fn stored_git() -> Result<HashMap<String, GitInfo>> {
+    stored_map(LocalStorage::conversation_git, "Git repositories")
+}
+
+/// Workspace names from local storage, empty if unavailable.
+///
+/// Workspaces are only known for conversations synced to local storage.
+pub(super) fn stored_workspaces() -> Result<HashMap<String, String>> {
+    stored_map(LocalStorage::conversation_workspaces, "Workspaces")
+}
+
+/// A per-conversation map read from local storage, empty if unavailable.
+fn stored_map<T>(
+    read: impl FnOnce(&LocalStorage) -> Result<HashMap<String, T>>,
+    what: &str,
+) -> Result<HashMap<String, T>> {
+    let storage_path = load_config()?.storage_db_path();
+    if !storage_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(LocalStorage::open_read_only(&storage_path)
+        .and_then(|s| read(&s))
+        .unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "{what} unavailable");
+            HashMap::new()
+        }))
+}
+
+/// Attach the git repository recorded for each conversation.
+pub(super) fn apply_git(conversations: &mut [Conversation], git: &HashMap<String, GitInfo>) {
+    for conv in conversations {
+        conv.git = git.get(&conv.composer_id).cloned();
+    }
+}
+
+/// Attach the workspace name recorded for each conversation.
+fn apply_workspaces(conversations: &mut [Conversation], workspaces: &HashMap<String, String>) {
+    for conv in conversations {
+        // Keep the workspace of chats extracted from workspace databases
+        if let Some(workspace) = workspaces.get(&conv.composer_id) {
+            conv.workspace = Some(workspace.clone());
+        }
+    }
+}
+
+/// Keep only the conversations of workspace `name` (case-insensitive).
+///
+/// Conversations not synced yet have no recorded workspace; they are
+/// attributed from Cursor's `workspaceStorage` on the fly.
+fn retain_workspace(conversations: &mut Vec<Conversation>, name: &str) {
+    if conversations.iter().any(|c| c.workspace.is_none()) {
+        match composer_workspaces() {
+            Ok(live) => {
+                for conv in conversations.iter_mut().filter(|c| c.workspace.is_none()) {
+                    conv.workspace = live.get(&conv.composer_id).map(|info| info.name.clone());
+                }
+            }
+            Err(e) => tracing::debug!(error = %e, "Workspace storage unavailable"),
+        }
+    }
+
+    conversations.retain(|c| c.in_workspace(name));
+}
+
+/// Show stored titles instead of heuristic ones.
+pub(super) fn apply_titles(conversations: &mut [Conversation], titles: &HashMap<String, String>) {
+    for conv in conversations {
+        if let Some(title) = titles.get(&conv.composer_id) {
+            conv.title.clone_from(title);
+        }
+    }
+}
+
+/// Which conversations `list` and `export` work on.
+#[derive(Clone, Copy)]
+pub struct Selection<'a> {
+    /// The one conversation to export (all if `None`).
+    pub conversation_id: Option<&'a str>,
+    pub min_messages: usize,
+    /// Only conversations of this workspace.
+    pub workspace: Option<&'a str>,
+    /// Only conversations with this tag.
+    pub tag: Option<&'a str>,
+    pub range: DateRange,
+}
+
+/// Period of the messages `list` and `export` work on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DateRange {
+    /// Only conversations with messages since this time.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only conversations with messages before this time.
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Also leave out the messages outside the period.
+    pub trim_messages: bool,
+}
+
+impl Selection<'_> {
+    /// Extraction options applying everything but the workspace.
+    pub(super) fn extract_options(&self) -> ExtractOptions {
+        ExtractOptions {
+            min_messages: self.min_messages,
+            conversation_ids: self.conversation_id.map(|id| vec![id.to_string()]),
+            since: self.range.since,
+            until: self.range.until,
+            trim_bubbles: self.range.trim_messages,
+            ..Default::default()
+        }
+    }
+
+    /// Drop the conversations outside the workspace and tag filters, which
+    /// extraction does not apply. Returns whether either is set.
+    pub(super) fn retain(&self, conversations: &mut Vec<Conversation>) -> Result<bool> {
+        if let Some(name) = self.workspace {
+            retain_workspace(conversations, name);
+        }
+        if let Some(tag) = self.tag {
+            let tag = normalize_tag(tag)?;
+            let tags = stored_map(LocalStorage::conversation_tags, "Tags")?;
+            conversations.retain(|c| tags.get(&c.composer_id).is_some_and(|t| t.contains(&tag)));
+        }
+        Ok(self.workspace.is_some() || self.tag.is_some())
+    }
+}
+
+/// The conversations `export` writes: all of them, those of a workspace, or
+/// the one matching the selected ID.
+pub(super) fn conversations_to_export(
+    selection: Selection<'_>,
+) -> Result<(Vec<Conversation>, ExtractionStats)> {
+    let (mut conversations, mut stats) = load_conversations(&selection.extract_options())?;
+    if selection.retain(&mut conversations)? {
+        stats = stats.of_subset(&conversations);
+    }
+
+    // The ID filter is a partial match; exporting must target one conversation
+    if let Some(id) = selection.conversation_id {
+        let composer_id = find_conversation(&conversations, id)?.composer_id.clone();
+        conversations.retain(|c| c.composer_id == composer_id);
+        stats.conversation_count = conversations.len();
+    }
+
+    let path_map = export_path_map()?;
+    for conv in &mut conversations {
+        path_map.apply_to_conversation(conv);
+    }
+
+    Ok((conversations, stats))
+}
+
+/// Replacements from `export.path_map` (or `--path-map`), empty if unset.
+pub(super) fn export_path_map() -> Result<PathMap> {
+    load_config()?
+        .export
+        .path_map
+        .map_or_else(|| Ok(PathMap::default()), |path| PathMap::load(&path))
+}
+
+/// A tag as stored: trimmed, lowercase, without a leading `#`.
+pub(super) fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(AppError::Config {
+            message: format!(
+                "Invalid tag '{tag}': tags cannot be empty or contain spaces or commas"
+            ),
+        });
+    }
+    Ok(tag)
+}
+
+/// Shortest unambiguous IDs for display.
+pub(super) fn short_ids() -> Result<ShortIds> {
+    Ok(load_short_ids(&load_config()?.storage_db_path()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ModelConfig;
+
+    fn conversation(id: &str, workspace: Option<&str>) -> Conversation {
+        Conversation {
+            composer_id: id.to_string(),
+            title: "Heuristic title".to_string(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: workspace.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert!(matches!(normalize_tag(" #Bug ").as_deref(), Ok("bug")));
+        assert!(normalize_tag("#").is_err());
+        assert!(normalize_tag("two words").is_err());
+        assert!(normalize_tag("a,b").is_err());
+    }
+
+    #[test]
+    fn test_extract_options() {
+        let since = chrono::DateTime::from_timestamp(1_700_000_000, 0);
+        let selection = Selection {
+            conversation_id: Some("abc"),
+            min_messages: 3,
+            workspace: Some("api"),
+            tag: None,
+            range: DateRange {
+                since,
+                until: None,
+                trim_messages: true,
+            },
+        };
+
+        let options = selection.extract_options();
+        assert_eq!(options.min_messages, 3);
+        assert_eq!(options.conversation_ids, Some(vec!["abc".to_string()]));
+        assert_eq!(options.since, since);
+        assert!(options.trim_bubbles);
+    }
+
+    #[test]
+    fn test_apply_stored_data() {
+        let mut conversations = vec![conversation("a", None), conversation("b", Some("web"))];
+        let titles = HashMap::from([("a".to_string(), "Renamed".to_string())]);
+        let workspaces = HashMap::from([
+            ("a".to_string(), "api".to_string()),
+            ("c".to_string(), "cli".to_string()),
+        ]);
+
+        apply_titles(&mut conversations, &titles);
+        apply_workspaces(&mut conversations, &workspaces);
+
+        assert_eq!(conversations[0].title, "Renamed");
+        assert_eq!(conversations[0].workspace.as_deref(), Some("api"));
+        assert_eq!(conversations[1].title, "Heuristic title");
+        assert_eq!(conversations[1].workspace.as_deref(), Some("web"));
+    }
+}
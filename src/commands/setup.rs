@@ -0,0 +1,277 @@
+//! Installation, configuration and diagnostics (`uninstall`, `config`,
+//! `doctor`, `bench`).
+
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::application::{
+    format_bench, generate_synthetic_db, is_data_dir, profile_data_dirs, purge_data_dir,
+    run_benchmark, run_diagnostics, BenchReport, CheckStatus, OutputFormat,
+};
+use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::{
+    config_file_path, edit_config, ensure_writable, get_config_value, global_state_db_path,
+    installed_service_instances, load_base_config, load_config, save_config_to_file,
+    service_manager_for, set_config_value,
+};
+
+/// Remove every sync service instance and the config file; with `purge`,
+/// the data directories too.
+///
+/// # Errors
+/// Returns error if storage is read-only, the purge is not confirmed or a file
+/// cannot be removed.
+pub fn cmd_uninstall(purge: bool, yes: bool) -> Result<()> {
+    ensure_writable("uninstall")?;
+    let config = load_config()?;
+
+    // Every profile's data, but never a directory this tool did not create
+    let (mut data_dirs, foreign): (Vec<_>, Vec<_>) = profile_data_dirs(&load_base_config()?)
+        .into_iter()
+        .partition(|dir| is_data_dir(dir));
+    // Nested profile directories (`nightly/`) before the one holding them
+    data_dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    if purge && !yes {
+        confirm_purge(&data_dirs)?;
+    }
+
+    println!("{}", "🗑️  Uninstalling cursor-chat...".bold());
+
+    // The default unit plus one per profile instance
+    let instances = installed_service_instances()?;
+    for instance in std::iter::once(None).chain(instances.into_iter().map(Some)) {
+        let service = service_manager_for(config.clone(), instance);
+        if service.get_status()?.is_installed {
+            service.uninstall()?;
+            println!("  {} Removed {}", "✓".green(), service.service_name());
+        }
+    }
+
+    // Data first: the config says where it is, and is kept if this fails
+    if purge {
+        for dir in &data_dirs {
+            if purge_data_dir(dir)? {
+                println!("  {} Removed {}", "✓".green(), dir.display());
+            } else {
+                println!(
+                    "  {} Removed cursor-chat data from {}",
+                    "✓".green(),
+                    dir.display()
+                );
+            }
+        }
+    } else {
+        for dir in &data_dirs {
+            println!(
+                "  Data kept in {} (use {} to delete it)",
+                dir.display(),
+                "--purge".cyan()
+            );
+        }
+    }
+    for dir in &foreign {
+        println!(
+            "  {} Not removing {}: not a data directory of cursor-chat",
+            "⚠".yellow(),
+            dir.display()
+        );
+    }
+
+    let config_path = config_file_path();
+    if config_path.exists() {
+        std::fs::remove_file(&config_path)
+            .map_err(|e| AppError::io("Failed to remove config file", e))?;
+        println!("  {} Removed {}", "✓".green(), config_path.display());
+    }
+    // Left empty once the config is gone; fails harmlessly otherwise
+    let _ = std::fs::remove_dir(AppConfig::default_data_dir());
+
+    Ok(())
+}
+
+/// Ask on the terminal before deleting `dirs` for good.
+fn confirm_purge(dirs: &[PathBuf]) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(AppError::Config {
+            message: "Refusing to purge without confirmation; pass --yes".into(),
+        });
+    }
+
+    println!(
+        "{}",
+        "This permanently deletes all stored chats, backups and exports in:".yellow()
+    );
+    for dir in dirs {
+        println!("  {}", dir.display());
+    }
+    print!("Type 'yes' to continue: ");
+    std::io::stdout()
+        .flush()
+        .map_err(|e| AppError::io("Failed to write prompt", e))?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .map_err(|e| AppError::io("Failed to read answer", e))?;
+    if answer.trim() != "yes" {
+        return Err(AppError::Config {
+            message: "Purge cancelled".into(),
+        });
+    }
+
+    Ok(())
+}
+
+/// What `bench` measures.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchOptions {
+    /// Also benchmark a generated dataset with this many conversations.
+    pub synthetic: Option<usize>,
+    /// Messages per generated conversation.
+    pub messages: usize,
+    /// Runs per phase; the median is reported.
+    pub iterations: usize,
+    /// Skip the real Cursor database.
+    pub synthetic_only: bool,
+}
+
+/// Benchmark the extraction pipeline on the real and/or a synthetic database.
+///
+/// # Errors
+/// Returns error if a benchmark run fails.
+pub fn cmd_bench(args: &BenchOptions, format: OutputFormat) -> Result<()> {
+    let scratch = std::env::temp_dir().join(format!("cursor-chat-bench-{}", std::process::id()));
+    let result = run_bench_datasets(args, &scratch);
+    let _ = std::fs::remove_dir_all(&scratch);
+    let reports = result?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&reports).map_err(AppError::json_parse)?;
+        println!("{output}");
+    } else {
+        println!("{}", "⏱  Benchmark".bold());
+        println!(
+            "{}",
+            format!("Median of {} run(s) per phase", args.iterations.max(1)).dimmed()
+        );
+        println!("{}", format_bench(&reports));
+    }
+
+    Ok(())
+}
+
+fn run_bench_datasets(args: &BenchOptions, scratch: &std::path::Path) -> Result<Vec<BenchReport>> {
+    let mut reports = Vec::new();
+
+    if !args.synthetic_only {
+        let db_path = global_state_db_path()?;
+        eprintln!("Benchmarking {}...", db_path.display());
+        reports.push(run_benchmark("real", &db_path, scratch, args.iterations)?);
+    }
+
+    if let Some(conversations) = args.synthetic {
+        std::fs::create_dir_all(scratch)
+            .map_err(|e| AppError::io("Failed to create benchmark directory", e))?;
+        let db_path = scratch.join("synthetic.vscdb");
+        eprintln!(
+            "Generating {conversations} synthetic conversations of {} messages...",
+            args.messages
+        );
+        generate_synthetic_db(&db_path, conversations, args.messages)?;
+        let label = format!("synthetic ({conversations}x{})", args.messages);
+        reports.push(run_benchmark(&label, &db_path, scratch, args.iterations)?);
+    }
+
+    Ok(reports)
+}
+
+/// Run end-to-end diagnostics.
+///
+/// # Errors
+/// Returns error if an essential check fails.
+pub fn cmd_doctor() -> Result<()> {
+    // Fall back to defaults so the remaining checks still run with a broken config
+    let config = load_config().unwrap_or_default();
+
+    println!("{}", "🩺 Cursor Chat Handler Doctor".bold());
+    println!();
+
+    let results = run_diagnostics(&config);
+    for result in &results {
+        let icon = match result.status {
+            CheckStatus::Pass => "✓".green(),
+            CheckStatus::Warn => "⚠".yellow(),
+            CheckStatus::Fail => "✗".red(),
+        };
+        println!("  {icon} {:<16} {}", result.name, result.detail);
+        if let Some(hint) = &result.hint {
+            println!("    {} {}", "→".dimmed(), hint.dimmed());
+        }
+    }
+    println!();
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(AppError::Config {
+            message: format!("{failed} check(s) failed"),
+        });
+    }
+
+    println!("{}", "✅ All essential checks passed".green().bold());
+    Ok(())
+}
+
+/// Print a setting, or the whole config without `key` (`config get`).
+///
+/// # Errors
+/// Returns error if the config cannot be read or the key is unknown.
+pub fn cmd_config_get(key: Option<&str>) -> Result<()> {
+    let config = load_base_config()?;
+    let value = match key {
+        Some(key) => get_config_value(&config, key)?,
+        None => toml::Value::try_from(&config).map_err(|e| AppError::Config {
+            message: format!("Failed to serialize config: {e}"),
+        })?,
+    };
+
+    match value {
+        toml::Value::String(s) => println!("{s}"),
+        toml::Value::Table(table) => print!("{table}"),
+        other => println!("{other}"),
+    }
+    Ok(())
+}
+
+/// Change a setting and save the config (`config set`).
+///
+/// # Errors
+/// Returns error if the key or value is invalid or the config cannot be saved.
+pub fn cmd_config_set(key: &str, value: &str) -> Result<()> {
+    let config = load_base_config()?;
+    let updated = set_config_value(&config, key, value)?;
+    save_config_to_file(&updated, &config_file_path())?;
+
+    println!("{} {} = {}", "✓".green(), key.cyan(), value);
+    if key.starts_with("sync.") {
+        println!(
+            "  Restart the daemon to apply: {}",
+            "cursor-chat sync stop && cursor-chat sync start".dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Open the config in the user's editor and validate it (`config edit`).
+///
+/// # Errors
+/// Returns error if the editor fails or the edited config is invalid.
+pub fn cmd_config_edit() -> Result<()> {
+    edit_config()?;
+    println!("{} Saved {}", "✓".green(), config_file_path().display());
+    Ok(())
+}
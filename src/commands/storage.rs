@@ -0,0 +1,296 @@
+//! Local storage commands (`storage ...`).
+
+use colored::Colorize;
+
+use crate::application::{format_bytes, StorageManager, StorageSummary, SyncService};
+use crate::domain::{BackupMetadata, Result, ShortIds};
+use crate::infrastructure::load_config;
+
+use super::selection::short_ids;
+
+/// Show storage usage (`storage stats`).
+///
+/// # Errors
+/// Returns error if storage cannot be read.
+pub fn cmd_storage_stats() -> Result<()> {
+    let config = load_config()?;
+    let storage_mgr = StorageManager::new(config.clone());
+    storage_mgr.ensure_directories()?;
+    let summary = storage_mgr.get_summary()?;
+
+    println!("{}", "💾 Storage Statistics".bold());
+    println!();
+    println!(
+        "  Total used:     {} / {} ({:.1}%)",
+        summary.total_human(),
+        summary.max_human(),
+        summary.usage_percent
+    );
+    println!();
+    println!("  Database:       {}", summary.db_human());
+    println!("  Exports:        {}", summary.exports_human());
+    println!(
+        "  Backups:        {} ({} files)",
+        summary.backups_human(),
+        summary.backup_count
+    );
+    if let Some(backup) = &summary.latest_backup {
+        println!("  Latest backup:  {}", describe_backup(backup));
+    }
+    println!();
+    println!("  Data directory: {}", config.data_dir().display());
+    print_storage_breakdown(&summary, &short_ids()?);
+    Ok(())
+}
+
+/// Delete old backups and enforce the storage limit (`storage cleanup`).
+///
+/// # Errors
+/// Returns error if files cannot be deleted.
+pub fn cmd_storage_cleanup() -> Result<()> {
+    let storage_mgr = StorageManager::new(load_config()?);
+    println!("{}", "🧹 Running cleanup...".bold());
+
+    let result = storage_mgr.enforce_storage_limit()?;
+
+    if result.deleted_count > 0 {
+        println!(
+            "  {} Deleted {} files, freed {}",
+            "✓".green(),
+            result.deleted_count,
+            result.freed_human()
+        );
+    } else {
+        println!("  {} Nothing to clean up", "✓".green());
+    }
+    Ok(())
+}
+
+/// List storage backups (`storage backups`).
+///
+/// # Errors
+/// Returns error if the backups directory cannot be read.
+pub fn cmd_storage_backups() -> Result<()> {
+    print_backups(&StorageManager::new(load_config()?).list_backups()?);
+    Ok(())
+}
+
+/// List the workspaces of synced conversations (`storage workspaces`).
+///
+/// # Errors
+/// Returns error if storage cannot be read.
+pub fn cmd_storage_workspaces() -> Result<()> {
+    let sync_service = SyncService::new(load_config()?)?;
+    let workspaces = sync_service.get_workspaces()?;
+
+    println!("{}", "📁 Workspaces".bold());
+    println!();
+
+    if workspaces.is_empty() {
+        println!("  No workspaces found. Run 'cursor-chat sync now' first.");
+    } else {
+        for ws in &workspaces {
+            let path = ws
+                .path
+                .as_ref()
+                .map_or_else(|| "(unknown)".to_string(), |p| p.display().to_string());
+            println!("  {} → {}", ws.name.cyan(), path);
+        }
+    }
+    Ok(())
+}
+
+/// Show the storage settings (`storage config`).
+///
+/// # Errors
+/// Returns error if the config cannot be read.
+pub fn cmd_storage_config() -> Result<()> {
+    let config = load_config()?;
+    println!("{}", "⚙️  Storage Configuration".bold());
+    println!();
+    println!("  Max storage:       {} GB", config.storage.max_size_gb);
+    println!(
+        "  Backup retention:  {} days",
+        config.storage.backup_retention_days
+    );
+    println!("  Sync interval:     {} seconds", config.sync.interval_secs);
+    println!("  Sync enabled:      {}", config.sync.enabled);
+    println!();
+    println!("  Data directory:    {}", config.data_dir().display());
+    println!(
+        "  Config file:       {}",
+        config.config_file_path().display()
+    );
+    Ok(())
+}
+
+/// Compact storage.db (`storage vacuum`).
+///
+/// # Errors
+/// Returns error if storage.db cannot be vacuumed.
+pub fn cmd_storage_vacuum() -> Result<()> {
+    let storage_mgr = StorageManager::new(load_config()?);
+    println!("{}", "🧹 Vacuuming storage...".bold());
+
+    let result = storage_mgr.vacuum()?;
+
+    println!(
+        "  {} {} → {} (reclaimed {})",
+        "✓".green(),
+        result.before_human(),
+        result.after_human(),
+        result.reclaimed_human()
+    );
+    Ok(())
+}
+
+/// Copy every stored chat into a standalone database (`storage export-db`).
+///
+/// # Errors
+/// Returns error if the output exists (without `force`) or cannot be written.
+pub fn cmd_storage_export_db(output: &str, force: bool) -> Result<()> {
+    let storage_mgr = StorageManager::new(load_config()?);
+    let result = storage_mgr.export_db(std::path::Path::new(output), force)?;
+
+    println!(
+        "{} Exported {} conversations, {} messages to {}",
+        "✓".green(),
+        result.conversations,
+        result.messages,
+        output
+    );
+    println!("  Try: sqlite3 {output} 'SELECT * FROM v_conversations'");
+    Ok(())
+}
+
+/// List backups, newest first.
+fn print_backups(backups: &[BackupMetadata]) {
+    println!("{}", "🗄️  Backups".bold());
+    println!();
+    if backups.is_empty() {
+        println!("  No backups yet. Maintenance creates one periodically.");
+        return;
+    }
+    for backup in backups {
+        println!("  {}  {}", backup.id.cyan(), describe_backup(backup));
+        if let Some(machine) = &backup.source_machine {
+            let version = backup.app_version.as_deref().unwrap_or("?");
+            println!("  {:<24} from {machine}, v{version}", "");
+        }
+    }
+}
+
+/// Date, size and contents of a backup; contents are unknown for backups
+/// written without a metadata sidecar.
+fn describe_backup(backup: &BackupMetadata) -> String {
+    let contents = if backup.has_details() {
+        format!(
+            "{} chats, {} messages",
+            backup.conversation_count, backup.message_count
+        )
+    } else {
+        "contents unknown".to_string()
+    };
+    format!(
+        "{}  {:>9}  {contents}",
+        backup.created_at.format("%Y-%m-%d %H:%M"),
+        format_bytes(backup.size_bytes)
+    )
+}
+
+/// Print per-table usage and the largest conversations.
+fn print_storage_breakdown(summary: &StorageSummary, ids: &ShortIds) {
+    if !summary.tables.is_empty() {
+        println!();
+        println!("{}", "📊 Tables".bold());
+        for table in &summary.tables {
+            println!(
+                "  {:<16} {:>10} rows  {:>10}",
+                table.name,
+                table.rows,
+                format_bytes(table.bytes)
+            );
+        }
+    }
+
+    if !summary.largest_conversations.is_empty() {
+        println!();
+        println!("{}", "🐘 Largest conversations".bold());
+        for conv in &summary.largest_conversations {
+            let title = if conv.title.is_empty() {
+                "(untitled)"
+            } else {
+                &conv.title
+            };
+            println!(
+                "  {} {:>10}  {:>5} msgs  {}",
+                ids.get(&conv.composer_id).dimmed(),
+                format_bytes(conv.bytes),
+                conv.messages,
+                title
+            );
+        }
+    }
+}
+
+/// Report storage health and optionally repair it (`storage doctor`).
+///
+/// # Errors
+/// Returns error if storage.db cannot be checked or repaired.
+pub fn cmd_storage_doctor(fix: bool) -> Result<()> {
+    let storage_mgr = StorageManager::new(load_config()?);
+    println!("{}", "🩺 Checking storage...".bold());
+    println!();
+
+    let report = storage_mgr.check_health()?;
+
+    if report.is_corrupt() {
+        println!("  {} Integrity check failed:", "✗".red());
+        for msg in report.integrity_errors.iter().take(10) {
+            println!("    {}", msg.dimmed());
+        }
+    } else {
+        println!("  {} Integrity check passed", "✓".green());
+    }
+
+    let checks = [
+        ("Orphan bubbles", report.orphan_bubbles),
+        ("Foreign key violations", report.foreign_key_violations),
+        ("Malformed timestamps", report.malformed_timestamps),
+    ];
+    for (label, count) in checks {
+        if count == 0 {
+            println!("  {} {label}: none", "✓".green());
+        } else {
+            println!("  {} {label}: {count}", "⚠".yellow());
+        }
+    }
+    println!();
+
+    if report.is_healthy() {
+        println!("{}", "✅ Storage is healthy".green().bold());
+        return Ok(());
+    }
+
+    if !fix {
+        println!(
+            "Run {} to repair.",
+            "cursor-chat storage doctor --fix".cyan()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🔧 Repairing...".bold());
+    let result = storage_mgr.repair()?;
+
+    if let Some(corrupt) = &result.corrupt_backup {
+        println!("  {} Salvaged into a fresh database", "✓".green());
+        println!("  Corrupt file kept at: {}", corrupt.display());
+        for table in &result.failed_tables {
+            println!("  {} Could not recover table: {table}", "⚠".yellow());
+        }
+    }
+    println!("  {} Fixed {} rows", "✓".green(), result.rows_fixed);
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+//! Sync service commands (`sync ...`).
+
+use colored::Colorize;
+
+use crate::application::{OutputFormat, RestoreService, StorageManager, SyncService};
+use crate::domain::{AppError, ResetEvent, Result};
+use crate::infrastructure::{
+    active_profile, installed_service_instances, load_config, service_manager, ServiceMode,
+    SyncLock,
+};
+
+use super::edit::{print_restore_problems, warn_if_cursor_running, with_restore_progress};
+use super::selection::short_ids;
+
+/// Compare local storage with Cursor's database (`sync diff`).
+///
+/// # Errors
+/// Returns error if either database cannot be read.
+pub fn cmd_sync_diff(format: OutputFormat) -> Result<()> {
+    let diff = SyncService::new(load_config()?)?.diff()?;
+
+    if matches!(format, OutputFormat::Json) {
+        let output = serde_json::to_string_pretty(&diff).map_err(AppError::json_parse)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("{}", "🔍 Local storage vs Cursor".bold());
+
+    let sections = [
+        (
+            "Only in local storage (restorable)",
+            &diff.local_only,
+            "restore",
+        ),
+        (
+            "Only in Cursor (not synced yet)",
+            &diff.cursor_only,
+            "sync now",
+        ),
+        (
+            "Changed in Cursor since last sync",
+            &diff.changed,
+            "sync now",
+        ),
+    ];
+    let ids = short_ids()?;
+    for (heading, entries, action) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        println!("\n{} ({})", heading.cyan(), entries.len());
+        for entry in entries {
+            println!("  {}  {}", ids.get(&entry.composer_id), entry.title);
+        }
+        println!("  {}", format!("→ cursor-chat {action}").dimmed());
+    }
+
+    println!();
+    if diff.is_in_sync() {
+        println!("{} In sync ({} conversations)", "✓".green(), diff.unchanged);
+    } else {
+        println!("{} unchanged", diff.unchanged);
+    }
+
+    Ok(())
+}
+
+/// Show the sync service status and, once installed, the sync state (`sync status`).
+///
+/// # Errors
+/// Returns error if the service status cannot be read.
+pub fn cmd_sync_status() -> Result<()> {
+    let config = load_config()?;
+    let service = service_manager(config.clone());
+    let status = service.get_status()?;
+
+    println!("{}", "📊 Sync Service Status".bold());
+    println!();
+    println!("  Unit:      {}", service.service_name());
+    println!(
+        "  Installed: {}",
+        if status.is_installed {
+            "Yes".green()
+        } else {
+            "No".red()
+        }
+    );
+    if status.is_installed && status.mode == ServiceMode::Timer {
+        println!("  Mode:      timer");
+    }
+    println!(
+        "  Enabled:   {}",
+        if status.is_enabled {
+            "Yes".green()
+        } else {
+            "No".yellow()
+        }
+    );
+    println!(
+        "  Running:   {}",
+        if status.is_running {
+            "Yes".green()
+        } else {
+            "No".red()
+        }
+    );
+    if SyncLock::is_held(&config.lock_file_path()) {
+        println!("  Syncing:   {}", "Yes (in progress)".cyan());
+    }
+    let instances = installed_service_instances()?;
+    if !instances.is_empty() {
+        println!(
+            "  Profiles:  {} (--profile <name> sync status)",
+            instances.join(", ")
+        );
+    }
+    println!();
+
+    if status.is_installed {
+        // Show sync state
+        let sync_service = SyncService::new(config)?;
+        let state = sync_service.get_state()?;
+
+        println!(
+            "  Last sync:      {}",
+            state.last_sync.map_or_else(
+                || "Never".to_string(),
+                |dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()
+            )
+        );
+        println!("  Conversations:  {}", state.conversation_count);
+        println!("  Messages:       {}", state.message_count);
+        if let Some(version) = &state.cursor_version {
+            println!("  Cursor:         {version}");
+        }
+
+        if let Some(err) = &state.last_error {
+            println!("  Last error:     {}", err.red());
+        }
+
+        print_reset_events(&sync_service.get_reset_events(5)?);
+    }
+
+    Ok(())
+}
+
+/// Install and start the sync service (`sync start`).
+///
+/// # Errors
+/// Returns error if the service cannot be installed or started.
+pub fn cmd_sync_start(mode: ServiceMode) -> Result<()> {
+    let config = load_config()?;
+    let service = service_manager(config.clone());
+    println!("{}", "🚀 Installing sync service...".bold());
+
+    // Ensure directories exist
+    let storage_mgr = StorageManager::new(config.clone());
+    storage_mgr.ensure_directories()?;
+
+    // Install and start service
+    let result = service.install(mode)?;
+    println!(
+        "  {} Service file: {}",
+        "✓".green(),
+        result.service_path.display()
+    );
+    if let Some(timer_path) = &result.timer_path {
+        println!("  {} Timer file:   {}", "✓".green(), timer_path.display());
+    }
+
+    service.enable_and_start()?;
+    println!("  {} Service enabled and started", "✓".green());
+
+    println!();
+    match mode {
+        ServiceMode::Daemon => println!("Sync daemon is now running! Check status with:"),
+        ServiceMode::Timer => println!(
+            "Sync runs every {} seconds! Check status with:",
+            config.sync.interval_secs
+        ),
+    }
+    match active_profile() {
+        Some(name) => println!("  cursor-chat --profile {name} sync status"),
+        None => println!("  cursor-chat sync status"),
+    }
+
+    Ok(())
+}
+
+/// Stop and disable the sync service (`sync stop`).
+///
+/// # Errors
+/// Returns error if the service cannot be stopped.
+pub fn cmd_sync_stop() -> Result<()> {
+    let service = service_manager(load_config()?);
+    println!("{}", "⏹️  Stopping sync service...".bold());
+    service.stop_and_disable()?;
+    println!("  {} Service stopped and disabled", "✓".green());
+    Ok(())
+}
+
+/// Run a sync immediately (`sync now`).
+///
+/// # Errors
+/// Returns error if the sync fails.
+pub fn cmd_sync_now() -> Result<()> {
+    let config = load_config()?;
+    println!("{}", "🔄 Running sync...".bold());
+    if SyncLock::is_held(&config.lock_file_path()) {
+        println!(
+            "  {} Another sync is running, waiting for it to finish",
+            "ℹ️".blue()
+        );
+    }
+
+    let sync_service = SyncService::new(config)?;
+    let state = sync_service.sync()?;
+
+    println!();
+    println!("  {} Sync completed!", "✓".green());
+    println!("  Conversations: {}", state.conversation_count);
+    println!("  Messages:      {}", state.message_count);
+    Ok(())
+}
+
+/// Print the last `lines` lines of the sync service log (`sync logs`).
+///
+/// # Errors
+/// Returns error if the logs cannot be read.
+pub fn cmd_sync_logs(lines: usize) -> Result<()> {
+    let logs = service_manager(load_config()?).view_logs(lines)?;
+    println!("{logs}");
+    Ok(())
+}
+
+/// Uninstall the sync service (`sync uninstall`).
+///
+/// # Errors
+/// Returns error if the service cannot be removed.
+pub fn cmd_sync_uninstall() -> Result<()> {
+    let service = service_manager(load_config()?);
+    println!("{}", "🗑️  Uninstalling sync service...".bold());
+    service.uninstall()?;
+    println!("  {} Service uninstalled", "✓".green());
+    Ok(())
+}
+
+/// Restore chats to Cursor when a reset is detected, or always with
+/// `force` (`sync restore`).
+///
+/// # Errors
+/// Returns error if the restore is refused or fails.
+pub fn cmd_sync_restore(ids: &[String], force: bool, close_cursor: bool) -> Result<()> {
+    let config = load_config()?;
+    println!("{}", "🔄 Restoring chats to Cursor...".bold());
+
+    let restore_service =
+        with_restore_progress(RestoreService::new(config).with_close_cursor(close_cursor));
+    warn_if_cursor_running(close_cursor, "restoring");
+
+    // Check if restore is needed
+    if !force && !restore_service.needs_restore()? {
+        println!(
+            "  {} Cursor database looks fine, no restore needed",
+            "ℹ️".blue()
+        );
+        println!("  Use --force to restore anyway");
+        return Ok(());
+    }
+
+    // Perform restore
+    let result = if ids.is_empty() {
+        restore_service.restore_all()?
+    } else {
+        restore_service.restore_by_ids(ids)?
+    };
+
+    println!();
+    println!("  {} Restore completed!", "✓".green());
+    println!("  Conversations: {}", result.restored_conversations);
+    println!("  Messages:      {}", result.restored_messages);
+    print_restore_problems(&result);
+    println!();
+    println!("  Database: {}", result.cursor_db_path.display());
+    println!();
+    println!("  {} Restart Cursor to see restored chats", "💡".bold());
+    Ok(())
+}
+
+/// Print recently detected Cursor resets.
+fn print_reset_events(events: &[ResetEvent]) {
+    if events.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("  {}", "Resets detected:".bold());
+    for event in events {
+        let outcome = if event.restored {
+            "restored".green()
+        } else {
+            "not restored".yellow()
+        };
+        println!(
+            "    {} {} conversations vanished ({})",
+            event.detected_at.format("%Y-%m-%d %H:%M:%S"),
+            event.vanished(),
+            outcome
+        );
+    }
+}
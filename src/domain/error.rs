@@ -33,10 +33,28 @@ pub enum AppError {
         source: Option<serde_json::Error>,
     },
 
+    /// Synchronization with Cursor's database failed.
+    #[error("Sync error: {message}")]
+    Sync { message: String },
+
+    /// Request to the LLM endpoint failed.
+    #[error("LLM request failed: {message}")]
+    Llm { message: String },
+
+    /// The local embedding model failed to load or run.
+    #[error("Embedding model error: {message}")]
+    Embedding { message: String },
+
     /// Configuration or environment error.
     #[error("Configuration error: {message}")]
     Config { message: String },
 
+    /// Operation refused because read-only mode is on.
+    #[error(
+        "Read-only mode: refusing to {action} (drop --read-only or set safety.read_only = false)"
+    )]
+    ReadOnly { action: String },
+
     /// IO operation failed.
     #[error("IO error: {message}")]
     Io {
@@ -48,6 +66,7 @@ pub enum AppError {
 
 impl AppError {
     /// Create a database error from rusqlite error.
+    #[must_use]
     pub fn database(err: rusqlite::Error) -> Self {
         Self::Database {
             message: err.to_string(),
@@ -56,6 +75,7 @@ impl AppError {
     }
 
     /// Create a JSON parse error.
+    #[must_use]
     pub fn json_parse(err: serde_json::Error) -> Self {
         Self::JsonParse {
             message: err.to_string(),
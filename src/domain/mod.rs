@@ -9,6 +9,12 @@ pub mod sync;
 
 pub use error::{AppError, Result};
 pub use models::{
-    Bubble, BubbleType, Conversation, ExtractionStats, ModelConfig, ThinkingBlock, TokenCount,
+    truncate_chars, Bubble, BubbleContext, BubbleType, ContextFile, Conversation, Diagnostic,
+    ExtractionStats, GitCommit, GitInfo, ModelConfig, ShortIds, TerminalCommand, ThinkingBlock,
+    TokenCount, ToolCall, ToolKind,
+};
+pub use sync::{
+    AppConfig, BackupMetadata, Change, ChangeType, CursorChannel, EmbeddingBackend,
+    FormatterPlugin, LlmConfig, Operation, OperationKind, ProfileConfig, QuickMenu, ResetEvent,
+    StorageConfig, SyncConfig, SyncState, WorkspaceInfo,
 };
-pub use sync::{AppConfig, BackupMetadata, StorageConfig, SyncConfig, SyncState, WorkspaceInfo};
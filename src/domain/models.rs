@@ -2,11 +2,13 @@
 //!
 //! These models represent the core entities extracted from Cursor's `SQLite` database.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Type of message in a chat bubble.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(try_from = "u8", into = "u8")]
 pub enum BubbleType {
     /// Message from the user (human).
@@ -14,6 +16,7 @@ pub enum BubbleType {
     /// Message from the AI assistant.
     Assistant = 2,
     /// Unknown or other type.
+    #[default]
     Unknown = 0,
 }
 
@@ -58,6 +61,7 @@ pub struct ThinkingBlock {
 /// Token usage information.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)] // Named like Cursor's fields
 pub struct TokenCount {
     /// Number of input tokens.
     #[serde(default)]
@@ -65,10 +69,43 @@ pub struct TokenCount {
     /// Number of output tokens.
     #[serde(default)]
     pub output_tokens: u64,
+    /// Number of reasoning (thinking) tokens, billed separately by some models.
+    ///
+    /// Left out of the JSON when zero, so content hashes of conversations
+    /// without reasoning tokens do not change.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub reasoning_tokens: u64,
+}
+
+impl TokenCount {
+    /// Input, output and reasoning tokens together.
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.reasoning_tokens
+    }
+
+    /// Whether no tokens were recorded.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl std::ops::AddAssign<&Self> for TokenCount {
+    fn add_assign(&mut self, other: &Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+    }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde's skip_serializing_if passes a reference
+const fn is_zero(n: &u64) -> bool {
+    *n == 0
 }
 
 /// A single chat message (bubble) in a conversation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Bubble {
     /// Unique identifier for this bubble.
     pub bubble_id: String,
@@ -90,6 +127,166 @@ pub struct Bubble {
     /// Whether this is an agentic response.
     #[serde(default)]
     pub is_agentic: bool,
+    /// Terminal commands the agent ran in this message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<TerminalCommand>,
+    /// Other tools the agent called in this message (edits, reads, searches).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Files, selections and folders attached to this message.
+    #[serde(default, skip_serializing_if = "BubbleContext::is_empty")]
+    pub context: BubbleContext,
+    /// Linter errors and warnings attached to this message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Files, code selections and folders attached to a message as context.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BubbleContext {
+    /// Attached files and code selections, in the order they were added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<ContextFile>,
+    /// Attached folders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
+}
+
+impl BubbleContext {
+    /// Whether nothing was attached.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.folders.is_empty()
+    }
+}
+
+/// Files then folders, comma separated: `src/foo.rs lines 10-40, docs/`.
+impl std::fmt::Display for BubbleContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let files = self.files.iter().map(ToString::to_string);
+        let folders = self.folders.iter().map(|folder| {
+            if folder.ends_with('/') {
+                folder.clone()
+            } else {
+                format!("{folder}/")
+            }
+        });
+        write!(f, "{}", files.chain(folders).collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// A file attached to a message, whole or as a range of lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFile {
+    /// File path, relative to the workspace when known.
+    pub path: String,
+    /// First selected line (1-based); `None` for the whole file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Last selected line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+}
+
+impl std::fmt::Display for ContextFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+        match (self.start_line, self.end_line) {
+            (Some(start), Some(end)) if end > start => write!(f, " lines {start}-{end}"),
+            (Some(start), _) => write!(f, " line {start}"),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+/// A linter error or warning the agent was shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// Diagnostic text.
+    pub message: String,
+    /// File it refers to, relative to the workspace when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line it starts on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Severity as reported (`error`, `warning`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+impl Bubble {
+    /// Whether the message has no text, tool calls or diagnostics.
+    ///
+    /// Tool-call messages often have no text but still ran a command or
+    /// edited a file.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+            && self.commands.is_empty()
+            && self.tool_calls.is_empty()
+            && self.diagnostics.is_empty()
+    }
+}
+
+/// A shell command the agent ran in the terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalCommand {
+    /// Command line as run.
+    pub command: String,
+    /// Directory it ran in, if recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Captured output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Exit code, if the command finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+/// What a tool call did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolKind {
+    /// Created, changed or deleted a file.
+    Edit,
+    /// Read a file or listed a directory.
+    Read,
+    /// Searched the codebase, file names or the web.
+    Search,
+    /// Any other tool (MCP servers, todo lists, ...).
+    #[default]
+    Other,
+}
+
+/// A tool the agent called other than the terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    /// Tool name as recorded (`edit_file`, `codebase_search`, ...).
+    pub name: String,
+    /// What the tool did.
+    pub kind: ToolKind,
+    /// File or directory it targeted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Search query or pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// The agent's description of the call (edit instructions, explanation).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// New content of an edit, as the agent wrote it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit: Option<String>,
+    /// Outcome as recorded (`completed`, `error`, `cancelled`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
 }
 
 /// Model configuration used for a conversation.
@@ -104,6 +301,101 @@ pub struct ModelConfig {
     pub max_mode: bool,
 }
 
+/// Longest heuristic title, in characters.
+const MAX_TITLE_CHARS: usize = 50;
+
+/// Most words in a heuristic title.
+const MAX_TITLE_WORDS: usize = 8;
+
+/// Words dropped from heuristic titles.
+const STOP_WORDS: &[&str] = &[
+    // English
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "could", "do", "does", "for",
+    "from", "have", "how", "i", "if", "in", "is", "it", "its", "me", "my", "of", "on", "or",
+    "please", "so", "that", "the", "this", "to", "we", "what", "when", "where", "why", "with",
+    "you", "your", // Portuguese
+    "ao", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "em", "esse", "essa", "este",
+    "esta", "eu", "isso", "isto", "meu", "minha", "na", "nas", "no", "nos", "o", "os", "ou",
+    "para", "pelo", "pela", "por", "pra", "que", "se", "um", "uma", "voce", "você",
+    // Spanish
+    "al", "el", "en", "es", "la", "las", "lo", "los", "mi", "para", "por", "su", "un", "una", "y",
+];
+
+/// Lines of `text` that read as prose: outside code fences and not
+/// dominated by symbols (code, paths, stack traces).
+fn prose_lines(text: &str) -> String {
+    let mut in_fence = false;
+    let mut prose = String::new();
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        let total = line.chars().filter(|c| !c.is_whitespace()).count();
+        let symbols = line
+            .chars()
+            .filter(|c| !c.is_alphanumeric() && !c.is_whitespace())
+            .count();
+        if in_fence || total == 0 || symbols * 4 > total {
+            continue;
+        }
+        prose.push_str(line);
+        prose.push('\n');
+    }
+
+    prose
+}
+
+/// The first `max` characters of `s`, never splitting a character.
+#[must_use]
+pub fn truncate_chars(s: &str, max: usize) -> &str {
+    s.char_indices().nth(max).map_or(s, |(end, _)| &s[..end])
+}
+
+/// Shortest ID prefix shown, even when fewer characters would be unique.
+const MIN_SHORT_ID: usize = 4;
+
+/// Prefix shown for IDs outside the known set.
+const DEFAULT_SHORT_ID: usize = 8;
+
+/// Shortest unambiguous prefix of each conversation ID, like git short hashes.
+#[derive(Debug, Clone, Default)]
+pub struct ShortIds {
+    lengths: HashMap<String, usize>,
+}
+
+impl ShortIds {
+    /// Compute prefixes that are unique among `ids`.
+    pub fn new<'a>(ids: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut sorted: Vec<&str> = ids.into_iter().collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        // In sorted order, the longest shared prefix is always with a neighbour
+        let shared =
+            |a: &str, b: &str| a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+        let lengths = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let before = i.checked_sub(1).map_or(0, |j| shared(id, sorted[j]));
+                let after = sorted.get(i + 1).map_or(0, |next| shared(id, next));
+                ((*id).to_string(), (before.max(after) + 1).max(MIN_SHORT_ID))
+            })
+            .collect();
+
+        Self { lengths }
+    }
+
+    /// The short form of `id`; unknown IDs get a fixed-length prefix.
+    #[must_use]
+    pub fn get<'a>(&self, id: &'a str) -> &'a str {
+        let len = self.lengths.get(id).copied().unwrap_or(DEFAULT_SHORT_ID);
+        truncate_chars(id, len)
+    }
+}
+
 /// Metadata for a conversation (composer).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
@@ -123,9 +415,82 @@ pub struct Conversation {
     /// All bubbles in this conversation (sorted by time).
     #[serde(default)]
     pub bubbles: Vec<Bubble>,
+    /// Git repository of the workspace, as recorded by the last sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitInfo>,
+    /// Name of the workspace, as recorded by the last sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+}
+
+/// Git repository a conversation's workspace belonged to at sync time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitInfo {
+    /// Top-level directory of the repository.
+    pub repo_root: String,
+    /// URL of the `origin` remote.
+    #[serde(default)]
+    pub origin_url: Option<String>,
+    /// Checked out branch (`None` on a detached HEAD).
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Commit checked out.
+    #[serde(default)]
+    pub head_commit: Option<String>,
+}
+
+/// A commit from a workspace's git history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitCommit {
+    /// Full commit hash.
+    pub hash: String,
+    /// Committer date.
+    pub committed_at: DateTime<Utc>,
+    /// Author name.
+    pub author: String,
+    /// First line of the commit message.
+    pub subject: String,
 }
 
 impl Conversation {
+    /// Stable hash of the whole conversation, for change detection.
+    ///
+    /// blake3 over a JSON serialization with bubbles in ID order, so it only
+    /// depends on the content, not on how the conversation was loaded.
+    #[must_use]
+    pub fn content_hash(&self) -> String {
+        #[derive(Serialize)]
+        struct Canonical<'a> {
+            composer_id: &'a str,
+            title: &'a str,
+            created_at: Option<DateTime<Utc>>,
+            model_config: &'a ModelConfig,
+            unified_mode: &'a str,
+            bubbles: Vec<&'a Bubble>,
+        }
+
+        let mut bubbles: Vec<&Bubble> = self.bubbles.iter().collect();
+        bubbles.sort_by(|a, b| a.bubble_id.cmp(&b.bubble_id));
+
+        let canonical = Canonical {
+            composer_id: &self.composer_id,
+            title: &self.title,
+            created_at: self.created_at,
+            model_config: &self.model_config,
+            unified_mode: &self.unified_mode,
+            bubbles,
+        };
+        // Serializing plain structs to a Vec cannot fail
+        let bytes = serde_json::to_vec(&canonical).unwrap_or_default();
+
+        blake3::hash(&bytes).to_hex().to_string()
+    }
+
+    /// Terminal commands the agent ran, in message order.
+    pub fn terminal_commands(&self) -> impl Iterator<Item = &TerminalCommand> {
+        self.bubbles.iter().flat_map(|b| &b.commands)
+    }
+
     /// Get the first message text as a preview/title.
     #[must_use]
     pub fn preview(&self) -> &str {
@@ -134,46 +499,82 @@ impl Conversation {
             .map_or("[Empty conversation]", |b| b.text.as_str())
     }
 
-    /// Generate a clean title from the first user message.
+    /// Generate a clean `snake_case` title from the first user message.
+    ///
+    /// Prefers prose over pasted code, drops stop words (English, Portuguese
+    /// and Spanish) and keeps letters from any script.
     #[must_use]
     pub fn generate_title(&self) -> String {
-        // Find first user message
         let first_user_msg = self
             .bubbles
             .iter()
             .find(|b| b.bubble_type == BubbleType::User)
-            .map(|b| b.text.as_str())
-            .unwrap_or("conversa");
+            .map_or("", |b| b.text.as_str());
 
-        // Clean and extract meaningful words
-        let cleaned: String = first_user_msg
-            .chars()
-            .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-' || *c == '_')
+        let prose = prose_lines(first_user_msg);
+        let source = if prose.trim().is_empty() {
+            first_user_msg
+        } else {
+            &prose
+        };
+
+        // Symbols separate words, so `src/main.rs:12` becomes `src main rs 12`
+        let words: Vec<String> = source
+            .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+            .map(|w| w.trim_matches('-').to_lowercase())
+            .filter(|w| !w.is_empty())
             .collect();
 
-        // Take first ~50 chars, trim at word boundary
-        let truncated = if cleaned.len() > 50 {
-            let cut = &cleaned[..50];
-            cut.rfind(' ').map_or(cut, |i| &cut[..i])
+        let content: Vec<&String> = words
+            .iter()
+            .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+            .collect();
+        let chosen: Vec<&String> = if content.len() >= 2 {
+            content
         } else {
-            &cleaned
+            words.iter().collect()
         };
 
-        // Convert to snake_case filename
-        truncated
-            .split_whitespace()
-            .take(8) // max 8 words
-            .collect::<Vec<_>>()
-            .join("_")
-            .to_lowercase()
+        let mut title = String::new();
+        for word in chosen.into_iter().take(MAX_TITLE_WORDS) {
+            let extra = usize::from(!title.is_empty()) + word.chars().count();
+            if !title.is_empty() && title.chars().count() + extra > MAX_TITLE_CHARS {
+                break;
+            }
+            if !title.is_empty() {
+                title.push('_');
+            }
+            title.push_str(truncate_chars(word, MAX_TITLE_CHARS));
+        }
+
+        if title.is_empty() {
+            "conversa".to_string()
+        } else {
+            title
+        }
+    }
+
+    /// First characters of the composer ID, for display.
+    #[must_use]
+    pub fn short_id(&self) -> &str {
+        truncate_chars(&self.composer_id, 8)
     }
 
     /// Get a safe filename based on title.
     #[must_use]
     pub fn filename(&self) -> String {
-        let title = self.generate_title();
-        let short_id = &self.composer_id[..8.min(self.composer_id.len())];
-        format!("{short_id}_{title}")
+        format!("{}_{}", self.short_id(), self.generate_title())
+    }
+
+    /// Time of the latest message, or the creation time if no message
+    /// has one.
+    #[must_use]
+    pub fn last_activity(&self) -> Option<DateTime<Utc>> {
+        self.bubbles
+            .iter()
+            .filter_map(|b| b.created_at)
+            .max()
+            .or(self.created_at)
     }
 
     /// Get total message count.
@@ -182,6 +583,16 @@ impl Conversation {
         self.bubbles.len()
     }
 
+    /// Tokens used by all messages.
+    #[must_use]
+    pub fn token_count(&self) -> TokenCount {
+        let mut total = TokenCount::default();
+        for bubble in &self.bubbles {
+            total += &bubble.token_count;
+        }
+        total
+    }
+
     /// Get user message count.
     #[must_use]
     pub fn user_message_count(&self) -> usize {
@@ -199,6 +610,15 @@ impl Conversation {
             .filter(|b| b.bubble_type == BubbleType::Assistant)
             .count()
     }
+
+    /// Whether the conversation belongs to workspace `name` (ignoring ASCII
+    /// case). Conversations without a known workspace belong to none.
+    #[must_use]
+    pub fn in_workspace(&self, name: &str) -> bool {
+        self.workspace
+            .as_deref()
+            .is_some_and(|w| w.eq_ignore_ascii_case(name))
+    }
 }
 
 /// Summary statistics for extracted chats.
@@ -215,3 +635,121 @@ pub struct ExtractionStats {
     /// Database files scanned.
     pub databases_scanned: usize,
 }
+
+impl ExtractionStats {
+    /// Stats of `conversations`, a subset of the ones these stats count.
+    #[must_use]
+    pub fn of_subset(&self, conversations: &[Conversation]) -> Self {
+        Self {
+            conversation_count: conversations.len(),
+            total_bubbles: conversations.iter().map(|c| c.bubbles.len()).sum(),
+            user_messages: conversations
+                .iter()
+                .map(Conversation::user_message_count)
+                .sum(),
+            assistant_messages: conversations
+                .iter()
+                .map(Conversation::assistant_message_count)
+                .sum(),
+            databases_scanned: self.databases_scanned,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(first_message: &str) -> Conversation {
+        Conversation {
+            composer_id: "c1".to_string(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![Bubble {
+                bubble_id: "b1".to_string(),
+                bubble_type: BubbleType::User,
+                text: first_message.to_string(),
+                ..Default::default()
+            }],
+            git: None,
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_title() {
+        assert_eq!(
+            conversation("How do I fix the login bug in the parser?").generate_title(),
+            "fix_login_bug_parser"
+        );
+        assert_eq!(
+            conversation("Como faço para corrigir o erro de conexão com o banco?").generate_title(),
+            "faço_corrigir_erro_conexão_banco"
+        );
+        assert_eq!(
+            conversation("Explain this error:\n```\nfn main() { x.unwrap(); }\n```")
+                .generate_title(),
+            "explain_error"
+        );
+        assert_eq!(
+            conversation("/home/me/x.rs:12 error").generate_title(),
+            "home_x_rs_12_error"
+        );
+        assert_eq!(conversation("").generate_title(), "conversa");
+
+        let long = conversation(&"日本語のテキスト ".repeat(20)).generate_title();
+        assert!(long.chars().count() <= MAX_TITLE_CHARS);
+    }
+
+    #[test]
+    fn test_in_workspace() {
+        let mut conv = conversation("hello");
+        assert!(!conv.in_workspace("api"));
+
+        conv.workspace = Some("API".to_string());
+        assert!(conv.in_workspace("api"));
+        assert!(conv.in_workspace("API"));
+        assert!(!conv.in_workspace("api-client"));
+    }
+
+    #[test]
+    fn test_stats_of_subset() {
+        let stats = ExtractionStats {
+            conversation_count: 5,
+            total_bubbles: 40,
+            user_messages: 20,
+            assistant_messages: 20,
+            databases_scanned: 3,
+        };
+        let subset = [conversation("one"), conversation("two")];
+
+        let filtered = stats.of_subset(&subset);
+        assert_eq!(filtered.conversation_count, 2);
+        assert_eq!(filtered.total_bubbles, 2);
+        assert_eq!(
+            (filtered.user_messages, filtered.assistant_messages),
+            (2, 0)
+        );
+        assert_eq!(filtered.databases_scanned, 3);
+        assert_eq!(stats.of_subset(&[]).total_bubbles, 0);
+    }
+
+    #[test]
+    fn test_short_ids() {
+        let ids = ShortIds::new(["abcdef01", "abcdef99", "abd00000", "ffff0000"]);
+
+        assert_eq!(ids.get("abcdef01"), "abcdef0");
+        assert_eq!(ids.get("abcdef99"), "abcdef9");
+        assert_eq!(ids.get("abd00000"), "abd0");
+        assert_eq!(ids.get("ffff0000"), "ffff");
+        assert_eq!(ids.get("0123456789"), "01234567");
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("ação", 2), "aç");
+        assert_eq!(truncate_chars("abc", 10), "abc");
+    }
+}
@@ -3,11 +3,14 @@
 //! Contains types for managing synchronization state, configuration,
 //! and backup metadata for the auto-sync system.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::{AppError, Result};
+
 /// Configuration for the sync daemon.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
@@ -18,6 +21,29 @@ pub struct SyncConfig {
     /// Whether sync is enabled.
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+
+    /// Send a desktop notification when the daemon detects a Cursor reset.
+    #[serde(default)]
+    pub notify_on_reset: bool,
+
+    /// Send a desktop notification when a daemon sync or restore fails, or
+    /// storage stays over its limit.
+    #[serde(default)]
+    pub notify_on_error: bool,
+
+    /// Address the daemon serves `/events` on (same as `daemon --listen`).
+    #[serde(default)]
+    pub listen: Option<String>,
+
+    /// Bearer token required by the `listen` endpoints; generated on first
+    /// use.
+    #[serde(default)]
+    pub listen_token: Option<String>,
+
+    /// Origin browsers may read the `listen` endpoints from (CORS), e.g.
+    /// `http://localhost:3000`; none if unset.
+    #[serde(default)]
+    pub listen_origin: Option<String>,
 }
 
 impl Default for SyncConfig {
@@ -25,6 +51,11 @@ impl Default for SyncConfig {
         Self {
             interval_secs: default_interval(),
             enabled: default_enabled(),
+            notify_on_reset: false,
+            notify_on_error: false,
+            listen: None,
+            listen_token: None,
+            listen_origin: None,
         }
     }
 }
@@ -51,6 +82,14 @@ pub struct StorageConfig {
     /// Whether to compress backups.
     #[serde(default = "default_compression")]
     pub compression: bool,
+
+    /// Compress large message and thinking text with zstd when storing it.
+    #[serde(default)]
+    pub compress_text: bool,
+
+    /// Hours between daemon maintenance runs (0 disables maintenance).
+    #[serde(default = "default_maintenance_interval_hours")]
+    pub maintenance_interval_hours: u64,
 }
 
 impl Default for StorageConfig {
@@ -59,6 +98,8 @@ impl Default for StorageConfig {
             max_size_gb: default_max_size_gb(),
             backup_retention_days: default_retention_days(),
             compression: default_compression(),
+            compress_text: false,
+            maintenance_interval_hours: default_maintenance_interval_hours(),
         }
     }
 }
@@ -75,20 +116,384 @@ const fn default_compression() -> bool {
     true
 }
 
+const fn default_maintenance_interval_hours() -> u64 {
+    24 * 7 // weekly
+}
+
+/// Cursor release channel, each with its own configuration directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorChannel {
+    /// Regular Cursor releases.
+    #[default]
+    Stable,
+    /// Cursor Nightly builds.
+    Nightly,
+}
+
+impl CursorChannel {
+    /// All channels, stable first.
+    pub const ALL: [Self; 2] = [Self::Stable, Self::Nightly];
+
+    /// Name used in config and on the command line.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Nightly => "nightly",
+        }
+    }
+
+    /// Name of the application, as used for its directories.
+    #[must_use]
+    pub const fn app_name(self) -> &'static str {
+        match self {
+            Self::Stable => "Cursor",
+            Self::Nightly => "Cursor Nightly",
+        }
+    }
+}
+
+impl std::str::FromStr for CursorChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(Self::Stable),
+            "nightly" => Ok(Self::Nightly),
+            _ => Err(format!("Unknown channel: {s}. Use: stable, nightly")),
+        }
+    }
+}
+
 /// Path configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PathConfig {
     /// Base data directory.
     #[serde(default)]
     pub data_dir: Option<PathBuf>,
+
+    /// Cursor configuration directory (auto-detected if not set).
+    #[serde(default)]
+    pub cursor_dir: Option<PathBuf>,
+
+    /// Cursor channel whose directory is auto-detected.
+    #[serde(default)]
+    pub channel: CursorChannel,
 }
 
 impl Default for PathConfig {
     fn default() -> Self {
-        Self { data_dir: None }
+        Self {
+            data_dir: None,
+            cursor_dir: None,
+            channel: CursorChannel::Stable,
+        }
     }
 }
 
+/// OpenAI-compatible chat completion endpoint used for summaries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// Base URL of the API (`/chat/completions` is appended).
+    #[serde(default = "default_llm_endpoint")]
+    pub endpoint: String,
+
+    /// Model name sent with each request.
+    #[serde(default = "default_llm_model")]
+    pub model: String,
+
+    /// Environment variable holding the API key (empty for endpoints without auth).
+    #[serde(default = "default_llm_api_key_env")]
+    pub api_key_env: String,
+
+    /// Approximate token budget for the conversation text sent to the model.
+    #[serde(default = "default_llm_max_input_tokens")]
+    pub max_input_tokens: usize,
+
+    /// Mask e-mail addresses, API keys and passwords before sending.
+    #[serde(default = "default_llm_redact")]
+    pub redact: bool,
+
+    /// Request timeout in seconds.
+    #[serde(default = "default_llm_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: default_llm_endpoint(),
+            model: default_llm_model(),
+            api_key_env: default_llm_api_key_env(),
+            max_input_tokens: default_llm_max_input_tokens(),
+            redact: default_llm_redact(),
+            timeout_secs: default_llm_timeout_secs(),
+        }
+    }
+}
+
+fn default_llm_endpoint() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_llm_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_llm_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+const fn default_llm_max_input_tokens() -> usize {
+    12_000
+}
+
+const fn default_llm_redact() -> bool {
+    true
+}
+
+const fn default_llm_timeout_secs() -> u64 {
+    120
+}
+
+/// Where semantic index embeddings are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingBackend {
+    /// Built-in hashed word and character n-grams computed in-process; no
+    /// chat text leaves the machine, but only shared terms match.
+    #[default]
+    Hashed,
+    /// A sentence-transformer model read from `model_dir` and run
+    /// in-process; needs the `local-model` feature.
+    Local,
+    /// The `/embeddings` route of an OpenAI-compatible endpoint.
+    Api,
+}
+
+/// Semantic index configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    /// Embedding backend.
+    #[serde(default)]
+    pub backend: EmbeddingBackend,
+
+    /// Endpoint of the API backend (defaults to `llm.endpoint`).
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Environment variable holding the API backend key (defaults to `llm.api_key_env`).
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Model name requested from the API backend.
+    #[serde(default = "default_embedding_api_model")]
+    pub api_model: String,
+
+    /// Directory of the local backend's model (`config.json`,
+    /// `tokenizer.json`, `model.safetensors`).
+    #[serde(default)]
+    pub model_dir: Option<PathBuf>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            backend: EmbeddingBackend::default(),
+            endpoint: None,
+            api_key_env: None,
+            api_model: default_embedding_api_model(),
+            model_dir: None,
+        }
+    }
+}
+
+fn default_embedding_api_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+/// Conversation extraction settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractConfig {
+    /// Approximate memory for extracted conversations, in megabytes (0 = unlimited).
+    ///
+    /// Beyond it, `sync` and `export` process conversations in batches.
+    #[serde(default)]
+    pub max_memory_mb: u64,
+
+    /// Keep parsed conversations on disk between commands.
+    #[serde(default = "default_enabled")]
+    pub cache: bool,
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_mb: 0,
+            cache: default_enabled(),
+        }
+    }
+}
+
+/// Systemd unit settings (`[service]`), applied by `sync start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    /// Memory limit of the service (`MemoryMax=`).
+    #[serde(default = "default_memory_max")]
+    pub memory_max: String,
+
+    /// CPU limit of the service (`CPUQuota=`).
+    #[serde(default = "default_cpu_quota")]
+    pub cpu_quota: String,
+
+    /// Scheduling priority (`Nice=`, from -20 to 19).
+    #[serde(default)]
+    pub nice: Option<i32>,
+
+    /// Seconds before a failed daemon is restarted (`RestartSec=`).
+    #[serde(default = "default_restart_sec")]
+    pub restart_sec: u64,
+
+    /// Target that starts the daemon (`WantedBy=`).
+    #[serde(default = "default_wanted_by")]
+    pub wanted_by: String,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            memory_max: default_memory_max(),
+            cpu_quota: default_cpu_quota(),
+            nice: None,
+            restart_sec: default_restart_sec(),
+            wanted_by: default_wanted_by(),
+        }
+    }
+}
+
+fn default_memory_max() -> String {
+    "256M".to_string()
+}
+
+fn default_cpu_quota() -> String {
+    "25%".to_string()
+}
+
+const fn default_restart_sec() -> u64 {
+    30
+}
+
+fn default_wanted_by() -> String {
+    "default.target".to_string()
+}
+
+/// Safety settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SafetyConfig {
+    /// Refuse to write to Cursor's database or run system-modifying commands
+    /// (restore, delete, reset, service install).
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// Export settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// File of `from → to` replacements (internal paths, project names)
+    /// applied to exported conversations.
+    #[serde(default)]
+    pub path_map: Option<PathBuf>,
+
+    /// Have the daemon export the conversations changed by each sync.
+    #[serde(default)]
+    pub auto: bool,
+
+    /// Directory of automatic exports (default: the exports directory).
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+
+    /// Format of automatic exports: `markdown`, `json` or `table`.
+    #[serde(default = "default_export_format")]
+    pub format: String,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            path_map: None,
+            auto: false,
+            dir: None,
+            format: default_export_format(),
+        }
+    }
+}
+
+fn default_export_format() -> String {
+    "markdown".to_string()
+}
+
+/// Scripts run on daemon events (`[hooks]`), with a JSON payload on stdin.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[allow(clippy::struct_field_names)]
+pub struct HooksConfig {
+    /// Run after a sync that wrote changes.
+    #[serde(default)]
+    pub on_sync_complete: Option<String>,
+
+    /// Run after conversations were restored to Cursor.
+    #[serde(default)]
+    pub on_restore: Option<String>,
+
+    /// Run once for each conversation synced for the first time.
+    #[serde(default)]
+    pub on_new_conversation: Option<String>,
+}
+
+/// External output format (`[formatters.<name>]`, used as `-f <name>`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatterPlugin {
+    /// Executable reading the conversations JSON on stdin and writing the
+    /// formatted output to stdout.
+    pub command: String,
+
+    /// Arguments passed to the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// File extension used by `export-all`.
+    #[serde(default = "default_plugin_extension")]
+    pub extension: String,
+}
+
+fn default_plugin_extension() -> String {
+    "txt".into()
+}
+
+/// Settings overridden by a named profile (`[profile.<name>]`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProfileConfig {
+    /// Data directory for this profile.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Cursor configuration directory for this profile.
+    #[serde(default)]
+    pub cursor_dir: Option<PathBuf>,
+
+    /// Cursor channel for this profile.
+    #[serde(default)]
+    pub channel: Option<CursorChannel>,
+
+    /// Maximum storage size in gigabytes.
+    #[serde(default)]
+    pub max_size_gb: Option<u64>,
+
+    /// Number of days to retain backups.
+    #[serde(default)]
+    pub backup_retention_days: Option<u32>,
+}
+
 /// Complete application configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
@@ -103,9 +508,97 @@ pub struct AppConfig {
     /// Path configuration.
     #[serde(default)]
     pub paths: PathConfig,
+
+    /// LLM endpoint for summaries.
+    #[serde(default)]
+    pub llm: LlmConfig,
+
+    /// Semantic index settings.
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+
+    /// Extraction settings.
+    #[serde(default)]
+    pub extract: ExtractConfig,
+
+    /// Safety settings.
+    #[serde(default)]
+    pub safety: SafetyConfig,
+
+    /// Systemd unit settings.
+    #[serde(default)]
+    pub service: ServiceConfig,
+
+    /// Export settings.
+    #[serde(default)]
+    pub export: ExportConfig,
+
+    /// Hook scripts.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// External formatters, by format name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub formatters: BTreeMap<String, FormatterPlugin>,
+
+    /// Named profiles that override the settings above.
+    #[serde(
+        default,
+        rename = "profile",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
 impl AppConfig {
+    /// Apply the overrides of a named profile.
+    ///
+    /// A profile named after a channel other than stable (`nightly`) is
+    /// built in: it reads that channel into its own data directory. Fields
+    /// a `[profile.nightly]` section leaves out keep those defaults.
+    ///
+    /// # Errors
+    /// Returns error if no profile with that name is defined.
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let channel = name
+            .parse::<CursorChannel>()
+            .ok()
+            .filter(|c| *c != CursorChannel::Stable);
+        let profile = match (self.profiles.get(name).cloned(), channel) {
+            (Some(profile), _) => profile,
+            (None, Some(_)) => ProfileConfig::default(),
+            (None, None) => {
+                let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                return Err(AppError::Config {
+                    message: format!("Unknown profile '{name}'. Defined profiles: {known:?}"),
+                });
+            }
+        };
+
+        if let Some(channel) = channel {
+            self.paths.data_dir = Some(Self::default_data_dir().join(channel.as_str()));
+            self.paths.cursor_dir = None;
+            self.paths.channel = channel;
+        }
+        if profile.data_dir.is_some() {
+            self.paths.data_dir = profile.data_dir;
+        }
+        if profile.cursor_dir.is_some() {
+            self.paths.cursor_dir = profile.cursor_dir;
+        }
+        if let Some(channel) = profile.channel {
+            self.paths.channel = channel;
+        }
+        if let Some(max_size_gb) = profile.max_size_gb {
+            self.storage.max_size_gb = max_size_gb;
+        }
+        if let Some(days) = profile.backup_retention_days {
+            self.storage.backup_retention_days = days;
+        }
+
+        Ok(self)
+    }
+
     /// Get the data directory, using default if not configured.
     #[must_use]
     pub fn data_dir(&self) -> PathBuf {
@@ -141,12 +634,33 @@ impl AppConfig {
         self.data_dir().join("sync.lock")
     }
 
+    /// Get the path of the last quick menu numbering.
+    #[must_use]
+    pub fn quick_menu_path(&self) -> PathBuf {
+        self.data_dir().join("quick_menu.json")
+    }
+
+    /// Get the path of the parsed-conversation cache.
+    #[must_use]
+    pub fn conversation_cache_path(&self) -> PathBuf {
+        self.data_dir().join("cache").join("conversations.json.zst")
+    }
+
     /// Get the exports directory path.
     #[must_use]
     pub fn exports_dir(&self) -> PathBuf {
         self.data_dir().join("exports")
     }
 
+    /// Directory the daemon exports changed conversations to.
+    #[must_use]
+    pub fn auto_export_dir(&self) -> PathBuf {
+        self.export
+            .dir
+            .clone()
+            .unwrap_or_else(|| self.exports_dir())
+    }
+
     /// Get the backups directory path.
     #[must_use]
     pub fn backups_dir(&self) -> PathBuf {
@@ -158,15 +672,24 @@ impl AppConfig {
     pub const fn max_storage_bytes(&self) -> u64 {
         self.storage.max_size_gb * 1024 * 1024 * 1024
     }
+
+    /// Extraction memory budget in bytes, if limited.
+    #[must_use]
+    pub fn memory_budget_bytes(&self) -> Option<usize> {
+        match self.extract.max_memory_mb {
+            0 => None,
+            mb => usize::try_from(mb.saturating_mul(1024 * 1024)).ok(),
+        }
+    }
 }
 
 /// Current state of synchronization.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SyncState {
     /// Last successful sync timestamp.
     pub last_sync: Option<DateTime<Utc>>,
 
-    /// Hash of last synced data (for change detection).
+    /// Fingerprint of Cursor's database at the last sync (for change detection).
     pub last_hash: Option<String>,
 
     /// Number of conversations synced.
@@ -183,20 +706,9 @@ pub struct SyncState {
 
     /// Last error message if any.
     pub last_error: Option<String>,
-}
 
-impl Default for SyncState {
-    fn default() -> Self {
-        Self {
-            last_sync: None,
-            last_hash: None,
-            conversation_count: 0,
-            message_count: 0,
-            storage_bytes: 0,
-            is_syncing: false,
-            last_error: None,
-        }
-    }
+    /// Cursor version installed at the last sync, if detected.
+    pub cursor_version: Option<String>,
 }
 
 impl SyncState {
@@ -237,6 +749,254 @@ impl SyncState {
     }
 }
 
+/// A Cursor reset detected by comparing local storage with Cursor's database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetEvent {
+    /// When the reset was detected.
+    pub detected_at: DateTime<Utc>,
+
+    /// Conversations in local storage at detection time.
+    pub local_conversations: usize,
+
+    /// Conversations left in Cursor's database at detection time.
+    pub cursor_conversations: usize,
+
+    /// Whether the conversations were restored automatically.
+    pub restored: bool,
+
+    /// Why the automatic restore failed, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restore_error: Option<String>,
+}
+
+impl ResetEvent {
+    /// Create a reset event detected now.
+    #[must_use]
+    pub fn new(local_conversations: usize, cursor_conversations: usize) -> Self {
+        Self {
+            detected_at: Utc::now(),
+            local_conversations,
+            cursor_conversations,
+            restored: false,
+            restore_error: None,
+        }
+    }
+
+    /// Mark as restored.
+    #[must_use]
+    pub const fn restored(mut self) -> Self {
+        self.restored = true;
+        self
+    }
+
+    /// Mark the automatic restore as failed with `error`.
+    #[must_use]
+    pub fn restore_failed(mut self, error: &impl std::fmt::Display) -> Self {
+        self.restore_error = Some(error.to_string());
+        self
+    }
+
+    /// Number of conversations that vanished from Cursor.
+    #[must_use]
+    pub const fn vanished(&self) -> usize {
+        self.local_conversations
+            .saturating_sub(self.cursor_conversations)
+    }
+}
+
+/// Kind of operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    /// Cursor to local storage sync.
+    Sync,
+    /// Local storage to Cursor restore.
+    Restore,
+    /// Conversation erased from Cursor.
+    Delete,
+    /// Conversation archived, optionally erased from Cursor.
+    Archive,
+    /// Old backups or exports removed.
+    Prune,
+    /// Exported conversations imported into local storage.
+    Import,
+    /// Conversations merged into one.
+    Merge,
+    /// Storage snapshot written.
+    Backup,
+    /// Cursor reset.
+    Reset,
+}
+
+impl OperationKind {
+    /// Name stored in the database and shown to the user.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sync => "sync",
+            Self::Restore => "restore",
+            Self::Delete => "delete",
+            Self::Archive => "archive",
+            Self::Prune => "prune",
+            Self::Import => "import",
+            Self::Merge => "merge",
+            Self::Backup => "backup",
+            Self::Reset => "reset",
+        }
+    }
+}
+
+impl std::str::FromStr for OperationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "sync" => Ok(Self::Sync),
+            "restore" => Ok(Self::Restore),
+            "delete" => Ok(Self::Delete),
+            "archive" => Ok(Self::Archive),
+            "prune" => Ok(Self::Prune),
+            "import" => Ok(Self::Import),
+            "merge" => Ok(Self::Merge),
+            "backup" => Ok(Self::Backup),
+            "reset" => Ok(Self::Reset),
+            _ => Err(format!(
+                "unknown operation '{s}' (sync, restore, delete, archive, prune, import, merge, backup, reset)"
+            )),
+        }
+    }
+}
+
+/// An entry of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// What was done.
+    pub kind: OperationKind,
+
+    /// When it started.
+    pub started_at: DateTime<Utc>,
+
+    /// How long it took.
+    pub duration_ms: u64,
+
+    /// Items affected (conversations, files, ...).
+    pub items: usize,
+
+    /// Whether it completed.
+    pub success: bool,
+
+    /// Summary, or the error when it failed.
+    pub details: String,
+}
+
+impl Operation {
+    /// A completed operation that started at `started_at`.
+    #[must_use]
+    pub fn succeeded(
+        kind: OperationKind,
+        started_at: DateTime<Utc>,
+        items: usize,
+        details: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            started_at,
+            duration_ms: elapsed_ms(started_at),
+            items,
+            success: true,
+            details: details.into(),
+        }
+    }
+
+    /// A failed operation that started at `started_at`.
+    #[must_use]
+    pub fn failed(
+        kind: OperationKind,
+        started_at: DateTime<Utc>,
+        error: &impl std::fmt::Display,
+    ) -> Self {
+        Self {
+            kind,
+            started_at,
+            duration_ms: elapsed_ms(started_at),
+            items: 0,
+            success: false,
+            details: error.to_string(),
+        }
+    }
+}
+
+fn elapsed_ms(since: DateTime<Utc>) -> u64 {
+    u64::try_from((Utc::now() - since).num_milliseconds()).unwrap_or_default()
+}
+
+/// How a conversation changed in a sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeType {
+    /// First time the conversation was synced.
+    Created,
+    /// Synced before, with different content.
+    Updated,
+}
+
+impl ChangeType {
+    /// Name stored in the database.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+        }
+    }
+}
+
+/// An entry of the change feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    /// Composer ID.
+    pub composer_id: String,
+
+    /// Conversation title.
+    pub title: String,
+
+    /// What happened.
+    pub kind: ChangeType,
+
+    /// When the sync that picked up the change ran.
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Numbers shown by the last `quick` listing, so `open N` targets the same chat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickMenu {
+    /// When the listing was printed.
+    pub generated_at: DateTime<Utc>,
+
+    /// Composer IDs in listing order (number 1 first).
+    pub composer_ids: Vec<String>,
+}
+
+impl QuickMenu {
+    /// Create a menu from IDs in listing order.
+    #[must_use]
+    pub fn new(composer_ids: Vec<String>) -> Self {
+        Self {
+            generated_at: Utc::now(),
+            composer_ids,
+        }
+    }
+
+    /// Composer ID listed as `number` (1-based).
+    #[must_use]
+    pub fn get(&self, number: usize) -> Option<&str> {
+        number
+            .checked_sub(1)
+            .and_then(|i| self.composer_ids.get(i))
+            .map(String::as_str)
+    }
+}
+
 /// Metadata for a backup file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupMetadata {
@@ -252,9 +1012,21 @@ pub struct BackupMetadata {
     /// Number of conversations in this backup.
     pub conversation_count: usize,
 
-    /// Hash of backup contents.
+    /// Number of messages in this backup.
+    #[serde(default)]
+    pub message_count: usize,
+
+    /// blake3 hash of the backup file; empty when unknown.
     pub content_hash: String,
 
+    /// Host name of the machine the backup was taken on.
+    #[serde(default)]
+    pub source_machine: Option<String>,
+
+    /// Version of cursor-chat-handler that wrote the backup.
+    #[serde(default)]
+    pub app_version: Option<String>,
+
     /// Whether backup is compressed.
     pub is_compressed: bool,
 
@@ -277,12 +1049,22 @@ impl BackupMetadata {
             created_at: Utc::now(),
             size_bytes,
             conversation_count,
+            message_count: 0,
             content_hash: content_hash.into(),
+            source_machine: None,
+            app_version: None,
             is_compressed: false,
             file_path,
         }
     }
 
+    /// Whether the counts and hash come from a metadata sidecar, rather
+    /// than being unknown (backups from older versions).
+    #[must_use]
+    pub const fn has_details(&self) -> bool {
+        !self.content_hash.is_empty()
+    }
+
     /// Mark as compressed.
     #[must_use]
     pub const fn compressed(mut self) -> Self {
@@ -312,12 +1094,10 @@ pub struct WorkspaceInfo {
 }
 
 impl WorkspaceInfo {
-    /// Create workspace info from a URI like "file:///path/to/project".
+    /// Create workspace info from a URI like `file:///path/to/project`.
     #[must_use]
     pub fn from_uri(uri: &str) -> Self {
-        let path = uri
-            .strip_prefix("file://")
-            .map(PathBuf::from);
+        let path = uri.strip_prefix("file://").map(PathBuf::from);
 
         let name = path
             .as_ref()
@@ -354,6 +1134,36 @@ impl WorkspaceInfo {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_with_profile_overrides() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [storage]
+            max_size_gb = 10
+
+            [profile.work]
+            data_dir = "/data/work"
+            backup_retention_days = 90
+            "#,
+        )
+        .unwrap();
+
+        let work = config.clone().with_profile("work").unwrap();
+        assert_eq!(work.data_dir(), PathBuf::from("/data/work"));
+        assert_eq!(work.storage.backup_retention_days, 90);
+        assert_eq!(work.storage.max_size_gb, 10);
+
+        assert!(config.clone().with_profile("personal").is_err());
+
+        // Built in, with its own data directory
+        let nightly = config.with_profile("nightly").unwrap();
+        assert_eq!(nightly.paths.channel, CursorChannel::Nightly);
+        assert_eq!(
+            nightly.data_dir(),
+            AppConfig::default_data_dir().join("nightly")
+        );
+    }
+
     #[test]
     fn test_default_config() {
         let config = AppConfig::default();
@@ -364,10 +1174,7 @@ mod tests {
 
     #[test]
     fn test_sync_state_transitions() {
-        let state = SyncState::default()
-            .syncing()
-            .with_sync_time()
-            .completed();
+        let state = SyncState::default().syncing().with_sync_time().completed();
 
         assert!(!state.is_syncing);
         assert!(state.last_sync.is_some());
@@ -377,24 +1184,24 @@ mod tests {
     fn test_workspace_from_uri() {
         let ws = WorkspaceInfo::from_uri("file:///home/user/projects/my-app");
         assert_eq!(ws.name, "my-app");
-        assert_eq!(
-            ws.path,
-            Some(PathBuf::from("/home/user/projects/my-app"))
-        );
+        assert_eq!(ws.path, Some(PathBuf::from("/home/user/projects/my-app")));
+    }
+
+    #[test]
+    fn test_reset_event_vanished() {
+        let event = ResetEvent::new(40, 3);
+        assert_eq!(event.vanished(), 37);
+        assert!(!event.restored);
+        assert!(event.restored().restored);
+        assert_eq!(ResetEvent::new(0, 5).vanished(), 0);
     }
 
     #[test]
     fn test_backup_expiry() {
-        let backup = BackupMetadata::new(
-            "test",
-            1000,
-            5,
-            "abc123",
-            PathBuf::from("/tmp/backup.db"),
-        );
+        let backup =
+            BackupMetadata::new("test", 1000, 5, "abc123", PathBuf::from("/tmp/backup.db"));
 
         // Fresh backup should not be expired
         assert!(!backup.is_expired(30));
     }
 }
-
@@ -0,0 +1,334 @@
+//! gRPC server (`cursor-chat grpc`, `grpc` feature).
+//!
+//! Serves the RPCs of `proto/cursor_chat.proto` over the same conversations
+//! the CLI reads; each request reloads them (through the parse cache), so
+//! results follow Cursor without restarting the server.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::application::{find_conversation, write_conversations, OutputFormat};
+use crate::domain::{truncate_chars, AppError, Bubble, BubbleType, Conversation, Result, ShortIds};
+
+/// Code generated from `proto/cursor_chat.proto`.
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+pub mod proto {
+    tonic::include_proto!("cursor_chat.v1");
+}
+
+use proto::cursor_chat_server::{CursorChat, CursorChatServer};
+
+/// Characters of context kept on each side of a search match.
+const SNIPPET_CONTEXT: usize = 60;
+
+type Loader = dyn Fn() -> Result<Vec<Conversation>> + Send + Sync;
+
+/// Implementation of the `CursorChat` service.
+pub struct ChatService {
+    load: Arc<Loader>,
+}
+
+impl ChatService {
+    /// Service answering from the conversations returned by `load`.
+    pub fn new(load: impl Fn() -> Result<Vec<Conversation>> + Send + Sync + 'static) -> Self {
+        Self {
+            load: Arc::new(load),
+        }
+    }
+
+    async fn conversations(&self) -> std::result::Result<Vec<Conversation>, Status> {
+        let load = Arc::clone(&self.load);
+        tokio::task::spawn_blocking(move || load())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(to_status)
+    }
+}
+
+/// Serve `service` on `addr` until the process is stopped.
+///
+/// # Errors
+/// Returns error if the runtime cannot start or the address cannot be bound.
+pub fn serve(addr: SocketAddr, service: ChatService) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| AppError::io("Failed to start async runtime", e))?;
+
+    runtime
+        .block_on(
+            tonic::transport::Server::builder()
+                .add_service(CursorChatServer::new(service))
+                .serve(addr),
+        )
+        .map_err(|e| AppError::Config {
+            message: format!("gRPC server on {addr} failed: {e}"),
+        })
+}
+
+#[tonic::async_trait]
+impl CursorChat for ChatService {
+    async fn list_conversations(
+        &self,
+        request: Request<proto::ListConversationsRequest>,
+    ) -> std::result::Result<Response<proto::ListConversationsResponse>, Status> {
+        let request = request.into_inner();
+        let min_messages = request.min_messages as usize;
+        let conversations = self
+            .conversations()
+            .await?
+            .iter()
+            .filter(|c| c.bubbles.len() >= min_messages)
+            .take(limit(request.limit))
+            .map(summary)
+            .collect();
+
+        Ok(Response::new(proto::ListConversationsResponse {
+            conversations,
+        }))
+    }
+
+    async fn get_conversation(
+        &self,
+        request: Request<proto::GetConversationRequest>,
+    ) -> std::result::Result<Response<proto::Conversation>, Status> {
+        let conversations = self.conversations().await?;
+        let conv =
+            find_conversation(&conversations, &request.into_inner().id).map_err(to_status)?;
+
+        Ok(Response::new(proto::Conversation {
+            summary: Some(summary(conv)),
+            messages: conv.bubbles.iter().map(message).collect(),
+        }))
+    }
+
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> std::result::Result<Response<proto::SearchResponse>, Status> {
+        let request = request.into_inner();
+        let query = request.query.trim().to_lowercase();
+        if query.is_empty() {
+            return Err(Status::invalid_argument("query is empty"));
+        }
+
+        let conversations = self.conversations().await?;
+        let hits = conversations
+            .iter()
+            .flat_map(|conv| search_conversation(conv, &query))
+            .take(limit(request.limit))
+            .collect();
+
+        Ok(Response::new(proto::SearchResponse { hits }))
+    }
+
+    async fn export(
+        &self,
+        request: Request<proto::ExportRequest>,
+    ) -> std::result::Result<Response<proto::ExportResponse>, Status> {
+        let request = request.into_inner();
+        let format = match request.format() {
+            proto::ExportFormat::Markdown => OutputFormat::Markdown,
+            proto::ExportFormat::Json => OutputFormat::Json,
+        };
+
+        let conversations = self.conversations().await?;
+        let selected: Vec<Conversation> = if request.ids.is_empty() {
+            conversations
+        } else {
+            request
+                .ids
+                .iter()
+                .map(|id| find_conversation(&conversations, id).cloned())
+                .collect::<Result<_>>()
+                .map_err(to_status)?
+        };
+
+        let mut content = Vec::new();
+        write_conversations(&mut content, &selected, format, &ShortIds::default())
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(proto::ExportResponse {
+            content: String::from_utf8_lossy(&content).into_owned(),
+            conversations: u32::try_from(selected.len()).unwrap_or(u32::MAX),
+        }))
+    }
+}
+
+fn to_status(error: AppError) -> Status {
+    match error {
+        AppError::InvalidData { message } => Status::not_found(message),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+/// Request limit, where 0 means no limit.
+const fn limit(requested: u32) -> usize {
+    if requested == 0 {
+        usize::MAX
+    } else {
+        requested as usize
+    }
+}
+
+fn summary(conv: &Conversation) -> proto::ConversationSummary {
+    proto::ConversationSummary {
+        composer_id: conv.composer_id.clone(),
+        title: conv.title.clone(),
+        created_at_ms: conv.created_at.map(|t| t.timestamp_millis()),
+        message_count: u32::try_from(conv.bubbles.len()).unwrap_or(u32::MAX),
+        model: conv.model_config.model_name.clone(),
+    }
+}
+
+fn message(bubble: &Bubble) -> proto::Message {
+    let role = match bubble.bubble_type {
+        BubbleType::User => proto::Role::User,
+        BubbleType::Assistant => proto::Role::Assistant,
+        BubbleType::Unknown => proto::Role::Unknown,
+    };
+
+    proto::Message {
+        bubble_id: bubble.bubble_id.clone(),
+        role: role.into(),
+        text: bubble.text.clone(),
+        created_at_ms: bubble.created_at.map(|t| t.timestamp_millis()),
+        input_tokens: bubble.token_count.input_tokens,
+        output_tokens: bubble.token_count.output_tokens,
+    }
+}
+
+/// Hits for `query` (already lowercase) in the title and messages of `conv`.
+fn search_conversation(conv: &Conversation, query: &str) -> Vec<proto::SearchHit> {
+    let title_hit = conv
+        .title
+        .to_lowercase()
+        .contains(query)
+        .then(|| (String::new(), conv.title.clone()));
+    let message_hits = conv
+        .bubbles
+        .iter()
+        .filter_map(|b| snippet(&b.text, query).map(|s| (b.bubble_id.clone(), s)));
+
+    title_hit
+        .into_iter()
+        .chain(message_hits)
+        .map(|(bubble_id, snippet)| proto::SearchHit {
+            conversation: Some(summary(conv)),
+            bubble_id,
+            snippet,
+        })
+        .collect()
+}
+
+/// The text around the first match of `query`, if any.
+///
+/// Matches on `text` itself: lowercasing can change the number of chars
+/// (`İ` becomes two), so positions in a lowercased copy are off.
+fn snippet(text: &str, query: &str) -> Option<String> {
+    let pos = text
+        .char_indices()
+        .position(|(i, _)| starts_with_lowercase(&text[i..], query))?;
+    let start = pos.saturating_sub(SNIPPET_CONTEXT);
+    let rest: String = text.chars().skip(start).collect();
+    let len = SNIPPET_CONTEXT * 2 + query.chars().count();
+
+    Some(truncate_chars(&rest, len).trim().to_string())
+}
+
+/// Whether `text`, lowercased, starts with `query` (already lowercase).
+fn starts_with_lowercase(text: &str, query: &str) -> bool {
+    let mut lower = text.chars().flat_map(char::to_lowercase);
+    query.chars().all(|q| lower.next() == Some(q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ModelConfig;
+
+    fn conversation() -> Conversation {
+        let bubble = |id: &str, bubble_type, text: &str| Bubble {
+            bubble_id: id.into(),
+            bubble_type,
+            text: text.into(),
+            ..Default::default()
+        };
+        Conversation {
+            composer_id: "abc123".into(),
+            title: "Fix the parser".into(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: vec![
+                bubble("b1", BubbleType::User, "The Parser panics on empty input"),
+                bubble("b2", BubbleType::Assistant, "Return an error instead."),
+            ],
+            git: None,
+            workspace: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_rpcs() {
+        let service = ChatService::new(|| Ok(vec![conversation()]));
+
+        let list = service
+            .list_conversations(Request::new(proto::ListConversationsRequest {
+                limit: 0,
+                min_messages: 2,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(list.conversations[0].message_count, 2);
+
+        let conv = service
+            .get_conversation(Request::new(proto::GetConversationRequest {
+                id: "abc".into(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(conv.messages[1].role(), proto::Role::Assistant);
+
+        let missing = service
+            .get_conversation(Request::new(proto::GetConversationRequest {
+                id: "zzz".into(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        let search = service
+            .search(Request::new(proto::SearchRequest {
+                query: "PARSER".into(),
+                limit: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let found: Vec<&str> = search.hits.iter().map(|h| h.bubble_id.as_str()).collect();
+        assert_eq!(found, ["", "b1"]);
+
+        let export = service
+            .export(Request::new(proto::ExportRequest {
+                ids: vec!["abc".into()],
+                format: proto::ExportFormat::Json.into(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(export.conversations, 1);
+        assert!(export.content.contains("\"abc123\""));
+    }
+
+    #[test]
+    fn test_snippet_after_chars_that_grow_when_lowercased() {
+        let text = format!("{} Parser panics", "İ".repeat(SNIPPET_CONTEXT * 2));
+
+        let snippet = snippet(&text, "parser").unwrap();
+        assert!(snippet.ends_with("Parser panics"));
+        assert!(snippet.starts_with('İ'));
+    }
+}
@@ -0,0 +1,67 @@
+//! System clipboard access.
+//!
+//! Pipes text into the platform clipboard tool: `pbcopy` on macOS,
+//! `wl-copy`, `xclip` or `xsel` on Linux.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::domain::{AppError, Result};
+
+/// Clipboard commands tried in order on Linux.
+const LINUX_CLIPBOARD_COMMANDS: [&[&str]; 3] = [
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copy `text` to the system clipboard.
+///
+/// # Errors
+/// Returns error if no clipboard tool is available or it fails.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let candidates: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else {
+        &LINUX_CLIPBOARD_COMMANDS
+    };
+
+    for command in candidates {
+        match pipe_to(command, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => tracing::debug!(command = command[0], error = %e, "Clipboard tool failed"),
+        }
+    }
+
+    Err(AppError::Config {
+        message: "No clipboard tool worked; install wl-clipboard, xclip or xsel".into(),
+    })
+}
+
+/// Run `command` with `text` on its stdin.
+fn pipe_to(command: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::io("Failed to start clipboard tool", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| AppError::io("Failed to write to clipboard tool", e))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| AppError::io("Failed to wait for clipboard tool", e))?;
+    if !status.success() {
+        return Err(AppError::Config {
+            message: format!("{} exited with {status}", command[0]),
+        });
+    }
+
+    Ok(())
+}
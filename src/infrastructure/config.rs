@@ -3,10 +3,23 @@
 //! Handles loading and saving TOML configuration files.
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use crate::domain::{AppConfig, AppError, Result};
 
+/// Environment variable selecting a profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "CURSOR_CHAT_PROFILE";
+
+/// Profile chosen for this process, applied by [`load_config`].
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// `--max-memory` given for this process, applied by [`load_config`].
+static MAX_MEMORY_OVERRIDE: OnceLock<u64> = OnceLock::new();
+
+/// `--path-map` given for this process, applied by [`load_config`].
+static PATH_MAP_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
 /// Default configuration file content.
 const DEFAULT_CONFIG: &str = r#"# Cursor Chat Handler Configuration
 # Auto-generated - edit as needed
@@ -18,6 +31,20 @@ interval_secs = 120
 # Whether sync is enabled
 enabled = true
 
+# Desktop notification when the daemon detects a Cursor reset
+notify_on_reset = false
+
+# Desktop notification when a daemon sync or restore fails, or storage stays
+# over max_size_gb. Sent once per failure until it clears.
+notify_on_error = false
+
+# Serve synced conversations/messages as server-sent events on GET /events
+# listen = "127.0.0.1:7878"
+# Bearer token the listener requires (generated on the first --listen)
+# listen_token = "..."
+# Origin a browser dashboard may read it from (CORS); none by default
+# listen_origin = "http://localhost:3000"
+
 [storage]
 # Maximum storage size in GB (default: 10)
 max_size_gb = 10
@@ -28,16 +55,186 @@ backup_retention_days = 30
 # Whether to compress backups
 compression = true
 
+# Compress large message/thinking text stored in storage.db with zstd
+compress_text = false
+
+# Hours between maintenance runs in the daemon: WAL checkpoint, optimize,
+# orphan purge and backup rotation (default: 168 = weekly, 0 = disabled)
+maintenance_interval_hours = 168
+
+[extract]
+# Approximate memory for extracted conversations in MB (0 = unlimited).
+# Beyond it, sync and export process conversations in batches instead of
+# loading the whole history at once. Overridden by --max-memory.
+max_memory_mb = 0
+
+# Keep parsed conversations in cache/ so consecutive commands skip re-reading
+# Cursor's database while it is unchanged.
+cache = true
+
+[export]
+# File of replacements applied to exported conversations, one per line as
+# "from → to" (or "from => to"), so shared transcripts hide internal paths
+# and project names. Overridden by --path-map.
+# path_map = "/home/me/.cursor-chat-handler/path-map.txt"
+
+# Have the daemon export the conversations changed by each sync, keeping a
+# readable mirror current. Each conversation's file is replaced on change.
+auto = false
+
+# Directory of automatic exports (default: <data_dir>/exports)
+# dir = "/home/me/Documents/cursor-chats"
+
+# Format of automatic exports: markdown, json or table
+format = "markdown"
+
+[safety]
+# Never write to Cursor's database or run system-modifying commands
+# (restore, delete, reset, sync start/stop). Same as --read-only.
+read_only = false
+
+[service]
+# Systemd unit written by `sync start` (rerun it after changing these)
+memory_max = "256M"
+cpu_quota = "25%"
+# nice = 10
+restart_sec = 30
+wanted_by = "default.target"
+
 [paths]
 # Custom data directory (optional, defaults to ~/.cursor-chat-handler)
 # data_dir = "/custom/path"
+
+# Cursor config directory (optional, auto-detected)
+# cursor_dir = "/home/me/.config/Cursor"
+
+# Cursor channel to auto-detect: "stable" or "nightly"
+# channel = "stable"
+
+[llm]
+# OpenAI-compatible endpoint used by `summarize` (local servers such as
+# Ollama or llama.cpp work too, e.g. "http://localhost:11434/v1")
+endpoint = "https://api.openai.com/v1"
+model = "gpt-4o-mini"
+
+# Environment variable holding the API key (set to "" if none is needed)
+api_key_env = "OPENAI_API_KEY"
+
+# Approximate tokens of conversation text sent per request; older messages
+# beyond this are left out
+max_input_tokens = 12000
+
+# Mask e-mails, API keys and passwords before sending
+redact = true
+
+timeout_secs = 120
+
+[embeddings]
+# Where the semantic index (`index build`, `index search`) computes embeddings:
+#   "hashed" - built-in hashed word/character n-grams, computed in-process;
+#              no chat text leaves the machine. A bag of words: it matches
+#              shared terms, not meaning
+#   "local"  - a sentence-transformer (e.g. all-MiniLM-L6-v2) read from model_dir
+#              and run in-process; matches meaning, no chat text leaves the
+#              machine. Needs a build with --features local-model
+#   "api"    - the /embeddings route of an OpenAI-compatible endpoint. Point it at
+#              a local server (e.g. Ollama with all-minilm) for sentence-transformer
+#              quality while keeping chats on this machine.
+backend = "hashed"
+
+# Local backend model: config.json, tokenizer.json and model.safetensors
+# model_dir = "/home/me/models/all-MiniLM-L6-v2"
+
+# API backend endpoint and key variable (default: the [llm] values)
+# endpoint = "http://localhost:11434/v1"
+# api_key_env = ""
+
+# Model requested from the API backend
+api_model = "text-embedding-3-small"
+
+# Shell commands run on daemon events, with a JSON payload on stdin and the
+# event name in CURSOR_CHAT_EVENT. Failures are logged, never fatal.
+[hooks]
+# on_sync_complete = "~/bin/notify-sync.sh"
+# on_restore = "~/bin/on-restore.sh"
+# on_new_conversation = "~/bin/new-chat.sh"
+
+# External formatters, used as `-f <name>` with show, export and export-all.
+# The command gets the conversations as JSON (like `-f json`) on stdin and
+# writes the formatted output to stdout.
+# [formatters.confluence]
+# command = "md2confluence"
+# args = ["--from-json"]
+# extension = "wiki"
+
+# Named profiles, selected with --profile <name> or CURSOR_CHAT_PROFILE.
+# Each may override data_dir, cursor_dir, channel, max_size_gb and
+# backup_retention_days. A "nightly" profile is built in (also selected by
+# --channel nightly): Cursor Nightly, stored in ~/.cursor-chat-handler/nightly.
+# [profile.work]
+# data_dir = "/home/me/work-chats"
+# backup_retention_days = 90
 "#;
 
-/// Load configuration from file or create default.
+/// Select the profile applied by [`load_config`] for the rest of the process.
+///
+/// Only the first selection takes effect.
+pub fn select_profile(name: &str) {
+    if ACTIVE_PROFILE.set(name.to_string()).is_err() {
+        tracing::warn!(profile = name, "Profile already selected, ignoring");
+    }
+}
+
+/// Name of the selected profile, if any.
+#[must_use]
+pub fn active_profile() -> Option<&'static str> {
+    ACTIVE_PROFILE.get().map(String::as_str)
+}
+
+/// Override `export.path_map` for the rest of the process.
+///
+/// Only the first call takes effect.
+pub fn set_path_map_file(path: PathBuf) {
+    if PATH_MAP_OVERRIDE.set(path).is_err() {
+        tracing::warn!("Path map already set, ignoring");
+    }
+}
+
+/// Override `extract.max_memory_mb` for the rest of the process.
+///
+/// Only the first call takes effect.
+pub fn set_max_memory_mb(mb: u64) {
+    if MAX_MEMORY_OVERRIDE.set(mb).is_err() {
+        tracing::warn!("Memory budget already set, ignoring");
+    }
+}
+
+/// Load configuration with the selected profile and overrides applied.
 ///
 /// # Errors
-/// Returns error if file exists but cannot be read or parsed.
+/// Returns error if the file cannot be read or parsed, or the selected
+/// profile is not defined.
 pub fn load_config() -> Result<AppConfig> {
+    let mut config = match active_profile() {
+        Some(name) => load_base_config()?.with_profile(name)?,
+        None => load_base_config()?,
+    };
+
+    if let Some(&mb) = MAX_MEMORY_OVERRIDE.get() {
+        config.extract.max_memory_mb = mb;
+    }
+    if let Some(path) = PATH_MAP_OVERRIDE.get() {
+        config.export.path_map = Some(path.clone());
+    }
+
+    Ok(config)
+}
+
+/// Load configuration from file or create default, ignoring profiles.
+///
+/// # Errors
+/// Returns error if file exists but cannot be read or parsed.
+pub fn load_base_config() -> Result<AppConfig> {
     let config_path = AppConfig::default_data_dir().join("config.toml");
 
     if config_path.exists() {
@@ -65,8 +262,14 @@ pub fn load_config_from_file(path: &Path) -> Result<AppConfig> {
 /// # Errors
 /// Returns error if file cannot be written.
 pub fn save_config(config: &AppConfig) -> Result<()> {
-    let config_path = config.config_file_path();
+    save_config_to_file(config, &config.config_file_path())
+}
 
+/// Save configuration to a specific file.
+///
+/// # Errors
+/// Returns error if file cannot be written.
+pub fn save_config_to_file(config: &AppConfig, config_path: &Path) -> Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)
@@ -77,14 +280,171 @@ pub fn save_config(config: &AppConfig) -> Result<()> {
         message: format!("Failed to serialize config: {e}"),
     })?;
 
-    fs::write(&config_path, content)
-        .map_err(|e| AppError::io(format!("Failed to write config file: {}", config_path.display()), e))?;
+    fs::write(config_path, content).map_err(|e| {
+        AppError::io(
+            format!("Failed to write config file: {}", config_path.display()),
+            e,
+        )
+    })?;
+    // It may hold sync.listen_token
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(config_path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| AppError::io("Failed to restrict config file permissions", e))?;
+    }
 
     tracing::info!(path = %config_path.display(), "Configuration saved");
 
     Ok(())
 }
 
+/// Check configuration values that parse but make no sense.
+///
+/// # Errors
+/// Returns error describing the first invalid value.
+pub fn validate_config(config: &AppConfig) -> Result<()> {
+    let invalid = |message: &str| {
+        Err(AppError::Config {
+            message: message.into(),
+        })
+    };
+
+    if config.sync.interval_secs == 0 {
+        return invalid("sync.interval_secs must be greater than 0");
+    }
+    if config.storage.max_size_gb == 0 {
+        return invalid("storage.max_size_gb must be greater than 0");
+    }
+    if config
+        .paths
+        .data_dir
+        .as_ref()
+        .is_some_and(|p| p.as_os_str().is_empty())
+    {
+        return invalid("paths.data_dir must not be empty");
+    }
+
+    Ok(())
+}
+
+/// Look up a dotted key (e.g. `sync.interval_secs`) in the configuration.
+///
+/// # Errors
+/// Returns error if the key does not exist.
+pub fn get_config_value(config: &AppConfig, key: &str) -> Result<toml::Value> {
+    let root = config_to_value(config)?;
+
+    key.split('.')
+        .try_fold(&root, |value, part| value.get(part))
+        .cloned()
+        .ok_or_else(|| unknown_key(key))
+}
+
+/// Return a copy of the configuration with a dotted key set from its text form.
+///
+/// The value is parsed according to the type of the setting and the result is
+/// validated before being returned.
+///
+/// # Errors
+/// Returns error if the key is unknown, the value has the wrong type or the
+/// resulting configuration is invalid.
+pub fn set_config_value(config: &AppConfig, key: &str, raw: &str) -> Result<AppConfig> {
+    let mut root = config_to_value(config)?;
+    let (parents, name) = key.rsplit_once('.').map_or(("", key), |(p, n)| (p, n));
+
+    let mut table = &mut root;
+    for part in parents.split('.').filter(|p| !p.is_empty()) {
+        table = table.get_mut(part).ok_or_else(|| unknown_key(key))?;
+    }
+    let table = table.as_table_mut().ok_or_else(|| unknown_key(key))?;
+
+    let value = match table.get(name) {
+        Some(toml::Value::Table(_)) => {
+            return Err(AppError::Config {
+                message: format!("'{key}' is a section; set one of its keys instead"),
+            });
+        }
+        Some(toml::Value::Integer(_)) => {
+            toml::Value::Integer(raw.parse().map_err(|_| AppError::Config {
+                message: format!("'{key}' expects an integer, got '{raw}'"),
+            })?)
+        }
+        Some(toml::Value::Boolean(_)) => {
+            toml::Value::Boolean(raw.parse().map_err(|_| AppError::Config {
+                message: format!("'{key}' expects true or false, got '{raw}'"),
+            })?)
+        }
+        _ => toml::Value::String(raw.to_string()),
+    };
+    table.insert(name.to_string(), value.clone());
+
+    let updated: AppConfig = root.try_into().map_err(|e| AppError::Config {
+        message: format!("Invalid value for '{key}': {e}"),
+    })?;
+
+    // Unknown keys are ignored by serde, so make sure the value survived
+    if get_config_value(&updated, key).ok().as_ref() != Some(&value) {
+        return Err(unknown_key(key));
+    }
+
+    validate_config(&updated)?;
+    Ok(updated)
+}
+
+/// Open the configuration file in `$VISUAL`/`$EDITOR` and validate it afterwards.
+///
+/// The previous content is restored if the edited file is invalid.
+///
+/// # Errors
+/// Returns error if the editor cannot be run or the edited file is invalid.
+pub fn edit_config() -> Result<()> {
+    ensure_config_exists()?;
+    let config_path = config_file_path();
+    let original = fs::read_to_string(&config_path)
+        .map_err(|e| AppError::io("Failed to read config file", e))?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Allow editors with arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&config_path)
+        .status()
+        .map_err(|e| AppError::io(format!("Failed to run editor '{editor}'"), e))?;
+
+    if !status.success() {
+        return Err(AppError::Config {
+            message: format!("Editor '{editor}' exited with {status}"),
+        });
+    }
+
+    let result = load_config_from_file(&config_path).and_then(|c| validate_config(&c));
+    if result.is_err() {
+        fs::write(&config_path, original)
+            .map_err(|e| AppError::io("Failed to restore config file", e))?;
+    }
+
+    result
+}
+
+/// Serialize the configuration into a TOML value tree.
+fn config_to_value(config: &AppConfig) -> Result<toml::Value> {
+    toml::Value::try_from(config).map_err(|e| AppError::Config {
+        message: format!("Failed to serialize config: {e}"),
+    })
+}
+
+fn unknown_key(key: &str) -> AppError {
+    AppError::Config {
+        message: format!("Unknown config key: '{key}'"),
+    }
+}
+
 /// Create default configuration file if it doesn't exist.
 ///
 /// # Errors
@@ -126,6 +486,46 @@ mod tests {
         assert_eq!(config.storage.max_size_gb, 10);
     }
 
+    #[test]
+    fn test_get_config_value() {
+        let config = AppConfig::default();
+        assert_eq!(
+            get_config_value(&config, "sync.interval_secs").unwrap(),
+            toml::Value::Integer(120)
+        );
+        assert!(get_config_value(&config, "sync").unwrap().is_table());
+        assert!(get_config_value(&config, "sync.nope").is_err());
+    }
+
+    #[test]
+    fn test_default_config_profile_example_parses() {
+        let uncommented = DEFAULT_CONFIG
+            .replace("# [profile.work]", "[profile.work]")
+            .replace(
+                "# data_dir = \"/home/me/work-chats\"",
+                "data_dir = \"/home/me/work-chats\"",
+            );
+        let config: AppConfig = toml::from_str(&uncommented).unwrap();
+        assert!(config.profiles.contains_key("work"));
+    }
+
+    #[test]
+    fn test_set_config_value() {
+        let config = AppConfig::default();
+
+        let updated = set_config_value(&config, "sync.interval_secs", "60").unwrap();
+        assert_eq!(updated.sync.interval_secs, 60);
+
+        let updated = set_config_value(&config, "paths.data_dir", "/tmp/chats").unwrap();
+        assert_eq!(updated.data_dir(), std::path::PathBuf::from("/tmp/chats"));
+
+        assert!(set_config_value(&config, "sync.interval_secs", "soon").is_err());
+        assert!(set_config_value(&config, "sync.interval_secs", "0").is_err());
+        assert!(set_config_value(&config, "sync.enabled", "maybe").is_err());
+        assert!(set_config_value(&config, "sync.unknown", "1").is_err());
+        assert!(set_config_value(&config, "storage", "1").is_err());
+    }
+
     #[test]
     fn test_save_and_load() {
         let dir = tempdir().unwrap();
@@ -144,4 +544,3 @@ mod tests {
         assert_eq!(loaded.storage.max_size_gb, config.storage.max_size_gb);
     }
 }
-
@@ -0,0 +1,150 @@
+//! On-disk cache of parsed conversations.
+//!
+//! Consecutive commands (`quick`, then `open 1`, then `export`) would each
+//! read and parse Cursor's whole database. The cache keeps the parsed
+//! conversations together with the database fingerprint they came from, and
+//! a hash of each conversation's raw rows so that after a change only the
+//! conversations that actually changed are parsed again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{AppError, Conversation, Result};
+
+use super::timings::TIMINGS_TARGET;
+
+/// Bumped whenever the cached layout or parsing changes.
+const CACHE_VERSION: u32 = 3;
+
+/// zstd level: the cache is rewritten often, so favour speed.
+const COMPRESSION_LEVEL: i32 = 1;
+
+/// A parsed conversation and the hash of the rows it was parsed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedConversation {
+    /// blake3 hash of the composer and bubble rows.
+    pub raw_hash: String,
+    /// Whether the conversation has bubbles but no usable composer row.
+    pub orphan: bool,
+    /// Conversation with every bubble, before filtering and titling.
+    pub conversation: Conversation,
+}
+
+/// Parsed conversations of one Cursor database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversationCache {
+    version: u32,
+    /// Database the conversations were read from.
+    pub database: PathBuf,
+    /// Fingerprint of the database when it was read.
+    pub fingerprint: String,
+    /// Cached conversations, in database order.
+    pub entries: Vec<CachedConversation>,
+}
+
+impl ConversationCache {
+    /// Create a cache for `database` at `fingerprint`.
+    #[must_use]
+    pub fn new(database: &Path, fingerprint: String, entries: Vec<CachedConversation>) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            database: database.to_path_buf(),
+            fingerprint,
+            entries,
+        }
+    }
+
+    /// Load the cache at `path`; a missing, stale or unreadable file gives
+    /// an empty cache.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        let _reading = tracing::info_span!(target: TIMINGS_TARGET, "cache read").entered();
+        let Ok(bytes) = fs::read(path) else {
+            return Self::default();
+        };
+
+        zstd::decode_all(bytes.as_slice())
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Self>(&json).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_else(|| {
+                tracing::debug!("Ignoring unreadable conversation cache {}", path.display());
+                Self::default()
+            })
+    }
+
+    /// Whether the cache holds `database` exactly as of `fingerprint`.
+    #[must_use]
+    pub fn is_current(&self, database: &Path, fingerprint: &str) -> bool {
+        self.version == CACHE_VERSION
+            && self.database == database
+            && self.fingerprint == fingerprint
+    }
+
+    /// Write the cache to `path`.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::io("Failed to create cache directory", e))?;
+        }
+
+        let json = serde_json::to_vec(self).map_err(AppError::json_parse)?;
+        let compressed = zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL)
+            .map_err(|e| AppError::io("Failed to compress conversation cache", e))?;
+
+        // Write then rename, so a concurrent command never reads a partial file
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, compressed)
+            .map_err(|e| AppError::io("Failed to write conversation cache", e))?;
+        fs::rename(&tmp, path).map_err(|e| AppError::io("Failed to save conversation cache", e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ModelConfig;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_conversation_cache_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cache").join("conversations.json.zst");
+        let db = dir.path().join("state.vscdb");
+        assert!(!ConversationCache::load(&path).is_current(&db, "fp"));
+
+        let conversation = Conversation {
+            composer_id: "abc".into(),
+            title: String::new(),
+            created_at: None,
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        };
+        let entry = CachedConversation {
+            raw_hash: "hash".into(),
+            orphan: false,
+            conversation,
+        };
+        ConversationCache::new(&db, "fp".into(), vec![entry])
+            .save(&path)
+            .unwrap();
+
+        let loaded = ConversationCache::load(&path);
+        assert!(loaded.is_current(&db, "fp"));
+        assert!(!loaded.is_current(&db, "other"));
+        assert_eq!(loaded.entries[0].conversation.composer_id, "abc");
+
+        fs::write(&path, b"garbage").unwrap();
+        assert!(ConversationCache::load(&path).entries.is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+//! Cron fallback for Linux without systemd (Alpine, containers, WSL).
+//!
+//! The service is a line in the user's crontab, tagged with a trailing
+//! `# cursor-chat-sync` comment (`# cursor-chat-sync@work` for a profile)
+//! so it can be found again. In timer mode cron runs one sync cycle every
+//! interval; in daemon mode an `@reboot` entry starts the daemon at boot and
+//! `sync start` also launches it right away. Either way the daemon records
+//! its PID in `sync.pid` (`daemon --pid-file`), so it can be stopped.
+//! Stopping comments the line out, which keeps it installed.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::service_manager::{
+    is_valid_instance_name, service_log_path, tail_lines,
+};
+use crate::infrastructure::{
+    active_profile, ensure_writable, read_pid, InstallResult, ServiceManager, ServiceMode,
+    ServiceStatus, PROFILE_ENV,
+};
+
+/// Tag of the crontab line, without instance.
+const MARKER: &str = "cursor-chat-sync";
+
+/// Crontab-based service manager.
+pub struct CronService {
+    config: AppConfig,
+    /// Profile the entry is an instance for.
+    instance: Option<String>,
+}
+
+impl CronService {
+    /// Create a new cron service manager for the selected profile.
+    #[must_use]
+    pub fn new(config: AppConfig) -> Self {
+        Self {
+            config,
+            instance: active_profile().map(str::to_string),
+        }
+    }
+
+    /// Manage the entry of profile `instance` (`None`: the default entry)
+    /// instead of the selected profile's.
+    #[must_use]
+    pub fn with_instance(mut self, instance: Option<String>) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    /// Tag of this instance's line, e.g. `cursor-chat-sync@work`.
+    fn marker(&self) -> String {
+        self.instance
+            .as_ref()
+            .map_or_else(|| MARKER.to_string(), |name| format!("{MARKER}@{name}"))
+    }
+
+    /// Profiles with an entry in the crontab.
+    ///
+    /// # Errors
+    /// Returns error if `crontab` cannot be run.
+    pub fn installed_instances() -> Result<Vec<String>> {
+        let mut instances: Vec<String> = read_crontab()?
+            .lines()
+            .filter_map(|line| {
+                let (_, tag) = line.rsplit_once(&format!("# {MARKER}@"))?;
+                (!tag.is_empty()).then(|| tag.trim().to_string())
+            })
+            .collect();
+        instances.sort();
+        instances.dedup();
+
+        Ok(instances)
+    }
+
+    /// File holding the PID of the running daemon.
+    fn pid_path(&self) -> PathBuf {
+        self.config.data_dir().join("sync.pid")
+    }
+
+    /// Command line cron runs for `mode`, output appended to the sync log.
+    fn command_line(&self, binary: &Path, mode: ServiceMode) -> String {
+        let args = match mode {
+            ServiceMode::Daemon => format!(
+                "daemon --interval {} --pid-file {}",
+                self.config.sync.interval_secs,
+                shell_quote(&self.pid_path().display().to_string())
+            ),
+            ServiceMode::Timer => "daemon --once".to_string(),
+        };
+        let profile_env = self
+            .instance
+            .as_ref()
+            .map(|name| format!("{PROFILE_ENV}={name} "))
+            .unwrap_or_default();
+        format!(
+            "{profile_env}RUST_LOG=info {} {args} >> {} 2>&1",
+            shell_quote(&binary.display().to_string()),
+            shell_quote(&service_log_path(&self.config).display().to_string()),
+        )
+    }
+
+    /// The crontab line for `mode`, tagged with this instance's marker.
+    fn generate_entry(&self, binary: &Path, mode: ServiceMode) -> String {
+        let schedule = match mode {
+            ServiceMode::Daemon => "@reboot".to_string(),
+            ServiceMode::Timer => cron_schedule(self.config.sync.interval_secs),
+        };
+        format!(
+            "{schedule} {} # {}",
+            self.command_line(binary, mode),
+            self.marker()
+        )
+    }
+
+    /// This instance's line, if installed.
+    fn find_entry(&self, crontab: &str) -> Option<String> {
+        let tag = format!("# {}", self.marker());
+        crontab
+            .lines()
+            .find(|line| line.trim_end().ends_with(&tag))
+            .map(str::to_string)
+    }
+
+    /// Rewrite the crontab with this instance's line replaced by `entry`
+    /// (removed with `None`).
+    fn replace_entry(&self, entry: Option<&str>) -> Result<()> {
+        let tag = format!("# {}", self.marker());
+        let mut lines: Vec<String> = read_crontab()?
+            .lines()
+            .filter(|line| !line.trim_end().ends_with(&tag))
+            .map(str::to_string)
+            .collect();
+        lines.extend(entry.map(str::to_string));
+        write_crontab(&lines)
+    }
+
+    /// PID of the daemon, if it is still alive.
+    fn running_pid(&self) -> Option<u32> {
+        let pid = read_pid(&self.pid_path())?;
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .status()
+            .ok()?
+            .success()
+            .then_some(pid)
+    }
+
+    /// Stop the running daemon, if any, and forget its PID.
+    fn stop_daemon(&self) {
+        if let Some(pid) = self.running_pid() {
+            let _ = Command::new("kill").arg(pid.to_string()).status();
+        }
+        let _ = fs::remove_file(self.pid_path());
+    }
+
+    /// Start the daemon in the background, detached from the terminal.
+    fn spawn_daemon(&self) -> Result<()> {
+        if self.running_pid().is_some() {
+            return Ok(());
+        }
+
+        let binary = std::env::current_exe()
+            .map_err(|e| AppError::io("Failed to get executable path", e))?;
+        let log_path = service_log_path(&self.config);
+        let log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(|e| AppError::io(format!("Failed to open {}", log_path.display()), e))?;
+        let stderr = log
+            .try_clone()
+            .map_err(|e| AppError::io("Failed to open sync log", e))?;
+
+        let mut command = Command::new(binary);
+        command
+            .args([
+                "daemon",
+                "--interval",
+                &self.config.sync.interval_secs.to_string(),
+            ])
+            .arg("--pid-file")
+            .arg(self.pid_path())
+            .env("RUST_LOG", "info")
+            .stdin(Stdio::null())
+            .stdout(log)
+            .stderr(stderr);
+        if let Some(name) = &self.instance {
+            command.env(PROFILE_ENV, name);
+        }
+        // Its own process group, so Ctrl-C in this terminal does not reach it
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let child = command
+            .spawn()
+            .map_err(|e| AppError::io("Failed to start sync daemon", e))?;
+
+        // The daemon writes it too, but status must see it right away
+        fs::write(self.pid_path(), format!("{}\n", child.id()))
+            .map_err(|e| AppError::io("Failed to write sync.pid", e))?;
+        tracing::info!(pid = child.id(), "Sync daemon started");
+
+        Ok(())
+    }
+}
+
+impl ServiceManager for CronService {
+    fn service_name(&self) -> String {
+        format!("crontab ({})", self.marker())
+    }
+
+    fn install(&self, mode: ServiceMode) -> Result<InstallResult> {
+        ensure_writable("install the sync service")?;
+        if let Some(name) = &self.instance {
+            if !is_valid_instance_name(name) {
+                return Err(AppError::Config {
+                    message: format!(
+                        "Profile '{name}' cannot tag a crontab entry (use letters, digits, '-', '_' or '.')"
+                    ),
+                });
+            }
+        }
+
+        // A daemon from a previous install would keep running unmanaged
+        let _ = self.stop_and_disable();
+
+        let binary_path = std::env::current_exe()
+            .map_err(|e| AppError::io("Failed to get executable path", e))?;
+        let entry = self.generate_entry(&binary_path, mode);
+
+        // Installed disabled; `enable_and_start` uncomments it
+        self.replace_entry(Some(&format!("# {entry}")))?;
+
+        tracing::info!(entry = %entry, "Crontab entry written");
+
+        Ok(InstallResult {
+            service_path: PathBuf::from("crontab"),
+            timer_path: None,
+            unit_content: entry,
+        })
+    }
+
+    fn enable_and_start(&self) -> Result<()> {
+        ensure_writable("start the sync service")?;
+        let entry = self
+            .find_entry(&read_crontab()?)
+            .ok_or_else(|| AppError::Config {
+                message: "Sync service is not installed".into(),
+            })?;
+
+        let entry = entry.trim_start_matches(['#', ' ']).to_string();
+        self.replace_entry(Some(&entry))?;
+
+        if entry.starts_with("@reboot") {
+            self.spawn_daemon()?;
+        }
+
+        tracing::info!("Crontab entry enabled");
+
+        Ok(())
+    }
+
+    fn stop_and_disable(&self) -> Result<()> {
+        ensure_writable("stop the sync service")?;
+
+        self.stop_daemon();
+
+        if let Some(entry) = self.find_entry(&read_crontab()?) {
+            if !entry.starts_with('#') {
+                self.replace_entry(Some(&format!("# {entry}")))?;
+            }
+        }
+
+        tracing::info!("Crontab entry disabled");
+
+        Ok(())
+    }
+
+    fn get_status(&self) -> Result<ServiceStatus> {
+        let Some(entry) = self.find_entry(&read_crontab()?) else {
+            return Ok(ServiceStatus {
+                is_installed: false,
+                mode: ServiceMode::Daemon,
+                is_enabled: false,
+                is_running: false,
+                status_text: "not installed".into(),
+            });
+        };
+
+        let is_enabled = !entry.starts_with('#');
+        let mode = if entry.contains("--once") {
+            ServiceMode::Timer
+        } else {
+            ServiceMode::Daemon
+        };
+        let pid = self.running_pid();
+        let is_running = match mode {
+            ServiceMode::Timer => is_enabled,
+            ServiceMode::Daemon => pid.is_some(),
+        };
+        let status_text = pid.map_or_else(|| entry.clone(), |pid| format!("{entry}\nPID: {pid}"));
+
+        Ok(ServiceStatus {
+            is_installed: true,
+            mode,
+            is_enabled,
+            is_running,
+            status_text,
+        })
+    }
+
+    fn uninstall(&self) -> Result<()> {
+        ensure_writable("uninstall the sync service")?;
+
+        let _ = self.stop_and_disable();
+        self.replace_entry(None)?;
+
+        tracing::info!("Crontab entry removed");
+
+        Ok(())
+    }
+
+    fn view_logs(&self, lines: usize) -> Result<String> {
+        tail_lines(&service_log_path(&self.config), lines)
+    }
+}
+
+/// Cron schedule closest to every `interval_secs` (at least every minute).
+fn cron_schedule(interval_secs: u64) -> String {
+    let minutes = interval_secs.div_ceil(60).max(1);
+    match minutes {
+        1 => "* * * * *".to_string(),
+        2..=59 => format!("*/{minutes} * * * *"),
+        _ => format!("0 */{} * * *", (minutes / 60).min(23)),
+    }
+}
+
+/// The user's crontab, empty if there is none yet or cron is missing.
+fn read_crontab() -> Result<String> {
+    let output = match Command::new("crontab")
+        .arg("-l")
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        // Nothing can be installed then; `write_crontab` reports it
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(AppError::io("Failed to run crontab", e)),
+    };
+
+    // `crontab -l` fails when the user has no crontab
+    Ok(if output.status.success() {
+        String::from_utf8_lossy(&output.stdout).to_string()
+    } else {
+        String::new()
+    })
+}
+
+fn write_crontab(lines: &[String]) -> Result<()> {
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            AppError::io(
+                "Failed to run crontab (without systemd or cron, run `cursor-chat daemon` from your own init system)",
+                e,
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut content = lines.join("\n");
+        content.push('\n');
+        stdin
+            .write_all(content.as_bytes())
+            .map_err(|e| AppError::io("Failed to write crontab", e))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| AppError::io("Failed to write crontab", e))?;
+    if !status.success() {
+        return Err(AppError::Config {
+            message: "crontab rejected the new entry".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Quote `value` for `/bin/sh`, which cron runs entries with.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cron_schedule() {
+        assert_eq!(cron_schedule(30), "* * * * *");
+        assert_eq!(cron_schedule(300), "*/5 * * * *");
+        assert_eq!(cron_schedule(7200), "0 */2 * * *");
+    }
+
+    #[test]
+    fn test_generate_entry() {
+        let mut config = AppConfig::default();
+        config.sync.interval_secs = 600;
+        let mut cron = CronService::new(config);
+        cron.instance = Some("work".to_string());
+        let binary = Path::new("/opt/it's/cursor-chat");
+
+        let timer = cron.generate_entry(binary, ServiceMode::Timer);
+        assert!(timer.starts_with(
+            r"*/10 * * * * CURSOR_CHAT_PROFILE=work RUST_LOG=info '/opt/it'\''s/cursor-chat' daemon --once >> "
+        ));
+        assert!(timer.ends_with("sync.log' 2>&1 # cursor-chat-sync@work"));
+
+        // The daemon started at boot records its PID where stop looks
+        let daemon = cron.generate_entry(binary, ServiceMode::Daemon);
+        assert!(daemon.starts_with("@reboot "));
+        let pid_file = shell_quote(&cron.pid_path().display().to_string());
+        assert!(daemon.contains(&format!("daemon --interval 600 --pid-file {pid_file} >> ")));
+
+        let crontab = format!("MAILTO=\"\"\n# {daemon}\n{timer}x\n");
+        assert_eq!(cron.find_entry(&crontab), Some(format!("# {daemon}")));
+        cron.instance = None;
+        assert_eq!(cron.find_entry(&crontab), None);
+    }
+
+    #[test]
+    fn test_stop_daemon_kills_recorded_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = Some(dir.path().to_path_buf());
+        let cron = CronService::new(config);
+
+        // A daemon started at boot, known only by the PID it recorded
+        let mut daemon = Command::new("sleep").arg("30").spawn().unwrap();
+        fs::write(cron.pid_path(), format!("{}\n", daemon.id())).unwrap();
+        assert_eq!(cron.running_pid(), Some(daemon.id()));
+
+        cron.stop_daemon();
+        assert!(!daemon.wait().unwrap().success());
+        assert!(!cron.pid_path().exists());
+        assert_eq!(cron.running_pid(), None);
+    }
+}
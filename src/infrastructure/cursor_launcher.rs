@@ -0,0 +1,94 @@
+//! Launching and focusing the Cursor editor.
+//!
+//! Tries the `cursor` shell command first, then the `cursor://` URI scheme
+//! registered by the desktop app.
+
+use std::process::{Command, Stdio};
+
+use crate::domain::{AppError, Result};
+
+/// How Cursor was brought to the front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMethod {
+    /// The `cursor` shell command.
+    Cli,
+    /// The `cursor://` URI scheme, through the system opener.
+    UriScheme,
+}
+
+impl LaunchMethod {
+    /// Human-readable description.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Cli => "cursor command",
+            Self::UriScheme => "cursor:// link",
+        }
+    }
+}
+
+/// Launch Cursor, or focus its window if it is already running.
+///
+/// # Errors
+/// Returns error if neither the CLI nor the URI scheme is available.
+pub fn focus_cursor() -> Result<LaunchMethod> {
+    // The CLI may be an AppImage that keeps running, so don't wait for it
+    if launch_command(LaunchMethod::Cli).spawn().is_ok() {
+        return Ok(LaunchMethod::Cli);
+    }
+
+    match launch_command(LaunchMethod::UriScheme).status() {
+        Ok(status) if status.success() => Ok(LaunchMethod::UriScheme),
+        _ => Err(AppError::Config {
+            message: "Could not launch Cursor. Install the `cursor` shell command \
+                      (Command Palette → \"Install 'cursor' command\") or open Cursor manually"
+                .into(),
+        }),
+    }
+}
+
+/// The command bringing up Cursor with `method`.
+fn launch_command(method: LaunchMethod) -> Command {
+    let mut command = match method {
+        LaunchMethod::Cli => Command::new("cursor"),
+        LaunchMethod::UriScheme if cfg!(target_os = "macos") => {
+            let mut open = Command::new("open");
+            open.args(["-a", "Cursor"]);
+            open
+        }
+        LaunchMethod::UriScheme => {
+            let mut open = Command::new("xdg-open");
+            open.arg("cursor://");
+            open
+        }
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_line(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_launch_command() {
+        assert_eq!(command_line(&launch_command(LaunchMethod::Cli)), ["cursor"]);
+
+        let uri = command_line(&launch_command(LaunchMethod::UriScheme));
+        if cfg!(target_os = "macos") {
+            assert_eq!(uri, ["open", "-a", "Cursor"]);
+        } else {
+            assert_eq!(uri, ["xdg-open", "cursor://"]);
+        }
+    }
+}
@@ -1,48 +1,188 @@
 //! Cursor IDE path discovery.
 //!
-//! Handles locating Cursor's data directories across different platforms.
+//! Handles locating Cursor's data directories across different platforms
+//! and release channels, and the installed Cursor version.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use crate::domain::{AppError, Result};
+use crate::domain::{AppError, CursorChannel, Result};
 
-/// Known Cursor data directory locations by platform.
+/// Known Cursor data directory locations by platform, relative to `$HOME`;
+/// `{app}` is the channel's application name.
 const CURSOR_CONFIG_PATHS: &[&str] = &[
     // Linux
-    ".config/Cursor",
+    ".config/{app}",
     // macOS
-    "Library/Application Support/Cursor",
-    // Alternative locations
-    ".cursor",
+    "Library/Application Support/{app}",
 ];
 
+/// Older stable installs keep their data here.
+const LEGACY_CONFIG_PATH: &str = ".cursor";
+
 /// Subdirectory containing state databases.
 const GLOBAL_STORAGE_PATH: &str = "User/globalStorage";
 const WORKSPACE_STORAGE_PATH: &str = "User/workspaceStorage";
-const STATE_DB_NAME: &str = "state.vscdb";
+pub(crate) const STATE_DB_NAME: &str = "state.vscdb";
+
+/// Known locations of Cursor's `product.json`; relative ones are under
+/// `$HOME`. `{app}` is the application name and `{pkg}` its package name.
+const CURSOR_PRODUCT_PATHS: &[&str] = &[
+    // Linux packages
+    "/usr/share/{pkg}/resources/app/product.json",
+    "/opt/{app}/resources/app/product.json",
+    "/opt/{pkg}/resources/app/product.json",
+    // Extracted AppImage
+    "Applications/{pkg}/resources/app/product.json",
+    ".local/share/{pkg}/resources/app/product.json",
+    // macOS
+    "/Applications/{app}.app/Contents/Resources/app/product.json",
+    "Applications/{app}.app/Contents/Resources/app/product.json",
+];
+
+/// Newest Cursor release (major.minor) whose database schema the parser
+/// has been checked against.
+pub const NEWEST_KNOWN_CURSOR_VERSION: &str = "2.0";
+
+/// Cursor directory configured by the user, used instead of auto-detection.
+static CURSOR_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Channel whose directory is auto-detected.
+static CURSOR_CHANNEL: OnceLock<CursorChannel> = OnceLock::new();
+
+/// Auto-detect the directory of `channel` for the rest of the process.
+///
+/// Only the first call takes effect.
+pub fn set_cursor_channel(channel: CursorChannel) {
+    if CURSOR_CHANNEL.set(channel).is_err() {
+        tracing::warn!("Cursor channel already set, ignoring");
+    }
+}
+
+/// Channel whose directory is auto-detected (stable unless set).
+#[must_use]
+pub fn cursor_channel() -> CursorChannel {
+    CURSOR_CHANNEL.get().copied().unwrap_or_default()
+}
+
+/// Candidate configuration directories of `channel`, relative to `$HOME`.
+fn config_paths(channel: CursorChannel) -> Vec<String> {
+    let mut paths: Vec<String> = CURSOR_CONFIG_PATHS
+        .iter()
+        .map(|path| path.replace("{app}", channel.app_name()))
+        .collect();
+    if channel == CursorChannel::Stable {
+        paths.push(LEGACY_CONFIG_PATH.to_string());
+    }
+    paths
+}
+
+/// Configuration directory of `channel`, if it exists.
+fn channel_config_dir(channel: CursorChannel) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    config_paths(channel)
+        .into_iter()
+        .map(|path| home.join(path))
+        .find(|path| path.is_dir())
+}
+
+/// Channels with a configuration directory on this machine.
+#[must_use]
+pub fn detected_channels() -> Vec<CursorChannel> {
+    CursorChannel::ALL
+        .into_iter()
+        .filter(|channel| channel_config_dir(*channel).is_some())
+        .collect()
+}
+
+/// Use a fixed Cursor configuration directory for the rest of the process.
+///
+/// # Errors
+/// Returns error if a directory was already set: only the first call takes
+/// effect.
+pub fn set_cursor_config_dir(path: PathBuf) -> Result<()> {
+    CURSOR_DIR_OVERRIDE.set(path).map_err(|path| {
+        let current = CURSOR_DIR_OVERRIDE.get().unwrap_or(&path);
+        AppError::Config {
+            message: format!(
+                "Cursor config directory already set to {}",
+                current.display()
+            ),
+        }
+    })
+}
 
 /// Discovers the Cursor configuration directory.
 ///
 /// # Errors
 /// Returns error if home directory cannot be determined or Cursor is not installed.
 pub fn find_cursor_config_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Config {
-        message: "Could not determine home directory".into(),
-    })?;
-
-    for path in CURSOR_CONFIG_PATHS {
-        let full_path = home.join(path);
-        if full_path.exists() && full_path.is_dir() {
-            tracing::debug!("Found Cursor config at: {}", full_path.display());
-            return Ok(full_path);
-        }
+    if let Some(dir) = CURSOR_DIR_OVERRIDE.get() {
+        return if dir.is_dir() {
+            Ok(dir.clone())
+        } else {
+            Err(AppError::Config {
+                message: format!("Configured Cursor directory not found: {}", dir.display()),
+            })
+        };
+    }
+
+    if dirs::home_dir().is_none() {
+        return Err(AppError::Config {
+            message: "Could not determine home directory".into(),
+        });
+    }
+
+    let channel = cursor_channel();
+    if let Some(dir) = channel_config_dir(channel) {
+        tracing::debug!("Found {} config at: {}", channel.app_name(), dir.display());
+        return Ok(dir);
     }
 
     Err(AppError::Config {
-        message: format!("Cursor config directory not found. Searched: {CURSOR_CONFIG_PATHS:?}"),
+        message: format!(
+            "{} config directory not found. Searched: {:?}",
+            channel.app_name(),
+            config_paths(channel)
+        ),
     })
 }
 
+/// Path of Cursor's global state database, even if it does not exist yet.
+///
+/// Falls back to the platform default location when Cursor's config
+/// directory is missing (e.g. right after a reset).
+///
+/// # Errors
+/// Returns error if home directory cannot be determined.
+pub fn global_state_db_path() -> Result<PathBuf> {
+    let config_dir = if let Ok(dir) = find_cursor_config_dir() {
+        dir
+    } else if let Some(dir) = CURSOR_DIR_OVERRIDE.get() {
+        dir.clone()
+    } else {
+        let home = dirs::home_dir().ok_or_else(|| AppError::Config {
+            message: "Could not determine home directory".into(),
+        })?;
+        let default = if cfg!(target_os = "macos") {
+            CURSOR_CONFIG_PATHS[1]
+        } else {
+            CURSOR_CONFIG_PATHS[0]
+        };
+        home.join(default.replace("{app}", cursor_channel().app_name()))
+    };
+
+    Ok(config_dir.join(GLOBAL_STORAGE_PATH).join(STATE_DB_NAME))
+}
+
+/// Cursor's `workspaceStorage` directory, with one folder per opened workspace.
+///
+/// # Errors
+/// Returns error if Cursor config directory cannot be found.
+pub fn workspace_storage_dir() -> Result<PathBuf> {
+    Ok(find_cursor_config_dir()?.join(WORKSPACE_STORAGE_PATH))
+}
+
 /// Finds all state.vscdb files in Cursor's data directories.
 ///
 /// # Errors
@@ -86,10 +226,97 @@ pub fn find_state_databases() -> Result<Vec<PathBuf>> {
     Ok(databases)
 }
 
+/// Installed version of the selected channel, read from its `product.json`
+/// (or the `package.json` next to it).
+///
+/// Returns `None` when no install is found in the known locations.
+#[must_use]
+pub fn detect_cursor_version() -> Option<String> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let channel = cursor_channel();
+    let package = channel.app_name().to_lowercase().replace(' ', "-");
+    CURSOR_PRODUCT_PATHS
+        .iter()
+        .map(|path| {
+            home.join(
+                path.replace("{app}", channel.app_name())
+                    .replace("{pkg}", &package),
+            )
+        })
+        .filter(|path| path.is_file())
+        .find_map(|path| {
+            read_version(&path).or_else(|| read_version(&path.with_file_name("package.json")))
+        })
+}
+
+fn read_version(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let version = json.get("version")?.as_str()?.to_string();
+    tracing::debug!(version, "Found Cursor version in {}", path.display());
+    Some(version)
+}
+
+/// Whether `version` is a release newer than [`NEWEST_KNOWN_CURSOR_VERSION`].
+///
+/// Only major and minor are compared; unparsable versions are not newer.
+#[must_use]
+pub fn is_newer_than_known(version: &str) -> bool {
+    match (
+        major_minor(version),
+        major_minor(NEWEST_KNOWN_CURSOR_VERSION),
+    ) {
+        (Some(version), Some(known)) => version > known,
+        _ => false,
+    }
+}
+
+fn major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    Some((major, minor))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_paths_per_channel() {
+        assert_eq!(
+            config_paths(CursorChannel::Stable),
+            [
+                ".config/Cursor",
+                "Library/Application Support/Cursor",
+                ".cursor"
+            ]
+        );
+        assert_eq!(
+            config_paths(CursorChannel::Nightly),
+            [
+                ".config/Cursor Nightly",
+                "Library/Application Support/Cursor Nightly"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_newer_than_known() {
+        assert!(!is_newer_than_known("0.42.3"));
+        assert!(!is_newer_than_known(NEWEST_KNOWN_CURSOR_VERSION));
+        assert!(!is_newer_than_known("2.0.77"));
+        assert!(is_newer_than_known("2.1.0"));
+        assert!(is_newer_than_known("3"));
+        assert!(!is_newer_than_known("nightly"));
+    }
+
+    #[test]
+    fn test_global_state_db_path() {
+        let path = global_state_db_path().unwrap();
+        assert!(path.ends_with("User/globalStorage/state.vscdb"));
+    }
+
     #[test]
     fn test_find_cursor_config_returns_result() {
         // This test just ensures the function doesn't panic
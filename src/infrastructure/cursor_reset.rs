@@ -3,11 +3,13 @@
 //! Handles killing processes, resetting machine ID, and cleaning config directories
 //! for a complete Cursor trial reset.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::domain::{AppError, Result};
 
+use super::read_only::ensure_writable;
+
 /// Configuration directories to clean during reset.
 const CONFIG_DIRS: &[&str] = &[
     ".config/Cursor",
@@ -18,22 +20,111 @@ const CONFIG_DIRS: &[&str] = &[
 ];
 
 /// Desktop entry patterns to clean.
-const DESKTOP_PATTERNS: &[&str] = &[
-    "cursor*.desktop",
-    "co.anysphere.cursor*.desktop",
-];
+const DESKTOP_PATTERNS: &[&str] = &["cursor*.desktop", "co.anysphere.cursor*.desktop"];
 
 /// Icon patterns to clean.
-const ICON_PATTERNS: &[&str] = &[
-    "cursor*.*",
-    "co.anysphere.cursor*.*",
+const ICON_PATTERNS: &[&str] = &["cursor*.*", "co.anysphere.cursor*.*"];
+
+/// The Cursor process name, `cursor` on Linux and `Cursor` on macOS. The
+/// exact name never matches this tool.
+const PROCESS_NAME: &str = "cursor";
+
+/// `pkill` arguments matching the Cursor process.
+const PROCESS_MATCH: [&str; 2] = ["-ix", PROCESS_NAME];
+
+/// Individual steps of a Cursor reset.
+///
+/// Each flag maps onto one `CursorReset` method, so callers can run
+/// only the parts they need instead of a full reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ResetSteps {
+    /// Kill running Cursor processes.
+    pub kill: bool,
+    /// Remove Cursor configuration directories.
+    pub config: bool,
+    /// Remove desktop entries and icons.
+    pub desktop: bool,
+    /// Remove downloaded Cursor images from the downloads folder.
+    pub appimage: bool,
+    /// Reset the machine ID (requires sudo).
+    pub machine_id: bool,
+}
+
+impl ResetSteps {
+    /// Every step of a complete reset.
+    #[must_use]
+    pub const fn full(clean_appimage: bool) -> Self {
+        Self {
+            kill: true,
+            config: true,
+            desktop: true,
+            appimage: clean_appimage,
+            machine_id: true,
+        }
+    }
+
+    /// Only kill Cursor processes.
+    #[must_use]
+    pub const fn kill_only() -> Self {
+        Self {
+            kill: true,
+            config: false,
+            desktop: false,
+            appimage: false,
+            machine_id: false,
+        }
+    }
+
+    /// Drop the machine ID step.
+    #[must_use]
+    pub const fn without_machine_id(mut self) -> Self {
+        self.machine_id = false;
+        self
+    }
+
+    /// Whether any step removes data or changes system state beyond killing processes.
+    #[must_use]
+    pub const fn is_destructive(self) -> bool {
+        self.config || self.desktop || self.appimage || self.machine_id
+    }
+
+    /// Names of the selected steps, in execution order.
+    #[must_use]
+    pub fn names(self) -> Vec<&'static str> {
+        [
+            (self.kill, "kill"),
+            (self.config, "config"),
+            (self.desktop, "desktop"),
+            (self.appimage, "appimage"),
+            (self.machine_id, "machine-id"),
+        ]
+        .into_iter()
+        .filter_map(|(selected, name)| selected.then_some(name))
+        .collect()
+    }
+}
+
+/// System-level files removed with sudo.
+const SYSTEM_PATTERNS: &[&str] = &[
+    "/usr/share/applications/cursor*.desktop",
+    "/usr/share/applications/co.anysphere.cursor*.desktop",
+    "/usr/share/icons/hicolor/*/apps/cursor.png",
+    "/usr/share/icons/hicolor/*/apps/co.anysphere.cursor.*",
+    "/usr/share/pixmaps/cursor*.*",
+    "/usr/share/pixmaps/co.anysphere.cursor.*",
 ];
 
+/// Machine ID files replaced during reset.
+const MACHINE_ID_FILES: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
 /// Cursor reset service.
 pub struct CursorReset {
-    /// Whether to clean AppImage files.
+    /// Whether to clean `AppImage` files.
     clean_appimage: bool,
-    /// Downloads directory for AppImage cleanup.
+    /// Whether system-level operations may use sudo.
+    use_sudo: bool,
+    /// Downloads directory for `AppImage` cleanup.
     downloads_dir: PathBuf,
 }
 
@@ -46,29 +137,125 @@ impl CursorReset {
 
         Self {
             clean_appimage,
+            use_sudo: true,
             downloads_dir,
         }
     }
 
+    /// Skip operations that need sudo (machine ID, files under /usr/share).
+    #[must_use]
+    pub const fn without_sudo(mut self) -> Self {
+        self.use_sudo = false;
+        self
+    }
+
+    /// Whether system-level operations may use sudo.
+    #[must_use]
+    pub const fn uses_sudo(&self) -> bool {
+        self.use_sudo
+    }
+
+    /// List everything the given steps would touch, without changing anything.
+    ///
+    /// # Errors
+    /// Returns error if the home directory cannot be determined.
+    pub fn plan(&self, steps: ResetSteps) -> Result<ResetPlan> {
+        let home = home_dir()?;
+        let mut plan = ResetPlan::default();
+
+        if steps.kill {
+            plan.commands.push("killall cursor".into());
+            plan.commands
+                .push(format!("pkill {}", PROCESS_MATCH.join(" ")));
+        }
+
+        if steps.config {
+            plan.dirs.extend(Self::config_dir_targets(&home));
+        }
+
+        if steps.desktop {
+            let user_apps = home.join(".local/share/applications");
+            plan.files
+                .extend(matching_files(&user_apps, DESKTOP_PATTERNS));
+            plan.files.extend(matching_files(
+                &home.join(".local/share/icons"),
+                ICON_PATTERNS,
+            ));
+
+            if self.use_sudo {
+                for pattern in SYSTEM_PATTERNS {
+                    plan.commands.push(format!("sudo sh -c 'rm -f {pattern}'"));
+                }
+                plan.commands
+                    .push("sudo update-desktop-database /usr/share/applications".into());
+            }
+            plan.commands
+                .push(format!("update-desktop-database {}", user_apps.display()));
+        }
+
+        if steps.appimage {
+            let (files, squashfs) = self.appimage_targets(&home);
+            plan.files.extend(files);
+            plan.dirs.extend(squashfs);
+        }
+
+        if steps.machine_id && self.use_sudo {
+            plan.commands
+                .push(format!("sudo rm -f {}", MACHINE_ID_FILES.join(" ")));
+            plan.commands.push("sudo systemd-machine-id-setup".into());
+        }
+
+        Ok(plan)
+    }
+
+    /// Configuration directories that currently exist.
+    fn config_dir_targets(home: &Path) -> Vec<PathBuf> {
+        CONFIG_DIRS
+            .iter()
+            .map(|dir_name| home.join(dir_name))
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    /// Downloaded Cursor images plus an extracted `squashfs-root` directory.
+    fn appimage_targets(&self, home: &Path) -> (Vec<PathBuf>, Option<PathBuf>) {
+        let files = std::fs::read_dir(&self.downloads_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(std::result::Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|name| matches_pattern(name, "Cursor-*.AppImage"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let squashfs = home.join("squashfs-root");
+        (files, squashfs.exists().then_some(squashfs))
+    }
+
     /// Kill all running Cursor processes.
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn kill_cursor(&self) -> Result<KillResult> {
+        ensure_writable("stop Cursor processes")?;
         tracing::info!("Killing Cursor processes...");
 
         let mut killed = 0;
 
         // Try killall first
-        let status = Command::new("killall")
-            .arg("cursor")
-            .status();
+        let status = Command::new("killall").arg("cursor").status();
 
         if status.is_ok() {
             killed += 1;
         }
 
         // Also try pkill for any remaining
-        let _ = Command::new("pkill")
-            .args(["-f", "cursor"])
-            .status();
+        let _ = Command::new("pkill").args(PROCESS_MATCH).status();
 
         // Give processes time to terminate
         std::thread::sleep(std::time::Duration::from_millis(500));
@@ -77,12 +264,17 @@ impl CursorReset {
     }
 
     /// Reset the machine ID (requires sudo).
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn reset_machine_id(&self) -> Result<MachineIdResult> {
+        ensure_writable("reset the machine ID")?;
         tracing::info!("Resetting machine ID...");
 
         // Remove existing machine-id files
         let rm_result = Command::new("sudo")
-            .args(["rm", "-f", "/etc/machine-id", "/var/lib/dbus/machine-id"])
+            .args(["rm", "-f"])
+            .args(MACHINE_ID_FILES)
             .status()
             .map_err(|e| AppError::io("Failed to remove machine-id", e))?;
 
@@ -106,20 +298,20 @@ impl CursorReset {
 
         // Read new machine-id
         let new_id = std::fs::read_to_string("/etc/machine-id")
-            .map(|s| s.trim().to_string())
-            .unwrap_or_else(|_| "unknown".to_string());
+            .map_or_else(|_| "unknown".to_string(), |s| s.trim().to_string());
 
         Ok(MachineIdResult { new_id })
     }
 
     /// Clean Cursor configuration directories.
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn clean_config_dirs(&self) -> Result<CleanupStats> {
+        ensure_writable("delete Cursor's configuration")?;
         tracing::info!("Cleaning configuration directories...");
 
-        let home = dirs::home_dir().ok_or_else(|| AppError::Config {
-            message: "Could not determine home directory".into(),
-        })?;
-
+        let home = home_dir()?;
         let mut stats = CleanupStats::default();
 
         for dir_name in CONFIG_DIRS {
@@ -149,25 +341,32 @@ impl CursorReset {
     }
 
     /// Clean desktop entries and icons.
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn clean_desktop_entries(&self) -> Result<CleanupStats> {
+        ensure_writable("remove desktop entries")?;
         tracing::info!("Cleaning desktop entries and icons...");
 
-        let home = dirs::home_dir().ok_or_else(|| AppError::Config {
-            message: "Could not determine home directory".into(),
-        })?;
-
+        let home = home_dir()?;
         let mut stats = CleanupStats::default();
 
         // User desktop entries
         let user_apps = home.join(".local/share/applications");
-        self.clean_by_patterns(&user_apps, DESKTOP_PATTERNS, &mut stats);
+        Self::clean_by_patterns(&user_apps, DESKTOP_PATTERNS, &mut stats);
 
         // User icons
         let user_icons = home.join(".local/share/icons");
-        self.clean_by_patterns(&user_icons, ICON_PATTERNS, &mut stats);
+        Self::clean_by_patterns(&user_icons, ICON_PATTERNS, &mut stats);
 
         // System desktop entries (requires sudo)
-        self.clean_system_files(&mut stats);
+        if self.use_sudo {
+            Self::clean_system_files(&mut stats);
+        } else {
+            stats
+                .paths_skipped
+                .extend(SYSTEM_PATTERNS.iter().map(ToString::to_string));
+        }
 
         // Update desktop database
         let _ = Command::new("update-desktop-database")
@@ -178,46 +377,21 @@ impl CursorReset {
     }
 
     /// Clean files matching patterns in a directory.
-    fn clean_by_patterns(&self, dir: &PathBuf, patterns: &[&str], stats: &mut CleanupStats) {
-        if !dir.exists() {
-            return;
-        }
-
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.filter_map(std::result::Result::ok) {
-                let path = entry.path();
-                let name = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("");
-
-                for pattern in patterns {
-                    if matches_pattern(name, pattern) {
-                        if std::fs::remove_file(&path).is_ok() {
-                            stats.files_removed += 1;
-                            stats.paths_cleaned.push(path.display().to_string());
-                        }
-                        break;
-                    }
-                }
+    fn clean_by_patterns(dir: &Path, patterns: &[&str], stats: &mut CleanupStats) {
+        for path in matching_files(dir, patterns) {
+            if std::fs::remove_file(&path).is_ok() {
+                stats.files_removed += 1;
+                stats.paths_cleaned.push(path.display().to_string());
             }
         }
     }
 
     /// Clean system-level files (requires sudo).
-    fn clean_system_files(&self, stats: &mut CleanupStats) {
-        let system_paths = [
-            "/usr/share/applications/cursor*.desktop",
-            "/usr/share/applications/co.anysphere.cursor*.desktop",
-            "/usr/share/icons/hicolor/*/apps/cursor.png",
-            "/usr/share/icons/hicolor/*/apps/co.anysphere.cursor.*",
-            "/usr/share/pixmaps/cursor*.*",
-            "/usr/share/pixmaps/co.anysphere.cursor.*",
-        ];
-
-        for pattern in system_paths {
+    fn clean_system_files(stats: &mut CleanupStats) {
+        for pattern in SYSTEM_PATTERNS {
             // Use sudo rm with glob pattern via sh
             let result = Command::new("sudo")
-                .args(["sh", "-c", &format!("rm -f {}", pattern)])
+                .args(["sh", "-c", &format!("rm -f {pattern}")])
                 .status();
 
             if result.is_ok() {
@@ -231,8 +405,12 @@ impl CursorReset {
             .status();
     }
 
-    /// Clean AppImage files from downloads directory.
+    /// Clean `AppImage` files from downloads directory.
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn clean_appimages(&self) -> Result<CleanupStats> {
+        ensure_writable("delete Cursor images")?;
         if !self.clean_appimage {
             return Ok(CleanupStats::default());
         }
@@ -240,33 +418,19 @@ impl CursorReset {
         tracing::info!("Cleaning AppImage files...");
 
         let mut stats = CleanupStats::default();
+        let home = dirs::home_dir().unwrap_or_default();
+        let (files, squashfs) = self.appimage_targets(&home);
 
-        if !self.downloads_dir.exists() {
-            return Ok(stats);
-        }
-
-        if let Ok(entries) = std::fs::read_dir(&self.downloads_dir) {
-            for entry in entries.filter_map(std::result::Result::ok) {
-                let path = entry.path();
-                let name = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("");
-
-                // Match Cursor-*.AppImage pattern
-                if name.starts_with("Cursor-") && name.ends_with(".AppImage") {
-                    if std::fs::remove_file(&path).is_ok() {
-                        stats.files_removed += 1;
-                        stats.paths_cleaned.push(path.display().to_string());
-                        tracing::info!(path = %path.display(), "Removed AppImage");
-                    }
-                }
+        for path in files {
+            if std::fs::remove_file(&path).is_ok() {
+                stats.files_removed += 1;
+                stats.paths_cleaned.push(path.display().to_string());
+                tracing::info!(path = %path.display(), "Removed AppImage");
             }
         }
 
         // Also clean extracted squashfs-root
-        let home = dirs::home_dir().unwrap_or_default();
-        let squashfs = home.join("squashfs-root");
-        if squashfs.exists() {
+        if let Some(squashfs) = squashfs {
             if std::fs::remove_dir_all(&squashfs).is_ok() {
                 stats.dirs_removed += 1;
                 stats.paths_cleaned.push(squashfs.display().to_string());
@@ -277,6 +441,9 @@ impl CursorReset {
     }
 
     /// Run the complete reset process.
+    ///
+    /// # Errors
+    /// Returns error if the processes or files cannot be accessed.
     pub fn run_full_reset(&self) -> Result<ResetResult> {
         let kill_result = self.kill_cursor()?;
         let config_stats = self.clean_config_dirs()?;
@@ -294,19 +461,77 @@ impl CursorReset {
     }
 }
 
-/// Simple glob pattern matching (supports * wildcard).
-fn matches_pattern(name: &str, pattern: &str) -> bool {
-    if pattern.contains('*') {
-        let parts: Vec<&str> = pattern.split('*').collect();
+/// Check whether a Cursor process is currently running.
+#[must_use]
+pub fn is_cursor_running() -> bool {
+    Command::new("ps")
+        .args(["-A", "-o", "comm="])
+        .output()
+        .is_ok_and(|output| lists_cursor(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Whether a `ps -o comm=` listing has a Cursor process, in any case, as a
+/// bare name (Linux) or the last component of the executable path (macOS).
+fn lists_cursor(processes: &str) -> bool {
+    processes
+        .lines()
+        .filter_map(|comm| Path::new(comm.trim()).file_name()?.to_str())
+        .any(|name| name.eq_ignore_ascii_case(PROCESS_NAME))
+}
+
+/// Determine the home directory.
+fn home_dir() -> Result<PathBuf> {
+    dirs::home_dir().ok_or_else(|| AppError::Config {
+        message: "Could not determine home directory".into(),
+    })
+}
+
+/// Files in a directory whose names match any of the patterns.
+fn matching_files(dir: &Path, patterns: &[&str]) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(name, pattern))
+        })
+        .collect()
+}
 
-        if parts.len() == 2 {
-            // Pattern like "cursor*" or "*cursor" or "cursor*.desktop"
-            let (prefix, suffix) = (parts[0], parts[1]);
-            return name.starts_with(prefix) && name.ends_with(suffix);
+/// Simple glob pattern matching (supports * wildcard), ignoring ASCII case
+/// since Cursor names its files both `cursor…` and `Cursor…`.
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let (name, pattern) = (name.to_ascii_lowercase(), pattern.to_ascii_lowercase());
+    let mut parts = pattern.split('*');
+    let Some(prefix) = parts.next() else {
+        return name.is_empty();
+    };
+
+    let Some(mut rest) = name.strip_prefix(prefix) else {
+        return false;
+    };
+
+    let middle: Vec<&str> = parts.collect();
+    let Some((suffix, inner)) = middle.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+
+    // Pattern like "cursor*.desktop" or "cursor*.*": match inner parts in order
+    for part in inner {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
         }
     }
 
-    name == pattern
+    rest.len() >= suffix.len() && rest.ends_with(suffix)
 }
 
 /// Result of killing processes.
@@ -338,7 +563,7 @@ pub struct CleanupStats {
 
 impl CleanupStats {
     /// Merge another stats into this one.
-    pub fn merge(&mut self, other: CleanupStats) {
+    pub fn merge(&mut self, other: Self) {
         self.dirs_removed += other.dirs_removed;
         self.files_removed += other.files_removed;
         self.paths_cleaned.extend(other.paths_cleaned);
@@ -346,6 +571,17 @@ impl CleanupStats {
     }
 }
 
+/// Everything a reset would touch, for dry runs.
+#[derive(Debug, Default)]
+pub struct ResetPlan {
+    /// Commands that would be executed.
+    pub commands: Vec<String>,
+    /// Directories that would be removed.
+    pub dirs: Vec<PathBuf>,
+    /// Files that would be removed.
+    pub files: Vec<PathBuf>,
+}
+
 /// Complete reset result.
 #[derive(Debug)]
 pub struct ResetResult {
@@ -355,7 +591,7 @@ pub struct ResetResult {
     pub config_stats: CleanupStats,
     /// Desktop entries cleanup stats.
     pub desktop_stats: CleanupStats,
-    /// AppImage cleanup stats.
+    /// `AppImage` cleanup stats.
     pub appimage_stats: CleanupStats,
     /// Machine ID reset result.
     pub machine_id: MachineIdResult,
@@ -365,6 +601,19 @@ pub struct ResetResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_lists_cursor() {
+        assert!(lists_cursor("systemd\nbash\ncursor\n"));
+        assert!(lists_cursor(
+            "/sbin/launchd\n/Applications/Cursor.app/Contents/MacOS/Cursor\n"
+        ));
+        assert!(!lists_cursor("bash\ncursor-chat-han\ncursor-agent\n"));
+        assert!(!lists_cursor(
+            "/Applications/Cursor.app/Contents/MacOS/Cursor Helper\n"
+        ));
+        assert!(!lists_cursor(""));
+    }
+
     #[test]
     fn test_matches_pattern() {
         assert!(matches_pattern("cursor.desktop", "cursor*.desktop"));
@@ -373,6 +622,44 @@ mod tests {
 
         assert!(matches_pattern("cursor.png", "cursor*.*"));
         assert!(matches_pattern("cursor-icon.svg", "cursor*.*"));
+
+        // macOS and the AppImage capitalize the name
+        assert!(matches_pattern("Cursor.desktop", "cursor*.desktop"));
+        assert!(matches_pattern(
+            "Cursor-0.45.2-x86_64.AppImage",
+            "Cursor-*.AppImage"
+        ));
+        assert!(matches_pattern("Cursor", "cursor"));
+        assert!(!matches_pattern("Cursors", "cursor"));
+    }
+
+    #[test]
+    fn test_reset_steps() {
+        let full = ResetSteps::full(false);
+        assert!(full.machine_id && !full.appimage);
+        assert!(!full.without_machine_id().machine_id);
+
+        let kill = ResetSteps::kill_only();
+        assert!(kill.kill && !kill.is_destructive());
+    }
+
+    #[test]
+    fn test_plan_without_sudo() {
+        let reset = CursorReset::new(false).without_sudo();
+        let plan = reset.plan(ResetSteps::full(false)).unwrap();
+
+        assert!(plan.commands.iter().all(|c| !c.starts_with("sudo")));
+        assert!(plan.commands.iter().any(|c| c == "killall cursor"));
+    }
+
+    #[test]
+    fn test_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cursor.desktop"), "").unwrap();
+        std::fs::write(dir.path().join("other.desktop"), "").unwrap();
+
+        let files = matching_files(dir.path(), DESKTOP_PATTERNS);
+        assert_eq!(files, vec![dir.path().join("cursor.desktop")]);
     }
 
     #[test]
@@ -398,4 +685,3 @@ mod tests {
         assert_eq!(stats1.paths_cleaned.len(), 2);
     }
 }
-
@@ -1,6 +1,6 @@
 //! Cursor database writer for restoring chat history.
 //!
-//! Writes chat data back to Cursor's SQLite database,
+//! Writes chat data back to Cursor's `SQLite` database,
 //! restoring conversations after a reset/cleanup.
 
 use std::path::Path;
@@ -9,6 +9,8 @@ use rusqlite::{params, Connection, OpenFlags};
 
 use crate::domain::{AppError, Bubble, BubbleType, Conversation, Result};
 
+use super::read_only::ensure_writable;
+
 /// Writer for Cursor's state database.
 pub struct CursorWriter {
     conn: Connection,
@@ -20,7 +22,11 @@ impl CursorWriter {
     /// # Errors
     /// Returns error if database cannot be opened.
     pub fn open(path: &Path) -> Result<Self> {
-        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        ensure_writable("write to Cursor's database")?;
+
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE
+            | OpenFlags::SQLITE_OPEN_CREATE
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX;
 
         let conn = Connection::open_with_flags(path, flags).map_err(AppError::database)?;
 
@@ -43,7 +49,7 @@ impl CursorWriter {
     pub fn restore_conversation(&self, conv: &Conversation) -> Result<()> {
         // Write composer data
         let composer_key = format!("composerData:{}", conv.composer_id);
-        let composer_value = self.serialize_composer(conv)?;
+        let composer_value = Self::serialize_composer(conv)?;
 
         self.conn
             .execute(
@@ -55,7 +61,7 @@ impl CursorWriter {
         // Write bubbles
         for bubble in &conv.bubbles {
             let bubble_key = format!("bubbleId:{}:{}", conv.composer_id, bubble.bubble_id);
-            let bubble_value = self.serialize_bubble(bubble)?;
+            let bubble_value = Self::serialize_bubble(bubble)?;
 
             self.conn
                 .execute(
@@ -66,7 +72,7 @@ impl CursorWriter {
         }
 
         tracing::debug!(
-            composer_id = &conv.composer_id[..8],
+            composer_id = conv.short_id(),
             bubbles = conv.bubbles.len(),
             "Restored conversation"
         );
@@ -74,8 +80,64 @@ impl CursorWriter {
         Ok(())
     }
 
+    /// Delete a conversation from Cursor's database.
+    ///
+    /// Removes `composerData:<id>` and every `<kind>:<id>:...` key (bubbles,
+    /// checkpoints, request context, code block diffs).
+    ///
+    /// # Errors
+    /// Returns error if the delete fails.
+    pub fn delete_conversation(&self, composer_id: &str) -> Result<usize> {
+        let removed = self
+            .conn
+            .execute(
+                "DELETE FROM cursorDiskKV
+                 WHERE key = 'composerData:' || ?1 OR instr(key, ':' || ?1 || ':') > 0",
+                [composer_id],
+            )
+            .map_err(AppError::database)?;
+
+        tracing::debug!(composer_id, removed, "Deleted conversation from Cursor");
+
+        Ok(removed)
+    }
+
+    /// Delete several conversations in one transaction, as
+    /// [`Self::delete_conversation`] does for one. Returns the number of
+    /// keys removed.
+    ///
+    /// # Errors
+    /// Returns error if a delete fails; nothing is removed then.
+    pub fn delete_conversations(&self, composer_ids: &[String]) -> Result<usize> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(AppError::database)?;
+        let mut removed = 0;
+        for composer_id in composer_ids {
+            removed += self.delete_conversation(composer_id)?;
+        }
+        tx.commit().map_err(AppError::database)?;
+
+        Ok(removed)
+    }
+
+    /// Rebuild the database file so space freed by deletes is returned to
+    /// the file system.
+    ///
+    /// # Errors
+    /// Returns error if the database is busy or the vacuum fails.
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn
+            .execute_batch(
+                "PRAGMA wal_checkpoint(TRUNCATE);
+                 VACUUM;",
+            )
+            .map_err(AppError::database)
+    }
+
     /// Serialize composer data to JSON.
-    fn serialize_composer(&self, conv: &Conversation) -> Result<Vec<u8>> {
+    fn serialize_composer(conv: &Conversation) -> Result<Vec<u8>> {
         let data = serde_json::json!({
             "_v": 10,
             "composerId": conv.composer_id,
@@ -112,7 +174,7 @@ impl CursorWriter {
     }
 
     /// Serialize bubble data to JSON.
-    fn serialize_bubble(&self, bubble: &Bubble) -> Result<Vec<u8>> {
+    fn serialize_bubble(bubble: &Bubble) -> Result<Vec<u8>> {
         let bubble_type: u8 = match bubble.bubble_type {
             BubbleType::User => 1,
             BubbleType::Assistant => 2,
@@ -145,6 +207,9 @@ impl CursorWriter {
     }
 
     /// Check if database is empty (was reset).
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
     pub fn is_empty(&self) -> Result<bool> {
         let count: i64 = self
             .conn
@@ -159,6 +224,9 @@ impl CursorWriter {
     }
 
     /// Get count of conversations in Cursor DB.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
     pub fn conversation_count(&self) -> Result<usize> {
         let count: i64 = self
             .conn
@@ -169,7 +237,7 @@ impl CursorWriter {
             )
             .map_err(AppError::database)?;
 
-        Ok(count as usize)
+        Ok(usize::try_from(count).unwrap_or(0))
     }
 }
 
@@ -186,5 +254,35 @@ mod tests {
         let writer = CursorWriter::open(&db_path).unwrap();
         assert!(writer.is_empty().unwrap());
     }
-}
 
+    #[test]
+    fn test_delete_conversation_removes_only_its_keys() {
+        let dir = tempdir().unwrap();
+        let writer = CursorWriter::open(&dir.path().join("test.vscdb")).unwrap();
+
+        for key in [
+            "composerData:abc-1",
+            "bubbleId:abc-1:b1",
+            "bubbleId:abc-1:b2",
+            "checkpointId:abc-1:c1",
+            "composerData:abc-12",
+            "bubbleId:abc-12:b1",
+        ] {
+            writer
+                .conn
+                .execute(
+                    "INSERT INTO cursorDiskKV (key, value) VALUES (?1, '{}')",
+                    [key],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(writer.delete_conversation("abc-1").unwrap(), 4);
+        assert_eq!(writer.conversation_count().unwrap(), 1);
+
+        let ids = ["abc-12".to_string(), "missing".to_string()];
+        assert_eq!(writer.delete_conversations(&ids).unwrap(), 2);
+        assert!(writer.is_empty().unwrap());
+        writer.vacuum().unwrap();
+    }
+}
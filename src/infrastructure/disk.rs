@@ -0,0 +1,50 @@
+//! Filesystem capacity queries.
+//!
+//! Uses the POSIX `df` command so no platform-specific bindings are needed.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::domain::{AppError, Result};
+
+/// Bytes available to the current user on the filesystem holding `path`.
+///
+/// # Errors
+/// Returns error if `df` cannot be run or its output is not understood.
+pub fn available_space(path: &Path) -> Result<u64> {
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .map_err(|e| AppError::io("Failed to run df", e))?;
+
+    if !output.status.success() {
+        return Err(AppError::Config {
+            message: format!("df failed for {}", path.display()),
+        });
+    }
+
+    parse_df_available(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| AppError::Config {
+        message: "Unrecognized df output".into(),
+    })
+}
+
+/// Extract the "Available" column (in KiB) from `df -P` output, as bytes.
+fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kib: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_available() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/nvme0n1p2   479079112 312301128 142370592      69% /\n";
+        assert_eq!(parse_df_available(output), Some(142_370_592 * 1024));
+        assert_eq!(parse_df_available("garbage"), None);
+    }
+}
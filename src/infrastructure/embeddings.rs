@@ -0,0 +1,210 @@
+//! Embedding backends for the semantic index.
+//!
+//! The hashed backend hashes word and character n-grams into a fixed-size
+//! vector in-process, so chat text never leaves the machine and no model
+//! files are needed. It is a bag of words, not a language model: it matches
+//! shared terms, not meaning. The local backend runs a sentence-transformer
+//! from `embeddings.model_dir` in-process (`local-model` feature), which does
+//! match meaning. The API backend uses the `/embeddings` route of an
+//! OpenAI-compatible endpoint, which may itself be a local server.
+
+use crate::domain::{AppConfig, AppError, EmbeddingBackend, LlmConfig, Result};
+
+use super::llm_client::LlmClient;
+
+/// Dimensions of hashed embedding vectors.
+const HASHED_DIMS: usize = 512;
+
+/// Weight of character trigrams relative to whole words.
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Turns texts into fixed-size vectors.
+pub trait Embedder {
+    /// Identifier of the model; vectors from different models are not comparable.
+    fn model_id(&self) -> &str;
+
+    /// Embed `texts`, returning one vector per text in the same order.
+    ///
+    /// # Errors
+    /// Returns error if the backend fails.
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Create the embedder selected by `embeddings.backend`.
+///
+/// # Errors
+/// Returns error if the API backend cannot be configured, or the local
+/// model is not configured, not built in or cannot be loaded.
+pub fn create_embedder(config: &AppConfig) -> Result<Box<dyn Embedder>> {
+    match config.embeddings.backend {
+        // The ID predates the rename, so existing indexes stay valid
+        EmbeddingBackend::Hashed => Ok(Box::new(HashedEmbedder {
+            model_id: format!("local:ngram-{HASHED_DIMS}"),
+        })),
+        EmbeddingBackend::Local => create_local_embedder(config),
+        EmbeddingBackend::Api => {
+            let embeddings = &config.embeddings;
+            let llm = LlmConfig {
+                endpoint: embeddings
+                    .endpoint
+                    .clone()
+                    .unwrap_or_else(|| config.llm.endpoint.clone()),
+                api_key_env: embeddings
+                    .api_key_env
+                    .clone()
+                    .unwrap_or_else(|| config.llm.api_key_env.clone()),
+                ..config.llm.clone()
+            };
+            Ok(Box::new(ApiEmbedder {
+                client: LlmClient::new(&llm)?,
+                model_id: format!("api:{}", embeddings.api_model),
+                model: embeddings.api_model.clone(),
+            }))
+        }
+    }
+}
+
+/// The sentence-transformer in `embeddings.model_dir`.
+fn create_local_embedder(config: &AppConfig) -> Result<Box<dyn Embedder>> {
+    let Some(dir) = &config.embeddings.model_dir else {
+        return Err(AppError::Config {
+            message: "embeddings.backend = \"local\" needs embeddings.model_dir, a directory \
+                      with config.json, tokenizer.json and model.safetensors"
+                .into(),
+        });
+    };
+
+    #[cfg(feature = "local-model")]
+    {
+        Ok(Box::new(super::sentence_embedder::SentenceEmbedder::load(
+            dir,
+        )?))
+    }
+    #[cfg(not(feature = "local-model"))]
+    {
+        Err(AppError::Config {
+            message: format!(
+                "Cannot load the model in {}: this build has no local model support \
+                 (rebuild with --features local-model, or use backend = \"hashed\")",
+                dir.display()
+            ),
+        })
+    }
+}
+
+/// Embeddings computed by an OpenAI-compatible endpoint.
+struct ApiEmbedder {
+    client: LlmClient,
+    model_id: String,
+    model: String,
+}
+
+impl Embedder for ApiEmbedder {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.client.embed(&self.model, texts)
+    }
+}
+
+/// Hashed bag of words and character trigrams, computed in-process.
+///
+/// Trigrams let related word forms ("parser", "parsing") and identifiers
+/// share dimensions; term counts are dampened logarithmically.
+struct HashedEmbedder {
+    model_id: String,
+}
+
+impl Embedder for HashedEmbedder {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| hash_embedding(text)).collect())
+    }
+}
+
+fn hash_embedding(text: &str) -> Vec<f32> {
+    let mut counts = vec![0.0f32; HASHED_DIMS];
+
+    let lower = text.to_lowercase();
+    for word in lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1)
+    {
+        add_feature(&mut counts, word.as_bytes(), 1.0);
+
+        let padded: Vec<char> = format!("#{word}#").chars().collect();
+        for gram in padded.windows(3) {
+            let gram: String = gram.iter().collect();
+            add_feature(&mut counts, gram.as_bytes(), TRIGRAM_WEIGHT);
+        }
+    }
+
+    // Sublinear term frequency keeps repeated words from dominating
+    for value in &mut counts {
+        *value = value.signum() * value.abs().ln_1p();
+    }
+
+    let norm = counts.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut counts {
+            *value /= norm;
+        }
+    }
+    counts
+}
+
+/// Add `weight` to the hashed slot of `feature`, with a hashed sign to
+/// cancel out collisions on average.
+fn add_feature(counts: &mut [f32], feature: &[u8], weight: f32) {
+    let hash = fnv1a(feature);
+    let slot = usize::try_from(hash % counts.len() as u64).unwrap_or_default();
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    counts[slot] += sign * weight;
+}
+
+/// 64-bit FNV-1a; stable across runs and platforms, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn similarity(a: &str, b: &str) -> f32 {
+        hash_embedding(a)
+            .iter()
+            .zip(hash_embedding(b))
+            .map(|(x, y)| x * y)
+            .sum()
+    }
+
+    #[test]
+    fn test_hash_embedding_ranks_related_text_higher() {
+        let query = "sqlite database locked error";
+        let related = similarity(query, "Why is my SQLite database locked during sync?");
+        let unrelated = similarity(query, "Center the button with flexbox in CSS");
+
+        assert!(related > unrelated);
+        assert!((similarity(query, query) - 1.0).abs() < 1e-5);
+        assert!(hash_embedding("").iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_local_backend_needs_model_dir() {
+        let mut config: AppConfig = toml::from_str("[embeddings]\nbackend = \"local\"").unwrap();
+        assert_eq!(config.embeddings.backend, EmbeddingBackend::Local);
+        assert!(create_embedder(&config).is_err());
+
+        // Missing files, or no model support built in
+        config.embeddings.model_dir = Some("/nonexistent/all-MiniLM-L6-v2".into());
+        assert!(create_embedder(&config).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Encrypted exports through the `age` or `gpg` command line tools.
+//!
+//! The export is piped into the tool, which writes the encrypted file
+//! itself, so the plaintext never touches the disk. An export that fails
+//! midway leaves no partial file behind.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::domain::{AppError, Result};
+
+/// Who an export is encrypted to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recipient {
+    /// An age (`age1…`) or SSH public key, encrypted with `age`.
+    Age(String),
+    /// A GPG key ID, fingerprint or e-mail, encrypted with `gpg`.
+    Gpg(String),
+}
+
+impl std::str::FromStr for Recipient {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            Err("the recipient cannot be empty".into())
+        } else if s.starts_with("age1") || s.starts_with("ssh-") {
+            Ok(Self::Age(s.to_string()))
+        } else {
+            Ok(Self::Gpg(s.to_string()))
+        }
+    }
+}
+
+impl Recipient {
+    /// Extension added to encrypted files.
+    #[must_use]
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Age(_) => "age",
+            Self::Gpg(_) => "gpg",
+        }
+    }
+
+    /// `path` with the extension of encrypted files, unless it has it.
+    #[must_use]
+    pub fn encrypted_path(&self, path: &str) -> String {
+        let suffix = format!(".{}", self.extension());
+        if path.ends_with(&suffix) {
+            path.to_string()
+        } else {
+            format!("{path}{suffix}")
+        }
+    }
+
+    /// Start encrypting into `output`, or ASCII-armored to stdout if `None`.
+    ///
+    /// # Errors
+    /// Returns error if the tool cannot be started.
+    pub fn encrypt_to(&self, output: Option<&Path>) -> Result<EncryptedWriter> {
+        let (tool, command) = match self {
+            Self::Age(key) => {
+                let mut command = Command::new("age");
+                command.args(["--encrypt", "--recipient", key]);
+                if let Some(path) = output {
+                    command.arg("--output").arg(path);
+                } else {
+                    command.arg("--armor");
+                }
+                ("age", command)
+            }
+            Self::Gpg(key) => {
+                let mut command = Command::new("gpg");
+                command.args(["--batch", "--yes", "--encrypt", "--recipient", key]);
+                if let Some(path) = output {
+                    command.arg("--output").arg(path);
+                } else {
+                    command.arg("--armor");
+                }
+                ("gpg", command)
+            }
+        };
+
+        EncryptedWriter::spawn(tool, command, output)
+    }
+}
+
+/// Writer piping into a running `age` or `gpg`.
+///
+/// Call [`EncryptedWriter::finish`] once everything is written; only then
+/// is the output complete. Dropped before that, or if the tool fails, it
+/// stops the tool and removes the output file.
+pub struct EncryptedWriter {
+    tool: &'static str,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    output: Option<PathBuf>,
+    finished: bool,
+}
+
+impl EncryptedWriter {
+    /// Run `command`, which writes to `output` (or stdout if `None`).
+    fn spawn(tool: &'static str, mut command: Command, output: Option<&Path>) -> Result<Self> {
+        let mut child = command.stdin(Stdio::piped()).spawn().map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                AppError::Config {
+                    message: format!("'{tool}' is not installed; install it to encrypt exports"),
+                }
+            } else {
+                AppError::io(format!("Failed to start {tool}"), e)
+            }
+        })?;
+        let stdin = child.stdin.take();
+
+        Ok(Self {
+            tool,
+            child,
+            stdin,
+            output: output.map(Path::to_path_buf),
+            finished: false,
+        })
+    }
+
+    /// Close the input and wait for the tool to write its output.
+    ///
+    /// # Errors
+    /// Returns error if the tool fails (e.g. an unknown recipient).
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| AppError::io(format!("{} did not finish", self.tool), e))?;
+        if !status.success() {
+            return Err(AppError::Config {
+                message: format!("{} failed to encrypt the export ({status})", self.tool),
+            });
+        }
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Write for EncryptedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::BrokenPipe))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin.as_mut().map_or(Ok(()), Write::flush)
+    }
+}
+
+impl Drop for EncryptedWriter {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // An error midway or a failed tool: the output is incomplete
+        drop(self.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(path) = &self.output {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipient_parse() {
+        let age: Recipient = "age1qyqszqgpqyqszqgpqyqszqgp".parse().unwrap();
+        assert_eq!(age.extension(), "age");
+        assert_eq!(
+            "ssh-ed25519 AAAA".parse::<Recipient>().unwrap().extension(),
+            "age"
+        );
+        let gpg: Recipient = "me@example.com".parse().unwrap();
+        assert_eq!(gpg, Recipient::Gpg("me@example.com".into()));
+        assert!("  ".parse::<Recipient>().is_err());
+
+        assert_eq!(gpg.encrypted_path("chats.md"), "chats.md.gpg");
+        assert_eq!(gpg.encrypted_path("chats.md.gpg"), "chats.md.gpg");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_export_leaves_no_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("chat.md.age");
+        let writer = |script: &str| {
+            let mut command = Command::new("sh");
+            command.args(["-c", script, "sh"]).arg(&output);
+            EncryptedWriter::spawn("sh", command, Some(&output)).unwrap()
+        };
+
+        // Dropped midway, e.g. when rendering the export fails
+        let mut unfinished = writer(r#"cat > "$1""#);
+        unfinished.write_all(b"partial").unwrap();
+        drop(unfinished);
+        assert!(!output.exists());
+
+        // The tool fails after writing part of its output
+        let mut failing = writer(r#"cat > "$1"; exit 1"#);
+        failing.write_all(b"partial").unwrap();
+        assert!(failing.finish().is_err());
+        assert!(!output.exists());
+
+        let mut ok = writer(r#"cat > "$1""#);
+        ok.write_all(b"done").unwrap();
+        ok.finish().unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "done");
+    }
+}
@@ -0,0 +1,563 @@
+//! Server-sent events endpoint for the daemon (`daemon --listen`).
+//!
+//! A minimal HTTP listener: `GET /events` keeps the connection open as a
+//! `text/event-stream`, `GET /openapi.json` describes the API, anything
+//! else gets a 404. Every request must carry the bearer token, so the
+//! listener can be bound beyond localhost, and browsers may only read it
+//! from the configured origin. Each request is read on its own thread, so a
+//! slow client does not hold up the others, and its head is bounded in size,
+//! header count and time. Events are pushed by the daemon
+//! after each sync; clients that stop reading are dropped.
+
+use std::collections::hash_map::RandomState;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Take, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::domain::{AppError, Result};
+
+/// How long a client may block a write before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a client has to send its request line and headers.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most bytes read for the request line and headers together.
+const MAX_HEAD_BYTES: u64 = 8 * 1024;
+
+/// Most headers read for a request.
+const MAX_HEADERS: usize = 64;
+
+/// Requests read at once; connections beyond it are closed.
+const MAX_PENDING: usize = 64;
+
+/// What a request must carry, and what browsers are told.
+struct Access {
+    token: String,
+    origin: Option<String>,
+}
+
+impl Access {
+    /// CORS headers, ending in `\r\n`, when an origin is configured.
+    fn cors_headers(&self) -> String {
+        self.origin.as_ref().map_or_else(String::new, |origin| {
+            format!("Access-Control-Allow-Origin: {origin}\r\nVary: Origin\r\n")
+        })
+    }
+}
+
+/// Connected `/events` clients.
+#[derive(Clone)]
+pub struct EventStream {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    local_addr: SocketAddr,
+}
+
+impl EventStream {
+    /// Listen on `addr` (e.g. `127.0.0.1:7878`) and accept clients carrying
+    /// `token` in the background. Browsers may read the stream from `origin`
+    /// only; from none if `None`.
+    ///
+    /// # Errors
+    /// Returns error if the token is empty or the address cannot be bound.
+    pub fn listen(addr: &str, token: &str, origin: Option<&str>) -> Result<Self> {
+        if token.trim().is_empty() {
+            return Err(AppError::Config {
+                message: "The event stream needs a non-empty sync.listen_token".into(),
+            });
+        }
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| AppError::io(format!("Failed to listen on {addr}"), e))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| AppError::io("Failed to read listen address", e))?;
+
+        let stream = Self {
+            clients: Arc::new(Mutex::new(Vec::new())),
+            local_addr,
+        };
+
+        let clients = Arc::clone(&stream.clients);
+        let access = Arc::new(Access {
+            token: token.to_string(),
+            origin: origin.map(str::to_string),
+        });
+        let pending = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                if pending.fetch_add(1, Ordering::SeqCst) >= MAX_PENDING {
+                    pending.fetch_sub(1, Ordering::SeqCst);
+                    tracing::debug!("Too many pending event stream requests; closing one");
+                    continue;
+                }
+                let (clients, access, pending) = (
+                    Arc::clone(&clients),
+                    Arc::clone(&access),
+                    Arc::clone(&pending),
+                );
+                std::thread::spawn(move || {
+                    let client = accept(connection, &access);
+                    pending.fetch_sub(1, Ordering::SeqCst);
+                    if let Some(client) = client {
+                        clients
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .push(client);
+                    }
+                });
+            }
+        });
+
+        Ok(stream)
+    }
+
+    /// Address actually bound (useful with port 0).
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Number of connected clients.
+    #[must_use]
+    pub fn client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Send `data` as JSON under the event name `event` to every client.
+    ///
+    /// # Errors
+    /// Returns error if `data` cannot be serialized.
+    pub fn broadcast<T: Serialize>(&self, event: &str, data: &T) -> Result<()> {
+        let json = serde_json::to_string(data).map_err(AppError::json_parse)?;
+        self.send(&format!("event: {event}\ndata: {json}\n\n"));
+        Ok(())
+    }
+
+    /// Send a comment line, so idle connections stay open and dead ones
+    /// are noticed.
+    pub fn heartbeat(&self) {
+        self.send(": keep-alive\n\n");
+    }
+
+    fn send(&self, message: &str) {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        clients.retain_mut(|client| {
+            let sent = client
+                .write_all(message.as_bytes())
+                .and_then(|()| client.flush());
+            if let Err(e) = &sent {
+                tracing::debug!(error = %e, "Dropping event stream client");
+            }
+            sent.is_ok()
+        });
+    }
+}
+
+/// A new random token for `sync.listen_token`, 64 hex characters.
+#[must_use]
+pub fn generate_token() -> String {
+    let mut seed = [0u8; 32];
+    let random = File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut seed));
+    if random.is_err() {
+        // No /dev/urandom: std seeds its hash keys from the OS
+        for chunk in seed.chunks_mut(8) {
+            let key = RandomState::new().build_hasher().finish();
+            chunk.copy_from_slice(&key.to_le_bytes());
+        }
+    }
+    blake3::hash(&seed).to_hex().to_string()
+}
+
+/// `OpenAPI` document of the listener, served on `GET /openapi.json`.
+#[must_use]
+pub fn openapi_document() -> serde_json::Value {
+    let unauthorized = json!({"description": "Missing or wrong bearer token"});
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "cursor-chat events",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Conversations and messages synced by `cursor-chat daemon`, \
+                            as server-sent events.",
+        },
+        "security": [{"bearer": []}],
+        "components": {
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "sync.listen_token from config.toml; EventSource \
+                                    clients, which cannot set headers, may pass it as \
+                                    ?token= instead.",
+                },
+            },
+            "schemas": {
+                "ConversationEvent": {
+                    "type": "object",
+                    "description": "`event: conversation`, once per created or updated conversation",
+                    "required": ["kind", "composer_id", "title", "messages", "synced_at"],
+                    "properties": {
+                        "kind": {"type": "string", "enum": ["created", "updated"]},
+                        "composer_id": {"type": "string"},
+                        "title": {"type": "string"},
+                        "messages": {"type": "integer", "minimum": 0},
+                        "synced_at": {"type": "string", "format": "date-time"},
+                    },
+                },
+                "MessageEvent": {
+                    "type": "object",
+                    "description": "`event: message`, once per new message",
+                    "required": ["composer_id", "bubble_id", "role", "created_at", "text"],
+                    "properties": {
+                        "composer_id": {"type": "string"},
+                        "bubble_id": {"type": "string"},
+                        "role": {"type": "string", "enum": ["user", "assistant", "unknown"]},
+                        "created_at": {"type": ["string", "null"], "format": "date-time"},
+                        "text": {"type": "string"},
+                    },
+                },
+            },
+        },
+        "paths": {
+            "/events": {
+                "get": {
+                    "summary": "Stream synced conversations and messages",
+                    "responses": {
+                        "200": {
+                            "description": "`conversation` and `message` events, with the \
+                                            JSON in `data`",
+                            "content": {"text/event-stream": {"schema": {"oneOf": [
+                                {"$ref": "#/components/schemas/ConversationEvent"},
+                                {"$ref": "#/components/schemas/MessageEvent"},
+                            ]}}},
+                        },
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "responses": {
+                        "200": {"description": "OpenAPI document", "content": {"application/json": {}}},
+                        "401": unauthorized,
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Whether the request line or headers carry `token`, compared in constant
+/// time.
+fn is_authorized(path: &str, headers: &[String], token: &str) -> bool {
+    let from_header = headers.iter().find_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("authorization")
+            .then(|| value.trim().strip_prefix("Bearer "))?
+    });
+    let from_query = path
+        .split_once('?')
+        .and_then(|(_, query)| query.split('&').find_map(|p| p.strip_prefix("token=")));
+
+    from_header.or(from_query).is_some_and(|given| {
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    })
+}
+
+/// Why a request head could not be read.
+#[derive(Debug, PartialEq, Eq)]
+enum HeadError {
+    /// Request line over [`MAX_HEAD_BYTES`]: answered with 400.
+    BadRequest,
+    /// Headers over [`MAX_HEAD_BYTES`] or [`MAX_HEADERS`]: answered with 431.
+    TooLarge,
+    /// Closed, failed or past [`READ_TIMEOUT`]: nothing to answer.
+    Closed,
+}
+
+/// Read the request line and the headers up to the blank line, within the
+/// size, count and time limits.
+fn read_head(connection: &TcpStream) -> std::result::Result<(String, Vec<String>), HeadError> {
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let clone = connection.try_clone().map_err(|_| HeadError::Closed)?;
+    let mut reader = BufReader::new(clone).take(MAX_HEAD_BYTES);
+
+    // One line, the socket timeout shrunk to what is left of the deadline
+    let read_line = |reader: &mut Take<BufReader<TcpStream>>, over: HeadError| {
+        let left = deadline
+            .checked_duration_since(Instant::now())
+            .filter(|left| !left.is_zero())
+            .ok_or(HeadError::Closed)?;
+        connection
+            .set_read_timeout(Some(left))
+            .map_err(|_| HeadError::Closed)?;
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|_| HeadError::Closed)?;
+        if line.ends_with('\n') {
+            Ok(line)
+        } else if reader.limit() == 0 {
+            Err(over)
+        } else {
+            Err(HeadError::Closed)
+        }
+    };
+
+    let request_line = read_line(&mut reader, HeadError::BadRequest)?;
+    let mut headers = Vec::new();
+    loop {
+        let header = read_line(&mut reader, HeadError::TooLarge)?;
+        if header.trim_end().is_empty() {
+            return Ok((request_line, headers));
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(HeadError::TooLarge);
+        }
+        headers.push(header);
+    }
+}
+
+/// Read the request and, for `GET /events`, answer with the stream headers.
+fn accept(mut connection: TcpStream, access: &Access) -> Option<TcpStream> {
+    connection.set_write_timeout(Some(WRITE_TIMEOUT)).ok()?;
+
+    let cors = access.cors_headers();
+    let (request_line, headers) = match read_head(&connection) {
+        Ok(head) => head,
+        Err(HeadError::Closed) => return None,
+        Err(error) => {
+            let status = if error == HeadError::TooLarge {
+                "431 Request Header Fields Too Large"
+            } else {
+                "400 Bad Request"
+            };
+            let _ = write!(
+                connection,
+                "HTTP/1.1 {status}\r\n{cors}Content-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+            return None;
+        }
+    };
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    // CORS preflight, which never carries the token
+    if request_line.starts_with("OPTIONS ") && access.origin.is_some() {
+        let _ = write!(
+            connection,
+            "HTTP/1.1 204 No Content\r\n{cors}\
+             Access-Control-Allow-Methods: GET\r\n\
+             Access-Control-Allow-Headers: Authorization\r\n\
+             Connection: close\r\n\r\n"
+        );
+        return None;
+    }
+    if !is_authorized(path, &headers, &access.token) {
+        let _ = write!(
+            connection,
+            "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer\r\n{cors}\
+             Content-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        return None;
+    }
+
+    let is_get = request_line.starts_with("GET ");
+    let route = path.split('?').next();
+    if is_get && route == Some("/openapi.json") {
+        let body = openapi_document().to_string();
+        let _ = write!(
+            connection,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{cors}\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        return None;
+    }
+    if !is_get || route != Some("/events") {
+        let _ = write!(
+            connection,
+            "HTTP/1.1 404 Not Found\r\n{cors}Content-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        return None;
+    }
+
+    write!(
+        connection,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/event-stream\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: keep-alive\r\n\
+         {cors}\r\n\
+         : connected\n\n"
+    )
+    .ok()?;
+    tracing::info!(peer = ?connection.peer_addr().ok(), "Event stream client connected");
+
+    Some(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "s3cret";
+
+    fn request(addr: SocketAddr, request: &str) -> String {
+        let mut client = TcpStream::connect(addr).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_event_stream_requires_token() {
+        assert!(EventStream::listen("127.0.0.1:0", " ", None).is_err());
+        let addr = EventStream::listen("127.0.0.1:0", TOKEN, None)
+            .unwrap()
+            .local_addr();
+
+        for unauthorized in [
+            "GET /events HTTP/1.1\r\n\r\n",
+            "GET /openapi.json HTTP/1.1\r\nAuthorization: Bearer s3cre\r\n\r\n",
+            "GET /other?token=wrong HTTP/1.1\r\n\r\n",
+        ] {
+            assert!(request(addr, unauthorized).starts_with("HTTP/1.1 401"));
+        }
+
+        let missing = request(addr, "GET /other?token=s3cret HTTP/1.1\r\n\r\n");
+        assert!(missing.starts_with("HTTP/1.1 404"));
+
+        let spec = request(
+            addr,
+            "GET /openapi.json HTTP/1.1\r\nauthorization: Bearer s3cret\r\n\r\n",
+        );
+        assert!(spec.starts_with("HTTP/1.1 200"));
+        let body: serde_json::Value =
+            serde_json::from_str(spec.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body, openapi_document());
+        assert!(body["paths"]["/events"]["get"].is_object());
+    }
+
+    #[test]
+    fn test_event_stream_cors() {
+        let closed = EventStream::listen("127.0.0.1:0", TOKEN, None)
+            .unwrap()
+            .local_addr();
+        let response = request(closed, "OPTIONS /events HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 401"));
+        assert!(!response.contains("Access-Control"));
+
+        let origin = "http://localhost:3000";
+        let open = EventStream::listen("127.0.0.1:0", TOKEN, Some(origin))
+            .unwrap()
+            .local_addr();
+        let preflight = request(open, "OPTIONS /events HTTP/1.1\r\n\r\n");
+        assert!(preflight.starts_with("HTTP/1.1 204"));
+        assert!(preflight.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+        assert!(preflight.contains("Access-Control-Allow-Headers: Authorization"));
+        let spec = request(open, "GET /openapi.json?token=s3cret HTTP/1.1\r\n\r\n");
+        assert!(spec.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+    }
+
+    #[test]
+    fn test_event_stream_slow_client_does_not_block() {
+        let addr = EventStream::listen("127.0.0.1:0", TOKEN, None)
+            .unwrap()
+            .local_addr();
+        // Connected but silent, so its request is read until READ_TIMEOUT
+        let _silent = TcpStream::connect(addr).unwrap();
+
+        let started = std::time::Instant::now();
+        let missing = request(addr, "GET /other?token=s3cret HTTP/1.1\r\n\r\n");
+        assert!(missing.starts_with("HTTP/1.1 404"));
+        assert!(started.elapsed() < READ_TIMEOUT / 2);
+    }
+
+    #[test]
+    fn test_event_stream_limits_request_head() {
+        let addr = EventStream::listen("127.0.0.1:0", TOKEN, None)
+            .unwrap()
+            .local_addr();
+        let limit = usize::try_from(MAX_HEAD_BYTES).unwrap();
+
+        // Each request ends where the limit is hit, so nothing is left unread
+        let endless_line = "G".repeat(limit);
+        assert!(request(addr, &endless_line).starts_with("HTTP/1.1 400"));
+
+        let request_line = "GET /events?token=s3cret HTTP/1.1\r\n";
+        let long_header = format!(
+            "{request_line}X-Fill: {}",
+            "a".repeat(limit - request_line.len() - "X-Fill: ".len())
+        );
+        assert!(request(addr, &long_header).starts_with("HTTP/1.1 431"));
+
+        let many_headers = format!("{request_line}{}", "X: 1\r\n".repeat(MAX_HEADERS + 1));
+        assert!(request(addr, &many_headers).starts_with("HTTP/1.1 431"));
+
+        let enough_headers = format!(
+            "GET /other?token=s3cret HTTP/1.1\r\n{}\r\n",
+            "X: 1\r\n".repeat(MAX_HEADERS)
+        );
+        assert!(request(addr, &enough_headers).starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_event_stream_delivers_events() {
+        let stream = EventStream::listen("127.0.0.1:0", TOKEN, None).unwrap();
+        let addr = stream.local_addr();
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .write_all(
+                b"GET /events HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer s3cret\r\n\r\n",
+            )
+            .unwrap();
+        for _ in 0..100 {
+            if stream.client_count() == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(stream.client_count(), 1);
+
+        stream
+            .broadcast("conversation", &serde_json::json!({"id": "abc"}))
+            .unwrap();
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut received = String::new();
+        let mut buf = [0; 512];
+        while !received.contains("\n\n") || !received.contains("data:") {
+            let n = client.read(&mut buf).unwrap();
+            received.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        assert!(received.contains("text/event-stream"));
+        assert!(received.contains("event: conversation\ndata: {\"id\":\"abc\"}\n\n"));
+    }
+}
@@ -0,0 +1,89 @@
+//! External formatter plugins (`[formatters.<name>]` in config).
+//!
+//! A plugin is an executable that reads the conversations as a JSON array
+//! (the same document as `-f json`) on stdin and writes the formatted output
+//! to stdout.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::domain::{AppError, FormatterPlugin, Result};
+
+/// Run `plugin` with `input` on stdin, copying its stdout into `output`.
+///
+/// # Errors
+/// Returns error if the command cannot be started, exits with a failure
+/// status, or the output cannot be written.
+pub fn run_formatter_plugin(
+    name: &str,
+    plugin: &FormatterPlugin,
+    input: Vec<u8>,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let mut child = Command::new(&plugin.command)
+        .args(&plugin.args)
+        .env("CURSOR_CHAT_FORMAT", name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::io(format!("Failed to start formatter '{name}'"), e))?;
+
+    // Feed stdin from another thread so a plugin that writes before it has
+    // read everything cannot deadlock on a full pipe
+    let mut stdin = child.stdin.take();
+    let feeder = std::thread::spawn(move || -> io::Result<()> {
+        if let Some(stdin) = stdin.as_mut() {
+            stdin.write_all(&input)?;
+        }
+        Ok(())
+    });
+
+    let mut copied = Ok(0);
+    if let Some(mut stdout) = child.stdout.take() {
+        copied = io::copy(&mut stdout, output);
+        // Drain what is left if the output failed, so the plugin can exit
+        let _ = io::copy(&mut stdout, &mut io::sink());
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| AppError::io(format!("Formatter '{name}' did not finish"), e))?;
+    if !status.success() {
+        return Err(AppError::Config {
+            message: format!("Formatter '{name}' failed ({status})"),
+        });
+    }
+
+    copied.map_err(|e| AppError::io("Failed to write formatter output", e))?;
+    // A plugin that exits without reading all its input is not an error
+    let _ = feeder.join();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_formatter_plugin_pipes_json() {
+        let plugin = FormatterPlugin {
+            command: "sh".into(),
+            args: vec![
+                "-c".into(),
+                "echo \"$CURSOR_CHAT_FORMAT\"; tr a-z A-Z".into(),
+            ],
+            extension: "txt".into(),
+        };
+        let mut output = Vec::new();
+        run_formatter_plugin("upper", &plugin, b"[{\"id\": 1}]".to_vec(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "upper\n[{\"ID\": 1}]");
+
+        let failing = FormatterPlugin {
+            args: vec!["-c".into(), "exit 3".into()],
+            ..plugin
+        };
+        assert!(run_formatter_plugin("x", &failing, Vec::new(), &mut Vec::new()).is_err());
+    }
+}
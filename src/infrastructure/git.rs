@@ -0,0 +1,163 @@
+//! Git repository detection and history for workspace paths.
+//!
+//! Uses the `git` binary, so a missing git install simply means no
+//! repository is detected.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{AppError, GitCommit, GitInfo, Result};
+
+/// Separates the fields of one commit in `git log` output.
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Repository root, origin URL, branch and commit checked out at `path`.
+///
+/// Returns `None` if `path` is not inside a git work tree or git is not
+/// installed.
+#[must_use]
+pub fn detect_git(path: &Path) -> Option<GitInfo> {
+    if !path.is_dir() {
+        return None;
+    }
+
+    let repo_root = git_output(path, &["rev-parse", "--show-toplevel"])?;
+    Some(GitInfo {
+        repo_root,
+        origin_url: git_output(path, &["config", "--get", "remote.origin.url"]),
+        branch: git_output(path, &["symbolic-ref", "--short", "--quiet", "HEAD"]),
+        head_commit: git_output(path, &["rev-parse", "--verify", "--quiet", "HEAD"]),
+    })
+}
+
+/// Commits in the repository at `repo`, on any branch, committed between
+/// `since` and `until` (inclusive), oldest first.
+///
+/// # Errors
+/// Returns error if git cannot be run or `repo` is not a repository.
+pub fn commits_between(
+    repo: &Path,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<GitCommit>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            "--all",
+            "--reverse",
+            "--format=%H%x1f%cI%x1f%an%x1f%s",
+        ])
+        .arg(format!("--since={}", since.to_rfc3339()))
+        .arg(format!("--until={}", until.to_rfc3339()))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| AppError::io("Failed to run git", e))?;
+
+    if !output.status.success() {
+        return Err(AppError::Config {
+            message: format!(
+                "git log failed in {}: {}",
+                repo.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_commit)
+        .collect())
+}
+
+/// One `hash, date, author, subject` line of `git log` output.
+fn parse_commit(line: &str) -> Option<GitCommit> {
+    let mut fields = line.splitn(4, FIELD_SEPARATOR);
+    let hash = fields.next()?.to_string();
+    let committed_at = DateTime::parse_from_rfc3339(fields.next()?)
+        .ok()?
+        .with_timezone(&Utc);
+
+    Some(GitCommit {
+        hash,
+        committed_at,
+        author: fields.next()?.to_string(),
+        subject: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Trimmed stdout of a successful git command, `None` if it fails or is empty.
+fn git_output(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_git() {
+        let dir = tempdir().unwrap();
+        assert!(detect_git(dir.path()).is_none());
+        assert!(detect_git(&dir.path().join("missing")).is_none());
+
+        let Some(_) = git_output(dir.path(), &["--version"]) else {
+            return; // git not installed
+        };
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q", "-b", "feature/x"]);
+        git(&["remote", "add", "origin", "git@example.com:me/repo.git"]);
+
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+        let info = detect_git(&nested).unwrap();
+        assert_eq!(
+            Path::new(&info.repo_root).canonicalize().unwrap(),
+            dir.path().canonicalize().unwrap()
+        );
+        assert_eq!(
+            info.origin_url.as_deref(),
+            Some("git@example.com:me/repo.git")
+        );
+        // No commits yet: the branch is known, the commit is not
+        assert_eq!(info.branch.as_deref(), Some("feature/x"));
+        assert_eq!(info.head_commit, None);
+    }
+
+    #[test]
+    fn test_parse_commit() {
+        let commit =
+            parse_commit("abc123\u{1f}2024-05-01T10:30:00+02:00\u{1f}Ana\u{1f}Fix: a \u{1f} b")
+                .unwrap();
+        assert_eq!(commit.hash, "abc123");
+        assert_eq!(
+            commit.committed_at.to_rfc3339(),
+            "2024-05-01T08:30:00+00:00"
+        );
+        assert_eq!(commit.author, "Ana");
+        assert_eq!(commit.subject, "Fix: a \u{1f} b");
+
+        assert!(parse_commit("abc123\u{1f}yesterday").is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! User hook scripts (`[hooks]` in config).
+//!
+//! A hook is a shell command run with a JSON payload on stdin and the event
+//! name in `CURSOR_CHAT_EVENT`. Hooks are best effort: a failing or slow
+//! script is logged and never fails the sync or restore that triggered it.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::domain::{AppError, Result};
+
+/// How long a hook may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a running hook is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run `command` for `event` if it is configured, logging any failure.
+pub fn fire_hook(command: Option<&str>, event: &str, payload: &impl Serialize) {
+    let Some(command) = command.filter(|c| !c.trim().is_empty()) else {
+        return;
+    };
+
+    match run_hook(command, event, payload, HOOK_TIMEOUT) {
+        Ok(()) => tracing::debug!(event, "Hook completed"),
+        Err(e) => tracing::warn!(event, error = %e, "Hook failed"),
+    }
+}
+
+/// Run `command` through the shell with `payload` as JSON on stdin.
+///
+/// # Errors
+/// Returns error if the command cannot be started, fails, or runs longer
+/// than `timeout` (it is killed then).
+pub fn run_hook(
+    command: &str,
+    event: &str,
+    payload: &impl Serialize,
+    timeout: Duration,
+) -> Result<()> {
+    let input = serde_json::to_vec(payload).map_err(AppError::json_parse)?;
+
+    let mut child = shell(command)
+        .env("CURSOR_CHAT_EVENT", event)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::io(format!("Failed to start {event} hook"), e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its payload closes stdin early; that is fine
+        let _ = stdin.write_all(&input);
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| AppError::io(format!("Failed to wait for {event} hook"), e))?
+        {
+            break status;
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Config {
+                message: format!("{event} hook timed out after {}s", timeout.as_secs()),
+            });
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    if !status.success() {
+        return Err(AppError::Config {
+            message: format!("{event} hook failed ({status})"),
+        });
+    }
+
+    Ok(())
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_hook_receives_payload_and_event() {
+        let dir = tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let command = format!(
+            "{{ echo \"$CURSOR_CHAT_EVENT\"; cat; }} > '{}'",
+            out.display()
+        );
+
+        let payload = serde_json::json!({"conversations": 2});
+        run_hook(&command, "sync_complete", &payload, HOOK_TIMEOUT).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "sync_complete\n{\"conversations\":2}"
+        );
+
+        assert!(run_hook("exit 1", "x", &payload, HOOK_TIMEOUT).is_err());
+        let slow = run_hook("sleep 5", "x", &payload, Duration::from_millis(100));
+        assert!(slow.unwrap_err().to_string().contains("timed out"));
+    }
+}
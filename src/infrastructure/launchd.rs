@@ -0,0 +1,347 @@
+//! Launchd agent integration (macOS).
+//!
+//! Writes a property list to `~/Library/LaunchAgents` running the sync
+//! daemon, or one sync cycle every interval in timer mode, and controls it
+//! with `launchctl`. The agent's output goes to `sync.log` in the data
+//! directory, which `sync logs` reads back.
+//!
+//! With a profile selected, the agent is named after it
+//! (`com.cursor-chat.sync.work`), so each profile syncs independently.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::service_manager::{
+    is_valid_instance_name, service_log_path, tail_lines,
+};
+use crate::infrastructure::{
+    active_profile, ensure_writable, InstallResult, ServiceManager, ServiceMode, ServiceStatus,
+    PROFILE_ENV,
+};
+
+/// Agent label without instance.
+const LABEL_PREFIX: &str = "com.cursor-chat.sync";
+
+/// Launchd agent manager.
+pub struct LaunchdService {
+    config: AppConfig,
+    /// Profile the agent is an instance for.
+    instance: Option<String>,
+}
+
+impl LaunchdService {
+    /// Create a new launchd agent manager for the selected profile.
+    #[must_use]
+    pub fn new(config: AppConfig) -> Self {
+        Self {
+            config,
+            instance: active_profile().map(str::to_string),
+        }
+    }
+
+    /// Manage the agent of profile `instance` (`None`: the default agent)
+    /// instead of the selected profile's.
+    #[must_use]
+    pub fn with_instance(mut self, instance: Option<String>) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    /// Agent label, e.g. `com.cursor-chat.sync.work`.
+    fn label(&self) -> String {
+        self.instance.as_ref().map_or_else(
+            || LABEL_PREFIX.to_string(),
+            |name| format!("{LABEL_PREFIX}.{name}"),
+        )
+    }
+
+    /// Profiles with an agent installed.
+    ///
+    /// # Errors
+    /// Returns error if the `LaunchAgents` directory cannot be read.
+    pub fn installed_instances() -> Result<Vec<String>> {
+        let dir = Self::launch_agents_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries =
+            fs::read_dir(&dir).map_err(|e| AppError::io("Failed to read LaunchAgents", e))?;
+        let mut instances: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let instance = name
+                    .strip_prefix(LABEL_PREFIX)?
+                    .strip_prefix('.')?
+                    .strip_suffix(".plist")?;
+                (!instance.is_empty()).then(|| instance.to_string())
+            })
+            .collect();
+        instances.sort();
+
+        Ok(instances)
+    }
+
+    /// Get the per-user `LaunchAgents` directory.
+    fn launch_agents_dir() -> Result<PathBuf> {
+        let home = dirs::home_dir().ok_or_else(|| AppError::Config {
+            message: "Could not determine home directory".into(),
+        })?;
+
+        Ok(home.join("Library/LaunchAgents"))
+    }
+
+    /// Get the property list path.
+    fn plist_path(&self) -> Result<PathBuf> {
+        Ok(Self::launch_agents_dir()?.join(format!("{}.plist", self.label())))
+    }
+
+    /// Generate the property list running `binary` in `mode`.
+    fn generate_plist(&self, binary: &Path, mode: ServiceMode) -> String {
+        let service = &self.config.service;
+        let interval = self.config.sync.interval_secs;
+        let (arguments, schedule) = match mode {
+            ServiceMode::Daemon => (
+                vec!["daemon".to_string(), "--interval".into(), interval.to_string()],
+                format!(
+                    "    <key>KeepAlive</key>\n    <dict>\n        <key>SuccessfulExit</key>\n        <false/>\n    </dict>\n    <key>ThrottleInterval</key>\n    <integer>{}</integer>\n",
+                    service.restart_sec
+                ),
+            ),
+            ServiceMode::Timer => (
+                vec!["daemon".to_string(), "--once".into()],
+                format!("    <key>StartInterval</key>\n    <integer>{interval}</integer>\n"),
+            ),
+        };
+
+        let mut program = String::new();
+        for arg in std::iter::once(binary.display().to_string()).chain(arguments) {
+            let _ = writeln!(program, "        <string>{}</string>", xml_escape(&arg));
+        }
+        let profile_env = self
+            .instance
+            .as_ref()
+            .map(|name| {
+                format!(
+                    "        <key>{PROFILE_ENV}</key>\n        <string>{}</string>\n",
+                    xml_escape(name)
+                )
+            })
+            .unwrap_or_default();
+        let nice = service
+            .nice
+            .map(|nice| format!("    <key>Nice</key>\n    <integer>{nice}</integer>\n"))
+            .unwrap_or_default();
+        let log = xml_escape(&service_log_path(&self.config).display().to_string());
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+{program}    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>RUST_LOG</key>
+        <string>info</string>
+{profile_env}    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+{schedule}{nice}    <key>ProcessType</key>
+    <string>Background</string>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+            label = self.label(),
+        )
+    }
+
+    /// Run `launchctl` with `args`, failing with `message` if it does.
+    fn launchctl(args: &[&str], message: &str) -> Result<()> {
+        let status = Command::new("launchctl")
+            .args(args)
+            .status()
+            .map_err(|e| AppError::io(message, e))?;
+        if !status.success() {
+            return Err(AppError::Config {
+                message: message.into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ServiceManager for LaunchdService {
+    fn service_name(&self) -> String {
+        self.label()
+    }
+
+    fn install(&self, mode: ServiceMode) -> Result<InstallResult> {
+        ensure_writable("install the sync service")?;
+        if let Some(name) = &self.instance {
+            if !is_valid_instance_name(name) {
+                return Err(AppError::Config {
+                    message: format!(
+                        "Profile '{name}' cannot name a launchd agent (use letters, digits, '-', '_' or '.')"
+                    ),
+                });
+            }
+        }
+
+        let agents_dir = Self::launch_agents_dir()?;
+        fs::create_dir_all(&agents_dir)
+            .map_err(|e| AppError::io("Failed to create LaunchAgents directory", e))?;
+
+        // A loaded agent keeps its old definition until unloaded
+        let plist_path = self.plist_path()?;
+        if plist_path.exists() {
+            let _ = self.stop_and_disable();
+        }
+
+        let binary_path = std::env::current_exe()
+            .map_err(|e| AppError::io("Failed to get executable path", e))?;
+        let unit_content = self.generate_plist(&binary_path, mode);
+        fs::write(&plist_path, &unit_content)
+            .map_err(|e| AppError::io("Failed to write launchd agent", e))?;
+
+        tracing::info!(path = %plist_path.display(), "Launchd agent written");
+
+        Ok(InstallResult {
+            service_path: plist_path,
+            timer_path: None,
+            unit_content,
+        })
+    }
+
+    fn enable_and_start(&self) -> Result<()> {
+        ensure_writable("start the sync service")?;
+        let plist = self.plist_path()?.display().to_string();
+
+        // -w clears a disabled override, so the agent also loads at login
+        Self::launchctl(&["load", "-w", &plist], "Failed to load launchd agent")?;
+
+        tracing::info!("Launchd agent loaded");
+
+        Ok(())
+    }
+
+    fn stop_and_disable(&self) -> Result<()> {
+        ensure_writable("stop the sync service")?;
+        let plist = self.plist_path()?.display().to_string();
+
+        if Self::launchctl(&["unload", "-w", &plist], "Failed to unload launchd agent").is_err() {
+            tracing::warn!("Launchd agent may not have been loaded");
+        }
+
+        tracing::info!("Launchd agent unloaded");
+
+        Ok(())
+    }
+
+    fn get_status(&self) -> Result<ServiceStatus> {
+        let plist_path = self.plist_path()?;
+        if !plist_path.exists() {
+            return Ok(ServiceStatus {
+                is_installed: false,
+                mode: ServiceMode::Daemon,
+                is_enabled: false,
+                is_running: false,
+                status_text: "not installed".into(),
+            });
+        }
+
+        let plist = fs::read_to_string(&plist_path)
+            .map_err(|e| AppError::io("Failed to read launchd agent", e))?;
+        let mode = if plist.contains("<key>StartInterval</key>") {
+            ServiceMode::Timer
+        } else {
+            ServiceMode::Daemon
+        };
+
+        // Only loaded agents are listed; a running one has a PID
+        let output = Command::new("launchctl")
+            .args(["list", &self.label()])
+            .output()
+            .map_err(|e| AppError::io("Failed to query launchd agent", e))?;
+        let status_text = String::from_utf8_lossy(&output.stdout).to_string();
+        let is_enabled = output.status.success();
+        let is_running =
+            is_enabled && (mode == ServiceMode::Timer || status_text.contains("\"PID\" ="));
+
+        Ok(ServiceStatus {
+            is_installed: true,
+            mode,
+            is_enabled,
+            is_running,
+            status_text,
+        })
+    }
+
+    fn uninstall(&self) -> Result<()> {
+        ensure_writable("uninstall the sync service")?;
+
+        let _ = self.stop_and_disable();
+
+        let plist_path = self.plist_path()?;
+        if plist_path.exists() {
+            fs::remove_file(&plist_path)
+                .map_err(|e| AppError::io("Failed to remove launchd agent", e))?;
+        }
+
+        tracing::info!("Launchd agent uninstalled");
+
+        Ok(())
+    }
+
+    fn view_logs(&self, lines: usize) -> Result<String> {
+        tail_lines(&service_log_path(&self.config), lines)
+    }
+}
+
+/// Escape `text` for an XML character node.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_plist_from_config() {
+        let mut config = AppConfig::default();
+        config.sync.interval_secs = 300;
+        config.service.nice = Some(10);
+        let mut launchd = LaunchdService::new(config);
+        launchd.instance = Some("work".to_string());
+        let binary = Path::new("/Applications/cursor chat & co/cursor-chat");
+
+        let daemon = launchd.generate_plist(binary, ServiceMode::Daemon);
+        assert!(daemon.contains("<string>com.cursor-chat.sync.work</string>"));
+        assert!(daemon.contains(
+            "<string>/Applications/cursor chat &amp; co/cursor-chat</string>\n        <string>daemon</string>\n        <string>--interval</string>\n        <string>300</string>\n"
+        ));
+        assert!(daemon.contains("<key>CURSOR_CHAT_PROFILE</key>\n        <string>work</string>"));
+        assert!(daemon.contains("<key>ThrottleInterval</key>\n    <integer>30</integer>"));
+        assert!(daemon.contains("<key>Nice</key>\n    <integer>10</integer>"));
+        assert!(!daemon.contains("StartInterval"));
+
+        let timer = launchd.generate_plist(binary, ServiceMode::Timer);
+        assert!(timer.contains("<string>--once</string>"));
+        assert!(timer.contains("<key>StartInterval</key>\n    <integer>300</integer>"));
+        assert!(!timer.contains("KeepAlive"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Client for OpenAI-compatible chat completion endpoints.
+//!
+//! Used for optional LLM features such as conversation summaries and
+//! API-computed embeddings.
+//! Works with hosted APIs and local servers (Ollama, llama.cpp, vLLM).
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::domain::{AppError, LlmConfig, Result};
+
+/// Longest part of an error response body included in error messages.
+const MAX_ERROR_BODY: usize = 300;
+
+/// Blocking chat completion client.
+pub struct LlmClient {
+    agent: ureq::Agent,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl LlmClient {
+    /// Create a client from configuration.
+    ///
+    /// The API key is read from the environment variable named in `api_key_env`.
+    ///
+    /// # Errors
+    /// Returns error if `api_key_env` is set but the variable is missing.
+    pub fn new(config: &LlmConfig) -> Result<Self> {
+        let api_key = if config.api_key_env.is_empty() {
+            None
+        } else {
+            Some(
+                std::env::var(&config.api_key_env).map_err(|_| AppError::Config {
+                    message: format!(
+                        "{} is not set. Export your API key or set llm.api_key_env = \"\" \
+                         for endpoints without auth.",
+                        config.api_key_env
+                    ),
+                })?,
+            )
+        };
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build();
+
+        Ok(Self {
+            agent,
+            base_url: config.endpoint.trim_end_matches('/').to_string(),
+            model: config.model.clone(),
+            api_key,
+        })
+    }
+
+    /// Model name used for requests.
+    #[must_use]
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send a system and user message and return the reply text.
+    ///
+    /// # Errors
+    /// Returns error if the request fails or the response has no content.
+    pub fn complete(&self, system: &str, user: &str) -> Result<String> {
+        let body = json!({
+            "model": self.model,
+            "temperature": 0.2,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": user }
+            ]
+        });
+
+        let response = self.post("chat/completions", &body)?;
+
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .ok_or_else(|| AppError::Llm {
+                message: "response contained no message content".into(),
+            })
+    }
+
+    /// Compute embeddings for `inputs` with `model`, in input order.
+    ///
+    /// # Errors
+    /// Returns error if the request fails or the response is malformed.
+    pub fn embed(&self, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        let body = json!({ "model": model, "input": inputs });
+        let response = self.post("embeddings", &body)?;
+
+        let data = response["data"].as_array().ok_or_else(|| AppError::Llm {
+            message: "embedding response has no data".into(),
+        })?;
+
+        let mut vectors = vec![Vec::new(); inputs.len()];
+        for (position, item) in data.iter().enumerate() {
+            let index = item["index"]
+                .as_u64()
+                .and_then(|i| usize::try_from(i).ok())
+                .unwrap_or(position);
+            let vector: Vec<f32> =
+                serde_json::from_value(item["embedding"].clone()).unwrap_or_default();
+            if let Some(slot) = vectors.get_mut(index) {
+                *slot = vector;
+            }
+        }
+
+        if vectors.iter().any(Vec::is_empty) {
+            return Err(AppError::Llm {
+                message: "embedding response is missing vectors".into(),
+            });
+        }
+        Ok(vectors)
+    }
+
+    /// POST a JSON body to `<endpoint>/<route>` and parse the JSON reply.
+    fn post(&self, route: &str, body: &Value) -> Result<Value> {
+        let url = format!("{}/{route}", self.base_url);
+        let mut request = self.agent.post(&url);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+
+        tracing::debug!(url = %url, model = %self.model, "Sending LLM request");
+
+        match request.send_json(body) {
+            Ok(response) => response.into_json().map_err(|e| AppError::Llm {
+                message: format!("invalid response: {e}"),
+            }),
+            Err(ureq::Error::Status(code, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                Err(AppError::Llm {
+                    message: format!(
+                        "{url} returned HTTP {code}: {}",
+                        body.chars().take(MAX_ERROR_BODY).collect::<String>()
+                    ),
+                })
+            }
+            Err(e) => Err(AppError::Llm {
+                message: format!("{url}: {e}"),
+            }),
+        }
+    }
+}
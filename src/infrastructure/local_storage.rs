@@ -1,20 +1,448 @@
-//! Local SQLite storage for synced chat data.
+//! Local `SQLite` storage for synced chat data.
 //!
 //! Provides persistent storage that survives Cursor resets,
 //! with support for incremental sync and workspace organization.
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::types::{Type, Value, ValueRef};
+use rusqlite::{
+    params, Connection, OpenFlags, OptionalExtension, Transaction, TransactionBehavior,
+};
+use serde::Serialize;
 
 use crate::domain::{
-    AppError, Bubble, BubbleType, Conversation, ModelConfig, Result, SyncState, WorkspaceInfo,
+    AppError, Bubble, BubbleType, Change, ChangeType, Conversation, GitInfo, ModelConfig,
+    Operation, OperationKind, ResetEvent, Result, SyncState, WorkspaceInfo,
 };
 
-/// Local storage repository using SQLite.
+use super::timings::TIMINGS_TARGET;
+
+/// Tables holding timestamps written as RFC 3339 text.
+const TIMESTAMPED_TABLES: [&str; 2] = ["conversations", "bubbles"];
+
+/// Tables copied when salvaging, in foreign key order.
+const SALVAGE_TABLES: [&str; 15] = [
+    "workspaces",
+    "conversations",
+    "blobs",
+    "bubbles",
+    "sync_state",
+    "reset_events",
+    "summaries",
+    "title_overrides",
+    "embeddings",
+    "operations",
+    "changes",
+    "pins",
+    "tags",
+    "archived",
+    "conversation_git",
+];
+
+/// Bubble text at least this long is stored once in `blobs`, keyed by its hash.
+const BLOB_MIN_BYTES: usize = 256;
+
+/// Text shorter than this is never compressed.
+const COMPRESS_MIN_BYTES: usize = 512;
+
+/// zstd level used for stored text (0 selects the library default).
+const COMPRESSION_LEVEL: i32 = 0;
+
+/// Most recent audit log entries kept by maintenance.
+const OPERATIONS_KEPT: i64 = 10_000;
+
+/// How long a statement waits for another connection's write lock.
+///
+/// The daemon and CLI share the database; a sync writes in one transaction,
+/// so this must outlast the longest sync rather than fail with "locked".
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Size the WAL file is truncated to after a checkpoint (64 MiB).
+const JOURNAL_SIZE_LIMIT: i64 = 64 * 1024 * 1024;
+
+/// Schema version written to `PRAGMA user_version` after all migrations.
+const SCHEMA_VERSION: i64 = 9;
+
+/// Internal storage schema, created on open.
+///
+/// Tables keep the columns of the release that added them; columns added
+/// later only come from [`LocalStorage::migrate`], so new and upgraded
+/// databases go through the same steps.
+const SCHEMA: &str = r"
+-- Workspaces/Projects table
+CREATE TABLE IF NOT EXISTS workspaces (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    path TEXT UNIQUE,
+    cursor_path TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Conversations table
+CREATE TABLE IF NOT EXISTS conversations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    composer_id TEXT UNIQUE NOT NULL,
+    workspace_id INTEGER REFERENCES workspaces(id),
+    title TEXT NOT NULL DEFAULT '',
+    model_name TEXT NOT NULL DEFAULT '',
+    max_mode INTEGER NOT NULL DEFAULT 0,
+    unified_mode TEXT NOT NULL DEFAULT '',
+    created_at TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    content_hash TEXT
+);
+
+-- Bubbles/Messages table
+CREATE TABLE IF NOT EXISTS bubbles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bubble_id TEXT UNIQUE NOT NULL,
+    conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    bubble_type INTEGER NOT NULL,
+    text TEXT NOT NULL DEFAULT '',
+    created_at TEXT,
+    thinking_text TEXT,
+    thinking_signature TEXT,
+    thinking_duration_ms INTEGER,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    is_agentic INTEGER NOT NULL DEFAULT 0,
+    workspace_uri TEXT,
+    workspace_project_dir TEXT
+);
+
+-- Sync state table
+CREATE TABLE IF NOT EXISTS sync_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last_sync TEXT,
+    last_hash TEXT,
+    conversation_count INTEGER NOT NULL DEFAULT 0,
+    message_count INTEGER NOT NULL DEFAULT 0,
+    storage_bytes INTEGER NOT NULL DEFAULT 0,
+    is_syncing INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+-- Cursor resets detected by the daemon
+CREATE TABLE IF NOT EXISTS reset_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    detected_at TEXT NOT NULL,
+    local_conversations INTEGER NOT NULL,
+    cursor_conversations INTEGER NOT NULL,
+    restored INTEGER NOT NULL DEFAULT 0
+);
+
+-- Audit log of sync, restore, delete, prune, backup and reset operations
+CREATE TABLE IF NOT EXISTS operations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    items INTEGER NOT NULL DEFAULT 0,
+    success INTEGER NOT NULL,
+    details TEXT NOT NULL DEFAULT ''
+);
+
+-- Change feed: conversations created or updated by each sync
+CREATE TABLE IF NOT EXISTS changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    composer_id TEXT NOT NULL,
+    change_type TEXT NOT NULL,
+    synced_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_changes_synced ON changes(synced_at);
+
+-- Periodic maintenance runs
+CREATE TABLE IF NOT EXISTS maintenance_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ran_at TEXT NOT NULL,
+    purged_rows INTEGER NOT NULL DEFAULT 0
+);
+
+-- LLM summaries, keyed by conversation
+CREATE TABLE IF NOT EXISTS summaries (
+    composer_id TEXT PRIMARY KEY,
+    summary TEXT NOT NULL,
+    model TEXT NOT NULL,
+    content_hash TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- Titles set by the user or generated by the LLM, shown instead of the heuristic one
+CREATE TABLE IF NOT EXISTS title_overrides (
+    composer_id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    source TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Conversations pinned to the top of the quick menu
+CREATE TABLE IF NOT EXISTS pins (
+    composer_id TEXT PRIMARY KEY,
+    pinned_at TEXT NOT NULL
+);
+
+-- Tags added by the user, any number per conversation
+CREATE TABLE IF NOT EXISTS tags (
+    composer_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    tagged_at TEXT NOT NULL,
+    PRIMARY KEY (composer_id, tag)
+);
+
+-- Conversations archived by the user, and whether they were erased from Cursor
+CREATE TABLE IF NOT EXISTS archived (
+    composer_id TEXT PRIMARY KEY,
+    removed_from_cursor INTEGER NOT NULL DEFAULT 0,
+    archived_at TEXT NOT NULL
+);
+
+-- Git repository of each conversation's workspace, recorded at sync time
+CREATE TABLE IF NOT EXISTS conversation_git (
+    composer_id TEXT PRIMARY KEY,
+    repo_root TEXT NOT NULL,
+    origin_url TEXT,
+    branch TEXT,
+    head_commit TEXT,
+    recorded_at TEXT NOT NULL
+);
+
+-- Semantic index: one vector (little-endian f32) per conversation and model
+CREATE TABLE IF NOT EXISTS embeddings (
+    composer_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    vector BLOB NOT NULL,
+    content_hash TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (composer_id, model)
+);
+
+-- Initialize sync state if not exists
+INSERT OR IGNORE INTO sync_state (id) VALUES (1);
+
+-- Indexes for common queries
+CREATE INDEX IF NOT EXISTS idx_conversations_workspace 
+    ON conversations(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_conversations_created 
+    ON conversations(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bubbles_conversation 
+    ON bubbles(conversation_id);
+CREATE INDEX IF NOT EXISTS idx_bubbles_created 
+    ON bubbles(created_at);
+CREATE INDEX IF NOT EXISTS idx_tags_tag 
+    ON tags(tag);
+";
+
+/// Schema of the standalone database written by `export_standalone`.
+///
+/// This is a stable, documented layout for other tools, independent of the
+/// internal storage schema. Comments are kept in `sqlite_master.sql`.
+const STANDALONE_SCHEMA: &str = r"
+PRAGMA journal_mode = DELETE;
+
+-- Key/value information about this export (format_version, exported_at, generator)
+CREATE TABLE about (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- Projects the conversations belong to
+CREATE TABLE workspaces (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,          -- Display name (folder name)
+    path TEXT                    -- Absolute project path, if known
+);
+
+-- One row per Cursor chat (composer)
+CREATE TABLE conversations (
+    id TEXT PRIMARY KEY,         -- Cursor composer ID
+    workspace_id INTEGER REFERENCES workspaces(id),
+    title TEXT NOT NULL,
+    model TEXT NOT NULL,         -- Model name ('' when unknown)
+    max_mode INTEGER NOT NULL,   -- 1 if max mode was enabled
+    mode TEXT NOT NULL,          -- Cursor mode (chat, agent, ...)
+    created_at TEXT              -- RFC 3339 timestamp
+);
+
+-- One row per message, plain text (never compressed)
+CREATE TABLE messages (
+    id TEXT PRIMARY KEY,         -- Cursor bubble ID
+    conversation_id TEXT NOT NULL REFERENCES conversations(id),
+    position INTEGER NOT NULL,   -- 0-based order within the conversation
+    role TEXT NOT NULL,          -- 'user', 'assistant' or 'unknown'
+    text TEXT NOT NULL,
+    thinking TEXT,               -- Model reasoning, if any
+    created_at TEXT,             -- RFC 3339 timestamp
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    is_agentic INTEGER NOT NULL, -- 1 if produced by an agent run
+    reasoning_tokens INTEGER NOT NULL DEFAULT 0  -- Thinking tokens, billed separately
+);
+
+CREATE INDEX idx_messages_conversation ON messages(conversation_id, position);
+
+-- Messages with their conversation and project, ready for querying
+CREATE VIEW v_messages AS
+SELECT m.conversation_id, c.title, w.name AS workspace, m.position, m.role,
+       m.text, m.thinking, m.created_at, m.input_tokens, m.output_tokens,
+       m.reasoning_tokens
+FROM messages m
+JOIN conversations c ON c.id = m.conversation_id
+LEFT JOIN workspaces w ON w.id = c.workspace_id;
+
+-- Conversations with message counts and token totals
+CREATE VIEW v_conversations AS
+SELECT c.id, c.title, w.name AS workspace, c.model, c.mode, c.created_at,
+       COUNT(m.id) AS message_count,
+       COALESCE(SUM(m.input_tokens), 0) AS input_tokens,
+       COALESCE(SUM(m.output_tokens), 0) AS output_tokens,
+       COALESCE(SUM(m.reasoning_tokens), 0) AS reasoning_tokens
+FROM conversations c
+LEFT JOIN workspaces w ON w.id = c.workspace_id
+LEFT JOIN messages m ON m.conversation_id = c.id
+GROUP BY c.id;
+";
+
+/// Version of [`STANDALONE_SCHEMA`], recorded in its `about` table.
+const STANDALONE_FORMAT_VERSION: &str = "1";
+
+/// Row counts written to a standalone export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandaloneExport {
+    /// Conversations exported.
+    pub conversations: usize,
+    /// Messages exported.
+    pub messages: usize,
+}
+
+/// Problems found by a storage health check.
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    /// Messages returned by `PRAGMA integrity_check` (empty when "ok").
+    pub integrity_errors: Vec<String>,
+    /// Bubbles whose conversation no longer exists.
+    pub orphan_bubbles: usize,
+    /// Rows reported by `PRAGMA foreign_key_check`.
+    pub foreign_key_violations: usize,
+    /// `created_at` values that are not valid RFC 3339 timestamps.
+    pub malformed_timestamps: usize,
+}
+
+impl HealthReport {
+    /// Whether the database file itself is damaged.
+    #[must_use]
+    pub const fn is_corrupt(&self) -> bool {
+        !self.integrity_errors.is_empty()
+    }
+
+    /// Whether no problem was found.
+    #[must_use]
+    pub const fn is_healthy(&self) -> bool {
+        !self.is_corrupt()
+            && self.orphan_bubbles == 0
+            && self.foreign_key_violations == 0
+            && self.malformed_timestamps == 0
+    }
+}
+
+/// Row count and on-disk size of a table (including its indexes).
+#[derive(Debug, Clone)]
+pub struct TableStats {
+    /// Table name.
+    pub name: String,
+    /// Number of rows.
+    pub rows: usize,
+    /// Bytes used by the table and its indexes.
+    pub bytes: u64,
+}
+
+/// Stored size of a single conversation.
+#[derive(Debug, Clone)]
+pub struct ConversationSize {
+    /// Cursor composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Number of stored messages.
+    pub messages: usize,
+    /// Bytes of message text and thinking stored for it.
+    pub bytes: u64,
+}
+
+/// A message matching a full-text search.
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageHit {
+    /// Conversation the message belongs to.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Matching message.
+    pub bubble_id: String,
+    /// Who wrote the message.
+    pub bubble_type: BubbleType,
+    /// When the message was sent.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Text around the match, with matched terms wrapped in `**`.
+    pub snippet: String,
+    /// Relevance; higher is better.
+    pub score: f64,
+}
+
+/// Title and last activity of a stored conversation.
+#[derive(Debug, Clone)]
+pub struct ConversationActivity {
+    /// Conversation title.
+    pub title: String,
+    /// When its last message was sent, or it was created.
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// A conversation marked archived.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedConversation {
+    /// Conversation ID.
+    pub composer_id: String,
+    /// Conversation title, empty if it is not in local storage.
+    pub title: String,
+    /// Whether it was erased from Cursor's database when archived.
+    pub removed_from_cursor: bool,
+    /// When it was archived.
+    pub archived_at: DateTime<Utc>,
+}
+
+/// A cached LLM summary of a conversation.
+#[derive(Debug, Clone)]
+pub struct StoredSummary {
+    /// Summary text.
+    pub summary: String,
+    /// Model that produced it.
+    pub model: String,
+    /// Conversation content hash when it was generated.
+    pub content_hash: Option<String>,
+    /// When it was generated.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A conversation vector from the semantic index.
+#[derive(Debug, Clone)]
+pub struct StoredEmbedding {
+    /// Cursor composer ID.
+    pub composer_id: String,
+    /// Conversation title.
+    pub title: String,
+    /// Embedding vector.
+    pub vector: Vec<f32>,
+}
+
+/// Local storage repository using `SQLite`.
 pub struct LocalStorage {
     conn: Connection,
+    compress: bool,
 }
 
 impl LocalStorage {
@@ -30,100 +458,490 @@ impl LocalStorage {
         }
 
         let conn = Connection::open(path).map_err(AppError::database)?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .map_err(AppError::database)?;
 
-        // Enable WAL mode for better concurrent access
-        conn.execute_batch(
+        // WAL lets readers run while a sync writes; cap the file it leaves behind
+        conn.execute_batch(&format!(
             "PRAGMA journal_mode = WAL;
              PRAGMA synchronous = NORMAL;
-             PRAGMA foreign_keys = ON;",
-        )
+             PRAGMA foreign_keys = ON;
+             PRAGMA journal_size_limit = {JOURNAL_SIZE_LIMIT};"
+        ))
         .map_err(AppError::database)?;
 
-        let storage = Self { conn };
+        let storage = Self {
+            conn,
+            compress: false,
+        };
         storage.init_schema()?;
+        storage.migrate()?;
 
         Ok(storage)
     }
 
+    /// Opens the database for queries only.
+    ///
+    /// Never takes a write lock, so it does not wait on a sync running in
+    /// the daemon. Falls back to [`Self::open`] when the database does not
+    /// exist yet or still needs migrating.
+    ///
+    /// # Errors
+    /// Returns error if the database cannot be opened.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Self::open(path);
+        }
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(AppError::database)?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .map_err(AppError::database)?;
+        conn.execute_batch("PRAGMA query_only = ON;")
+            .map_err(AppError::database)?;
+
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(AppError::database)?;
+        if version < SCHEMA_VERSION {
+            drop(conn);
+            return Self::open(path);
+        }
+
+        Ok(Self {
+            conn,
+            compress: false,
+        })
+    }
+
     /// Initialize database schema.
     fn init_schema(&self) -> Result<()> {
+        self.conn
+            .execute_batch(SCHEMA)
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Compress large text written from now on.
+    ///
+    /// Reads always handle both compressed and plain values.
+    #[must_use]
+    pub const fn with_compression(mut self, enabled: bool) -> Self {
+        self.compress = enabled;
+        self
+    }
+
+    /// Encode text for storage, compressing it when enabled and worthwhile.
+    fn encode_text(&self, text: &str) -> Result<Value> {
+        if self.compress && text.len() >= COMPRESS_MIN_BYTES {
+            let compressed = zstd::encode_all(text.as_bytes(), COMPRESSION_LEVEL)
+                .map_err(|e| AppError::io("Failed to compress text", e))?;
+            if compressed.len() < text.len() {
+                return Ok(Value::Blob(compressed));
+            }
+        }
+
+        Ok(Value::Text(text.to_string()))
+    }
+
+    /// A list stored as JSON text, or NULL when empty.
+    fn encode_json_list<T: serde::Serialize>(&self, items: &[T]) -> Result<Option<Value>> {
+        if items.is_empty() {
+            return Ok(None);
+        }
+        self.encode_json(items).map(Some)
+    }
+
+    /// A value stored as JSON text.
+    fn encode_json<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<Value> {
+        let json = serde_json::to_string(value).map_err(AppError::json_parse)?;
+        self.encode_text(&json)
+    }
+
+    /// Read a list stored by `encode_json_list`; unreadable JSON gives an
+    /// empty list.
+    fn decode_json_list<T: serde::de::DeserializeOwned>(
+        row: &rusqlite::Row,
+        idx: usize,
+    ) -> rusqlite::Result<Vec<T>> {
+        Self::decode_json(row, idx)
+    }
+
+    /// Read a value stored by `encode_json`; NULL or unreadable JSON gives
+    /// the default.
+    fn decode_json<T: serde::de::DeserializeOwned + Default>(
+        row: &rusqlite::Row,
+        idx: usize,
+    ) -> rusqlite::Result<T> {
+        Ok(Self::decode_text(row, idx)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Read a text column that may hold zstd-compressed bytes.
+    fn decode_text(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<String>> {
+        match row.get_ref(idx)? {
+            ValueRef::Null => Ok(None),
+            ValueRef::Text(text) => Ok(Some(String::from_utf8_lossy(text).into_owned())),
+            ValueRef::Blob(bytes) => zstd::decode_all(bytes)
+                .map(|text| Some(String::from_utf8_lossy(&text).into_owned()))
+                .map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(idx, Type::Blob, Box::new(e))
+                }),
+            ValueRef::Integer(_) => Err(rusqlite::Error::InvalidColumnType(
+                idx,
+                "text".into(),
+                Type::Integer,
+            )),
+            ValueRef::Real(_) => Err(rusqlite::Error::InvalidColumnType(
+                idx,
+                "text".into(),
+                Type::Real,
+            )),
+        }
+    }
+
+    /// Apply schema migrations newer than the stored `user_version`.
+    fn migrate(&self) -> Result<()> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(AppError::database)?;
+
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        // Take the write lock up front, then check again: another process
+        // may have migrated while this one waited
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)
+            .map_err(AppError::database)?;
+        let version: i64 = tx
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(AppError::database)?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        if version < 1 {
+            self.migrate_text_blobs()?;
+        }
+        // Before the hash migration, which reads bubbles with the current queries
+        if version < 3 {
+            self.add_column("bubbles", "reasoning_tokens", "INTEGER NOT NULL DEFAULT 0")?;
+        }
+        if version < 5 {
+            self.add_column("bubbles", "commands", "TEXT")?;
+        }
+        if version < 6 {
+            self.add_column("bubbles", "diagnostics", "TEXT")?;
+        }
+        if version < 8 {
+            self.add_column("bubbles", "tool_calls", "TEXT")?;
+        }
+        if version < 9 {
+            self.add_column("bubbles", "context", "TEXT")?;
+        }
+        if version < 2 {
+            self.migrate_content_hashes()?;
+        }
+        if version < 4 {
+            self.add_column("sync_state", "cursor_version", "TEXT")?;
+        }
+        if version < 7 {
+            self.migrate_search_index()?;
+        }
+
+        tx.execute_batch(&format!("PRAGMA user_version = {SCHEMA_VERSION};"))
+            .map_err(AppError::database)?;
+        tx.commit().map_err(AppError::database)?;
+
+        tracing::info!(
+            from = version,
+            to = SCHEMA_VERSION,
+            "Storage schema migrated"
+        );
+        Ok(())
+    }
+
+    /// Add a column unless the table already has it.
+    fn add_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: bool = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{table}') WHERE name = ?1"),
+                [column],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?;
+
+        if !exists {
+            self.conn
+                .execute_batch(&format!(
+                    "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+                ))
+                .map_err(AppError::database)?;
+        }
+        Ok(())
+    }
+
+    /// Migration 7: full-text index of message text and thinking.
+    ///
+    /// Rows are written by `upsert_bubble`, since stored text may be
+    /// compressed or kept in `blobs`; the trigger drops them with the bubble.
+    fn migrate_search_index(&self) -> Result<()> {
         self.conn
             .execute_batch(
                 r"
-            -- Workspaces/Projects table
-            CREATE TABLE IF NOT EXISTS workspaces (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                path TEXT UNIQUE,
-                cursor_path TEXT,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            CREATE VIRTUAL TABLE IF NOT EXISTS bubbles_fts USING fts5(
+                text, thinking, tokenize = 'unicode61 remove_diacritics 2'
             );
+            CREATE TRIGGER IF NOT EXISTS bubbles_fts_delete AFTER DELETE ON bubbles BEGIN
+                DELETE FROM bubbles_fts WHERE rowid = old.id;
+            END;
+            ",
+            )
+            .map_err(AppError::database)?;
 
-            -- Conversations table
-            CREATE TABLE IF NOT EXISTS conversations (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                composer_id TEXT UNIQUE NOT NULL,
-                workspace_id INTEGER REFERENCES workspaces(id),
-                title TEXT NOT NULL DEFAULT '',
-                model_name TEXT NOT NULL DEFAULT '',
-                max_mode INTEGER NOT NULL DEFAULT 0,
-                unified_mode TEXT NOT NULL DEFAULT '',
-                created_at TEXT,
-                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
-                content_hash TEXT
-            );
+        self.rebuild_search_index().map(|_| ())
+    }
 
-            -- Bubbles/Messages table
-            CREATE TABLE IF NOT EXISTS bubbles (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                bubble_id TEXT UNIQUE NOT NULL,
-                conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
-                bubble_type INTEGER NOT NULL,
-                text TEXT NOT NULL DEFAULT '',
-                created_at TEXT,
-                thinking_text TEXT,
-                thinking_signature TEXT,
-                thinking_duration_ms INTEGER,
-                input_tokens INTEGER NOT NULL DEFAULT 0,
-                output_tokens INTEGER NOT NULL DEFAULT 0,
-                is_agentic INTEGER NOT NULL DEFAULT 0,
-                workspace_uri TEXT,
-                workspace_project_dir TEXT
-            );
+    /// Index every stored message again, returning how many were indexed.
+    ///
+    /// # Errors
+    /// Returns error if the index cannot be written.
+    pub fn rebuild_search_index(&self) -> Result<usize> {
+        self.conn
+            .execute("DELETE FROM bubbles_fts", [])
+            .map_err(AppError::database)?;
 
-            -- Sync state table
-            CREATE TABLE IF NOT EXISTS sync_state (
-                id INTEGER PRIMARY KEY CHECK (id = 1),
-                last_sync TEXT,
-                last_hash TEXT,
-                conversation_count INTEGER NOT NULL DEFAULT 0,
-                message_count INTEGER NOT NULL DEFAULT 0,
-                storage_bytes INTEGER NOT NULL DEFAULT 0,
-                is_syncing INTEGER NOT NULL DEFAULT 0,
-                last_error TEXT
-            );
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT b.id, COALESCE(bl.data, b.text), b.thinking_text
+            FROM bubbles b
+            LEFT JOIN blobs bl ON bl.hash = b.text_hash
+            ",
+            )
+            .map_err(AppError::database)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    Self::decode_text(row, 1)?.unwrap_or_default(),
+                    Self::decode_text(row, 2)?,
+                ))
+            })
+            .map_err(AppError::database)?;
+
+        let mut indexed = 0;
+        for row in rows {
+            let (id, text, thinking) = row.map_err(AppError::database)?;
+            self.index_bubble(id, &text, thinking.as_deref())?;
+            indexed += 1;
+        }
+        Ok(indexed)
+    }
+
+    /// Write a message's text to the full-text index, replacing earlier text.
+    fn index_bubble(&self, id: i64, text: &str, thinking: Option<&str>) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO bubbles_fts (rowid, text, thinking) VALUES (?1, ?2, ?3)",
+                params![id, text, thinking],
+            )
+            .map_err(AppError::database)?;
+        Ok(())
+    }
+
+    /// Messages matching `query`, best first.
+    ///
+    /// Every word must appear; a trailing `*` matches a prefix. Only
+    /// conversations of `workspace` and with `tag` are searched when given.
+    ///
+    /// # Errors
+    /// Returns error if the query is empty or the search fails.
+    pub fn search_messages(
+        &self,
+        query: &str,
+        limit: usize,
+        workspace: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<Vec<MessageHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Err(AppError::InvalidData {
+                message: "Search query is empty".into(),
+            });
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT c.composer_id, COALESCE(t.title, c.title), b.bubble_id, b.bubble_type,
+                   b.created_at, snippet(bubbles_fts, -1, '**', '**', '…', 16),
+                   bm25(bubbles_fts)
+            FROM bubbles_fts
+            JOIN bubbles b ON b.id = bubbles_fts.rowid
+            JOIN conversations c ON c.id = b.conversation_id
+            LEFT JOIN workspaces w ON w.id = c.workspace_id
+            LEFT JOIN title_overrides t ON t.composer_id = c.composer_id
+            WHERE bubbles_fts MATCH ?1 AND (?2 IS NULL OR w.name = ?2)
+              AND (?4 IS NULL OR c.composer_id IN (SELECT composer_id FROM tags WHERE tag = ?4))
+            ORDER BY bm25(bubbles_fts)
+            LIMIT ?3
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let hits = stmt
+            .query_map(
+                params![
+                    fts_query,
+                    workspace,
+                    i64::try_from(limit).unwrap_or(i64::MAX),
+                    tag
+                ],
+                |row| {
+                    Ok(MessageHit {
+                        composer_id: row.get(0)?,
+                        title: row.get(1)?,
+                        bubble_id: row.get(2)?,
+                        bubble_type: BubbleType::try_from(row.get::<_, u8>(3)?)
+                            .unwrap_or(BubbleType::Unknown),
+                        created_at: row
+                            .get::<_, Option<String>>(4)?
+                            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                            .map(|dt| dt.with_timezone(&Utc)),
+                        snippet: row.get(5)?,
+                        // bm25 is lower for better matches
+                        score: -row.get::<_, f64>(6)?,
+                    })
+                },
+            )
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        Ok(hits)
+    }
 
-            -- Initialize sync state if not exists
-            INSERT OR IGNORE INTO sync_state (id) VALUES (1);
-
-            -- Indexes for common queries
-            CREATE INDEX IF NOT EXISTS idx_conversations_workspace 
-                ON conversations(workspace_id);
-            CREATE INDEX IF NOT EXISTS idx_conversations_created 
-                ON conversations(created_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_bubbles_conversation 
-                ON bubbles(conversation_id);
-            CREATE INDEX IF NOT EXISTS idx_bubbles_created 
-                ON bubbles(created_at);
+    /// Migration 1: move large bubble text into the content-addressed `blobs` table.
+    fn migrate_text_blobs(&self) -> Result<()> {
+        self.conn
+            .execute_batch(
+                r"
+            CREATE TABLE IF NOT EXISTS blobs (
+                hash TEXT PRIMARY KEY,
+                data TEXT NOT NULL,
+                size INTEGER NOT NULL
+            );
             ",
             )
             .map_err(AppError::database)?;
+        self.add_column("bubbles", "text_hash", "TEXT REFERENCES blobs(hash)")?;
+        self.conn
+            .execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_bubbles_text_hash ON bubbles(text_hash);",
+            )
+            .map_err(AppError::database)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, text FROM bubbles WHERE length(CAST(text AS BLOB)) >= ?1")
+            .map_err(AppError::database)?;
+        let rows = stmt
+            .query_map([BLOB_MIN_BYTES], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        for (id, text) in &rows {
+            let (inline, hash) = self.store_text(text)?;
+            self.conn
+                .execute(
+                    "UPDATE bubbles SET text = ?1, text_hash = ?2 WHERE id = ?3",
+                    params![inline, hash, id],
+                )
+                .map_err(AppError::database)?;
+        }
+
+        tracing::info!(bubbles = rows.len(), "Moved bubble text into blobs");
+        Ok(())
+    }
+
+    /// Migration 2: rehash stored conversations with [`Conversation::content_hash`].
+    ///
+    /// Hashes are recomputed from the stored copy, also for conversations
+    /// stored without one, so only conversations that changed in Cursor
+    /// since the last sync are picked up as changed. Summaries and
+    /// embeddings computed for the old hash stay up to date.
+    fn migrate_content_hashes(&self) -> Result<()> {
+        let conversations = self.get_conversations(None)?;
+        let stored = self.conversation_hashes()?;
+
+        for conv in &conversations {
+            let new = conv.content_hash();
+            self.conn
+                .execute(
+                    "UPDATE conversations SET content_hash = ?2 WHERE composer_id = ?1",
+                    params![conv.composer_id, new],
+                )
+                .map_err(AppError::database)?;
 
+            let Some((_, Some(old))) = stored.get(&conv.composer_id) else {
+                continue;
+            };
+            for table in ["summaries", "embeddings"] {
+                self.conn
+                    .execute(
+                        &format!(
+                            "UPDATE {table} SET content_hash = ?3
+                             WHERE composer_id = ?1 AND content_hash = ?2"
+                        ),
+                        params![conv.composer_id, old, new],
+                    )
+                    .map_err(AppError::database)?;
+            }
+        }
+
+        tracing::info!(
+            conversations = conversations.len(),
+            "Rehashed conversations"
+        );
         Ok(())
     }
 
+    /// Store bubble text, returning the inline value and blob hash to save on the row.
+    ///
+    /// Short text stays inline; long text is written once to `blobs`.
+    fn store_text<'a>(&self, text: &'a str) -> Result<(&'a str, Option<String>)> {
+        if text.len() < BLOB_MIN_BYTES {
+            return Ok((text, None));
+        }
+
+        let hash = blake3::hash(text.as_bytes()).to_hex().to_string();
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO blobs (hash, data, size) VALUES (?1, ?2, ?3)",
+                params![&hash, self.encode_text(text)?, text.len()],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(("", Some(hash)))
+    }
+
     /// Get or create a workspace by path.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
     pub fn get_or_create_workspace(&self, info: &WorkspaceInfo) -> Result<i64> {
         // Try to find existing
         if let Some(path) = &info.path {
@@ -157,20 +975,126 @@ impl LocalStorage {
         Ok(self.conn.last_insert_rowid())
     }
 
-    /// Upsert a conversation.
-    pub fn upsert_conversation(
-        &self,
-        conv: &Conversation,
-        workspace_id: Option<i64>,
-        content_hash: Option<&str>,
-    ) -> Result<i64> {
-        self.conn
+    /// Attribute a stored conversation to a workspace.
+    ///
+    /// Returns whether the conversation exists and changed workspace.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn set_conversation_workspace(&self, composer_id: &str, workspace_id: i64) -> Result<bool> {
+        let updated = self
+            .conn
             .execute(
-                r"
-            INSERT INTO conversations 
-                (composer_id, workspace_id, title, model_name, max_mode, unified_mode, created_at, content_hash)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-            ON CONFLICT(composer_id) DO UPDATE SET
+                "UPDATE conversations SET workspace_id = ?2
+                 WHERE composer_id = ?1 AND workspace_id IS NOT ?2",
+                params![composer_id, workspace_id],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(updated > 0)
+    }
+
+    /// Workspace path of each conversation changed by the sync at `synced_at`,
+    /// or with no git repository recorded yet.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn git_candidate_paths(&self, synced_at: DateTime<Utc>) -> Result<Vec<(String, PathBuf)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.composer_id, w.path FROM conversations c
+                 JOIN workspaces w ON w.id = c.workspace_id
+                 WHERE w.path IS NOT NULL
+                   AND (c.composer_id IN (SELECT composer_id FROM changes WHERE synced_at = ?1)
+                        OR c.composer_id NOT IN (SELECT composer_id FROM conversation_git))",
+            )
+            .map_err(AppError::database)?;
+
+        let paths = stmt
+            .query_map([synced_at.to_rfc3339()], |row| {
+                Ok((row.get(0)?, PathBuf::from(row.get::<_, String>(1)?)))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        Ok(paths)
+    }
+
+    /// Record the git repository a conversation's workspace is in.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn set_conversation_git(&self, composer_id: &str, git: &GitInfo) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO conversation_git
+                     (composer_id, repo_root, origin_url, branch, head_commit, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    composer_id,
+                    git.repo_root,
+                    git.origin_url,
+                    git.branch,
+                    git.head_commit,
+                    Utc::now().to_rfc3339()
+                ],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Recorded git repository of every conversation that has one, keyed by composer ID.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn conversation_git(&self) -> Result<HashMap<String, GitInfo>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT composer_id, repo_root, origin_url, branch, head_commit
+                 FROM conversation_git",
+            )
+            .map_err(AppError::database)?;
+
+        let git = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    GitInfo {
+                        repo_root: row.get(1)?,
+                        origin_url: row.get(2)?,
+                        branch: row.get(3)?,
+                        head_commit: row.get(4)?,
+                    },
+                ))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()
+            .map_err(AppError::database)?;
+
+        Ok(git)
+    }
+
+    /// Upsert a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn upsert_conversation(
+        &self,
+        conv: &Conversation,
+        workspace_id: Option<i64>,
+        content_hash: Option<&str>,
+    ) -> Result<i64> {
+        self.conn
+            .execute(
+                r"
+            INSERT INTO conversations 
+                (composer_id, workspace_id, title, model_name, max_mode, unified_mode, created_at, content_hash)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(composer_id) DO UPDATE SET
                 workspace_id = COALESCE(excluded.workspace_id, workspace_id),
                 title = excluded.title,
                 model_name = excluded.model_name,
@@ -184,7 +1108,7 @@ impl LocalStorage {
                     workspace_id,
                     &conv.title,
                     &conv.model_config.model_name,
-                    conv.model_config.max_mode as i32,
+                    i32::from(conv.model_config.max_mode),
                     &conv.unified_mode,
                     conv.created_at.map(|dt| dt.to_rfc3339()),
                     content_hash,
@@ -202,59 +1126,122 @@ impl LocalStorage {
             .map_err(AppError::database)
     }
 
+    /// Run `f` in one transaction, committed only if it succeeds.
+    ///
+    /// # Errors
+    /// Returns the error of `f`, or one from beginning or committing.
+    pub fn in_transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(AppError::database)?;
+        let value = f()?;
+        tx.commit().map_err(AppError::database)?;
+        Ok(value)
+    }
+
     /// Upsert a bubble/message.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
     pub fn upsert_bubble(&self, bubble: &Bubble, conversation_id: i64) -> Result<()> {
-        self.conn
-            .execute(
+        let (text, text_hash) = self.store_text(&bubble.text)?;
+        let thinking_text = bubble
+            .thinking
+            .as_ref()
+            .map(|t| self.encode_text(&t.text))
+            .transpose()?;
+        let commands = self.encode_json_list(&bubble.commands)?;
+        let diagnostics = self.encode_json_list(&bubble.diagnostics)?;
+        let tool_calls = self.encode_json_list(&bubble.tool_calls)?;
+        let context = if bubble.context.is_empty() {
+            None
+        } else {
+            Some(self.encode_json(&bubble.context)?)
+        };
+
+        let id: i64 = self
+            .conn
+            .query_row(
                 r"
             INSERT INTO bubbles 
                 (bubble_id, conversation_id, bubble_type, text, created_at,
                  thinking_text, thinking_signature, thinking_duration_ms,
-                 input_tokens, output_tokens, is_agentic)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                 input_tokens, output_tokens, is_agentic, text_hash, reasoning_tokens,
+                 commands, diagnostics, tool_calls, context)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
             ON CONFLICT(bubble_id) DO UPDATE SET
                 text = excluded.text,
+                text_hash = excluded.text_hash,
                 thinking_text = excluded.thinking_text,
                 thinking_signature = excluded.thinking_signature,
                 thinking_duration_ms = excluded.thinking_duration_ms,
                 input_tokens = excluded.input_tokens,
                 output_tokens = excluded.output_tokens,
-                is_agentic = excluded.is_agentic
+                reasoning_tokens = excluded.reasoning_tokens,
+                is_agentic = excluded.is_agentic,
+                commands = excluded.commands,
+                diagnostics = excluded.diagnostics,
+                tool_calls = excluded.tool_calls,
+                context = excluded.context
+            RETURNING id
             ",
                 params![
                     &bubble.bubble_id,
                     conversation_id,
                     bubble.bubble_type as u8,
-                    &bubble.text,
+                    text,
                     bubble.created_at.map(|dt| dt.to_rfc3339()),
-                    bubble.thinking.as_ref().map(|t| &t.text),
+                    thinking_text,
                     bubble.thinking.as_ref().and_then(|t| t.signature.as_ref()),
                     bubble.thinking_duration_ms,
-                    bubble.token_count.input_tokens as i64,
-                    bubble.token_count.output_tokens as i64,
-                    bubble.is_agentic as i32,
+                    bubble.token_count.input_tokens.cast_signed(),
+                    bubble.token_count.output_tokens.cast_signed(),
+                    i32::from(bubble.is_agentic),
+                    text_hash,
+                    bubble.token_count.reasoning_tokens.cast_signed(),
+                    commands,
+                    diagnostics,
+                    tool_calls,
+                    context,
                 ],
+                |row| row.get(0),
             )
             .map_err(AppError::database)?;
 
-        Ok(())
+        self.index_bubble(
+            id,
+            &bubble.text,
+            bubble.thinking.as_ref().map(|t| t.text.as_str()),
+        )
     }
 
     /// Get all conversations, optionally filtered by workspace.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
     pub fn get_conversations(&self, workspace_name: Option<&str>) -> Result<Vec<Conversation>> {
+        let span =
+            tracing::info_span!(target: TIMINGS_TARGET, "db read", rows = tracing::field::Empty);
+        let _reading = span.enter();
+
         let query = if workspace_name.is_some() {
             r"
-            SELECT c.composer_id, c.title, c.model_name, c.max_mode, c.unified_mode, c.created_at
+            SELECT c.composer_id, COALESCE(t.title, c.title), c.model_name, c.max_mode,
+                   c.unified_mode, c.created_at
             FROM conversations c
             JOIN workspaces w ON c.workspace_id = w.id
+            LEFT JOIN title_overrides t ON t.composer_id = c.composer_id
             WHERE w.name = ?1
             ORDER BY c.created_at DESC
             "
         } else {
             r"
-            SELECT composer_id, title, model_name, max_mode, unified_mode, created_at
-            FROM conversations
-            ORDER BY created_at DESC
+            SELECT c.composer_id, COALESCE(t.title, c.title), c.model_name, c.max_mode,
+                   c.unified_mode, c.created_at
+            FROM conversations c
+            LEFT JOIN title_overrides t ON t.composer_id = c.composer_id
+            ORDER BY c.created_at DESC
             "
         };
 
@@ -268,147 +1255,107 @@ impl LocalStorage {
         .map_err(AppError::database)?;
 
         let mut conversations = Vec::new();
-        for row in rows {
-            if let Ok(mut conv) = row {
-                // Load bubbles for this conversation
-                conv.bubbles = self.get_bubbles(&conv.composer_id)?;
-                conversations.push(conv);
-            }
+        for mut conv in rows.flatten() {
+            // Load bubbles for this conversation
+            conv.bubbles = self.get_bubbles(&conv.composer_id)?;
+            conversations.push(conv);
         }
+        span.record("rows", conversations.len());
 
         Ok(conversations)
     }
 
-    /// Convert a row to a Conversation.
-    fn row_to_conversation(row: &rusqlite::Row) -> rusqlite::Result<Conversation> {
-        let created_at_str: Option<String> = row.get(5)?;
-        let created_at = created_at_str.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-            .map(|dt| dt.with_timezone(&Utc));
-
-        Ok(Conversation {
-            composer_id: row.get(0)?,
-            title: row.get(1)?,
-            model_config: ModelConfig {
-                model_name: row.get(2)?,
-                max_mode: row.get::<_, i32>(3)? != 0,
-            },
-            unified_mode: row.get(4)?,
-            created_at,
-            bubbles: Vec::new(),
-        })
-    }
-
-    /// Get bubbles for a conversation.
-    pub fn get_bubbles(&self, composer_id: &str) -> Result<Vec<Bubble>> {
+    /// Find stored composer IDs containing `partial_id`.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn find_composer_ids(&self, partial_id: &str) -> Result<Vec<String>> {
         let mut stmt = self
             .conn
             .prepare(
-                r"
-            SELECT b.bubble_id, b.bubble_type, b.text, b.created_at,
-                   b.thinking_text, b.thinking_signature, b.thinking_duration_ms,
-                   b.input_tokens, b.output_tokens, b.is_agentic
-            FROM bubbles b
-            JOIN conversations c ON b.conversation_id = c.id
-            WHERE c.composer_id = ?1
-            ORDER BY b.created_at ASC
-            ",
+                "SELECT composer_id FROM conversations
+                 WHERE instr(composer_id, ?1) > 0
+                 ORDER BY created_at DESC",
             )
             .map_err(AppError::database)?;
 
-        let rows = stmt
-            .query_map([composer_id], |row| {
-                let created_at_str: Option<String> = row.get(3)?;
-                let created_at = created_at_str
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc));
+        let ids = stmt
+            .query_map([partial_id], |row| row.get(0))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .map_err(AppError::database)?;
 
-                let thinking_text: Option<String> = row.get(4)?;
-                let thinking_signature: Option<String> = row.get(5)?;
-                let thinking = thinking_text.map(|text| crate::domain::ThinkingBlock {
-                    text,
-                    signature: thinking_signature,
-                });
+        Ok(ids)
+    }
 
-                Ok(Bubble {
-                    bubble_id: row.get(0)?,
-                    bubble_type: BubbleType::try_from(row.get::<_, u8>(1)?)
-                        .unwrap_or(BubbleType::Unknown),
-                    text: row.get(2)?,
-                    created_at,
-                    thinking,
-                    thinking_duration_ms: row.get(6)?,
-                    token_count: crate::domain::TokenCount {
-                        input_tokens: row.get::<_, i64>(7)? as u64,
-                        output_tokens: row.get::<_, i64>(8)? as u64,
-                    },
-                    is_agentic: row.get::<_, i32>(9)? != 0,
-                })
-            })
+    /// Get a single conversation with its bubbles.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_conversation(&self, composer_id: &str) -> Result<Option<Conversation>> {
+        let conv = self
+            .conn
+            .query_row(
+                r"
+            SELECT c.composer_id, COALESCE(t.title, c.title), c.model_name, c.max_mode,
+                   c.unified_mode, c.created_at
+            FROM conversations c
+            LEFT JOIN title_overrides t ON t.composer_id = c.composer_id
+            WHERE c.composer_id = ?1
+            ",
+                [composer_id],
+                Self::row_to_conversation,
+            )
+            .optional()
             .map_err(AppError::database)?;
 
-        let mut bubbles = Vec::new();
-        for row in rows {
-            if let Ok(bubble) = row {
-                bubbles.push(bubble);
-            }
-        }
-
-        Ok(bubbles)
+        conv.map(|mut conv| {
+            conv.bubbles = self.get_bubbles(composer_id)?;
+            Ok(conv)
+        })
+        .transpose()
     }
 
-    /// Get sync state.
-    pub fn get_sync_state(&self) -> Result<SyncState> {
+    /// Get the cached summary of a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_summary(&self, composer_id: &str) -> Result<Option<StoredSummary>> {
         self.conn
             .query_row(
-                r"
-            SELECT last_sync, last_hash, conversation_count, message_count,
-                   storage_bytes, is_syncing, last_error
-            FROM sync_state WHERE id = 1
-            ",
-                [],
+                "SELECT summary, model, content_hash, created_at FROM summaries
+                 WHERE composer_id = ?1",
+                [composer_id],
                 |row| {
-                    let last_sync_str: Option<String> = row.get(0)?;
-                    let last_sync = last_sync_str
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc));
-
-                    Ok(SyncState {
-                        last_sync,
-                        last_hash: row.get(1)?,
-                        conversation_count: row.get::<_, i64>(2)? as usize,
-                        message_count: row.get::<_, i64>(3)? as usize,
-                        storage_bytes: row.get::<_, i64>(4)? as u64,
-                        is_syncing: row.get::<_, i32>(5)? != 0,
-                        last_error: row.get(6)?,
+                    let created_at: String = row.get(3)?;
+                    Ok(StoredSummary {
+                        summary: row.get(0)?,
+                        model: row.get(1)?,
+                        content_hash: row.get(2)?,
+                        created_at: DateTime::parse_from_rfc3339(&created_at)
+                            .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
                     })
                 },
             )
+            .optional()
             .map_err(AppError::database)
     }
 
-    /// Update sync state.
-    pub fn update_sync_state(&self, state: &SyncState) -> Result<()> {
+    /// Store (or replace) the summary of a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn save_summary(&self, composer_id: &str, summary: &StoredSummary) -> Result<()> {
         self.conn
             .execute(
-                r"
-            UPDATE sync_state SET
-                last_sync = ?1,
-                last_hash = ?2,
-                conversation_count = ?3,
-                message_count = ?4,
-                storage_bytes = ?5,
-                is_syncing = ?6,
-                last_error = ?7
-            WHERE id = 1
-            ",
+                "INSERT OR REPLACE INTO summaries (composer_id, summary, model, content_hash, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
                 params![
-                    state.last_sync.map(|dt| dt.to_rfc3339()),
-                    &state.last_hash,
-                    state.conversation_count as i64,
-                    state.message_count as i64,
-                    state.storage_bytes as i64,
-                    state.is_syncing as i32,
-                    &state.last_error,
+                    composer_id,
+                    summary.summary,
+                    summary.model,
+                    summary.content_hash,
+                    summary.created_at.to_rfc3339()
                 ],
             )
             .map_err(AppError::database)?;
@@ -416,76 +1363,1329 @@ impl LocalStorage {
         Ok(())
     }
 
-    /// Get total storage size in bytes.
-    pub fn get_storage_size(&self) -> Result<u64> {
-        let path = match self.conn.path() {
-            Some(p) => Path::new(p),
-            None => return Ok(0),
-        };
-        let metadata = std::fs::metadata(path)
-            .map_err(|e| AppError::io("Failed to get storage size", e))?;
-        Ok(metadata.len())
-    }
+    /// Get all title overrides, keyed by composer ID.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn title_overrides(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT composer_id, title FROM title_overrides")
+            .map_err(AppError::database)?;
 
-    /// Get conversation count.
-    pub fn get_conversation_count(&self) -> Result<usize> {
-        self.conn
-            .query_row("SELECT COUNT(*) FROM conversations", [], |row| {
-                row.get::<_, i64>(0)
-            })
-            .map(|c| c as usize)
-            .map_err(AppError::database)
-    }
+        let overrides = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<HashMap<String, String>>>()
+            .map_err(AppError::database)?;
 
-    /// Get message count.
-    pub fn get_message_count(&self) -> Result<usize> {
-        self.conn
-            .query_row("SELECT COUNT(*) FROM bubbles", [], |row| {
-                row.get::<_, i64>(0)
-            })
-            .map(|c| c as usize)
-            .map_err(AppError::database)
+        Ok(overrides)
     }
 
-    /// Check if a conversation exists and get its hash.
-    pub fn get_conversation_hash(&self, composer_id: &str) -> Result<Option<String>> {
+    /// Set the title shown for a conversation instead of its heuristic title.
+    ///
+    /// `source` records where it came from (e.g. the model name).
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn set_title_override(&self, composer_id: &str, title: &str, source: &str) -> Result<()> {
         self.conn
-            .query_row(
-                "SELECT content_hash FROM conversations WHERE composer_id = ?1",
-                [composer_id],
-                |row| row.get(0),
+            .execute(
+                "INSERT OR REPLACE INTO title_overrides (composer_id, title, source, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![composer_id, title, source, Utc::now().to_rfc3339()],
             )
-            .optional()
-            .map_err(AppError::database)
+            .map_err(AppError::database)?;
+
+        Ok(())
     }
 
-    /// Get all workspace names.
-    pub fn get_workspaces(&self) -> Result<Vec<WorkspaceInfo>> {
+    /// IDs of pinned conversations.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn pinned_ids(&self) -> Result<HashSet<String>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT name, path, cursor_path FROM workspaces ORDER BY name")
+            .prepare("SELECT composer_id FROM pins")
             .map_err(AppError::database)?;
 
-        let rows = stmt
-            .query_map([], |row| {
-                let path_str: Option<String> = row.get(1)?;
-                Ok(WorkspaceInfo {
-                    name: row.get(0)?,
-                    path: path_str.map(std::path::PathBuf::from),
-                    cursor_path: row.get(2)?,
-                })
-            })
+        let ids = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<HashSet<String>>>()
             .map_err(AppError::database)?;
 
-        let mut workspaces = Vec::new();
-        for row in rows {
-            if let Ok(ws) = row {
-                workspaces.push(ws);
-            }
+        Ok(ids)
+    }
+
+    /// Pin or unpin a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn set_pinned(&self, composer_id: &str, pinned: bool) -> Result<()> {
+        if pinned {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO pins (composer_id, pinned_at) VALUES (?1, ?2)",
+                params![composer_id, Utc::now().to_rfc3339()],
+            )
+        } else {
+            self.conn
+                .execute("DELETE FROM pins WHERE composer_id = ?1", [composer_id])
+        }
+        .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Title and time of the last message (or creation) of every stored
+    /// conversation, without loading messages.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn conversation_activity(&self) -> Result<HashMap<String, ConversationActivity>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.composer_id, COALESCE(t.title, c.title),
+                        COALESCE(MAX(b.created_at), c.created_at)
+                 FROM conversations c
+                 LEFT JOIN bubbles b ON b.conversation_id = c.id
+                 LEFT JOIN title_overrides t ON t.composer_id = c.composer_id
+                 GROUP BY c.id",
+            )
+            .map_err(AppError::database)?;
+
+        let activity = stmt
+            .query_map([], |row| {
+                let last: Option<String> = row.get(2)?;
+                Ok((
+                    row.get(0)?,
+                    ConversationActivity {
+                        title: row.get(1)?,
+                        last_activity: last
+                            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                            .map(|dt| dt.with_timezone(&Utc)),
+                    },
+                ))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(AppError::database)?;
+
+        Ok(activity)
+    }
+
+    /// Mark a conversation archived, replacing an earlier mark.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn set_archived(&self, composer_id: &str, removed_from_cursor: bool) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO archived (composer_id, removed_from_cursor, archived_at)
+                 VALUES (?1, ?2, ?3)",
+                params![composer_id, removed_from_cursor, Utc::now().to_rfc3339()],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Clear a conversation's archived mark. Returns whether it had one.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn unarchive(&self, composer_id: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM archived WHERE composer_id = ?1", [composer_id])
+            .map_err(AppError::database)?;
+
+        Ok(removed > 0)
+    }
+
+    /// Archived conversations, most recently archived first.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn archived(&self) -> Result<Vec<ArchivedConversation>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT a.composer_id, COALESCE(t.title, c.title, ''), a.removed_from_cursor,
+                        a.archived_at
+                 FROM archived a
+                 LEFT JOIN conversations c ON c.composer_id = a.composer_id
+                 LEFT JOIN title_overrides t ON t.composer_id = a.composer_id
+                 ORDER BY a.archived_at DESC",
+            )
+            .map_err(AppError::database)?;
+
+        let archived = stmt
+            .query_map([], |row| {
+                let archived_at: String = row.get(3)?;
+                Ok(ArchivedConversation {
+                    composer_id: row.get(0)?,
+                    title: row.get(1)?,
+                    removed_from_cursor: row.get(2)?,
+                    archived_at: DateTime::parse_from_rfc3339(&archived_at)
+                        .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                })
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        Ok(archived)
+    }
+
+    /// Tag a conversation. Returns whether the tag was new.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn add_tag(&self, composer_id: &str, tag: &str) -> Result<bool> {
+        let added = self
+            .conn
+            .execute(
+                "INSERT OR IGNORE INTO tags (composer_id, tag, tagged_at) VALUES (?1, ?2, ?3)",
+                params![composer_id, tag, Utc::now().to_rfc3339()],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(added > 0)
+    }
+
+    /// Remove a tag from a conversation. Returns whether it had the tag.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn remove_tag(&self, composer_id: &str, tag: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .execute(
+                "DELETE FROM tags WHERE composer_id = ?1 AND tag = ?2",
+                params![composer_id, tag],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(removed > 0)
+    }
+
+    /// Tags of each tagged conversation, sorted.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn conversation_tags(&self) -> Result<HashMap<String, Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT composer_id, tag FROM tags ORDER BY tag")
+            .map_err(AppError::database)?;
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .map_err(AppError::database)?;
+        for row in rows {
+            let (composer_id, tag) = row.map_err(AppError::database)?;
+            tags.entry(composer_id).or_default().push(tag);
+        }
+
+        Ok(tags)
+    }
+
+    /// Every tag in use with its number of conversations, most used first.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn tag_counts(&self) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT tag, COUNT(*) FROM tags
+                 GROUP BY tag
+                 ORDER BY COUNT(*) DESC, tag",
+            )
+            .map_err(AppError::database)?;
+
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    usize::try_from(row.get::<_, i64>(1)?).unwrap_or(0),
+                ))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        Ok(counts)
+    }
+
+    /// Content hash of the conversation when its embedding for `model` was computed.
+    ///
+    /// Returns `None` if there is no embedding for it yet.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn embedding_hash(&self, composer_id: &str, model: &str) -> Result<Option<String>> {
+        let hash: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT content_hash FROM embeddings WHERE composer_id = ?1 AND model = ?2",
+                params![composer_id, model],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::database)?;
+
+        Ok(hash.flatten())
+    }
+
+    /// Store (or replace) the embedding of a conversation for `model`.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn upsert_embedding(
+        &self,
+        composer_id: &str,
+        model: &str,
+        vector: &[f32],
+        content_hash: Option<&str>,
+    ) -> Result<()> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO embeddings (composer_id, model, vector, content_hash, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![composer_id, model, bytes, content_hash, Utc::now().to_rfc3339()],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get all embeddings computed with `model`, with their conversation titles.
+    ///
+    /// With `workspace`, only conversations of the workspace with that name
+    /// (case-insensitive) are returned; with `tag`, only those with the tag.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_embeddings(
+        &self,
+        model: &str,
+        workspace: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<Vec<StoredEmbedding>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT e.composer_id, COALESCE(t.title, c.title), e.vector
+            FROM embeddings e
+            JOIN conversations c ON c.composer_id = e.composer_id
+            LEFT JOIN title_overrides t ON t.composer_id = e.composer_id
+            LEFT JOIN workspaces w ON w.id = c.workspace_id
+            WHERE e.model = ?1 AND (?2 IS NULL OR w.name = ?2 COLLATE NOCASE)
+              AND (?3 IS NULL OR e.composer_id IN (SELECT composer_id FROM tags WHERE tag = ?3))
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map(params![model, workspace, tag], |row| {
+                let bytes: Vec<u8> = row.get(2)?;
+                Ok(StoredEmbedding {
+                    composer_id: row.get(0)?,
+                    title: row.get(1)?,
+                    vector: bytes
+                        .chunks_exact(4)
+                        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .collect(),
+                })
+            })
+            .map_err(AppError::database)?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)
+    }
+
+    /// Convert a row to a Conversation.
+    fn row_to_conversation(row: &rusqlite::Row) -> rusqlite::Result<Conversation> {
+        let created_at_str: Option<String> = row.get(5)?;
+        let created_at = created_at_str
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        Ok(Conversation {
+            composer_id: row.get(0)?,
+            title: row.get(1)?,
+            model_config: ModelConfig {
+                model_name: row.get(2)?,
+                max_mode: row.get::<_, i32>(3)? != 0,
+            },
+            unified_mode: row.get(4)?,
+            created_at,
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        })
+    }
+
+    /// Get bubbles for a conversation.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_bubbles(&self, composer_id: &str) -> Result<Vec<Bubble>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT b.bubble_id, b.bubble_type, COALESCE(bl.data, b.text), b.created_at,
+                   b.thinking_text, b.thinking_signature, b.thinking_duration_ms,
+                   b.input_tokens, b.output_tokens, b.is_agentic, b.reasoning_tokens,
+                   b.commands, b.diagnostics, b.tool_calls, b.context
+            FROM bubbles b
+            JOIN conversations c ON b.conversation_id = c.id
+            LEFT JOIN blobs bl ON bl.hash = b.text_hash
+            WHERE c.composer_id = ?1
+            ORDER BY b.created_at ASC
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map([composer_id], |row| {
+                let created_at_str: Option<String> = row.get(3)?;
+                let created_at = created_at_str
+                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|dt| dt.with_timezone(&Utc));
+
+                let thinking_text = Self::decode_text(row, 4)?;
+                let thinking_signature: Option<String> = row.get(5)?;
+                let thinking = thinking_text.map(|text| crate::domain::ThinkingBlock {
+                    text,
+                    signature: thinking_signature,
+                });
+
+                Ok(Bubble {
+                    bubble_id: row.get(0)?,
+                    bubble_type: BubbleType::try_from(row.get::<_, u8>(1)?)
+                        .unwrap_or(BubbleType::Unknown),
+                    text: Self::decode_text(row, 2)?.unwrap_or_default(),
+                    created_at,
+                    thinking,
+                    thinking_duration_ms: row.get(6)?,
+                    token_count: crate::domain::TokenCount {
+                        input_tokens: row.get::<_, i64>(7)?.cast_unsigned(),
+                        output_tokens: row.get::<_, i64>(8)?.cast_unsigned(),
+                        reasoning_tokens: row.get::<_, i64>(10)?.cast_unsigned(),
+                    },
+                    is_agentic: row.get::<_, i32>(9)? != 0,
+                    commands: Self::decode_json_list(row, 11)?,
+                    tool_calls: Self::decode_json_list(row, 13)?,
+                    context: Self::decode_json(row, 14)?,
+                    diagnostics: Self::decode_json_list(row, 12)?,
+                })
+            })
+            .map_err(AppError::database)?;
+
+        Ok(rows.flatten().collect())
+    }
+
+    /// Get sync state.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_sync_state(&self) -> Result<SyncState> {
+        self.conn
+            .query_row(
+                r"
+            SELECT last_sync, last_hash, conversation_count, message_count,
+                   storage_bytes, is_syncing, last_error, cursor_version
+            FROM sync_state WHERE id = 1
+            ",
+                [],
+                |row| {
+                    let last_sync_str: Option<String> = row.get(0)?;
+                    let last_sync = last_sync_str
+                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&Utc));
+
+                    Ok(SyncState {
+                        last_sync,
+                        last_hash: row.get(1)?,
+                        conversation_count: usize::try_from(row.get::<_, i64>(2)?).unwrap_or(0),
+                        message_count: usize::try_from(row.get::<_, i64>(3)?).unwrap_or(0),
+                        storage_bytes: row.get::<_, i64>(4)?.cast_unsigned(),
+                        is_syncing: row.get::<_, i32>(5)? != 0,
+                        last_error: row.get(6)?,
+                        cursor_version: row.get(7)?,
+                    })
+                },
+            )
+            .map_err(AppError::database)
+    }
+
+    /// Update sync state.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn update_sync_state(&self, state: &SyncState) -> Result<()> {
+        self.conn
+            .execute(
+                r"
+            UPDATE sync_state SET
+                last_sync = ?1,
+                last_hash = ?2,
+                conversation_count = ?3,
+                message_count = ?4,
+                storage_bytes = ?5,
+                is_syncing = ?6,
+                last_error = ?7,
+                cursor_version = ?8
+            WHERE id = 1
+            ",
+                params![
+                    state.last_sync.map(|dt| dt.to_rfc3339()),
+                    &state.last_hash,
+                    i64::try_from(state.conversation_count).unwrap_or(i64::MAX),
+                    i64::try_from(state.message_count).unwrap_or(i64::MAX),
+                    state.storage_bytes.cast_signed(),
+                    i32::from(state.is_syncing),
+                    &state.last_error,
+                    &state.cursor_version,
+                ],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Record a detected Cursor reset.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn record_reset_event(&self, event: &ResetEvent) -> Result<()> {
+        self.conn
+            .execute(
+                r"
+            INSERT INTO reset_events
+                (detected_at, local_conversations, cursor_conversations, restored)
+            VALUES (?1, ?2, ?3, ?4)
+            ",
+                params![
+                    event.detected_at.to_rfc3339(),
+                    event.local_conversations,
+                    event.cursor_conversations,
+                    i32::from(event.restored),
+                ],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get the most recent reset events (newest first).
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_reset_events(&self, limit: usize) -> Result<Vec<ResetEvent>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT detected_at, local_conversations, cursor_conversations, restored
+            FROM reset_events
+            ORDER BY id DESC
+            LIMIT ?1
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map([limit], |row| {
+                let detected_at: String = row.get(0)?;
+                Ok(ResetEvent {
+                    detected_at: DateTime::parse_from_rfc3339(&detected_at)
+                        .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                    local_conversations: row.get(1)?,
+                    cursor_conversations: row.get(2)?,
+                    restored: row.get::<_, i32>(3)? != 0,
+                    restore_error: None,
+                })
+            })
+            .map_err(AppError::database)?;
+
+        Ok(rows.filter_map(std::result::Result::ok).collect())
+    }
+
+    /// Append an entry to the audit log.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn record_operation(&self, op: &Operation) -> Result<()> {
+        self.conn
+            .execute(
+                r"
+            INSERT INTO operations (kind, started_at, duration_ms, items, success, details)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ",
+                params![
+                    op.kind.as_str(),
+                    op.started_at.to_rfc3339(),
+                    op.duration_ms,
+                    op.items,
+                    i32::from(op.success),
+                    op.details,
+                ],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get the most recent audit log entries (newest first), optionally of one kind.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_operations(
+        &self,
+        limit: usize,
+        kind: Option<OperationKind>,
+    ) -> Result<Vec<Operation>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT kind, started_at, duration_ms, items, success, details
+            FROM operations
+            WHERE ?1 IS NULL OR kind = ?1
+            ORDER BY id DESC
+            LIMIT ?2
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map(params![kind.map(OperationKind::as_str), limit], |row| {
+                let kind: String = row.get(0)?;
+                let started_at: String = row.get(1)?;
+                let Ok(kind) = kind.parse() else {
+                    return Ok(None);
+                };
+                Ok(Some(Operation {
+                    kind,
+                    started_at: DateTime::parse_from_rfc3339(&started_at)
+                        .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                    duration_ms: row.get(2)?,
+                    items: row.get(3)?,
+                    success: row.get::<_, i32>(4)? != 0,
+                    details: row.get(5)?,
+                }))
+            })
+            .map_err(AppError::database)?;
+
+        Ok(rows.filter_map(|row| row.ok().flatten()).collect())
+    }
+
+    /// Append an entry to the change feed.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn record_change(
+        &self,
+        composer_id: &str,
+        change_type: ChangeType,
+        synced_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO changes (composer_id, change_type, synced_at) VALUES (?1, ?2, ?3)",
+                params![composer_id, change_type.as_str(), synced_at.to_rfc3339()],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get the most recent changes (newest first), optionally only those
+    /// synced after `since`.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_changes(&self, since: Option<DateTime<Utc>>, limit: usize) -> Result<Vec<Change>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT ch.composer_id, COALESCE(t.title, c.title, ''), ch.change_type, ch.synced_at
+            FROM changes ch
+            LEFT JOIN conversations c ON c.composer_id = ch.composer_id
+            LEFT JOIN title_overrides t ON t.composer_id = ch.composer_id
+            WHERE ?1 IS NULL OR ch.synced_at > ?1
+            ORDER BY ch.id DESC
+            LIMIT ?2
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map(params![since.map(|t| t.to_rfc3339()), limit], |row| {
+                let change_type: String = row.get(2)?;
+                let synced_at: String = row.get(3)?;
+                let change_type = match change_type.as_str() {
+                    "created" => ChangeType::Created,
+                    "updated" => ChangeType::Updated,
+                    _ => return Ok(None),
+                };
+                Ok(Some(Change {
+                    composer_id: row.get(0)?,
+                    title: row.get(1)?,
+                    kind: change_type,
+                    synced_at: DateTime::parse_from_rfc3339(&synced_at)
+                        .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                }))
+            })
+            .map_err(AppError::database)?;
+
+        Ok(rows.filter_map(|row| row.ok().flatten()).collect())
+    }
+
+    /// Run routine upkeep: WAL checkpoint, orphan purge, audit log trimming
+    /// and query planner optimization.
+    ///
+    /// Returns the number of rows purged.
+    ///
+    /// # Errors
+    /// Returns error if any maintenance statement fails.
+    pub fn maintain(&self) -> Result<usize> {
+        let mut purged = self
+            .conn
+            .execute(
+                "DELETE FROM bubbles
+                 WHERE conversation_id NOT IN (SELECT id FROM conversations)",
+                [],
+            )
+            .map_err(AppError::database)?;
+
+        // Blobs no longer referenced after bubble text changed
+        purged += self
+            .conn
+            .execute(
+                "DELETE FROM blobs WHERE hash NOT IN
+                     (SELECT text_hash FROM bubbles WHERE text_hash IS NOT NULL)",
+                [],
+            )
+            .map_err(AppError::database)?;
+
+        // The daemon logs a sync every few minutes; keep the audit log bounded
+        purged += self
+            .conn
+            .execute(
+                "DELETE FROM operations
+                 WHERE id <= (SELECT MAX(id) FROM operations) - ?1",
+                [OPERATIONS_KEPT],
+            )
+            .map_err(AppError::database)?;
+
+        self.conn
+            .execute_batch(
+                "PRAGMA wal_checkpoint(TRUNCATE);
+                 PRAGMA optimize;",
+            )
+            .map_err(AppError::database)?;
+
+        Ok(purged)
+    }
+
+    /// Record a completed maintenance run.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn record_maintenance(&self, ran_at: DateTime<Utc>, purged_rows: usize) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO maintenance_runs (ran_at, purged_rows) VALUES (?1, ?2)",
+                params![ran_at.to_rfc3339(), purged_rows],
+            )
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get when maintenance last ran, if ever.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn last_maintenance(&self) -> Result<Option<DateTime<Utc>>> {
+        let ran_at: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT ran_at FROM maintenance_runs ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::database)?;
+
+        Ok(ran_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    /// Checkpoint the WAL and rebuild the database file to release free pages.
+    ///
+    /// # Errors
+    /// Returns error if any maintenance statement fails.
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn
+            .execute_batch(
+                "PRAGMA wal_checkpoint(TRUNCATE);
+                 PRAGMA incremental_vacuum;
+                 VACUUM;
+                 PRAGMA wal_checkpoint(TRUNCATE);",
+            )
+            .map_err(AppError::database)
+    }
+
+    /// Run integrity and consistency checks without modifying anything.
+    ///
+    /// # Errors
+    /// Returns error if the checks cannot be executed.
+    pub fn check_health(&self) -> Result<HealthReport> {
+        let mut stmt = self
+            .conn
+            .prepare("PRAGMA integrity_check")
+            .map_err(AppError::database)?;
+        let integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?
+            .into_iter()
+            .filter(|msg| msg != "ok")
+            .collect();
+
+        let orphan_bubbles = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM bubbles
+                 WHERE conversation_id NOT IN (SELECT id FROM conversations)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("PRAGMA foreign_key_check")
+            .map_err(AppError::database)?;
+        let foreign_key_violations = stmt
+            .query_map([], |_| Ok(()))
+            .map_err(AppError::database)?
+            .count();
+
+        let mut malformed_timestamps = 0;
+        for table in TIMESTAMPED_TABLES {
+            malformed_timestamps += self.malformed_timestamp_ids(table)?.len();
+        }
+
+        Ok(HealthReport {
+            integrity_errors,
+            orphan_bubbles,
+            foreign_key_violations,
+            malformed_timestamps,
+        })
+    }
+
+    /// Fix consistency problems in place, returning the number of rows changed.
+    ///
+    /// Orphan bubbles are deleted, dangling workspace references are cleared
+    /// and malformed timestamps are reset to NULL.
+    ///
+    /// # Errors
+    /// Returns error if any repair statement fails.
+    pub fn repair(&self) -> Result<usize> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(AppError::database)?;
+
+        let mut fixed = tx
+            .execute(
+                "DELETE FROM bubbles
+                 WHERE conversation_id NOT IN (SELECT id FROM conversations)",
+                [],
+            )
+            .map_err(AppError::database)?;
+
+        fixed += tx
+            .execute(
+                "UPDATE conversations SET workspace_id = NULL
+                 WHERE workspace_id IS NOT NULL
+                   AND workspace_id NOT IN (SELECT id FROM workspaces)",
+                [],
+            )
+            .map_err(AppError::database)?;
+
+        for table in TIMESTAMPED_TABLES {
+            let sql = format!("UPDATE {table} SET created_at = NULL WHERE id = ?1");
+            for id in self.malformed_timestamp_ids(table)? {
+                fixed += tx.execute(&sql, [id]).map_err(AppError::database)?;
+            }
+        }
+
+        tx.commit().map_err(AppError::database)?;
+
+        tracing::info!(rows = fixed, "Storage repaired");
+        Ok(fixed)
+    }
+
+    /// Copy every readable table into a fresh database at `dest`.
+    ///
+    /// Returns the names of tables that could not be copied.
+    ///
+    /// # Errors
+    /// Returns error if the destination cannot be created or attached.
+    pub fn salvage_into(&self, dest: &Path) -> Result<Vec<String>> {
+        // Create the destination with the current schema
+        drop(Self::open(dest)?);
+
+        self.conn
+            .execute_batch("PRAGMA foreign_keys = OFF;")
+            .map_err(AppError::database)?;
+        self.conn
+            .execute(
+                "ATTACH DATABASE ?1 AS salvage",
+                [dest.to_string_lossy().as_ref()],
+            )
+            .map_err(AppError::database)?;
+
+        let mut failed = Vec::new();
+        for table in SALVAGE_TABLES {
+            let sql = format!("INSERT OR REPLACE INTO salvage.{table} SELECT * FROM main.{table}");
+            if let Err(e) = self.conn.execute(&sql, []) {
+                tracing::warn!(table = table, error = %e, "Failed to salvage table");
+                failed.push(table.to_string());
+            }
+        }
+
+        self.conn
+            .execute_batch("DETACH DATABASE salvage; PRAGMA foreign_keys = ON;")
+            .map_err(AppError::database)?;
+
+        // The search index is derived from bubbles, so it is rebuilt, not copied
+        Self::open(dest)?.rebuild_search_index()?;
+
+        Ok(failed)
+    }
+
+    /// Row counts and page usage for every table, largest first.
+    ///
+    /// # Errors
+    /// Returns error if the statistics cannot be queried.
+    pub fn table_stats(&self) -> Result<Vec<TableStats>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT m.tbl_name, COALESCE(SUM(d.pgsize), 0)
+            FROM sqlite_master m
+            LEFT JOIN dbstat d ON d.name = m.name
+            WHERE m.type IN ('table', 'index') AND m.tbl_name NOT LIKE 'sqlite_%'
+            GROUP BY m.tbl_name
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let sizes = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+
+        let mut stats = Vec::with_capacity(sizes.len());
+        for (name, bytes) in sizes {
+            let rows = self
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                    row.get(0)
+                })
+                .map_err(AppError::database)?;
+            stats.push(TableStats { name, rows, bytes });
+        }
+
+        stats.sort_by_key(|t| std::cmp::Reverse(t.bytes));
+        Ok(stats)
+    }
+
+    /// Conversations using the most stored bytes.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn largest_conversations(&self, limit: usize) -> Result<Vec<ConversationSize>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r"
+            SELECT c.composer_id, c.title, COUNT(b.id),
+                   COALESCE(SUM(COALESCE(bl.size, length(CAST(b.text AS BLOB)))
+                       + COALESCE(length(CAST(b.thinking_text AS BLOB)), 0)), 0) AS bytes
+            FROM conversations c
+            LEFT JOIN bubbles b ON b.conversation_id = c.id
+            LEFT JOIN blobs bl ON bl.hash = b.text_hash
+            GROUP BY c.id
+            ORDER BY bytes DESC
+            LIMIT ?1
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map([limit], |row| {
+                Ok(ConversationSize {
+                    composer_id: row.get(0)?,
+                    title: row.get(1)?,
+                    messages: row.get(2)?,
+                    bytes: row.get(3)?,
+                })
+            })
+            .map_err(AppError::database)?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)
+    }
+
+    /// Write all chats to a self-contained database at `dest` using the
+    /// documented standalone schema.
+    ///
+    /// # Errors
+    /// Returns error if the destination cannot be created or written.
+    pub fn export_standalone(&self, dest: &Path) -> Result<StandaloneExport> {
+        {
+            let out = Connection::open(dest).map_err(AppError::database)?;
+            out.execute_batch(STANDALONE_SCHEMA)
+                .map_err(AppError::database)?;
+            out.execute(
+                "INSERT INTO about (key, value) VALUES
+                     ('format_version', ?1), ('exported_at', ?2), ('generator', ?3)",
+                params![
+                    STANDALONE_FORMAT_VERSION,
+                    Utc::now().to_rfc3339(),
+                    concat!("cursor-chat-handler ", env!("CARGO_PKG_VERSION")),
+                ],
+            )
+            .map_err(AppError::database)?;
         }
 
+        self.conn
+            .execute(
+                "ATTACH DATABASE ?1 AS export",
+                [dest.to_string_lossy().as_ref()],
+            )
+            .map_err(AppError::database)?;
+
+        let result = self.fill_standalone();
+
+        self.conn
+            .execute_batch("DETACH DATABASE export;")
+            .map_err(AppError::database)?;
+
+        result
+    }
+
+    /// Copy rows into the attached `export` database.
+    fn fill_standalone(&self) -> Result<StandaloneExport> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(AppError::database)?;
+
+        tx.execute_batch(
+            r"
+            INSERT INTO export.workspaces (id, name, path)
+            SELECT id, name, path FROM main.workspaces;
+
+            INSERT INTO export.conversations
+                (id, workspace_id, title, model, max_mode, mode, created_at)
+            SELECT c.composer_id, c.workspace_id, COALESCE(t.title, c.title), c.model_name,
+                   c.max_mode, c.unified_mode, c.created_at
+            FROM main.conversations c
+            LEFT JOIN main.title_overrides t ON t.composer_id = c.composer_id;
+            ",
+        )
+        .map_err(AppError::database)?;
+
+        let conversations = tx
+            .query_row("SELECT COUNT(*) FROM export.conversations", [], |row| {
+                row.get(0)
+            })
+            .map_err(AppError::database)?;
+
+        let mut select = tx
+            .prepare(
+                r"
+            SELECT b.bubble_id, c.composer_id, b.bubble_type, COALESCE(bl.data, b.text),
+                   b.thinking_text, b.created_at, b.input_tokens, b.output_tokens, b.is_agentic,
+                   b.reasoning_tokens
+            FROM main.bubbles b
+            JOIN main.conversations c ON c.id = b.conversation_id
+            LEFT JOIN main.blobs bl ON bl.hash = b.text_hash
+            ORDER BY c.id, b.created_at, b.id
+            ",
+            )
+            .map_err(AppError::database)?;
+        let mut insert = tx
+            .prepare(
+                r"
+            INSERT INTO export.messages
+                (id, conversation_id, position, role, text, thinking, created_at,
+                 input_tokens, output_tokens, is_agentic, reasoning_tokens)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ",
+            )
+            .map_err(AppError::database)?;
+
+        let mut rows = select.query([]).map_err(AppError::database)?;
+        let mut messages = 0;
+        let mut current: Option<String> = None;
+        let mut position = 0_usize;
+
+        while let Some(row) = rows.next().map_err(AppError::database)? {
+            let conversation_id: String = row.get(1).map_err(AppError::database)?;
+            if current.as_deref() == Some(conversation_id.as_str()) {
+                position += 1;
+            } else {
+                position = 0;
+                current = Some(conversation_id.clone());
+            }
+
+            let role = match BubbleType::try_from(row.get::<_, u8>(2).map_err(AppError::database)?)
+            {
+                Ok(BubbleType::User) => "user",
+                Ok(BubbleType::Assistant) => "assistant",
+                _ => "unknown",
+            };
+
+            insert
+                .execute(params![
+                    row.get::<_, String>(0).map_err(AppError::database)?,
+                    conversation_id,
+                    position,
+                    role,
+                    Self::decode_text(row, 3)
+                        .map_err(AppError::database)?
+                        .unwrap_or_default(),
+                    Self::decode_text(row, 4).map_err(AppError::database)?,
+                    row.get::<_, Option<String>>(5)
+                        .map_err(AppError::database)?,
+                    row.get::<_, i64>(6).map_err(AppError::database)?,
+                    row.get::<_, i64>(7).map_err(AppError::database)?,
+                    row.get::<_, i64>(8).map_err(AppError::database)?,
+                    row.get::<_, i64>(9).map_err(AppError::database)?,
+                ])
+                .map_err(AppError::database)?;
+            messages += 1;
+        }
+
+        drop(rows);
+        drop(select);
+        drop(insert);
+        tx.commit().map_err(AppError::database)?;
+
+        Ok(StandaloneExport {
+            conversations,
+            messages,
+        })
+    }
+
+    /// Row IDs whose `created_at` is set but cannot be parsed.
+    fn malformed_timestamp_ids(&self, table: &str) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT id, created_at FROM {table} WHERE created_at IS NOT NULL"
+            ))
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(AppError::database)?;
+
+        let mut ids = Vec::new();
+        for row in rows {
+            let (id, created_at) = row.map_err(AppError::database)?;
+            if DateTime::parse_from_rfc3339(&created_at).is_err() {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Write a consistent copy of the database to another file.
+    ///
+    /// # Errors
+    /// Returns error if the destination exists or the copy fails.
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
+        self.conn
+            .execute("VACUUM INTO ?1", [dest.to_string_lossy().as_ref()])
+            .map_err(AppError::database)?;
+
+        Ok(())
+    }
+
+    /// Get total storage size in bytes.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_storage_size(&self) -> Result<u64> {
+        let path = match self.conn.path() {
+            Some(p) => Path::new(p),
+            None => return Ok(0),
+        };
+        let metadata =
+            std::fs::metadata(path).map_err(|e| AppError::io("Failed to get storage size", e))?;
+        Ok(metadata.len())
+    }
+
+    /// Get conversation count.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_conversation_count(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM conversations", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|c| usize::try_from(c).unwrap_or(0))
+            .map_err(AppError::database)
+    }
+
+    /// Get message count.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_message_count(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM bubbles", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|c| usize::try_from(c).unwrap_or(0))
+            .map_err(AppError::database)
+    }
+
+    /// Check if a conversation exists and get its hash.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_conversation_hash(&self, composer_id: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT content_hash FROM conversations WHERE composer_id = ?1",
+                [composer_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map(Option::flatten)
+            .map_err(AppError::database)
+    }
+
+    /// Title and content hash of every stored conversation, keyed by composer ID.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn conversation_hashes(&self) -> Result<HashMap<String, (String, Option<String>)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.composer_id, COALESCE(t.title, c.title), c.content_hash
+                 FROM conversations c
+                 LEFT JOIN title_overrides t ON t.composer_id = c.composer_id",
+            )
+            .map_err(AppError::database)?;
+
+        let hashes = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()
+            .map_err(AppError::database)?;
+
+        Ok(hashes)
+    }
+
+    /// Workspace name of every conversation that has one, keyed by composer ID.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn conversation_workspaces(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.composer_id, w.name FROM conversations c
+                 JOIN workspaces w ON w.id = c.workspace_id",
+            )
+            .map_err(AppError::database)?;
+
+        let workspaces = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<HashMap<String, String>>>()
+            .map_err(AppError::database)?;
+
         Ok(workspaces)
     }
+
+    /// Get all workspace names.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn get_workspaces(&self) -> Result<Vec<WorkspaceInfo>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, path, cursor_path FROM workspaces ORDER BY name")
+            .map_err(AppError::database)?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let path_str: Option<String> = row.get(1)?;
+                Ok(WorkspaceInfo {
+                    name: row.get(0)?,
+                    path: path_str.map(std::path::PathBuf::from),
+                    cursor_path: row.get(2)?,
+                })
+            })
+            .map_err(AppError::database)?;
+
+        Ok(rows.flatten().collect())
+    }
+}
+
+/// FTS5 query matching every word of `input`, each quoted so punctuation
+/// is not read as query syntax; `None` if there are no words.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .filter_map(|word| {
+            let (word, prefix) = word
+                .strip_suffix('*')
+                .map_or((word, false), |stem| (stem, true));
+            (!word.is_empty()).then(|| {
+                format!(
+                    "\"{}\"{}",
+                    word.replace('"', "\"\""),
+                    if prefix { "*" } else { "" }
+                )
+            })
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
 }
 
 #[cfg(test)]
@@ -513,15 +2713,775 @@ mod tests {
         assert!(count >= 4); // At least 4 tables
     }
 
+    #[test]
+    fn test_backup_to() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+
+        let backup_path = dir.path().join("backup.db");
+        storage.backup_to(&backup_path).unwrap();
+
+        let backup = LocalStorage::open(&backup_path).unwrap();
+        assert_eq!(backup.get_conversation_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reset_events_roundtrip() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+
+        storage.record_reset_event(&ResetEvent::new(10, 0)).unwrap();
+        storage
+            .record_reset_event(&ResetEvent::new(12, 2).restored())
+            .unwrap();
+
+        let events = storage.get_reset_events(5).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].vanished(), 10);
+        assert!(events[0].restored);
+    }
+
+    #[test]
+    fn test_operations_roundtrip() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        let started = Utc::now();
+
+        storage
+            .record_operation(&Operation::succeeded(
+                OperationKind::Sync,
+                started,
+                3,
+                "3 conversations",
+            ))
+            .unwrap();
+        storage
+            .record_operation(&Operation::failed(
+                OperationKind::Restore,
+                started,
+                &"Cursor is running",
+            ))
+            .unwrap();
+
+        let all = storage.get_operations(10, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].kind, OperationKind::Restore);
+        assert!(!all[0].success);
+
+        let syncs = storage
+            .get_operations(10, Some(OperationKind::Sync))
+            .unwrap();
+        assert_eq!(syncs.len(), 1);
+        assert_eq!(syncs[0].items, 3);
+    }
+
+    #[test]
+    fn test_content_hashes_migrated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: "Title".to_string(),
+            model_config: ModelConfig::default(),
+            unified_mode: "agent".to_string(),
+            created_at: Some(Utc::now()),
+            bubbles: vec![bubble("b2", "second"), bubble("b1", "first")],
+            git: None,
+            workspace: None,
+        };
+
+        {
+            let storage = LocalStorage::open(&path).unwrap();
+            let conv_id = storage
+                .upsert_conversation(&conv, None, Some("0123456789abcdef"))
+                .unwrap();
+            for b in &conv.bubbles {
+                storage.upsert_bubble(b, conv_id).unwrap();
+            }
+            storage
+                .save_summary(
+                    "c1",
+                    &StoredSummary {
+                        summary: "summary".to_string(),
+                        model: "model".to_string(),
+                        content_hash: Some("0123456789abcdef".to_string()),
+                        created_at: Utc::now(),
+                    },
+                )
+                .unwrap();
+            storage
+                .conn
+                .execute_batch("PRAGMA user_version = 1;")
+                .unwrap();
+        }
+
+        let storage = LocalStorage::open(&path).unwrap();
+        let hash = storage.get_conversation_hash("c1").unwrap();
+        assert_eq!(hash, Some(conv.content_hash()));
+        assert_eq!(
+            storage.get_summary("c1").unwrap().unwrap().content_hash,
+            hash
+        );
+    }
+
+    #[test]
+    fn test_baseline_store_upgrade_keeps_conversations_unchanged() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        {
+            // Schema of the first release, hashes from `DefaultHasher`
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE workspaces (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     name TEXT NOT NULL, path TEXT UNIQUE, cursor_path TEXT,
+                     created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                     updated_at TEXT NOT NULL DEFAULT (datetime('now')));
+                 CREATE TABLE conversations (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     composer_id TEXT UNIQUE NOT NULL, workspace_id INTEGER,
+                     title TEXT NOT NULL DEFAULT '', model_name TEXT NOT NULL DEFAULT '',
+                     max_mode INTEGER NOT NULL DEFAULT 0, unified_mode TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                     content_hash TEXT);
+                 CREATE TABLE bubbles (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     bubble_id TEXT UNIQUE NOT NULL, conversation_id INTEGER NOT NULL,
+                     bubble_type INTEGER NOT NULL, text TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, thinking_text TEXT, thinking_signature TEXT,
+                     thinking_duration_ms INTEGER, input_tokens INTEGER NOT NULL DEFAULT 0,
+                     output_tokens INTEGER NOT NULL DEFAULT 0,
+                     is_agentic INTEGER NOT NULL DEFAULT 0, workspace_uri TEXT,
+                     workspace_project_dir TEXT);
+                 CREATE TABLE sync_state (id INTEGER PRIMARY KEY CHECK (id = 1),
+                     last_sync TEXT, last_hash TEXT,
+                     conversation_count INTEGER NOT NULL DEFAULT 0,
+                     message_count INTEGER NOT NULL DEFAULT 0,
+                     storage_bytes INTEGER NOT NULL DEFAULT 0,
+                     is_syncing INTEGER NOT NULL DEFAULT 0, last_error TEXT);
+                 INSERT INTO sync_state (id) VALUES (1);
+                 INSERT INTO conversations (composer_id, title, unified_mode, content_hash)
+                     VALUES ('c1', 'Title', 'agent', '9f86d081884c7d65'),
+                            ('c2', 'Unhashed', 'agent', NULL);
+                 INSERT INTO bubbles (bubble_id, conversation_id, bubble_type, text)
+                     VALUES ('b1', 1, 2, 'first'), ('b2', 1, 2, 'second'),
+                            ('b3', 2, 2, 'third');",
+            )
+            .unwrap();
+        }
+
+        let storage = LocalStorage::open(&path).unwrap();
+        let conv = |id: &str, title: &str, bubbles: Vec<Bubble>| Conversation {
+            composer_id: id.to_string(),
+            title: title.to_string(),
+            model_config: ModelConfig::default(),
+            unified_mode: "agent".to_string(),
+            created_at: None,
+            bubbles,
+            git: None,
+            workspace: None,
+        };
+        // What the next sync extracts from Cursor hashes the same
+        let synced = [
+            conv(
+                "c1",
+                "Title",
+                vec![bubble("b1", "first"), bubble("b2", "second")],
+            ),
+            conv("c2", "Unhashed", vec![bubble("b3", "third")]),
+        ];
+        for conv in &synced {
+            assert_eq!(
+                storage.get_conversation_hash(&conv.composer_id).unwrap(),
+                Some(conv.content_hash()),
+                "{}",
+                conv.composer_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_1_store_migrates_to_current_schema() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let long_text = format!("needle {}", "x".repeat(BLOB_MIN_BYTES));
+        {
+            // Schema after migration 1: long text moved into `blobs`
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE conversations (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     composer_id TEXT UNIQUE NOT NULL, workspace_id INTEGER,
+                     title TEXT NOT NULL DEFAULT '', model_name TEXT NOT NULL DEFAULT '',
+                     max_mode INTEGER NOT NULL DEFAULT 0, unified_mode TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                     content_hash TEXT);
+                 CREATE TABLE blobs (hash TEXT PRIMARY KEY, data TEXT NOT NULL,
+                     size INTEGER NOT NULL);
+                 CREATE TABLE bubbles (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     bubble_id TEXT UNIQUE NOT NULL, conversation_id INTEGER NOT NULL,
+                     bubble_type INTEGER NOT NULL, text TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, thinking_text TEXT, thinking_signature TEXT,
+                     thinking_duration_ms INTEGER, input_tokens INTEGER NOT NULL DEFAULT 0,
+                     output_tokens INTEGER NOT NULL DEFAULT 0,
+                     is_agentic INTEGER NOT NULL DEFAULT 0, workspace_uri TEXT,
+                     workspace_project_dir TEXT,
+                     text_hash TEXT REFERENCES blobs(hash));
+                 CREATE TABLE sync_state (id INTEGER PRIMARY KEY CHECK (id = 1),
+                     last_sync TEXT, last_hash TEXT,
+                     conversation_count INTEGER NOT NULL DEFAULT 0,
+                     message_count INTEGER NOT NULL DEFAULT 0,
+                     storage_bytes INTEGER NOT NULL DEFAULT 0,
+                     is_syncing INTEGER NOT NULL DEFAULT 0, last_error TEXT);
+                 INSERT INTO sync_state (id) VALUES (1);
+                 INSERT INTO conversations (composer_id, title, unified_mode)
+                     VALUES ('c1', 'Title', 'agent');
+                 PRAGMA user_version = 1;",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO blobs (hash, data, size) VALUES ('h1', ?1, ?2)",
+                params![long_text, long_text.len()],
+            )
+            .unwrap();
+            conn.execute_batch(
+                "INSERT INTO bubbles (bubble_id, conversation_id, bubble_type, text, text_hash)
+                     VALUES ('b1', 1, 2, '', 'h1'), ('b2', 1, 2, 'short', NULL);",
+            )
+            .unwrap();
+        }
+
+        let storage = LocalStorage::open(&path).unwrap();
+        let version: i64 = storage
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let conv = storage.get_conversation("c1").unwrap().unwrap();
+        let texts: Vec<&str> = conv.bubbles.iter().map(|b| b.text.as_str()).collect();
+        assert_eq!(texts, [long_text.as_str(), "short"]);
+        assert_eq!(storage.get_sync_state().unwrap().cursor_version, None);
+        let hits = storage.search_messages("needle", 10, None, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].bubble_id, "b1");
+        drop(storage);
+
+        // Opening again finds nothing left to migrate
+        LocalStorage::open(&path).unwrap();
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        let first = Utc::now() - chrono::Duration::hours(2);
+        let second = Utc::now();
+
+        storage
+            .record_change("c1", ChangeType::Created, first)
+            .unwrap();
+        storage
+            .record_change("c2", ChangeType::Created, second)
+            .unwrap();
+        storage
+            .record_change("c1", ChangeType::Updated, second)
+            .unwrap();
+
+        assert_eq!(storage.get_changes(None, 10).unwrap().len(), 3);
+
+        let recent = storage
+            .get_changes(Some(second - chrono::Duration::hours(1)), 10)
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].composer_id, "c1");
+        assert_eq!(recent[0].kind, ChangeType::Updated);
+    }
+
+    #[test]
+    fn test_pins() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+
+        storage.set_pinned("c1", true).unwrap();
+        storage.set_pinned("c1", true).unwrap();
+        storage.set_pinned("c2", true).unwrap();
+        storage.set_pinned("c2", false).unwrap();
+
+        let pinned = storage.pinned_ids().unwrap();
+        assert_eq!(pinned.len(), 1);
+        assert!(pinned.contains("c1"));
+    }
+
+    #[test]
+    fn test_archived() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+
+        storage.set_archived("c1", false).unwrap();
+        storage.set_archived("c1", true).unwrap();
+        storage.set_archived("c2", false).unwrap();
+        assert!(storage.unarchive("c2").unwrap());
+        assert!(!storage.unarchive("c2").unwrap());
+
+        let archived = storage.archived().unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].composer_id, "c1");
+        assert!(archived[0].removed_from_cursor);
+        assert!(archived[0].title.is_empty());
+    }
+
+    #[test]
+    fn test_tags() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+
+        assert!(storage.add_tag("c1", "rust").unwrap());
+        assert!(!storage.add_tag("c1", "rust").unwrap());
+        storage.add_tag("c1", "bug").unwrap();
+        storage.add_tag("c2", "rust").unwrap();
+        assert!(storage.remove_tag("c2", "rust").unwrap());
+        assert!(!storage.remove_tag("c2", "rust").unwrap());
+        storage.add_tag("c3", "rust").unwrap();
+
+        let tags = storage.conversation_tags().unwrap();
+        assert_eq!(tags["c1"], ["bug", "rust"]);
+        assert!(!tags.contains_key("c2"));
+        assert_eq!(
+            storage.tag_counts().unwrap(),
+            [("rust".to_string(), 2), ("bug".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_vacuum() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        storage.record_reset_event(&ResetEvent::new(1, 0)).unwrap();
+
+        storage.vacuum().unwrap();
+        assert_eq!(storage.get_reset_events(1).unwrap().len(), 1);
+    }
+
+    /// Insert a conversation with one orphan bubble and bad timestamps.
+    fn insert_inconsistent_rows(storage: &LocalStorage) {
+        storage
+            .conn
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO conversations (composer_id, workspace_id, created_at)
+                     VALUES ('c1', 42, 'not-a-date');
+                 INSERT INTO bubbles (bubble_id, conversation_id, bubble_type, created_at)
+                     VALUES ('b1', 1, 1, '2024-01-01T00:00:00+00:00'),
+                            ('b2', 99, 1, 'yesterday');
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_check_health_and_repair() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        assert!(storage.check_health().unwrap().is_healthy());
+
+        insert_inconsistent_rows(&storage);
+
+        let report = storage.check_health().unwrap();
+        assert!(!report.is_corrupt());
+        assert_eq!(report.orphan_bubbles, 1);
+        assert_eq!(report.foreign_key_violations, 2);
+        assert_eq!(report.malformed_timestamps, 2);
+
+        assert_eq!(storage.repair().unwrap(), 3);
+        assert!(storage.check_health().unwrap().is_healthy());
+        assert_eq!(storage.get_message_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_salvage_into() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        insert_inconsistent_rows(&storage);
+
+        let dest = dir.path().join("salvage.db");
+        assert!(storage.salvage_into(&dest).unwrap().is_empty());
+
+        let salvaged = LocalStorage::open(&dest).unwrap();
+        assert_eq!(salvaged.get_conversation_count().unwrap(), 1);
+        assert_eq!(salvaged.get_message_count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_table_stats_and_largest_conversations() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        insert_inconsistent_rows(&storage);
+        storage.repair().unwrap();
+
+        let stats = storage.table_stats().unwrap();
+        let bubbles = stats.iter().find(|t| t.name == "bubbles").unwrap();
+        assert_eq!(bubbles.rows, 1);
+        assert!(bubbles.bytes > 0);
+
+        let largest = storage.largest_conversations(5).unwrap();
+        assert_eq!(largest.len(), 1);
+        assert_eq!(largest[0].composer_id, "c1");
+        assert_eq!(largest[0].messages, 1);
+    }
+
+    #[test]
+    fn test_maintain_and_record() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        insert_inconsistent_rows(&storage);
+        assert!(storage.last_maintenance().unwrap().is_none());
+
+        let purged = storage.maintain().unwrap();
+        assert_eq!(purged, 1);
+
+        let now = Utc::now();
+        storage.record_maintenance(now, purged).unwrap();
+        let last = storage.last_maintenance().unwrap().unwrap();
+        assert_eq!(last.timestamp(), now.timestamp());
+    }
+
+    fn bubble(id: &str, text: &str) -> Bubble {
+        Bubble {
+            bubble_id: id.to_string(),
+            bubble_type: BubbleType::Assistant,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_large_text_is_deduplicated() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: String::new(),
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            created_at: None,
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        };
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+
+        let dump = "fn main() {}\n".repeat(100);
+        storage
+            .upsert_bubble(&bubble("b1", &dump), conv_id)
+            .unwrap();
+        storage
+            .upsert_bubble(&bubble("b2", &dump), conv_id)
+            .unwrap();
+        storage
+            .upsert_bubble(&bubble("b3", "short"), conv_id)
+            .unwrap();
+
+        let blobs: i64 = storage
+            .conn
+            .query_row("SELECT COUNT(*) FROM blobs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(blobs, 1);
+
+        let texts: Vec<String> = storage
+            .get_bubbles("c1")
+            .unwrap()
+            .into_iter()
+            .map(|b| b.text)
+            .collect();
+        assert_eq!(texts.iter().filter(|t| **t == dump).count(), 2);
+        assert!(texts.contains(&"short".to_string()));
+
+        // Rewriting the text leaves the old blob unreferenced until maintenance
+        storage
+            .upsert_bubble(&bubble("b1", "edited"), conv_id)
+            .unwrap();
+        storage
+            .upsert_bubble(&bubble("b2", "edited"), conv_id)
+            .unwrap();
+        assert_eq!(storage.maintain().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_compressed_text_roundtrip() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db"))
+            .unwrap()
+            .with_compression(true);
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: String::new(),
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            created_at: None,
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        };
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+
+        let dump = "let x = 1;\n".repeat(200);
+        let mut verbose = bubble("b1", &dump);
+        verbose.thinking = Some(crate::domain::ThinkingBlock {
+            text: dump.clone(),
+            signature: None,
+        });
+        storage.upsert_bubble(&verbose, conv_id).unwrap();
+
+        let (blob_type, thinking_type): (String, String) = storage
+            .conn
+            .query_row(
+                "SELECT typeof(bl.data), typeof(b.thinking_text)
+                 FROM bubbles b JOIN blobs bl ON bl.hash = b.text_hash",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(blob_type, "blob");
+        assert_eq!(thinking_type, "blob");
+
+        // Plain reader still decodes compressed rows
+        let plain = storage.with_compression(false);
+        let read = plain.get_bubbles("c1").unwrap();
+        assert_eq!(read[0].text, dump);
+        assert_eq!(read[0].thinking.as_ref().unwrap().text, dump);
+    }
+
+    #[test]
+    fn test_search_messages() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db"))
+            .unwrap()
+            .with_compression(true);
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: "Parser work".to_string(),
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            created_at: None,
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        };
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+
+        // Long enough to be compressed and moved to blobs
+        let dump = format!("{}the parser fails here", "let x = 1;\n".repeat(200));
+        storage
+            .upsert_bubble(&bubble("b1", &dump), conv_id)
+            .unwrap();
+        storage
+            .upsert_bubble(&bubble("b2", "Café com o résumé"), conv_id)
+            .unwrap();
+
+        let hits = storage.search_messages("parser", 10, None, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].bubble_id, "b1");
+        assert!(hits[0].snippet.contains("**parser**"));
+
+        let accents = storage
+            .search_messages("cafe resume", 10, None, None)
+            .unwrap();
+        assert_eq!(accents[0].bubble_id, "b2");
+        assert_eq!(
+            storage
+                .search_messages("pars*", 10, None, None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(storage
+            .search_messages("parser", 10, Some("other"), None)
+            .unwrap()
+            .is_empty());
+        assert!(storage.search_messages("  ", 10, None, None).is_err());
+        assert!(storage
+            .search_messages("parser", 10, None, Some("rust"))
+            .unwrap()
+            .is_empty());
+        storage.add_tag("c1", "rust").unwrap();
+        assert_eq!(
+            storage
+                .search_messages("parser", 10, None, Some("rust"))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Edited text replaces the indexed text
+        storage
+            .upsert_bubble(&bubble("b1", "fixed"), conv_id)
+            .unwrap();
+        assert!(storage
+            .search_messages("parser", 10, None, None)
+            .unwrap()
+            .is_empty());
+
+        storage
+            .conn
+            .execute("DELETE FROM conversations", [])
+            .unwrap();
+        assert!(storage
+            .search_messages("cafe", 10, None, None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(storage.rebuild_search_index().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fts_query_quotes_words() {
+        assert_eq!(
+            fts_query(r#"say "hi" pre* -x"#).unwrap(),
+            r#""say" """hi""" "pre"* "-x""#
+        );
+        assert_eq!(fts_query(" * "), None);
+    }
+
+    #[test]
+    fn test_export_standalone() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db"))
+            .unwrap()
+            .with_compression(true);
+        let conv = Conversation {
+            composer_id: "c1".to_string(),
+            title: "Refactor".to_string(),
+            model_config: ModelConfig::default(),
+            unified_mode: String::new(),
+            created_at: None,
+            bubbles: Vec::new(),
+            git: None,
+            workspace: None,
+        };
+        let conv_id = storage.upsert_conversation(&conv, None, None).unwrap();
+        let dump = "a".repeat(COMPRESS_MIN_BYTES * 2);
+        storage
+            .upsert_bubble(&bubble("b1", &dump), conv_id)
+            .unwrap();
+        storage.upsert_bubble(&bubble("b2", "ok"), conv_id).unwrap();
+
+        let dest = dir.path().join("export.sqlite");
+        let summary = storage.export_standalone(&dest).unwrap();
+        assert_eq!(summary.conversations, 1);
+        assert_eq!(summary.messages, 2);
+
+        let out = Connection::open(&dest).unwrap();
+        let journal: String = out
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal, "delete");
+
+        let (title, role, text): (String, String, String) = out
+            .query_row(
+                "SELECT title, role, text FROM v_messages WHERE position = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "Refactor");
+        assert_eq!(role, "assistant");
+        assert_eq!(text.len(), dump.len());
+    }
+
+    #[test]
+    fn test_migrates_inline_text_to_blobs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let dump = "x".repeat(BLOB_MIN_BYTES);
+        {
+            // Pre-blob schema with data already stored inline
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE conversations (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     composer_id TEXT UNIQUE NOT NULL, workspace_id INTEGER,
+                     title TEXT NOT NULL DEFAULT '', model_name TEXT NOT NULL DEFAULT '',
+                     max_mode INTEGER NOT NULL DEFAULT 0, unified_mode TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                     content_hash TEXT);
+                 CREATE TABLE bubbles (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     bubble_id TEXT UNIQUE NOT NULL, conversation_id INTEGER NOT NULL,
+                     bubble_type INTEGER NOT NULL, text TEXT NOT NULL DEFAULT '',
+                     created_at TEXT, thinking_text TEXT, thinking_signature TEXT,
+                     thinking_duration_ms INTEGER, input_tokens INTEGER NOT NULL DEFAULT 0,
+                     output_tokens INTEGER NOT NULL DEFAULT 0,
+                     is_agentic INTEGER NOT NULL DEFAULT 0, workspace_uri TEXT,
+                     workspace_project_dir TEXT);
+                 INSERT INTO conversations (composer_id) VALUES ('c1');",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO bubbles (bubble_id, conversation_id, bubble_type, text)
+                 VALUES ('b1', 1, 2, ?1)",
+                [&dump],
+            )
+            .unwrap();
+        }
+
+        let storage = LocalStorage::open(&path).unwrap();
+        let inline: String = storage
+            .conn
+            .query_row("SELECT text FROM bubbles", [], |row| row.get(0))
+            .unwrap();
+        assert!(inline.is_empty());
+        assert_eq!(storage.get_bubbles("c1").unwrap()[0].text, dump);
+    }
+
+    #[test]
+    fn test_get_embeddings_by_workspace() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::open(&dir.path().join("test.db")).unwrap();
+        let workspace = storage
+            .get_or_create_workspace(&WorkspaceInfo {
+                name: "api".to_string(),
+                path: Some(PathBuf::from("/src/api")),
+                cursor_path: None,
+            })
+            .unwrap();
+
+        for (id, workspace_id) in [("c1", Some(workspace)), ("c2", None)] {
+            let conv = Conversation {
+                composer_id: id.to_string(),
+                title: id.to_string(),
+                model_config: ModelConfig::default(),
+                unified_mode: String::new(),
+                created_at: None,
+                bubbles: Vec::new(),
+                git: None,
+                workspace: None,
+            };
+            storage
+                .upsert_conversation(&conv, workspace_id, None)
+                .unwrap();
+            storage.upsert_embedding(id, "m", &[1.0], None).unwrap();
+        }
+
+        assert_eq!(storage.get_embeddings("m", None, None).unwrap().len(), 2);
+        let scoped = storage.get_embeddings("m", Some("API"), None).unwrap();
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].composer_id, "c1");
+        assert!(storage
+            .get_embeddings("m", Some("web"), None)
+            .unwrap()
+            .is_empty());
+
+        storage.add_tag("c2", "rust").unwrap();
+        let tagged = storage.get_embeddings("m", None, Some("rust")).unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].composer_id, "c2");
+        assert!(storage
+            .get_embeddings("m", Some("api"), Some("rust"))
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_sync_state_roundtrip() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.db");
         let storage = LocalStorage::open(&db_path).unwrap();
 
-        let state = SyncState::default()
-            .with_sync_time()
-            .completed();
+        let state = SyncState::default().with_sync_time().completed();
 
         storage.update_sync_state(&state).unwrap();
         let loaded = storage.get_sync_state().unwrap();
@@ -530,4 +3490,3 @@ mod tests {
         assert!(loaded.last_sync.is_some());
     }
 }
-
@@ -2,18 +2,82 @@
 //!
 //! This layer handles all I/O operations and external dependencies.
 
+pub mod clipboard;
 pub mod config;
+pub mod conversation_cache;
+pub mod cron;
+pub mod cursor_launcher;
 pub mod cursor_paths;
 pub mod cursor_reset;
 pub mod cursor_writer;
+pub mod disk;
+pub mod embeddings;
+pub mod encrypt;
+pub mod event_stream;
+pub mod formatter_plugin;
+pub mod git;
+pub mod hooks;
+pub mod launchd;
+pub mod llm_client;
 pub mod local_storage;
+pub mod notify;
+pub mod pid_file;
+pub mod quick_menu;
+pub mod read_only;
+#[cfg(feature = "local-model")]
+pub mod sentence_embedder;
+pub mod service_manager;
 pub mod sqlite_reader;
+pub mod state_watcher;
+pub mod storage_query;
+pub mod sync_lock;
 pub mod systemd;
+pub mod timings;
+pub mod workspace_storage;
 
-pub use config::{ensure_config_exists, load_config, save_config};
-pub use cursor_paths::{find_cursor_config_dir, find_state_databases};
-pub use cursor_reset::{CursorReset, ResetResult};
+pub use clipboard::copy_to_clipboard;
+pub use config::{
+    active_profile, config_file_path, edit_config, ensure_config_exists, get_config_value,
+    load_base_config, load_config, save_config, save_config_to_file, select_profile,
+    set_config_value, set_max_memory_mb, set_path_map_file, validate_config, PROFILE_ENV,
+};
+pub use conversation_cache::{CachedConversation, ConversationCache};
+pub use cron::CronService;
+pub use cursor_launcher::focus_cursor;
+pub use cursor_paths::{
+    cursor_channel, detect_cursor_version, detected_channels, find_cursor_config_dir,
+    find_state_databases, global_state_db_path, is_newer_than_known, set_cursor_channel,
+    set_cursor_config_dir, NEWEST_KNOWN_CURSOR_VERSION,
+};
+pub use cursor_reset::{is_cursor_running, CursorReset, ResetResult, ResetSteps};
 pub use cursor_writer::CursorWriter;
-pub use local_storage::LocalStorage;
-pub use sqlite_reader::StateDbReader;
-pub use systemd::{InstallResult, ServiceStatus, SystemdService};
+pub use disk::available_space;
+pub use embeddings::{create_embedder, Embedder};
+pub use encrypt::{EncryptedWriter, Recipient};
+pub use event_stream::{generate_token, EventStream};
+pub use formatter_plugin::run_formatter_plugin;
+pub use git::{commits_between, detect_git};
+pub use hooks::fire_hook;
+pub use launchd::LaunchdService;
+pub use llm_client::LlmClient;
+pub use local_storage::{
+    ArchivedConversation, ConversationActivity, ConversationSize, HealthReport, LocalStorage,
+    MessageHit, StandaloneExport, StoredEmbedding, StoredSummary, TableStats,
+};
+pub use notify::{send_notification, FailureNotifier};
+pub use pid_file::{read_pid, PidFile};
+pub use quick_menu::{load_quick_menu, save_quick_menu};
+pub use read_only::{enable_read_only, ensure_writable, is_read_only};
+pub use service_manager::{
+    installed_service_instances, service_manager, service_manager_for, InstallResult,
+    ServiceManager, ServiceMode, ServiceStatus,
+};
+pub use sqlite_reader::{
+    inspect_state_db, state_db_fingerprint, KeyFootprint, RawKvEntry, StateDbReader,
+};
+pub use state_watcher::StateDbWatcher;
+pub use storage_query::{query_storage, QueryResult};
+pub use sync_lock::SyncLock;
+pub use systemd::SystemdService;
+pub use timings::{stage_timings, StageTiming, TimedWriter, TimingLayer, TIMINGS_TARGET};
+pub use workspace_storage::{composer_workspaces, workspace_of_database};
@@ -0,0 +1,104 @@
+//! Desktop notifications.
+//!
+//! Sends best-effort notifications through `notify-send` on Linux
+//! and `osascript` on macOS.
+
+use std::collections::HashSet;
+use std::process::Command;
+
+use crate::domain::{AppError, Result};
+
+/// Application name shown in notifications.
+const APP_NAME: &str = "Cursor Chat Handler";
+
+/// Send a desktop notification.
+///
+/// # Errors
+/// Returns error if the notification command is missing or fails.
+pub fn send_notification(summary: &str, body: &str) -> Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(summary)
+        );
+        Command::new("osascript").args(["-e", &script]).status()
+    } else {
+        Command::new("notify-send")
+            .args(["--app-name", APP_NAME, summary, body])
+            .status()
+    }
+    .map_err(|e| AppError::io("Failed to send desktop notification", e))?;
+
+    if !status.success() {
+        return Err(AppError::Config {
+            message: "Desktop notification command failed".into(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Notifies about a daemon failure once, not on every cycle it persists.
+#[derive(Debug, Default)]
+pub struct FailureNotifier {
+    enabled: bool,
+    failing: HashSet<&'static str>,
+}
+
+impl FailureNotifier {
+    /// Notifier that only tracks failures unless `enabled`.
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            failing: HashSet::new(),
+        }
+    }
+
+    /// Report a failure of `kind`, notifying if it was not failing already.
+    ///
+    /// Returns whether this is a new failure.
+    pub fn failed(&mut self, kind: &'static str, summary: &str, body: &str) -> bool {
+        if !self.failing.insert(kind) {
+            return false;
+        }
+        if self.enabled {
+            if let Err(e) = send_notification(summary, body) {
+                tracing::warn!(error = %e, "Failed to send notification");
+            }
+        }
+        true
+    }
+
+    /// Mark `kind` as working again, so its next failure notifies.
+    pub fn recovered(&mut self, kind: &'static str) {
+        self.failing.remove(kind);
+    }
+}
+
+/// Quote a string for use in an `AppleScript` expression.
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(applescript_string(r#"say "hi""#), r#""say \"hi\"""#);
+    }
+
+    #[test]
+    fn test_failure_notifier_reports_each_failure_once() {
+        let mut notifier = FailureNotifier::new(false);
+        assert!(notifier.failed("sync", "Sync failed", "locked"));
+        assert!(!notifier.failed("sync", "Sync failed", "locked"));
+        assert!(notifier.failed("restore", "Restore failed", "locked"));
+
+        notifier.recovered("sync");
+        assert!(notifier.failed("sync", "Sync failed", "locked"));
+    }
+}
@@ -0,0 +1,68 @@
+//! PID file of a running daemon (`daemon --pid-file`).
+//!
+//! The cron service finds the daemon through `sync.pid`, whether `sync
+//! start` launched it or cron's `@reboot` entry did, so the daemon records
+//! its own PID there and removes it when it exits.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::{AppError, Result};
+
+/// This process's PID, written to a file and removed on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write this process's PID to `path`, replacing what it held.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be written.
+    pub fn create(path: &Path) -> Result<Self> {
+        fs::write(path, format!("{}\n", std::process::id()))
+            .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Left alone once another daemon has taken it over
+        let ours = read_pid(&self.path) == Some(std::process::id());
+        if ours {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// PID recorded in `path`, if it holds one.
+#[must_use]
+pub fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sync.pid");
+
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        drop(pid_file);
+        assert!(!path.exists());
+
+        // Another daemon's PID is kept
+        let pid_file = PidFile::create(&path).unwrap();
+        fs::write(&path, "1\n").unwrap();
+        drop(pid_file);
+        assert_eq!(read_pid(&path), Some(1));
+    }
+}
@@ -0,0 +1,67 @@
+//! Persistence of the quick menu numbering.
+//!
+//! `quick` saves the order it printed so that `open N` keeps pointing at the
+//! same conversation even after new chats appear.
+
+use std::fs;
+use std::path::Path;
+
+use crate::domain::{AppError, QuickMenu, Result};
+
+/// Save the numbering of a quick listing.
+///
+/// # Errors
+/// Returns error if the file cannot be written.
+pub fn save_quick_menu(path: &Path, menu: &QuickMenu) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create data directory", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(menu).map_err(AppError::json_parse)?;
+
+    // Write then rename, so a concurrent `open` never reads a partial file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| AppError::io("Failed to write quick menu", e))?;
+    fs::rename(&tmp, path).map_err(|e| AppError::io("Failed to save quick menu", e))?;
+
+    Ok(())
+}
+
+/// Load the numbering of the last quick listing, if any.
+///
+/// # Errors
+/// Returns error if the file exists but cannot be read or parsed.
+pub fn load_quick_menu(path: &Path) -> Result<Option<QuickMenu>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(path).map_err(|e| AppError::io("Failed to read quick menu", e))?;
+    let menu = serde_json::from_str(&content).map_err(AppError::json_parse)?;
+
+    Ok(Some(menu))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_quick_menu_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("quick_menu.json");
+        assert!(load_quick_menu(&path).unwrap().is_none());
+
+        let menu = QuickMenu::new(vec!["aaa".into(), "bbb".into()]);
+        save_quick_menu(&path, &menu).unwrap();
+
+        let loaded = load_quick_menu(&path).unwrap().unwrap();
+        assert_eq!(loaded.get(2), Some("bbb"));
+        assert_eq!(loaded.get(0), None);
+        assert_eq!(loaded.get(3), None);
+        assert_eq!(loaded.generated_at, menu.generated_at);
+    }
+}
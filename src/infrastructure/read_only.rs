@@ -0,0 +1,82 @@
+//! Read-only guard (`--read-only` / `safety.read_only`).
+//!
+//! Every path that writes to Cursor's database or changes the system calls
+//! [`ensure_writable`] first, so enabling the guard once at startup covers
+//! all commands.
+
+#[cfg(not(test))]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::domain::{AppError, Result};
+
+#[cfg(not(test))]
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// Per thread in tests, so a test turning it on leaves the others alone
+#[cfg(test)]
+thread_local! {
+    static READ_ONLY: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Turn on read-only mode for the rest of the process.
+pub fn enable_read_only() {
+    #[cfg(not(test))]
+    READ_ONLY.store(true, Ordering::Relaxed);
+    #[cfg(test)]
+    READ_ONLY.set(true);
+}
+
+/// Whether read-only mode is on.
+#[must_use]
+pub fn is_read_only() -> bool {
+    #[cfg(not(test))]
+    return READ_ONLY.load(Ordering::Relaxed);
+    #[cfg(test)]
+    READ_ONLY.get()
+}
+
+/// Fail with [`AppError::ReadOnly`] if read-only mode is on.
+///
+/// `action` completes "refusing to ...", e.g. `write to Cursor's database`.
+///
+/// # Errors
+/// Returns error in read-only mode.
+pub fn ensure_writable(action: &str) -> Result<()> {
+    if is_read_only() {
+        return Err(AppError::ReadOnly {
+            action: action.to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::{CursorWriter, StateDbReader};
+
+    #[test]
+    fn test_read_only_refuses_writes_but_not_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("state.vscdb");
+        CursorWriter::open(&db_path).unwrap();
+        assert!(ensure_writable("write").is_ok());
+
+        enable_read_only();
+        assert!(is_read_only());
+        assert!(matches!(
+            ensure_writable("write to Cursor's database"),
+            Err(AppError::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            CursorWriter::open(&db_path),
+            Err(AppError::ReadOnly { .. })
+        ));
+        // Nothing is created either
+        assert!(CursorWriter::open(&dir.path().join("new.vscdb")).is_err());
+        assert!(!dir.path().join("new.vscdb").exists());
+
+        let reader = StateDbReader::open(&db_path).unwrap();
+        assert_eq!(reader.count_composers().unwrap(), 0);
+    }
+}
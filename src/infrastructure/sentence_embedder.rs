@@ -0,0 +1,222 @@
+//! Sentence-transformer embeddings computed in-process (`local-model`
+//! feature).
+//!
+//! Loads a BERT-family model exported the Hugging Face way from a local
+//! directory: `config.json`, `tokenizer.json` and `model.safetensors`, as
+//! shipped by `sentence-transformers/all-MiniLM-L6-v2`. Nothing is
+//! downloaded and no chat text leaves the machine. Vectors are the
+//! attention-masked mean of the last hidden states, L2-normalized, as
+//! sentence-transformers computes them.
+
+use std::path::{Path, PathBuf};
+
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+use crate::domain::{AppError, Result};
+
+use super::embeddings::Embedder;
+
+/// Tokens per text; longer texts are truncated, as sentence-transformers does.
+const MAX_TOKENS: usize = 256;
+
+/// Texts run through the model at once.
+const BATCH_SIZE: usize = 16;
+
+/// A BERT sentence-transformer run on the CPU.
+pub struct SentenceEmbedder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    model_id: String,
+}
+
+impl SentenceEmbedder {
+    /// Load the model in `dir`.
+    ///
+    /// # Errors
+    /// Returns error if a model file is missing or cannot be loaded.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let file = |name: &str| -> Result<PathBuf> {
+            let path = dir.join(name);
+            if path.is_file() {
+                Ok(path)
+            } else {
+                Err(AppError::Config {
+                    message: format!(
+                        "Embedding model file {} not found (embeddings.model_dir)",
+                        path.display()
+                    ),
+                })
+            }
+        };
+
+        let config = std::fs::read_to_string(file("config.json")?)
+            .map_err(|e| AppError::io("Failed to read model config.json", e))?;
+        let config: Config = serde_json::from_str(&config).map_err(AppError::json_parse)?;
+
+        let mut tokenizer = Tokenizer::from_file(file("tokenizer.json")?).map_err(model_error)?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS.min(config.max_position_embeddings),
+                ..TruncationParams::default()
+            }))
+            .map_err(model_error)?;
+
+        let weights = [file("model.safetensors")?];
+        // Memory-mapped: the file must not change while the model is loaded
+        #[allow(unsafe_code)]
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&weights, DTYPE, &Device::Cpu) }
+            .map_err(model_error)?;
+        let model = BertModel::load(vb, &config).map_err(model_error)?;
+
+        let name = dir.file_name().map_or_else(
+            || dir.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Ok(Self {
+            model,
+            tokenizer,
+            model_id: format!("model:{name}"),
+        })
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(model_error)?;
+        let rows = |ids: fn(&tokenizers::Encoding) -> &[u32]| {
+            let rows = encodings
+                .iter()
+                .map(|encoding| Tensor::new(ids(encoding), &Device::Cpu))
+                .collect::<candle_core::Result<Vec<_>>>()?;
+            Tensor::stack(&rows, 0)
+        };
+
+        let input_ids = rows(tokenizers::Encoding::get_ids).map_err(model_error)?;
+        let type_ids = rows(tokenizers::Encoding::get_type_ids).map_err(model_error)?;
+        let mask = rows(tokenizers::Encoding::get_attention_mask).map_err(model_error)?;
+
+        let pooled = (|| {
+            let hidden = self.model.forward(&input_ids, &type_ids, Some(&mask))?;
+            // Mean over the real tokens only, padding masked out
+            let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+            let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+            let mean = summed.broadcast_div(&mask.sum(1)?)?;
+            let norm = mean.sqr()?.sum_keepdim(1)?.sqrt()?;
+            mean.broadcast_div(&norm)?.to_vec2::<f32>()
+        })()
+        .map_err(model_error)?;
+        Ok(pooled)
+    }
+}
+
+impl Embedder for SentenceEmbedder {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH_SIZE) {
+            vectors.extend(self.embed_batch(batch)?);
+        }
+        Ok(vectors)
+    }
+}
+
+fn model_error(err: impl std::fmt::Display) -> AppError {
+    AppError::Embedding {
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use candle_nn::VarMap;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+    use super::*;
+
+    const WORDS: &[&str] = &[
+        "[PAD]", "[UNK]", "sqlite", "database", "locked", "css", "button",
+    ];
+
+    /// A tiny randomly initialized BERT, saved the way `model_dir` expects.
+    fn write_tiny_model(dir: &Path) {
+        let config = serde_json::json!({
+            "vocab_size": WORDS.len(),
+            "hidden_size": 8,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": 16,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": 32,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0,
+            "classifier_dropout": null,
+            "model_type": "bert",
+        });
+        std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+
+        let config: Config = serde_json::from_value(config).unwrap();
+        let vars = VarMap::new();
+        let vb = VarBuilder::from_varmap(&vars, DTYPE, &Device::Cpu);
+        BertModel::load(vb, &config).unwrap();
+        vars.save(dir.join("model.safetensors")).unwrap();
+
+        let vocab: HashMap<String, u32> = WORDS
+            .iter()
+            .zip(0..)
+            .map(|(word, id)| ((*word).to_string(), id))
+            .collect();
+        let model = WordLevel::builder()
+            .vocab(vocab.into_iter().collect())
+            .unk_token("[UNK]".into())
+            .build()
+            .unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer.save(dir.join("tokenizer.json"), false).unwrap();
+    }
+
+    #[test]
+    fn test_sentence_embedder() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(SentenceEmbedder::load(dir.path()).is_err());
+
+        let model_dir = dir.path().join("tiny-bert");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        write_tiny_model(&model_dir);
+        let embedder = SentenceEmbedder::load(&model_dir).unwrap();
+        assert_eq!(embedder.model_id(), "model:tiny-bert");
+
+        // Padding must not change a text's vector
+        let texts = vec![
+            "sqlite database locked".to_string(),
+            "css".to_string(),
+            "css".to_string(),
+        ];
+        let vectors = embedder.embed(&texts).unwrap();
+        let alone = embedder.embed(&texts[1..2]).unwrap();
+        assert_eq!(vectors.len(), 3);
+        for vector in &vectors {
+            assert_eq!(vector.len(), 8);
+            let norm: f32 = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+        for (a, b) in vectors[1].iter().zip(&alone[0]) {
+            assert!((a - b).abs() < 1e-4);
+        }
+        assert_eq!(vectors[1], vectors[2]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Platform-neutral control of the sync service.
+//!
+//! `sync start`, `stop`, `status`, `logs` and `uninstall` go through
+//! [`ServiceManager`], implemented by systemd user units on Linux, by a
+//! launchd agent on macOS and by a crontab entry where systemd is not
+//! running. [`service_manager`] picks the one for this system.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::{active_profile, CronService, LaunchdService, SystemdService};
+
+/// How the sync service runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ServiceMode {
+    /// Long-running daemon syncing every interval.
+    #[default]
+    Daemon,
+    /// Scheduled job running one sync cycle every interval.
+    Timer,
+}
+
+impl std::str::FromStr for ServiceMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "daemon" => Ok(Self::Daemon),
+            "timer" => Ok(Self::Timer),
+            _ => Err(format!("Unknown mode: {s}. Use: daemon, timer")),
+        }
+    }
+}
+
+/// Installs and controls the sync service of one profile.
+pub trait ServiceManager {
+    /// Name of the service, e.g. `cursor-chat-sync@work.service`.
+    fn service_name(&self) -> String;
+
+    /// Write the service definition for `mode`.
+    ///
+    /// # Errors
+    /// Returns error if the definition cannot be written or loaded.
+    fn install(&self, mode: ServiceMode) -> Result<InstallResult>;
+
+    /// Enable the service at login and start it.
+    ///
+    /// # Errors
+    /// Returns error if the service manager refuses.
+    fn enable_and_start(&self) -> Result<()>;
+
+    /// Stop the service and keep it from starting at login.
+    ///
+    /// # Errors
+    /// Returns error if the service manager cannot be reached.
+    fn stop_and_disable(&self) -> Result<()>;
+
+    /// Whether the service is installed, enabled and running.
+    ///
+    /// # Errors
+    /// Returns error if the service manager cannot be queried.
+    fn get_status(&self) -> Result<ServiceStatus>;
+
+    /// Stop the service and remove its definition.
+    ///
+    /// # Errors
+    /// Returns error if the definition cannot be removed.
+    fn uninstall(&self) -> Result<()>;
+
+    /// The last `lines` lines the service logged.
+    ///
+    /// # Errors
+    /// Returns error if the logs cannot be read.
+    fn view_logs(&self, lines: usize) -> Result<String>;
+}
+
+/// Service manager of this system for the selected profile.
+#[must_use]
+pub fn service_manager(config: AppConfig) -> Box<dyn ServiceManager> {
+    service_manager_for(config, active_profile().map(str::to_string))
+}
+
+/// Service manager of this system for profile `instance` (`None`: the
+/// default service) instead of the selected profile's.
+#[must_use]
+pub fn service_manager_for(config: AppConfig, instance: Option<String>) -> Box<dyn ServiceManager> {
+    if cfg!(target_os = "macos") {
+        Box::new(LaunchdService::new(config).with_instance(instance))
+    } else if systemd_running() {
+        Box::new(SystemdService::new(config).with_instance(instance))
+    } else {
+        Box::new(CronService::new(config).with_instance(instance))
+    }
+}
+
+/// Profiles with a service installed on this system.
+///
+/// # Errors
+/// Returns error if the service directory or crontab cannot be read.
+pub fn installed_service_instances() -> Result<Vec<String>> {
+    if cfg!(target_os = "macos") {
+        LaunchdService::installed_instances()
+    } else if systemd_running() {
+        SystemdService::installed_instances()
+    } else {
+        CronService::installed_instances()
+    }
+}
+
+/// Whether the system was booted with systemd (the check of `sd_booted`).
+fn systemd_running() -> bool {
+    Path::new("/run/systemd/system").is_dir()
+}
+
+/// File the launchd and cron services append their output to.
+pub(crate) fn service_log_path(config: &AppConfig) -> PathBuf {
+    config.data_dir().join("sync.log")
+}
+
+/// The last `lines` lines of the log at `path`, empty if there is none.
+pub(crate) fn tail_lines(path: &Path, lines: usize) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+
+    let log = fs::read_to_string(path).map_err(|e| AppError::io("Failed to read sync log", e))?;
+    let skip = log.lines().count().saturating_sub(lines);
+    Ok(log.lines().skip(skip).collect::<Vec<_>>().join("\n"))
+}
+
+/// Whether `name` can name a service instance.
+pub(crate) fn is_valid_instance_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Result of installing the service.
+#[derive(Debug)]
+pub struct InstallResult {
+    /// Path where service file was written (`crontab` for cron entries).
+    pub service_path: PathBuf,
+    /// Path where the timer file was written (systemd timer mode).
+    pub timer_path: Option<PathBuf>,
+    /// Content of the service file.
+    pub unit_content: String,
+}
+
+/// Service status information.
+#[derive(Debug, Clone)]
+pub struct ServiceStatus {
+    /// Whether the service file is installed.
+    pub is_installed: bool,
+    /// Whether it runs as a daemon or on a schedule.
+    pub mode: ServiceMode,
+    /// Whether the service is enabled to start on login.
+    pub is_enabled: bool,
+    /// Whether the service is currently running.
+    pub is_running: bool,
+    /// Full status text from the service manager.
+    pub status_text: String,
+}
+
+impl ServiceStatus {
+    /// Get a short status string.
+    #[must_use]
+    pub const fn short_status(&self) -> &'static str {
+        match (self.is_installed, self.is_enabled, self.is_running) {
+            (false, _, _) => "not installed",
+            (true, false, false) => "installed, disabled",
+            (true, true, false) => "enabled, stopped",
+            (true, false, true) => "running (not enabled)",
+            (true, true, true) => "running",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_status_short() {
+        let status = ServiceStatus {
+            is_installed: true,
+            mode: ServiceMode::Daemon,
+            is_enabled: true,
+            is_running: true,
+            status_text: String::new(),
+        };
+        assert_eq!(status.short_status(), "running");
+
+        let status = ServiceStatus {
+            is_installed: false,
+            mode: ServiceMode::Daemon,
+            is_enabled: false,
+            is_running: false,
+            status_text: String::new(),
+        };
+        assert_eq!(status.short_status(), "not installed");
+    }
+}
@@ -1,18 +1,31 @@
 //! `SQLite` database reader for Cursor's state.vscdb files.
 //!
-//! Extracts chat data from the `cursorDiskKV` table.
+//! Extracts chat data from the `cursorDiskKV` table, or from `ItemTable` in
+//! Cursor builds that keep it there.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use rusqlite::types::ValueRef;
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
 
 use crate::domain::{AppError, Result};
 
+use super::timings::TIMINGS_TARGET;
+
 /// Key prefixes used in Cursor's KV store.
 const BUBBLE_PREFIX: &str = "bubbleId:";
 const COMPOSER_PREFIX: &str = "composerData:";
 
+/// How long reads wait while Cursor holds a write lock on its database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tables chat keys may be stored in, in lookup order.
+const CHAT_TABLES: [&str; 2] = ["cursorDiskKV", "ItemTable"];
+
 /// Raw key-value pair from the database.
 #[derive(Debug)]
 pub struct RawKvEntry {
@@ -20,9 +33,126 @@ pub struct RawKvEntry {
     pub value: Vec<u8>,
 }
 
+/// Keys and bytes a conversation takes up in a state database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct KeyFootprint {
+    /// Number of keys (composer entry, bubbles, checkpoints, ...).
+    pub keys: usize,
+    /// Total size of their values.
+    pub bytes: u64,
+}
+
+/// File-level details about a Cursor state database.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateDbInfo {
+    /// Database path.
+    pub path: PathBuf,
+    /// Whether this is the global database (vs. a workspace one).
+    pub is_global: bool,
+    /// Size of the database file plus its WAL.
+    pub size_bytes: u64,
+    /// Last modification time of the database or its WAL.
+    pub modified: Option<DateTime<Utc>>,
+    /// A non-empty WAL exists while Cursor is running, so Cursor holds it open.
+    pub wal_locked: bool,
+    /// Number of composer keys, if the database could be read.
+    pub composer_count: Option<usize>,
+}
+
+/// Collect size, timestamps, lock state and composer count for a state database.
+#[must_use]
+pub fn inspect_state_db(path: &Path, cursor_running: bool) -> StateDbInfo {
+    let mut wal_path = path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    let db_meta = std::fs::metadata(path).ok();
+    let wal_meta = std::fs::metadata(PathBuf::from(wal_path)).ok();
+
+    let size_bytes = [&db_meta, &wal_meta]
+        .iter()
+        .filter_map(|m| m.as_ref().map(std::fs::Metadata::len))
+        .sum();
+    let modified = [&db_meta, &wal_meta]
+        .iter()
+        .filter_map(|m| m.as_ref()?.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from);
+    let wal_locked = cursor_running && wal_meta.is_some_and(|m| m.len() > 0);
+
+    let composer_count = StateDbReader::open(path)
+        .and_then(|reader| {
+            if reader.has_kv_table()? {
+                reader.count_composers().map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .unwrap_or_else(|e| {
+            tracing::debug!(path = %path.display(), error = %e, "Could not count composers");
+            None
+        });
+
+    StateDbInfo {
+        path: path.to_path_buf(),
+        is_global: path.to_string_lossy().contains("globalStorage"),
+        size_bytes,
+        modified,
+        wal_locked,
+        composer_count,
+    }
+}
+
+/// Cheap signature of a state database's content, for skipping unchanged syncs.
+///
+/// Combines the key count and highest rowid of `cursorDiskKV` with the size
+/// and modification time of the database and its WAL. `PRAGMA data_version`
+/// would be cheaper but is only comparable within one connection, and every
+/// sync opens a new one. A checkpoint may change the signature without any
+/// new data; that only costs one extra sync.
+///
+/// # Errors
+/// Returns error if the database cannot be read.
+pub fn state_db_fingerprint(path: &Path) -> Result<String> {
+    let reader = StateDbReader::open(path)?;
+    let (keys, max_rowid): (i64, i64) = reader
+        .conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM {}",
+                reader.table
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(AppError::database)?;
+
+    let mut wal_path = path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    let file_stamp = |path: &Path| {
+        std::fs::metadata(path).map_or_else(
+            |_| "-".to_string(),
+            |meta| {
+                let modified = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .unwrap_or_default();
+                format!("{}@{}", meta.len(), modified.as_nanos())
+            },
+        )
+    };
+
+    Ok(format!(
+        "{keys}:{max_rowid}:{}:{}",
+        file_stamp(path),
+        file_stamp(Path::new(&wal_path))
+    ))
+}
+
 /// `SQLite` reader for Cursor state databases.
 pub struct StateDbReader {
     conn: Connection,
+    /// Table holding the chat keys.
+    table: &'static str,
 }
 
 impl StateDbReader {
@@ -34,6 +164,8 @@ impl StateDbReader {
         let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
 
         let conn = Connection::open_with_flags(path, flags).map_err(AppError::database)?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .map_err(AppError::database)?;
 
         // Optimize for read-only access
         conn.execute_batch(
@@ -42,7 +174,14 @@ impl StateDbReader {
         )
         .map_err(AppError::database)?;
 
-        Ok(Self { conn })
+        let table = discover_chat_table(&conn)?;
+        Ok(Self { conn, table })
+    }
+
+    /// Table the chat keys are read from.
+    #[must_use]
+    pub const fn chat_table(&self) -> &'static str {
+        self.table
     }
 
     /// Fetches all bubble entries from the database.
@@ -61,16 +200,254 @@ impl StateDbReader {
         self.fetch_by_prefix(COMPOSER_PREFIX)
     }
 
+    /// Whether the database has the key-value table chats are stored in.
+    ///
+    /// # Errors
+    /// Returns error if the schema cannot be queried.
+    pub fn has_kv_table(&self) -> Result<bool> {
+        table_exists(&self.conn, self.table)
+    }
+
+    /// Counts composer (conversation) entries without loading them.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn count_composers(&self) -> Result<usize> {
+        self.conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE key LIKE ?1", self.table),
+                [format!("{COMPOSER_PREFIX}%")],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)
+    }
+
+    /// Lists composer (conversation) IDs without loading their data.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn composer_ids(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT substr(key, ?1) FROM {} WHERE key LIKE ?2",
+                self.table
+            ))
+            .map_err(AppError::database)?;
+
+        let ids = stmt
+            .query_map(
+                rusqlite::params![COMPOSER_PREFIX.len() + 1, format!("{COMPOSER_PREFIX}%")],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .map_err(AppError::database)?;
+
+        Ok(ids)
+    }
+
+    /// Composer IDs containing `partial_id`, in key order.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn find_composer_ids(&self, partial_id: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT substr(key, ?1) FROM {} WHERE key LIKE ?2 AND instr(substr(key, ?1), ?3) > 0
+                 ORDER BY key",
+                self.table
+            ))
+            .map_err(AppError::database)?;
+
+        let ids = stmt
+            .query_map(
+                rusqlite::params![
+                    COMPOSER_PREFIX.len() + 1,
+                    format!("{COMPOSER_PREFIX}%"),
+                    partial_id
+                ],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .map_err(AppError::database)?;
+
+        Ok(ids)
+    }
+
+    /// Keys and bytes taken up by each conversation: its composer entry and
+    /// every `<kind>:<id>:...` key, without loading the values.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn conversation_footprints(&self) -> Result<HashMap<String, KeyFootprint>> {
+        // The conversation ID is the key's second `:`-separated segment
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT CASE WHEN instr(rest, ':') > 0
+                            THEN substr(rest, 1, instr(rest, ':') - 1) ELSE rest END AS id,
+                        COUNT(*), SUM(length(CAST(value AS BLOB)))
+                 FROM (SELECT substr(key, instr(key, ':') + 1) AS rest, value
+                       FROM {} WHERE instr(key, ':') > 0)
+                 GROUP BY id",
+                self.table
+            ))
+            .map_err(AppError::database)?;
+
+        let mut footprints: HashMap<String, KeyFootprint> = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    KeyFootprint {
+                        keys: row.get(1)?,
+                        bytes: row.get::<_, Option<i64>>(2)?.unwrap_or(0).unsigned_abs(),
+                    },
+                ))
+            })
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(AppError::database)?;
+
+        let composers = self.composer_ids()?;
+        footprints.retain(|id, _| composers.contains(id));
+        Ok(footprints)
+    }
+
+    /// Conversation IDs that have at least one bubble, without loading any.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn bubble_conversation_ids(&self) -> Result<Vec<String>> {
+        let start = BUBBLE_PREFIX.len() + 1;
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT DISTINCT substr(key, ?1, instr(substr(key, ?1), ':') - 1)
+                 FROM {} WHERE key LIKE ?2",
+                self.table
+            ))
+            .map_err(AppError::database)?;
+
+        let ids = stmt
+            .query_map(
+                rusqlite::params![start, format!("{BUBBLE_PREFIX}%:%")],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .map_err(AppError::database)?;
+
+        Ok(ids)
+    }
+
+    /// Fetches the bubbles of one conversation.
+    ///
+    /// Uses a key range rather than `LIKE`, so the lookup goes through the
+    /// key index instead of scanning the table.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn fetch_bubbles_for(&self, composer_id: &str) -> Result<Vec<RawKvEntry>> {
+        // ';' sorts right after ':', closing the range of `bubbleId:<id>:*`
+        let from = format!("{BUBBLE_PREFIX}{composer_id}:");
+        let to = format!("{BUBBLE_PREFIX}{composer_id};");
+        self.fetch_where("key >= ?1 AND key < ?2", &[&from, &to])
+    }
+
+    /// Visits every bubble entry in key order, so the bubbles of each
+    /// conversation come one after another, without loading them all.
+    ///
+    /// Stops early when `visit` returns `false`.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn for_each_bubble(&self, mut visit: impl FnMut(RawKvEntry) -> bool) -> Result<()> {
+        let span =
+            tracing::info_span!(target: TIMINGS_TARGET, "db read", rows = tracing::field::Empty);
+        let _reading = span.enter();
+
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT key, value FROM {} WHERE key >= ?1 AND key < ?2 ORDER BY key",
+                self.table
+            ))
+            .map_err(AppError::database)?;
+        // ';' sorts right after ':', closing the range of `bubbleId:*`
+        let to = format!("{};", BUBBLE_PREFIX.trim_end_matches(':'));
+        let mut rows = stmt
+            .query([BUBBLE_PREFIX, to.as_str()])
+            .map_err(AppError::database)?;
+
+        let mut count = 0;
+        while let Some(row) = rows.next().map_err(AppError::database)? {
+            let key: String = row.get(0).map_err(AppError::database)?;
+            let value = match row.get_ref(1).map_err(AppError::database)? {
+                ValueRef::Blob(b) => b.to_vec(),
+                ValueRef::Text(t) => t.to_vec(),
+                _ => Vec::new(),
+            };
+            count += 1;
+            if !visit(RawKvEntry { key, value }) {
+                break;
+            }
+        }
+        span.record("rows", count);
+
+        Ok(())
+    }
+
+    /// Reads one value from the `ItemTable` (VS Code's key-value table),
+    /// `None` if the key or the table is missing.
+    ///
+    /// # Errors
+    /// Returns error if query fails.
+    pub fn item_value(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        if !table_exists(&self.conn, "ItemTable")? {
+            return Ok(None);
+        }
+
+        self.conn
+            .query_row("SELECT value FROM ItemTable WHERE key = ?1", [key], |row| {
+                Ok(match row.get_ref(0)? {
+                    ValueRef::Blob(b) => b.to_vec(),
+                    ValueRef::Text(t) => t.to_vec(),
+                    _ => Vec::new(),
+                })
+            })
+            .optional()
+            .map_err(AppError::database)
+    }
+
     /// Fetches entries matching a key prefix.
     fn fetch_by_prefix(&self, prefix: &str) -> Result<Vec<RawKvEntry>> {
+        let pattern = format!("{prefix}%");
+        let entries = self.fetch_where("key LIKE ?1", &[&pattern])?;
+
+        tracing::debug!("Fetched {} entries with prefix '{}'", entries.len(), prefix);
+
+        Ok(entries)
+    }
+
+    /// Fetches entries whose key matches `condition`.
+    fn fetch_where(&self, condition: &str, params: &[&str]) -> Result<Vec<RawKvEntry>> {
+        let span =
+            tracing::info_span!(target: TIMINGS_TARGET, "db read", rows = tracing::field::Empty);
+        let _reading = span.enter();
+
         let mut stmt = self
             .conn
-            .prepare("SELECT key, value FROM cursorDiskKV WHERE key LIKE ?1")
+            .prepare(&format!(
+                "SELECT key, value FROM {} WHERE {condition}",
+                self.table
+            ))
             .map_err(AppError::database)?;
 
-        let pattern = format!("{prefix}%");
         let rows = stmt
-            .query_map([&pattern], |row| {
+            .query_map(rusqlite::params_from_iter(params), |row| {
                 let key: String = row.get(0)?;
                 // Handle both TEXT and BLOB value types
                 let value = match row.get_ref(1)? {
@@ -91,9 +468,172 @@ impl StateDbReader {
                 }
             }
         }
-
-        tracing::debug!("Fetched {} entries with prefix '{}'", entries.len(), prefix);
+        span.record("rows", entries.len());
 
         Ok(entries)
     }
 }
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(AppError::database)
+}
+
+/// Pick the table holding the chat keys: the first with a composer or
+/// bubble key, else the first that exists (`cursorDiskKV` if neither does).
+fn discover_chat_table(conn: &Connection) -> Result<&'static str> {
+    let mut existing = Vec::new();
+    for table in CHAT_TABLES {
+        if table_exists(conn, table)? {
+            existing.push(table);
+        }
+    }
+
+    for &table in &existing {
+        // Key ranges, so the lookup goes through the key index
+        let has_chats: bool = conn
+            .query_row(
+                &format!(
+                    "SELECT EXISTS(SELECT 1 FROM {table}
+                     WHERE (key >= ?1 AND key < ?2) OR (key >= ?3 AND key < ?4))"
+                ),
+                [COMPOSER_PREFIX, "composerData;", BUBBLE_PREFIX, "bubbleId;"],
+                |row| row.get(0),
+            )
+            .map_err(AppError::database)?;
+        if has_chats {
+            if table != CHAT_TABLES[0] {
+                tracing::debug!(table, "Reading chat data from fallback table");
+            }
+            return Ok(table);
+        }
+    }
+
+    Ok(existing.first().copied().unwrap_or(CHAT_TABLES[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect_state_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("globalStorage").join("state.vscdb");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES ('composerData:a', '{}'), ('bubbleId:a:1', '{}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let info = inspect_state_db(&path, false);
+        assert!(info.is_global);
+        assert!(info.size_bytes > 0);
+        assert!(info.modified.is_some());
+        assert!(!info.wal_locked);
+        assert_eq!(info.composer_count, Some(1));
+    }
+
+    #[test]
+    fn test_fetch_bubbles_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES
+                 ('bubbleId:a:1', '{}'), ('bubbleId:a:2', '{}'),
+                 ('bubbleId:ab:1', '{}'), ('composerData:a', '{}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let reader = StateDbReader::open(&path).unwrap();
+        assert_eq!(reader.fetch_bubbles_for("a").unwrap().len(), 2);
+
+        let mut ids = reader.bubble_conversation_ids().unwrap();
+        ids.sort();
+        assert_eq!(ids, ["a", "ab"]);
+    }
+
+    #[test]
+    fn test_find_composer_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES
+                 ('composerData:abc-12', '{}'), ('composerData:abc-1', '{}'),
+                 ('bubbleId:abc-3:b1', '{}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let reader = StateDbReader::open(&path).unwrap();
+        assert_eq!(
+            reader.find_composer_ids("abc-1").unwrap(),
+            ["abc-1", "abc-12"]
+        );
+        assert_eq!(reader.find_composer_ids("c-12").unwrap(), ["abc-12"]);
+        assert!(reader.find_composer_ids("abc-3").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_conversation_footprints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             INSERT INTO cursorDiskKV VALUES
+                 ('composerData:a', '{}'), ('bubbleId:a:1', '12345'),
+                 ('checkpointId:a:c1', X'0102'), ('bubbleId:orphan:1', '{}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let footprints = StateDbReader::open(&path)
+            .unwrap()
+            .conversation_footprints()
+            .unwrap();
+        assert_eq!(footprints.len(), 1);
+        assert_eq!(footprints["a"], KeyFootprint { keys: 3, bytes: 9 });
+    }
+
+    #[test]
+    fn test_falls_back_to_item_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cursorDiskKV (key TEXT UNIQUE, value BLOB);
+             CREATE TABLE ItemTable (key TEXT UNIQUE, value BLOB);
+             INSERT INTO ItemTable VALUES
+                 ('workbench.panel.chat', '{}'),
+                 ('composerData:a', '{}'), ('bubbleId:a:1', '{}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let reader = StateDbReader::open(&path).unwrap();
+        assert_eq!(reader.chat_table(), "ItemTable");
+        assert!(reader.has_kv_table().unwrap());
+        assert_eq!(reader.composer_ids().unwrap(), ["a"]);
+        assert_eq!(reader.fetch_bubbles_for("a").unwrap().len(), 1);
+        assert!(state_db_fingerprint(&path).unwrap().starts_with("3:3:"));
+    }
+}
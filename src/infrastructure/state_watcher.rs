@@ -0,0 +1,151 @@
+//! Change notifications for Cursor's state databases (`daemon --watch`).
+//!
+//! Watches the `globalStorage` and `workspaceStorage` directories and
+//! reports writes to `state.vscdb` and its WAL file, so the daemon syncs
+//! shortly after Cursor saves a message instead of on a fixed interval.
+//! Writes come in bursts while a reply streams in; they are coalesced until
+//! the database has been quiet for [`SETTLE_DELAY`].
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::domain::{AppError, Result};
+use crate::infrastructure::cursor_paths::{
+    global_state_db_path, workspace_storage_dir, STATE_DB_NAME,
+};
+
+/// Quiet time after the last write before a change is reported.
+pub const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest a change waits for writes to settle, so long agent turns still
+/// get synced while they run.
+const MAX_SETTLE: Duration = Duration::from_secs(30);
+
+/// Watches Cursor's state databases for writes.
+pub struct StateDbWatcher {
+    /// Kept alive for as long as notifications are wanted.
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+    dirs: Vec<PathBuf>,
+}
+
+impl StateDbWatcher {
+    /// Start watching the state databases of the Cursor installation.
+    ///
+    /// # Errors
+    /// Returns error if no storage directory exists or none can be watched.
+    pub fn start() -> Result<Self> {
+        let (sender, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if is_state_db_event(&event) => {
+                    let _ = sender.send(());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "File watch error"),
+            })
+            .map_err(|e| AppError::Config {
+                message: format!("Failed to create file watcher: {e}"),
+            })?;
+
+        let mut targets = Vec::new();
+        if let Some(global_dir) = global_state_db_path()?.parent() {
+            targets.push((global_dir.to_path_buf(), RecursiveMode::NonRecursive));
+        }
+        if let Ok(workspace_dir) = workspace_storage_dir() {
+            targets.push((workspace_dir, RecursiveMode::Recursive));
+        }
+
+        let mut dirs = Vec::new();
+        for (dir, mode) in targets {
+            if !dir.is_dir() {
+                continue;
+            }
+            match watcher.watch(&dir, mode) {
+                Ok(()) => dirs.push(dir),
+                Err(e) => tracing::warn!(dir = %dir.display(), error = %e, "Failed to watch"),
+            }
+        }
+        if dirs.is_empty() {
+            return Err(AppError::Config {
+                message: "No Cursor storage directory to watch".into(),
+            });
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+            dirs,
+        })
+    }
+
+    /// Directories being watched.
+    #[must_use]
+    pub fn watched(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Block until a state database changed and its writes settled, or
+    /// until `timeout` passes. Returns whether a change was seen.
+    #[must_use]
+    pub fn wait_for_change(&self, timeout: Duration) -> bool {
+        match self.changes.recv_timeout(timeout) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => return false,
+            Err(RecvTimeoutError::Disconnected) => {
+                std::thread::sleep(timeout);
+                return false;
+            }
+        }
+
+        let deadline = Instant::now() + MAX_SETTLE;
+        while Instant::now() < deadline {
+            if self.changes.recv_timeout(SETTLE_DELAY).is_err() {
+                break;
+            }
+        }
+        true
+    }
+}
+
+/// Whether `event` wrote to, created or removed a state database or its WAL.
+fn is_state_db_event(event: &Event) -> bool {
+    let writes = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    writes && event.paths.iter().any(|path| is_state_db_file(path))
+}
+
+fn is_state_db_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix(STATE_DB_NAME))
+        .is_some_and(|suffix| suffix.is_empty() || suffix == "-wal")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+
+    #[test]
+    fn test_is_state_db_event() {
+        let write = |path: &str| {
+            Event::new(EventKind::Modify(ModifyKind::Any)).add_path(PathBuf::from(path))
+        };
+        assert!(is_state_db_event(&write("/c/globalStorage/state.vscdb")));
+        assert!(is_state_db_event(&write("/c/ws/abc/state.vscdb-wal")));
+        assert!(!is_state_db_event(&write(
+            "/c/globalStorage/state.vscdb-shm"
+        )));
+        assert!(!is_state_db_event(&write("/c/globalStorage/storage.json")));
+
+        let read = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("/c/globalStorage/state.vscdb"));
+        assert!(!is_state_db_event(&read));
+    }
+}
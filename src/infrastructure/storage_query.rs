@@ -0,0 +1,189 @@
+//! Read-only SQL against local storage (`query`).
+//!
+//! The connection is opened read-only with `query_only` set, so a query can
+//! never modify storage. Besides the internal tables, it sees a stable set
+//! of temporary views with the same columns as the standalone export
+//! (`storage export-db`), so saved queries keep working across schema
+//! migrations:
+//!
+//! - `v_conversations`: `id, title, workspace, model, mode, created_at,
+//!   message_count, input_tokens, output_tokens, reasoning_tokens`
+//! - `v_messages`: `conversation_id, title, workspace, position, role, text,
+//!   thinking, created_at, input_tokens, output_tokens, reasoning_tokens`
+//! - `v_changes`: `composer_id, title, kind, synced_at`
+//! - `v_operations`: `kind, started_at, duration_ms, items, success, details`
+
+use std::path::Path;
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+
+use crate::domain::{AppError, Result};
+
+const VIEWS: &str = r"
+CREATE TEMP VIEW v_conversations AS
+SELECT c.composer_id AS id, COALESCE(t.title, c.title) AS title, w.name AS workspace,
+       c.model_name AS model, c.unified_mode AS mode, c.created_at,
+       COUNT(b.id) AS message_count,
+       COALESCE(SUM(b.input_tokens), 0) AS input_tokens,
+       COALESCE(SUM(b.output_tokens), 0) AS output_tokens,
+       COALESCE(SUM(b.reasoning_tokens), 0) AS reasoning_tokens
+FROM main.conversations c
+LEFT JOIN main.title_overrides t ON t.composer_id = c.composer_id
+LEFT JOIN main.workspaces w ON w.id = c.workspace_id
+LEFT JOIN main.bubbles b ON b.conversation_id = c.id
+GROUP BY c.id;
+
+CREATE TEMP VIEW v_messages AS
+SELECT c.composer_id AS conversation_id, COALESCE(t.title, c.title) AS title,
+       w.name AS workspace,
+       ROW_NUMBER() OVER (PARTITION BY b.conversation_id ORDER BY b.created_at, b.id) - 1
+           AS position,
+       CASE b.bubble_type WHEN 1 THEN 'user' WHEN 2 THEN 'assistant' ELSE 'unknown' END
+           AS role,
+       plain_text(COALESCE(bl.data, b.text)) AS text,
+       plain_text(b.thinking_text) AS thinking,
+       b.created_at, b.input_tokens, b.output_tokens, b.reasoning_tokens
+FROM main.bubbles b
+JOIN main.conversations c ON c.id = b.conversation_id
+LEFT JOIN main.blobs bl ON bl.hash = b.text_hash
+LEFT JOIN main.title_overrides t ON t.composer_id = c.composer_id
+LEFT JOIN main.workspaces w ON w.id = c.workspace_id;
+
+CREATE TEMP VIEW v_changes AS
+SELECT ch.composer_id, COALESCE(t.title, c.title, '') AS title,
+       ch.change_type AS kind, ch.synced_at
+FROM main.changes ch
+LEFT JOIN main.conversations c ON c.composer_id = ch.composer_id
+LEFT JOIN main.title_overrides t ON t.composer_id = ch.composer_id;
+
+CREATE TEMP VIEW v_operations AS
+SELECT kind, started_at, duration_ms, items, success, details FROM main.operations;
+";
+
+/// Columns and rows returned by a query.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryResult {
+    /// Column names, in select order.
+    pub columns: Vec<String>,
+    /// Row values, one per column.
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+impl QueryResult {
+    /// Rows as JSON objects keyed by column name.
+    #[must_use]
+    pub fn to_objects(&self) -> Vec<serde_json::Map<String, serde_json::Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Run a single SQL statement against the storage at `path`, read-only.
+///
+/// # Errors
+/// Returns error if storage does not exist, or the statement is invalid or
+/// tries to write.
+pub fn query_storage(path: &Path, sql: &str) -> Result<QueryResult> {
+    if !path.exists() {
+        return Err(AppError::Config {
+            message: "No local storage found. Run 'cursor-chat sync now' first.".into(),
+        });
+    }
+
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(AppError::database)?;
+
+    conn.create_scalar_function(
+        "plain_text",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            Ok(match ctx.get_raw(0) {
+                ValueRef::Blob(bytes) => zstd::decode_all(bytes)
+                    .ok()
+                    .map(|text| String::from_utf8_lossy(&text).into_owned()),
+                ValueRef::Text(text) => Some(String::from_utf8_lossy(text).into_owned()),
+                _ => None,
+            })
+        },
+    )
+    .map_err(AppError::database)?;
+    conn.execute_batch(VIEWS).map_err(AppError::database)?;
+    conn.execute_batch("PRAGMA query_only = ON;")
+        .map_err(AppError::database)?;
+
+    let mut stmt = conn.prepare(sql).map_err(AppError::database)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+
+    let mut rows = Vec::new();
+    let mut cursor = stmt.query([]).map_err(AppError::database)?;
+    while let Some(row) = cursor.next().map_err(AppError::database)? {
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(json_value))
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(AppError::database)?;
+        rows.push(values);
+    }
+
+    Ok(QueryResult { columns, rows })
+}
+
+fn json_value(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+        ValueRef::Blob(bytes) => format!("<blob {} bytes>", bytes.len()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChangeType;
+    use crate::infrastructure::LocalStorage;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_query_storage_views_and_read_only() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("storage.db");
+        let storage = LocalStorage::open(&path).unwrap();
+        storage
+            .record_change("c1", ChangeType::Created, Utc::now())
+            .unwrap();
+        drop(storage);
+
+        let result = query_storage(&path, "SELECT composer_id, kind FROM v_changes").unwrap();
+        assert_eq!(result.columns, ["composer_id", "kind"]);
+        assert_eq!(
+            result.rows,
+            [vec![serde_json::json!("c1"), serde_json::json!("created")]]
+        );
+        assert_eq!(result.to_objects()[0]["kind"], "created");
+
+        for view in ["v_conversations", "v_messages", "v_changes", "v_operations"] {
+            query_storage(&path, &format!("SELECT * FROM {view}")).unwrap();
+        }
+
+        assert!(query_storage(&path, "DELETE FROM changes").is_err());
+        let remaining = query_storage(&path, "SELECT COUNT(*) FROM changes").unwrap();
+        assert_eq!(remaining.rows[0][0], 1);
+    }
+}
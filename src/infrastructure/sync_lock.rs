@@ -0,0 +1,108 @@
+//! Cross-process lock around writes to local storage.
+//!
+//! The daemon and CLI commands both sync and maintain the same storage.
+//! Holding `sync.lock` for the duration of those operations keeps them
+//! from interleaving; readers never take it and rely on WAL instead.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+
+use crate::domain::{AppError, Result};
+
+/// Exclusive hold on the lock file, released on drop.
+#[derive(Debug)]
+pub struct SyncLock {
+    file: File,
+}
+
+impl SyncLock {
+    /// Take the lock at `path`, waiting for another process to release it.
+    ///
+    /// # Errors
+    /// Returns error if the lock file cannot be opened or locked.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let file = open_lock_file(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                tracing::info!(
+                    lock = %path.display(),
+                    "Another process is syncing, waiting for it to finish"
+                );
+                file.lock()
+                    .map_err(|e| AppError::io("Failed to lock sync.lock", e))?;
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(AppError::io("Failed to lock sync.lock", e));
+            }
+        }
+        Ok(Self::held(file))
+    }
+
+    /// Take the lock at `path` if no other process holds it.
+    ///
+    /// # Errors
+    /// Returns error if the lock file cannot be opened or locked.
+    pub fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        let file = open_lock_file(path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self::held(file))),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(AppError::io("Failed to lock sync.lock", e)),
+        }
+    }
+
+    /// Whether another process holds the lock at `path` right now.
+    #[must_use]
+    pub fn is_held(path: &Path) -> bool {
+        path.exists() && matches!(Self::try_acquire(path), Ok(None))
+    }
+
+    fn held(mut file: File) -> Self {
+        // The PID is informational only; the OS lock is what excludes
+        let _ = file
+            .set_len(0)
+            .and_then(|()| writeln!(file, "{}", std::process::id()));
+        Self { file }
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+fn open_lock_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create data directory", e))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| AppError::io(format!("Failed to open {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_excludes_second_holder_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sync.lock");
+
+        let lock = SyncLock::acquire(&path).unwrap();
+        assert!(SyncLock::try_acquire(&path).unwrap().is_none());
+        assert!(SyncLock::is_held(&path));
+
+        drop(lock);
+        assert!(SyncLock::try_acquire(&path).unwrap().is_some());
+        assert!(!SyncLock::is_held(&path));
+    }
+}
@@ -1,27 +1,88 @@
 //! Systemd service integration.
 //!
 //! Handles generation and installation of systemd user service
-//! for the sync daemon.
+//! for the sync daemon, or of a timer running one sync cycle at a time.
+//!
+//! With a profile selected, the units are named after it
+//! (`cursor-chat-sync@work.service`), so each profile syncs independently.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::domain::{AppConfig, AppError, Result};
+use crate::infrastructure::service_manager::is_valid_instance_name;
+use crate::infrastructure::{
+    active_profile, ensure_writable, InstallResult, ServiceManager, ServiceMode, ServiceStatus,
+    PROFILE_ENV,
+};
 
-/// Service unit file name.
-const SERVICE_NAME: &str = "cursor-chat-sync.service";
+/// Unit name without instance or suffix.
+const UNIT_PREFIX: &str = "cursor-chat-sync";
 
 /// Systemd service manager.
 pub struct SystemdService {
     config: AppConfig,
+    /// Profile the units are an instance for.
+    instance: Option<String>,
 }
 
 impl SystemdService {
-    /// Create a new systemd service manager.
+    /// Create a new systemd service manager for the selected profile.
     #[must_use]
-    pub const fn new(config: AppConfig) -> Self {
-        Self { config }
+    pub fn new(config: AppConfig) -> Self {
+        Self {
+            config,
+            instance: active_profile().map(str::to_string),
+        }
+    }
+
+    /// Manage the instance of profile `instance` (`None`: the default unit)
+    /// instead of the selected profile's.
+    #[must_use]
+    pub fn with_instance(mut self, instance: Option<String>) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    /// Name of this instance's unit of type `suffix`.
+    fn unit_name(&self, suffix: &str) -> String {
+        self.instance.as_ref().map_or_else(
+            || format!("{UNIT_PREFIX}.{suffix}"),
+            |name| format!("{UNIT_PREFIX}@{name}.{suffix}"),
+        )
+    }
+
+    /// Timer unit name (timer mode).
+    fn timer_name(&self) -> String {
+        self.unit_name("timer")
+    }
+
+    /// Profiles with a service instance installed.
+    ///
+    /// # Errors
+    /// Returns error if the systemd user directory cannot be read.
+    pub fn installed_instances() -> Result<Vec<String>> {
+        let dir = Self::user_systemd_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries =
+            fs::read_dir(&dir).map_err(|e| AppError::io("Failed to read systemd directory", e))?;
+        let mut instances: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let instance = name
+                    .strip_prefix(UNIT_PREFIX)?
+                    .strip_prefix('@')?
+                    .strip_suffix(".service")?;
+                (!instance.is_empty()).then(|| instance.to_string())
+            })
+            .collect();
+        instances.sort();
+
+        Ok(instances)
     }
 
     /// Get the systemd user directory path.
@@ -34,34 +95,61 @@ impl SystemdService {
     }
 
     /// Get the service file path.
-    fn service_file_path() -> Result<PathBuf> {
-        Ok(Self::user_systemd_dir()?.join(SERVICE_NAME))
+    fn service_file_path(&self) -> Result<PathBuf> {
+        Ok(Self::user_systemd_dir()?.join(self.service_name()))
     }
 
-    /// Generate the systemd unit file content.
-    fn generate_unit_file(&self) -> Result<String> {
-        // Find the binary path
-        let binary_path = std::env::current_exe()
-            .map_err(|e| AppError::io("Failed to get executable path", e))?;
+    /// Get the timer file path.
+    fn timer_file_path(&self) -> Result<PathBuf> {
+        Ok(Self::user_systemd_dir()?.join(self.timer_name()))
+    }
 
-        let interval_secs = self.config.sync.interval_secs;
+    /// Unit that is enabled and started: the timer if one is installed.
+    fn main_unit(&self) -> Result<String> {
+        Ok(if self.timer_file_path()?.exists() {
+            self.timer_name()
+        } else {
+            self.service_name()
+        })
+    }
+
+    /// Generate the service unit running `binary` in `mode`.
+    fn generate_unit_file(&self, binary: &Path, mode: ServiceMode) -> String {
+        let service = &self.config.service;
+        let (description, exec, restart, install) = match mode {
+            ServiceMode::Daemon => (
+                "Cursor Chat Handler Sync Daemon",
+                format!("daemon --interval {}", self.config.sync.interval_secs),
+                format!("Restart=on-failure\nRestartSec={}\n", service.restart_sec),
+                format!("\n[Install]\nWantedBy={}\n", service.wanted_by),
+            ),
+            // Started by the timer, so no [Install] section
+            ServiceMode::Timer => (
+                "Cursor Chat Handler Sync",
+                "daemon --once".to_string(),
+                String::new(),
+                String::new(),
+            ),
+        };
+        let service_type = match mode {
+            ServiceMode::Daemon => "simple",
+            ServiceMode::Timer => "oneshot",
+        };
 
-        let unit = format!(
-            r#"[Unit]
-Description=Cursor Chat Handler Sync Daemon
+        format!(
+            r"[Unit]
+Description={description}
 Documentation=https://github.com/user/cursor-chat-handler
 After=network.target
 
 [Service]
-Type=simple
-ExecStart={binary} daemon --interval {interval}
-Restart=on-failure
-RestartSec=30
-Environment=RUST_LOG=info
+Type={service_type}
+ExecStart={binary} {exec}
+{restart}Environment=RUST_LOG=info{profile_env}
 
 # Resource limits
-MemoryMax=256M
-CPUQuota=25%
+MemoryMax={memory_max}
+CPUQuota={cpu_quota}{nice}
 
 # Security
 ProtectSystem=strict
@@ -69,34 +157,98 @@ ProtectHome=read-only
 ReadWritePaths={data_dir}
 NoNewPrivileges=yes
 PrivateTmp=yes
+{install}",
+            binary = binary.display(),
+            memory_max = service.memory_max,
+            cpu_quota = service.cpu_quota,
+            nice = service
+                .nice
+                .map(|nice| format!("\nNice={nice}"))
+                .unwrap_or_default(),
+            data_dir = self.config.data_dir().display(),
+            profile_env = self
+                .instance
+                .as_ref()
+                .map(|name| format!("\nEnvironment={PROFILE_ENV}={name}"))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Generate the timer unit starting the service every sync interval.
+    fn generate_timer_file(&self) -> String {
+        format!(
+            r"[Unit]
+Description=Cursor Chat Handler Sync Timer
+
+[Timer]
+OnBootSec=1min
+OnUnitActiveSec={interval}s
+Unit={service}
 
 [Install]
-WantedBy=default.target
-"#,
-            binary = binary_path.display(),
-            interval = interval_secs,
-            data_dir = self.config.data_dir().display(),
-        );
+WantedBy=timers.target
+",
+            interval = self.config.sync.interval_secs,
+            service = self.service_name(),
+        )
+    }
+}
 
-        Ok(unit)
+impl ServiceManager for SystemdService {
+    fn service_name(&self) -> String {
+        self.unit_name("service")
     }
 
-    /// Install the systemd service.
-    pub fn install(&self) -> Result<InstallResult> {
+    fn install(&self, mode: ServiceMode) -> Result<InstallResult> {
+        ensure_writable("install the sync service")?;
+        if let Some(name) = &self.instance {
+            if !is_valid_instance_name(name) {
+                return Err(AppError::Config {
+                    message: format!(
+                        "Profile '{name}' cannot name a service instance (use letters, digits, '-', '_' or '.')"
+                    ),
+                });
+            }
+        }
+
         // Ensure systemd user directory exists
         let systemd_dir = Self::user_systemd_dir()?;
         fs::create_dir_all(&systemd_dir)
             .map_err(|e| AppError::io("Failed to create systemd user directory", e))?;
 
+        // Switching modes: the other mode's units must not keep running
+        if self.main_unit()? == self.timer_name() || mode == ServiceMode::Timer {
+            let _ = self.stop_and_disable();
+        }
+
         // Write service file
-        let service_path = Self::service_file_path()?;
-        let unit_content = self.generate_unit_file()?;
+        let binary_path = std::env::current_exe()
+            .map_err(|e| AppError::io("Failed to get executable path", e))?;
+        let service_path = self.service_file_path()?;
+        let unit_content = self.generate_unit_file(&binary_path, mode);
 
         fs::write(&service_path, &unit_content)
             .map_err(|e| AppError::io("Failed to write service file", e))?;
 
         tracing::info!(path = %service_path.display(), "Service file written");
 
+        let timer_path = self.timer_file_path()?;
+        let timer_path = match mode {
+            ServiceMode::Timer => {
+                fs::write(&timer_path, self.generate_timer_file())
+                    .map_err(|e| AppError::io("Failed to write timer file", e))?;
+                tracing::info!(path = %timer_path.display(), "Timer file written");
+                Some(timer_path)
+            }
+            ServiceMode::Daemon => {
+                if timer_path.exists() {
+                    fs::remove_file(&timer_path)
+                        .map_err(|e| AppError::io("Failed to remove timer file", e))?;
+                }
+                None
+            }
+        };
+
         // Reload systemd daemon
         let reload_status = Command::new("systemctl")
             .args(["--user", "daemon-reload"])
@@ -111,15 +263,18 @@ WantedBy=default.target
 
         Ok(InstallResult {
             service_path,
+            timer_path,
             unit_content,
         })
     }
 
-    /// Enable and start the service.
-    pub fn enable_and_start(&self) -> Result<()> {
+    fn enable_and_start(&self) -> Result<()> {
+        ensure_writable("start the sync service")?;
+        let unit = self.main_unit()?;
+
         // Enable service
         let enable_status = Command::new("systemctl")
-            .args(["--user", "enable", SERVICE_NAME])
+            .args(["--user", "enable", &unit])
             .status()
             .map_err(|e| AppError::io("Failed to enable service", e))?;
 
@@ -131,7 +286,7 @@ WantedBy=default.target
 
         // Start service
         let start_status = Command::new("systemctl")
-            .args(["--user", "start", SERVICE_NAME])
+            .args(["--user", "start", &unit])
             .status()
             .map_err(|e| AppError::io("Failed to start service", e))?;
 
@@ -146,16 +301,18 @@ WantedBy=default.target
         Ok(())
     }
 
-    /// Stop and disable the service.
-    pub fn stop_and_disable(&self) -> Result<()> {
-        // Stop service (ignore errors if not running)
+    fn stop_and_disable(&self) -> Result<()> {
+        ensure_writable("stop the sync service")?;
+        let unit = self.main_unit()?;
+
+        // Stop timer and service (ignore errors if not running)
         let _ = Command::new("systemctl")
-            .args(["--user", "stop", SERVICE_NAME])
+            .args(["--user", "stop", &unit, &self.service_name()])
             .status();
 
         // Disable service
         let disable_status = Command::new("systemctl")
-            .args(["--user", "disable", SERVICE_NAME])
+            .args(["--user", "disable", &unit])
             .status()
             .map_err(|e| AppError::io("Failed to disable service", e))?;
 
@@ -168,24 +325,27 @@ WantedBy=default.target
         Ok(())
     }
 
-    /// Get service status.
-    pub fn get_status(&self) -> Result<ServiceStatus> {
+    fn get_status(&self) -> Result<ServiceStatus> {
         // Check if service file exists
-        let service_path = Self::service_file_path()?;
+        let service_path = self.service_file_path()?;
         let is_installed = service_path.exists();
 
         if !is_installed {
             return Ok(ServiceStatus {
                 is_installed: false,
+                mode: ServiceMode::Daemon,
                 is_enabled: false,
                 is_running: false,
                 status_text: "not installed".into(),
             });
         }
 
+        // A timer is enabled and active while the service only runs per sync
+        let unit = self.main_unit()?;
+
         // Check if enabled
         let enabled_output = Command::new("systemctl")
-            .args(["--user", "is-enabled", SERVICE_NAME])
+            .args(["--user", "is-enabled", &unit])
             .output()
             .map_err(|e| AppError::io("Failed to check service enabled status", e))?;
 
@@ -193,7 +353,7 @@ WantedBy=default.target
 
         // Check if running
         let active_output = Command::new("systemctl")
-            .args(["--user", "is-active", SERVICE_NAME])
+            .args(["--user", "is-active", &unit])
             .output()
             .map_err(|e| AppError::io("Failed to check service active status", e))?;
 
@@ -201,7 +361,7 @@ WantedBy=default.target
 
         // Get full status
         let status_output = Command::new("systemctl")
-            .args(["--user", "status", SERVICE_NAME, "--no-pager"])
+            .args(["--user", "status", &unit, "--no-pager"])
             .output()
             .map_err(|e| AppError::io("Failed to get service status", e))?;
 
@@ -209,22 +369,29 @@ WantedBy=default.target
 
         Ok(ServiceStatus {
             is_installed,
+            mode: if unit == self.timer_name() {
+                ServiceMode::Timer
+            } else {
+                ServiceMode::Daemon
+            },
             is_enabled,
             is_running,
             status_text,
         })
     }
 
-    /// Uninstall the service.
-    pub fn uninstall(&self) -> Result<()> {
+    fn uninstall(&self) -> Result<()> {
+        ensure_writable("uninstall the sync service")?;
+
         // Stop and disable first
         let _ = self.stop_and_disable();
 
-        // Remove service file
-        let service_path = Self::service_file_path()?;
-        if service_path.exists() {
-            fs::remove_file(&service_path)
-                .map_err(|e| AppError::io("Failed to remove service file", e))?;
+        // Remove service and timer files
+        for path in [self.service_file_path()?, self.timer_file_path()?] {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| AppError::io("Failed to remove service file", e))?;
+            }
         }
 
         // Reload daemon
@@ -237,13 +404,12 @@ WantedBy=default.target
         Ok(())
     }
 
-    /// View service logs.
-    pub fn view_logs(&self, lines: usize) -> Result<String> {
+    fn view_logs(&self, lines: usize) -> Result<String> {
         let output = Command::new("journalctl")
             .args([
                 "--user",
                 "-u",
-                SERVICE_NAME,
+                &self.service_name(),
                 "-n",
                 &lines.to_string(),
                 "--no-pager",
@@ -255,63 +421,45 @@ WantedBy=default.target
     }
 }
 
-/// Result of installing the service.
-#[derive(Debug)]
-pub struct InstallResult {
-    /// Path where service file was written.
-    pub service_path: PathBuf,
-    /// Content of the unit file.
-    pub unit_content: String,
-}
-
-/// Service status information.
-#[derive(Debug, Clone)]
-pub struct ServiceStatus {
-    /// Whether the service file is installed.
-    pub is_installed: bool,
-    /// Whether the service is enabled to start on boot.
-    pub is_enabled: bool,
-    /// Whether the service is currently running.
-    pub is_running: bool,
-    /// Full status text from systemctl.
-    pub status_text: String,
-}
-
-impl ServiceStatus {
-    /// Get a short status string.
-    #[must_use]
-    pub fn short_status(&self) -> &'static str {
-        match (self.is_installed, self.is_enabled, self.is_running) {
-            (false, _, _) => "not installed",
-            (true, false, false) => "installed, disabled",
-            (true, true, false) => "enabled, stopped",
-            (true, false, true) => "running (not enabled)",
-            (true, true, true) => "running",
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_service_status_short() {
-        let status = ServiceStatus {
-            is_installed: true,
-            is_enabled: true,
-            is_running: true,
-            status_text: String::new(),
-        };
-        assert_eq!(status.short_status(), "running");
+    fn test_generate_units_from_config() {
+        let mut config = AppConfig::default();
+        config.sync.interval_secs = 300;
+        config.service.nice = Some(10);
+        config.service.wanted_by = "graphical-session.target".to_string();
+        let systemd = SystemdService::new(config);
+        let binary = Path::new("/usr/bin/cursor-chat");
+
+        let daemon = systemd.generate_unit_file(binary, ServiceMode::Daemon);
+        assert!(daemon.contains("ExecStart=/usr/bin/cursor-chat daemon --interval 300\n"));
+        assert!(daemon.contains("RestartSec=30\n"));
+        assert!(daemon.contains("MemoryMax=256M\nCPUQuota=25%\nNice=10\n"));
+        assert!(daemon.ends_with("[Install]\nWantedBy=graphical-session.target\n"));
+
+        let oneshot = systemd.generate_unit_file(binary, ServiceMode::Timer);
+        assert!(oneshot.contains("Type=oneshot\nExecStart=/usr/bin/cursor-chat daemon --once\n"));
+        assert!(!oneshot.contains("Restart") && !oneshot.contains("[Install]"));
+
+        let timer = systemd.generate_timer_file();
+        assert!(timer.contains("OnUnitActiveSec=300s\nUnit=cursor-chat-sync.service\n"));
+    }
 
-        let status = ServiceStatus {
-            is_installed: false,
-            is_enabled: false,
-            is_running: false,
-            status_text: String::new(),
-        };
-        assert_eq!(status.short_status(), "not installed");
+    #[test]
+    fn test_profile_instance_unit_names() {
+        let mut systemd = SystemdService::new(AppConfig::default());
+        systemd.instance = Some("work".to_string());
+
+        assert_eq!(systemd.service_name(), "cursor-chat-sync@work.service");
+        assert_eq!(systemd.timer_name(), "cursor-chat-sync@work.timer");
+        assert!(systemd
+            .generate_timer_file()
+            .contains("Unit=cursor-chat-sync@work.service\n"));
+        assert!(systemd
+            .generate_unit_file(Path::new("/bin/cc"), ServiceMode::Daemon)
+            .contains("Environment=CURSOR_CHAT_PROFILE=work\n"));
     }
 }
-
@@ -0,0 +1,169 @@
+//! Per-stage timing collected from tracing spans (`--timings`).
+//!
+//! Pipeline stages open spans with target [`TIMINGS_TARGET`] and a `rows`
+//! field. [`TimingLayer`] aggregates their elapsed time per stage, excluding
+//! time spent in nested stage spans so the totals add up.
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Tracing target of stage spans.
+pub const TIMINGS_TARGET: &str = "timings";
+
+/// Aggregated timing of one stage.
+#[derive(Debug, Clone)]
+pub struct StageTiming {
+    /// Stage name (the span name, e.g. `db read`).
+    pub stage: &'static str,
+    /// Number of spans closed for this stage.
+    pub calls: usize,
+    /// Time spent in the stage itself, excluding nested stages.
+    pub elapsed: Duration,
+    /// Sum of the `rows` recorded by the spans.
+    pub rows: u64,
+}
+
+static STAGES: Mutex<Vec<StageTiming>> = Mutex::new(Vec::new());
+
+/// Stage timings collected so far, in order of first completion.
+#[must_use]
+pub fn stage_timings() -> Vec<StageTiming> {
+    STAGES.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Layer that records stage spans; only add it when `--timings` is given.
+pub struct TimingLayer;
+
+struct OpenSpan {
+    started: Instant,
+    nested: Duration,
+    rows: u64,
+}
+
+struct RowsVisitor<'a>(&'a mut u64);
+
+impl Visit for RowsVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "rows" {
+            *self.0 = value;
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value.unsigned_abs());
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut rows = 0;
+        attrs.record(&mut RowsVisitor(&mut rows));
+        span.extensions_mut().insert(OpenSpan {
+            started: Instant::now(),
+            nested: Duration::ZERO,
+            rows,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+                values.record(&mut RowsVisitor(&mut open.rows));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        let elapsed = open.started.elapsed();
+
+        if let Some(parent) = span.parent() {
+            if let Some(outer) = parent.extensions_mut().get_mut::<OpenSpan>() {
+                outer.nested += elapsed;
+            }
+        }
+
+        let Ok(mut stages) = STAGES.lock() else {
+            return;
+        };
+        let name = span.name();
+        let index = stages
+            .iter()
+            .position(|s| s.stage == name)
+            .unwrap_or_else(|| {
+                stages.push(StageTiming {
+                    stage: name,
+                    calls: 0,
+                    elapsed: Duration::ZERO,
+                    rows: 0,
+                });
+                stages.len() - 1
+            });
+        let stage = &mut stages[index];
+        stage.calls += 1;
+        stage.elapsed += elapsed.saturating_sub(open.nested);
+        stage.rows += open.rows;
+    }
+}
+
+/// Writer that times every write to the underlying sink as a `write` stage.
+pub struct TimedWriter<W>(pub W);
+
+impl<W: Write> Write for TimedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _span =
+            tracing::info_span!(target: TIMINGS_TARGET, "write", rows = buf.len()).entered();
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _span = tracing::info_span!(target: TIMINGS_TARGET, "write").entered();
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_nested_stages_exclude_inner_time() {
+        let subscriber = tracing_subscriber::registry().with(TimingLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!(target: TIMINGS_TARGET, "test outer", rows = 2_u64);
+            let _outer = outer.enter();
+            for _ in 0..2 {
+                let _inner =
+                    tracing::info_span!(target: TIMINGS_TARGET, "test inner", rows = 5_u64)
+                        .entered();
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        let stages = stage_timings();
+        let outer = stages.iter().find(|s| s.stage == "test outer").unwrap();
+        let inner = stages.iter().find(|s| s.stage == "test inner").unwrap();
+        assert_eq!((outer.calls, outer.rows), (1, 2));
+        assert_eq!((inner.calls, inner.rows), (2, 10));
+        assert!(inner.elapsed >= Duration::from_millis(40));
+        assert!(outer.elapsed < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,198 @@
+//! Workspace attribution from Cursor's `workspaceStorage`.
+//!
+//! Each folder there belongs to one opened workspace: its `workspace.json`
+//! names the project folder (or `.code-workspace` file), and the
+//! `composer.composerData` entry of its `state.vscdb` lists the composers
+//! created in it. This is far more complete than the `workspaceUris` some
+//! bubbles carry.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::domain::{Result, WorkspaceInfo};
+
+use super::cursor_paths::workspace_storage_dir;
+use super::sqlite_reader::StateDbReader;
+
+/// `ItemTable` key listing a workspace's composers.
+const COMPOSER_DATA_KEY: &str = "composer.composerData";
+
+#[derive(Deserialize)]
+struct WorkspaceJson {
+    folder: Option<String>,
+    workspace: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComposerData {
+    #[serde(default)]
+    all_composers: Vec<ComposerHead>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComposerHead {
+    composer_id: String,
+}
+
+/// Workspace of each composer, from every readable `workspaceStorage` folder.
+///
+/// # Errors
+/// Returns error if Cursor's config directory cannot be found.
+pub fn composer_workspaces() -> Result<HashMap<String, WorkspaceInfo>> {
+    Ok(scan_workspace_storage(&workspace_storage_dir()?))
+}
+
+/// Map composer IDs to workspaces from the folders in `dir`.
+///
+/// Folders without a `workspace.json` or a readable database are skipped.
+#[must_use]
+pub fn scan_workspace_storage(dir: &Path) -> HashMap<String, WorkspaceInfo> {
+    let mut workspaces = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return workspaces;
+    };
+
+    for entry in entries.filter_map(std::result::Result::ok) {
+        let folder = entry.path();
+        let Some(info) = read_workspace_json(&folder) else {
+            continue;
+        };
+
+        match composer_ids(&folder) {
+            Ok(ids) => {
+                for id in ids {
+                    workspaces.insert(id, info.clone());
+                }
+            }
+            Err(e) => {
+                tracing::debug!(folder = %folder.display(), error = %e, "Skipping workspace");
+            }
+        }
+    }
+
+    tracing::debug!(composers = workspaces.len(), "Resolved workspaces");
+    workspaces
+}
+
+/// The workspace of a database in a `workspaceStorage` folder.
+#[must_use]
+pub fn workspace_of_database(db_path: &Path) -> Option<WorkspaceInfo> {
+    read_workspace_json(db_path.parent()?)
+}
+
+/// The workspace a `workspaceStorage` folder belongs to.
+fn read_workspace_json(folder: &Path) -> Option<WorkspaceInfo> {
+    let content = std::fs::read(folder.join("workspace.json")).ok()?;
+    let json: WorkspaceJson = serde_json::from_slice(&content).ok()?;
+    let uri = json.folder.or(json.workspace)?;
+    let hash = folder.file_name()?.to_string_lossy().into_owned();
+
+    // Remote workspaces (SSH, WSL, containers) keep their URI as path
+    let mut info = uri
+        .strip_prefix("file://")
+        .map_or_else(|| remote_workspace(&uri), local_workspace);
+    info.cursor_path = Some(hash);
+    Some(info)
+}
+
+fn local_workspace(path: &str) -> WorkspaceInfo {
+    let path = PathBuf::from(percent_decode(path));
+    // A multi-root workspace is named after its .code-workspace file
+    let name = if path.extension().is_some_and(|e| e == "code-workspace") {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+
+    WorkspaceInfo {
+        name: name.map_or_else(|| "unknown".into(), |n| n.to_string_lossy().into_owned()),
+        path: Some(path),
+        cursor_path: None,
+    }
+}
+
+fn remote_workspace(uri: &str) -> WorkspaceInfo {
+    let last = uri.trim_end_matches('/').rsplit('/').next().unwrap_or(uri);
+    WorkspaceInfo {
+        name: percent_decode(last),
+        path: Some(PathBuf::from(uri)),
+        cursor_path: None,
+    }
+}
+
+/// Composer IDs listed in a workspace database.
+fn composer_ids(folder: &Path) -> Result<Vec<String>> {
+    let db = folder.join("state.vscdb");
+    if !db.exists() {
+        return Ok(Vec::new());
+    }
+
+    let Some(value) = StateDbReader::open(&db)?.item_value(COMPOSER_DATA_KEY)? else {
+        return Ok(Vec::new());
+    };
+    let data: ComposerData = serde_json::from_slice(&value).unwrap_or(ComposerData {
+        all_composers: Vec::new(),
+    });
+    Ok(data
+        .all_composers
+        .into_iter()
+        .map(|c| c.composer_id)
+        .collect())
+}
+
+/// Decode `%XX` escapes in a URI path.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        let (byte, len) = escaped.map_or((bytes[i], 1), |byte| (byte, 3));
+        out.push(byte);
+        i += len;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_workspace_storage() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("0123abcd");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("workspace.json"),
+            r#"{"folder": "file:///home/me/my%20app"}"#,
+        )
+        .unwrap();
+        let conn = Connection::open(folder.join("state.vscdb")).unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE ItemTable (key TEXT UNIQUE, value BLOB);
+               INSERT INTO ItemTable VALUES ('composer.composerData',
+                 '{"allComposers": [{"composerId": "c1"}, {"composerId": "c2"}]}');"#,
+        )
+        .unwrap();
+
+        // Folders without workspace.json are ignored
+        std::fs::create_dir_all(dir.path().join("empty")).unwrap();
+
+        let workspaces = scan_workspace_storage(dir.path());
+        assert_eq!(workspaces.len(), 2);
+        let info = &workspaces["c1"];
+        assert_eq!(info.name, "my app");
+        assert_eq!(info.path.as_deref(), Some(Path::new("/home/me/my app")));
+        assert_eq!(info.cursor_path.as_deref(), Some("0123abcd"));
+    }
+}
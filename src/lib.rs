@@ -0,0 +1,36 @@
+//! Cursor Chat Handler as a library.
+//!
+//! Reads conversations out of Cursor's `SQLite` databases, keeps them in
+//! local storage and restores them after a reset. The `cursor-chat` CLI in
+//! `main.rs` is a thin layer over this crate; other tools can embed the
+//! same extraction:
+//!
+//! ```no_run
+//! use cursor_chat_handler::{extract_all_conversations, ExtractOptions};
+//!
+//! let (conversations, stats) = extract_all_conversations(&ExtractOptions::default())?;
+//! for conv in &conversations {
+//!     println!("{} ({} messages)", conv.title, conv.message_count());
+//! }
+//! eprintln!("{} conversations", stats.conversation_count);
+//! # Ok::<(), cursor_chat_handler::AppError>(())
+//! ```
+//!
+//! - [`domain`]: conversation models, configuration and errors.
+//! - [`application`]: extraction, formatting, sync, restore and analysis.
+//! - [`infrastructure`]: database, filesystem and service adapters.
+//!
+//! With the `capi` feature the cdylib also exposes a C ABI ([`capi`]).
+
+pub mod application;
+pub mod domain;
+pub mod infrastructure;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+pub use application::{
+    extract_all_conversations, find_conversation, ExtractOptions, RestoreResult, RestoreService,
+    SyncService,
+};
+pub use domain::{AppConfig, AppError, Bubble, BubbleType, Conversation, Result};
@@ -11,831 +11,514 @@
 //!   cursor-chat export -c <id> -o file  # Save chat for later reference
 //!   cursor-chat sync start              # Start auto-sync daemon
 
-mod application;
 mod cli;
-mod domain;
-mod infrastructure;
+mod commands;
+#[cfg(feature = "grpc")]
+mod grpc;
+
+use cursor_chat_handler::{application, domain, infrastructure};
 
-use std::io::Write;
 use std::time::Duration;
 
 use clap::Parser;
 use colored::Colorize;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*, EnvFilter};
 
 use application::{
-    extract_all_conversations, format_conversation_markdown, format_conversations_json,
-    format_conversations_table, format_stats, ExtractOptions, OutputFormat,
-    RestoreService, StorageManager, SyncService,
+    format_timings, ChunkOptions, OutputFormat, PruneFilter, SplitLimit, TrainingOptions,
+};
+use cli::{
+    Cli, Commands, ConfigCommands, DateRangeArgs, IndexCommands, StatsCommands, StorageCommands,
+    SyncCommands, TagCommands, TitlesCommands,
+};
+#[cfg(feature = "grpc")]
+use commands::cmd_grpc;
+use commands::{
+    cmd_archive, cmd_archive_list, cmd_bench, cmd_changes, cmd_commands, cmd_config_edit,
+    cmd_config_get, cmd_config_set, cmd_correlate, cmd_daemon, cmd_delete, cmd_doctor, cmd_export,
+    cmd_export_all, cmd_export_all_with_plugin, cmd_export_rag, cmd_export_script,
+    cmd_export_training, cmd_export_with_plugin, cmd_history, cmd_import, cmd_index_build,
+    cmd_index_search, cmd_list, cmd_merge, cmd_open, cmd_paths, cmd_prune, cmd_query, cmd_quick,
+    cmd_replay, cmd_reset, cmd_restore, cmd_search, cmd_show, cmd_show_with_plugin, cmd_similar,
+    cmd_stats, cmd_stats_modes, cmd_stats_sessions, cmd_stats_top, cmd_storage_backups,
+    cmd_storage_cleanup, cmd_storage_config, cmd_storage_doctor, cmd_storage_export_db,
+    cmd_storage_stats, cmd_storage_vacuum, cmd_storage_workspaces, cmd_summarize, cmd_sync_diff,
+    cmd_sync_logs, cmd_sync_now, cmd_sync_restore, cmd_sync_start, cmd_sync_status, cmd_sync_stop,
+    cmd_sync_uninstall, cmd_tag, cmd_tag_list, cmd_timeline, cmd_titles_regenerate, cmd_todos,
+    cmd_uninstall, formatter_plugin, BenchOptions, DateRange, ResetOptions, Selection,
+};
+use domain::CursorChannel;
+use infrastructure::{
+    enable_read_only, load_config, select_profile, set_cursor_channel, set_cursor_config_dir,
+    set_max_memory_mb, set_path_map_file, stage_timings, TimingLayer, TIMINGS_TARGET,
 };
-use cli::{Cli, Commands, StorageCommands, SyncCommands};
-use domain::AppConfig;
-use infrastructure::{find_state_databases, CursorReset, SystemdService};
 
 fn main() {
     let cli = Cli::parse();
 
     // Setup logging based on verbosity
-    setup_logging(cli.verbose);
+    setup_logging(cli.verbose, cli.timings);
 
-    if let Err(e) = run(cli) {
+    let timings = cli.timings;
+    let result = run(cli);
+    if timings {
+        print_timings();
+    }
+
+    if let Err(e) = result {
         eprintln!("{} {}", "Error:".red().bold(), e);
         std::process::exit(1);
     }
 }
 
 /// Main application logic.
+#[allow(clippy::too_many_lines)] // One arm per command
 fn run(cli: Cli) -> domain::Result<()> {
-    let format = cli
-        .output_format()
-        .map_err(|e| domain::AppError::Config { message: e })?;
+    apply_global_options(&cli)?;
+
+    let format = match cli.output_format() {
+        Ok(format) => format,
+        Err(e) => return run_with_plugin(cli, e),
+    };
 
+    let filter = cli.filter.as_ref();
     match cli.command {
         Commands::List {
             limit,
             min_messages,
             workspace,
-        } => {
-            cmd_list(limit, min_messages, workspace.as_deref())?;
-        }
+            tag,
+            group_by,
+            range,
+        } => cmd_list(
+            limit,
+            Selection {
+                conversation_id: None,
+                min_messages,
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            group_by,
+            format,
+            filter,
+        ),
         Commands::Show {
             conversation_id,
             include_empty,
             last,
-        } => {
-            cmd_show(&conversation_id, include_empty, last, format)?;
-        }
+        } => cmd_show(&conversation_id, include_empty, last, format, filter),
         Commands::Export {
             output,
             conversation,
             min_messages,
-        } => {
-            cmd_export(
-                output.as_deref(),
-                conversation.as_deref(),
+            workspace,
+            tag,
+            range,
+            dataset: Some(_),
+            no_thinking,
+            drop_noise,
+            encrypt,
+            ..
+        } => cmd_export_training(
+            output.as_deref(),
+            Selection {
+                conversation_id: conversation.as_deref(),
                 min_messages,
-                format,
-            )?;
-        }
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            TrainingOptions {
+                drop_thinking: no_thinking,
+                drop_noise,
+            },
+            filter,
+            encrypt.as_ref(),
+        ),
+        Commands::Export {
+            output,
+            conversation,
+            min_messages,
+            workspace,
+            tag,
+            range,
+            split_tokens,
+            split_bytes,
+            encrypt,
+            ..
+        } => cmd_export(
+            output.as_deref(),
+            Selection {
+                conversation_id: conversation.as_deref(),
+                min_messages,
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            format,
+            split_tokens
+                .map(SplitLimit::Tokens)
+                .or_else(|| split_bytes.map(SplitLimit::Bytes)),
+            filter,
+            encrypt.as_ref(),
+        ),
         Commands::ExportAll {
             dir,
             limit,
             min_messages,
-        } => {
-            cmd_export_all(&dir, limit, min_messages, format)?;
-        }
-        Commands::Stats => {
-            cmd_stats()?;
-        }
-        Commands::Paths => {
-            cmd_paths()?;
-        }
-        Commands::Quick { limit } => {
-            cmd_quick(limit)?;
-        }
-        Commands::Open { id } => {
-            cmd_open(&id)?;
-        }
-        Commands::Sync(sync_cmd) => {
-            cmd_sync(sync_cmd)?;
-        }
-        Commands::Storage(storage_cmd) => {
-            cmd_storage(storage_cmd)?;
-        }
-        Commands::Daemon { interval } => {
-            cmd_daemon(interval)?;
-        }
-        Commands::Restore { ids, force } => {
-            cmd_restore(&ids, force)?;
+            workspace,
+            tag,
+            range,
+            encrypt,
+        } => cmd_export_all(
+            &dir,
+            limit,
+            Selection {
+                conversation_id: None,
+                min_messages,
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            format,
+            encrypt.as_ref(),
+        ),
+        Commands::ExportRag {
+            output,
+            chunk_tokens,
+            overlap,
+            min_messages,
+        } => cmd_export_rag(
+            output.as_deref(),
+            ChunkOptions {
+                chunk_tokens,
+                overlap,
+            },
+            min_messages,
+        ),
+        Commands::Terminal { conversation_id } => cmd_commands(&conversation_id, format),
+        Commands::ExportScript {
+            conversation_id,
+            output,
+        } => cmd_export_script(&conversation_id, output.as_deref()),
+        Commands::Replay {
+            conversation_id,
+            timed,
+            speed,
+        } => cmd_replay(&conversation_id, timed, speed),
+        Commands::Todos {
+            conversation_id,
+            all: _,
+        } => cmd_todos(conversation_id.as_deref(), format),
+        Commands::Summarize {
+            conversation_id,
+            refresh,
+        } => cmd_summarize(&conversation_id, refresh, format),
+        Commands::Timeline { workspace, since } => {
+            cmd_timeline(workspace.as_deref(), since, format)
         }
-        Commands::Reset { no_restore, clean_appimage } => {
-            cmd_reset(no_restore, clean_appimage)?;
+        Commands::Correlate {
+            conversation_id,
+            margin,
+            repo,
+        } => cmd_correlate(&conversation_id, margin, repo.as_deref(), format),
+        Commands::Changes { since, limit } => cmd_changes(since, limit, format),
+        Commands::History { limit, kind } => cmd_history(limit, kind, format),
+        Commands::Query { sql, csv } => cmd_query(&sql, csv, format),
+        Commands::Titles(TitlesCommands::Regenerate {
+            missing_only,
+            limit,
+        }) => cmd_titles_regenerate(missing_only, limit, format),
+        Commands::Tag(TagCommands::Add {
+            conversation_id,
+            tags,
+        }) => cmd_tag(&conversation_id, &tags, true),
+        Commands::Tag(TagCommands::Remove {
+            conversation_id,
+            tags,
+        }) => cmd_tag(&conversation_id, &tags, false),
+        Commands::Tag(TagCommands::List { conversation_id }) => {
+            cmd_tag_list(conversation_id.as_deref(), format)
         }
+        Commands::Similar {
+            conversation_id,
+            limit,
+        } => cmd_similar(&conversation_id, limit, format),
+        Commands::Index(IndexCommands::Build { force }) => cmd_index_build(force, format),
+        Commands::Search {
+            query,
+            limit,
+            workspace,
+            tag,
+        } => cmd_search(&query, limit, workspace.as_deref(), tag.as_deref(), format),
+        Commands::Index(IndexCommands::Search {
+            query,
+            limit,
+            workspace,
+            tag,
+        }) => cmd_index_search(&query, limit, workspace.as_deref(), tag.as_deref(), format),
+        Commands::Stats {
+            command: None,
+            export,
+        } => cmd_stats(export.as_deref()),
+        Commands::Stats {
+            command: Some(StatsCommands::Top { limit }),
+            ..
+        } => cmd_stats_top(limit, format),
+        Commands::Stats {
+            command:
+                Some(StatsCommands::Sessions {
+                    gap,
+                    since,
+                    workspace,
+                }),
+            ..
+        } => cmd_stats_sessions(gap, since, workspace.as_deref(), format),
+        Commands::Stats {
+            command: Some(StatsCommands::Modes),
+            ..
+        } => cmd_stats_modes(format),
+        Commands::Paths { json } => cmd_paths(json),
+        Commands::Quick { limit, machine } => cmd_quick(limit, machine),
+        Commands::Open { id, in_cursor } => cmd_open(&id, in_cursor),
+        Commands::Sync(command) => run_sync(command, format),
+        Commands::Storage(command) => run_storage(command),
+        Commands::Daemon {
+            interval,
+            listen,
+            once,
+            watch,
+            pid_file,
+        } => cmd_daemon(interval, listen, once, watch, pid_file.as_deref()),
+        #[cfg(feature = "grpc")]
+        Commands::Grpc { listen } => cmd_grpc(listen),
+        Commands::Restore {
+            ids,
+            force,
+            close_cursor,
+        } => cmd_restore(&ids, force, close_cursor),
+        Commands::Delete { id, close_cursor } => cmd_delete(&id, close_cursor),
+        Commands::Archive {
+            id: Some(id),
+            delete,
+            close_cursor,
+            list: false,
+        } => cmd_archive(&id, delete, close_cursor),
+        Commands::Archive { .. } => cmd_archive_list(format),
+        Commands::Prune {
+            older_than,
+            ids,
+            dry_run,
+            close_cursor,
+        } => cmd_prune(
+            &PruneFilter { older_than, ids },
+            dry_run,
+            close_cursor,
+            format,
+        ),
+        Commands::Import { path } => cmd_import(&path, format),
+        Commands::Merge {
+            ids,
+            into,
+            restore,
+            close_cursor,
+        } => cmd_merge(&ids, into.as_deref(), restore, close_cursor, format),
+        Commands::Reset(args) => cmd_reset(&ResetOptions {
+            steps: args.steps(),
+            no_backup: args.no_backup,
+            no_restore: args.no_restore,
+            restore: args.restore,
+            wait_timeout: Duration::from_secs(args.wait_timeout),
+            no_sudo: args.no_sudo,
+            dry_run: args.dry_run,
+        }),
+        Commands::Doctor => cmd_doctor(),
+        Commands::Bench(args) => cmd_bench(
+            &BenchOptions {
+                synthetic: args.synthetic,
+                messages: args.messages,
+                iterations: args.iterations,
+                synthetic_only: args.synthetic_only,
+            },
+            format,
+        ),
+        Commands::Config(command) => run_config(command),
+        Commands::Uninstall { purge, yes } => cmd_uninstall(purge, yes),
     }
-
-    Ok(())
-}
-
-/// List conversations command.
-fn cmd_list(limit: usize, min_messages: usize, _workspace: Option<&str>) -> domain::Result<()> {
-    let options = ExtractOptions {
-        min_messages,
-        ..Default::default()
-    };
-
-    let (mut conversations, stats) = extract_all_conversations(&options)?;
-    conversations.truncate(limit);
-
-    println!("{}", format_conversations_table(&conversations));
-    println!();
-    println!("{}", format_stats(&stats));
-
-    Ok(())
 }
 
-/// Show a single conversation.
-fn cmd_show(
-    id: &str,
-    include_empty: bool,
-    last: Option<usize>,
-    format: OutputFormat,
-) -> domain::Result<()> {
-    let options = ExtractOptions {
-        include_empty,
-        ..Default::default()
-    };
-
-    let (conversations, _) = extract_all_conversations(&options)?;
-
-    // Find conversation by ID (partial match)
-    let conv = conversations
-        .iter()
-        .find(|c| c.composer_id.starts_with(id) || c.composer_id.contains(id))
-        .ok_or_else(|| domain::AppError::InvalidData {
-            message: format!("Conversation not found: {id}"),
-        })?;
-
-    // Apply --last filter if specified
-    let conv = if let Some(n) = last {
-        let mut filtered = conv.clone();
-        let len = filtered.bubbles.len();
-        if n < len {
-            filtered.bubbles = filtered.bubbles.into_iter().skip(len - n).collect();
-        }
-        filtered
-    } else {
-        conv.clone()
-    };
-
-    let output = match format {
-        OutputFormat::Markdown => format_conversation_markdown(&conv),
-        OutputFormat::Json => format_conversations_json(std::slice::from_ref(&conv))
-            .map_err(domain::AppError::json_parse)?,
-        OutputFormat::Table => format_conversations_table(std::slice::from_ref(&conv)),
-    };
-
-    println!("{output}");
-    Ok(())
-}
-
-/// Export conversations to file or stdout.
-fn cmd_export(
-    output_path: Option<&str>,
-    conversation_id: Option<&str>,
-    min_messages: usize,
-    format: OutputFormat,
-) -> domain::Result<()> {
-    let options = ExtractOptions {
-        min_messages,
-        conversation_ids: conversation_id.map(|id| vec![id.to_string()]),
-        ..Default::default()
-    };
-
-    let (conversations, stats) = extract_all_conversations(&options)?;
-
-    let content = match format {
-        OutputFormat::Markdown => {
-            let mut out = String::new();
-            for conv in &conversations {
-                out.push_str(&format_conversation_markdown(conv));
-                out.push_str("\n\n");
-            }
-            out
-        }
-        OutputFormat::Json => {
-            format_conversations_json(&conversations).map_err(domain::AppError::json_parse)?
-        }
-        OutputFormat::Table => format_conversations_table(&conversations),
-    };
-
-    match output_path {
-        Some(path) => {
-            let mut file = std::fs::File::create(path)
-                .map_err(|e| domain::AppError::io(format!("Failed to create {path}"), e))?;
-            file.write_all(content.as_bytes())
-                .map_err(|e| domain::AppError::io("Failed to write file", e))?;
-            println!(
-                "{} Exported {} conversations to {}",
-                "✓".green().bold(),
-                stats.conversation_count,
-                path
-            );
-        }
-        None => {
-            println!("{content}");
-        }
+/// Run the sync service subcommands.
+fn run_sync(command: SyncCommands, format: OutputFormat) -> domain::Result<()> {
+    match command {
+        SyncCommands::Start { mode } => cmd_sync_start(mode),
+        SyncCommands::Stop => cmd_sync_stop(),
+        SyncCommands::Status => cmd_sync_status(),
+        SyncCommands::Now => cmd_sync_now(),
+        SyncCommands::Diff => cmd_sync_diff(format),
+        SyncCommands::Logs { lines } => cmd_sync_logs(lines),
+        SyncCommands::Uninstall => cmd_sync_uninstall(),
+        SyncCommands::Restore {
+            ids,
+            force,
+            close_cursor,
+        } => cmd_sync_restore(&ids, force, close_cursor),
     }
-
-    Ok(())
 }
 
-/// Export all conversations to separate files with auto-generated names.
-fn cmd_export_all(
-    dir: &str,
-    limit: usize,
-    min_messages: usize,
-    format: OutputFormat,
-) -> domain::Result<()> {
-    let options = ExtractOptions {
-        min_messages,
-        ..Default::default()
-    };
-
-    let (mut conversations, _) = extract_all_conversations(&options)?;
-
-    if limit > 0 {
-        conversations.truncate(limit);
-    }
-
-    // Create output directory
-    std::fs::create_dir_all(dir)
-        .map_err(|e| domain::AppError::io(format!("Failed to create directory {dir}"), e))?;
-
-    let ext = match format {
-        OutputFormat::Markdown => "md",
-        OutputFormat::Json => "json",
-        OutputFormat::Table => "txt",
-    };
-
-    for conv in &conversations {
-        let filename = format!("{}/{}.{}", dir, conv.filename(), ext);
-
-        let content = match format {
-            OutputFormat::Markdown => format_conversation_markdown(conv),
-            OutputFormat::Json => format_conversations_json(std::slice::from_ref(conv))
-                .map_err(domain::AppError::json_parse)?,
-            OutputFormat::Table => format_conversations_table(std::slice::from_ref(conv)),
-        };
-
-        let mut file = std::fs::File::create(&filename)
-            .map_err(|e| domain::AppError::io(format!("Failed to create {filename}"), e))?;
-        file.write_all(content.as_bytes())
-            .map_err(|e| domain::AppError::io("Failed to write file", e))?;
-
-        println!("{} {} → {}", "✓".green(), conv.title.cyan(), filename);
+/// Run the storage subcommands.
+fn run_storage(command: StorageCommands) -> domain::Result<()> {
+    match command {
+        StorageCommands::Stats => cmd_storage_stats(),
+        StorageCommands::Cleanup => cmd_storage_cleanup(),
+        StorageCommands::Backups => cmd_storage_backups(),
+        StorageCommands::Workspaces => cmd_storage_workspaces(),
+        StorageCommands::Config => cmd_storage_config(),
+        StorageCommands::Vacuum => cmd_storage_vacuum(),
+        StorageCommands::ExportDb { output, force } => cmd_storage_export_db(&output, force),
+        StorageCommands::Doctor { fix } => cmd_storage_doctor(fix),
     }
-
-    println!(
-        "\n{} Exported {} conversations to {}/",
-        "📁".bold(),
-        conversations.len(),
-        dir
-    );
-
-    Ok(())
-}
-
-/// Show statistics command.
-fn cmd_stats() -> domain::Result<()> {
-    let options = ExtractOptions {
-        include_empty: true,
-        ..Default::default()
-    };
-
-    let (_, stats) = extract_all_conversations(&options)?;
-    println!("{}", format_stats(&stats));
-
-    Ok(())
 }
 
-/// Show database paths command.
-fn cmd_paths() -> domain::Result<()> {
-    let databases = find_state_databases()?;
-
-    println!("{}", "📂 Cursor Database Paths".bold());
-    println!();
-
-    for (i, path) in databases.iter().enumerate() {
-        let label = if path.to_string_lossy().contains("globalStorage") {
-            "global".green()
-        } else {
-            "workspace".blue()
-        };
-
-        println!("  {}. [{}] {}", i + 1, label, path.display());
+/// Run the config subcommands.
+fn run_config(command: ConfigCommands) -> domain::Result<()> {
+    match command {
+        ConfigCommands::Get { key } => cmd_config_get(key.as_deref()),
+        ConfigCommands::Set { key, value } => cmd_config_set(&key, &value),
+        ConfigCommands::Edit => cmd_config_edit(),
     }
-
-    println!();
-    println!("Total: {} database(s)", databases.len());
-
-    Ok(())
 }
 
-/// Quick access menu command.
-fn cmd_quick(limit: usize) -> domain::Result<()> {
-    let options = ExtractOptions {
-        min_messages: 1,
-        ..Default::default()
-    };
-
-    let (mut conversations, _) = extract_all_conversations(&options)?;
-    conversations.truncate(limit);
+/// Run `show`, `export` or `export-all` with a `[formatters.<name>]` plugin
+/// as the output format.
+fn run_with_plugin(cli: Cli, format_error: String) -> domain::Result<()> {
+    let name = cli.format.as_str();
+    let plugin = formatter_plugin(name, format_error)?;
 
-    println!("🚀 Quick Access Menu");
-    println!("==================");
-    println!();
-
-    for (i, conv) in conversations.iter().enumerate() {
-        let model = if conv.model_config.model_name.is_empty() {
-            "unknown".to_string()
-        } else {
-            conv.model_config.model_name[..15.min(conv.model_config.model_name.len())].to_string()
-        };
-
-        let title = if conv.title.is_empty() {
-            "Untitled".to_string()
-        } else {
-            conv.title[..40.min(conv.title.len())].to_string()
-        };
-
-        println!(
-            "  {:2}. {} | {} | {} msgs | {}",
-            i + 1,
-            &conv.composer_id[..8],
-            model,
-            conv.message_count(),
-            title
-        );
+    match cli.command {
+        Commands::Show {
+            conversation_id,
+            include_empty,
+            last,
+        } => cmd_show_with_plugin(name, &plugin, &conversation_id, include_empty, last),
+        Commands::Export {
+            output,
+            conversation,
+            min_messages,
+            workspace,
+            tag,
+            range,
+            split_tokens: None,
+            split_bytes: None,
+            dataset: None,
+            encrypt,
+            ..
+        } => cmd_export_with_plugin(
+            name,
+            &plugin,
+            output.as_deref(),
+            Selection {
+                conversation_id: conversation.as_deref(),
+                min_messages,
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            encrypt.as_ref(),
+        ),
+        Commands::ExportAll {
+            dir,
+            limit,
+            min_messages,
+            workspace,
+            tag,
+            range,
+            encrypt,
+        } => cmd_export_all_with_plugin(
+            name,
+            &plugin,
+            &dir,
+            limit,
+            Selection {
+                conversation_id: None,
+                min_messages,
+                workspace: workspace.as_deref(),
+                tag: tag.as_deref(),
+                range: range.into(),
+            },
+            encrypt.as_ref(),
+        ),
+        _ => Err(domain::AppError::Config {
+            message: format!(
+                "Formatter plugin '{name}' only works with show, export (without splitting) \
+                 and export-all"
+            ),
+        }),
     }
-
-    println!();
-    println!("💡 Quick commands:");
-    println!(
-        "   cursor-chat open {}        # Open conversation by number",
-        conversations.first().map(|_| "1").unwrap_or("N")
-    );
-    println!(
-        "   cursor-chat show {}         # Show full conversation",
-        conversations
-            .first()
-            .map(|c| &c.composer_id[..8])
-            .unwrap_or("ID")
-    );
-    println!(
-        "   cursor-chat export -c {}    # Export conversation",
-        conversations
-            .first()
-            .map(|c| &c.composer_id[..8])
-            .unwrap_or("ID")
-    );
-
-    Ok(())
-}
-
-/// Open conversation directly command.
-fn cmd_open(id_or_number: &str) -> domain::Result<()> {
-    let options = ExtractOptions {
-        min_messages: 1,
-        ..Default::default()
-    };
-
-    let (conversations, _) = extract_all_conversations(&options)?;
-
-    // Try to parse as number first (1-based index)
-    let conv = if let Ok(number) = id_or_number.parse::<usize>() {
-        if number == 0 || number > conversations.len() {
-            return Err(domain::AppError::InvalidData {
-                message: format!(
-                    "Number {} is out of range (1-{})",
-                    number,
-                    conversations.len()
-                ),
-            });
-        }
-        &conversations[number - 1]
-    } else {
-        // Try as ID (partial match)
-        conversations
-            .iter()
-            .find(|c| {
-                c.composer_id.starts_with(id_or_number) || c.composer_id.contains(id_or_number)
-            })
-            .ok_or_else(|| domain::AppError::InvalidData {
-                message: format!("Conversation '{}' not found", id_or_number),
-            })?
-    };
-
-    // Show the conversation with last 10 messages
-    cmd_show(&conv.composer_id, false, Some(10), OutputFormat::Markdown)?;
-
-    println!();
-    println!("💡 Pro tips:");
-    println!(
-        "   cursor-chat export -c {} -o current.md    # Save this chat",
-        &conv.composer_id[..8]
-    );
-    println!(
-        "   cursor-chat show {} --last 20            # See more messages",
-        &conv.composer_id[..8]
-    );
-
-    Ok(())
 }
 
-/// Handle sync subcommands.
-fn cmd_sync(cmd: SyncCommands) -> domain::Result<()> {
-    let config = AppConfig::default();
-    let systemd = SystemdService::new(config.clone());
-
-    match cmd {
-        SyncCommands::Start => {
-            println!("{}", "🚀 Installing sync service...".bold());
-
-            // Ensure directories exist
-            let storage_mgr = StorageManager::new(config.clone());
-            storage_mgr.ensure_directories()?;
-
-            // Install and start service
-            let result = systemd.install()?;
-            println!("  {} Service file: {}", "✓".green(), result.service_path.display());
-
-            systemd.enable_and_start()?;
-            println!("  {} Service enabled and started", "✓".green());
-
-            println!();
-            println!("Sync daemon is now running! Check status with:");
-            println!("  cursor-chat sync status");
-        }
-        SyncCommands::Stop => {
-            println!("{}", "⏹️  Stopping sync service...".bold());
-            systemd.stop_and_disable()?;
-            println!("  {} Service stopped and disabled", "✓".green());
-        }
-        SyncCommands::Status => {
-            let status = systemd.get_status()?;
-
-            println!("{}", "📊 Sync Service Status".bold());
-            println!();
-            println!("  Installed: {}", if status.is_installed { "Yes".green() } else { "No".red() });
-            println!("  Enabled:   {}", if status.is_enabled { "Yes".green() } else { "No".yellow() });
-            println!("  Running:   {}", if status.is_running { "Yes".green() } else { "No".red() });
-            println!();
-
-            if status.is_installed {
-                // Show sync state
-                let sync_service = SyncService::new(config)?;
-                let state = sync_service.get_state()?;
-
-                println!("  Last sync:      {}", state.last_sync
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|| "Never".to_string()));
-                println!("  Conversations:  {}", state.conversation_count);
-                println!("  Messages:       {}", state.message_count);
-
-                if let Some(err) = &state.last_error {
-                    println!("  Last error:     {}", err.red());
-                }
-            }
-        }
-        SyncCommands::Now => {
-            println!("{}", "🔄 Running sync...".bold());
-
-            let sync_service = SyncService::new(config)?;
-            let state = sync_service.sync()?;
-
-            println!();
-            println!("  {} Sync completed!", "✓".green());
-            println!("  Conversations: {}", state.conversation_count);
-            println!("  Messages:      {}", state.message_count);
-        }
-        SyncCommands::Logs { lines } => {
-            let logs = systemd.view_logs(lines)?;
-            println!("{}", logs);
-        }
-        SyncCommands::Uninstall => {
-            println!("{}", "🗑️  Uninstalling sync service...".bold());
-            systemd.uninstall()?;
-            println!("  {} Service uninstalled", "✓".green());
-        }
-        SyncCommands::Restore { ids, force } => {
-            use application::RestoreService;
-
-            println!("{}", "🔄 Restoring chats to Cursor...".bold());
-
-            let restore_service = RestoreService::new(config);
-
-            // Check if restore is needed
-            if !force {
-                if !restore_service.needs_restore()? {
-                    println!("  {} Cursor database looks fine, no restore needed", "ℹ️".blue());
-                    println!("  Use --force to restore anyway");
-                    return Ok(());
-                }
-            }
-
-            // Perform restore
-            let result = if ids.is_empty() {
-                restore_service.restore_all()?
-            } else {
-                restore_service.restore_by_ids(&ids)?
-            };
-
-            println!();
-            println!("  {} Restore completed!", "✓".green());
-            println!("  Conversations: {}", result.restored_conversations);
-            println!("  Messages:      {}", result.restored_messages);
-            println!();
-            println!("  Database: {}", result.cursor_db_path.display());
-            println!();
-            println!("  {} Restart Cursor to see restored chats", "💡".bold());
-        }
+/// Apply process-wide settings: profile, memory budget, Cursor directory
+/// and channel.
+fn apply_global_options(cli: &Cli) -> domain::Result<()> {
+    if let Some(mb) = cli.max_memory {
+        set_max_memory_mb(mb);
     }
-
-    Ok(())
-}
-
-/// Handle storage subcommands.
-fn cmd_storage(cmd: StorageCommands) -> domain::Result<()> {
-    let config = AppConfig::default();
-    let storage_mgr = StorageManager::new(config.clone());
-
-    match cmd {
-        StorageCommands::Stats => {
-            storage_mgr.ensure_directories()?;
-            let summary = storage_mgr.get_summary()?;
-
-            println!("{}", "💾 Storage Statistics".bold());
-            println!();
-            println!("  Total used:     {} / {} ({:.1}%)",
-                summary.total_human(),
-                summary.max_human(),
-                summary.usage_percent);
-            println!();
-            println!("  Database:       {}", summary.db_human());
-            println!("  Exports:        {}", summary.exports_human());
-            println!("  Backups:        {} ({} files)", summary.backups_human(), summary.backup_count);
-            println!();
-            println!("  Data directory: {}", config.data_dir().display());
-        }
-        StorageCommands::Cleanup => {
-            println!("{}", "🧹 Running cleanup...".bold());
-
-            let result = storage_mgr.enforce_storage_limit()?;
-
-            if result.deleted_count > 0 {
-                println!("  {} Deleted {} files, freed {}",
-                    "✓".green(),
-                    result.deleted_count,
-                    result.freed_human());
-            } else {
-                println!("  {} Nothing to clean up", "✓".green());
-            }
-        }
-        StorageCommands::Workspaces => {
-            let sync_service = SyncService::new(config)?;
-            let workspaces = sync_service.get_workspaces()?;
-
-            println!("{}", "📁 Workspaces".bold());
-            println!();
-
-            if workspaces.is_empty() {
-                println!("  No workspaces found. Run 'cursor-chat sync now' first.");
-            } else {
-                for ws in &workspaces {
-                    let path = ws.path.as_ref()
-                        .map(|p| p.display().to_string())
-                        .unwrap_or_else(|| "(unknown)".to_string());
-                    println!("  {} → {}", ws.name.cyan(), path);
-                }
-            }
-        }
-        StorageCommands::Config => {
-            println!("{}", "⚙️  Storage Configuration".bold());
-            println!();
-            println!("  Max storage:       {} GB", config.storage.max_size_gb);
-            println!("  Backup retention:  {} days", config.storage.backup_retention_days);
-            println!("  Sync interval:     {} seconds", config.sync.interval_secs);
-            println!("  Sync enabled:      {}", config.sync.enabled);
-            println!();
-            println!("  Data directory:    {}", config.data_dir().display());
-            println!("  Config file:       {}", config.config_file_path().display());
-        }
+    if let Some(path) = &cli.path_map {
+        set_path_map_file(path.clone());
     }
-
-    Ok(())
-}
-
-/// Restore chat history to Cursor.
-fn cmd_restore(ids: &[String], force: bool) -> domain::Result<()> {
-    let config = AppConfig::default();
-    let restore_service = RestoreService::new(config);
-
-    println!("{}", "🔄 Checking restore status...".bold());
-
-    // Check if restore is needed
-    let cursor_empty = restore_service.cursor_is_empty()?;
-    let needs_restore = restore_service.needs_restore()?;
-
-    if !cursor_empty && !needs_restore && !force {
-        println!();
-        println!("  {} Cursor has chats - restore not needed", "ℹ".blue());
-        println!();
-        println!("  Use --force to restore anyway");
-        return Ok(());
+    if cli.read_only {
+        enable_read_only();
     }
-
-    if cursor_empty {
-        println!("  {} Cursor database is empty", "!".yellow());
-    } else if needs_restore {
-        println!("  {} Cursor appears to have been reset", "!".yellow());
+    if cli.filter.is_some()
+        && !matches!(
+            cli.command,
+            Commands::List { .. } | Commands::Show { .. } | Commands::Export { .. }
+        )
+    {
+        return Err(domain::AppError::Config {
+            message: "--filter only applies to list, show and export".into(),
+        });
     }
 
-    println!();
-    println!("{}", "📥 Restoring chats from backup...".bold());
-
-    let result = if ids.is_empty() {
-        restore_service.restore_all()?
-    } else {
-        restore_service.restore_by_ids(ids)?
-    };
-
-    println!();
-    println!("  {} Restore completed!", "✓".green());
-    println!("  Conversations: {}", result.restored_conversations);
-    println!("  Messages:      {}", result.restored_messages);
-    println!();
-    println!("  Cursor DB: {}", result.cursor_db_path.display());
-    println!();
-    println!("{}", "💡 Reinicie o Cursor para ver os chats restaurados".cyan());
+    // Config commands must work even when the file or profile is broken
+    if !matches!(cli.command, Commands::Config(_) | Commands::Doctor) {
+        let profile = cli.profile.as_deref().or_else(|| {
+            cli.channel
+                .filter(|c| *c != CursorChannel::Stable)
+                .map(CursorChannel::as_str)
+        });
+        if let Some(profile) = profile {
+            select_profile(profile);
+        }
+        let config = load_config()?;
+        if config.safety.read_only {
+            enable_read_only();
+        }
+        if let Some(dir) = config.paths.cursor_dir {
+            set_cursor_config_dir(dir)?;
+        }
+        set_cursor_channel(cli.channel.unwrap_or(config.paths.channel));
+    } else if let Some(channel) = cli.channel {
+        set_cursor_channel(channel);
+    }
 
     Ok(())
 }
 
-/// Run as daemon (background sync service).
-fn cmd_daemon(interval_secs: u64) -> domain::Result<()> {
-    let config = AppConfig::default();
-
-    println!("{}", "🔄 Starting sync daemon...".bold());
-    println!("  Interval: {} seconds", interval_secs);
-    println!("  Data dir: {}", config.data_dir().display());
-    println!("  Auto-restore: enabled");
-    println!();
-
-    // Ensure directories exist
-    let storage_mgr = StorageManager::new(config.clone());
-    storage_mgr.ensure_directories()?;
-
-    let sync_service = SyncService::new(config.clone())?;
-    let restore_service = RestoreService::new(config);
-
-    loop {
-        tracing::info!("Starting sync cycle...");
-
-        // First, check if restore is needed (Cursor was cleared)
-        match restore_service.auto_restore_if_needed() {
-            Ok(true) => {
-                tracing::info!("Auto-restore completed");
-            }
-            Ok(false) => {}
-            Err(e) => {
-                tracing::warn!(error = %e, "Auto-restore check failed");
-            }
-        }
-
-        // Then sync from Cursor to local storage
-        match sync_service.sync() {
-            Ok(state) => {
-                tracing::info!(
-                    conversations = state.conversation_count,
-                    messages = state.message_count,
-                    "Sync completed successfully"
-                );
-            }
-            Err(e) => {
-                tracing::error!(error = %e, "Sync failed");
-            }
-        }
-
-        // Check and enforce storage limits
-        if let Err(e) = storage_mgr.enforce_storage_limit() {
-            tracing::warn!(error = %e, "Failed to enforce storage limits");
+impl From<DateRangeArgs> for DateRange {
+    fn from(args: DateRangeArgs) -> Self {
+        Self {
+            since: args.since,
+            until: args.until,
+            trim_messages: args.trim_messages,
         }
-
-        // Sleep until next sync
-        std::thread::sleep(Duration::from_secs(interval_secs));
     }
 }
 
-/// Complete Cursor reset with backup and restore.
-fn cmd_reset(no_restore: bool, clean_appimage: bool) -> domain::Result<()> {
-    let config = AppConfig::default();
-
-    println!("{}", "🔄 CURSOR RESET".bold());
-    println!("{}", "═══════════════════════════════════════════".cyan());
-    println!();
-
-    // Step 1: Sync to ensure backup is up to date
-    println!("{}", "📦 Step 1: Backing up chats...".bold());
-    let sync_service = SyncService::new(config.clone())?;
-    match sync_service.sync() {
-        Ok(state) => {
-            println!("  {} Backup complete: {} chats, {} messages",
-                "✓".green(),
-                state.conversation_count,
-                state.message_count
-            );
-        }
-        Err(e) => {
-            println!("  {} Backup warning: {} (continuing anyway)", "⚠".yellow(), e);
-        }
-    }
-    println!();
-
-    // Step 2: Kill Cursor processes
-    println!("{}", "🔪 Step 2: Stopping Cursor...".bold());
-    let reset = CursorReset::new(clean_appimage);
-    match reset.kill_cursor() {
-        Ok(_) => println!("  {} Cursor processes terminated", "✓".green()),
-        Err(e) => println!("  {} Could not kill Cursor: {}", "⚠".yellow(), e),
-    }
-    println!();
-
-    // Step 3: Clean config directories
-    println!("{}", "🗑️  Step 3: Cleaning configuration...".bold());
-    match reset.clean_config_dirs() {
-        Ok(stats) => {
-            println!("  {} Removed {} directories", "✓".green(), stats.dirs_removed);
-            for path in &stats.paths_cleaned {
-                println!("    {} {}", "→".dimmed(), path);
-            }
-        }
-        Err(e) => println!("  {} Failed: {}", "✗".red(), e),
-    }
-    println!();
-
-    // Step 4: Clean desktop entries
-    println!("{}", "🖥️  Step 4: Cleaning desktop entries...".bold());
-    match reset.clean_desktop_entries() {
-        Ok(stats) => {
-            println!("  {} Removed {} files", "✓".green(), stats.files_removed);
-        }
-        Err(e) => println!("  {} Failed: {}", "✗".red(), e),
-    }
-    println!();
-
-    // Step 5: Clean AppImages (optional)
-    if clean_appimage {
-        println!("{}", "📦 Step 5: Cleaning AppImages...".bold());
-        match reset.clean_appimages() {
-            Ok(stats) => {
-                if stats.files_removed > 0 {
-                    println!("  {} Removed {} AppImage files", "✓".green(), stats.files_removed);
-                } else {
-                    println!("  {} No AppImages found", "ℹ".blue());
-                }
-            }
-            Err(e) => println!("  {} Failed: {}", "✗".red(), e),
-        }
-        println!();
-    }
-
-    // Step 6: Reset machine ID
-    println!("{}", "🔑 Step 6: Resetting machine ID...".bold());
-    println!("  {} This requires sudo password", "ℹ".blue());
-    match reset.reset_machine_id() {
-        Ok(result) => {
-            println!("  {} New machine ID: {}", "✓".green(), &result.new_id[..8]);
-        }
-        Err(e) => {
-            println!("  {} Failed: {}", "✗".red(), e);
-            println!("  {} You may need to run with sudo", "💡".yellow());
-        }
-    }
-    println!();
-
-    // Step 7: Restore chats
-    if !no_restore {
-        println!("{}", "📥 Step 7: Restoring chats...".bold());
-        let restore_service = RestoreService::new(config);
-        match restore_service.restore_all() {
-            Ok(result) => {
-                println!("  {} Restored {} chats, {} messages",
-                    "✓".green(),
-                    result.restored_conversations,
-                    result.restored_messages
-                );
-            }
-            Err(e) => {
-                println!("  {} Restore failed: {}", "⚠".yellow(), e);
-                println!("  {} Run 'cursor-chat sync restore' later", "💡".blue());
-            }
-        }
-        println!();
-    }
-
-    // Done
-    println!("{}", "═══════════════════════════════════════════".cyan());
-    println!("{}", "✨ RESET COMPLETE!".green().bold());
-    println!();
-    println!("  {} Reopen Cursor to start fresh", "→".cyan());
-    println!("  {} Your chats have been preserved", "→".cyan());
-    println!();
-
-    Ok(())
-}
-
 /// Setup tracing/logging based on verbosity level.
-fn setup_logging(verbosity: u8) {
+///
+/// With `timings`, stage spans are also collected for [`print_timings`].
+fn setup_logging(verbosity: u8, timings: bool) {
     let filter = match verbosity {
         0 => "warn",
         1 => "info",
@@ -845,8 +528,62 @@ fn setup_logging(verbosity: u8) {
 
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(filter));
 
+    let timing_layer = timings.then(|| {
+        TimingLayer.with_filter(filter_fn(|metadata| metadata.target() == TIMINGS_TARGET))
+    });
+
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(false).without_time())
-        .with(filter)
+        .with(
+            fmt::layer()
+                .with_target(false)
+                .without_time()
+                .with_filter(filter),
+        )
+        .with(timing_layer)
         .init();
 }
+
+/// Print the per-stage timings collected with `--timings` to stderr.
+fn print_timings() {
+    let stages = stage_timings();
+    eprintln!();
+    if stages.is_empty() {
+        eprintln!("{}", "No timed stages ran.".dimmed());
+    } else {
+        eprintln!("{}", format_timings(&stages));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_date_range_from_args() {
+        let cli = Cli::parse_from([
+            "cursor-chat",
+            "list",
+            "--since",
+            "2024-05-01T12:00:00Z",
+            "--trim-messages",
+        ]);
+        let Commands::List { range, .. } = cli.command else {
+            panic!("expected the list command");
+        };
+
+        let range = DateRange::from(range);
+        assert_eq!(
+            range.since.map(|since| since.to_rfc3339()).as_deref(),
+            Some("2024-05-01T12:00:00+00:00")
+        );
+        assert_eq!(range.until, None);
+        assert!(range.trim_messages);
+    }
+}
@@ -0,0 +1,54 @@
+//! The C API end to end. `cch_set_cursor_dir` sets the Cursor directory for
+//! the whole process, so this runs in its own test binary.
+
+#![cfg(feature = "capi")]
+#![allow(unsafe_code)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use cursor_chat_handler::application::generate_synthetic_db;
+use cursor_chat_handler::capi::{
+    cch_free_string, cch_get_conversation, cch_last_error, cch_list_conversations,
+    cch_set_cursor_dir,
+};
+
+fn take(s: *mut c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    let text = unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned();
+    unsafe { cch_free_string(s) };
+    Some(text)
+}
+
+#[test]
+fn test_c_api_lists_and_gets_conversations() {
+    let dir = tempfile::tempdir().unwrap();
+    let global = dir.path().join("User/globalStorage");
+    std::fs::create_dir_all(&global).unwrap();
+    generate_synthetic_db(&global.join("state.vscdb"), 2, 3).unwrap();
+
+    let path = CString::new(dir.path().to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { cch_set_cursor_dir(path.as_ptr()) }, 0);
+
+    let list: serde_json::Value =
+        serde_json::from_str(&take(cch_list_conversations()).unwrap()).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 2);
+    assert_eq!(list[0]["messages"], 3);
+
+    let id = CString::new("00000001").unwrap();
+    let conv = take(unsafe { cch_get_conversation(id.as_ptr()) }).unwrap();
+    assert!(conv.contains("\"bubbles\""));
+
+    assert!(take(unsafe { cch_get_conversation(ptr::null()) }).is_none());
+    let error = unsafe { CStr::from_ptr(cch_last_error()) };
+    assert!(error.to_str().unwrap().contains("NULL"));
+
+    let other = CString::new("/elsewhere").unwrap();
+    assert_eq!(unsafe { cch_set_cursor_dir(other.as_ptr()) }, -1);
+    let error = unsafe { CStr::from_ptr(cch_last_error()) };
+    assert!(error.to_str().unwrap().contains("already set"));
+    let list = take(cch_list_conversations()).unwrap();
+    assert!(list.starts_with('['));
+}